@@ -0,0 +1,56 @@
+//! Benchmarks do router da API via `tower::ServiceExt::oneshot`
+//!
+//! Medem o custo de roteamento, extractors e serialização sem abrir sockets,
+//! com os usuários no repositório em memória.
+
+use axum::{body::Body, http::Request, Router};
+use criterion::{criterion_group, criterion_main, Criterion};
+use rust_app_exemplo::api::{create_router, AppState};
+use rust_app_exemplo::fixtures::Fixtures;
+use rust_app_exemplo::repository::InMemoryUserRepository;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tower::ServiceExt;
+
+fn app() -> Router {
+    let repo = InMemoryUserRepository::with_users(Fixtures::seeded(42).users(100));
+    create_router(AppState::new(Arc::new(repo), Default::default()))
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::get(uri).body(Body::empty()).unwrap()
+}
+
+fn router_benchmark(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let app = app();
+
+    for uri in ["/health", "/version", "/not-found"] {
+        c.bench_function(&format!("GET {}", uri), |b| {
+            b.to_async(&runtime).iter(|| app.clone().oneshot(get(uri)))
+        });
+    }
+
+    c.bench_function("GET /api/users (100 users)", |b| {
+        b.to_async(&runtime)
+            .iter(|| app.clone().oneshot(get("/api/users")))
+    });
+
+    c.bench_function("GET /api/users/1", |b| {
+        b.to_async(&runtime)
+            .iter(|| app.clone().oneshot(get("/api/users/1")))
+    });
+
+    c.bench_function("POST /api/users (validation error)", |b| {
+        b.to_async(&runtime).iter(|| {
+            let request = Request::post("/api/users")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"name": "", "email": "not-an-email"}"#))
+                .unwrap();
+            app.clone().oneshot(request)
+        })
+    });
+}
+
+criterion_group!(benches, router_benchmark);
+criterion_main!(benches);
@@ -1,4 +1,4 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 use rust_app_exemplo::*;
 
 fn fibonacci_benchmark(c: &mut Criterion) {
@@ -34,6 +34,18 @@ fn string_utils_benchmark(c: &mut Criterion) {
         })
     });
 
+    // Varredura por bytes contra a referência caractere a caractere
+    let text = "Ação rápida: the quick brown fox jumps over the lazy dog. ".repeat(1000);
+    let mut group = c.benchmark_group("count_vowels_64k");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.bench_function("chars", |b| {
+        b.iter(|| string_utils::count_vowels_chars(black_box(&text)))
+    });
+    group.bench_function("bytes", |b| {
+        b.iter(|| string_utils::count_vowels(black_box(&text)))
+    });
+    group.finish();
+
     c.bench_function("reverse_string", |b| {
         b.iter(|| string_utils::reverse(black_box("abcdefghijklmnopqrstuvwxyz")))
     });
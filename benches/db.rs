@@ -0,0 +1,71 @@
+//! Benchmarks do CRUD de `DbUser` contra um Postgres em container
+//!
+//! Requer Docker; sem ele os benchmarks são pulados com um aviso.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rust_app_exemplo::db::DbUser;
+use rust_app_exemplo::fixtures::Fixtures;
+use rust_app_exemplo::tenant::TenantContext;
+use rust_app_exemplo::test_support::TestDatabase;
+use std::sync::Mutex;
+use tokio::runtime::Runtime;
+
+fn crud_benchmark(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+
+    let test_db = match runtime.block_on(TestDatabase::start()) {
+        Ok(test_db) => test_db,
+        Err(e) => {
+            eprintln!("⚠️  Pulando benchmarks de banco: {:#}", e);
+            return;
+        }
+    };
+    let pool = test_db.db().pool();
+    let existing = test_db.seeded_users[0].clone();
+    let tenant = &TenantContext::default_tenant();
+    let fixtures = Mutex::new(Fixtures::seeded(42));
+
+    c.bench_function("db create_user", |b| {
+        b.to_async(&runtime).iter(|| {
+            let user = fixtures.lock().unwrap().user().build();
+            async move {
+                DbUser::create(pool, tenant, &user.name, &user.email)
+                    .await
+                    .unwrap()
+            }
+        })
+    });
+
+    c.bench_function("db find_by_id", |b| {
+        b.to_async(&runtime)
+            .iter(|| DbUser::find_by_id(pool, tenant, existing.id))
+    });
+
+    c.bench_function("db find_by_email", |b| {
+        b.to_async(&runtime)
+            .iter(|| DbUser::find_by_email(pool, tenant, &existing.email))
+    });
+
+    c.bench_function("db update", |b| {
+        b.to_async(&runtime).iter(|| existing.update(pool, tenant))
+    });
+
+    c.bench_function("db count", |b| {
+        b.to_async(&runtime).iter(|| DbUser::count(pool, tenant))
+    });
+
+    c.bench_function("db create + delete", |b| {
+        b.to_async(&runtime).iter(|| {
+            let user = fixtures.lock().unwrap().user().build();
+            async move {
+                let user = DbUser::create(pool, tenant, &user.name, &user.email)
+                    .await
+                    .unwrap();
+                DbUser::delete(pool, tenant, user.id).await.unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(benches, crud_benchmark);
+criterion_main!(benches);
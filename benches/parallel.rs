@@ -0,0 +1,62 @@
+//! Versões sequenciais contra as paralelas (feature "parallel")
+//!
+//! ```bash
+//! cargo bench --features parallel --bench parallel
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rust_app_exemplo::parallel::{factorial_batch, fibonacci_batch, primes_parallel};
+use rust_app_exemplo::{factorial, fibonacci_optimized, is_prime};
+
+fn primes_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("primes");
+    for end in [10_000u64, 1_000_000] {
+        group.bench_with_input(BenchmarkId::new("sequential", end), &end, |b, &end| {
+            b.iter(|| {
+                (0..black_box(end))
+                    .filter(|&n| is_prime(n))
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", end), &end, |b, &end| {
+            b.iter(|| primes_parallel(0..black_box(end)))
+        });
+    }
+    group.finish();
+}
+
+fn batch_benchmark(c: &mut Criterion) {
+    let values: Vec<u64> = (0..100_000).map(|i| i % 90).collect();
+
+    let mut group = c.benchmark_group("fibonacci_batch");
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            black_box(&values)
+                .iter()
+                .map(|&n| fibonacci_optimized(n))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| fibonacci_batch(black_box(&values)))
+    });
+    group.finish();
+
+    let values: Vec<u64> = (0..100_000).map(|i| i % 21).collect();
+    let mut group = c.benchmark_group("factorial_batch");
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            black_box(&values)
+                .iter()
+                .map(|&n| factorial(n))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| factorial_batch(black_box(&values)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, primes_benchmark, batch_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,95 @@
+//! Embute informações de build no binário
+//!
+//! Variáveis expostas via `env!`: `BUILD_GIT_SHA`, `BUILD_GIT_BRANCH`,
+//! `BUILD_TIMESTAMP` e `BUILD_RUSTC_VERSION`. Fora de um repositório git
+//! (ex.: build no Docker ou no Nix), `GIT_SHA`/`GIT_BRANCH` podem ser
+//! passados pelo ambiente; `SOURCE_DATE_EPOCH` fixa a data de build.
+//!
+//! Com a feature `protobuf`, também gera as mensagens de `proto/` no
+//! `OUT_DIR` (incluídas por `src/api/proto.rs`).
+
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let git_sha = env_or("GIT_SHA", || git(&["rev-parse", "HEAD"]));
+    let git_branch = env_or("GIT_BRANCH", || git(&["rev-parse", "--abbrev-ref", "HEAD"]));
+
+    let build_time = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+        .unwrap_or_else(SystemTime::now);
+
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]);
+
+    println!("cargo:rustc-env=BUILD_GIT_SHA={}", git_sha);
+    println!("cargo:rustc-env=BUILD_GIT_BRANCH={}", git_branch);
+    println!(
+        "cargo:rustc-env=BUILD_TIMESTAMP={}",
+        humantime::format_rfc3339_seconds(build_time)
+    );
+    println!("cargo:rustc-env=BUILD_RUSTC_VERSION={}", rustc_version);
+
+    for var in ["GIT_SHA", "GIT_BRANCH", "SOURCE_DATE_EPOCH"] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    println!("cargo:rerun-if-changed=build.rs");
+    // `sqlx::migrate!` embute as migrations: recompilar quando mudarem
+    println!("cargo:rerun-if-changed=migrations");
+
+    #[cfg(feature = "protobuf")]
+    compile_protos();
+}
+
+/// Gera o código das mensagens de `proto/users.proto`
+///
+/// O `.proto` é lido pelo parser em Rust puro do `protobuf-parse` (sem
+/// depender do `protoc` instalado) e os descritores resultantes passam
+/// pelo `prost-build`.
+#[cfg(feature = "protobuf")]
+fn compile_protos() {
+    use prost::Message as _;
+
+    let descriptors = protobuf_parse::Parser::new()
+        .pure()
+        .include("proto")
+        .input("proto/users.proto")
+        .file_descriptor_set()
+        .expect("failed to parse proto/users.proto");
+    let bytes = protobuf::Message::write_to_bytes(&descriptors)
+        .expect("failed to encode the proto descriptors");
+    let descriptors = prost_types::FileDescriptorSet::decode(bytes.as_slice())
+        .expect("failed to decode the proto descriptors");
+
+    prost_build::Config::new()
+        .compile_fds(descriptors)
+        .expect("failed to generate the protobuf messages");
+    println!("cargo:rerun-if-changed=proto");
+}
+
+fn env_or(var: &str, fallback: impl FnOnce() -> String) -> String {
+    std::env::var(var)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(fallback)
+}
+
+fn git(args: &[&str]) -> String {
+    command_output("git", args)
+}
+
+fn command_output(program: &str, args: &[&str]) -> String {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|stdout| stdout.trim().to_string())
+        .filter(|stdout| !stdout.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
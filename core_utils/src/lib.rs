@@ -0,0 +1,290 @@
+//! Algoritmos matemáticos e de strings sem dependência da `std`
+//!
+//! Compila com `#![no_std]` usando apenas `alloc`, para reaproveitar os
+//! algoritmos em alvos embarcados. A biblioteca principal reexporta tudo
+//! (`rust_app_exemplo::core_utils`, `fibonacci_optimized`, `string_utils`...).
+//!
+//! ```bash
+//! cargo build -p core-utils --target thumbv7em-none-eabihf
+//! ```
+
+#![no_std]
+
+extern crate alloc;
+
+/// Calcula fibonacci de forma otimizada usando iteração
+pub fn fibonacci_optimized(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    if n == 1 {
+        return 1;
+    }
+
+    let mut prev = 0;
+    let mut curr = 1;
+
+    for _ in 2..=n {
+        let next = prev + curr;
+        prev = curr;
+        curr = next;
+    }
+
+    curr
+}
+
+/// Calcula o fatorial de um número
+pub fn factorial(n: u64) -> u64 {
+    match n {
+        0 | 1 => 1,
+        _ => (2..=n).product(),
+    }
+}
+
+/// Verifica se um número é primo
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    if n == 2 {
+        return true;
+    }
+    if n.is_multiple_of(2) {
+        return false;
+    }
+
+    // `i <= n / i` equivale a `i * i <= n` sem estourar (e sem `f64::sqrt`,
+    // que não existe em `core`)
+    let mut i = 3;
+    while i <= n / i {
+        if n.is_multiple_of(i) {
+            return false;
+        }
+        i += 2;
+    }
+
+    true
+}
+
+/// Módulo de processamento de strings
+pub mod string_utils {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    mod diff;
+    mod fuzzy;
+
+    pub use diff::{diff, diff_words, is_unchanged, render, Change};
+    pub use fuzzy::{fuzzy_match, fuzzy_score};
+
+    /// Mapa devolvido por `word_frequencies` (o `HashMap` do `hashbrown`,
+    /// já que `std::collections` não existe sem `std`)
+    pub use hashbrown::HashMap;
+
+    /// Converte uma string para título (primeira letra de cada palavra em maiúscula)
+    pub fn to_title_case(s: &str) -> String {
+        s.split_whitespace()
+            .map(|word| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    None => String::new(),
+                    Some(first) => {
+                        first.to_uppercase().collect::<String>()
+                            + chars.as_str().to_lowercase().as_str()
+                    }
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// Conta o número de vogais (ASCII, sem acento) em uma string
+    ///
+    /// Varre os bytes com `count_vowels_bytes`: em UTF-8, todo byte de um
+    /// caractere não ASCII é >= 0x80, então só os caracteres ASCII podem
+    /// casar e o resultado é o mesmo de `count_vowels_chars`.
+    pub fn count_vowels(s: &str) -> usize {
+        count_vowels_bytes(s.as_bytes())
+    }
+
+    /// Conta as vogais ASCII (`aeiouAEIOU`) em bytes quaisquer
+    ///
+    /// Sem desvios por byte e com contadores de 8 bits por bloco, o que o
+    /// compilador vetoriza (SIMD); ver o benchmark `count_vowels_64k`.
+    pub fn count_vowels_bytes(bytes: &[u8]) -> usize {
+        // Até 255 bytes por bloco, para o contador de 8 bits não estourar
+        bytes
+            .chunks(255)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|&b| u8::from(is_ascii_vowel(b)))
+                    .fold(0u8, u8::wrapping_add) as usize
+            })
+            .sum()
+    }
+
+    /// Implementação de referência, caractere a caractere
+    pub fn count_vowels_chars(s: &str) -> usize {
+        s.chars()
+            .filter(|c| matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u'))
+            .count()
+    }
+
+    /// `b` é uma vogal ASCII, maiúscula ou minúscula
+    #[inline]
+    pub fn is_ascii_vowel(b: u8) -> bool {
+        // `| 0x20` leva só `A-Z` (e as próprias minúsculas) às minúsculas
+        // entre as letras testadas
+        let lower = b | 0x20;
+        (lower == b'a') | (lower == b'e') | (lower == b'i') | (lower == b'o') | (lower == b'u')
+    }
+
+    /// Inverte uma string
+    pub fn reverse(s: &str) -> String {
+        s.chars().rev().collect()
+    }
+
+    /// Palavras da string em minúsculas, segmentadas pelas regras Unicode
+    /// (UAX #29): pontuação e espaços ficam de fora, e "d'água" ou "3.14"
+    /// são uma palavra só
+    pub fn words(s: &str) -> Vec<String> {
+        s.unicode_words().map(str::to_lowercase).collect()
+    }
+
+    /// Quantas vezes cada palavra (em minúsculas) aparece
+    pub fn word_frequencies(s: &str) -> HashMap<String, usize> {
+        count(words(s))
+    }
+
+    /// As `k` palavras mais frequentes, da mais para a menos frequente
+    ///
+    /// Empates saem em ordem alfabética, para o resultado ser estável.
+    pub fn top_words(s: &str, k: usize) -> Vec<(String, usize)> {
+        top(word_frequencies(s), k)
+    }
+
+    /// Sequências de `n` palavras consecutivas, unidas por um espaço
+    ///
+    /// Vazio se `n` for 0 ou maior que o número de palavras.
+    pub fn ngrams(s: &str, n: usize) -> Vec<String> {
+        if n == 0 {
+            return Vec::new();
+        }
+        words(s).windows(n).map(|window| window.join(" ")).collect()
+    }
+
+    /// Os `k` n-gramas de `n` palavras mais frequentes, como em `top_words`
+    pub fn top_ngrams(s: &str, n: usize, k: usize) -> Vec<(String, usize)> {
+        top(count(ngrams(s, n)), k)
+    }
+
+    fn count(items: Vec<String>) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for item in items {
+            *counts.entry(item).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    fn top(counts: HashMap<String, usize>, k: usize) -> Vec<(String, usize)> {
+        let mut entries: Vec<(String, usize)> = counts.into_iter().collect();
+        entries.sort_unstable_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then_with(|| a.cmp(b))
+        });
+        entries.truncate(k);
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_prime_large_inputs() {
+        assert!(is_prime(1_000_000_007));
+        assert!(is_prime(4_294_967_291)); // maior primo em u32
+        assert!(!is_prime(u64::MAX));
+        assert!(!is_prime(4_294_967_297)); // 641 * 6700417
+    }
+
+    #[test]
+    fn test_string_utils_unicode() {
+        assert_eq!(
+            string_utils::to_title_case("ÉRICA   da silva"),
+            "Érica Da Silva"
+        );
+        assert_eq!(string_utils::reverse("olá"), "álo");
+        assert_eq!(string_utils::count_vowels("AEIOU xyz"), 5);
+    }
+
+    #[test]
+    fn test_word_frequencies_and_ngrams() {
+        use alloc::string::ToString;
+        use alloc::vec;
+
+        let text = "O rato roeu a roupa do rei. O REI riu; o rato, não!";
+        let frequencies = string_utils::word_frequencies(text);
+        assert_eq!(frequencies["o"], 3);
+        assert_eq!(frequencies["rei"], 2);
+        assert_eq!(frequencies["não"], 1);
+        assert!(!frequencies.contains_key(";"));
+
+        assert_eq!(
+            string_utils::top_words(text, 3),
+            vec![
+                ("o".to_string(), 3),
+                ("rato".to_string(), 2),
+                ("rei".to_string(), 2)
+            ]
+        );
+        assert!(string_utils::top_words("", 5).is_empty());
+
+        assert_eq!(
+            string_utils::ngrams("Água d'água, 3.14 vezes", 2),
+            ["água d'água", "d'água 3.14", "3.14 vezes"]
+        );
+        assert!(string_utils::ngrams("uma palavra", 0).is_empty());
+        assert!(string_utils::ngrams("uma palavra", 3).is_empty());
+        assert_eq!(
+            string_utils::top_ngrams(text, 2, 1),
+            vec![("o rato".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_count_vowels_bytes() {
+        use alloc::string::String;
+
+        // Mais de um bloco de 255 bytes, inclusive só de vogais
+        let long: String = "aEiOu".repeat(200);
+        assert_eq!(string_utils::count_vowels(&long), 1000);
+        assert_eq!(string_utils::count_vowels_chars(&long), 1000);
+
+        for s in [
+            "",
+            "xyz",
+            "ação über ÀÉÍ",
+            "🦀 rust é incrível",
+            "AaEeIiOoUu\u{e1}",
+        ] {
+            assert_eq!(
+                string_utils::count_vowels(s),
+                string_utils::count_vowels_chars(s),
+                "{}",
+                s
+            );
+        }
+
+        // Bytes fora do ASCII nunca são vogais, nem os que viram letras com `| 0x20`
+        for b in 0..=u8::MAX {
+            let expected = matches!(
+                b,
+                b'a' | b'e' | b'i' | b'o' | b'u' | b'A' | b'E' | b'I' | b'O' | b'U'
+            );
+            assert_eq!(string_utils::is_ascii_vowel(b), expected, "{:#x}", b);
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Diff de textos por linha e por palavra (algoritmo de Myers)
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const REVERSE: &str = "\x1b[7m";
+const NO_REVERSE: &str = "\x1b[27m";
+const RESET: &str = "\x1b[0m";
+
+/// Um trecho do diff: igual nos dois textos, só no primeiro ou só no segundo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+impl<'a> Change<'a> {
+    /// Texto do trecho
+    pub fn text(&self) -> &'a str {
+        match *self {
+            Change::Equal(s) | Change::Delete(s) | Change::Insert(s) => s,
+        }
+    }
+}
+
+/// Diff linha a linha de `a` para `b` (sem os `\n`)
+///
+/// Cada linha de `a` aparece como `Equal` ou `Delete` e cada linha de `b`
+/// como `Equal` ou `Insert`, na ordem; os `Delete` de um trecho alterado vêm
+/// antes dos `Insert`.
+pub fn diff<'a>(a: &'a str, b: &'a str) -> Vec<Change<'a>> {
+    let a: Vec<&str> = a.lines().collect();
+    let b: Vec<&str> = b.lines().collect();
+    myers(&a, &b)
+}
+
+/// Diff palavra a palavra, com os espaços entre elas como trechos próprios
+///
+/// Concatenar os `Equal` e `Delete` reconstrói `a`; os `Equal` e `Insert`,
+/// `b`.
+pub fn diff_words<'a>(a: &'a str, b: &'a str) -> Vec<Change<'a>> {
+    myers(&tokens(a), &tokens(b))
+}
+
+/// `true` se o diff não tem nenhuma linha removida ou inserida
+pub fn is_unchanged(changes: &[Change<'_>]) -> bool {
+    changes
+        .iter()
+        .all(|change| matches!(change, Change::Equal(_)))
+}
+
+/// Formata um diff de linhas no estilo unificado (`  `, `- ` e `+ `)
+///
+/// Com `color`, as linhas removidas saem em vermelho e as inseridas em
+/// verde (ANSI). Quando um trecho troca N linhas por outras N, cada par é
+/// comparado com `diff_words` e as palavras alteradas saem em vídeo
+/// reverso.
+pub fn render(changes: &[Change<'_>], color: bool) -> String {
+    let mut out = String::new();
+    let mut i = 0;
+    while i < changes.len() {
+        if let Change::Equal(line) = changes[i] {
+            let _ = writeln!(out, "  {}", line);
+            i += 1;
+            continue;
+        }
+
+        // Trecho alterado: os `Delete` seguidos dos `Insert`
+        let deleted = run_len(&changes[i..], |c| matches!(c, Change::Delete(_)));
+        let inserted = run_len(&changes[i + deleted..], |c| matches!(c, Change::Insert(_)));
+        let (old, new) = changes[i..i + deleted + inserted].split_at(deleted);
+
+        if color && deleted == inserted {
+            for (old, new) in old.iter().zip(new) {
+                let words = diff_words(old.text(), new.text());
+                render_words(&mut out, '-', RED, &words, |c| {
+                    matches!(c, Change::Delete(_))
+                });
+                render_words(&mut out, '+', GREEN, &words, |c| {
+                    matches!(c, Change::Insert(_))
+                });
+            }
+        } else {
+            for change in old.iter().chain(new) {
+                let (sign, ansi) = match change {
+                    Change::Delete(_) => ('-', RED),
+                    _ => ('+', GREEN),
+                };
+                if color {
+                    let _ = writeln!(out, "{}{} {}{}", ansi, sign, change.text(), RESET);
+                } else {
+                    let _ = writeln!(out, "{} {}", sign, change.text());
+                }
+            }
+        }
+        i += deleted + inserted;
+    }
+    out
+}
+
+/// Uma linha de um par alterado: os trechos iguais e os do lado `side`,
+/// com estes em vídeo reverso
+fn render_words(
+    out: &mut String,
+    sign: char,
+    ansi: &str,
+    words: &[Change<'_>],
+    side: impl Fn(&Change<'_>) -> bool,
+) {
+    let _ = write!(out, "{}{} ", ansi, sign);
+    for word in words {
+        match word {
+            Change::Equal(text) => out.push_str(text),
+            change if side(change) => {
+                let _ = write!(out, "{}{}{}", REVERSE, change.text(), NO_REVERSE);
+            }
+            _ => {}
+        }
+    }
+    let _ = writeln!(out, "{}", RESET);
+}
+
+fn run_len(changes: &[Change<'_>], pred: impl Fn(&Change<'_>) -> bool) -> usize {
+    changes.iter().take_while(|c| pred(c)).count()
+}
+
+/// Palavras e sequências de espaços, na ordem
+fn tokens(s: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    for (i, c) in s.char_indices() {
+        let space = c.is_whitespace();
+        if in_space.is_some_and(|prev| prev != space) {
+            tokens.push(&s[start..i]);
+            start = i;
+        }
+        in_space = Some(space);
+    }
+    if start < s.len() {
+        tokens.push(&s[start..]);
+    }
+    tokens
+}
+
+/// Menor sequência de edições de `a` para `b` (Myers, O((N+M)·D))
+fn myers<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<Change<'a>> {
+    // Prefixo e sufixo comuns ficam fora da busca
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut changes: Vec<Change<'a>> = a[..prefix].iter().map(|s| Change::Equal(s)).collect();
+    changes.extend(middle(a_mid, b_mid));
+    changes.extend(a[a.len() - suffix..].iter().map(|s| Change::Equal(s)));
+    changes
+}
+
+fn middle<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<Change<'a>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let offset = max as usize;
+    // `v[k]`: maior `x` alcançado na diagonal `k = x - y`
+    let mut v = vec![0isize; 2 * offset + 2];
+    let mut trace = Vec::new();
+
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let idx = (k + max) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // Refaz o caminho de trás para frente, a partir do estado de cada `d`
+    let mut changes = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let idx = (k + max) as usize;
+        let prev_k = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[(prev_k + max) as usize];
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            changes.push(Change::Equal(a[x as usize]));
+        }
+        if d > 0 {
+            if x == prev_x {
+                changes.push(Change::Insert(b[prev_y as usize]));
+            } else {
+                changes.push(Change::Delete(a[prev_x as usize]));
+            }
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    changes.reverse();
+    group_edits(changes)
+}
+
+/// Reordena cada trecho de edições para os `Delete` virem antes dos `Insert`
+fn group_edits(changes: Vec<Change<'_>>) -> Vec<Change<'_>> {
+    let mut out = Vec::with_capacity(changes.len());
+    let mut inserts = Vec::new();
+    for change in changes {
+        match change {
+            Change::Insert(_) => inserts.push(change),
+            Change::Delete(_) => out.push(change),
+            Change::Equal(_) => {
+                out.append(&mut inserts);
+                out.push(change);
+            }
+        }
+    }
+    out.append(&mut inserts);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn rebuild<'a>(changes: &[Change<'a>], side: fn(&Change<'a>) -> bool) -> Vec<&'a str> {
+        changes
+            .iter()
+            .filter(|c| side(c))
+            .map(Change::text)
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let a = "um\ndois\ntrês\nquatro";
+        let b = "um\n2\ntrês\nquatro\ncinco";
+        assert_eq!(
+            diff(a, b),
+            [
+                Change::Equal("um"),
+                Change::Delete("dois"),
+                Change::Insert("2"),
+                Change::Equal("três"),
+                Change::Equal("quatro"),
+                Change::Insert("cinco"),
+            ]
+        );
+        assert!(is_unchanged(&diff(a, a)));
+        assert_eq!(diff("", "x"), [Change::Insert("x")]);
+        assert_eq!(diff("x", ""), [Change::Delete("x")]);
+    }
+
+    #[test]
+    fn test_diff_is_minimal_and_rebuilds_both_sides() {
+        let a: Vec<&str> = "a b c a b b a".split(' ').collect();
+        let b: Vec<&str> = "c b a b a c".split(' ').collect();
+        let changes = myers(&a, &b);
+
+        let edits = changes
+            .iter()
+            .filter(|c| !matches!(c, Change::Equal(_)))
+            .count();
+        assert_eq!(edits, 5); // D do exemplo do artigo de Myers
+        assert_eq!(rebuild(&changes, |c| !matches!(c, Change::Insert(_))), a);
+        assert_eq!(rebuild(&changes, |c| !matches!(c, Change::Delete(_))), b);
+    }
+
+    #[test]
+    fn test_diff_words_keeps_whitespace() {
+        let changes = diff_words("o rato  roeu", "o gato  roeu");
+        assert_eq!(
+            changes,
+            [
+                Change::Equal("o"),
+                Change::Equal(" "),
+                Change::Delete("rato"),
+                Change::Insert("gato"),
+                Change::Equal("  "),
+                Change::Equal("roeu"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let changes = diff("a\nb c\nd", "a\nb x\nd\ne");
+        assert_eq!(render(&changes, false), "  a\n- b c\n+ b x\n  d\n+ e\n");
+
+        let colored = render(&changes, true);
+        assert!(colored.contains("\x1b[31m- b \x1b[7mc\x1b[27m\x1b[0m"));
+        assert!(colored.contains("\x1b[32m+ b \x1b[7mx\x1b[27m\x1b[0m"));
+        assert!(colored.contains("\x1b[32m+ e\x1b[0m"));
+        assert_eq!(render(&[], true), "".to_string());
+    }
+}
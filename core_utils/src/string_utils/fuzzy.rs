@@ -0,0 +1,153 @@
+//! Busca aproximada por subsequência, no estilo do fzf
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Pontos de cada caractere do padrão encontrado
+const SCORE_MATCH: i32 = 16;
+/// Bônus para o caractere logo após o anterior do padrão
+const BONUS_CONSECUTIVE: i32 = 8;
+/// Bônus para o início do texto ou de uma palavra (após espaço, `-`, `_`...)
+const BONUS_BOUNDARY: i32 = 10;
+/// Bônus para uma maiúscula depois de minúscula (`camelCase`)
+const BONUS_CAMEL: i32 = 8;
+/// Penalidade por pular caracteres entre dois caracteres do padrão
+const PENALTY_GAP_START: i32 = 3;
+/// Penalidade por caractere pulado além do primeiro
+const PENALTY_GAP_EXTENSION: i32 = 1;
+
+/// Candidatos que contêm os caracteres de `pattern` na ordem (não
+/// necessariamente juntos), do mais para o menos relevante
+///
+/// A comparação ignora maiúsculas. Sequências contíguas e caracteres no
+/// início de palavras valem mais; caracteres pulados entre eles, menos.
+/// Empates ficam com o candidato mais curto e, depois, na ordem original.
+/// Com `pattern` vazio, todos os candidatos voltam com pontuação 0.
+pub fn fuzzy_match<'a, I>(pattern: &str, candidates: I) -> Vec<(i32, &'a str)>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut matches: Vec<(i32, &'a str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| Some((fuzzy_score(pattern, candidate)?, candidate)))
+        .collect();
+    // `sort_by` é estável: empates completos mantêm a ordem original
+    matches.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| a.chars().count().cmp(&b.chars().count()))
+    });
+    matches
+}
+
+/// Pontuação de `candidate` para `pattern`; `None` se não casar
+///
+/// Escolhe, entre todas as formas de casar o padrão, a de maior pontuação.
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i32> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    let text: Vec<char> = candidate.chars().collect();
+    if pattern.len() > text.len() {
+        return None;
+    }
+
+    let bonuses: Vec<i32> = (0..text.len())
+        .map(|j| bonus(j.checked_sub(1).map(|p| text[p]), text[j]))
+        .collect();
+
+    // `prev[j]`: melhor pontuação com o caractere anterior do padrão em `j`
+    let mut prev: Vec<Option<i32>> = vec![None; text.len()];
+    for (i, &p) in pattern.iter().enumerate() {
+        let mut row = vec![None; text.len()];
+        // Melhor `prev[k]` menos a penalidade do intervalo `k + 1..j`, com
+        // `k < j - 1`
+        let mut best_gap: Option<i32> = None;
+        for j in 0..text.len() {
+            if j >= 2 {
+                best_gap = max_opt(
+                    best_gap.map(|s| s - PENALTY_GAP_EXTENSION),
+                    prev[j - 2].map(|s| s - PENALTY_GAP_START),
+                );
+            }
+            if !eq_ignore_case(text[j], p) {
+                continue;
+            }
+            let score = SCORE_MATCH + bonuses[j];
+            row[j] = if i == 0 {
+                Some(score)
+            } else {
+                let consecutive = j
+                    .checked_sub(1)
+                    .and_then(|k| prev[k])
+                    .map(|s| s + BONUS_CONSECUTIVE);
+                max_opt(consecutive, best_gap).map(|s| s + score)
+            };
+        }
+        prev = row;
+    }
+
+    prev.into_iter().flatten().max()
+}
+
+fn bonus(prev: Option<char>, c: char) -> i32 {
+    match prev {
+        None => BONUS_BOUNDARY,
+        Some(p) if !p.is_alphanumeric() && c.is_alphanumeric() => BONUS_BOUNDARY,
+        Some(p) if p.is_lowercase() && c.is_uppercase() => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+fn eq_ignore_case(c: char, lower: char) -> bool {
+    c == lower || c.to_lowercase().eq(core::iter::once(lower))
+}
+
+fn max_opt(a: Option<i32>, b: Option<i32>) -> Option<i32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "qualquer"), Some(0));
+        assert_eq!(fuzzy_score("abc", "ab"), None);
+        assert_eq!(fuzzy_score("ba", "abc"), None);
+        assert!(fuzzy_score("HC", "healthcheck").is_some());
+
+        // Contíguo vale mais que espalhado; início de palavra, mais que meio
+        assert!(fuzzy_score("ser", "serve") > fuzzy_score("ser", "s-e-r"));
+        assert!(fuzzy_score("lu", "list-users") > fuzzy_score("lu", "fallout"));
+        assert!(fuzzy_score("cu", "createUser") > fuzzy_score("cu", "createuser"));
+        // A melhor forma de casar, não a primeira encontrada
+        assert_eq!(
+            fuzzy_score("ab", "a-xab"),
+            Some(SCORE_MATCH * 2 + BONUS_CONSECUTIVE)
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_match_ranks_candidates() {
+        let candidates = ["fibonacci", "db", "diff", "create-user", "healthcheck"];
+        assert_eq!(
+            fuzzy_match("df", candidates),
+            [(SCORE_MATCH * 2 + BONUS_BOUNDARY - PENALTY_GAP_START, "diff")]
+        );
+
+        let names: Vec<&str> = fuzzy_match("ana", ["Mariana", "Ana Paula", "Ana", "Joana"])
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+        assert_eq!(names, ["Ana", "Ana Paula", "Joana", "Mariana"]);
+
+        let all = fuzzy_match("", ["b", "a"]);
+        assert_eq!(all, [(0, "b"), (0, "a")]);
+    }
+}
@@ -0,0 +1,32 @@
+/*
+ * Exemplo de uso da interface C (feature "ffi")
+ *
+ *   cargo build --release --no-default-features --features ffi
+ *   cc examples/ffi/main.c -Iinclude -Ltarget/release -lrust_app_exemplo -o ffi-demo
+ *   LD_LIBRARY_PATH=target/release ./ffi-demo
+ */
+#include <inttypes.h>
+#include <stdio.h>
+
+#include "rust_app_exemplo.h"
+
+int main(void) {
+    User *user = rae_user_new(1, "Ana Souza", "ana@example.com");
+    if (user == NULL) {
+        fprintf(stderr, "falha ao criar usuário\n");
+        return 1;
+    }
+
+    rae_user_set_active(user, false);
+
+    char *name = rae_user_name(user);
+    printf("Usuário %" PRIu64 ": %s (ativo: %s)\n", rae_user_id(user), name,
+           rae_user_is_active(user) ? "sim" : "não");
+    rae_string_free(name);
+    rae_user_free(user);
+
+    printf("fibonacci(50) = %" PRIu64 "\n", rae_fibonacci(50));
+    printf("97 é primo? %s\n", rae_is_prime(97) ? "sim" : "não");
+
+    return 0;
+}
@@ -0,0 +1,10 @@
+//! Carregamento de configuração a partir de um arquivo TOML
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_app_exemplo::config::AppConfig;
+
+fuzz_target!(|content: &str| {
+    let _ = AppConfig::from_str(content, config::FileFormat::Toml);
+});
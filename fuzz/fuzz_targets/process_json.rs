@@ -0,0 +1,13 @@
+//! Parsing JSON do comando `process`
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_app_exemplo::formats::{self, DocumentFormat};
+
+fuzz_target!(|content: &str| {
+    if let Ok(value) = formats::parse(content, DocumentFormat::Json) {
+        // O comando imprime o resultado formatado
+        serde_json::to_string_pretty(&value).unwrap();
+    }
+});
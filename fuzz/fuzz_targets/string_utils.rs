@@ -0,0 +1,13 @@
+//! Funções de `string_utils` com texto arbitrário
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rust_app_exemplo::string_utils::{count_vowels, ngrams, reverse, to_title_case, words};
+
+fuzz_target!(|s: &str| {
+    assert_eq!(reverse(&reverse(s)), s);
+    assert!(count_vowels(s) <= s.chars().count());
+    to_title_case(s);
+    assert_eq!(ngrams(s, 1).len(), words(s).len());
+});
@@ -0,0 +1,81 @@
+#ifndef RUST_APP_EXEMPLO_H
+#define RUST_APP_EXEMPLO_H
+
+/* Gerado pelo cbindgen; não edite à mão (make ffi-header). */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Estrutura que representa um usuário do sistema
+typedef struct User User;
+
+// Cria um usuário ativo
+//
+// Devolve `NULL` se `name` ou `email` forem nulos ou não forem UTF-8.
+//
+// # Safety
+//
+// `name` e `email` devem ser nulos ou apontar para strings C válidas.
+struct User *rae_user_new(uint64_t id, const char *name, const char *email);
+
+// Libera um usuário criado por `rae_user_new` (aceita `NULL`)
+//
+// # Safety
+//
+// `user` deve ser nulo ou ter vindo de `rae_user_new`, e não pode ser
+// usado depois desta chamada.
+void rae_user_free(struct User *user);
+
+// ID do usuário
+//
+// # Safety
+//
+// `user` deve ser um handle válido (não nulo).
+uint64_t rae_user_id(const struct User *user);
+
+// Nome do usuário; libere o resultado com `rae_string_free`
+//
+// # Safety
+//
+// `user` deve ser um handle válido (não nulo).
+char *rae_user_name(const struct User *user);
+
+// Email do usuário; libere o resultado com `rae_string_free`
+//
+// # Safety
+//
+// `user` deve ser um handle válido (não nulo).
+char *rae_user_email(const struct User *user);
+
+// Indica se o usuário está ativo
+//
+// # Safety
+//
+// `user` deve ser um handle válido (não nulo).
+bool rae_user_is_active(const struct User *user);
+
+// Ativa ou desativa o usuário
+//
+// # Safety
+//
+// `user` deve ser um handle válido (não nulo).
+void rae_user_set_active(struct User *user, bool active);
+
+// Libera uma string devolvida pela biblioteca (aceita `NULL`)
+//
+// # Safety
+//
+// `s` deve ser nulo ou ter vindo desta biblioteca, e não pode ser usado
+// depois desta chamada.
+void rae_string_free(char *s);
+
+// N-ésimo número de Fibonacci; `UINT64_MAX` se `n` > 93 (não cabe em 64 bits)
+uint64_t rae_fibonacci(uint64_t n);
+
+// Verifica se `n` é primo
+bool rae_is_prime(uint64_t n);
+
+#endif  /* RUST_APP_EXEMPLO_H */
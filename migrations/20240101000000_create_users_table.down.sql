@@ -0,0 +1,2 @@
+-- Reverte 20240101000000_create_users_table.up.sql
+DROP TABLE IF EXISTS users;
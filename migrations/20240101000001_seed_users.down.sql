@@ -0,0 +1,3 @@
+-- Reverte 20240101000001_seed_users.up.sql
+DELETE FROM users
+WHERE email IN ('alice@example.com', 'bob@example.com', 'charlie@example.com');
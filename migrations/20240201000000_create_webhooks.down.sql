@@ -0,0 +1,3 @@
+-- Reverte 20240201000000_create_webhooks.up.sql
+DROP TABLE IF EXISTS webhook_deliveries;
+DROP TABLE IF EXISTS webhooks;
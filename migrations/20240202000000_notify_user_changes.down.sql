@@ -0,0 +1,3 @@
+-- Reverte 20240202000000_notify_user_changes.up.sql
+DROP TRIGGER IF EXISTS users_notify_changes ON users;
+DROP FUNCTION IF EXISTS notify_user_changes();
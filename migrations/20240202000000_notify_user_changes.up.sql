@@ -0,0 +1,37 @@
+-- Notifica mudanças em usuários no canal user_changes (LISTEN/NOTIFY), para
+-- que outras instâncias da aplicação publiquem o evento localmente.
+-- "origin" é o app.instance_id da conexão que fez a mudança.
+CREATE OR REPLACE FUNCTION notify_user_changes() RETURNS trigger AS $$
+DECLARE
+    payload json;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        payload := json_build_object(
+            'op', TG_OP,
+            'id', OLD.id,
+            'origin', current_setting('app.instance_id', true)
+        );
+    ELSE
+        payload := json_build_object(
+            'op', TG_OP,
+            'id', NEW.id,
+            'origin', current_setting('app.instance_id', true),
+            'user', json_build_object(
+                'id', NEW.id,
+                'name', NEW.name,
+                'email', NEW.email,
+                'active', NEW.active,
+                'created_at', NEW.created_at
+            )
+        );
+    END IF;
+
+    PERFORM pg_notify('user_changes', payload::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS users_notify_changes ON users;
+CREATE TRIGGER users_notify_changes
+    AFTER INSERT OR UPDATE OR DELETE ON users
+    FOR EACH ROW EXECUTE FUNCTION notify_user_changes();
@@ -0,0 +1,2 @@
+-- Reverte 20240203000000_create_outbox.up.sql
+DROP TABLE IF EXISTS outbox;
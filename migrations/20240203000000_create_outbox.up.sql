@@ -0,0 +1,16 @@
+-- Outbox transacional: eventos gravados na mesma transação da mudança em
+-- users e publicados depois pelo relay (webhooks, fila)
+CREATE TABLE IF NOT EXISTS outbox (
+    id BIGSERIAL PRIMARY KEY,
+    event_type VARCHAR(100) NOT NULL,
+    -- DomainEvent serializado ({"type": ..., "data": ...})
+    payload JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMPTZ,
+    -- Falhas de publicação até agora e a última delas
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT
+);
+
+-- Pendentes, na ordem em que o relay os publica
+CREATE INDEX IF NOT EXISTS idx_outbox_pending ON outbox(id) WHERE published_at IS NULL;
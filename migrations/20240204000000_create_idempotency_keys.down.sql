@@ -0,0 +1,2 @@
+-- Reverte 20240204000000_create_idempotency_keys.up.sql
+DROP TABLE IF EXISTS idempotency_keys;
@@ -0,0 +1,15 @@
+-- Respostas guardadas por Idempotency-Key (middleware da API)
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    key VARCHAR(255) PRIMARY KEY,
+    -- SHA-256 (hex) do método, caminho e corpo da requisição
+    request_hash CHAR(64) NOT NULL,
+    -- NULL enquanto a requisição está em andamento
+    status_code INTEGER,
+    content_type TEXT,
+    body BYTEA,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+-- Limpeza das chaves expiradas
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_expires_at ON idempotency_keys(expires_at);
@@ -0,0 +1,7 @@
+-- Exclusão em duas fases: a conta fica marcada até o fim do prazo de
+-- carência e só então é removida (com as linhas que dependem dela)
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deletion_scheduled_at TIMESTAMPTZ;
+
+-- Contas a remover, na ordem do prazo
+CREATE INDEX IF NOT EXISTS idx_users_deletion_scheduled_at ON users(deletion_scheduled_at)
+    WHERE deletion_scheduled_at IS NOT NULL;
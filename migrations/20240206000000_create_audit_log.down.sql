@@ -0,0 +1,2 @@
+-- Reverte 20240206000000_create_audit_log.up.sql
+DROP TABLE IF EXISTS audit_log;
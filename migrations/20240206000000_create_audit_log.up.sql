@@ -0,0 +1,17 @@
+-- Log de auditoria: uma linha por requisição que altera dados (middleware
+-- da API)
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Principal autenticado ("anonymous", "admin")
+    actor VARCHAR(255) NOT NULL,
+    ip TEXT,
+    user_agent TEXT,
+    method VARCHAR(16) NOT NULL,
+    path TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    request_id VARCHAR(128)
+);
+
+-- Ações de um ator, das mais recentes para as mais antigas
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor ON audit_log(actor, id DESC);
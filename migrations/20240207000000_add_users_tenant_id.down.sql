@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_users_tenant;
+CREATE INDEX IF NOT EXISTS idx_users_email ON users(email);
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_tenant_email_key;
+ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email);
+ALTER TABLE users DROP COLUMN IF EXISTS tenant_id;
@@ -0,0 +1,12 @@
+-- Isolamento por tenant: as linhas existentes ficam no tenant padrão e o
+-- email passa a ser único dentro de cada tenant
+ALTER TABLE users ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_key;
+ALTER TABLE users ADD CONSTRAINT users_tenant_email_key UNIQUE (tenant_id, email);
+
+-- A unique acima já atende à busca por email
+DROP INDEX IF EXISTS idx_users_email;
+
+-- Listagens por tenant, na ordem do ID
+CREATE INDEX IF NOT EXISTS idx_users_tenant ON users(tenant_id, id);
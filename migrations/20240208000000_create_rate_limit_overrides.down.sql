@@ -0,0 +1,2 @@
+-- Reverte 20240208000000_create_rate_limit_overrides.up.sql
+DROP TABLE IF EXISTS rate_limit_overrides;
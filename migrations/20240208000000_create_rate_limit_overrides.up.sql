@@ -0,0 +1,9 @@
+-- Limites de requisições por cliente, no lugar de
+-- features.rate_limit_per_minute. A chave é a do limitador: "key:<chave de
+-- API>" ou "ip:<endereço>"
+CREATE TABLE IF NOT EXISTS rate_limit_overrides (
+    key VARCHAR(255) PRIMARY KEY,
+    requests_per_minute INTEGER NOT NULL CHECK (requests_per_minute > 0),
+    note TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
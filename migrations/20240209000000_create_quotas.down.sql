@@ -0,0 +1,3 @@
+-- Reverte 20240209000000_create_quotas.up.sql
+DROP TABLE IF EXISTS quota_usage;
+DROP TABLE IF EXISTS quotas;
@@ -0,0 +1,19 @@
+-- Cotas mensais por tenant: limites próprios (sem linha, vale [quotas] da
+-- configuração) e o uso de cada mês
+CREATE TABLE IF NOT EXISTS quotas (
+    tenant_id VARCHAR(64) NOT NULL,
+    -- "users_created", "exports"
+    resource VARCHAR(64) NOT NULL,
+    monthly_limit INTEGER NOT NULL CHECK (monthly_limit >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, resource)
+);
+
+CREATE TABLE IF NOT EXISTS quota_usage (
+    tenant_id VARCHAR(64) NOT NULL,
+    resource VARCHAR(64) NOT NULL,
+    -- Início do mês (UTC)
+    period_start TIMESTAMPTZ NOT NULL,
+    used INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (tenant_id, resource, period_start)
+);
@@ -0,0 +1,3 @@
+-- Reverte 20240210000000_add_outbox_trace_context.up.sql
+ALTER TABLE outbox DROP COLUMN IF EXISTS tracestate;
+ALTER TABLE outbox DROP COLUMN IF EXISTS traceparent;
@@ -0,0 +1,4 @@
+-- Trace da requisição que gerou o evento (W3C Trace Context), repassado
+-- pelo relay às entregas
+ALTER TABLE outbox ADD COLUMN IF NOT EXISTS traceparent VARCHAR(55);
+ALTER TABLE outbox ADD COLUMN IF NOT EXISTS tracestate VARCHAR(512);
@@ -0,0 +1,17 @@
+-- Login dos usuários: senha (hash argon2; NULL enquanto não definida) e
+-- o último login bem-sucedido
+ALTER TABLE users ADD COLUMN IF NOT EXISTS password_hash TEXT;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS last_login_at TIMESTAMPTZ;
+
+-- Histórico das tentativas de login (com e sem sucesso) de cada usuário
+CREATE TABLE IF NOT EXISTS login_events (
+    id BIGSERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ip TEXT,
+    user_agent TEXT,
+    success BOOLEAN NOT NULL
+);
+
+-- Tentativas de um usuário, das mais recentes para as mais antigas
+CREATE INDEX IF NOT EXISTS idx_login_events_user ON login_events(user_id, id DESC);
@@ -0,0 +1,12 @@
+-- Papel do usuário (define a política de 2FA) e o segredo TOTP, pendente
+-- até a confirmação (totp_enabled_at)
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'user';
+ALTER TABLE users ADD COLUMN IF NOT EXISTS totp_secret TEXT;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS totp_enabled_at TIMESTAMPTZ;
+
+-- Códigos de recuperação do 2FA (SHA-256), cada um de uso único
+CREATE TABLE IF NOT EXISTS backup_codes (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    PRIMARY KEY (user_id, code_hash)
+);
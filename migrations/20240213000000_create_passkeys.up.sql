@@ -0,0 +1,14 @@
+-- Passkeys (credenciais WebAuthn) dos usuários, para o login sem senha
+CREATE TABLE IF NOT EXISTS passkeys (
+    id BIGSERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Id da credencial (hex), único entre todos os autenticadores
+    credential_id TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    -- Credencial serializada pelo webauthn-rs (chave pública e contador)
+    passkey JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_passkeys_user ON passkeys(user_id);
@@ -0,0 +1,38 @@
+-- Reverte 20240214000000_add_user_status.up.sql (só "active" sobrevive
+-- como ativa)
+ALTER TABLE users ADD COLUMN IF NOT EXISTS active BOOLEAN NOT NULL DEFAULT true;
+UPDATE users SET active = (status = 'active');
+CREATE INDEX IF NOT EXISTS idx_users_active ON users(active);
+DROP INDEX IF EXISTS idx_users_status;
+ALTER TABLE users DROP COLUMN IF EXISTS status;
+DROP TYPE IF EXISTS user_status;
+
+CREATE OR REPLACE FUNCTION notify_user_changes() RETURNS trigger AS $$
+DECLARE
+    payload json;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        payload := json_build_object(
+            'op', TG_OP,
+            'id', OLD.id,
+            'origin', current_setting('app.instance_id', true)
+        );
+    ELSE
+        payload := json_build_object(
+            'op', TG_OP,
+            'id', NEW.id,
+            'origin', current_setting('app.instance_id', true),
+            'user', json_build_object(
+                'id', NEW.id,
+                'name', NEW.name,
+                'email', NEW.email,
+                'active', NEW.active,
+                'created_at', NEW.created_at
+            )
+        );
+    END IF;
+
+    PERFORM pg_notify('user_changes', payload::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,45 @@
+-- Situação da conta no lugar do antigo booleano "active": contas inativas
+-- viram "deactivated"
+DO $$ BEGIN
+    CREATE TYPE user_status AS ENUM ('pending', 'active', 'suspended', 'deactivated', 'banned');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END $$;
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS status user_status NOT NULL DEFAULT 'active';
+UPDATE users SET status = 'deactivated' WHERE NOT active;
+
+DROP INDEX IF EXISTS idx_users_active;
+ALTER TABLE users DROP COLUMN IF EXISTS active;
+CREATE INDEX IF NOT EXISTS idx_users_status ON users(status);
+
+-- O evento de user_changes passa a levar "status" no lugar de "active"
+CREATE OR REPLACE FUNCTION notify_user_changes() RETURNS trigger AS $$
+DECLARE
+    payload json;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        payload := json_build_object(
+            'op', TG_OP,
+            'id', OLD.id,
+            'origin', current_setting('app.instance_id', true)
+        );
+    ELSE
+        payload := json_build_object(
+            'op', TG_OP,
+            'id', NEW.id,
+            'origin', current_setting('app.instance_id', true),
+            'user', json_build_object(
+                'id', NEW.id,
+                'name', NEW.name,
+                'email', NEW.email,
+                'status', NEW.status,
+                'created_at', NEW.created_at
+            )
+        );
+    END IF;
+
+    PERFORM pg_notify('user_changes', payload::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,2 @@
+-- Reverte 20240215000000_add_users_email_lower_unique.up.sql
+DROP INDEX IF EXISTS users_tenant_email_lower_key;
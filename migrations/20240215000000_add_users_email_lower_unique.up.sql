@@ -0,0 +1,3 @@
+-- Email único por tenant sem diferenciar maiúsculas: "Ana@x.com" e
+-- "ana@x.com" são a mesma conta (base do `DbUser::create_or_get`)
+CREATE UNIQUE INDEX IF NOT EXISTS users_tenant_email_lower_key ON users (tenant_id, lower(email));
@@ -0,0 +1,2 @@
+-- Reverte 20240216000000_add_outbox_tenant_id.up.sql
+ALTER TABLE outbox DROP COLUMN IF EXISTS tenant_id;
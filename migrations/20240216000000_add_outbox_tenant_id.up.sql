@@ -0,0 +1,10 @@
+-- Tenant do usuário de cada evento, para o SSE e os webhooks filtrarem por
+-- ele. Os eventos já gravados ficam com o tenant atual do usuário (ou com o
+-- padrão, se ele já foi removido).
+ALTER TABLE outbox ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+
+UPDATE outbox SET tenant_id = users.tenant_id
+FROM users
+WHERE users.id::text = outbox.payload->'data'->>'id';
+
+ALTER TABLE outbox ALTER COLUMN tenant_id DROP DEFAULT;
@@ -0,0 +1,33 @@
+-- O evento de user_changes passa a levar o tenant do usuário, para as
+-- outras instâncias publicarem o evento no tenant certo
+CREATE OR REPLACE FUNCTION notify_user_changes() RETURNS trigger AS $$
+DECLARE
+    payload json;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        payload := json_build_object(
+            'op', TG_OP,
+            'id', OLD.id,
+            'tenant_id', OLD.tenant_id,
+            'origin', current_setting('app.instance_id', true)
+        );
+    ELSE
+        payload := json_build_object(
+            'op', TG_OP,
+            'id', NEW.id,
+            'tenant_id', NEW.tenant_id,
+            'origin', current_setting('app.instance_id', true),
+            'user', json_build_object(
+                'id', NEW.id,
+                'name', NEW.name,
+                'email', NEW.email,
+                'status', NEW.status,
+                'created_at', NEW.created_at
+            )
+        );
+    END IF;
+
+    PERFORM pg_notify('user_changes', payload::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,3 @@
+-- Reverte 20240218000000_add_webhooks_tenant_id.up.sql
+DROP INDEX IF EXISTS idx_webhooks_tenant;
+ALTER TABLE webhooks DROP COLUMN IF EXISTS tenant_id;
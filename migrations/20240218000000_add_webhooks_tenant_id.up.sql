@@ -0,0 +1,5 @@
+-- Cada webhook recebe só os eventos do próprio tenant; os já cadastrados
+-- ficam no tenant padrão
+ALTER TABLE webhooks ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default';
+
+CREATE INDEX IF NOT EXISTS idx_webhooks_tenant ON webhooks(tenant_id, id);
@@ -0,0 +1,3 @@
+-- Reverte 20240219000000_add_audit_log_subject_id.up.sql
+DROP INDEX IF EXISTS idx_audit_log_subject;
+ALTER TABLE audit_log DROP COLUMN IF EXISTS subject_id;
@@ -0,0 +1,11 @@
+-- Usuário afetado por cada requisição, para a exportação dos dados dele.
+-- As linhas existentes recebem o ID do caminho (/api/users/:id/...,
+-- /api/admin/impersonate/:id), quando há um.
+ALTER TABLE audit_log ADD COLUMN IF NOT EXISTS subject_id INTEGER;
+
+UPDATE audit_log
+SET subject_id = substring(path FROM '^/api/(?:users|admin/impersonate)/([0-9]{1,9})(?:/|$)')::int
+WHERE subject_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_subject ON audit_log(subject_id, id)
+    WHERE subject_id IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Reverte 20240220000000_add_outbox_user_id.up.sql
+DROP INDEX IF EXISTS idx_outbox_user;
+ALTER TABLE outbox DROP COLUMN IF EXISTS user_id;
@@ -0,0 +1,9 @@
+-- Usuário de cada evento, para buscar o histórico dele (exportação) pelo
+-- índice em vez de varrer o payload de todo o outbox
+ALTER TABLE outbox ADD COLUMN IF NOT EXISTS user_id INTEGER;
+
+UPDATE outbox SET user_id = (payload->'data'->>'id')::int WHERE user_id IS NULL;
+
+ALTER TABLE outbox ALTER COLUMN user_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_outbox_user ON outbox(user_id, id);
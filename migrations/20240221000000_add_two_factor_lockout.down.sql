@@ -0,0 +1,4 @@
+-- Reverte 20240221000000_add_two_factor_lockout.up.sql
+ALTER TABLE users DROP COLUMN IF EXISTS totp_locked_until;
+ALTER TABLE users DROP COLUMN IF EXISTS totp_failed_attempts;
+ALTER TABLE users DROP COLUMN IF EXISTS totp_last_step;
@@ -0,0 +1,5 @@
+-- Último passo TOTP aceito (um código não vale duas vezes) e o bloqueio do
+-- segundo fator depois de tentativas erradas seguidas
+ALTER TABLE users ADD COLUMN IF NOT EXISTS totp_last_step BIGINT;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS totp_failed_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS totp_locked_until TIMESTAMPTZ;
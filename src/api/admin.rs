@@ -0,0 +1,41 @@
+//! Endpoints administrativos da API
+
+use crate::api::{ApiError, ApiResponse};
+use crate::logging;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct LogLevelRequest {
+    /// Diretivas no formato do `EnvFilter` (ex.: `"debug"`, `"info,sqlx=warn"`)
+    pub level: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogLevelResponse {
+    pub level: Option<String>,
+}
+
+/// Retorna o nível de log em vigor
+pub async fn get_log_level() -> Json<ApiResponse<LogLevelResponse>> {
+    Json(ApiResponse::success(LogLevelResponse {
+        level: logging::current_level(),
+    }))
+}
+
+/// Altera o nível de log sem reiniciar o processo
+pub async fn set_log_level(
+    Json(payload): Json<LogLevelRequest>,
+) -> Result<Json<ApiResponse<LogLevelResponse>>, ApiError> {
+    if payload.level.trim().is_empty() {
+        return Err(ApiError::BadRequest("level must not be empty".to_string()));
+    }
+
+    logging::validate_level(&payload.level)
+        .map_err(|e| ApiError::BadRequest(format!("{:#}", e)))?;
+    logging::set_level(&payload.level).map_err(|e| ApiError::InternalError(format!("{:#}", e)))?;
+
+    Ok(Json(ApiResponse::success(LogLevelResponse {
+        level: logging::current_level(),
+    })))
+}
@@ -0,0 +1,990 @@
+//! Login dos usuários e histórico de acessos
+//!
+//! - `POST /api/auth/login`: confere email e senha e devolve um token de
+//!   acesso (`Authorization: Bearer <token>`); toda tentativa vai para
+//!   `login_events`, e as bem-sucedidas atualizam `last_login_at`
+//! - `PUT /api/users/:id/password`: define a senha (o próprio usuário ou
+//!   um admin)
+//! - `GET /api/users/:id/logins`: as tentativas mais recentes (o próprio
+//!   usuário ou um admin)
+//! - `POST /api/auth/2fa/setup`, `POST /api/auth/2fa/enable` e
+//!   `DELETE /api/auth/2fa`: cadastro do segundo fator (TOTP) do usuário do
+//!   token; com ele ativo, o login exige também um código do app ou de
+//!   recuperação
+//! - `POST /api/admin/impersonate/:id`: token de curta duração para o admin
+//!   agir como o usuário, com o admin nas claims e na auditoria
+
+use crate::api::handlers::UserResponse;
+use crate::api::{ApiError, ApiResponse, AppState};
+use crate::audit::Principal;
+use crate::auth::{password, totp, IssuedToken};
+use crate::config::TwoFactorPolicy;
+use crate::models::{DbUser, LoginEvent, TwoFactor};
+use crate::repository::OrNotFound;
+use crate::tenant::{LoginTenant, TenantContext};
+use axum::{
+    extract::{ConnectInfo, Path, State},
+    http::{header, HeaderMap},
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use validator::Validate;
+
+/// Maior user agent gravado em `login_events`
+const MAX_USER_AGENT_CHARS: usize = 512;
+
+/// Tentativas devolvidas por `GET /api/users/:id/logins`
+const LOGIN_HISTORY_LIMIT: i64 = 100;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/auth/login", post(login))
+        .route("/api/users/:id/password", put(set_password))
+        .route("/api/users/:id/logins", get(list_logins))
+        .route("/api/auth/2fa/setup", post(setup_two_factor))
+        .route("/api/auth/2fa/enable", post(enable_two_factor))
+        .route("/api/auth/2fa", delete(disable_two_factor))
+        .route("/api/admin/impersonate/:id", post(impersonate))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+    /// Código do app autenticador ou de recuperação, com o 2FA ativo
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginResponse {
+    pub token: String,
+    pub token_type: String,
+    pub expires_at: DateTime<Utc>,
+    pub user: UserResponse,
+    /// O papel exige 2FA e ele não está ativo: o token só serve para
+    /// `/api/auth/2fa/setup` e `/api/auth/2fa/enable`
+    pub two_factor_setup_required: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorSetupResponse {
+    /// Segredo em base32, para digitar no app
+    pub secret: String,
+    /// Conteúdo do QR code
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TwoFactorCodeRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorEnableResponse {
+    /// Códigos de recuperação, mostrados só desta vez
+    pub backup_codes: Vec<String>,
+    /// Token completo, quando o do pedido só servia para ativar o 2FA
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<IssuedToken>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImpersonationResponse {
+    pub token: String,
+    pub token_type: String,
+    pub expires_at: DateTime<Utc>,
+    pub user: UserResponse,
+    /// Quem age como o usuário
+    pub impersonated_by: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetPasswordRequest {
+    #[validate(length(min = 8, max = 128))]
+    pub password: String,
+}
+
+/// Troca email e senha (e o código do 2FA, se ativo) por um token de acesso
+///
+/// Email desconhecido, senha ou código errados, usuário sem senha, inativo
+/// ou com a exclusão agendada: todos respondem o mesmo 401. Com a senha
+/// certa e o 2FA ativo, a falta do código responde 401 com
+/// "Two-factor code required".
+async fn login(
+    State(state): State<AppState>,
+    LoginTenant(tenant): LoginTenant,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Json<ApiResponse<LoginResponse>>, ApiError> {
+    let user = state
+        .users
+        .find_by_email(&tenant, &payload.email)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    let hash = match &user {
+        Some(user) => state
+            .users
+            .password_hash(&tenant, user.id)
+            .await
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?,
+        None => None,
+    };
+    let password = payload.password;
+    let valid = tokio::task::spawn_blocking(move || password::verify(&password, hash.as_deref()))
+        .await
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+
+    let invalid = || ApiError::Unauthorized("Invalid credentials".to_string());
+    let mut user = user.ok_or_else(invalid)?;
+    let mut success = valid && user.is_active() && user.deletion_scheduled_at.is_none();
+
+    let two_factor = if success {
+        state
+            .users
+            .two_factor(&tenant, user.id)
+            .await
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+            .unwrap_or_default()
+    } else {
+        TwoFactor::default()
+    };
+    if let (true, Some(secret)) = (two_factor.is_enabled(), &two_factor.secret) {
+        let Some(code) = &payload.code else {
+            return Err(ApiError::Unauthorized(
+                "Two-factor code required".to_string(),
+            ));
+        };
+        success = check_code(&state, &tenant, user.id, &two_factor, secret, code).await?;
+    }
+
+    let occurred_at = record_login(&state, &tenant, user.id, success, connect_info, &headers).await;
+    if !success {
+        return Err(invalid());
+    }
+
+    let setup_required = !two_factor.is_enabled()
+        && state.two_factor.policy(&user.role) == TwoFactorPolicy::Required;
+    user.last_login_at = Some(occurred_at);
+    Ok(Json(ApiResponse::success(login_response(
+        &state,
+        &tenant,
+        user,
+        setup_required,
+    ))))
+}
+
+/// Grava uma tentativa de login e devolve quando ela ocorreu; uma falha na
+/// gravação só vai para os logs
+pub(crate) async fn record_login(
+    state: &AppState,
+    tenant: &TenantContext,
+    user_id: i32,
+    success: bool,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: &HeaderMap,
+) -> DateTime<Utc> {
+    let event = LoginEvent {
+        user_id,
+        occurred_at: Utc::now(),
+        ip: connect_info.map(|ConnectInfo(addr)| addr.ip().to_string()),
+        user_agent: headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.chars().take(MAX_USER_AGENT_CHARS).collect()),
+        success,
+    };
+    if let Err(e) = state.users.record_login(tenant, &event).await {
+        tracing::warn!(error = %e, user_id, "Failed to record login");
+    }
+    event.occurred_at
+}
+
+/// Resposta de um login bem-sucedido, com um token novo para `user`
+pub(crate) fn login_response(
+    state: &AppState,
+    tenant: &TenantContext,
+    user: DbUser,
+    two_factor_setup_required: bool,
+) -> LoginResponse {
+    let mut claims = state.tokens.claims(user.id, tenant);
+    claims.two_factor_pending = two_factor_setup_required;
+    let issued = state.tokens.issue(&claims);
+    LoginResponse {
+        token: issued.token,
+        token_type: "Bearer".to_string(),
+        expires_at: issued.expires_at,
+        user: user.into(),
+        two_factor_setup_required,
+    }
+}
+
+/// Define a senha do usuário
+async fn set_password(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    Path(id): Path<i32>,
+    Json(payload): Json<SetPasswordRequest>,
+) -> Result<Json<ApiResponse<()>>, ApiError> {
+    authorize(&principal, &tenant, id)?;
+    payload.validate()?;
+
+    let hash = tokio::task::spawn_blocking(move || password::hash(&payload.password))
+        .await
+        .map_err(|e| ApiError::InternalError(e.to_string()))?
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+    let updated = state
+        .users
+        .set_password_hash(&tenant, id, &hash)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    if !updated {
+        return Err(ApiError::NotFound(format!("User with id {} not found", id)));
+    }
+
+    Ok(Json(ApiResponse::success(())))
+}
+
+/// Tentativas de login do usuário, da mais recente para a mais antiga
+async fn list_logins(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<LoginEvent>>>, ApiError> {
+    authorize(&principal, &tenant, id)?;
+
+    state.users.get(&tenant, id).await?;
+    let logins = state
+        .users
+        .logins(&tenant, id, LOGIN_HISTORY_LIMIT)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(logins)))
+}
+
+/// Começa o cadastro do 2FA: gera um segredo novo, pendente até
+/// `enable_two_factor` confirmar um código dele
+async fn setup_two_factor(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+) -> Result<Json<ApiResponse<TwoFactorSetupResponse>>, ApiError> {
+    let (id, _) = token_user(&principal, &tenant)?;
+    let user = state.users.get(&tenant, id).await?;
+    if current_two_factor(&state, &tenant, id).await?.is_enabled() {
+        return Err(ApiError::Conflict(
+            "Two-factor authentication is already enabled".to_string(),
+        ));
+    }
+
+    let secret = totp::generate_secret();
+    let otpauth_uri = totp::otpauth_uri(&secret, &state.two_factor.issuer, &user.email)
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+    state
+        .users
+        .set_two_factor_secret(&tenant, id, Some(&secret))
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(TwoFactorSetupResponse {
+        secret,
+        otpauth_uri,
+    })))
+}
+
+/// Ativa o 2FA com um código do segredo pendente e devolve os códigos de
+/// recuperação
+async fn enable_two_factor(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> Result<Json<ApiResponse<TwoFactorEnableResponse>>, ApiError> {
+    let (id, pending) = token_user(&principal, &tenant)?;
+    let two_factor = current_two_factor(&state, &tenant, id).await?;
+    let secret = match &two_factor.secret {
+        Some(secret) if !two_factor.is_enabled() => secret,
+        _ => {
+            return Err(ApiError::Conflict(
+                "There is no pending two-factor setup".to_string(),
+            ))
+        }
+    };
+    if !check_code(&state, &tenant, id, &two_factor, secret, &payload.code).await? {
+        return Err(ApiError::BadRequest("Invalid two-factor code".to_string()));
+    }
+
+    let backup_codes = totp::generate_backup_codes();
+    let hashes: Vec<String> = backup_codes
+        .iter()
+        .map(|code| totp::hash_backup_code(code))
+        .collect();
+    let enabled = state
+        .users
+        .enable_two_factor(&tenant, id, &hashes)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    if !enabled {
+        return Err(ApiError::Conflict(
+            "There is no pending two-factor setup".to_string(),
+        ));
+    }
+
+    let token = pending.then(|| state.tokens.issue(&state.tokens.claims(id, &tenant)));
+    Ok(Json(ApiResponse::success(TwoFactorEnableResponse {
+        backup_codes,
+        token,
+    })))
+}
+
+/// Desativa o 2FA, mediante um código; recusado se o papel o exige
+async fn disable_two_factor(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> Result<Json<ApiResponse<()>>, ApiError> {
+    let (id, pending) = token_user(&principal, &tenant)?;
+    let user = state.users.get(&tenant, id).await?;
+    if pending || state.two_factor.policy(&user.role) == TwoFactorPolicy::Required {
+        return Err(ApiError::Forbidden(
+            "Two-factor authentication is required for this account".to_string(),
+        ));
+    }
+    let two_factor = current_two_factor(&state, &tenant, id).await?;
+    let Some(secret) = two_factor
+        .secret
+        .as_deref()
+        .filter(|_| two_factor.is_enabled())
+    else {
+        return Err(ApiError::Conflict(
+            "Two-factor authentication is not enabled".to_string(),
+        ));
+    };
+    if !check_code(&state, &tenant, id, &two_factor, secret, &payload.code).await? {
+        return Err(ApiError::BadRequest("Invalid two-factor code".to_string()));
+    }
+
+    state
+        .users
+        .set_two_factor_secret(&tenant, id, None)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    Ok(Json(ApiResponse::success(())))
+}
+
+/// Se `code` é o código atual de `secret`, ainda não usado, ou um código
+/// de recuperação ainda não usado (que então é gasto)
+///
+/// `auth.two_factor.max_failed_attempts` códigos errados seguidos bloqueiam
+/// o segundo fator por `lockout_seconds`; bloqueado, responde 429 sem
+/// conferir o código.
+async fn check_code(
+    state: &AppState,
+    tenant: &TenantContext,
+    id: i32,
+    two_factor: &TwoFactor,
+    secret: &str,
+    code: &str,
+) -> Result<bool, ApiError> {
+    let now = Utc::now();
+    if let Some(until) = two_factor.locked_at(now) {
+        return Err(ApiError::TwoFactorLocked(until));
+    }
+
+    let valid = match totp::verify_step(secret, code, unix_seconds(now)) {
+        Some(step) => {
+            state
+                .users
+                .accept_totp_step(tenant, id, i64::try_from(step).unwrap_or(i64::MAX))
+                .await
+        }
+        None => {
+            state
+                .users
+                .consume_backup_code(tenant, id, &totp::hash_backup_code(code))
+                .await
+        }
+    }
+    .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    let config = &state.two_factor;
+    if valid {
+        if two_factor.failed_attempts > 0 {
+            state
+                .users
+                .reset_two_factor_failures(tenant, id)
+                .await
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        }
+    } else if config.max_failed_attempts > 0 {
+        let lockout =
+            chrono::Duration::from_std(std::time::Duration::from_secs(config.lockout_seconds))
+                .unwrap_or(chrono::Duration::MAX);
+        let locked = state
+            .users
+            .record_two_factor_failure(
+                tenant,
+                id,
+                i32::try_from(config.max_failed_attempts).unwrap_or(i32::MAX),
+                now.checked_add_signed(lockout)
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC),
+            )
+            .await
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        if locked {
+            tracing::warn!(
+                user_id = id,
+                "Two-factor locked after too many invalid codes"
+            );
+        }
+    }
+    Ok(valid)
+}
+
+async fn current_two_factor(
+    state: &AppState,
+    tenant: &TenantContext,
+    id: i32,
+) -> Result<TwoFactor, ApiError> {
+    state
+        .users
+        .two_factor(tenant, id)
+        .await
+        .or_not_found(id)
+        .map_err(ApiError::from)
+}
+
+fn unix_seconds(now: DateTime<Utc>) -> u64 {
+    u64::try_from(now.timestamp()).unwrap_or_default()
+}
+
+/// Emite um token de `auth.impersonation_ttl_seconds` para o admin agir
+/// como o usuário `id`
+///
+/// Só o admin; o pedido vai para a auditoria como qualquer escrita, e as
+/// requisições com o token aparecem nela como `admin as user:<tenant>/<id>`.
+/// O token não serve para mexer no 2FA nem nas passkeys do usuário.
+async fn impersonate(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<ImpersonationResponse>>, ApiError> {
+    require_admin(&principal, "impersonate users")?;
+    let user = state.users.get(&tenant, id).await?;
+
+    let actor = principal.actor();
+    let issued = state
+        .tokens
+        .issue(&state.tokens.impersonation_claims(user.id, &tenant, &actor));
+    tracing::info!(actor = %actor, user_id = id, tenant = tenant.id(), "Impersonation token issued");
+    Ok(Json(ApiResponse::success(ImpersonationResponse {
+        token: issued.token,
+        token_type: "Bearer".to_string(),
+        expires_at: issued.expires_at,
+        user: user.into(),
+        impersonated_by: actor,
+    })))
+}
+
+/// Id do usuário do token (e se o token só serve para ativar o 2FA); 401
+/// sem token de usuário do tenant, 403 com um token de impersonação
+pub(crate) fn token_user(
+    principal: &Principal,
+    tenant: &TenantContext,
+) -> Result<(i32, bool), ApiError> {
+    match principal {
+        Principal::User { id, tenant: own } if own == tenant.id() => Ok((*id, false)),
+        Principal::Enrolling { id, tenant: own } if own == tenant.id() => Ok((*id, true)),
+        Principal::Impersonating { tenant: own, .. } if own == tenant.id() => Err(
+            ApiError::Forbidden("Not allowed while impersonating".to_string()),
+        ),
+        _ => Err(ApiError::Unauthorized(
+            "A user token is required".to_string(),
+        )),
+    }
+}
+
+/// 401 sem credenciais, 403 para quem não é o admin (`action` completa a
+/// mensagem: "Only admins can <action>")
+pub(crate) fn require_admin(principal: &Principal, action: &str) -> Result<(), ApiError> {
+    match principal {
+        Principal::Admin => Ok(()),
+        Principal::Anonymous => Err(ApiError::Unauthorized(
+            "Authentication required".to_string(),
+        )),
+        _ => Err(ApiError::Forbidden(format!("Only admins can {}", action))),
+    }
+}
+
+/// 401 sem credenciais, 403 para quem não é o usuário `id` nem admin
+pub(crate) fn authorize(
+    principal: &Principal,
+    tenant: &TenantContext,
+    id: i32,
+) -> Result<(), ApiError> {
+    match principal {
+        _ if principal.can_manage_user(tenant, id) => Ok(()),
+        Principal::Anonymous => Err(ApiError::Unauthorized(
+            "Authentication required".to_string(),
+        )),
+        _ => Err(ApiError::Forbidden(
+            "Not allowed to access this user".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::middleware::build_stack;
+    use crate::api::{create_router, AppState};
+    use crate::auth::totp;
+    use crate::config::{AppConfig, TwoFactorPolicy};
+    use crate::models::DbUser;
+    use crate::repository::{InMemoryUserRepository, UserRepository};
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use chrono::{DateTime, Utc};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn app(users: InMemoryUserRepository) -> Router {
+        let mut config = AppConfig::default();
+        config.features.admin_token = Some("s3cret".to_string());
+        config.auth.token_secret = Some("segredo".to_string());
+        config
+            .auth
+            .two_factor
+            .roles
+            .insert("admin".to_string(), TwoFactorPolicy::Required);
+        let state = AppState::new(Arc::new(users), Default::default())
+            .with_tokens(crate::auth::TokenSigner::from_config(&config.auth))
+            .with_two_factor(config.auth.two_factor.clone());
+        build_stack(create_router(state), &config)
+    }
+
+    async fn send(
+        app: &Router,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::USER_AGENT, "test-agent");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null),
+        )
+    }
+
+    fn credentials(password: &str) -> serde_json::Value {
+        serde_json::json!({ "email": "ana@example.com", "password": password })
+    }
+
+    #[tokio::test]
+    async fn test_login_records_history() {
+        let app = app(InMemoryUserRepository::new());
+        for name in ["ana", "bia"] {
+            let user = serde_json::json!({ "name": name, "email": format!("{name}@example.com") });
+            let (status, _) = send(&app, Method::POST, "/api/users", None, user).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let login = |password: &'static str| {
+            send(
+                &app,
+                Method::POST,
+                "/api/auth/login",
+                None,
+                credentials(password),
+            )
+        };
+
+        // Sem senha definida ainda
+        assert_eq!(login("password123").await.0, StatusCode::UNAUTHORIZED);
+
+        let new_password = serde_json::json!({ "password": "password123" });
+        let set = |token: Option<&'static str>, body: serde_json::Value| {
+            send(&app, Method::PUT, "/api/users/1/password", token, body)
+        };
+        assert_eq!(
+            set(None, new_password.clone()).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            set(Some("s3cret"), serde_json::json!({ "password": "short" }))
+                .await
+                .0,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(set(Some("s3cret"), new_password).await.0, StatusCode::OK);
+
+        assert_eq!(login("wrong-password").await.0, StatusCode::UNAUTHORIZED);
+        // O email não diferencia maiúsculas
+        let mixed_case =
+            serde_json::json!({ "email": "Ana@Example.com", "password": "password123" });
+        let (status, _) = send(&app, Method::POST, "/api/auth/login", None, mixed_case).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = login("password123").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["token_type"], "Bearer");
+        assert_eq!(body["data"]["user"]["id"], 1);
+        let token = body["data"]["token"].as_str().unwrap().to_string();
+
+        let (status, body) = send(
+            &app,
+            Method::GET,
+            "/api/users/1/logins",
+            Some(&token),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let successes: Vec<bool> = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["success"].as_bool().unwrap())
+            .collect();
+        assert_eq!(successes, vec![true, true, false, false]);
+        assert_eq!(body["data"][0]["user_agent"], "test-agent");
+
+        // Outro usuário só com o token de admin
+        let other = "/api/users/2/logins";
+        let (status, _) = send(
+            &app,
+            Method::GET,
+            other,
+            Some(&token),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = send(
+            &app,
+            Method::GET,
+            other,
+            Some("s3cret"),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(&app, Method::GET, other, None, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    fn user(id: i32, name: &str, role: &str) -> DbUser {
+        DbUser {
+            id,
+            name: name.to_string(),
+            email: format!("{}@example.com", name.to_lowercase()),
+            status: crate::models::UserStatus::Active,
+            created_at: None,
+            deletion_scheduled_at: None,
+            last_login_at: None,
+            role: role.to_string(),
+        }
+    }
+
+    async fn enable(app: &Router, token: &str, code: String) -> (StatusCode, serde_json::Value) {
+        let body = serde_json::json!({ "code": code });
+        send(app, Method::POST, "/api/auth/2fa/enable", Some(token), body).await
+    }
+
+    fn current_code(secret: &str) -> String {
+        totp::code(secret, chrono::Utc::now().timestamp() as u64).unwrap()
+    }
+
+    /// Código do passo seguinte, aceito pela tolerância do relógio
+    fn next_code(secret: &str) -> String {
+        let now = chrono::Utc::now().timestamp() as u64;
+        totp::code(secret, now + totp::STEP_SECONDS).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_two_factor_login() {
+        let app = app(InMemoryUserRepository::with_users([
+            user(1, "Ana", "user"),
+            user(2, "Root", "admin"),
+        ]));
+        let password = serde_json::json!({ "password": "password123" });
+        for id in [1, 2] {
+            let uri = format!("/api/users/{id}/password");
+            let (status, _) = send(&app, Method::PUT, &uri, Some("s3cret"), password.clone()).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let login = |email: &'static str, code: Option<String>| {
+            let body =
+                serde_json::json!({ "email": email, "password": "password123", "code": code });
+            send(&app, Method::POST, "/api/auth/login", None, body)
+        };
+
+        // 2FA opcional: ativado pela Ana
+        let (status, body) = login("ana@example.com", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["two_factor_setup_required"], false);
+        let token = body["data"]["token"].as_str().unwrap().to_string();
+
+        let (status, body) = send(
+            &app,
+            Method::POST,
+            "/api/auth/2fa/setup",
+            Some(&token),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let secret = body["data"]["secret"].as_str().unwrap().to_string();
+        assert!(body["data"]["otpauth_uri"]
+            .as_str()
+            .unwrap()
+            .starts_with("otpauth://totp/"));
+
+        let (status, _) = enable(&app, &token, "000000".to_string()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, body) = enable(&app, &token, current_code(&secret)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body["data"]["backup_codes"].as_array().unwrap().len(),
+            totp::BACKUP_CODES
+        );
+        assert!(body["data"].get("token").is_none());
+        let backup = body["data"]["backup_codes"][0]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let other_backup = body["data"]["backup_codes"][1]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let (status, body) = login("ana@example.com", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "Two-factor code required");
+        // O código usado na ativação não vale de novo; o do passo seguinte, sim
+        assert_eq!(
+            login("ana@example.com", Some(current_code(&secret)))
+                .await
+                .0,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            login("ana@example.com", Some(next_code(&secret))).await.0,
+            StatusCode::OK
+        );
+        assert_eq!(
+            login("ana@example.com", Some(backup.clone())).await.0,
+            StatusCode::OK
+        );
+        // Código de recuperação é de uso único
+        assert_eq!(
+            login("ana@example.com", Some(backup)).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let body = serde_json::json!({ "code": other_backup });
+        let (status, _) = send(&app, Method::DELETE, "/api/auth/2fa", Some(&token), body).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(login("ana@example.com", None).await.0, StatusCode::OK);
+
+        // 2FA obrigatório para admin: o token do login só serve para ativá-lo
+        let (status, body) = login("root@example.com", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["two_factor_setup_required"], true);
+        let pending = body["data"]["token"].as_str().unwrap().to_string();
+        let (status, _) = send(
+            &app,
+            Method::GET,
+            "/api/users/2/logins",
+            Some(&pending),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (_, body) = send(
+            &app,
+            Method::POST,
+            "/api/auth/2fa/setup",
+            Some(&pending),
+            serde_json::Value::Null,
+        )
+        .await;
+        let secret = body["data"]["secret"].as_str().unwrap().to_string();
+        let (status, body) = enable(&app, &pending, current_code(&secret)).await;
+        assert_eq!(status, StatusCode::OK);
+        let token = body["data"]["token"]["token"].as_str().unwrap().to_string();
+        let (status, _) = send(
+            &app,
+            Method::GET,
+            "/api/users/2/logins",
+            Some(&token),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let body = serde_json::json!({ "code": current_code(&secret) });
+        let (status, _) = send(&app, Method::DELETE, "/api/auth/2fa", Some(&token), body).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_two_factor_locks_after_invalid_codes() {
+        let users = Arc::new(InMemoryUserRepository::with_users([user(1, "Ana", "user")]));
+        let tenant = crate::tenant::TenantContext::default_tenant();
+        let secret = totp::generate_secret();
+        users
+            .set_two_factor_secret(&tenant, 1, Some(&secret))
+            .await
+            .unwrap();
+        users.enable_two_factor(&tenant, 1, &[]).await.unwrap();
+        let hash = crate::auth::password::hash("password123").unwrap();
+        users.set_password_hash(&tenant, 1, &hash).await.unwrap();
+
+        let mut config = AppConfig::default();
+        config.auth.two_factor.max_failed_attempts = 3;
+        let state = AppState::new(users.clone(), Default::default())
+            .with_two_factor(config.auth.two_factor.clone());
+        let app = build_stack(create_router(state), &config);
+        let login = |code: String| {
+            let body = serde_json::json!({
+                "email": "ana@example.com",
+                "password": "password123",
+                "code": code
+            });
+            send(&app, Method::POST, "/api/auth/login", None, body)
+        };
+
+        // Um acerto zera a contagem
+        for _ in 0..2 {
+            assert_eq!(
+                login("000000".to_string()).await.0,
+                StatusCode::UNAUTHORIZED
+            );
+        }
+        assert_eq!(login(current_code(&secret)).await.0, StatusCode::OK);
+        let two_factor = users.two_factor(&tenant, 1).await.unwrap().unwrap();
+        assert_eq!(two_factor.failed_attempts, 0);
+
+        for _ in 0..3 {
+            assert_eq!(
+                login("000000".to_string()).await.0,
+                StatusCode::UNAUTHORIZED
+            );
+        }
+        // Bloqueado: nem o código certo passa
+        let (status, body) = login(next_code(&secret)).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            body["error"],
+            "Too many invalid two-factor codes, try again later"
+        );
+        let two_factor = users.two_factor(&tenant, 1).await.unwrap().unwrap();
+        assert!(two_factor.locked_at(Utc::now()).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_admin_impersonation() {
+        let mut config = AppConfig::default();
+        config.features.admin_token = Some("s3cret".to_string());
+        config.auth.token_secret = Some("segredo".to_string());
+        let state = AppState::new(Arc::new(InMemoryUserRepository::new()), Default::default())
+            .with_tokens(crate::auth::TokenSigner::from_config(&config.auth));
+        let audit = state.audit.clone();
+        let app = build_stack(create_router(state), &config);
+
+        let user = serde_json::json!({ "name": "ana", "email": "ana@example.com" });
+        let (status, _) = send(&app, Method::POST, "/api/users", None, user).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let impersonate = |uri: &'static str, token: Option<String>| {
+            let app = app.clone();
+            async move {
+                send(
+                    &app,
+                    Method::POST,
+                    uri,
+                    token.as_deref(),
+                    serde_json::json!({}),
+                )
+                .await
+            }
+        };
+        let uri = "/api/admin/impersonate/1";
+        assert_eq!(impersonate(uri, None).await.0, StatusCode::UNAUTHORIZED);
+        let missing = impersonate("/api/admin/impersonate/99", Some("s3cret".to_string()));
+        assert_eq!(missing.await.0, StatusCode::NOT_FOUND);
+
+        let (status, body) = impersonate(uri, Some("s3cret".to_string())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["impersonated_by"], "admin");
+        assert_eq!(body["data"]["user"]["id"], 1);
+        let expires_at: DateTime<Utc> =
+            serde_json::from_value(body["data"]["expires_at"].clone()).unwrap();
+        assert!(expires_at <= Utc::now() + chrono::Duration::minutes(15));
+        let token = body["data"]["token"].as_str().unwrap().to_string();
+
+        // Age como o usuário, com a marca na resposta
+        let request = Request::put("/api/users/1/password")
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"password": "password123"}"#))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[crate::api::middleware::IMPERSONATING_HEADER],
+            "admin as user:default/1"
+        );
+
+        // Mas não impersona outros nem mexe no 2FA
+        assert_eq!(
+            impersonate(uri, Some(token.clone())).await.0,
+            StatusCode::FORBIDDEN
+        );
+        let (status, _) = send(
+            &app,
+            Method::POST,
+            "/api/auth/2fa/setup",
+            Some(&token),
+            serde_json::json!({}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let entries = audit.store.recent(10).await.unwrap();
+        let actors: Vec<_> = entries
+            .iter()
+            .map(|e| (e.actor.as_str(), e.path.as_str()))
+            .collect();
+        assert!(actors.contains(&("admin", uri)));
+        assert!(actors.contains(&("admin as user:default/1", "/api/users/1/password")));
+    }
+}
@@ -0,0 +1,288 @@
+//! Log dos corpos JSON das requisições e respostas (`logging.log_bodies`)
+//!
+//! Para depurar integrações de clientes: registra o corpo das requisições e
+//! das respostas com `Content-Type` JSON depois de trocar por `[REDACTED]`
+//! os valores das chaves de `logging.redact_fields` e de `SECRET_FIELDS` e
+//! de mascarar as strings com cara de email ou telefone (ver `pii::mask`).
+//! Cada corpo é cortado em `logging.log_body_max_bytes`.
+//!
+//! Só são lidos os corpos de tamanho conhecido (`Content-Length` ou resposta
+//! já montada) de até `MAX_BUFFERED_BYTES`; uploads e respostas em stream
+//! passam sem ser tocados.
+
+use crate::api::{ApiError, ApiResponse};
+use crate::config::LoggingConfig;
+use crate::logging::redact::REDACTED;
+use crate::pii::{self, Masked};
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::{MatchedPath, State},
+    http::{header, HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Maior corpo lido para o log
+const MAX_BUFFERED_BYTES: u64 = 1024 * 1024;
+
+/// Chaves de credenciais trocadas por `[REDACTED]` mesmo fora de
+/// `redact_fields`: senhas, tokens, o segredo e a URI do TOTP, os códigos de
+/// 2FA e de recuperação e os segredos dos webhooks
+pub const SECRET_FIELDS: &[&str] = &[
+    "password",
+    "token",
+    "secret",
+    "otpauth_uri",
+    "backup_codes",
+    "code",
+];
+
+/// Estado do `log_bodies`
+#[derive(Debug, Clone)]
+pub struct BodyLogger {
+    redact_fields: Vec<String>,
+    max_bytes: usize,
+}
+
+impl BodyLogger {
+    pub fn new(redact_fields: &[String], max_bytes: usize) -> Self {
+        Self {
+            redact_fields: redact_fields.to_vec(),
+            max_bytes,
+        }
+    }
+
+    /// `None` com `logging.log_bodies` desligado
+    pub fn from_config(config: &LoggingConfig) -> Option<Self> {
+        config.log_bodies.then(|| {
+            Self::new(
+                &config.redact_fields,
+                usize::try_from(config.log_body_max_bytes).unwrap_or(usize::MAX),
+            )
+        })
+    }
+
+    /// Corpo como sai no log: JSON compacto, sem os dados sensíveis e cortado
+    /// em `max_bytes`
+    pub fn render(&self, body: &[u8]) -> String {
+        let rendered = match serde_json::from_slice::<Value>(body) {
+            Ok(mut value) => {
+                self.redact(&mut value);
+                value.to_string()
+            }
+            Err(_) => format!("<invalid JSON, {} bytes>", body.len()),
+        };
+        truncate(rendered, self.max_bytes)
+    }
+
+    fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    let redacted = SECRET_FIELDS
+                        .iter()
+                        .copied()
+                        .chain(self.redact_fields.iter().map(String::as_str))
+                        .any(|field| key.eq_ignore_ascii_case(field));
+                    if redacted {
+                        *value = Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact(value);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|value| self.redact(value)),
+            Value::String(text) if text.contains('@') || pii::looks_like_phone(text) => {
+                *text = pii::mask(text);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Corta em `max_bytes`, sem partir um caractere, marcando o corte com `…`
+fn truncate(mut text: String, max_bytes: usize) -> String {
+    if text.len() > max_bytes {
+        let mut end = max_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push('…');
+    }
+    text
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase())
+        .is_some_and(|mime| mime == "application/json" || mime.ends_with("+json"))
+}
+
+/// Se o corpo é JSON e pequeno o bastante para ser lido
+fn is_loggable(headers: &HeaderMap, body: &Body) -> bool {
+    is_json(headers)
+        && body
+            .size_hint()
+            .upper()
+            .is_some_and(|size| size <= MAX_BUFFERED_BYTES)
+}
+
+/// Middleware que registra os corpos JSON (ver o módulo)
+pub async fn log_bodies(
+    State(logger): State<Arc<BodyLogger>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let method = req.method().clone();
+    let route = match req.extensions().get::<MatchedPath>() {
+        Some(route) => route.as_str().to_owned(),
+        None => Masked(req.uri().path()).to_string(),
+    };
+
+    let req = if is_loggable(req.headers(), req.body()) {
+        let (parts, body) = req.into_parts();
+        let body: Bytes = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(_) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::<()>::error("Failed to read request body")),
+                )
+                    .into_response()
+            }
+        };
+        info!(
+            method = %method,
+            route = %route,
+            body = %logger.render(&body),
+            "Request body"
+        );
+        Request::from_parts(parts, Body::from(body))
+    } else {
+        req
+    };
+
+    let response = next.run(req).await;
+    if !is_loggable(response.headers(), response.body()) {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(error = %e, "Failed to buffer response body for logging");
+            return ApiError::InternalError("Failed to read response".to_string()).into_response();
+        }
+    };
+    info!(
+        method = %method,
+        route = %route,
+        status = %parts.status,
+        body = %logger.render(&body),
+        "Response body"
+    );
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware::from_fn_with_state, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn logger(max_bytes: usize) -> BodyLogger {
+        BodyLogger::new(&["password".to_string(), "Token".to_string()], max_bytes)
+    }
+
+    #[test]
+    fn test_render_redacts_and_truncates() {
+        let body = br#"{"name":"Ana","email":"ana@example.com","phone":"+55 11 98765-4321",
+            "credentials":[{"PASSWORD":"s3cret","token":{"value":"abc"}}],"age":30}"#;
+        let rendered: Value = serde_json::from_str(&logger(4096).render(body)).unwrap();
+        assert_eq!(
+            rendered,
+            serde_json::json!({
+                "name": "Ana",
+                "email": "a***@e***.com",
+                "phone": "+** ** *****-4321",
+                "credentials": [{ "PASSWORD": "[REDACTED]", "token": "[REDACTED]" }],
+                "age": 30
+            })
+        );
+
+        assert_eq!(logger(4096).render(b"not json"), "<invalid JSON, 8 bytes>");
+        assert_eq!(
+            logger(8).render(r#"{"name":"João"}"#.as_bytes()),
+            "{\"name\":…"
+        );
+        assert_eq!(truncate("ação".to_string(), 2), "a…");
+    }
+
+    #[test]
+    fn test_render_always_redacts_credentials() {
+        // Mesmo sem nada em `redact_fields`
+        let logger = BodyLogger::new(&[], 4096);
+        let setup = br#"{"success":true,"data":{"secret":"JBSWY3DPEHPK3PXP",
+            "otpauth_uri":"otpauth://totp/app:ana?secret=JBSWY3DPEHPK3PXP"}}"#;
+        let enable = br#"{"data":{"backup_codes":["a1b2c3","d4e5f6"]}}"#;
+        let login = br#"{"password":"s3cret","code":"123456"}"#;
+        let webhook = br#"{"url":"https://example.com/hook","secret":"whsec"}"#;
+
+        let rendered =
+            |body: &[u8]| -> Value { serde_json::from_str(&logger.render(body)).unwrap() };
+        assert_eq!(
+            rendered(setup)["data"],
+            serde_json::json!({ "secret": "[REDACTED]", "otpauth_uri": "[REDACTED]" })
+        );
+        assert_eq!(rendered(enable)["data"]["backup_codes"], "[REDACTED]");
+        assert_eq!(
+            rendered(login),
+            serde_json::json!({ "password": "[REDACTED]", "code": "[REDACTED]" })
+        );
+        assert_eq!(rendered(webhook)["secret"], "[REDACTED]");
+        assert_eq!(rendered(webhook)["url"], "https://example.com/hook");
+    }
+
+    #[tokio::test]
+    async fn test_log_bodies_keeps_bodies_intact() {
+        let echo = |headers: HeaderMap, body: String| async move {
+            (
+                [(header::CONTENT_TYPE, headers[header::CONTENT_TYPE].clone())],
+                body,
+            )
+        };
+        let app = Router::new()
+            .route("/echo", post(echo))
+            .layer(from_fn_with_state(Arc::new(logger(16)), log_bodies));
+        let request = |content_type: &str, body: &'static str| {
+            Request::post("/echo")
+                .header(header::CONTENT_TYPE, content_type)
+                .header(header::CONTENT_LENGTH, body.len())
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let body = r#"{"password":"s3cret","items":[1,2,3,4,5,6,7,8,9]}"#;
+        for content_type in ["application/json; charset=utf-8", "text/plain"] {
+            let response = app
+                .clone()
+                .oneshot(request(content_type, body))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert_eq!(bytes, body.as_bytes());
+        }
+    }
+}
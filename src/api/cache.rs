@@ -0,0 +1,467 @@
+//! Cache de respostas dos endpoints de leitura
+//!
+//! Opcional (seção `[cache]`, desligada por padrão): `GET`s sob os prefixos
+//! configurados são guardados em memória por rota, query e escopo (o
+//! `Principal` do `authenticate`, `Authorization`, `Accept` e
+//! `X-Tenant-Id`), cada prefixo com seu TTL. O cache de um
+//! prefixo é descartado quando uma escrita sob ele dá certo e quando o
+//! barramento publica um evento que o afeta (inclusive os vindos de outras
+//! instâncias), então a espera pelo TTL só vale para mudanças feitas por
+//! fora da aplicação.
+
+use crate::audit::Principal;
+use crate::config::CacheConfig;
+use crate::events::{DomainEvent, EventBus};
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, HeaderName, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+
+/// Header que indica se a resposta veio do cache (`HIT`) ou não (`MISS`)
+pub const CACHE_STATUS_HEADER: &str = "x-cache";
+
+/// Maior resposta guardada no cache
+const MAX_CACHED_BODY_BYTES: usize = 1024 * 1024;
+
+/// Prefixos de rota afetados por um evento de domínio
+pub fn invalidated_prefixes(event: &DomainEvent) -> &'static [&'static str] {
+    match event {
+        DomainEvent::UserCreated(_)
+        | DomainEvent::UserUpdated(_)
+        | DomainEvent::UserDeleted { .. } => &["/api/users"],
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    path_and_query: String,
+    scope: String,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    content_type: Option<HeaderValue>,
+    body: Bytes,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    map: HashMap<CacheKey, Entry>,
+    /// Incrementada a cada invalidação; respostas obtidas antes dela não
+    /// entram no cache
+    generation: u64,
+}
+
+/// Cache de respostas em memória, compartilhado entre as cópias
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    /// Prefixos e TTLs, do mais longo ao mais curto
+    routes: Arc<[(Arc<str>, Duration)]>,
+    max_entries: usize,
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl ResponseCache {
+    pub fn new(config: &CacheConfig) -> Self {
+        let mut routes: Vec<(Arc<str>, Duration)> = config
+            .routes
+            .iter()
+            .map(|route| {
+                (
+                    Arc::from(route.prefix.trim_end_matches('/')),
+                    Duration::from_secs(route.ttl_seconds),
+                )
+            })
+            .collect();
+        routes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+
+        Self {
+            routes: routes.into(),
+            max_entries: config.max_entries,
+            entries: Arc::default(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Prefixo configurado (o mais longo) que cobre o caminho, com seu TTL
+    fn route_for(&self, path: &str) -> Option<&(Arc<str>, Duration)> {
+        self.routes.iter().find(|(prefix, _)| {
+            path.strip_prefix(prefix.as_ref())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    /// Descarta as respostas guardadas sob o prefixo; devolve quantas
+    pub fn invalidate_prefix(&self, prefix: &str) -> usize {
+        let prefix = prefix.trim_end_matches('/');
+        let mut entries = self.lock();
+        entries.generation += 1;
+        let before = entries.map.len();
+        entries.map.retain(|key, _| {
+            !key.path_and_query.strip_prefix(prefix).is_some_and(|rest| {
+                rest.is_empty() || rest.starts_with('/') || rest.starts_with('?')
+            })
+        });
+        before - entries.map.len()
+    }
+
+    /// Quantidade de respostas guardadas (inclusive expiradas ainda não
+    /// descartadas)
+    pub fn len(&self) -> usize {
+        self.lock().map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Invalida o cache conforme os eventos publicados no barramento
+    pub fn spawn_invalidation(&self, bus: &EventBus) -> tokio::task::JoinHandle<()> {
+        let cache = self.clone();
+        let mut receiver = bus.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        for prefix in invalidated_prefixes(&event.payload) {
+                            cache.invalidate_prefix(prefix);
+                        }
+                    }
+                    // Eventos perdidos: não dá para saber o que mudou
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Response cache lagged behind the event bus");
+                        cache.invalidate_prefix("");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<Entry> {
+        let mut entries = self.lock();
+        match entries.map.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.clone()),
+            Some(_) => {
+                entries.map.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    fn insert(&self, key: CacheKey, entry: Entry, generation: u64) {
+        let mut entries = self.lock();
+        if entries.generation != generation {
+            return;
+        }
+        if entries.map.len() >= self.max_entries {
+            let now = Instant::now();
+            entries.map.retain(|_, entry| entry.expires_at > now);
+            if entries.map.len() >= self.max_entries {
+                return;
+            }
+        }
+        entries.map.insert(key, entry);
+    }
+}
+
+/// Escopo da resposta: quem pede (o `Principal` e `Authorization`), em que
+/// formato (`Accept`) e para qual tenant (`X-Tenant-Id`)
+///
+/// O `Principal` cobre quem se autentica sem `Authorization`, como as
+/// sessões por cookie: sem ele, a resposta de um admin logado seria servida
+/// às requisições anônimas.
+fn scope(req: &Request<Body>) -> String {
+    let mut hasher = Sha256::new();
+    let principal = req
+        .extensions()
+        .get::<Principal>()
+        .cloned()
+        .unwrap_or_default();
+    hasher.update(principal.actor().as_bytes());
+    hasher.update(b"\n");
+    for name in [
+        header::AUTHORIZATION,
+        header::ACCEPT,
+        HeaderName::from_static(crate::tenant::TENANT_HEADER),
+    ] {
+        if let Some(value) = req.headers().get(&name) {
+            hasher.update(value.as_bytes());
+        }
+        hasher.update(b"\n");
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Middleware do cache de respostas
+///
+/// `GET`s com `Cache-Control: no-cache` ignoram o que está guardado (mas
+/// renovam o cache); só respostas 200 são guardadas.
+pub async fn cache_responses(
+    State(cache): State<ResponseCache>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some((prefix, ttl)) = cache.route_for(req.uri().path()).cloned() else {
+        return next.run(req).await;
+    };
+
+    if req.method() != Method::GET {
+        let response = next.run(req).await;
+        if response.status().is_success() {
+            cache.invalidate_prefix(&prefix);
+        }
+        return response;
+    }
+
+    let key = CacheKey {
+        path_and_query: req
+            .uri()
+            .path_and_query()
+            .map_or_else(|| req.uri().path().to_string(), |p| p.to_string()),
+        scope: scope(&req),
+    };
+    let no_cache = req
+        .headers()
+        .get(header::CACHE_CONTROL)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("no-cache"));
+
+    if !no_cache {
+        if let Some(entry) = cache.get(&key) {
+            let mut response = (StatusCode::OK, entry.body).into_response();
+            let headers = response.headers_mut();
+            headers.remove(header::CONTENT_TYPE);
+            if let Some(content_type) = entry.content_type {
+                headers.insert(header::CONTENT_TYPE, content_type);
+            }
+            headers.insert(CACHE_STATUS_HEADER, HeaderValue::from_static("HIT"));
+            return response;
+        }
+    }
+
+    let generation = cache.generation();
+    let response = next.run(req).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_CACHED_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to buffer response for the cache");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    cache.insert(
+        key,
+        Entry {
+            content_type: parts.headers.get(header::CONTENT_TYPE).cloned(),
+            body: body.clone(),
+            expires_at: Instant::now() + ttl,
+        },
+        generation,
+    );
+    parts
+        .headers
+        .insert(CACHE_STATUS_HEADER, HeaderValue::from_static("MISS"));
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CacheRoute;
+    use axum::{routing::get, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    fn cache() -> ResponseCache {
+        ResponseCache::new(&CacheConfig {
+            enabled: true,
+            routes: vec![CacheRoute {
+                prefix: "/api/users".to_string(),
+                ttl_seconds: 60,
+            }],
+            max_entries: 100,
+        })
+    }
+
+    fn app(cache: ResponseCache, calls: Arc<AtomicUsize>) -> Router {
+        let handler = move || {
+            let calls = calls.clone();
+            async move { format!("call {}", calls.fetch_add(1, Ordering::SeqCst) + 1) }
+        };
+        Router::new()
+            .route(
+                "/api/users",
+                get(handler.clone()).post(|| async { "created" }),
+            )
+            .route("/api/other", get(handler))
+            .route_layer(axum::middleware::from_fn_with_state(cache, cache_responses))
+    }
+
+    async fn send(app: &Router, req: Request<Body>) -> (Option<String>, String) {
+        let response = app.clone().oneshot(req).await.unwrap();
+        let status = response
+            .headers()
+            .get(CACHE_STATUS_HEADER)
+            .map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn get_req(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hit_miss_and_scope() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(cache(), calls.clone());
+
+        assert_eq!(
+            send(&app, get_req("/api/users")).await,
+            (Some("MISS".into()), "call 1".into())
+        );
+        assert_eq!(
+            send(&app, get_req("/api/users")).await,
+            (Some("HIT".into()), "call 1".into())
+        );
+
+        // Outra query e outro escopo não compartilham a resposta
+        assert_eq!(send(&app, get_req("/api/users?page=2")).await.1, "call 2");
+        let authorized = Request::get("/api/users")
+            .header(header::AUTHORIZATION, "Bearer abc")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(&app, authorized).await.1, "call 3");
+        let other_tenant = Request::get("/api/users")
+            .header(crate::tenant::TENANT_HEADER, "acme")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(&app, other_tenant).await.1, "call 4");
+
+        // `no-cache` renova a resposta guardada
+        let fresh = Request::get("/api/users")
+            .header(header::CACHE_CONTROL, "no-cache")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(
+            send(&app, fresh).await,
+            (Some("MISS".into()), "call 5".into())
+        );
+        assert_eq!(send(&app, get_req("/api/users")).await.1, "call 5");
+
+        // Rotas fora dos prefixos configurados não passam pelo cache
+        assert_eq!(
+            send(&app, get_req("/api/other")).await,
+            (None, "call 6".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_principal_is_part_of_the_scope() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        // Como a sessão por cookie: `Principal` sem header `Authorization`
+        let app = app(cache(), calls.clone()).layer(axum::middleware::from_fn(
+            |mut req: Request<Body>, next: Next| async move {
+                if req.headers().contains_key(header::COOKIE) {
+                    req.extensions_mut().insert(Principal::Admin);
+                }
+                next.run(req).await
+            },
+        ));
+
+        let admin = Request::get("/api/users")
+            .header(header::COOKIE, "session=abc")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(
+            send(&app, admin).await,
+            (Some("MISS".into()), "call 1".into())
+        );
+        assert_eq!(
+            send(&app, get_req("/api/users")).await,
+            (Some("MISS".into()), "call 2".into())
+        );
+        assert_eq!(
+            send(&app, get_req("/api/users")).await,
+            (Some("HIT".into()), "call 2".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_writes_invalidate_prefix() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let cache = cache();
+        let app = app(cache.clone(), calls.clone());
+
+        send(&app, get_req("/api/users")).await;
+        send(&app, get_req("/api/users?page=2")).await;
+        assert_eq!(cache.len(), 2);
+
+        send(
+            &app,
+            Request::post("/api/users").body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert!(cache.is_empty());
+        assert_eq!(send(&app, get_req("/api/users")).await.1, "call 3");
+    }
+
+    #[tokio::test]
+    async fn test_events_invalidate_cache() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let cache = cache();
+        let app = app(cache.clone(), calls.clone());
+        let bus = EventBus::default();
+        cache.spawn_invalidation(&bus);
+
+        send(&app, get_req("/api/users")).await;
+        assert_eq!(cache.len(), 1);
+
+        bus.publish(
+            &crate::tenant::TenantContext::default_tenant(),
+            DomainEvent::UserDeleted { id: 1 },
+        );
+        for _ in 0..100 {
+            if cache.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_route_matching() {
+        let cache = cache();
+        assert!(cache.route_for("/api/users").is_some());
+        assert!(cache.route_for("/api/users/7").is_some());
+        assert!(cache.route_for("/api/usersx").is_none());
+        assert!(cache.route_for("/health").is_none());
+    }
+}
@@ -0,0 +1,170 @@
+//! IP do cliente atrás de proxies reversos
+//!
+//! O IP da conexão (`ConnectInfo`) é o do cliente, a menos que seja de um
+//! proxy em `server.trusted_proxies`. Nesse caso vale o `Forwarded` (RFC
+//! 7239) ou, sem ele, o `X-Forwarded-For`: os saltos são lidos da direita
+//! para a esquerda, pulando os proxies confiáveis, e o primeiro que não é
+//! um deles é o cliente. Entradas à esquerda dele podem ter sido forjadas
+//! pelo próprio cliente e são ignoradas.
+
+use crate::config::IpRange;
+use axum::http::{header, HeaderMap};
+use std::net::IpAddr;
+
+/// IP do cliente, dado o IP da conexão (`peer`); `None` sem conexão
+/// conhecida (ex.: testes com `oneshot`)
+pub fn client_ip(headers: &HeaderMap, peer: Option<IpAddr>, trusted: &[IpRange]) -> Option<IpAddr> {
+    let mut client = peer?.to_canonical();
+    let is_trusted = |ip: IpAddr| trusted.iter().any(|range| range.contains(ip));
+    if !is_trusted(client) {
+        return Some(client);
+    }
+
+    for hop in forwarded_hops(headers).into_iter().rev() {
+        // Um salto ilegível ("unknown", nome ofuscado) encerra a busca no
+        // último proxy que o informou
+        let Some(ip) = hop else { break };
+        client = ip;
+        if !is_trusted(ip) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+/// Saltos informados pelos proxies, do cliente original ao mais próximo
+fn forwarded_hops(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let values = |name: &str| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|element| !element.is_empty())
+            .collect::<Vec<_>>()
+    };
+
+    let forwarded = values(header::FORWARDED.as_str());
+    if !forwarded.is_empty() {
+        return forwarded
+            .into_iter()
+            .map(|element| {
+                element
+                    .split(';')
+                    .filter_map(|pair| pair.trim().split_once('='))
+                    .find(|(name, _)| name.eq_ignore_ascii_case("for"))
+                    .and_then(|(_, node)| parse_node(node))
+            })
+            .collect();
+    }
+    values("x-forwarded-for")
+        .into_iter()
+        .map(parse_node)
+        .collect()
+}
+
+/// `1.2.3.4`, `"1.2.3.4:80"`, `"[2001:db8::1]:443"` ou `2001:db8::1`
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split_once(']')?.0.parse().ok();
+    }
+    if let Ok(ip) = node.parse::<IpAddr>() {
+        return Some(ip.to_canonical());
+    }
+    // IPv4 com porta
+    let (ip, _port) = node.rsplit_once(':')?;
+    ip.parse::<std::net::Ipv4Addr>().ok().map(IpAddr::V4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let trusted: Vec<IpRange> = vec!["10.0.0.0/8".parse().unwrap()];
+        let xff = headers(&[("x-forwarded-for", "6.6.6.6, 1.2.3.4, 10.0.0.7")]);
+
+        // Sem proxy confiável na conexão, os headers não valem
+        assert_eq!(
+            client_ip(&xff, Some(ip("5.5.5.5")), &trusted),
+            Some(ip("5.5.5.5"))
+        );
+        assert_eq!(
+            client_ip(&xff, Some(ip("10.0.0.1")), &[]),
+            Some(ip("10.0.0.1"))
+        );
+        assert_eq!(client_ip(&xff, None, &trusted), None);
+
+        // Pula os proxies confiáveis e ignora o que o cliente forjou à esquerda
+        assert_eq!(
+            client_ip(&xff, Some(ip("10.0.0.1")), &trusted),
+            Some(ip("1.2.3.4"))
+        );
+        assert_eq!(
+            client_ip(&xff, Some(ip("::ffff:10.0.0.1")), &trusted),
+            Some(ip("1.2.3.4"))
+        );
+
+        // Só proxies: fica o mais distante
+        let internal = headers(&[("x-forwarded-for", "10.0.0.9")]);
+        assert_eq!(
+            client_ip(&internal, Some(ip("10.0.0.1")), &trusted),
+            Some(ip("10.0.0.9"))
+        );
+        assert_eq!(
+            client_ip(&HeaderMap::new(), Some(ip("10.0.0.1")), &trusted),
+            Some(ip("10.0.0.1"))
+        );
+
+        // Vários headers são lidos em sequência
+        let split = headers(&[
+            ("x-forwarded-for", "1.2.3.4"),
+            ("x-forwarded-for", "10.0.0.7"),
+        ]);
+        assert_eq!(
+            client_ip(&split, Some(ip("10.0.0.1")), &trusted),
+            Some(ip("1.2.3.4"))
+        );
+    }
+
+    #[test]
+    fn test_forwarded_header() {
+        let trusted: Vec<IpRange> = vec!["10.0.0.0/8".parse().unwrap()];
+        let peer = Some(ip("10.0.0.1"));
+
+        // `Forwarded` tem precedência sobre o `X-Forwarded-For`
+        let forwarded = headers(&[
+            (
+                "forwarded",
+                "for=6.6.6.6, for=\"[2001:db8::1]:4711\";proto=https, For=\"10.0.0.7:80\"",
+            ),
+            ("x-forwarded-for", "9.9.9.9"),
+        ]);
+        assert_eq!(
+            client_ip(&forwarded, peer, &trusted),
+            Some(ip("2001:db8::1"))
+        );
+
+        // Salto ofuscado: fica o proxy que o informou
+        let hidden = headers(&[("forwarded", "for=1.2.3.4, for=_hidden, for=10.0.0.7")]);
+        assert_eq!(client_ip(&hidden, peer, &trusted), Some(ip("10.0.0.7")));
+
+        assert_eq!(parse_node("\"1.2.3.4:8080\""), Some(ip("1.2.3.4")));
+        assert_eq!(parse_node("unknown"), None);
+    }
+}
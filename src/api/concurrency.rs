@@ -0,0 +1,216 @@
+//! Limite de requisições simultâneas
+//!
+//! Cada requisição ocupa uma vaga do limite global (`concurrency.max_in_flight`)
+//! e, se o caminho cair num grupo de `concurrency.routes`, uma vaga do grupo,
+//! até a resposta ser devolvida. Sem vaga, a requisição não espera: recebe
+//! 503 com `Retry-After`. Assim um endpoint caro (como as exportações) fica
+//! restrito às próprias vagas e não esgota as do restante da API.
+//!
+//! As vagas são por instância, como o `RateLimiter` em memória.
+
+use crate::api::ApiResponse;
+use crate::config::ConcurrencyConfig;
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Estado do `limit_concurrency`
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit {
+    global: Option<Arc<Semaphore>>,
+    /// Prefixos e vagas dos grupos, do prefixo mais longo ao mais curto
+    routes: Vec<(String, Arc<Semaphore>)>,
+    retry_after_seconds: u64,
+}
+
+/// Vagas ocupadas por uma requisição, devolvidas quando dropadas
+#[derive(Debug)]
+pub struct Permits {
+    _route: Option<OwnedSemaphorePermit>,
+    _global: Option<OwnedSemaphorePermit>,
+}
+
+impl ConcurrencyLimit {
+    /// `None` quando não há nenhum limite configurado
+    pub fn from_config(config: &ConcurrencyConfig) -> Option<Self> {
+        if config.max_in_flight.is_none() && config.routes.is_empty() {
+            return None;
+        }
+        let mut routes: Vec<(String, Arc<Semaphore>)> = config
+            .routes
+            .iter()
+            .map(|route| {
+                (
+                    route.prefix.trim_end_matches('/').to_string(),
+                    Arc::new(Semaphore::new(route.max_in_flight)),
+                )
+            })
+            .collect();
+        routes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        Some(Self {
+            global: config
+                .max_in_flight
+                .map(|limit| Arc::new(Semaphore::new(limit))),
+            routes,
+            retry_after_seconds: config.retry_after_seconds,
+        })
+    }
+
+    /// Grupo do caminho, pelo prefixo mais longo
+    fn route_for(&self, path: &str) -> Option<&(String, Arc<Semaphore>)> {
+        self.routes.iter().find(|(prefix, _)| {
+            path.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    /// Ocupa as vagas de uma requisição ao caminho; `Err` com o limite
+    /// esgotado (o prefixo do grupo ou `"global"`)
+    pub fn try_acquire(&self, path: &str) -> Result<Permits, &str> {
+        let route = match self.route_for(path) {
+            Some((prefix, semaphore)) => Some(
+                semaphore
+                    .clone()
+                    .try_acquire_owned()
+                    .map_err(|_| prefix.as_str())?,
+            ),
+            None => None,
+        };
+        let global = match &self.global {
+            Some(semaphore) => Some(
+                semaphore
+                    .clone()
+                    .try_acquire_owned()
+                    .map_err(|_| "global")?,
+            ),
+            None => None,
+        };
+        Ok(Permits {
+            _route: route,
+            _global: global,
+        })
+    }
+}
+
+/// Middleware que recusa com 503 as requisições acima do limite
+pub async fn limit_concurrency(
+    State(limit): State<ConcurrencyLimit>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let permits = match limit.try_acquire(req.uri().path()) {
+        Ok(permits) => permits,
+        Err(group) => {
+            tracing::warn!(group, "Concurrency limit reached; rejecting request");
+            #[cfg(feature = "observability")]
+            metrics::counter!("http_concurrency_rejections_total", "group" => group.to_string())
+                .increment(1);
+            let mut response = (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ApiResponse::<()>::error("Server is busy, try again later")),
+            )
+                .into_response();
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                HeaderValue::from(limit.retry_after_seconds.max(1)),
+            );
+            return response;
+        }
+    };
+
+    let response = next.run(req).await;
+    drop(permits);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RouteConcurrencyLimit;
+    use axum::{middleware::from_fn_with_state, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn limit(max_in_flight: Option<usize>) -> ConcurrencyLimit {
+        ConcurrencyLimit::from_config(&ConcurrencyConfig {
+            max_in_flight,
+            routes: vec![
+                RouteConcurrencyLimit {
+                    prefix: "/api/exports".to_string(),
+                    max_in_flight: 1,
+                },
+                RouteConcurrencyLimit {
+                    prefix: "/api/exports/bulk/".to_string(),
+                    max_in_flight: 2,
+                },
+            ],
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_try_acquire() {
+        assert!(ConcurrencyLimit::from_config(&ConcurrencyConfig::default()).is_none());
+
+        let limit = limit(Some(3));
+        let export = limit.try_acquire("/api/exports/1").unwrap();
+        assert_eq!(
+            limit.try_acquire("/api/exports").unwrap_err(),
+            "/api/exports"
+        );
+
+        // O grupo mais específico tem vagas próprias; `/api/exportsx` não é
+        // do grupo
+        let bulk = limit.try_acquire("/api/exports/bulk/9").unwrap();
+        let other = limit.try_acquire("/api/exportsx").unwrap();
+        assert_eq!(limit.try_acquire("/api/users").unwrap_err(), "global");
+
+        drop((export, bulk, other));
+        assert!(limit.try_acquire("/api/exports").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_limit_concurrency_rejects_with_retry_after() {
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel::<()>();
+        let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+        let slow = Arc::new(std::sync::Mutex::new(Some((started_tx, release_rx))));
+        let app = Router::new()
+            .route(
+                "/api/exports",
+                get(move || {
+                    let channels = slow.lock().unwrap().take();
+                    async move {
+                        if let Some((started, release)) = channels {
+                            started.send(()).unwrap();
+                            release.await.unwrap();
+                        }
+                        "done"
+                    }
+                }),
+            )
+            .route("/api/users", get(|| async { "users" }))
+            .layer(from_fn_with_state(limit(None), limit_concurrency));
+        let request = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+
+        let in_flight = tokio::spawn(app.clone().oneshot(request("/api/exports")));
+        started_rx.await.unwrap();
+
+        let busy = app.clone().oneshot(request("/api/exports")).await.unwrap();
+        assert_eq!(busy.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(busy.headers()[header::RETRY_AFTER], "1");
+        let users = app.clone().oneshot(request("/api/users")).await.unwrap();
+        assert_eq!(users.status(), StatusCode::OK);
+
+        release_tx.send(()).unwrap();
+        assert_eq!(in_flight.await.unwrap().unwrap().status(), StatusCode::OK);
+        let again = app.oneshot(request("/api/exports")).await.unwrap();
+        assert_eq!(again.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,125 @@
+//! Stream dos eventos de domínio via Server-Sent Events
+//!
+//! `GET /api/events/stream` envia cada `Event` do tenant da requisição como
+//! JSON no campo `data` (o tipo vem em `type`), com o ID do evento no campo
+//! `id`.
+//! Comentários `: heartbeat` mantêm a conexão viva; ao reconectar, o
+//! `EventSource` manda `Last-Event-ID` e recebe os eventos perdidos que
+//! ainda estão no histórico do barramento.
+
+use crate::api::AppState;
+use crate::events::Event;
+use crate::tenant::TenantContext;
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::sse::{self, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Intervalo entre os comentários de heartbeat
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+const LAST_EVENT_ID: &str = "Last-Event-ID";
+
+/// Stream SSE dos eventos de domínio do tenant
+pub async fn stream(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<sse::Event, Infallible>>> {
+    let last_id = headers
+        .get(LAST_EVENT_ID)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+
+    let (missed, receiver) = match last_id {
+        Some(id) => state.events.subscribe_after(id),
+        None => (Vec::new(), state.events.subscribe()),
+    };
+
+    let live = stream::unfold(receiver, |mut receiver| async move {
+        match receiver.recv().await {
+            Ok(event) => Some((event, receiver)),
+            // Encerra o stream: o cliente reconecta com `Last-Event-ID` e
+            // recupera do histórico o que perdeu
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!(skipped, "SSE client lagged behind; closing stream");
+                None
+            }
+            Err(RecvError::Closed) => None,
+        }
+    });
+
+    let events = stream::iter(missed)
+        .chain(live)
+        .filter(move |event| std::future::ready(event.tenant == tenant.id()))
+        .map(|event| Ok(to_sse(&event)));
+
+    Sse::new(events).keep_alive(
+        KeepAlive::new()
+            .interval(HEARTBEAT_INTERVAL)
+            .text("heartbeat"),
+    )
+}
+
+fn to_sse(event: &Event) -> sse::Event {
+    sse::Event::default()
+        .id(event.id.to_string())
+        .json_data(event)
+        .expect("events are always serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{create_router, AppState};
+    use crate::events::DomainEvent;
+    use crate::repository::InMemoryUserRepository;
+    use crate::tenant::TenantContext;
+    use axum::{body::Body, http::Request};
+    use futures_util::StreamExt;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_stream_resumes_after_last_event_id() {
+        let state = AppState::new(Arc::new(InMemoryUserRepository::new()), Default::default());
+        let events = state.events.clone();
+        let acme = TenantContext::new("acme").unwrap();
+        let globex = TenantContext::new("globex").unwrap();
+        events.publish(&acme, DomainEvent::UserDeleted { id: 1 });
+        events.publish(&globex, DomainEvent::UserDeleted { id: 2 });
+        events.publish(&acme, DomainEvent::UserDeleted { id: 3 });
+
+        // O admin escolhe o tenant pelo header
+        let mut request = Request::get("/api/events/stream")
+            .header("Last-Event-ID", "1")
+            .header("X-Tenant-Id", "acme")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(crate::audit::Principal::Admin);
+        let response = create_router(state).oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        let mut body = response.into_body().into_data_stream();
+        let chunk = |bytes: axum::body::Bytes| String::from_utf8(bytes.to_vec()).unwrap();
+
+        // O evento 2 é de outro tenant
+        let replayed = chunk(body.next().await.unwrap().unwrap());
+        assert!(replayed.contains("id: 3\n"), "{}", replayed);
+        assert!(
+            replayed.contains(r#""type":"user.deleted""#),
+            "{}",
+            replayed
+        );
+
+        events.publish(&globex, DomainEvent::UserDeleted { id: 4 });
+        events.publish(&acme, DomainEvent::UserDeleted { id: 5 });
+        assert!(chunk(body.next().await.unwrap().unwrap()).contains("id: 5\n"));
+    }
+}
@@ -0,0 +1,272 @@
+//! Exportações assíncronas de usuários em CSV (`/api/exports`)
+//!
+//! `POST /api/exports` responde 202 com o job e o header `Location`;
+//! `GET /api/exports/:id` traz a situação e, quando concluída, a
+//! `download_url` de `GET /api/exports/:id/download`. `POST
+//! /api/exports/:id/share` copia o arquivo para o storage e devolve uma URL
+//! pré-assinada, que baixa o arquivo sem headers de autenticação.
+//!
+//! As exportações dos dados de um usuário (iniciadas por
+//! `GET /api/users/:id/export`) passam pelas mesmas rotas, mas só o próprio
+//! usuário ou o admin as enxergam.
+
+use crate::api::auth::authorize;
+use crate::api::{ApiError, ApiResponse, AppState};
+use crate::audit::Principal;
+use crate::exports::{ExportJob, ExportKind, ExportStatus};
+use crate::quotas::Resource;
+use crate::storage::PresignedUrl;
+use crate::tenant::TenantContext;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Validade padrão das URLs de `share`
+const DEFAULT_SHARE_EXPIRY: Duration = Duration::from_secs(60 * 60);
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/exports", post(create_export))
+        .route("/api/exports/:id", get(get_export))
+        .route("/api/exports/:id/download", get(download_export))
+        .route("/api/exports/:id/share", post(share_export))
+}
+
+/// Uma exportação como devolvida pela API
+#[derive(Debug, Serialize)]
+pub struct ExportResponse {
+    #[serde(flatten)]
+    pub job: ExportJob,
+    /// Presente quando a exportação foi concluída
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+}
+
+impl From<ExportJob> for ExportResponse {
+    fn from(job: ExportJob) -> Self {
+        let download_url = (job.status == ExportStatus::Completed)
+            .then(|| format!("/api/exports/{}/download", job.id));
+        Self { job, download_url }
+    }
+}
+
+fn not_found(id: Uuid) -> ApiError {
+    ApiError::NotFound(format!("Export {} not found", id))
+}
+
+/// A exportação `id` do tenant; a dos dados de um usuário só para ele e o
+/// admin
+fn find(
+    state: &AppState,
+    tenant: &TenantContext,
+    principal: &Principal,
+    id: Uuid,
+) -> Result<ExportJob, ApiError> {
+    let job = state.exports.get(tenant, id).ok_or_else(|| not_found(id))?;
+    if let ExportKind::User { user_id } = job.kind {
+        authorize(principal, tenant, user_id)?;
+    }
+    Ok(job)
+}
+
+/// Inicia a exportação dos usuários do tenant
+async fn create_export(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+) -> Result<impl IntoResponse, ApiError> {
+    state.quotas.consume(&tenant, Resource::Exports).await?;
+
+    let job = state.exports.start(state.users.clone(), tenant);
+    let location = format!("/api/exports/{}", job.id);
+
+    Ok((
+        StatusCode::ACCEPTED,
+        [(header::LOCATION, location)],
+        Json(ApiResponse::success(ExportResponse::from(job))),
+    ))
+}
+
+/// Situação de uma exportação
+async fn get_export(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ExportResponse>>, ApiError> {
+    let job = find(&state, &tenant, &principal, id)?;
+    Ok(Json(ApiResponse::success(job.into())))
+}
+
+/// O arquivo de uma exportação concluída
+async fn download_export(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let job = find(&state, &tenant, &principal, id)?;
+    let file = state
+        .exports
+        .download(&tenant, id)
+        .ok_or_else(|| ApiError::Conflict(format!("Export {} is not completed", id)))?;
+    let disposition = format!("attachment; filename=\"{}\"", job.kind.file_name(id));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, job.kind.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        file.to_vec(),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShareQuery {
+    /// Validade da URL em segundos (limitada por `storage.max_expiry_seconds`)
+    pub expires_in: Option<u64>,
+}
+
+/// URL pré-assinada do arquivo de uma exportação concluída
+async fn share_export(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ShareQuery>,
+) -> Result<Json<ApiResponse<PresignedUrl>>, ApiError> {
+    let job = find(&state, &tenant, &principal, id)?;
+    let file = state
+        .exports
+        .download(&tenant, id)
+        .ok_or_else(|| ApiError::Conflict(format!("Export {} is not completed", id)))?;
+
+    let key = format!("exports/{}.{}", id, job.kind.extension());
+    state
+        .storage
+        .put(&key, &file)
+        .await
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+    let expiry = query
+        .expires_in
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_SHARE_EXPIRY);
+    let presigned = state
+        .storage
+        .presign(&key, expiry)
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(presigned)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use crate::fixtures::Fixtures;
+    use crate::repository::InMemoryUserRepository;
+    use axum::{body::Body, http::Request};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    async fn send(app: &Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_export_lifecycle() {
+        let users = Fixtures::seeded(1).users(2);
+        let repo = Arc::new(InMemoryUserRepository::with_users(users.clone()));
+        let dir = std::env::temp_dir().join(format!("exports-test-{}", Uuid::new_v4()));
+        let app = create_router(
+            AppState::new(repo, Default::default())
+                .with_storage(crate::storage::Storage::new(&dir, b"segredo")),
+        );
+
+        let response = app
+            .clone()
+            .oneshot(Request::post("/api/exports").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let location = response.headers()[header::LOCATION]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let mut job = serde_json::Value::Null;
+        for _ in 0..100 {
+            let (status, body) =
+                send(&app, Request::get(&location).body(Body::empty()).unwrap()).await;
+            assert_eq!(status, StatusCode::OK);
+            job = body["data"].clone();
+            if job["status"] == "completed" {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(job["status"], "completed");
+        assert_eq!(job["rows"], 2);
+
+        let url = job["download_url"].as_str().unwrap();
+        let response = app
+            .clone()
+            .oneshot(Request::get(url).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/csv; charset=utf-8"
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let csv = String::from_utf8(bytes.to_vec()).unwrap();
+        assert!(csv.contains(&users[1].email));
+
+        // A URL compartilhada baixa o mesmo CSV, sem headers
+        let share = format!("{}/share?expires_in=60", location);
+        let (status, body) = send(&app, Request::post(share).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        let url = body["data"]["url"].as_str().unwrap();
+        assert!(url.starts_with("/files/exports/"));
+        let response = app
+            .clone()
+            .oneshot(Request::get(url).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8(bytes.to_vec()).unwrap(), csv);
+        std::fs::remove_dir_all(dir).unwrap();
+
+        // Outro tenant não enxerga a exportação
+        let mut request = Request::get(&location)
+            .header(crate::tenant::TENANT_HEADER, "other")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(crate::audit::Principal::Admin);
+        assert_eq!(send(&app, request).await.0, StatusCode::NOT_FOUND);
+
+        let request = Request::get(format!("/api/exports/{}", Uuid::new_v4()))
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(&app, request).await.0, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,122 @@
+//! Downloads por URL pré-assinada (`GET /files/*key`)
+//!
+//! Não exige autenticação: a assinatura de `Storage::presign` na query é a
+//! credencial. Assinatura ausente, inválida ou vencida recebe 403.
+
+use crate::api::{ApiError, AppState};
+use crate::storage;
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use chrono::Utc;
+use serde::Deserialize;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/files/*key", get(download))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PresignedQuery {
+    pub expires: Option<i64>,
+    pub signature: Option<String>,
+}
+
+async fn download(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    Query(query): Query<PresignedQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (Some(expires), Some(signature)) = (query.expires, query.signature) else {
+        return Err(ApiError::Forbidden("Missing signature".to_string()));
+    };
+    state
+        .storage
+        .verify(&key, expires, &signature, Utc::now())
+        .map_err(|e| ApiError::Forbidden(e.to_string()))?;
+
+    let bytes = state
+        .storage
+        .get(&key)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("File {} not found", key)))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, storage::content_type(&key)),
+            (header::CACHE_CONTROL, "private, no-store"),
+        ],
+        bytes,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use crate::repository::InMemoryUserRepository;
+    use crate::storage::Storage;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_presigned_download() {
+        let dir = std::env::temp_dir().join(format!("files-test-{}", uuid::Uuid::new_v4()));
+        let storage = Storage::new(&dir, b"segredo");
+        storage.put("avatars/7.png", b"png").await.unwrap();
+        let app = create_router(
+            AppState::new(Arc::new(InMemoryUserRepository::new()), Default::default())
+                .with_storage(storage.clone()),
+        );
+        let status = |uri: String| {
+            let app = app.clone();
+            async move {
+                app.oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap()
+                    .status()
+            }
+        };
+
+        let url = storage
+            .presign("avatars/7.png", Duration::from_secs(60))
+            .unwrap()
+            .url;
+        let response = app
+            .clone()
+            .oneshot(Request::get(&url).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&bytes[..], b"png");
+
+        assert_eq!(
+            status("/files/avatars/7.png".to_string()).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(url.replace("avatars/7.png", "avatars/8.png")).await,
+            StatusCode::FORBIDDEN
+        );
+        let missing = storage
+            .presign("avatars/8.png", Duration::from_secs(60))
+            .unwrap()
+            .url;
+        assert_eq!(status(missing).await, StatusCode::NOT_FOUND);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,98 +1,1105 @@
 //! Handlers da API para operações de usuários
+//!
+//! Operam sobre `AppState::users`, de modo que funcionam tanto com o
+//! Postgres quanto com o repositório em memória, sempre dentro do tenant da
+//! requisição (`TenantContext`, do header `X-Tenant-Id`). As respostas de
+//! sucesso saem no formato pedido no `Accept` (`Negotiated`).
 
-#[cfg(feature = "postgres")]
-pub use postgres_handlers::*;
+use crate::api::exports::ExportResponse;
+use crate::api::negotiate::{Encoding, Negotiated, Payload};
+use crate::api::pagination::Pagination;
+use crate::api::{ApiError, ApiResponse, AppState};
+use crate::audit::{AuditEntry, Principal};
+use crate::events::{DomainEvent, Event};
+use crate::exports::ExportKind;
+use crate::models::{DbUser, InvalidTransition, LoginEvent, StoredPasskey, UserField, UserStatus};
+use crate::quotas::Resource;
+use crate::repository::OrNotFound;
+use crate::tenant::TenantContext;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
 
-#[cfg(feature = "postgres")]
-mod postgres_handlers {
-    use crate::api::{ApiError, ApiResponse, AppState};
-    use crate::db::DbUser;
-    use axum::{
-        extract::{Path, State},
-        Json,
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateUserRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserResponse {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    #[serde(default)]
+    pub status: UserStatus,
+    /// `status == active`, mantido para os clientes antigos
+    pub active: bool,
+    /// Presente enquanto a exclusão da conta aguarda a carência
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deletion_scheduled_at: Option<DateTime<Utc>>,
+}
+
+impl From<DbUser> for UserResponse {
+    fn from(user: DbUser) -> Self {
+        Self {
+            id: user.id,
+            name: user.name,
+            email: user.email,
+            active: user.status == UserStatus::Active,
+            status: user.status,
+            deletion_scheduled_at: user.deletion_scheduled_at,
+        }
+    }
+}
+
+/// Campos pedidos em `?fields=id,name`
+#[derive(Debug, Default, Deserialize)]
+pub struct FieldsQuery {
+    pub fields: Option<String>,
+}
+
+/// Lista os usuários, uma página por vez (`?page=` e `?per_page=`)
+///
+/// Os totais vão em `meta` e os links das outras páginas no header `Link`.
+/// Com `?fields=`, cada usuário traz só os campos pedidos (e só eles são
+/// lidos do banco).
+pub async fn list_users(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    encoding: Encoding,
+    uri: Uri,
+    Query(pagination): Query<Pagination>,
+    Query(query): Query<FieldsQuery>,
+) -> Result<Response, ApiError> {
+    let fields = query
+        .fields
+        .map(|list| UserField::parse_list(&list))
+        .transpose()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let total = state
+        .users
+        .count(&tenant)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    let limit = i64::from(pagination.per_page());
+    let meta = pagination.meta(total);
+
+    let mut response = match fields {
+        Some(fields) => {
+            let users = state
+                .users
+                .list_page_fields(&tenant, &fields, limit, pagination.offset())
+                .await
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            Negotiated::new(encoding, ApiResponse::paginated(users, meta)).into_response()
+        }
+        None => {
+            let users = state
+                .users
+                .list_page(&tenant, limit, pagination.offset())
+                .await
+                .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+            let users: Vec<UserResponse> = users.into_iter().map(Into::into).collect();
+            Negotiated::new(encoding, ApiResponse::paginated(users, meta)).into_response()
+        }
+    };
+    if let Some(link) = meta.link_header(&uri) {
+        response.headers_mut().insert(header::LINK, link);
+    }
+    Ok(response)
+}
+
+/// Cria um novo usuário
+pub async fn create_user(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    encoding: Encoding,
+    Payload(payload): Payload<CreateUserRequest>,
+) -> Result<Negotiated<ApiResponse<UserResponse>>, ApiError> {
+    // Validar dados
+    payload.validate()?;
+
+    let quota = state
+        .quotas
+        .consume(&tenant, Resource::UsersCreated)
+        .await?;
+
+    // Criar usuário; se falhar, a cota consumida volta
+    let user = match state
+        .users
+        .create(&tenant, &payload.name, &payload.email)
+        .await
+    {
+        Ok(user) => user,
+        Err(e) => {
+            state.quotas.release(&tenant, &quota).await;
+            return Err(ApiError::DatabaseError(e.to_string()));
+        }
     };
-    use serde::{Deserialize, Serialize};
-    use validator::Validate;
 
-    #[derive(Debug, Deserialize, Validate)]
-    pub struct CreateUserRequest {
-        #[validate(length(min = 1, max = 255))]
-        pub name: String,
-        
-        #[validate(email)]
-        pub email: String,
+    state
+        .events
+        .publish(&tenant, DomainEvent::UserCreated(user.clone()));
+
+    Ok(Negotiated::new(encoding, ApiResponse::success(user.into())))
+}
+
+/// Busca um usuário por ID
+pub async fn get_user(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    encoding: Encoding,
+    Path(id): Path<i32>,
+) -> Result<Negotiated<ApiResponse<UserResponse>>, ApiError> {
+    let user = state.users.get(&tenant, id).await?;
+
+    Ok(Negotiated::new(encoding, ApiResponse::success(user.into())))
+}
+
+/// Deleta um usuário
+///
+/// Com carência (`AppState::deletion_grace`), só marca a conta: responde 202
+/// com a data da remoção, feita depois por `AppState::spawn_deletion_purge`
+/// e cancelável por `restore_user`. Sem carência, remove na hora. 404 se o
+/// usuário não existe.
+pub async fn delete_user(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    encoding: Encoding,
+    Path(id): Path<i32>,
+) -> Result<Response, ApiError> {
+    if !state.deletion_grace.is_zero() {
+        let at = Utc::now() + state.deletion_grace;
+        let user = state
+            .users
+            .schedule_deletion(&tenant, id, at)
+            .await
+            .or_not_found(id)?;
+
+        state
+            .events
+            .publish(&tenant, DomainEvent::UserUpdated(user.clone()));
+
+        let body = Negotiated::new(encoding, ApiResponse::success(UserResponse::from(user)));
+        return Ok((StatusCode::ACCEPTED, body).into_response());
     }
 
-    #[derive(Debug, Serialize)]
-    pub struct UserResponse {
-        pub id: i32,
-        pub name: String,
-        pub email: String,
-        pub active: bool,
+    state.users.delete(&tenant, id).await?;
+    state
+        .events
+        .publish(&tenant, DomainEvent::UserDeleted { id });
+
+    Ok(Negotiated::new(encoding, ApiResponse::success(())).into_response())
+}
+
+/// Cancela a exclusão de um usuário ainda dentro da carência
+pub async fn restore_user(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    encoding: Encoding,
+    Path(id): Path<i32>,
+) -> Result<Negotiated<ApiResponse<UserResponse>>, ApiError> {
+    let user = state.users.get(&tenant, id).await?;
+    if user.deletion_scheduled_at.is_none() {
+        return Err(ApiError::Conflict(format!(
+            "User with id {} is not scheduled for deletion",
+            id
+        )));
     }
 
-    impl From<DbUser> for UserResponse {
-        fn from(user: DbUser) -> Self {
-            Self {
-                id: user.id,
-                name: user.name,
-                email: user.email,
-                active: user.active,
+    let user = state
+        .users
+        .cancel_deletion(&tenant, id)
+        .await
+        .or_not_found(id)?;
+
+    state
+        .events
+        .publish(&tenant, DomainEvent::UserUpdated(user.clone()));
+
+    Ok(Negotiated::new(encoding, ApiResponse::success(user.into())))
+}
+
+/// Ativa uma conta pendente
+pub async fn activate_user(
+    state: State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    encoding: Encoding,
+    id: Path<i32>,
+) -> Result<Negotiated<ApiResponse<UserResponse>>, ApiError> {
+    change_status(state, tenant, principal, encoding, id, UserStatus::activate).await
+}
+
+/// Suspende uma conta ativa
+pub async fn suspend_user(
+    state: State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    encoding: Encoding,
+    id: Path<i32>,
+) -> Result<Negotiated<ApiResponse<UserResponse>>, ApiError> {
+    change_status(state, tenant, principal, encoding, id, UserStatus::suspend).await
+}
+
+/// Reativa uma conta suspensa ou desativada
+pub async fn reinstate_user(
+    state: State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    encoding: Encoding,
+    id: Path<i32>,
+) -> Result<Negotiated<ApiResponse<UserResponse>>, ApiError> {
+    change_status(
+        state,
+        tenant,
+        principal,
+        encoding,
+        id,
+        UserStatus::reinstate,
+    )
+    .await
+}
+
+/// Desativa uma conta
+pub async fn deactivate_user(
+    state: State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    encoding: Encoding,
+    id: Path<i32>,
+) -> Result<Negotiated<ApiResponse<UserResponse>>, ApiError> {
+    change_status(
+        state,
+        tenant,
+        principal,
+        encoding,
+        id,
+        UserStatus::deactivate,
+    )
+    .await
+}
+
+/// Bane uma conta (não há volta)
+pub async fn ban_user(
+    state: State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    encoding: Encoding,
+    id: Path<i32>,
+) -> Result<Negotiated<ApiResponse<UserResponse>>, ApiError> {
+    change_status(state, tenant, principal, encoding, id, UserStatus::ban).await
+}
+
+/// Aplica uma transição de `UserStatus`; só o admin, e 409 se ela não é
+/// permitida
+async fn change_status(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    encoding: Encoding,
+    Path(id): Path<i32>,
+    transition: fn(UserStatus) -> Result<UserStatus, InvalidTransition>,
+) -> Result<Negotiated<ApiResponse<UserResponse>>, ApiError> {
+    crate::api::auth::require_admin(&principal, "change a user's status")?;
+    let user = state.users.get(&tenant, id).await?;
+    let status = transition(user.status).map_err(|e| ApiError::Conflict(e.to_string()))?;
+
+    let user = DbUser { status, ..user };
+    state.users.update(&tenant, &user).await?;
+
+    state
+        .events
+        .publish(&tenant, DomainEvent::UserUpdated(user.clone()));
+
+    Ok(Negotiated::new(encoding, ApiResponse::success(user.into())))
+}
+
+/// Anonimiza um usuário (LGPD/GDPR): remove nome e email de forma
+/// irreversível, mantendo o ID para as referências e o histórico
+///
+/// Só o próprio usuário ou o admin.
+pub async fn anonymize_user(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    encoding: Encoding,
+    Path(id): Path<i32>,
+) -> Result<Negotiated<ApiResponse<UserResponse>>, ApiError> {
+    crate::api::auth::authorize(&principal, &tenant, id)?;
+    let user = state.users.anonymize(&tenant, id).await.or_not_found(id)?;
+
+    state
+        .events
+        .publish(&tenant, DomainEvent::UserUpdated(user.clone()));
+
+    Ok(Negotiated::new(encoding, ApiResponse::success(user.into())))
+}
+
+/// Tudo o que está guardado sobre um usuário (LGPD/GDPR)
+#[derive(Debug, Serialize)]
+pub struct UserExport {
+    pub exported_at: DateTime<Utc>,
+    /// O registro completo, como está no banco
+    pub user: DbUser,
+    /// Tentativas de login, da mais nova para a mais antiga
+    pub logins: Vec<LoginEvent>,
+    /// Passkeys cadastradas (sem a credencial)
+    pub passkeys: Vec<StoredPasskey>,
+    /// Requisições feitas pelo usuário ou sobre ele (log de auditoria)
+    pub audit: Vec<AuditEntry>,
+    /// Eventos gravados sobre o usuário (outbox); vazio sem Postgres
+    pub events: Vec<Event>,
+}
+
+/// Registros por lista (logins, auditoria, eventos) acima dos quais a
+/// exportação de um usuário deixa de ser montada na própria requisição
+pub const MAX_INLINE_EXPORT_ITEMS: i64 = 1_000;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportQuery {
+    /// `?async=true` gera a exportação em segundo plano mesmo que ela caiba
+    /// na resposta
+    #[serde(default, rename = "async")]
+    pub background: bool,
+}
+
+impl UserExport {
+    /// Registros das listas (logins, passkeys, auditoria e eventos)
+    fn records(&self) -> usize {
+        self.logins.len() + self.passkeys.len() + self.audit.len() + self.events.len()
+    }
+
+    /// Alguma lista passa de `limit` registros
+    fn exceeds(&self, limit: i64) -> bool {
+        let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+        [self.logins.len(), self.audit.len(), self.events.len()]
+            .into_iter()
+            .any(|len| len > limit)
+    }
+}
+
+/// Lê os dados do usuário, com até `limit` registros em cada lista
+async fn collect_export(
+    state: &AppState,
+    tenant: &TenantContext,
+    id: i32,
+    limit: i64,
+) -> Result<UserExport, ApiError> {
+    let user = state.users.get(tenant, id).await?;
+    let logins = state
+        .users
+        .logins(tenant, id, limit)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    let passkeys = state
+        .users
+        .passkeys(tenant, id)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    let audit = state
+        .audit
+        .store
+        .for_user(tenant, id, limit)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    #[cfg(feature = "postgres")]
+    let events = match &state.db {
+        Some(db) => crate::outbox::events_for_user(db.pool(), tenant, id, limit)
+            .await
+            .map_err(|e| ApiError::DatabaseError(e.to_string()))?,
+        None => Vec::new(),
+    };
+    #[cfg(not(feature = "postgres"))]
+    let events = {
+        let _ = limit;
+        Vec::new()
+    };
+
+    Ok(UserExport {
+        exported_at: Utc::now(),
+        user,
+        logins,
+        passkeys,
+        audit,
+        events,
+    })
+}
+
+/// Exporta os dados de um usuário como um arquivo JSON para download
+///
+/// Só o próprio usuário ou o admin. Contas com mais de
+/// `MAX_INLINE_EXPORT_ITEMS` registros numa lista (ou com `?async=true`)
+/// são exportadas em segundo plano: a resposta é 202 com o job de
+/// `/api/exports` no header `Location`, como em `POST /api/exports`.
+pub async fn export_user(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    Path(id): Path<i32>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, ApiError> {
+    crate::api::auth::authorize(&principal, &tenant, id)?;
+
+    if query.background {
+        // O 404 sai na hora, não no job
+        state.users.get(&tenant, id).await?;
+    } else {
+        let export = collect_export(&state, &tenant, id, MAX_INLINE_EXPORT_ITEMS + 1).await?;
+        if !export.exceeds(MAX_INLINE_EXPORT_ITEMS) {
+            let disposition = format!("attachment; filename=\"user-{}-export.json\"", id);
+            return Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(export)).into_response());
+        }
+    }
+
+    let job = state
+        .exports
+        .spawn(tenant.clone(), ExportKind::User { user_id: id }, {
+            let state = state.clone();
+            async move {
+                let export = collect_export(&state, &tenant, id, i64::MAX)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+                Ok((export.records(), serde_json::to_vec(&export)?))
             }
+        });
+    let location = format!("/api/exports/{}", job.id);
+
+    Ok((
+        StatusCode::ACCEPTED,
+        [(header::LOCATION, location)],
+        Json(ApiResponse::success(ExportResponse::from(job))),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use crate::fixtures::Fixtures;
+    use crate::repository::{InMemoryUserRepository, UserRepository};
+    use axum::{body::Body, http::Request, http::StatusCode};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn app(repo: Arc<InMemoryUserRepository>) -> axum::Router {
+        create_router(AppState::new(repo, Default::default()))
+    }
+
+    async fn send(app: axum::Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = app.oneshot(request).await.unwrap();
+        (response.status(), body_json(response).await)
+    }
+
+    async fn body_json(response: axum::response::Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_and_get_user() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+
+        let response = app(repo.clone())
+            .oneshot(
+                Request::post("/api/users")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"name": "Ana", "email": "ana@example.com"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let id = body_json(response).await["data"]["id"].as_i64().unwrap();
+
+        let response = app(repo)
+            .oneshot(
+                Request::get(format!("/api/users/{}", id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["data"]["name"], "Ana");
+    }
+
+    #[tokio::test]
+    async fn test_list_users() {
+        let users = Fixtures::seeded(1).users(3);
+        let repo = Arc::new(InMemoryUserRepository::with_users(users.clone()));
+
+        let response = app(repo)
+            .oneshot(Request::get("/api/users").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = body_json(response).await;
+
+        let emails: Vec<&str> = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|u| u["email"].as_str().unwrap())
+            .collect();
+        let expected: Vec<&str> = users.iter().map(|u| u.email.as_str()).collect();
+        assert_eq!(emails, expected);
+    }
+
+    #[tokio::test]
+    async fn test_list_users_is_paginated() {
+        let users = Fixtures::seeded(1).users(5);
+        let repo = Arc::new(InMemoryUserRepository::with_users(users.clone()));
+
+        let response = app(repo)
+            .oneshot(
+                Request::get("/api/users?page=2&per_page=2")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let link = response.headers()[header::LINK]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = body_json(response).await;
+
+        assert_eq!(
+            body["meta"],
+            serde_json::json!({"total": 5, "page": 2, "per_page": 2, "total_pages": 3})
+        );
+        let ids: Vec<i64> = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|u| u["id"].as_i64().unwrap())
+            .collect();
+        assert_eq!(ids, [i64::from(users[2].id), i64::from(users[3].id)]);
+        assert!(link.contains("</api/users?page=3&per_page=2>; rel=\"next\""));
+        assert!(link.contains("</api/users?page=1&per_page=2>; rel=\"prev\""));
+    }
+
+    #[tokio::test]
+    async fn test_list_users_with_sparse_fields() {
+        let users = Fixtures::seeded(1).users(2);
+        let repo = Arc::new(InMemoryUserRepository::with_users(users.clone()));
+
+        let request = Request::get("/api/users?fields=id,name&per_page=1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app(repo.clone()).oneshot(request).await.unwrap();
+        assert!(response.headers()[header::LINK]
+            .to_str()
+            .unwrap()
+            .contains("</api/users?fields=id,name&page=2&per_page=1>; rel=\"next\""));
+        let body = body_json(response).await;
+        assert_eq!(
+            body["data"],
+            serde_json::json!([{"id": users[0].id, "name": users[0].name}])
+        );
+
+        let request = Request::get("/api/users?fields=id,password")
+            .body(Body::empty())
+            .unwrap();
+        let (status, body) = send(app(repo), request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("'password'"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_user_is_not_found() {
+        let response = app(Arc::new(InMemoryUserRepository::new()))
+            .oneshot(Request::get("/api/users/42").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app(Arc::new(InMemoryUserRepository::new()))
+            .oneshot(
+                Request::delete("/api/users/42")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(all(feature = "msgpack", feature = "cbor"))]
+    #[tokio::test]
+    async fn test_responses_follow_the_accept_header() {
+        let users = Fixtures::seeded(1).users(1);
+        let repo = Arc::new(InMemoryUserRepository::with_users(users.clone()));
+        let uri = format!("/api/users/{}", users[0].id);
+
+        for (accept, content_type) in [
+            ("application/msgpack", "application/msgpack"),
+            ("application/cbor", "application/cbor"),
+            ("text/html", "application/json"),
+        ] {
+            let request = Request::get(&uri)
+                .header(header::ACCEPT, accept)
+                .body(Body::empty())
+                .unwrap();
+            let response = app(repo.clone()).oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[header::CONTENT_TYPE], content_type);
+            assert_eq!(response.headers()[header::VARY], "accept");
+
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = match content_type {
+                "application/msgpack" => rmp_serde::from_slice(&bytes).unwrap(),
+                "application/cbor" => ciborium::from_reader(bytes.as_ref()).unwrap(),
+                _ => serde_json::from_slice(&bytes).unwrap(),
+            };
+            assert_eq!(body["data"]["email"], users[0].email.as_str());
         }
+
+        // Erros continuam em JSON
+        let request = Request::get("/api/users/999999")
+            .header(header::ACCEPT, "application/msgpack")
+            .body(Body::empty())
+            .unwrap();
+        let (status, body) = send(app(repo), request).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["success"], false);
     }
 
-    /// Lista todos os usuários
-    pub async fn list_users(
-        State(state): State<AppState>,
-    ) -> Result<Json<ApiResponse<Vec<UserResponse>>>, ApiError> {
-        let users = DbUser::list_all(state.db.pool())
+    #[cfg(feature = "protobuf")]
+    #[tokio::test]
+    async fn test_protobuf_requests_and_responses() {
+        use crate::api::proto;
+        use prost::Message;
+
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let body = proto::CreateUserRequest {
+            name: "Ana".to_string(),
+            email: "ana@example.com".to_string(),
+        };
+        let request = Request::post("/api/users")
+            .header(header::CONTENT_TYPE, "application/x-protobuf")
+            .header(header::ACCEPT, "application/x-protobuf")
+            .body(Body::from(body.encode_to_vec()))
+            .unwrap();
+        let response = app(repo.clone()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/x-protobuf"
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let user = proto::UserReply::decode(bytes).unwrap().user.unwrap();
+        assert_eq!(user.email.as_deref(), Some("ana@example.com"));
+
+        let request = Request::get("/api/users?fields=id")
+            .header(header::ACCEPT, "application/x-protobuf")
+            .body(Body::empty())
+            .unwrap();
+        let response = app(repo.clone()).oneshot(request).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
             .await
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-        let response: Vec<UserResponse> = users.into_iter().map(Into::into).collect();
-        
-        Ok(Json(ApiResponse::success(response)))
+            .unwrap();
+        let list = proto::UserList::decode(bytes).unwrap();
+        assert_eq!(
+            list.users,
+            vec![proto::User {
+                id: user.id,
+                ..Default::default()
+            }]
+        );
+        assert_eq!(list.meta.unwrap().total, 1);
+
+        let request = Request::post("/api/users")
+            .header(header::CONTENT_TYPE, "application/x-protobuf")
+            .body(Body::from(vec![0xff, 0xff]))
+            .unwrap();
+        let (status, _) = send(app(repo), request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
     }
 
-    /// Cria um novo usuário
-    pub async fn create_user(
-        State(state): State<AppState>,
-        Json(payload): Json<CreateUserRequest>,
-    ) -> Result<Json<ApiResponse<UserResponse>>, ApiError> {
-        // Validar dados
-        payload.validate()
-            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    #[tokio::test]
+    async fn test_anonymize_user() {
+        let users = Fixtures::seeded(1).users(2);
+        let repo = Arc::new(InMemoryUserRepository::with_users(users.clone()));
+        let anonymize = |id: i32, principal: Principal| {
+            let mut request = Request::post(format!("/api/users/{}/anonymize", id))
+                .body(Body::empty())
+                .unwrap();
+            request.extensions_mut().insert(principal);
+            request
+        };
+        let other = Principal::User {
+            id: users[1].id,
+            tenant: "default".to_string(),
+        };
 
-        // Criar usuário
-        let user = DbUser::create(state.db.pool(), &payload.name, &payload.email)
+        for (principal, status) in [
+            (Principal::Anonymous, StatusCode::UNAUTHORIZED),
+            (other, StatusCode::FORBIDDEN),
+        ] {
+            let response = app(repo.clone())
+                .oneshot(anonymize(users[0].id, principal))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status);
+        }
+        let tenant = TenantContext::default_tenant();
+        assert!(repo
+            .find_by_email(&tenant, &users[0].email)
+            .await
+            .unwrap()
+            .is_some());
+
+        let response = app(repo.clone())
+            .oneshot(anonymize(users[0].id, Principal::Admin))
             .await
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-        
-        Ok(Json(ApiResponse::success(user.into())))
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["name"], crate::models::ANONYMIZED_NAME);
+        assert_eq!(body["data"]["status"], "deactivated");
+        assert_eq!(body["data"]["active"], false);
+        assert!(repo
+            .find_by_email(&tenant, &users[0].email)
+            .await
+            .unwrap()
+            .is_none());
+
+        // O próprio usuário também pode
+        let own = Principal::User {
+            id: users[1].id,
+            tenant: "default".to_string(),
+        };
+        let response = app(repo.clone())
+            .oneshot(anonymize(users[1].id, own))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app(repo)
+            .oneshot(anonymize(999, Principal::Admin))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_status_transitions() {
+        let user = Fixtures::seeded(1)
+            .user()
+            .status(UserStatus::Active)
+            .build();
+        let repo = Arc::new(InMemoryUserRepository::with_users([user.clone()]));
+        let post_as = |action: &str, principal: Principal| {
+            let mut request = Request::post(format!("/api/users/{}/{}", user.id, action))
+                .body(Body::empty())
+                .unwrap();
+            request.extensions_mut().insert(principal);
+            request
+        };
+        let post = |action: &str| post_as(action, Principal::Admin);
+
+        // Só o admin, nem o próprio usuário
+        let own = Principal::User {
+            id: user.id,
+            tenant: "default".to_string(),
+        };
+        for action in ["suspend", "ban"] {
+            for (principal, status) in [
+                (Principal::Anonymous, StatusCode::UNAUTHORIZED),
+                (own.clone(), StatusCode::FORBIDDEN),
+            ] {
+                let response = app(repo.clone())
+                    .oneshot(post_as(action, principal))
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), status);
+            }
+        }
+        let tenant = TenantContext::default_tenant();
+        let stored = repo.find_by_id(&tenant, user.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, UserStatus::Active);
+
+        let response = app(repo.clone()).oneshot(post("suspend")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["status"], "suspended");
+        assert_eq!(body["data"]["active"], false);
+
+        // Só uma conta pendente pode ser ativada
+        let response = app(repo.clone()).oneshot(post("activate")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        for (action, status) in [("reinstate", "active"), ("ban", "banned")] {
+            let response = app(repo.clone()).oneshot(post(action)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(body_json(response).await["data"]["status"], status);
+        }
+        let response = app(repo.clone()).oneshot(post("reinstate")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let stored = repo.find_by_id(&tenant, user.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, UserStatus::Banned);
+
+        let mut request = Request::post("/api/users/999/suspend")
+            .body(Body::empty())
+            .unwrap();
+        request.extensions_mut().insert(Principal::Admin);
+        let response = app(repo).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_export_user() {
+        let users = Fixtures::seeded(1).users(2);
+        let repo = Arc::new(InMemoryUserRepository::with_users(users.clone()));
+        let export = |principal: Principal| {
+            let mut request = Request::get(format!("/api/users/{}/export", users[0].id))
+                .body(Body::empty())
+                .unwrap();
+            request.extensions_mut().insert(principal);
+            request
+        };
+        let user = |id: i32| Principal::User {
+            id,
+            tenant: "default".to_string(),
+        };
+
+        for (principal, status) in [
+            (Principal::Anonymous, StatusCode::UNAUTHORIZED),
+            (user(users[1].id), StatusCode::FORBIDDEN),
+            (user(users[0].id), StatusCode::OK),
+        ] {
+            let response = app(repo.clone()).oneshot(export(principal)).await.unwrap();
+            assert_eq!(response.status(), status);
+        }
+
+        let response = app(repo).oneshot(export(Principal::Admin)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["content-disposition"],
+            format!("attachment; filename=\"user-{}-export.json\"", users[0].id)
+        );
+        let body = body_json(response).await;
+        assert_eq!(body["user"]["email"], users[0].email.as_str());
+        assert!(body["exported_at"].is_string());
+        assert_eq!(body["events"], serde_json::json!([]));
+        assert_eq!(body["logins"], serde_json::json!([]));
+        assert_eq!(body["passkeys"], serde_json::json!([]));
+        assert!(body["audit"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_large_export_runs_in_background() {
+        let users = Fixtures::seeded(1).users(2);
+        let repo = Arc::new(InMemoryUserRepository::with_users(users.clone()));
+        let state = AppState::new(repo, Default::default());
+        let id = users[0].id;
+        for _ in 0..=MAX_INLINE_EXPORT_ITEMS {
+            let entry = AuditEntry {
+                occurred_at: Utc::now(),
+                actor: "admin".to_string(),
+                ip: None,
+                user_agent: None,
+                method: "POST".to_string(),
+                path: format!("/api/users/{}/suspend", id),
+                status: 200,
+                request_id: None,
+                subject_id: Some(id),
+            };
+            state.audit.store.record(&entry).await.unwrap();
+        }
+        let app = create_router(state);
+        let user = |id: i32| Principal::User {
+            id,
+            tenant: "default".to_string(),
+        };
+        let get = |uri: &str, principal: Principal| {
+            let mut request = Request::get(uri).body(Body::empty()).unwrap();
+            request.extensions_mut().insert(principal);
+            request
+        };
+
+        let response = app
+            .clone()
+            .oneshot(get(&format!("/api/users/{}/export", id), user(id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let location = response.headers()[header::LOCATION]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["kind"], "user");
+        assert_eq!(body["data"]["user_id"], id);
+
+        // O job é só do usuário e do admin
+        let (status, _) = send(app.clone(), get(&location, Principal::Anonymous)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = send(app.clone(), get(&location, user(users[1].id))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let mut job = serde_json::Value::Null;
+        for _ in 0..100 {
+            let (status, body) = send(app.clone(), get(&location, user(id))).await;
+            assert_eq!(status, StatusCode::OK);
+            job = body["data"].clone();
+            if job["status"] == "completed" {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(job["status"], "completed");
+
+        let url = job["download_url"].as_str().unwrap();
+        let response = app.clone().oneshot(get(url, user(id))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            format!("attachment; filename=\"user-{}-export.json\"", id)
+        );
+        let export = body_json(response).await;
+        assert_eq!(export["user"]["id"], id);
+        assert_eq!(
+            export["audit"].as_array().unwrap().len() as i64,
+            MAX_INLINE_EXPORT_ITEMS + 1
+        );
+
+        // Uma conta pequena vai para o job só com `?async=true`
+        let small = format!("/api/users/{}/export", users[1].id);
+        let response = app
+            .clone()
+            .oneshot(get(&small, user(users[1].id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(get(&format!("{}?async=true", small), user(users[1].id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let (status, _) = send(
+            app,
+            get("/api/users/999/export?async=true", Principal::Admin),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
     }
 
-    /// Busca um usuário por ID
-    pub async fn get_user(
-        State(state): State<AppState>,
-        Path(id): Path<i32>,
-    ) -> Result<Json<ApiResponse<UserResponse>>, ApiError> {
-        let user = DbUser::find_by_id(state.db.pool(), id)
+    #[tokio::test]
+    async fn test_delete_with_grace_period_and_restore() {
+        let users = Fixtures::seeded(1).users(2);
+        let repo = Arc::new(InMemoryUserRepository::with_users(users.clone()));
+        let state = AppState::new(repo.clone(), Default::default())
+            .with_deletion_grace(std::time::Duration::from_secs(3600));
+        let app = create_router(state);
+        let (id, other) = (users[0].id, users[1].id);
+
+        let delete = |id: i32| {
+            Request::delete(format!("/api/users/{}", id))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let restore = |id: i32| {
+            Request::post(format!("/api/users/{}/restore", id))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let (status, body) = send(app.clone(), delete(id)).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert!(body["data"]["deletion_scheduled_at"].is_string());
+
+        let (status, body) = send(app.clone(), restore(id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"].get("deletion_scheduled_at").is_none());
+
+        let (status, _) = send(app.clone(), restore(id)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) = send(app.clone(), delete(999)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        send(app, delete(other)).await;
+        let purged = repo
+            .purge_deletions(Utc::now() + chrono::Duration::hours(2))
             .await
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?
-            .ok_or_else(|| ApiError::NotFound(format!("User with id {} not found", id)))?;
-        
-        Ok(Json(ApiResponse::success(user.into())))
+            .unwrap();
+        let tenant = TenantContext::default_tenant();
+        assert_eq!(purged, vec![(tenant.clone(), other)]);
+        assert!(repo.find_by_id(&tenant, id).await.unwrap().is_some());
     }
 
-    /// Deleta um usuário
-    pub async fn delete_user(
-        State(state): State<AppState>,
-        Path(id): Path<i32>,
-    ) -> Result<Json<ApiResponse<()>>, ApiError> {
-        DbUser::delete(state.db.pool(), id)
+    #[tokio::test]
+    async fn test_create_user_validates_payload() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let response = app(repo.clone())
+            .oneshot(
+                Request::post("/api/users")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"name": "", "email": "nope"}"#))
+                    .unwrap(),
+            )
             .await
-            .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
-        
-        Ok(Json(ApiResponse::success(())))
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            repo.count(&TenantContext::default_tenant()).await.unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_scoped_to_the_tenant() {
+        let users = Fixtures::seeded(1).users(1);
+        let repo = Arc::new(InMemoryUserRepository::with_users(users.clone()));
+        let get = |tenant: Option<&str>| {
+            let request = Request::get(format!("/api/users/{}", users[0].id));
+            let mut request = match tenant {
+                Some(tenant) => request.header(crate::tenant::TENANT_HEADER, tenant),
+                None => request,
+            }
+            .body(Body::empty())
+            .unwrap();
+            // Só o admin escolhe o tenant pelo header
+            request.extensions_mut().insert(Principal::Admin);
+            request
+        };
+
+        let (status, _) = send(app(repo.clone()), get(None)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(app(repo.clone()), get(Some("default"))).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(app(repo.clone()), get(Some("acme"))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(app(repo.clone()), get(Some("not a tenant"))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Sem tenant padrão, o header passa a ser obrigatório
+        let state = AppState::new(repo, Default::default()).with_default_tenant(None);
+        let (status, body) = send(create_router(state), get(None)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Missing X-Tenant-Id header");
     }
 }
@@ -0,0 +1,202 @@
+//! Métricas HTTP exportadas no formato Prometheus
+//!
+//! O middleware `track_metrics` registra, para cada requisição roteada:
+//!
+//! - `http_requests_total` (counter)
+//! - `http_request_duration_seconds` (histogram)
+//!
+//! ambos com os labels `method`, `route` (o padrão da rota, ex.:
+//! `/api/users/:id`, nunca a URI crua) e `status` (classe: `2xx`, `4xx`...).
+//!
+//! A cada coleta, `/metrics` também publica os contadores do
+//! `FibCache::global` (`fibonacci_cache_hits_total`,
+//! `fibonacci_cache_misses_total` e `fibonacci_cache_entries`).
+//!
+//! `/metrics?format=json` devolve as mesmas métricas em JSON (as famílias
+//! de `metrics_snapshot`), para depurar sem um Prometheus.
+
+use crate::api::ApiError;
+use crate::fib_cache::FibCache;
+use crate::metrics_snapshot::{self, SnapshotFormat};
+use axum::{
+    body::Body,
+    extract::{MatchedPath, Query},
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use serde::Deserialize;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// Nome do histograma de latência
+pub const REQUEST_DURATION_METRIC: &str = "http_request_duration_seconds";
+
+/// Nome do contador de requisições
+pub const REQUESTS_TOTAL_METRIC: &str = "http_requests_total";
+
+/// Nome do contador de acertos do cache de fibonacci
+pub const FIB_CACHE_HITS_METRIC: &str = "fibonacci_cache_hits_total";
+
+/// Nome do contador de faltas do cache de fibonacci
+pub const FIB_CACHE_MISSES_METRIC: &str = "fibonacci_cache_misses_total";
+
+/// Nome do gauge de valores guardados no cache de fibonacci
+pub const FIB_CACHE_ENTRIES_METRIC: &str = "fibonacci_cache_entries";
+
+/// Buckets do histograma de latência, em segundos
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Instala o recorder Prometheus global (idempotente)
+pub fn install_recorder() -> anyhow::Result<PrometheusHandle> {
+    if let Some(handle) = HANDLE.get() {
+        return Ok(handle.clone());
+    }
+
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(REQUEST_DURATION_METRIC.to_string()),
+            LATENCY_BUCKETS,
+        )?
+        .install_recorder()?;
+
+    Ok(HANDLE.get_or_init(|| handle).clone())
+}
+
+/// Classe do status HTTP (`2xx`, `4xx`, ...)
+pub fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+/// Middleware que registra contagem e latência por rota
+///
+/// Deve ser aplicado com `Router::route_layer`, para que o `MatchedPath`
+/// já esteja disponível; requisições sem rota não são contabilizadas.
+pub async fn track_metrics(req: Request<Body>, next: Next) -> Response {
+    let start = Instant::now();
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(req).await;
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", status_class(response.status()).to_string()),
+    ];
+    metrics::counter!(REQUESTS_TOTAL_METRIC, &labels).increment(1);
+    metrics::histogram!(REQUEST_DURATION_METRIC, &labels).record(start.elapsed().as_secs_f64());
+
+    response
+}
+
+/// Copia as estatísticas do `FibCache::global` para o recorder
+fn record_fib_cache_stats() {
+    let stats = FibCache::global().stats();
+    metrics::counter!(FIB_CACHE_HITS_METRIC).absolute(stats.hits);
+    metrics::counter!(FIB_CACHE_MISSES_METRIC).absolute(stats.misses);
+    metrics::gauge!(FIB_CACHE_ENTRIES_METRIC).set(stats.entries as f64);
+}
+
+/// Métricas atuais do recorder global, no formato texto do Prometheus
+pub fn render() -> Option<String> {
+    record_fib_cache_stats();
+    HANDLE.get().map(PrometheusHandle::render)
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct MetricsQuery {
+    /// `json` para as famílias em JSON; padrão, o texto do Prometheus
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Endpoint `/metrics` no formato texto do Prometheus (ou em JSON, com
+/// `?format=json`)
+pub async fn metrics_handler(Query(query): Query<MetricsQuery>) -> Result<Response, ApiError> {
+    let text = render().ok_or_else(|| ApiError::NotFound("Metrics are not enabled".to_string()))?;
+    match query.format.as_deref() {
+        None => Ok(text.into_response()),
+        Some(format) => match format.parse().map_err(ApiError::BadRequest)? {
+            SnapshotFormat::OpenMetrics => Ok(text.into_response()),
+            SnapshotFormat::Json => {
+                let families = metrics_snapshot::parse(&text)
+                    .map_err(|e| ApiError::InternalError(e.to_string()))?;
+                Ok(axum::Json(families).into_response())
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_class() {
+        assert_eq!(status_class(StatusCode::OK), "2xx");
+        assert_eq!(status_class(StatusCode::NOT_FOUND), "4xx");
+        assert_eq!(status_class(StatusCode::BAD_GATEWAY), "5xx");
+    }
+
+    #[tokio::test]
+    async fn test_records_route_pattern() {
+        use crate::api::{create_router, AppState};
+        use crate::repository::InMemoryUserRepository;
+        use std::sync::Arc;
+        use tower::ServiceExt;
+
+        let handle = install_recorder().unwrap();
+        let app = create_router(AppState::new(
+            Arc::new(InMemoryUserRepository::new()),
+            Default::default(),
+        ));
+
+        let response = app
+            .oneshot(Request::get("/version").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let output = handle.render();
+        assert!(output.contains(REQUEST_DURATION_METRIC));
+        assert!(output.contains(r#"route="/version""#));
+        assert!(output.contains(r#"status="2xx""#));
+    }
+
+    #[tokio::test]
+    async fn test_exports_fib_cache_stats() {
+        install_recorder().unwrap();
+        crate::fib_cache::fib_cached(30);
+
+        let output = render().unwrap();
+        assert!(output.contains(FIB_CACHE_HITS_METRIC));
+        assert!(output.contains(FIB_CACHE_MISSES_METRIC));
+        assert!(output.contains(FIB_CACHE_ENTRIES_METRIC));
+
+        let query = MetricsQuery {
+            format: Some("json".to_string()),
+        };
+        let response = metrics_handler(Query(query)).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let families: Vec<metrics_snapshot::MetricFamily> = serde_json::from_slice(&bytes).unwrap();
+        assert!(families.iter().any(|f| f.name == FIB_CACHE_ENTRIES_METRIC));
+    }
+}
@@ -1,41 +1,663 @@
 //! Middlewares para a API
 
+use crate::api::body_logging::{log_bodies, BodyLogger};
+use crate::api::client_ip;
+use crate::api::concurrency::{limit_concurrency, ConcurrencyLimit};
+use crate::api::rate_limit::{rate_limit, RateLimit, RateLimiter};
+use crate::api::session::{self, Session, Sessions};
+use crate::api::{ApiError, ApiResponse};
+use crate::audit::{subject_of, AuditEntry, AuditLog, Principal};
+use crate::auth::TokenSigner;
+use crate::config::{AppConfig, IpRange, RequestLogSampling};
+use crate::deadline::Deadline;
+use crate::idempotency::{self, Begin, Idempotency, StoredResponse};
+use crate::pii::Masked;
+use crate::trace_context::{TraceContext, TRACEPARENT_HEADER, TRACESTATE_HEADER};
 use axum::{
     body::Body,
-    http::Request,
-    middleware::Next,
-    response::Response,
+    body::HttpBody,
+    extract::{ConnectInfo, MatchedPath, State},
+    http::{header, HeaderValue, Method, Request, StatusCode},
+    middleware::{from_fn, from_fn_with_state, Next},
+    response::{IntoResponse, Response},
+    Json, Router,
 };
-use std::time::Instant;
-use tracing::{info, warn};
+use futures_util::FutureExt;
+use std::any::Any;
+use std::net::SocketAddr;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tower_http::{compression::CompressionLayer, cors::CorsLayer, timeout::TimeoutLayer};
+use tracing::{info, info_span, warn, Instrument};
+
+/// Aplica ao router as camadas ligadas na configuração
+///
+/// Da mais externa para a mais interna: id da requisição, trace context
+/// (W3C), log, aviso de requisições lentas, captura de panics (ver
+/// `catch_panic`), timeout
+/// (`server.timeout_seconds`; 0 desliga, e o mesmo prazo segue como
+/// `Deadline` até as consultas ao banco), CORS, compressão, log dos corpos
+/// JSON (`logging.log_bodies`, ver `body_logging`), limite de
+/// requisições simultâneas (`concurrency`), autenticação (o `Principal`
+/// nas extensions), limite de requisições por cliente (contado pelo
+/// `Principal`) e, com `sessions.enabled`, o CSRF das sessões por cookie (cujas rotas de
+/// login e logout também entram aqui).
+///
+/// O limite de requisições é sempre em memória e sem overrides; para o
+/// Redis e a tabela `rate_limit_overrides`, use `build_stack_with_rate_limit`.
+pub fn build_stack(router: Router, config: &AppConfig) -> Router {
+    let rate_limit = config.features.rate_limit_per_minute.map(|limit| {
+        RateLimit::new(Arc::new(RateLimiter::per_minute(limit)), limit)
+            .with_trusted_proxies(config.server.trusted_proxies.clone())
+    });
+    build_stack_with_rate_limit(router, config, rate_limit)
+}
+
+/// `build_stack` com o limite de requisições dado (ver
+/// `RateLimit::from_config`)
+pub fn build_stack_with_rate_limit(
+    router: Router,
+    config: &AppConfig,
+    rate_limit_state: Option<RateLimit>,
+) -> Router {
+    let features = &config.features;
+    let admin_token = features.admin_token.as_deref().map(Arc::<str>::from);
+    let sessions = match (&admin_token, config.sessions.enabled) {
+        (Some(_), true) => Some(Sessions::new(config.sessions.clone())),
+        (None, true) => {
+            warn!("Sessions are enabled but there is no admin_token to log in with");
+            None
+        }
+        (_, false) => None,
+    };
+
+    let mut router = router;
+    if let (Some(sessions), Some(token)) = (&sessions, &admin_token) {
+        router = router
+            .merge(session::router(sessions.clone(), token.clone()))
+            .layer(from_fn_with_state(sessions.clone(), csrf));
+    }
+    // O limite vem depois da autenticação, para contar pelo `Principal`
+    if let Some(state) = rate_limit_state {
+        router = router.layer(from_fn_with_state(state, rate_limit));
+    }
+    router = router.layer(from_fn_with_state(
+        Auth {
+            admin_token,
+            sessions,
+            tokens: Some(TokenSigner::from_config(&config.auth)),
+        },
+        authenticate,
+    ));
+
+    if let Some(limit) = ConcurrencyLimit::from_config(&config.concurrency) {
+        router = router.layer(from_fn_with_state(limit, limit_concurrency));
+    }
+    if let Some(logger) = BodyLogger::from_config(&config.logging) {
+        router = router.layer(from_fn_with_state(Arc::new(logger), log_bodies));
+    }
+    if features.compression_enabled {
+        router = router.layer(CompressionLayer::new());
+    }
+    if features.cors_enabled {
+        router = router.layer(CorsLayer::permissive());
+    }
+    if config.server.timeout_seconds > 0 {
+        let timeout = Duration::from_secs(config.server.timeout_seconds);
+        router = router
+            .layer(from_fn_with_state(timeout, propagate_deadline))
+            .layer(TimeoutLayer::new(timeout));
+    }
+    router = router.layer(from_fn(catch_panic));
+    if config.server.slow_request_threshold_ms > 0 {
+        let threshold = Duration::from_millis(config.server.slow_request_threshold_ms);
+        router = router.layer(from_fn_with_state(threshold, warn_slow_requests));
+    }
+    if features.request_logging_enabled {
+        let logger = Arc::new(RequestLogger::from_config(config));
+        router = router.layer(from_fn_with_state(logger, log_requests));
+    }
+    if features.trace_context_enabled {
+        router = router.layer(from_fn(trace_context));
+    }
+    if features.request_id_enabled {
+        router = router.layer(from_fn(request_id));
+    }
+
+    router
+}
+
+/// Header usado para propagar o id da requisição
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Id da requisição, disponível nas extensions para os handlers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Middleware que atribui um id a cada requisição
+///
+/// Reaproveita o `X-Request-Id` recebido (ou gera um UUID), devolve-o na
+/// resposta e executa o restante da pilha dentro de um span `request`, de
+/// modo que todos os logs da requisição carreguem o `request_id`.
+pub async fn request_id(mut req: Request<Body>, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && v.len() <= 128)
+        .map(str::to_owned)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let span = info_span!(
+        "request",
+        request_id = %id,
+        trace_id = tracing::field::Empty
+    );
+    let mut response = next.run(req).instrument(span).await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
+/// Middleware que coloca a requisição num trace distribuído
+///
+/// Continua o trace do `traceparent` recebido (com um `span_id` novo) ou
+/// começa um; o `TraceContext` vai para as extensions, o `trace_id` para o
+/// span `request` e o restante da pilha roda com ele como o atual, de modo
+/// que as chamadas de saída geradas pela requisição (webhooks, NATS) levem
+/// o trace adiante.
+pub async fn trace_context(mut req: Request<Body>, next: Next) -> Response {
+    let context = {
+        let header = |name: &str| req.headers().get(name).and_then(|v| v.to_str().ok());
+        header(TRACEPARENT_HEADER)
+            .and_then(|traceparent| TraceContext::parse(traceparent, header(TRACESTATE_HEADER)))
+            .map(|context| context.child())
+            .unwrap_or_else(TraceContext::new_root)
+    };
+
+    tracing::Span::current().record("trace_id", tracing::field::display(context.trace_id_hex()));
+    req.extensions_mut().insert(context.clone());
+    context.scope(next.run(req)).await
+}
+
+/// Middleware que dá à requisição o prazo do timeout HTTP
+///
+/// O `Deadline` vai para as extensions e o restante da pilha roda com ele
+/// como o atual, de modo que o repositório abandone as consultas quando o
+/// cliente já recebeu o timeout (ver `crate::deadline`).
+pub async fn propagate_deadline(
+    State(timeout): State<Duration>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    let deadline = Deadline::after(timeout);
+    req.extensions_mut().insert(deadline);
+    deadline.scope(next.run(req)).await
+}
+
+/// Header das respostas a um token de impersonação, com quem age como quem
+/// (`admin as user:<tenant>/<id>`)
+pub const IMPERSONATING_HEADER: &str = "x-impersonating";
+
+/// Credenciais aceitas por `authenticate`
+#[derive(Clone, Default)]
+pub struct Auth {
+    pub admin_token: Option<Arc<str>>,
+    /// Sessões por cookie, quando ligadas
+    pub sessions: Option<Sessions>,
+    /// Tokens dos usuários (`POST /api/auth/login`)
+    pub tokens: Option<TokenSigner>,
+}
+
+/// Middleware que identifica quem faz a requisição, sem recusar nenhuma
+///
+/// Coloca o `Principal` nas extensions: `Admin` com o `admin_token` em
+/// `Authorization: Bearer`, `User` (ou `Enrolling`, se falta ativar o 2FA,
+/// ou `Impersonating`, com um token de impersonação) com um token de
+/// usuário válido no mesmo header, o da sessão com um cookie de sessão
+/// válido (que também vai para as extensions, para o `csrf`) e `Anonymous`
+/// nos demais casos. O bearer vale sobre o cookie. As respostas a um token
+/// de impersonação levam `X-Impersonating`.
+pub async fn authenticate(
+    State(auth): State<Auth>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    let user = auth.tokens.as_ref().and_then(|tokens| {
+        let claims = tokens.verify(bearer(&req)?, chrono::Utc::now()).ok()?;
+        Some(if let Some(actor) = claims.act {
+            Principal::Impersonating {
+                id: claims.sub,
+                tenant: claims.tenant,
+                actor,
+            }
+        } else if claims.two_factor_pending {
+            Principal::Enrolling {
+                id: claims.sub,
+                tenant: claims.tenant,
+            }
+        } else {
+            Principal::User {
+                id: claims.sub,
+                tenant: claims.tenant,
+            }
+        })
+    });
+    let principal = match (&auth.admin_token, user) {
+        (Some(token), _) if bearer_matches(&req, token) => Principal::Admin,
+        (_, Some(user)) => user,
+        _ => match auth.sessions.as_ref().and_then(|s| s.from_request(&req)) {
+            Some(session) => {
+                let principal = session.principal.clone();
+                req.extensions_mut().insert(session);
+                principal
+            }
+            None => Principal::Anonymous,
+        },
+    };
+    let impersonating = matches!(principal, Principal::Impersonating { .. })
+        .then(|| HeaderValue::from_str(&principal.actor()).ok())
+        .flatten();
+    req.extensions_mut().insert(principal);
+
+    let mut response = next.run(req).await;
+    if let Some(value) = impersonating {
+        response.headers_mut().insert(IMPERSONATING_HEADER, value);
+    }
+    response
+}
+
+/// Middleware que exige o token CSRF nas escritas autenticadas por sessão
+///
+/// Vale para POST, PUT, PATCH e DELETE com uma `Session` nas extensions: o
+/// header (`sessions.csrf_header`) e o cookie CSRF precisam ser iguais ao
+/// token da sessão. Requisições com bearer, anônimas e o próprio login
+/// passam direto.
+pub async fn csrf(State(sessions): State<Sessions>, req: Request<Body>, next: Next) -> Response {
+    let mutating = matches!(
+        *req.method(),
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    );
+    let Some(session) = req.extensions().get::<Session>() else {
+        return next.run(req).await;
+    };
+    if !mutating || (req.method() == Method::POST && req.uri().path() == session::SESSION_PATH) {
+        return next.run(req).await;
+    }
+
+    let expected = session.csrf_token.as_bytes();
+    let header = req
+        .headers()
+        .get(sessions.config.csrf_header.as_str())
+        .and_then(|v| v.to_str().ok());
+    let cookie = session::cookie(req.headers(), &sessions.config.csrf_cookie_name);
+    let valid = match (header, cookie) {
+        (Some(header), Some(cookie)) => {
+            constant_time_eq(header.as_bytes(), expected)
+                && constant_time_eq(cookie.as_bytes(), expected)
+        }
+        _ => false,
+    };
+    if !valid {
+        return ApiError::Forbidden("Missing or invalid CSRF token".to_string()).into_response();
+    }
+
+    next.run(req).await
+}
+
+/// Middleware que exige `Authorization: Bearer <token>`
+pub async fn require_bearer_token(
+    State(token): State<Arc<str>>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    if !bearer_matches(&req, &token) {
+        let mut response =
+            ApiError::Unauthorized("Missing or invalid bearer token".to_string()).into_response();
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        return response;
+    }
+
+    req.extensions_mut().insert(Principal::Admin);
+    next.run(req).await
+}
+
+fn bearer(req: &Request<Body>) -> Option<&str> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+fn bearer_matches(req: &Request<Body>, token: &str) -> bool {
+    bearer(req).is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+}
+
+/// Maior user agent gravado no log de auditoria
+const MAX_AUDIT_USER_AGENT_CHARS: usize = 512;
+
+/// Middleware que grava no log de auditoria as requisições que alteram
+/// dados (POST, PUT, PATCH e DELETE), depois da resposta
+///
+/// Uma falha ao gravar é registrada nos logs, mas não muda a resposta.
+pub async fn audit(State(log): State<AuditLog>, req: Request<Body>, next: Next) -> Response {
+    let mutating = matches!(
+        *req.method(),
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    );
+    if !mutating {
+        return next.run(req).await;
+    }
+
+    let principal = req
+        .extensions()
+        .get::<Principal>()
+        .cloned()
+        .unwrap_or_default();
+    let ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string());
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.chars().take(MAX_AUDIT_USER_AGENT_CHARS).collect());
+    let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let subject_id = subject_of(&path, &principal);
+
+    let response = next.run(req).await;
+
+    let entry = AuditEntry {
+        occurred_at: chrono::Utc::now(),
+        actor: principal.actor().to_string(),
+        ip,
+        user_agent,
+        method,
+        path,
+        status: response.status().as_u16(),
+        request_id,
+        subject_id,
+    };
+    if let Err(e) = log.store.record(&entry).await {
+        warn!(error = %e, method = %entry.method, path = %entry.path, "Failed to record audit entry");
+    }
+
+    response
+}
+
+/// Compara sem curto-circuito, para não vazar o token por tempo de resposta
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Header com a chave de idempotência enviada pelo cliente
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Header presente nas respostas repetidas a partir do armazenamento
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Maior corpo de requisição lido pelo middleware de idempotência (o mesmo
+/// limite padrão do extractor `Json`)
+const MAX_IDEMPOTENT_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Middleware de idempotência para requisições POST
+///
+/// Com `Idempotency-Key`, a primeira requisição é executada e sua resposta
+/// guardada; as seguintes com a mesma chave e o mesmo conteúdo recebem a
+/// resposta guardada, com `Idempotent-Replayed: true`. Responde 409 se a
+/// primeira ainda está em andamento e 422 se a chave já foi usada com outro
+/// conteúdo ou por outro cliente. Respostas 5xx, 401, 403, 408, 409 e 429
+/// não são guardadas (ver `idempotency::is_storable`).
+pub async fn idempotency(
+    State(idempotency): State<Idempotency>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if req.method() != Method::POST {
+        return next.run(req).await;
+    }
+    let Some(key) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(req).await;
+    };
+    let key = match key.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= 255 => key.to_owned(),
+        _ => {
+            return ApiError::BadRequest("Invalid Idempotency-Key header".to_string())
+                .into_response()
+        }
+    };
+
+    let (parts, body) = req.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_IDEMPOTENT_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(_) => {
+            return (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Json(ApiResponse::<()>::error("Request body too large")),
+            )
+                .into_response()
+        }
+    };
+    let path = parts.uri.path_and_query().map_or("", |p| p.as_str());
+    let tenant = parts
+        .headers
+        .get(crate::tenant::TENANT_HEADER)
+        .map(|v| v.as_bytes());
+    let actor = parts
+        .extensions
+        .get::<Principal>()
+        .cloned()
+        .unwrap_or_default()
+        .actor();
+    let hash = idempotency::request_hash(parts.method.as_str(), path, tenant, &actor, &body);
+
+    let begin = idempotency
+        .store
+        .begin(
+            &key,
+            &hash,
+            idempotency::deadline(idempotency::IN_PROGRESS_TIMEOUT),
+        )
+        .await;
+    match begin {
+        Ok(Begin::Started) => {}
+        Ok(Begin::Completed(stored)) => return replay(stored),
+        Ok(Begin::InProgress) => {
+            return ApiError::Conflict(
+                "A request with this Idempotency-Key is still in progress".to_string(),
+            )
+            .into_response()
+        }
+        Ok(Begin::Mismatch) => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ApiResponse::<()>::error(
+                    "Idempotency-Key was already used with a different request",
+                )),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to reserve idempotency key");
+            return ApiError::InternalError("Idempotency store unavailable".to_string())
+                .into_response();
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    if !idempotency::is_storable(response.status().as_u16()) {
+        if let Err(e) = idempotency.store.release(&key).await {
+            warn!(error = %e, "Failed to release idempotency key");
+        }
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(error = %e, "Failed to buffer response for idempotency key");
+            let _ = idempotency.store.release(&key).await;
+            return ApiError::InternalError("Failed to read response".to_string()).into_response();
+        }
+    };
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        content_type: parts
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned),
+        body: body.to_vec(),
+    };
+    if let Err(e) = idempotency
+        .store
+        .complete(&key, &stored, idempotency::deadline(idempotency.ttl))
+        .await
+    {
+        warn!(error = %e, "Failed to store idempotent response");
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Reconstrói a resposta guardada
+fn replay(stored: StoredResponse) -> Response {
+    let status = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    let mut response = (status, stored.body).into_response();
+    let headers = response.headers_mut();
+    headers.remove(header::CONTENT_TYPE);
+    if let Some(value) = stored
+        .content_type
+        .and_then(|v| HeaderValue::from_str(&v).ok())
+    {
+        headers.insert(header::CONTENT_TYPE, value);
+    }
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+/// Amostragem do log de requisições (`logging.request_sampling`)
+#[derive(Debug, Clone, Default)]
+pub struct LogSampler {
+    /// Prefixos e frações, do prefixo mais longo ao mais curto
+    rules: Vec<(String, f64)>,
+}
+
+impl LogSampler {
+    pub fn new(rules: &[RequestLogSampling]) -> Self {
+        let mut rules: Vec<(String, f64)> = rules
+            .iter()
+            .map(|rule| (rule.prefix.trim_end_matches('/').to_string(), rule.rate))
+            .collect();
+        rules.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        Self { rules }
+    }
+
+    /// Fração registrada das requisições sem erro ao caminho (1.0 sem regra)
+    pub fn rate_for(&self, path: &str) -> f64 {
+        self.rules
+            .iter()
+            .find(|(prefix, _)| {
+                path.strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .map_or(1.0, |(_, rate)| *rate)
+    }
+
+    /// Sorteia se a requisição entra no log; erros (>= 400) sempre entram
+    pub fn should_log(&self, path: &str, status: StatusCode) -> bool {
+        if status.is_client_error() || status.is_server_error() {
+            return true;
+        }
+        let rate = self.rate_for(path);
+        rate >= 1.0 || rand::random::<f64>() < rate
+    }
+}
+
+/// Estado do `log_requests`
+#[derive(Debug, Clone, Default)]
+pub struct RequestLogger {
+    pub sampler: LogSampler,
+    /// `server.trusted_proxies` (ver `client_ip`)
+    pub trusted_proxies: Vec<IpRange>,
+}
+
+impl RequestLogger {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            sampler: LogSampler::new(&config.logging.request_sampling),
+            trusted_proxies: config.server.trusted_proxies.clone(),
+        }
+    }
+}
 
 /// Middleware de logging de requisições
+///
+/// Registra a rota casada (`/api/users/:id`, não o caminho com o ID; sem
+/// rota, o caminho com emails e telefones mascarados por `pii::Masked`), o
+/// IP do cliente (`client_ip`, atrás dos proxies confiáveis) e o tamanho do
+/// corpo da resposta, quando conhecido (sem `Content-Length`, como nas
+/// respostas comprimidas ou em stream, o campo fica de fora).
+/// Requisições sem erro a rotas com amostragem configurada são registradas
+/// só na fração pedida (ver `LogSampler`).
 pub async fn log_requests(
+    State(logger): State<Arc<RequestLogger>>,
     req: Request<Body>,
     next: Next,
 ) -> Response {
     let method = req.method().clone();
-    let uri = req.uri().clone();
+    let path = req.uri().path().to_owned();
+    let route = req.extensions().get::<MatchedPath>().cloned();
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let client_ip = client_ip::client_ip(req.headers(), peer, &logger.trusted_proxies);
     let start = Instant::now();
 
     let response = next.run(req).await;
 
     let duration = start.elapsed();
     let status = response.status();
+    if !logger.sampler.should_log(&path, status) {
+        return response;
+    }
 
+    let route = match &route {
+        Some(route) => route.as_str().to_owned(),
+        None => Masked(&path).to_string(),
+    };
+    let response_bytes = response_size(&response);
     if status.is_server_error() {
         warn!(
             method = %method,
-            uri = %uri,
+            route = %route,
             status = %status,
+            client_ip = client_ip.map(tracing::field::display),
+            response_bytes,
             duration_ms = %duration.as_millis(),
             "Request completed with error"
         );
     } else {
         info!(
             method = %method,
-            uri = %uri,
+            route = %route,
             status = %status,
+            client_ip = client_ip.map(tracing::field::display),
+            response_bytes,
             duration_ms = %duration.as_millis(),
             "Request completed"
         );
@@ -43,3 +665,739 @@ pub async fn log_requests(
 
     response
 }
+
+/// Middleware que avisa das requisições mais lentas que o limite
+/// (`server.slow_request_threshold_ms`)
+///
+/// Independe do timeout: fica por fora dele, então uma requisição cortada
+/// pelo timeout também conta se o limite for menor. Além do aviso no log,
+/// com a rota, a duração e o `request_id`, incrementa `slow_requests_total`
+/// (feature "observability").
+pub async fn warn_slow_requests(
+    State(threshold): State<Duration>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let method = req.method().clone();
+    let route = req.extensions().get::<MatchedPath>().cloned();
+    let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+    let start = Instant::now();
+
+    let response = next.run(req).await;
+
+    let duration = start.elapsed();
+    if duration >= threshold {
+        let route = route.as_ref().map_or("unmatched", |route| route.as_str());
+        warn!(
+            method = %method,
+            route,
+            status = %response.status(),
+            duration_ms = duration.as_millis() as u64,
+            threshold_ms = threshold.as_millis() as u64,
+            request_id,
+            "Slow request"
+        );
+        #[cfg(feature = "observability")]
+        metrics::counter!(
+            "slow_requests_total",
+            "method" => method.to_string(),
+            "route" => route.to_string()
+        )
+        .increment(1);
+    }
+    response
+}
+
+/// Panic capturado por `catch_panic`
+#[derive(Debug, Clone)]
+pub struct PanicReport {
+    pub request_id: Option<String>,
+    pub method: Method,
+    /// Template da rota (`/users/:id`); o caminho mascarado sem rota
+    pub route: String,
+    /// Mensagem do panic
+    pub message: String,
+}
+
+/// Destino dos panics capturados (ex.: um serviço de rastreio de erros),
+/// além do log
+pub type PanicReporter = Box<dyn Fn(&PanicReport) + Send + Sync>;
+
+static PANIC_REPORTER: OnceLock<PanicReporter> = OnceLock::new();
+
+/// Instala o `PanicReporter` do processo; `false` se já havia um
+pub fn set_panic_reporter(reporter: PanicReporter) -> bool {
+    PANIC_REPORTER.set(reporter).is_ok()
+}
+
+/// Middleware que transforma um panic no restante da pilha num 500
+///
+/// Sem ele o hyper derruba a conexão e o cliente não recebe resposta. O
+/// corpo é um `ApiResponse` de erro com o `request_id` em `details`, para
+/// o cliente informar ao suporte; a mensagem do panic vai só para o log e
+/// para o `PanicReporter` (ver `set_panic_reporter`).
+pub async fn catch_panic(req: Request<Body>, next: Next) -> Response {
+    let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+    let method = req.method().clone();
+    let route = match req.extensions().get::<MatchedPath>() {
+        Some(route) => route.as_str().to_owned(),
+        None => Masked(req.uri().path()).to_string(),
+    };
+
+    let payload = match AssertUnwindSafe(next.run(req)).catch_unwind().await {
+        Ok(response) => return response,
+        Err(payload) => payload,
+    };
+
+    let report = PanicReport {
+        request_id,
+        method,
+        route,
+        message: panic_message(payload.as_ref()),
+    };
+    tracing::error!(
+        method = %report.method,
+        route = %report.route,
+        panic = %report.message,
+        "Request handler panicked"
+    );
+    #[cfg(feature = "observability")]
+    metrics::counter!("http_panics_total").increment(1);
+    if let Some(reporter) = PANIC_REPORTER.get() {
+        reporter(&report);
+    }
+
+    let body = match report.request_id {
+        Some(id) => ApiResponse::<()>::error_with_details(
+            "Internal server error",
+            serde_json::json!({ "request_id": id }),
+        ),
+        None => ApiResponse::<()>::error("Internal server error"),
+    };
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response()
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "non-string panic payload".to_owned()
+    }
+}
+
+/// Tamanho do corpo: o `Content-Length` ou, sem ele, o tamanho exato do
+/// corpo se já for conhecido
+fn response_size(response: &Response) -> Option<u64> {
+    response
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok()?.parse().ok())
+        .or_else(|| response.body().size_hint().exact())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn protected() -> Router {
+        Router::new()
+            .route("/admin", get(|| async { "ok" }))
+            .route_layer(from_fn_with_state(
+                Arc::<str>::from("s3cret"),
+                require_bearer_token,
+            ))
+    }
+
+    async fn status_for(authorization: Option<&str>) -> StatusCode {
+        let mut req = Request::get("/admin");
+        if let Some(value) = authorization {
+            req = req.header(header::AUTHORIZATION, value);
+        }
+        protected()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_is_required() {
+        assert_eq!(status_for(Some("Bearer s3cret")).await, StatusCode::OK);
+        assert_eq!(
+            status_for(Some("Bearer wrong")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status_for(Some("s3cret")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status_for(None).await, StatusCode::UNAUTHORIZED);
+    }
+
+    async fn stack_response(config: &AppConfig, req: Request<Body>) -> Response {
+        build_stack(Router::new().route("/", get(|| async { "ok" })), config)
+            .oneshot(req)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_build_stack_follows_config() {
+        let config = AppConfig::default();
+        let preflight = || {
+            Request::options("/")
+                .header(header::ORIGIN, "https://example.com")
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = stack_response(&config, preflight()).await;
+        assert!(response.headers().contains_key(REQUEST_ID_HEADER));
+        assert!(response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let mut config = AppConfig::default();
+        config.features.cors_enabled = false;
+        config.features.request_id_enabled = false;
+        let response = stack_response(&config, preflight()).await;
+        assert!(!response.headers().contains_key(REQUEST_ID_HEADER));
+        assert!(!response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[tokio::test]
+    async fn test_catch_panic_returns_structured_500() {
+        static REPORTS: std::sync::Mutex<Vec<PanicReport>> = std::sync::Mutex::new(Vec::new());
+        set_panic_reporter(Box::new(|report| {
+            REPORTS.lock().unwrap().push(report.clone())
+        }));
+
+        let router = Router::new().route(
+            "/boom/:id",
+            get(|| async {
+                if true {
+                    panic!("handler exploded");
+                }
+                "unreachable"
+            }),
+        );
+        let response = build_stack(router, &AppConfig::default())
+            .oneshot(Request::get("/boom/7").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let request_id = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_owned();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"], "Internal server error");
+        assert_eq!(body["details"]["request_id"], request_id.as_str());
+
+        let reports = REPORTS.lock().unwrap();
+        let report = reports
+            .iter()
+            .find(|report| report.request_id.as_deref() == Some(request_id.as_str()))
+            .expect("the panic was reported");
+        assert_eq!(report.method, Method::GET);
+        assert_eq!(report.route, "/boom/:id");
+        assert_eq!(report.message, "handler exploded");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_propagates_deadline() {
+        let app = |config: &AppConfig| {
+            build_stack(
+                Router::new().route(
+                    "/",
+                    get(|| async {
+                        match Deadline::current() {
+                            Some(deadline) => deadline.remaining().as_secs().to_string(),
+                            None => "none".to_string(),
+                        }
+                    }),
+                ),
+                config,
+            )
+        };
+        let body = |response: Response| async {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            String::from_utf8(bytes.to_vec()).unwrap()
+        };
+
+        let mut config = AppConfig::default();
+        config.server.timeout_seconds = 30;
+        let req = Request::get("/").body(Body::empty()).unwrap();
+        let remaining = body(app(&config).oneshot(req).await.unwrap()).await;
+        assert!(remaining == "29" || remaining == "30", "{}", remaining);
+
+        config.server.timeout_seconds = 0;
+        let req = Request::get("/").body(Body::empty()).unwrap();
+        assert_eq!(body(app(&config).oneshot(req).await.unwrap()).await, "none");
+    }
+
+    #[tokio::test]
+    async fn test_trace_context_continues_incoming_trace() {
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    TraceContext::current()
+                        .map(|context| context.traceparent())
+                        .unwrap_or_default()
+                }),
+            )
+            .layer(from_fn(trace_context));
+        let body = |response: Response| async {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            String::from_utf8(bytes.to_vec()).unwrap()
+        };
+
+        let req = Request::get("/")
+            .header(TRACEPARENT_HEADER, traceparent)
+            .body(Body::empty())
+            .unwrap();
+        let current = body(app.clone().oneshot(req).await.unwrap()).await;
+        assert!(current.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+        assert_ne!(current, traceparent);
+
+        // Sem traceparent (ou com um inválido), começa um trace novo
+        let req = Request::get("/")
+            .header(TRACEPARENT_HEADER, "invalid")
+            .body(Body::empty())
+            .unwrap();
+        let current = body(app.oneshot(req).await.unwrap()).await;
+        assert!(TraceContext::parse(&current, None).is_some());
+        assert!(!current.contains("4bf92f3577b34da6a3ce929d0e0e4736"));
+    }
+
+    #[tokio::test]
+    async fn test_session_writes_require_csrf_token() {
+        use axum::{extract::Extension, routing::post};
+
+        let mut config = AppConfig::default();
+        config.features.admin_token = Some("s3cret".to_string());
+        config.sessions.enabled = true;
+        config.sessions.secure_cookies = false;
+        let whoami = |Extension(principal): Extension<Principal>| async move { principal.actor() };
+        let app = build_stack(
+            Router::new().route("/orders", post(whoami).get(whoami)),
+            &config,
+        );
+        let send = |req: Request<Body>| {
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(req).await.unwrap();
+                let status = response.status();
+                let cookies: Vec<String> = response
+                    .headers()
+                    .get_all(header::SET_COOKIE)
+                    .iter()
+                    .map(|v| v.to_str().unwrap().to_string())
+                    .collect();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (status, cookies, String::from_utf8(body.to_vec()).unwrap())
+            }
+        };
+        let login = |token: &str| {
+            Request::post(session::SESSION_PATH)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(format!(r#"{{"token":"{}"}}"#, token)))
+                .unwrap()
+        };
+
+        assert_eq!(send(login("wrong")).await.0, StatusCode::UNAUTHORIZED);
+        let (status, cookies, _) = send(login("s3cret")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(cookies[0].starts_with("session=") && cookies[0].contains("HttpOnly"));
+        assert!(cookies[1].starts_with("csrf_token=") && !cookies[1].contains("HttpOnly"));
+        let value = |cookie: &str| cookie.split(';').next().unwrap().to_string();
+        let cookie = format!("{}; {}", value(&cookies[0]), value(&cookies[1]));
+        let csrf_token = value(&cookies[1])["csrf_token=".len()..].to_string();
+
+        let with_session = |method: Method, csrf: Option<&str>| {
+            let mut req = Request::builder()
+                .method(method)
+                .uri("/orders")
+                .header(header::COOKIE, &cookie);
+            if let Some(token) = csrf {
+                req = req.header("x-csrf-token", token);
+            }
+            req.body(Body::empty()).unwrap()
+        };
+
+        // Leituras com a sessão não precisam do token; escritas, sim
+        let (status, _, actor) = send(with_session(Method::GET, None)).await;
+        assert_eq!((status, actor.as_str()), (StatusCode::OK, "admin"));
+        assert_eq!(
+            send(with_session(Method::POST, None)).await.0,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            send(with_session(Method::POST, Some("forged"))).await.0,
+            StatusCode::FORBIDDEN
+        );
+        let (status, _, actor) = send(with_session(Method::POST, Some(&csrf_token))).await;
+        assert_eq!((status, actor.as_str()), (StatusCode::OK, "admin"));
+
+        // Bearer e anônimos não passam pela verificação
+        let bearer = Request::post("/orders")
+            .header(header::AUTHORIZATION, "Bearer s3cret")
+            .header(header::COOKIE, &cookie)
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(send(bearer).await.0, StatusCode::OK);
+        let (status, _, actor) = send(Request::post("/orders").body(Body::empty()).unwrap()).await;
+        assert_eq!((status, actor.as_str()), (StatusCode::OK, "anonymous"));
+
+        // O logout também exige o token e encerra a sessão
+        let logout = |csrf: Option<&str>| {
+            let mut req = Request::delete(session::SESSION_PATH).header(header::COOKIE, &cookie);
+            if let Some(token) = csrf {
+                req = req.header("x-csrf-token", token);
+            }
+            req.body(Body::empty()).unwrap()
+        };
+        assert_eq!(send(logout(None)).await.0, StatusCode::FORBIDDEN);
+        let (status, cookies, _) = send(logout(Some(&csrf_token))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(cookies[0].contains("Max-Age=0"));
+        let (_, _, actor) = send(with_session(Method::GET, None)).await;
+        assert_eq!(actor, "anonymous");
+    }
+
+    #[tokio::test]
+    async fn test_build_stack_rate_limit() {
+        let mut config = AppConfig::default();
+        config.features.rate_limit_per_minute = Some(1);
+        let app = build_stack(Router::new().route("/", get(|| async { "ok" })), &config);
+        let get_root = || Request::get("/").body(Body::empty()).unwrap();
+
+        let first = app.clone().oneshot(get_root()).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+
+        let second = app.oneshot(get_root()).await.unwrap();
+        assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(second.headers()[header::RETRY_AFTER], "60");
+    }
+
+    fn idempotent(calls: Arc<std::sync::atomic::AtomicUsize>) -> Router {
+        use axum::routing::post;
+        use std::sync::atomic::Ordering;
+
+        let store = Arc::new(crate::idempotency::InMemoryIdempotencyStore::new());
+        Router::new()
+            .route(
+                "/orders",
+                post(move |body: String| async move {
+                    let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                    if body == "boom" {
+                        return (StatusCode::INTERNAL_SERVER_ERROR, "failed".to_string());
+                    }
+                    if body == "busy" {
+                        return (StatusCode::TOO_MANY_REQUESTS, "slow down".to_string());
+                    }
+                    (StatusCode::CREATED, format!("order {n}: {body}"))
+                }),
+            )
+            .route_layer(from_fn_with_state(Idempotency::new(store), idempotency))
+    }
+
+    async fn post_order(app: &Router, key: Option<&str>, body: &str) -> (StatusCode, bool, String) {
+        let mut req = Request::post("/orders");
+        if let Some(key) = key {
+            req = req.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        let response = app
+            .clone()
+            .oneshot(req.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let replayed = response.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, replayed, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_replays_response() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let app = idempotent(calls.clone());
+
+        let first = post_order(&app, Some("k1"), "pizza").await;
+        assert_eq!(
+            first,
+            (StatusCode::CREATED, false, "order 1: pizza".to_string())
+        );
+
+        let retry = post_order(&app, Some("k1"), "pizza").await;
+        assert_eq!(
+            retry,
+            (StatusCode::CREATED, true, "order 1: pizza".to_string())
+        );
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Mesma chave, outro conteúdo
+        let (status, _, _) = post_order(&app, Some("k1"), "sushi").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        // Sem chave, toda requisição é executada
+        post_order(&app, None, "pizza").await;
+        post_order(&app, None, "pizza").await;
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_is_released_on_server_error() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let app = idempotent(calls.clone());
+
+        let (status, _, _) = post_order(&app, Some("k2"), "boom").await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        let (status, replayed, _) = post_order(&app, Some("k2"), "boom").await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!replayed);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        // 429 também não é guardada
+        let (status, _, _) = post_order(&app, Some("k3"), "busy").await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        let (_, replayed, _) = post_order(&app, Some("k3"), "busy").await;
+        assert!(!replayed);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 4);
+
+        let (status, _, _) = post_order(&app, Some(""), "pizza").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_is_scoped_to_the_principal() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let app = idempotent(calls.clone());
+        let order = |principal: Principal| {
+            let mut req = Request::post("/orders")
+                .header(IDEMPOTENCY_KEY_HEADER, "k1")
+                .body(Body::from("pizza"))
+                .unwrap();
+            req.extensions_mut().insert(principal);
+            app.clone().oneshot(req)
+        };
+        let user = |id: i32| Principal::User {
+            id,
+            tenant: "default".to_string(),
+        };
+
+        let first = order(user(1)).await.unwrap();
+        assert_eq!(first.status(), StatusCode::CREATED);
+        let replay = order(user(1)).await.unwrap();
+        assert!(replay.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER));
+
+        // Outro cliente com a mesma chave não recebe a resposta guardada
+        let other = order(user(2)).await.unwrap();
+        assert_eq!(other.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_audit_records_mutating_requests_with_actor() {
+        let log = AuditLog::default();
+        let router = Router::new()
+            .route(
+                "/things",
+                get(|| async { "ok" }).post(|| async { StatusCode::CREATED }),
+            )
+            .route_layer(from_fn_with_state(log.clone(), audit));
+        let config = crate::config::AppConfigBuilder::new()
+            .admin_token("s3cret")
+            .build();
+        let app = build_stack(router, &config);
+
+        let send = |req: Request<Body>| app.clone().oneshot(req);
+        send(Request::get("/things").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let mut req = Request::post("/things")
+            .header(header::AUTHORIZATION, "Bearer s3cret")
+            .header(header::USER_AGENT, "curl/8.0")
+            .body(Body::empty())
+            .unwrap();
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 7], 5000))));
+        send(req).await.unwrap();
+        send(Request::post("/things").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let entries = log.store.recent(10).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].actor, "anonymous");
+        let admin = &entries[1];
+        assert_eq!(admin.actor, "admin");
+        assert_eq!(admin.ip.as_deref(), Some("10.0.0.7"));
+        assert_eq!(admin.user_agent.as_deref(), Some("curl/8.0"));
+        assert_eq!(admin.method, "POST");
+        assert_eq!(admin.path, "/things");
+        assert_eq!(admin.status, 201);
+        assert!(admin.request_id.is_some());
+    }
+
+    #[test]
+    fn test_log_sampler() {
+        let sampler = LogSampler::new(&[
+            RequestLogSampling {
+                prefix: "/health".to_string(),
+                rate: 0.0,
+            },
+            RequestLogSampling {
+                prefix: "/api/users/".to_string(),
+                rate: 0.5,
+            },
+            RequestLogSampling {
+                prefix: "/api/users/export".to_string(),
+                rate: 1.0,
+            },
+        ]);
+
+        assert_eq!(sampler.rate_for("/health"), 0.0);
+        assert_eq!(sampler.rate_for("/health/live"), 0.0);
+        assert_eq!(sampler.rate_for("/healthz"), 1.0);
+        assert_eq!(sampler.rate_for("/api/users/1"), 0.5);
+        assert_eq!(sampler.rate_for("/api/users/export/1"), 1.0);
+        assert_eq!(sampler.rate_for("/version"), 1.0);
+
+        assert!(!sampler.should_log("/health", StatusCode::OK));
+        assert!(sampler.should_log("/health", StatusCode::NOT_FOUND));
+        assert!(sampler.should_log("/health", StatusCode::SERVICE_UNAVAILABLE));
+        assert!(sampler.should_log("/version", StatusCode::OK));
+        assert!(LogSampler::default().should_log("/health", StatusCode::OK));
+    }
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_warn_slow_requests() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let mut config = AppConfig::default();
+        config.server.slow_request_threshold_ms = 20;
+        let router = Router::new()
+            .route("/fast", get(|| async { "fast" }))
+            .route(
+                "/slow/:id",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(30)).await;
+                    "slow"
+                }),
+            );
+        let app = build_stack(router, &config);
+        for uri in ["/fast", "/slow/1"] {
+            let request = Request::get(uri)
+                .header(REQUEST_ID_HEADER, format!("req{}", uri.replace('/', "-")))
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let slow: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .filter(|record| record["fields"]["message"] == "Slow request")
+            .collect();
+        assert_eq!(slow.len(), 1, "{}", output);
+        let fields = &slow[0]["fields"];
+        assert_eq!(fields["route"], "/slow/:id");
+        assert_eq!(fields["request_id"], "req-slow-1");
+        assert_eq!(fields["threshold_ms"], 20);
+        assert!(fields["duration_ms"].as_u64().unwrap() >= 30);
+    }
+
+    #[tokio::test]
+    async fn test_log_requests_records_route_client_ip_and_size() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let logger = RequestLogger {
+            trusted_proxies: vec!["10.0.0.0/8".parse().unwrap()],
+            ..Default::default()
+        };
+        let app = Router::new()
+            .route("/users/:id", get(|| async { "hello" }))
+            .layer(from_fn_with_state(Arc::new(logger), log_requests));
+        let request = |uri: &str| {
+            let mut req = Request::get(uri)
+                .header("x-forwarded-for", "1.2.3.4, 10.0.0.7")
+                .body(Body::empty())
+                .unwrap();
+            req.extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))));
+            req
+        };
+        app.clone()
+            .oneshot(request("/users/42?email=ana@example.com"))
+            .await
+            .unwrap();
+        app.oneshot(request("/missing/ana@example.com"))
+            .await
+            .unwrap();
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let records: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let fields = &records[0]["fields"];
+        assert_eq!(fields["route"], "/users/:id");
+        assert_eq!(fields["client_ip"], "1.2.3.4");
+        assert_eq!(fields["response_bytes"], 5);
+        assert_eq!(fields["status"], "200 OK");
+
+        // Sem rota, o caminho mascarado
+        let fields = &records[1]["fields"];
+        assert_eq!(fields["status"], "404 Not Found");
+        let route = fields["route"].as_str().unwrap();
+        assert!(!route.contains("ana@example.com"), "{}", route);
+        assert!(!output.contains("/users/42"));
+    }
+}
@@ -9,25 +9,372 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+use crate::audit::AuditLog;
+use crate::auth::TokenSigner;
+use crate::build_info::BuildInfo;
+use crate::config::{HealthConfig, TwoFactorConfig};
+use crate::events::EventBus;
+use crate::exports::ExportJobs;
+use crate::health::{self, HealthRegistry, HealthReport};
+use crate::idempotency::Idempotency;
+use crate::quotas::{QuotaError, QuotaService, QuotaUsage};
+use crate::repository::{DbError, UserRepository};
+use crate::storage::Storage;
+use crate::tenant::TenantContext;
+use crate::validation::FieldErrors;
+
+pub mod admin;
+pub mod auth;
+pub mod body_logging;
+pub mod cache;
+pub mod client_ip;
+pub mod concurrency;
+pub mod events;
+pub mod exports;
+pub mod files;
 pub mod handlers;
+#[cfg(feature = "observability")]
+pub mod metrics;
 pub mod middleware;
+pub mod negotiate;
+pub mod pagination;
+#[cfg(feature = "passkeys")]
+pub mod passkeys;
+#[cfg(feature = "profiling")]
+pub mod profiling;
+#[cfg(feature = "protobuf")]
+pub mod proto;
+pub mod quotas;
+pub mod rate_limit;
+pub mod session;
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
 
 /// Estado compartilhado da aplicação
 #[derive(Clone)]
 pub struct AppState {
+    /// Armazenamento de usuários (Postgres ou em memória)
+    pub users: Arc<dyn UserRepository>,
+    /// Banco verificado pelo `/ready`, quando a aplicação usa Postgres
+    #[cfg(feature = "postgres")]
+    pub db: Option<Arc<crate::db::Database>>,
+    /// Todos os bancos por nome (o principal como `primary`), também
+    /// verificados pelo `/ready`
     #[cfg(feature = "postgres")]
-    pub db: Arc<crate::db::Database>,
+    pub databases: crate::db::DatabaseRegistry,
+    /// Verificações de recursos reportadas em `/health`
+    pub health: HealthConfig,
+    /// Verificações feitas em segundo plano, também reportadas em `/health`
+    pub health_checks: HealthRegistry,
+    /// Eventos de domínio publicados pelos handlers
+    pub events: EventBus,
+    /// Webhooks de saída cadastrados
+    #[cfg(feature = "webhooks")]
+    pub webhooks: Arc<dyn crate::webhooks::WebhookStore>,
+    /// Webhooks de entrada; sem provedores até `with_inbound_webhooks`
+    #[cfg(feature = "webhooks")]
+    pub inbound: crate::webhooks::InboundWebhooks,
+    /// Aceita cadastrar webhooks para endereços internos
+    /// (`webhooks.allow_private_destinations`)
+    #[cfg(feature = "webhooks")]
+    pub allow_private_webhooks: bool,
+    /// Respostas guardadas por `Idempotency-Key`
+    pub idempotency: Idempotency,
+    /// Quem alterou o quê (requisições POST, PUT, PATCH e DELETE)
+    pub audit: AuditLog,
+    /// Cache dos endpoints de leitura; desligado até `with_response_cache`
+    pub cache: Option<cache::ResponseCache>,
+    /// Carência entre o pedido de exclusão de uma conta e a remoção; zero
+    /// (o padrão) remove na hora
+    pub deletion_grace: std::time::Duration,
+    /// Tenant das requisições sem `X-Tenant-Id`; `None` torna o header
+    /// obrigatório
+    pub default_tenant: Option<TenantContext>,
+    /// Exportações de usuários em andamento e concluídas
+    pub exports: ExportJobs,
+    /// Cotas mensais por tenant, consumidas pelos handlers
+    pub quotas: QuotaService,
+    /// Arquivos baixados por URL pré-assinada (`/files/*`)
+    pub storage: Storage,
+    /// Tokens emitidos no login dos usuários
+    pub tokens: TokenSigner,
+    /// Emissor e políticas do segundo fator (TOTP)
+    pub two_factor: TwoFactorConfig,
+    /// Cerimônias das passkeys (WebAuthn)
+    #[cfg(feature = "passkeys")]
+    pub passkeys: crate::auth::passkeys::PasskeyService,
+}
+
+impl AppState {
+    pub fn new(users: Arc<dyn UserRepository>, health: HealthConfig) -> Self {
+        Self {
+            users,
+            #[cfg(feature = "postgres")]
+            db: None,
+            #[cfg(feature = "postgres")]
+            databases: crate::db::DatabaseRegistry::new(),
+            health,
+            health_checks: HealthRegistry::new(),
+            events: EventBus::default(),
+            #[cfg(feature = "webhooks")]
+            webhooks: Arc::new(crate::webhooks::InMemoryWebhookStore::new()),
+            #[cfg(feature = "webhooks")]
+            inbound: no_inbound_webhooks(),
+            #[cfg(feature = "webhooks")]
+            allow_private_webhooks: false,
+            idempotency: Idempotency::new(Arc::new(
+                crate::idempotency::InMemoryIdempotencyStore::new(),
+            )),
+            audit: AuditLog::default(),
+            cache: None,
+            deletion_grace: std::time::Duration::ZERO,
+            default_tenant: Some(TenantContext::default_tenant()),
+            exports: ExportJobs::default(),
+            quotas: QuotaService::default(),
+            storage: Storage::from_config(&Default::default()),
+            tokens: TokenSigner::from_config(&Default::default()),
+            two_factor: TwoFactorConfig::default(),
+            #[cfg(feature = "passkeys")]
+            passkeys: default_passkeys(),
+        }
+    }
+
+    /// Estado sobre o Postgres: usuários no banco e `/ready` checando a conexão
+    #[cfg(feature = "postgres")]
+    pub fn with_database(db: Arc<crate::db::Database>, health: HealthConfig) -> Self {
+        let users = Arc::new(
+            crate::repository::PgUserRepository::new(db.pool().clone())
+                .with_retry(db.retry_policy()),
+        );
+        Self {
+            users,
+            #[cfg(feature = "webhooks")]
+            webhooks: Arc::new(crate::webhooks::PgWebhookStore::new(db.pool().clone())),
+            #[cfg(feature = "webhooks")]
+            inbound: no_inbound_webhooks(),
+            #[cfg(feature = "webhooks")]
+            allow_private_webhooks: false,
+            idempotency: Idempotency::new(Arc::new(crate::idempotency::PgIdempotencyStore::new(
+                db.pool().clone(),
+            ))),
+            audit: AuditLog::new(Arc::new(crate::audit::PgAuditStore::new(db.pool().clone()))),
+            cache: None,
+            deletion_grace: std::time::Duration::ZERO,
+            default_tenant: Some(TenantContext::default_tenant()),
+            exports: ExportJobs::default(),
+            quotas: QuotaService::new(Arc::new(crate::quotas::PgQuotaStore::new(
+                db.pool().clone(),
+            ))),
+            storage: Storage::from_config(&Default::default()),
+            tokens: TokenSigner::from_config(&Default::default()),
+            two_factor: TwoFactorConfig::default(),
+            #[cfg(feature = "passkeys")]
+            passkeys: default_passkeys(),
+            databases: crate::db::DatabaseRegistry::new()
+                .with(crate::config::PRIMARY_DATABASE, db.clone()),
+            db: Some(db),
+            health,
+            health_checks: HealthRegistry::new(),
+            events: EventBus::default(),
+        }
+    }
+
+    /// Estado sobre vários bancos; usuários e `/ready` usam o `primary`
+    #[cfg(feature = "postgres")]
+    pub fn with_databases(
+        databases: crate::db::DatabaseRegistry,
+        health: HealthConfig,
+    ) -> anyhow::Result<Self> {
+        let primary = databases
+            .primary()
+            .ok_or_else(|| anyhow::anyhow!("the primary database is not registered"))?;
+        Ok(Self {
+            databases,
+            ..Self::with_database(primary, health)
+        })
+    }
+
+    /// Aceita os webhooks de entrada dos provedores configurados
+    #[cfg(feature = "webhooks")]
+    pub fn with_inbound_webhooks(self, inbound: crate::webhooks::InboundWebhooks) -> Self {
+        Self { inbound, ..self }
+    }
+
+    /// Aceita (ou não) webhooks para endereços internos
+    #[cfg(feature = "webhooks")]
+    pub fn with_private_webhook_destinations(self, allow: bool) -> Self {
+        Self {
+            allow_private_webhooks: allow,
+            ..self
+        }
+    }
+
+    /// Por quanto tempo as respostas idempotentes ficam guardadas
+    pub fn with_idempotency_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.idempotency.ttl = ttl;
+        self
+    }
+
+    /// Exclusões de conta passam a esperar `grace` antes da remoção
+    pub fn with_deletion_grace(self, grace: std::time::Duration) -> Self {
+        Self {
+            deletion_grace: grace,
+            ..self
+        }
+    }
+
+    /// Tenant das requisições sem `X-Tenant-Id` (`None`: header obrigatório)
+    pub fn with_default_tenant(self, tenant: Option<TenantContext>) -> Self {
+        Self {
+            default_tenant: tenant,
+            ..self
+        }
+    }
+
+    /// Limites mensais dos tenants sem linha em `quotas`
+    pub fn with_quota_defaults(self, defaults: crate::config::QuotaConfig) -> Self {
+        Self {
+            quotas: self.quotas.with_defaults(defaults),
+            ..self
+        }
+    }
+
+    /// Onde ficam os arquivos de `/files/*` e o segredo das URLs
+    pub fn with_storage(self, storage: Storage) -> Self {
+        Self { storage, ..self }
+    }
+
+    /// Segredo e validade dos tokens de login (os mesmos do `authenticate`)
+    pub fn with_tokens(self, tokens: TokenSigner) -> Self {
+        Self { tokens, ..self }
+    }
+
+    /// Emissor e políticas por papel do 2FA
+    pub fn with_two_factor(self, two_factor: TwoFactorConfig) -> Self {
+        Self { two_factor, ..self }
+    }
+
+    /// Relying party das passkeys
+    #[cfg(feature = "passkeys")]
+    pub fn with_passkeys(self, passkeys: crate::auth::passkeys::PasskeyService) -> Self {
+        Self { passkeys, ..self }
+    }
+
+    /// Remove, a cada `interval`, as contas cuja carência de exclusão
+    /// venceu, publicando `user.deleted` para cada uma
+    pub fn spawn_deletion_purge(
+        &self,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let users = self.users.clone();
+        let events = self.events.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let purged = match users.purge_deletions(chrono::Utc::now()).await {
+                    Ok(purged) => purged,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Failed to purge deleted accounts");
+                        continue;
+                    }
+                };
+                if !purged.is_empty() {
+                    tracing::info!(purged = purged.len(), "Deleted accounts purged");
+                }
+                for (tenant, id) in purged {
+                    events.publish(&tenant, crate::events::DomainEvent::UserDeleted { id });
+                }
+            }
+        })
+    }
+
+    /// Limpa, a cada `interval`, as contas sem alterações há mais de
+    /// `retention` (ver `UserRepository::cleanup_inactive`), publicando
+    /// `user.updated` ou `user.deleted` para cada uma
+    pub fn spawn_inactive_cleanup(
+        &self,
+        retention: std::time::Duration,
+        action: crate::config::InactiveUserAction,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        use crate::config::InactiveUserAction;
+        use crate::events::DomainEvent;
+
+        let users = self.users.clone();
+        let events = self.events.clone();
+        let retention = chrono::Duration::from_std(retention).unwrap_or(chrono::Duration::MAX);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(before) = chrono::Utc::now().checked_sub_signed(retention) else {
+                    continue;
+                };
+                let affected = match users.cleanup_inactive(before, action).await {
+                    Ok(affected) => affected,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Failed to clean up inactive accounts");
+                        continue;
+                    }
+                };
+                tracing::info!(
+                    ?action,
+                    affected = affected.len(),
+                    %before,
+                    "Inactive accounts cleaned up"
+                );
+                for (tenant, user) in affected {
+                    events.publish(
+                        &tenant,
+                        match action {
+                            InactiveUserAction::Deactivate => DomainEvent::UserUpdated(user),
+                            InactiveUserAction::Purge => DomainEvent::UserDeleted { id: user.id },
+                        },
+                    );
+                }
+            }
+        })
+    }
+
+    /// Liga o cache de respostas, invalidado pelos eventos do barramento
+    pub fn with_response_cache(self, cache: cache::ResponseCache) -> Self {
+        cache.spawn_invalidation(&self.events);
+        Self {
+            cache: Some(cache),
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "passkeys")]
+fn default_passkeys() -> crate::auth::passkeys::PasskeyService {
+    crate::auth::passkeys::PasskeyService::from_config(&Default::default())
+        .expect("the default passkey relying party is valid")
+}
+
+#[cfg(feature = "webhooks")]
+fn no_inbound_webhooks() -> crate::webhooks::InboundWebhooks {
+    crate::webhooks::InboundWebhooks::new(&Default::default()).0
 }
 
 /// Resposta padrão de API
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    /// Detalhes estruturados do erro (ex.: erros por campo)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// Totais das listagens paginadas
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<pagination::PageMeta>,
 }
 
 impl<T: Serialize> ApiResponse<T> {
@@ -36,6 +383,16 @@ impl<T: Serialize> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            details: None,
+            meta: None,
+        }
+    }
+
+    /// Uma página de uma listagem, com os totais em `meta`
+    pub fn paginated(data: T, meta: pagination::PageMeta) -> Self {
+        Self {
+            meta: Some(meta),
+            ..Self::success(data)
         }
     }
 
@@ -44,6 +401,18 @@ impl<T: Serialize> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(message.into()),
+            details: None,
+            meta: None,
+        }
+    }
+
+    pub fn error_with_details(
+        message: impl Into<String>,
+        details: serde_json::Value,
+    ) -> ApiResponse<()> {
+        ApiResponse {
+            details: Some(details),
+            ..ApiResponse::<()>::error(message)
         }
     }
 }
@@ -53,21 +422,82 @@ impl<T: Serialize> ApiResponse<T> {
 pub enum ApiError {
     NotFound(String),
     BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    Conflict(String),
     InternalError(String),
     DatabaseError(String),
+    ValidationFailed(FieldErrors),
+    /// Cota do tenant esgotada (429, com o uso em `details.quota`)
+    QuotaExceeded(QuotaUsage),
+    /// Segundo fator bloqueado por códigos errados, até a data (429)
+    TwoFactorLocked(chrono::DateTime<chrono::Utc>),
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, message) = match self {
-            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
-            ApiError::DatabaseError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        let mut retry_after = None;
+        let (status, message, details) = match self {
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg, None),
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg, None),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg, None),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg, None),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg, None),
+            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg, None),
+            ApiError::DatabaseError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg, None),
+            ApiError::ValidationFailed(errors) => (
+                StatusCode::BAD_REQUEST,
+                "Validation failed".to_string(),
+                Some(serde_json::json!({ "fields": errors })),
+            ),
+            ApiError::QuotaExceeded(usage) => {
+                let seconds = (usage.resets_at - chrono::Utc::now()).num_seconds().max(1);
+                retry_after = Some(seconds);
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    format!("Quota exceeded for {}", usage.resource),
+                    Some(serde_json::json!({ "quota": usage })),
+                )
+            }
+            ApiError::TwoFactorLocked(until) => {
+                retry_after = Some((until - chrono::Utc::now()).num_seconds().max(1));
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "Too many invalid two-factor codes, try again later".to_string(),
+                    None,
+                )
+            }
         };
 
-        let body = Json(ApiResponse::<()>::error(message));
-        (status, body).into_response()
+        let body = match details {
+            Some(details) => Json(ApiResponse::<()>::error_with_details(message, details)),
+            None => Json(ApiResponse::<()>::error(message)),
+        };
+        let mut response = (status, body).into_response();
+        if let Some(seconds) = retry_after {
+            response
+                .headers_mut()
+                .insert(axum::http::header::RETRY_AFTER, seconds.into());
+        }
+        response
+    }
+}
+
+impl From<QuotaError> for ApiError {
+    fn from(err: QuotaError) -> Self {
+        match err {
+            QuotaError::Exceeded(usage) => ApiError::QuotaExceeded(usage),
+            QuotaError::Store(e) => ApiError::InternalError(e.to_string()),
+        }
+    }
+}
+
+impl From<DbError> for ApiError {
+    fn from(err: DbError) -> Self {
+        match err {
+            DbError::NotFound { .. } => ApiError::NotFound(err.to_string()),
+            DbError::Other(e) => ApiError::DatabaseError(e.to_string()),
+        }
     }
 }
 
@@ -81,41 +511,126 @@ impl From<sqlx::Error> for ApiError {
 #[cfg(feature = "api")]
 impl From<validator::ValidationErrors> for ApiError {
     fn from(err: validator::ValidationErrors) -> Self {
-        ApiError::BadRequest(err.to_string())
+        ApiError::ValidationFailed(err.into())
+    }
+}
+
+impl From<FieldErrors> for ApiError {
+    fn from(errors: FieldErrors) -> Self {
+        ApiError::ValidationFailed(errors)
     }
 }
 
 /// Cria o router da API
 pub fn create_router(state: AppState) -> Router {
-    Router::new()
+    let router = Router::new()
         // Health check
         .route("/health", get(health_check))
         .route("/ready", get(readiness_check))
         // Info
         .route("/", get(root))
         .route("/version", get(version))
-        // Users API (se postgres está habilitado)
+        // Users API
         .merge(create_users_router())
-        .with_state(state)
+        // Login dos usuários, senhas e histórico de acessos
+        .merge(auth::router())
+        // Exportações assíncronas
+        .merge(exports::router())
+        // Uso das cotas do tenant
+        .merge(quotas::router())
+        // Downloads por URL pré-assinada
+        .merge(files::router())
+        // Eventos de domínio (SSE)
+        .route("/api/events/stream", get(events::stream));
+
+    #[cfg(feature = "webhooks")]
+    let router = router.merge(webhooks::router());
+
+    // Login sem senha por passkeys
+    #[cfg(feature = "passkeys")]
+    let router = router.merge(passkeys::router());
+
+    // Repetição de POSTs com `Idempotency-Key`
+    let router = router.route_layer(axum::middleware::from_fn_with_state(
+        state.idempotency.clone(),
+        middleware::idempotency,
+    ));
+
+    let router = match state.cache.clone() {
+        Some(cache) => router.route_layer(axum::middleware::from_fn_with_state(
+            cache,
+            cache::cache_responses,
+        )),
+        None => router,
+    };
+
+    // Log de auditoria das escritas, com o status final da resposta
+    let router = router.route_layer(axum::middleware::from_fn_with_state(
+        state.audit.clone(),
+        middleware::audit,
+    ));
+
+    // Métricas por rota (se observability está habilitado)
+    #[cfg(feature = "observability")]
+    let router = router.route_layer(axum::middleware::from_fn(metrics::track_metrics));
+
+    router.with_state(state)
+}
+
+/// Cria o router de gestão (`/metrics`, `/api/admin/*` e `/debug/pprof/*`)
+///
+/// Pode ser servido em uma porta separada ou mesclado ao router principal;
+/// com `admin_token`, todas as rotas exigem `Authorization: Bearer <token>`.
+pub fn create_management_router(admin_token: Option<String>) -> Router {
+    let router = Router::new().route(
+        "/api/admin/log-level",
+        get(admin::get_log_level).put(admin::set_log_level),
+    );
+
+    #[cfg(feature = "observability")]
+    let router = router.route("/metrics", get(metrics::metrics_handler));
+
+    #[cfg(feature = "profiling")]
+    let router = router.route("/debug/pprof/profile", get(profiling::cpu_profile));
+
+    match admin_token {
+        Some(token) => router.route_layer(axum::middleware::from_fn_with_state(
+            Arc::<str>::from(token),
+            middleware::require_bearer_token,
+        )),
+        None => router,
+    }
 }
 
 /// Health check endpoint
-async fn health_check() -> Json<ApiResponse<&'static str>> {
-    Json(ApiResponse::success("healthy"))
+///
+/// Inclui as verificações de recursos configuradas; responde 503 quando
+/// alguma delas está crítica.
+async fn health_check(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ApiResponse<HealthReport>>) {
+    let mut checks = health::system_checks(&state.health);
+    checks.extend(state.health_checks.checks());
+    let report = HealthReport::new(checks);
+    let status = if report.is_critical() {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+
+    (status, Json(ApiResponse::success(report)))
 }
 
 /// Readiness check endpoint
 async fn readiness_check(
     State(state): State<AppState>,
 ) -> Result<Json<ApiResponse<&'static str>>, ApiError> {
+    // Verificar a conexão com cada banco
     #[cfg(feature = "postgres")]
-    {
-        // Verificar conexão com banco
-        state
-            .db
-            .ping()
-            .await
-            .map_err(|e| ApiError::InternalError(format!("Database not ready: {}", e)))?;
+    for (name, db) in state.databases.iter() {
+        db.ping().await.map_err(|e| {
+            ApiError::InternalError(format!("Database '{}' not ready: {}", name, e))
+        })?;
     }
 
     #[cfg(not(feature = "postgres"))]
@@ -139,29 +654,27 @@ async fn root() -> Json<ApiResponse<serde_json::Value>> {
 }
 
 /// Version endpoint
-async fn version() -> Json<ApiResponse<serde_json::Value>> {
-    Json(ApiResponse::success(serde_json::json!({
-        "version": env!("CARGO_PKG_VERSION"),
-        "rust_version": env!("CARGO_PKG_RUST_VERSION"),
-    })))
+async fn version() -> Json<ApiResponse<BuildInfo>> {
+    Json(ApiResponse::success(BuildInfo::current()))
 }
 
 /// Router para endpoints de usuários
 fn create_users_router() -> Router<AppState> {
-    #[cfg(feature = "postgres")]
-    {
-        Router::new()
-            .route("/api/users", get(handlers::list_users))
-            .route("/api/users", post(handlers::create_user))
-            .route("/api/users/:id", get(handlers::get_user))
-            .route(
-                "/api/users/:id",
-                axum::routing::delete(handlers::delete_user),
-            )
-    }
-
-    #[cfg(not(feature = "postgres"))]
-    {
-        Router::new()
-    }
+    Router::new()
+        .route(
+            "/api/users",
+            get(handlers::list_users).post(handlers::create_user),
+        )
+        .route(
+            "/api/users/:id",
+            get(handlers::get_user).delete(handlers::delete_user),
+        )
+        .route("/api/users/:id/anonymize", post(handlers::anonymize_user))
+        .route("/api/users/:id/restore", post(handlers::restore_user))
+        .route("/api/users/:id/activate", post(handlers::activate_user))
+        .route("/api/users/:id/suspend", post(handlers::suspend_user))
+        .route("/api/users/:id/reinstate", post(handlers::reinstate_user))
+        .route("/api/users/:id/deactivate", post(handlers::deactivate_user))
+        .route("/api/users/:id/ban", post(handlers::ban_user))
+        .route("/api/users/:id/export", get(handlers::export_user))
 }
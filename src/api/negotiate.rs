@@ -0,0 +1,293 @@
+//! Negociação de conteúdo pelo header `Accept`
+//!
+//! Além de JSON, as respostas podem sair em MessagePack (feature `msgpack`),
+//! CBOR (feature `cbor`) ou protobuf (feature `protobuf`, com as mensagens de
+//! `proto/users.proto`). O handler recebe o `Encoding` escolhido como
+//! extrator e responde com `Negotiated<T>`. Um `Accept` sem nenhum formato
+//! conhecido recebe JSON, assim como os erros (`ApiError`).
+//!
+//! Na entrada, `Payload<T>` lê o corpo em JSON ou, com `Content-Type:
+//! application/x-protobuf`, em protobuf.
+
+use axum::{
+    async_trait,
+    extract::{FromRequest, FromRequestParts, Request},
+    http::{header, request::Parts, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::convert::Infallible;
+
+use super::ApiError;
+
+/// Conversão de um corpo de resposta na mensagem protobuf correspondente
+#[cfg(feature = "protobuf")]
+pub trait ToProtobuf {
+    /// A mensagem já codificada
+    fn to_protobuf(&self) -> Vec<u8>;
+}
+
+/// Leitura de um corpo de requisição a partir da mensagem protobuf
+#[cfg(feature = "protobuf")]
+pub trait FromProtobuf: Sized {
+    fn from_protobuf(bytes: &[u8]) -> Result<Self, prost::DecodeError>;
+}
+
+/// O que `Negotiated` sabe serializar: qualquer `Serialize` e, com a feature
+/// `protobuf`, também `ToProtobuf`
+#[cfg(feature = "protobuf")]
+pub trait ResponseBody: Serialize + ToProtobuf {}
+#[cfg(feature = "protobuf")]
+impl<T: Serialize + ToProtobuf> ResponseBody for T {}
+#[cfg(not(feature = "protobuf"))]
+pub trait ResponseBody: Serialize {}
+#[cfg(not(feature = "protobuf"))]
+impl<T: Serialize> ResponseBody for T {}
+
+/// O que `Payload` sabe ler: qualquer `DeserializeOwned` e, com a feature
+/// `protobuf`, também `FromProtobuf`
+#[cfg(feature = "protobuf")]
+pub trait RequestBody: DeserializeOwned + FromProtobuf {}
+#[cfg(feature = "protobuf")]
+impl<T: DeserializeOwned + FromProtobuf> RequestBody for T {}
+#[cfg(not(feature = "protobuf"))]
+pub trait RequestBody: DeserializeOwned {}
+#[cfg(not(feature = "protobuf"))]
+impl<T: DeserializeOwned> RequestBody for T {}
+
+/// Formato de uma resposta
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Json,
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+    #[cfg(feature = "cbor")]
+    Cbor,
+    #[cfg(feature = "protobuf")]
+    Protobuf,
+}
+
+impl Encoding {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => "application/msgpack",
+            #[cfg(feature = "cbor")]
+            Self::Cbor => "application/cbor",
+            #[cfg(feature = "protobuf")]
+            Self::Protobuf => "application/x-protobuf",
+        }
+    }
+
+    /// O formato de um media type do `Accept` (sem parâmetros)
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type.to_ascii_lowercase().as_str() {
+            "application/json" | "application/*" | "*/*" => Some(Self::Json),
+            #[cfg(feature = "msgpack")]
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Self::MessagePack)
+            }
+            #[cfg(feature = "cbor")]
+            "application/cbor" => Some(Self::Cbor),
+            #[cfg(feature = "protobuf")]
+            "application/x-protobuf" | "application/protobuf" => Some(Self::Protobuf),
+            _ => None,
+        }
+    }
+
+    /// Escolhe o formato de maior `q` entre os conhecidos; no empate, o que
+    /// vem primeiro. Sem header, ou sem nenhum conhecido, JSON.
+    pub fn from_accept(accept: Option<&str>) -> Self {
+        let mut best: Option<(Self, f32)> = None;
+        for item in accept.unwrap_or("").split(',') {
+            let mut params = item.split(';').map(str::trim);
+            let Some(encoding) = params.next().and_then(Self::from_media_type) else {
+                continue;
+            };
+            let q = params
+                .filter_map(|param| param.strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+                best = Some((encoding, q));
+            }
+        }
+        best.map(|(encoding, _)| encoding).unwrap_or_default()
+    }
+
+    /// Serializa `value` neste formato
+    pub fn encode<T: ResponseBody>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "cbor")]
+            Self::Cbor => {
+                let mut body = Vec::new();
+                ciborium::into_writer(value, &mut body).map_err(|e| e.to_string())?;
+                Ok(body)
+            }
+            #[cfg(feature = "protobuf")]
+            Self::Protobuf => Ok(value.to_protobuf()),
+        }
+    }
+}
+
+/// Lê o formato do header `Accept`; nunca rejeita a requisição
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Encoding {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let accept = parts
+            .headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok());
+        Ok(Self::from_accept(accept))
+    }
+}
+
+/// Resposta serializada no formato negociado, com `Vary: Accept`
+#[derive(Debug, Clone)]
+pub struct Negotiated<T> {
+    pub encoding: Encoding,
+    pub value: T,
+}
+
+impl<T> Negotiated<T> {
+    pub fn new(encoding: Encoding, value: T) -> Self {
+        Self { encoding, value }
+    }
+}
+
+impl<T: ResponseBody> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        match self.encoding.encode(&self.value) {
+            Ok(body) => (
+                [
+                    (
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(self.encoding.content_type()),
+                    ),
+                    (header::VARY, HeaderValue::from_static("accept")),
+                ],
+                body,
+            )
+                .into_response(),
+            Err(e) => {
+                ApiError::InternalError(format!("Failed to encode response: {}", e)).into_response()
+            }
+        }
+    }
+}
+
+/// Corpo da requisição em JSON ou, conforme o `Content-Type`, protobuf
+#[derive(Debug, Clone)]
+pub struct Payload<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for Payload<T>
+where
+    S: Send + Sync,
+    T: RequestBody,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        #[cfg(feature = "protobuf")]
+        {
+            let content_type = req
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(';').next())
+                .map(|value| value.trim().to_ascii_lowercase());
+            if content_type.as_deref().and_then(Encoding::from_media_type)
+                == Some(Encoding::Protobuf)
+            {
+                let bytes = axum::body::Bytes::from_request(req, state)
+                    .await
+                    .map_err(IntoResponse::into_response)?;
+                return T::from_protobuf(&bytes).map(Payload).map_err(|e| {
+                    ApiError::BadRequest(format!("Invalid protobuf body: {}", e)).into_response()
+                });
+            }
+        }
+
+        Json::<T>::from_request(req, state)
+            .await
+            .map(|Json(value)| Payload(value))
+            .map_err(IntoResponse::into_response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_accept() {
+        assert_eq!(Encoding::from_accept(None), Encoding::Json);
+        assert_eq!(Encoding::from_accept(Some("")), Encoding::Json);
+        assert_eq!(Encoding::from_accept(Some("text/html")), Encoding::Json);
+        assert_eq!(Encoding::from_accept(Some("*/*")), Encoding::Json);
+        assert_eq!(
+            Encoding::from_accept(Some("text/html, application/json;q=0.9")),
+            Encoding::Json
+        );
+    }
+
+    #[cfg(all(feature = "msgpack", feature = "cbor"))]
+    #[test]
+    fn test_from_accept_binary_formats() {
+        assert_eq!(
+            Encoding::from_accept(Some("application/msgpack")),
+            Encoding::MessagePack
+        );
+        assert_eq!(
+            Encoding::from_accept(Some("Application/CBOR")),
+            Encoding::Cbor
+        );
+        assert_eq!(
+            Encoding::from_accept(Some("application/json;q=0.5, application/cbor")),
+            Encoding::Cbor
+        );
+        assert_eq!(
+            Encoding::from_accept(Some("application/x-msgpack;q=0.8, */*;q=0.1")),
+            Encoding::MessagePack
+        );
+        assert_eq!(
+            Encoding::from_accept(Some("application/cbor;q=0, application/json")),
+            Encoding::Json
+        );
+        assert_eq!(
+            Encoding::from_accept(Some("application/cbor, application/msgpack")),
+            Encoding::Cbor
+        );
+    }
+
+    #[cfg(all(feature = "msgpack", feature = "cbor"))]
+    #[test]
+    fn test_encode_round_trip() {
+        let value = crate::api::ApiResponse::success(crate::api::handlers::UserResponse {
+            id: 1,
+            name: "Ana".to_string(),
+            email: "ana@example.com".to_string(),
+            status: crate::models::UserStatus::Active,
+            active: true,
+            deletion_scheduled_at: None,
+        });
+        let json = serde_json::to_value(&value).unwrap();
+
+        let msgpack = Encoding::MessagePack.encode(&value).unwrap();
+        let decoded: serde_json::Value = rmp_serde::from_slice(&msgpack).unwrap();
+        assert_eq!(decoded, json);
+
+        let cbor = Encoding::Cbor.encode(&value).unwrap();
+        let decoded: serde_json::Value = ciborium::from_reader(cbor.as_slice()).unwrap();
+        assert_eq!(decoded, json);
+    }
+}
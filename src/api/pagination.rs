@@ -0,0 +1,175 @@
+//! Paginação das listagens (`?page=2&per_page=20`)
+//!
+//! A resposta leva os totais em `meta` e os links das páginas vizinhas no
+//! header `Link` (RFC 5988), montados a partir da própria URL da requisição:
+//! os outros parâmetros da query são mantidos.
+
+use axum::http::{HeaderValue, Uri};
+use serde::{Deserialize, Serialize};
+
+/// Itens por página quando `per_page` não é informado
+pub const DEFAULT_PER_PAGE: u32 = 20;
+
+/// Maior `per_page` aceito (valores acima são reduzidos a ele)
+pub const MAX_PER_PAGE: u32 = 100;
+
+/// Parâmetros de paginação da query
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct Pagination {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+impl Pagination {
+    /// Página pedida, a partir de 1
+    pub fn page(&self) -> u32 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn per_page(&self) -> u32 {
+        self.per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE)
+    }
+
+    /// Quantos itens pular até a página pedida
+    pub fn offset(&self) -> i64 {
+        i64::from(self.page() - 1) * i64::from(self.per_page())
+    }
+
+    /// Os totais da página, dado o total de itens
+    pub fn meta(&self, total: i64) -> PageMeta {
+        let per_page = i64::from(self.per_page());
+        PageMeta {
+            total,
+            page: self.page(),
+            per_page: self.per_page(),
+            total_pages: (total.max(0) + per_page - 1) / per_page,
+        }
+    }
+}
+
+/// Totais de uma listagem paginada (`meta` na resposta)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageMeta {
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+    pub total_pages: i64,
+}
+
+impl PageMeta {
+    /// Header `Link` com `first`, `prev`, `next` e `last`, conforme existam;
+    /// `None` se não houver nenhum
+    pub fn link_header(&self, uri: &Uri) -> Option<HeaderValue> {
+        let page = i64::from(self.page);
+        let mut links = Vec::new();
+        if self.total_pages > 0 {
+            links.push((1, "first"));
+        }
+        if page > 1 {
+            links.push(((page - 1).min(self.total_pages.max(1)), "prev"));
+        }
+        if page < self.total_pages {
+            links.push((page + 1, "next"));
+        }
+        if self.total_pages > 0 {
+            links.push((self.total_pages, "last"));
+        }
+        if links.is_empty() {
+            return None;
+        }
+
+        let value = links
+            .into_iter()
+            .map(|(page, rel)| format!("<{}>; rel=\"{}\"", self.page_uri(uri, page), rel))
+            .collect::<Vec<_>>()
+            .join(", ");
+        HeaderValue::from_str(&value).ok()
+    }
+
+    /// A URL da requisição apontando para `page`
+    fn page_uri(&self, uri: &Uri, page: i64) -> String {
+        let mut query: Vec<&str> = uri
+            .query()
+            .unwrap_or("")
+            .split('&')
+            .filter(|pair| {
+                let key = pair.split('=').next().unwrap_or("");
+                !pair.is_empty() && key != "page" && key != "per_page"
+            })
+            .collect();
+        let paging = format!("page={}&per_page={}", page, self.per_page);
+        query.push(&paging);
+
+        format!("{}?{}", uri.path(), query.join("&"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pagination(page: u32, per_page: u32) -> Pagination {
+        Pagination {
+            page: Some(page),
+            per_page: Some(per_page),
+        }
+    }
+
+    #[test]
+    fn test_pagination_defaults_and_limits() {
+        let default = Pagination::default();
+        assert_eq!((default.page(), default.per_page()), (1, DEFAULT_PER_PAGE));
+        assert_eq!(default.offset(), 0);
+
+        let clamped = pagination(0, 1000);
+        assert_eq!((clamped.page(), clamped.per_page()), (1, MAX_PER_PAGE));
+        assert_eq!(pagination(3, 10).offset(), 20);
+    }
+
+    #[test]
+    fn test_page_meta() {
+        let meta = pagination(2, 10).meta(25);
+        assert_eq!(
+            meta,
+            PageMeta {
+                total: 25,
+                page: 2,
+                per_page: 10,
+                total_pages: 3,
+            }
+        );
+        assert_eq!(pagination(1, 10).meta(0).total_pages, 0);
+        assert_eq!(pagination(1, 10).meta(10).total_pages, 1);
+    }
+
+    #[test]
+    fn test_link_header() {
+        let uri: Uri = "/api/users?fields=id&page=2&per_page=10".parse().unwrap();
+        let link = pagination(2, 10).meta(25).link_header(&uri).unwrap();
+        assert_eq!(
+            link,
+            "</api/users?fields=id&page=1&per_page=10>; rel=\"first\", \
+             </api/users?fields=id&page=1&per_page=10>; rel=\"prev\", \
+             </api/users?fields=id&page=3&per_page=10>; rel=\"next\", \
+             </api/users?fields=id&page=3&per_page=10>; rel=\"last\""
+        );
+
+        let uri: Uri = "/api/users".parse().unwrap();
+        let link = Pagination::default().meta(5).link_header(&uri).unwrap();
+        assert_eq!(
+            link,
+            "</api/users?page=1&per_page=20>; rel=\"first\", \
+             </api/users?page=1&per_page=20>; rel=\"last\""
+        );
+        assert_eq!(Pagination::default().meta(0).link_header(&uri), None);
+
+        // Além da última página, `prev` aponta para a última
+        let link = pagination(9, 10).meta(25).link_header(&uri).unwrap();
+        assert!(link
+            .to_str()
+            .unwrap()
+            .contains("</api/users?page=3&per_page=10>; rel=\"prev\""));
+    }
+}
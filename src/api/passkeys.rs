@@ -0,0 +1,341 @@
+//! Login sem senha por passkeys (feature "passkeys")
+//!
+//! - `POST /api/auth/passkeys/register/start` e `.../register/finish`:
+//!   cadastro de uma passkey do usuário do token
+//! - `GET /api/auth/passkeys`: as passkeys do usuário do token
+//! - `POST /api/auth/passkeys/login/start` e `.../login/finish`: login com
+//!   uma passkey, no lugar de senha e 2FA; devolve o mesmo token de
+//!   `POST /api/auth/login`
+//!
+//! As etapas `start` devolvem as opções para `navigator.credentials.create`
+//! ou `.get` e um `ceremony_id`, repetido na etapa `finish` junto com a
+//! resposta do autenticador.
+
+use crate::api::auth::{login_response, record_login, token_user, LoginResponse};
+use crate::api::{ApiError, ApiResponse, AppState};
+use crate::audit::Principal;
+use crate::auth::passkeys::PasskeyError;
+use crate::models::StoredPasskey;
+use crate::tenant::{LoginTenant, TenantContext};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use webauthn_rs::prelude::{
+    CreationChallengeResponse, PublicKeyCredential, RegisterPublicKeyCredential,
+    RequestChallengeResponse,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/auth/passkeys", get(list_passkeys))
+        .route(
+            "/api/auth/passkeys/register/start",
+            post(start_registration),
+        )
+        .route(
+            "/api/auth/passkeys/register/finish",
+            post(finish_registration),
+        )
+        .route("/api/auth/passkeys/login/start", post(start_login))
+        .route("/api/auth/passkeys/login/finish", post(finish_login))
+}
+
+/// Opções de uma cerimônia para o navegador
+#[derive(Debug, Serialize)]
+pub struct PasskeyChallenge<T> {
+    pub ceremony_id: String,
+    pub options: T,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FinishRegistrationRequest {
+    pub ceremony_id: String,
+    /// Nome para reconhecer a passkey depois (ex.: "Notebook")
+    #[serde(default = "default_passkey_name")]
+    pub name: String,
+    pub credential: RegisterPublicKeyCredential,
+}
+
+fn default_passkey_name() -> String {
+    "Passkey".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartLoginRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FinishLoginRequest {
+    pub ceremony_id: String,
+    pub credential: PublicKeyCredential,
+}
+
+/// Passkeys do usuário do token
+async fn list_passkeys(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+) -> Result<Json<ApiResponse<Vec<StoredPasskey>>>, ApiError> {
+    let id = passkey_user(&principal, &tenant)?;
+    let passkeys = state
+        .users
+        .passkeys(&tenant, id)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    Ok(Json(ApiResponse::success(passkeys)))
+}
+
+/// Começa o cadastro de uma passkey do usuário do token
+async fn start_registration(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+) -> Result<Json<ApiResponse<PasskeyChallenge<CreationChallengeResponse>>>, ApiError> {
+    let id = passkey_user(&principal, &tenant)?;
+    let user = state.users.get(&tenant, id).await?;
+    let existing = state
+        .users
+        .passkeys(&tenant, id)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    let (ceremony_id, options) = state
+        .passkeys
+        .start_registration(&tenant, id, &user.email, &user.name, &existing)
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+    Ok(Json(ApiResponse::success(PasskeyChallenge {
+        ceremony_id,
+        options,
+    })))
+}
+
+/// Confere a resposta do autenticador e guarda a passkey
+async fn finish_registration(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+    principal: Principal,
+    Json(payload): Json<FinishRegistrationRequest>,
+) -> Result<Json<ApiResponse<StoredPasskey>>, ApiError> {
+    let id = passkey_user(&principal, &tenant)?;
+    let ceremony = state
+        .passkeys
+        .take(&payload.ceremony_id)
+        .filter(|c| c.user_id == id && c.tenant == tenant.id())
+        .ok_or_else(|| ApiError::BadRequest(PasskeyError::UnknownCeremony.to_string()))?;
+
+    let passkey = state
+        .passkeys
+        .finish_registration(&ceremony, &payload.credential, &payload.name)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let added = state
+        .users
+        .add_passkey(&tenant, id, &passkey)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    if !added {
+        return Err(ApiError::NotFound(format!("User with id {} not found", id)));
+    }
+
+    Ok(Json(ApiResponse::success(passkey)))
+}
+
+/// Começa o login com uma das passkeys do usuário do email
+///
+/// Email desconhecido, usuário inativo ou sem passkeys respondem 401.
+async fn start_login(
+    State(state): State<AppState>,
+    LoginTenant(tenant): LoginTenant,
+    Json(payload): Json<StartLoginRequest>,
+) -> Result<Json<ApiResponse<PasskeyChallenge<RequestChallengeResponse>>>, ApiError> {
+    let invalid = || ApiError::Unauthorized("Invalid credentials".to_string());
+    let user = state
+        .users
+        .find_by_email(&tenant, &payload.email)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+        .filter(|user| user.is_active() && user.deletion_scheduled_at.is_none())
+        .ok_or_else(invalid)?;
+    let passkeys = state
+        .users
+        .passkeys(&tenant, user.id)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+    if passkeys.is_empty() {
+        return Err(invalid());
+    }
+
+    let (ceremony_id, options) = state
+        .passkeys
+        .start_authentication(&tenant, user.id, &passkeys)
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+    Ok(Json(ApiResponse::success(PasskeyChallenge {
+        ceremony_id,
+        options,
+    })))
+}
+
+/// Confere a resposta do autenticador e devolve um token de acesso
+async fn finish_login(
+    State(state): State<AppState>,
+    LoginTenant(tenant): LoginTenant,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(payload): Json<FinishLoginRequest>,
+) -> Result<Json<ApiResponse<LoginResponse>>, ApiError> {
+    let invalid = || ApiError::Unauthorized("Invalid credentials".to_string());
+    let ceremony = state
+        .passkeys
+        .take(&payload.ceremony_id)
+        .filter(|c| c.tenant == tenant.id())
+        .ok_or_else(invalid)?;
+    let mut user = state
+        .users
+        .find_by_id(&tenant, ceremony.user_id)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+        .filter(|user| user.is_active() && user.deletion_scheduled_at.is_none())
+        .ok_or_else(invalid)?;
+    let passkeys = state
+        .users
+        .passkeys(&tenant, user.id)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    let used = state
+        .passkeys
+        .finish_authentication(&ceremony, &payload.credential, &passkeys);
+    let occurred_at = record_login(
+        &state,
+        &tenant,
+        user.id,
+        used.is_ok(),
+        connect_info,
+        &headers,
+    )
+    .await;
+    let used = used.map_err(|e| {
+        tracing::debug!(error = %e, user_id = user.id, "Passkey login rejected");
+        invalid()
+    })?;
+    state
+        .users
+        .update_passkey(
+            &tenant,
+            user.id,
+            &used.credential_id,
+            &used.passkey,
+            occurred_at,
+        )
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    user.last_login_at = Some(occurred_at);
+    Ok(Json(ApiResponse::success(login_response(
+        &state, &tenant, user, false,
+    ))))
+}
+
+/// Usuário do token; quem ainda precisa ativar o 2FA não cadastra passkeys
+fn passkey_user(principal: &Principal, tenant: &TenantContext) -> Result<i32, ApiError> {
+    match token_user(principal, tenant)? {
+        (id, false) => Ok(id),
+        (_, true) => Err(ApiError::Forbidden(
+            "Two-factor authentication must be enabled first".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::middleware::build_stack;
+    use crate::api::{create_router, AppState};
+    use crate::config::AppConfig;
+    use crate::models::{DbUser, DEFAULT_ROLE};
+    use crate::repository::InMemoryUserRepository;
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        Router,
+    };
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    async fn post(
+        app: &Router,
+        uri: &str,
+        token: Option<&str>,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::post(uri).header(header::CONTENT_TYPE, "application/json");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_passkey_ceremonies_require_a_user() {
+        let config = AppConfig::default();
+        let state = AppState::new(
+            Arc::new(InMemoryUserRepository::with_users([DbUser {
+                id: 1,
+                name: "Ana".to_string(),
+                email: "ana@example.com".to_string(),
+                status: crate::models::UserStatus::Active,
+                created_at: None,
+                deletion_scheduled_at: None,
+                last_login_at: None,
+                role: DEFAULT_ROLE.to_string(),
+            }])),
+            Default::default(),
+        );
+        let token = state
+            .tokens
+            .issue(
+                &state
+                    .tokens
+                    .claims(1, &crate::tenant::TenantContext::default_tenant()),
+            )
+            .token;
+        let app = build_stack(create_router(state), &config);
+
+        let start = "/api/auth/passkeys/register/start";
+        let (status, _) = post(&app, start, None, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, body) = post(&app, start, Some(&token), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["ceremony_id"].is_string());
+        assert_eq!(
+            body["data"]["options"]["publicKey"]["user"]["name"],
+            "ana@example.com"
+        );
+
+        // Sem passkeys cadastradas, o login por passkey não começa
+        let (status, _) = post(
+            &app,
+            "/api/auth/passkeys/login/start",
+            None,
+            serde_json::json!({ "email": "ana@example.com" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
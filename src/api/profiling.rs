@@ -0,0 +1,139 @@
+//! Profiling de CPU sob demanda (feature "profiling")
+//!
+//! `GET /debug/pprof/profile?seconds=10` amostra a CPU do processo pelo
+//! tempo pedido e devolve um perfil pprof (protobuf), compatível com
+//! `go tool pprof`; com `format=flamegraph` devolve um SVG.
+
+use crate::api::ApiError;
+use axum::{
+    extract::Query,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use pprof::protos::Message;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Duração padrão da amostragem, em segundos
+const DEFAULT_SECONDS: u64 = 10;
+
+/// Duração máxima aceita, em segundos
+const MAX_SECONDS: u64 = 60;
+
+/// Frequência de amostragem, em Hz
+const FREQUENCY: i32 = 99;
+
+/// Só um perfil pode ser coletado por vez
+static PROFILING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileFormat {
+    #[default]
+    Pprof,
+    Flamegraph,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProfileParams {
+    pub seconds: Option<u64>,
+    #[serde(default)]
+    pub format: ProfileFormat,
+}
+
+/// Libera o slot de profiling mesmo em caso de erro
+struct ProfilingSlot;
+
+impl ProfilingSlot {
+    fn acquire() -> Option<Self> {
+        PROFILING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| Self)
+    }
+}
+
+impl Drop for ProfilingSlot {
+    fn drop(&mut self) {
+        PROFILING.store(false, Ordering::Release);
+    }
+}
+
+/// Coleta um perfil de CPU
+pub async fn cpu_profile(Query(params): Query<ProfileParams>) -> Result<Response, ApiError> {
+    let seconds = params.seconds.unwrap_or(DEFAULT_SECONDS);
+    if seconds == 0 || seconds > MAX_SECONDS {
+        return Err(ApiError::BadRequest(format!(
+            "seconds must be between 1 and {}",
+            MAX_SECONDS
+        )));
+    }
+
+    let slot = ProfilingSlot::acquire()
+        .ok_or_else(|| ApiError::Conflict("A profile is already being collected".to_string()))?;
+
+    tracing::info!(seconds, format = ?params.format, "Collecting CPU profile");
+
+    // O guard do pprof não é `Send`; a coleta roda em uma thread própria
+    let format = params.format;
+    let body = tokio::task::spawn_blocking(move || {
+        let _slot = slot;
+        collect(Duration::from_secs(seconds), format)
+    })
+    .await
+    .map_err(|e| ApiError::InternalError(format!("Profiling task failed: {}", e)))?
+    .map_err(|e| ApiError::InternalError(format!("Profiling failed: {}", e)))?;
+
+    let content_type = match format {
+        ProfileFormat::Pprof => "application/octet-stream",
+        ProfileFormat::Flamegraph => "image/svg+xml",
+    };
+
+    Ok(([(header::CONTENT_TYPE, content_type)], body).into_response())
+}
+
+fn collect(duration: Duration, format: ProfileFormat) -> pprof::Result<Vec<u8>> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(FREQUENCY)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()?;
+
+    std::thread::sleep(duration);
+
+    let report = guard.report().build()?;
+
+    match format {
+        ProfileFormat::Pprof => Ok(report.pprof()?.encode_to_vec()),
+        ProfileFormat::Flamegraph => {
+            let mut svg = Vec::new();
+            report.flamegraph(&mut svg)?;
+            Ok(svg)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiling_slot_is_exclusive() {
+        let slot = ProfilingSlot::acquire().unwrap();
+        assert!(ProfilingSlot::acquire().is_none());
+        drop(slot);
+        assert!(ProfilingSlot::acquire().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_out_of_range_duration() {
+        let params = ProfileParams {
+            seconds: Some(MAX_SECONDS + 1),
+            format: ProfileFormat::Pprof,
+        };
+        assert!(matches!(
+            cpu_profile(Query(params)).await,
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
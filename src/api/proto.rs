@@ -0,0 +1,163 @@
+//! Mensagens protobuf da API de usuários (`proto/users.proto`)
+//!
+//! O código das mensagens é gerado pelo `build.rs`; aqui ficam as conversões
+//! de e para os tipos da API, usadas por `Negotiated` e `Payload`.
+
+use prost::Message;
+
+use super::handlers::{CreateUserRequest as CreateUserBody, UserResponse};
+use super::negotiate::{FromProtobuf, ToProtobuf};
+use super::{pagination, ApiResponse};
+use crate::models::UserProjection;
+#[cfg(test)]
+use crate::models::UserStatus;
+
+include!(concat!(env!("OUT_DIR"), "/users.v1.rs"));
+
+impl From<&UserResponse> for User {
+    fn from(user: &UserResponse) -> Self {
+        Self {
+            id: Some(user.id),
+            name: Some(user.name.clone()),
+            email: Some(user.email.clone()),
+            active: Some(user.active),
+            status: Some(user.status.to_string()),
+            deletion_scheduled_at: user
+                .deletion_scheduled_at
+                .map(|at| at.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)),
+        }
+    }
+}
+
+/// Um usuário com só os campos de `?fields=`
+impl From<&UserProjection> for User {
+    fn from(fields: &UserProjection) -> Self {
+        let string = |name: &str| fields.get(name).and_then(|v| v.as_str()).map(String::from);
+        Self {
+            id: fields
+                .get("id")
+                .and_then(|v| v.as_i64())
+                .and_then(|id| i32::try_from(id).ok()),
+            name: string("name"),
+            email: string("email"),
+            active: fields.get("active").and_then(|v| v.as_bool()),
+            status: string("status"),
+            deletion_scheduled_at: string("deletion_scheduled_at"),
+        }
+    }
+}
+
+impl From<pagination::PageMeta> for PageMeta {
+    fn from(meta: pagination::PageMeta) -> Self {
+        Self {
+            total: meta.total,
+            page: meta.page,
+            per_page: meta.per_page,
+            total_pages: meta.total_pages,
+        }
+    }
+}
+
+impl ToProtobuf for ApiResponse<UserResponse> {
+    fn to_protobuf(&self) -> Vec<u8> {
+        UserReply {
+            user: self.data.as_ref().map(User::from),
+        }
+        .encode_to_vec()
+    }
+}
+
+impl ToProtobuf for ApiResponse<Vec<UserResponse>> {
+    fn to_protobuf(&self) -> Vec<u8> {
+        user_list(self.data.iter().flatten().map(User::from), self.meta)
+    }
+}
+
+impl ToProtobuf for ApiResponse<Vec<UserProjection>> {
+    fn to_protobuf(&self) -> Vec<u8> {
+        user_list(self.data.iter().flatten().map(User::from), self.meta)
+    }
+}
+
+impl ToProtobuf for ApiResponse<()> {
+    fn to_protobuf(&self) -> Vec<u8> {
+        Empty {}.encode_to_vec()
+    }
+}
+
+fn user_list(users: impl Iterator<Item = User>, meta: Option<pagination::PageMeta>) -> Vec<u8> {
+    UserList {
+        users: users.collect(),
+        meta: meta.map(PageMeta::from),
+    }
+    .encode_to_vec()
+}
+
+impl FromProtobuf for CreateUserBody {
+    fn from_protobuf(bytes: &[u8]) -> Result<Self, prost::DecodeError> {
+        let request = CreateUserRequest::decode(bytes)?;
+        Ok(Self {
+            name: request.name,
+            email: request.email,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_messages() {
+        let user = UserResponse {
+            id: 7,
+            name: "Ana".to_string(),
+            email: "ana@example.com".to_string(),
+            status: UserStatus::Active,
+            active: true,
+            deletion_scheduled_at: None,
+        };
+        let reply = UserReply::decode(ApiResponse::success(user).to_protobuf().as_slice()).unwrap();
+        assert_eq!(
+            reply.user,
+            Some(User {
+                id: Some(7),
+                name: Some("Ana".to_string()),
+                email: Some("ana@example.com".to_string()),
+                active: Some(true),
+                status: Some("active".to_string()),
+                deletion_scheduled_at: None,
+            })
+        );
+
+        let mut fields = UserProjection::new();
+        fields.insert("id".to_string(), 7.into());
+        fields.insert("name".to_string(), "Ana".into());
+        let meta = pagination::Pagination::default().meta(1);
+        let list = UserList::decode(
+            ApiResponse::paginated(vec![fields], meta)
+                .to_protobuf()
+                .as_slice(),
+        )
+        .unwrap();
+        assert_eq!(
+            list.users,
+            vec![User {
+                id: Some(7),
+                name: Some("Ana".to_string()),
+                ..Default::default()
+            }]
+        );
+        assert_eq!(list.meta.unwrap().total_pages, 1);
+
+        let body = CreateUserRequest {
+            name: "Bia".to_string(),
+            email: "bia@example.com".to_string(),
+        };
+        let parsed = CreateUserBody::from_protobuf(&body.encode_to_vec()).unwrap();
+        assert_eq!(
+            (parsed.name.as_str(), parsed.email.as_str()),
+            ("Bia", "bia@example.com")
+        );
+    }
+}
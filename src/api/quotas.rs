@@ -0,0 +1,103 @@
+//! Uso das cotas do tenant (`GET /api/quotas`)
+
+use crate::api::{ApiError, ApiResponse, AppState};
+use crate::quotas::QuotaUsage;
+use crate::tenant::TenantContext;
+use axum::{extract::State, routing::get, Json, Router};
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/api/quotas", get(list_quotas))
+}
+
+/// Limite, uso e renovação de cada cota no mês corrente
+async fn list_quotas(
+    State(state): State<AppState>,
+    tenant: TenantContext,
+) -> Result<Json<ApiResponse<Vec<QuotaUsage>>>, ApiError> {
+    let usage = state
+        .quotas
+        .usage(&tenant)
+        .await
+        .map_err(|e| ApiError::InternalError(e.to_string()))?;
+    Ok(Json(ApiResponse::success(usage)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use crate::config::QuotaConfig;
+    use crate::repository::InMemoryUserRepository;
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn create(name: &str) -> Request<Body> {
+        Request::post("/api/users")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(format!(
+                r#"{{"name":"{0}","email":"{0}@example.com"}}"#,
+                name
+            )))
+            .unwrap()
+    }
+
+    async fn send(app: &Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_user_creation_quota() {
+        let state = AppState::new(Arc::new(InMemoryUserRepository::new()), Default::default())
+            .with_quota_defaults(QuotaConfig {
+                users_created_per_month: Some(2),
+                exports_per_month: None,
+            });
+        let app = create_router(state);
+
+        assert_eq!(send(&app, create("ana")).await.0, StatusCode::OK);
+        // Email repetido: a criação falha e não gasta a cota
+        assert!(!send(&app, create("ana")).await.0.is_success());
+        assert_eq!(send(&app, create("bia")).await.0, StatusCode::OK);
+
+        let response = app.clone().oneshot(create("caio")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"], "Quota exceeded for users_created");
+        assert_eq!(body["details"]["quota"]["limit"], 2);
+        assert_eq!(body["details"]["quota"]["used"], 2);
+
+        let (status, body) = send(
+            &app,
+            Request::get("/api/quotas").body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"][0]["resource"], "users_created");
+        assert_eq!(body["data"][0]["used"], 2);
+        assert_eq!(body["data"][1]["resource"], "exports");
+        assert_eq!(body["data"][1]["limit"], serde_json::Value::Null);
+
+        // Cada tenant tem a sua cota
+        let mut request = create("caio");
+        request
+            .headers_mut()
+            .insert(crate::tenant::TENANT_HEADER, "other".parse().unwrap());
+        request
+            .extensions_mut()
+            .insert(crate::audit::Principal::Admin);
+        assert_eq!(send(&app, request).await.0, StatusCode::OK);
+    }
+}
@@ -0,0 +1,463 @@
+//! Limite de requisições por cliente
+//!
+//! O cliente é quem o `authenticate` identificou (o `Principal` nas
+//! extensions): o usuário do token, com o tenant dele, ou o admin. Anônimos
+//! são identificados pelo IP (`client_ip`, atrás de `server.trusted_proxies`),
+//! nunca por um valor que o próprio cliente escolhe e pode trocar a cada
+//! requisição. Sem `ConnectInfo` (ex.: testes com `oneshot`), os anônimos
+//! dividem um só limite. Estourado o limite, a requisição recebe 429 com
+//! `Retry-After`.
+//!
+//! A contagem fica num `RateLimitStore`: o `RateLimiter` (token bucket em
+//! memória) conta por instância; o `RedisRateLimiter` (feature "redis") usa
+//! uma janela deslizante no Redis, compartilhada por todas as réplicas.
+//! Limites por cliente diferentes do padrão vêm da tabela
+//! `rate_limit_overrides` (`RateLimitOverrides`).
+
+use crate::api::{client_ip, ApiResponse};
+use crate::audit::Principal;
+use crate::config::{AppConfig, IpRange};
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+/// Baldes guardados antes de descartar os que já estão cheios
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// `Ok` libera a requisição; `Err` traz os segundos até a próxima
+pub type Verdict = Result<(), u64>;
+
+/// Onde as requisições de cada cliente são contadas
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Conta uma requisição de `key`, que pode fazer `limit` por minuto
+    async fn hit(&self, key: &str, limit: u32) -> anyhow::Result<Verdict>;
+}
+
+/// Identificação do cliente no limitador e em `rate_limit_overrides`:
+/// `user:<tenant>/<id>`, `admin` ou `ip:<endereço>`
+pub fn client_key(principal: Option<&Principal>, ip: Option<IpAddr>) -> String {
+    match (principal, ip) {
+        (
+            Some(
+                Principal::User { id, tenant }
+                | Principal::Enrolling { id, tenant }
+                | Principal::Impersonating { id, tenant, .. },
+            ),
+            _,
+        ) => format!("user:{}/{}", tenant, id),
+        (Some(Principal::Admin), _) => "admin".to_string(),
+        (_, Some(ip)) => format!("ip:{}", ip),
+        (_, None) => "ip:unknown".to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    /// Fichas repostas por segundo
+    fn refill_rate(&self) -> f64 {
+        self.capacity / 60.0
+    }
+}
+
+/// Token bucket em memória, compartilhado entre as cópias do router
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    limit: u32,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(requests: u32) -> Self {
+        Self {
+            limit: requests,
+            buckets: Arc::default(),
+        }
+    }
+
+    /// Consome uma ficha do cliente (pelo IP) com o limite padrão
+    pub fn check(&self, client: Option<IpAddr>) -> Verdict {
+        self.check_key(&client_key(None, client), self.limit)
+    }
+
+    /// Consome uma ficha de `key`, cujo balde comporta `limit` fichas
+    pub fn check_key(&self, key: &str, limit: u32) -> Verdict {
+        let now = Instant::now();
+        let capacity = f64::from(limit.max(1));
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| {
+                bucket.tokens
+                    + now.duration_since(bucket.updated_at).as_secs_f64() * bucket.refill_rate()
+                    < bucket.capacity
+            });
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            capacity,
+            updated_at: now,
+        });
+        // O limite do cliente pode ter mudado (override)
+        bucket.capacity = capacity;
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * bucket.refill_rate()).min(capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / bucket.refill_rate()).ceil() as u64)
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RateLimiter {
+    async fn hit(&self, key: &str, limit: u32) -> anyhow::Result<Verdict> {
+        Ok(self.check_key(key, limit))
+    }
+}
+
+/// Janela deslizante de um minuto no Redis (feature "redis")
+///
+/// Cada cliente é um sorted set com o horário de cada requisição aceita; um
+/// script Lua descarta as de mais de um minuto, conta as restantes e
+/// registra a nova se couber, tudo atomicamente e com o relógio do Redis
+/// (as réplicas não precisam estar sincronizadas).
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisRateLimiter {
+    client: redis::Client,
+    connection: Arc<tokio::sync::OnceCell<redis::aio::ConnectionManager>>,
+    script: Arc<redis::Script>,
+    prefix: String,
+}
+
+#[cfg(feature = "redis")]
+const SLIDING_WINDOW_SCRIPT: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local window = tonumber(ARGV[1])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], 0, now - window)
+if redis.call('ZCARD', KEYS[1]) < tonumber(ARGV[2]) then
+  redis.call('ZADD', KEYS[1], now, ARGV[3])
+  redis.call('PEXPIRE', KEYS[1], window)
+  return 0
+end
+local oldest = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES')
+return math.max(1, tonumber(oldest[2]) + window - now)
+"#;
+
+#[cfg(feature = "redis")]
+impl RedisRateLimiter {
+    /// Prefixo padrão das chaves no Redis
+    pub const DEFAULT_PREFIX: &'static str = "rate_limit:";
+
+    /// Valida a URL; a conexão só é aberta na primeira requisição
+    pub fn new(url: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: redis::Client::open(url)?,
+            connection: Arc::default(),
+            script: Arc::new(redis::Script::new(SLIDING_WINDOW_SCRIPT)),
+            prefix: Self::DEFAULT_PREFIX.to_string(),
+        })
+    }
+
+    /// Prefixo das chaves (ex.: para separar ambientes no mesmo Redis)
+    pub fn with_prefix(self, prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            ..self
+        }
+    }
+
+    async fn connection(&self) -> anyhow::Result<redis::aio::ConnectionManager> {
+        let connection = self
+            .connection
+            .get_or_try_init(|| redis::aio::ConnectionManager::new(self.client.clone()))
+            .await?;
+        Ok(connection.clone())
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl RateLimitStore for RedisRateLimiter {
+    async fn hit(&self, key: &str, limit: u32) -> anyhow::Result<Verdict> {
+        let mut connection = self.connection().await?;
+        let wait_ms: u64 = self
+            .script
+            .key(format!("{}{}", self.prefix, key))
+            .arg(60_000)
+            .arg(limit.max(1))
+            .arg(uuid::Uuid::new_v4().to_string())
+            .invoke_async(&mut connection)
+            .await?;
+
+        Ok(match wait_ms {
+            0 => Ok(()),
+            ms => Err(ms.div_ceil(1000)),
+        })
+    }
+}
+
+/// Limites por cliente diferentes do padrão (tabela `rate_limit_overrides`)
+///
+/// Clonável; os clones compartilham os limites. Com Postgres, `spawn_refresh`
+/// relê a tabela periodicamente.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitOverrides {
+    limits: Arc<RwLock<HashMap<String, u32>>>,
+}
+
+impl RateLimitOverrides {
+    pub fn get(&self, key: &str) -> Option<u32> {
+        self.limits
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .copied()
+    }
+
+    /// Substitui todos os limites
+    pub fn replace(&self, limits: HashMap<String, u32>) {
+        *self.limits.write().unwrap_or_else(|e| e.into_inner()) = limits;
+    }
+
+    /// Lê a tabela `rate_limit_overrides`
+    #[cfg(feature = "postgres")]
+    pub async fn load(pool: &sqlx::PgPool) -> anyhow::Result<HashMap<String, u32>> {
+        let rows = sqlx::query!("SELECT key, requests_per_minute FROM rate_limit_overrides")
+            .fetch_all(pool)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.key, u32::try_from(row.requests_per_minute).unwrap_or(1)))
+            .collect())
+    }
+
+    /// Relê a tabela a cada `interval` (a primeira leitura é imediata);
+    /// uma falha mantém os limites anteriores
+    #[cfg(feature = "postgres")]
+    pub fn spawn_refresh(
+        &self,
+        pool: sqlx::PgPool,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let overrides = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match Self::load(&pool).await {
+                    Ok(limits) => overrides.replace(limits),
+                    Err(e) => tracing::warn!(error = %e, "Failed to load rate limit overrides"),
+                }
+            }
+        })
+    }
+}
+
+/// Estado do middleware `rate_limit`
+#[derive(Clone)]
+pub struct RateLimit {
+    pub store: Arc<dyn RateLimitStore>,
+    /// Requisições por minuto dos clientes sem override
+    pub limit: u32,
+    /// `server.trusted_proxies`, para o IP dos anônimos
+    pub trusted_proxies: Vec<IpRange>,
+    pub overrides: RateLimitOverrides,
+}
+
+impl RateLimit {
+    pub fn new(store: Arc<dyn RateLimitStore>, limit: u32) -> Self {
+        Self {
+            store,
+            limit,
+            trusted_proxies: Vec::new(),
+            overrides: RateLimitOverrides::default(),
+        }
+    }
+
+    /// Limite da configuração (`None` se `features.rate_limit_per_minute`
+    /// não está definido): no Redis com `rate_limit.redis_url`, senão em
+    /// memória
+    pub fn from_config(config: &AppConfig) -> anyhow::Result<Option<Self>> {
+        let Some(limit) = config.features.rate_limit_per_minute else {
+            return Ok(None);
+        };
+        let settings = &config.rate_limit;
+
+        let store: Arc<dyn RateLimitStore> = match &settings.redis_url {
+            #[cfg(feature = "redis")]
+            Some(url) => Arc::new(RedisRateLimiter::new(url)?),
+            #[cfg(not(feature = "redis"))]
+            Some(_) => anyhow::bail!("rate_limit.redis_url requires the \"redis\" feature"),
+            None => Arc::new(RateLimiter::per_minute(limit)),
+        };
+
+        Ok(Some(Self::new(store, limit).with_trusted_proxies(
+            config.server.trusted_proxies.clone(),
+        )))
+    }
+
+    pub fn with_trusted_proxies(self, trusted_proxies: Vec<IpRange>) -> Self {
+        Self {
+            trusted_proxies,
+            ..self
+        }
+    }
+
+    pub fn with_overrides(self, overrides: RateLimitOverrides) -> Self {
+        Self { overrides, ..self }
+    }
+
+    /// A chave do cliente que fez `req`
+    fn client_key(&self, req: &Request<Body>) -> String {
+        let peer = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let ip = client_ip::client_ip(req.headers(), peer, &self.trusted_proxies);
+
+        client_key(req.extensions().get::<Principal>(), ip)
+    }
+}
+
+/// Middleware que aplica o `RateLimit`
+///
+/// Se o armazenamento falhar (ex.: Redis fora do ar), a requisição passa:
+/// o limite protege o serviço, não deve derrubá-lo.
+pub async fn rate_limit(
+    State(limit): State<RateLimit>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let key = limit.client_key(&req);
+    let requests = limit.overrides.get(&key).unwrap_or(limit.limit);
+
+    match limit.store.hit(&key, requests).await {
+        Ok(Ok(())) => next.run(req).await,
+        Ok(Err(retry_after)) => {
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(ApiResponse::<()>::error("Too many requests")),
+            )
+                .into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after.max(1)));
+            response
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Rate limit check failed; letting the request through");
+            next.run(req).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_per_client() {
+        let limiter = RateLimiter::per_minute(2);
+        let a = Some(IpAddr::from([10, 0, 0, 1]));
+        let b = Some(IpAddr::from([10, 0, 0, 2]));
+
+        assert!(limiter.check(a).is_ok());
+        assert!(limiter.check(a).is_ok());
+        assert_eq!(limiter.check(a), Err(30));
+        assert!(limiter.check(b).is_ok());
+    }
+
+    #[test]
+    fn test_client_key() {
+        let ip = Some(IpAddr::from([10, 0, 0, 1]));
+        let user = Principal::User {
+            id: 7,
+            tenant: "acme".to_string(),
+        };
+        assert_eq!(client_key(Some(&user), ip), "user:acme/7");
+        assert_eq!(client_key(Some(&Principal::Admin), ip), "admin");
+        assert_eq!(client_key(Some(&Principal::Anonymous), ip), "ip:10.0.0.1");
+        assert_eq!(client_key(None, None), "ip:unknown");
+    }
+
+    #[tokio::test]
+    async fn test_limits_by_principal_or_ip_with_overrides() {
+        use axum::{routing::get, Router};
+        use tower::ServiceExt;
+
+        let overrides = RateLimitOverrides::default();
+        overrides.replace(HashMap::from([("user:acme/2".to_string(), 3)]));
+        let limit = RateLimit::new(Arc::new(RateLimiter::per_minute(1)), 1)
+            .with_trusted_proxies(vec!["10.0.0.0/8".parse().unwrap()])
+            .with_overrides(overrides);
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(limit, rate_limit));
+        let status = |user: Option<i32>, api_key: &'static str, forwarded_for: &'static str| {
+            let app = app.clone();
+            async move {
+                let mut request = Request::get("/")
+                    .header("x-api-key", api_key)
+                    .header("x-forwarded-for", forwarded_for)
+                    .body(Body::empty())
+                    .unwrap();
+                request
+                    .extensions_mut()
+                    .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))));
+                if let Some(id) = user {
+                    request.extensions_mut().insert(Principal::User {
+                        id,
+                        tenant: "acme".to_string(),
+                    });
+                }
+                app.oneshot(request).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(status(Some(1), "a", "1.1.1.1").await, StatusCode::OK);
+        assert_eq!(
+            status(Some(1), "b", "2.2.2.2").await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        for _ in 0..3 {
+            assert_eq!(status(Some(2), "a", "1.1.1.1").await, StatusCode::OK);
+        }
+        assert_eq!(
+            status(Some(2), "a", "1.1.1.1").await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // Trocar o X-Api-Key não dá um limite novo ao anônimo; o IP é o do
+        // cliente atrás do proxy confiável
+        assert_eq!(status(None, "a", "3.3.3.3").await, StatusCode::OK);
+        assert_eq!(
+            status(None, "b", "3.3.3.3").await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(status(None, "c", "4.4.4.4").await, StatusCode::OK);
+    }
+}
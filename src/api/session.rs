@@ -0,0 +1,250 @@
+//! Sessões por cookie para clientes de navegador (`[sessions]`)
+//!
+//! `POST /api/session` troca o `admin_token` por dois cookies: o da sessão
+//! (HttpOnly) e o do token CSRF, que o JavaScript da página lê e devolve no
+//! header `X-CSRF-Token` em toda escrita (double submit). `authenticate`
+//! reconhece a sessão e `csrf` recusa com 403 as escritas sem o token; quem
+//! usa `Authorization: Bearer` não passa pela verificação. As sessões ficam
+//! na memória do processo.
+
+use crate::api::{ApiError, ApiResponse};
+use crate::audit::Principal;
+use crate::config::SessionConfig;
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Request},
+    response::{AppendHeaders, IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Caminho do login e do logout
+pub const SESSION_PATH: &str = "/api/session";
+
+/// Uma sessão aberta
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub principal: Principal,
+    pub csrf_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Sessões abertas e os nomes dos cookies (clonável; os clones
+/// compartilham as sessões)
+#[derive(Clone)]
+pub struct Sessions {
+    sessions: Arc<RwLock<HashMap<String, Session>>>,
+    pub config: Arc<SessionConfig>,
+}
+
+impl Sessions {
+    pub fn new(config: SessionConfig) -> Self {
+        Self {
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(config),
+        }
+    }
+
+    /// Abre uma sessão; devolve o id e a sessão
+    pub fn create(&self, principal: Principal) -> (String, Session) {
+        let now = Utc::now();
+        let ttl = chrono::Duration::from_std(Duration::from_secs(self.config.ttl_seconds))
+            .unwrap_or(chrono::Duration::MAX);
+        let session = Session {
+            principal,
+            csrf_token: random_token(),
+            expires_at: now + ttl,
+        };
+        let id = random_token();
+
+        let mut sessions = self.write();
+        sessions.retain(|_, session| session.expires_at > now);
+        sessions.insert(id.clone(), session.clone());
+        (id, session)
+    }
+
+    /// A sessão do id, se ainda não venceu
+    pub fn get(&self, id: &str) -> Option<Session> {
+        self.sessions
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+            .filter(|session| session.expires_at > Utc::now())
+            .cloned()
+    }
+
+    pub fn remove(&self, id: &str) {
+        self.write().remove(id);
+    }
+
+    /// A sessão do cookie da requisição
+    pub fn from_request(&self, req: &Request<Body>) -> Option<Session> {
+        cookie(req.headers(), &self.config.cookie_name).and_then(|id| self.get(id))
+    }
+
+    fn set_cookies(
+        &self,
+        id: &str,
+        session: &Session,
+    ) -> AppendHeaders<[(header::HeaderName, String); 2]> {
+        let max_age = self.config.ttl_seconds;
+        AppendHeaders([
+            (
+                header::SET_COOKIE,
+                self.cookie(&self.config.cookie_name, id, max_age, true),
+            ),
+            (
+                header::SET_COOKIE,
+                self.cookie(
+                    &self.config.csrf_cookie_name,
+                    &session.csrf_token,
+                    max_age,
+                    false,
+                ),
+            ),
+        ])
+    }
+
+    fn clear_cookies(&self) -> AppendHeaders<[(header::HeaderName, String); 2]> {
+        AppendHeaders([
+            (
+                header::SET_COOKIE,
+                self.cookie(&self.config.cookie_name, "", 0, true),
+            ),
+            (
+                header::SET_COOKIE,
+                self.cookie(&self.config.csrf_cookie_name, "", 0, false),
+            ),
+        ])
+    }
+
+    fn cookie(&self, name: &str, value: &str, max_age: u64, http_only: bool) -> String {
+        let mut cookie = format!(
+            "{}={}; Path=/; Max-Age={}; SameSite=Strict",
+            name, value, max_age
+        );
+        if http_only {
+            cookie.push_str("; HttpOnly");
+        }
+        if self.config.secure_cookies {
+            cookie.push_str("; Secure");
+        }
+        cookie
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, Session>> {
+        self.sessions.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Valor do cookie `name` no header `Cookie`
+pub fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Rotas de login e logout; o `admin_token` é a credencial do login
+pub fn router(sessions: Sessions, admin_token: Arc<str>) -> Router {
+    Router::new()
+        .route(SESSION_PATH, post(login).delete(logout))
+        .with_state((sessions, admin_token))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    /// O mesmo valor do cookie CSRF, para clientes que preferem guardá-lo
+    pub csrf_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+async fn login(
+    State((sessions, admin_token)): State<(Sessions, Arc<str>)>,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Response, ApiError> {
+    if !super::middleware::constant_time_eq(payload.token.as_bytes(), admin_token.as_bytes()) {
+        return Err(ApiError::Unauthorized("Invalid credentials".to_string()));
+    }
+
+    let (id, session) = sessions.create(Principal::Admin);
+    let body = LoginResponse {
+        csrf_token: session.csrf_token.clone(),
+        expires_at: session.expires_at,
+    };
+    Ok((
+        sessions.set_cookies(&id, &session),
+        Json(ApiResponse::success(body)),
+    )
+        .into_response())
+}
+
+async fn logout(
+    State((sessions, _)): State<(Sessions, Arc<str>)>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(id) = cookie(&headers, &sessions.config.cookie_name) {
+        sessions.remove(id);
+    }
+    let mut response = (sessions.clear_cookies(), Json(ApiResponse::success(()))).into_response();
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cookie() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; session=abc; csrf_token=x=y"),
+        );
+        assert_eq!(cookie(&headers, "session"), Some("abc"));
+        assert_eq!(cookie(&headers, "csrf_token"), Some("x=y"));
+        assert_eq!(cookie(&headers, "missing"), None);
+    }
+
+    #[test]
+    fn test_sessions_expire() {
+        let sessions = Sessions::new(SessionConfig::default());
+        let (id, session) = sessions.create(Principal::Admin);
+        assert_eq!(sessions.get(&id), Some(session));
+        assert_eq!(sessions.get("other"), None);
+        sessions.remove(&id);
+        assert_eq!(sessions.get(&id), None);
+
+        let sessions = Sessions::new(SessionConfig {
+            ttl_seconds: 0,
+            ..Default::default()
+        });
+        let (id, _) = sessions.create(Principal::Admin);
+        assert_eq!(sessions.get(&id), None);
+    }
+}
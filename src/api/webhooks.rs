@@ -0,0 +1,495 @@
+//! Cadastro de webhooks de saída e consulta ao log de entregas
+//!
+//! - `POST /api/webhooks/subscriptions`: cadastra (o segredo só aparece aqui)
+//! - `GET /api/webhooks/subscriptions`: lista
+//! - `GET|DELETE /api/webhooks/subscriptions/:id`
+//! - `GET /api/webhooks/subscriptions/:id/deliveries?limit=50`
+//!
+//! Essas rotas são só do admin e valem para o tenant do `X-Tenant-Id`, o
+//! único cujos eventos o webhook recebe. O destino precisa ser público (ver
+//! `crate::webhooks::destination`).
+//!
+//! E recebe webhooks de outros sistemas em `POST /api/webhooks/:provider`
+//! (ver `crate::webhooks::inbound`).
+
+use crate::api::auth::require_admin;
+use crate::api::{ApiError, ApiResponse, AppState};
+use crate::audit::Principal;
+use crate::events::DomainEvent;
+use crate::tenant::TenantContext;
+use crate::webhooks::inbound::{Accepted, InboundError};
+use crate::webhooks::{self, destination, Delivery, NewWebhook, Webhook};
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Entregas devolvidas quando `limit` não é informado
+const DEFAULT_DELIVERIES_LIMIT: i64 = 50;
+
+/// Máximo de entregas por consulta
+const MAX_DELIVERIES_LIMIT: i64 = 500;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/webhooks/subscriptions",
+            get(list_webhooks).post(create_webhook),
+        )
+        .route(
+            "/api/webhooks/subscriptions/:id",
+            get(get_webhook).delete(delete_webhook),
+        )
+        .route(
+            "/api/webhooks/subscriptions/:id/deliveries",
+            get(list_deliveries),
+        )
+        .route("/api/webhooks/:provider", post(receive_webhook))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateWebhookRequest {
+    #[validate(url)]
+    pub url: String,
+    /// Gerado automaticamente quando ausente
+    #[validate(length(min = 16, max = 255))]
+    pub secret: Option<String>,
+    /// Eventos assinados; ausente ou vazio = todos
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+/// Webhook recém-cadastrado, com o segredo usado nas assinaturas
+#[derive(Debug, Serialize)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveriesQuery {
+    pub limit: Option<i64>,
+}
+
+/// Cadastra um webhook
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    principal: Principal,
+    tenant: TenantContext,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<CreatedWebhook>>), ApiError> {
+    require_admin(&principal, "manage webhooks")?;
+    payload.validate()?;
+    if !state.allow_private_webhooks {
+        destination::check(&payload.url)
+            .await
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    }
+
+    if let Some(unknown) = payload
+        .events
+        .iter()
+        .find(|e| !DomainEvent::NAMES.contains(&e.as_str()))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "unknown event '{}' (expected one of: {})",
+            unknown,
+            DomainEvent::NAMES.join(", ")
+        )));
+    }
+
+    let webhook = state
+        .webhooks
+        .create(NewWebhook {
+            tenant,
+            url: payload.url,
+            secret: payload.secret.unwrap_or_else(webhooks::generate_secret),
+            events: payload.events,
+        })
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    let created = CreatedWebhook {
+        secret: webhook.secret.clone(),
+        webhook,
+    };
+    Ok((StatusCode::CREATED, Json(ApiResponse::success(created))))
+}
+
+/// Lista os webhooks do tenant (sem os segredos)
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    principal: Principal,
+    tenant: TenantContext,
+) -> Result<Json<ApiResponse<Vec<Webhook>>>, ApiError> {
+    require_admin(&principal, "manage webhooks")?;
+    let webhooks = state
+        .webhooks
+        .list(&tenant)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(webhooks)))
+}
+
+/// Busca um webhook por ID
+pub async fn get_webhook(
+    State(state): State<AppState>,
+    principal: Principal,
+    tenant: TenantContext,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Webhook>>, ApiError> {
+    require_admin(&principal, "manage webhooks")?;
+    let webhook = find_webhook(&state, &tenant, id).await?;
+
+    Ok(Json(ApiResponse::success(webhook)))
+}
+
+/// O webhook do tenant; 404 se não existe ou é de outro tenant
+async fn find_webhook(
+    state: &AppState,
+    tenant: &TenantContext,
+    id: i32,
+) -> Result<Webhook, ApiError> {
+    state
+        .webhooks
+        .find(tenant, id)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound(format!("Webhook with id {} not found", id)))
+}
+
+/// Remove um webhook e o log de entregas dele
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    principal: Principal,
+    tenant: TenantContext,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<()>>, ApiError> {
+    require_admin(&principal, "manage webhooks")?;
+    let deleted = state
+        .webhooks
+        .delete(&tenant, id)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    if !deleted {
+        return Err(ApiError::NotFound(format!(
+            "Webhook with id {} not found",
+            id
+        )));
+    }
+
+    Ok(Json(ApiResponse::success(())))
+}
+
+/// Entregas mais recentes de um webhook
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    principal: Principal,
+    tenant: TenantContext,
+    Path(id): Path<i32>,
+    Query(query): Query<DeliveriesQuery>,
+) -> Result<Json<ApiResponse<Vec<Delivery>>>, ApiError> {
+    require_admin(&principal, "manage webhooks")?;
+    find_webhook(&state, &tenant, id).await?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELIVERIES_LIMIT)
+        .clamp(1, MAX_DELIVERIES_LIMIT);
+
+    let deliveries = state
+        .webhooks
+        .deliveries(id, limit)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+    Ok(Json(ApiResponse::success(deliveries)))
+}
+
+/// Recebe um webhook de um provedor configurado
+///
+/// 202 quando o evento entra na fila, 200 para reenvios já recebidos.
+pub async fn receive_webhook(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    let (accepted, event_id) = match state.inbound.receive(&provider, &headers, &body) {
+        Ok(result) => result,
+        Err(InboundError::QueueFull) => {
+            tracing::warn!(provider = %provider, "Inbound webhook queue is full");
+            let body = Json(ApiResponse::<()>::error(
+                InboundError::QueueFull.to_string(),
+            ));
+            return Ok((StatusCode::SERVICE_UNAVAILABLE, body).into_response());
+        }
+        Err(e @ InboundError::UnknownProvider(_)) => return Err(ApiError::NotFound(e.to_string())),
+        Err(e @ (InboundError::InvalidPayload(_) | InboundError::MissingHeader(_))) => {
+            return Err(ApiError::BadRequest(e.to_string()))
+        }
+        Err(e) => {
+            tracing::warn!(provider = %provider, error = %e, "Rejected inbound webhook");
+            return Err(ApiError::Unauthorized(e.to_string()));
+        }
+    };
+
+    let duplicate = accepted == Accepted::Duplicate;
+    let status = if duplicate {
+        StatusCode::OK
+    } else {
+        StatusCode::ACCEPTED
+    };
+    let body = serde_json::json!({ "event_id": event_id, "duplicate": duplicate });
+    Ok((status, Json(ApiResponse::success(body))).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use crate::repository::InMemoryUserRepository;
+    use axum::{body::Body, http::Request};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        create_router(AppState::new(
+            Arc::new(InMemoryUserRepository::new()),
+            Default::default(),
+        ))
+    }
+
+    /// Requisição autenticada como o admin
+    fn admin(mut request: Request<Body>) -> Request<Body> {
+        request.extensions_mut().insert(Principal::Admin);
+        request
+    }
+
+    fn post(body: serde_json::Value) -> Request<Body> {
+        admin(
+            Request::post("/api/webhooks/subscriptions")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+    }
+
+    async fn body_json(response: axum::response::Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_returns_secret_once() {
+        let app = app();
+
+        let response = app
+            .clone()
+            .oneshot(post(serde_json::json!({
+                "url": "https://1.1.1.1/hook",
+                "events": ["user.created"]
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let created = body_json(response).await;
+        assert!(created["data"]["secret"]
+            .as_str()
+            .unwrap()
+            .starts_with("whsec_"));
+
+        let response = app
+            .oneshot(admin(
+                Request::get("/api/webhooks/subscriptions/1")
+                    .body(Body::empty())
+                    .unwrap(),
+            ))
+            .await
+            .unwrap();
+        let fetched = body_json(response).await;
+        assert_eq!(fetched["data"]["url"], "https://1.1.1.1/hook");
+        assert!(fetched["data"].get("secret").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_invalid_input() {
+        let response = app()
+            .oneshot(post(serde_json::json!({ "url": "not a url" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app()
+            .oneshot(post(serde_json::json!({
+                "url": "https://1.1.1.1/hook",
+                "events": ["user.exploded"]
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        for url in [
+            "http://169.254.169.254/latest/meta-data",
+            "http://10.0.0.5:8080/hook",
+            "http://localhost:5432/",
+        ] {
+            let response = app()
+                .oneshot(post(serde_json::json!({ "url": url })))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_are_per_tenant() {
+        let app = app();
+        let mut request = post(serde_json::json!({ "url": "https://1.1.1.1/hook" }));
+        request
+            .headers_mut()
+            .insert("x-tenant-id", "acme".parse().unwrap());
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(body_json(response).await["data"]["tenant_id"], "acme");
+
+        let get = |tenant: &str| {
+            admin(
+                Request::get("/api/webhooks/subscriptions/1")
+                    .header("x-tenant-id", tenant)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+        let response = app.clone().oneshot(get("acme")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.clone().oneshot(get("globex")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app
+            .oneshot(admin(
+                Request::get("/api/webhooks/subscriptions")
+                    .header("x-tenant-id", "globex")
+                    .body(Body::empty())
+                    .unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(body_json(response).await["data"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_require_admin() {
+        let user = Principal::User {
+            id: 1,
+            tenant: "default".to_string(),
+        };
+        for (principal, expected) in [
+            (None, StatusCode::UNAUTHORIZED),
+            (Some(user), StatusCode::FORBIDDEN),
+        ] {
+            for (method, uri) in [
+                ("GET", "/api/webhooks/subscriptions"),
+                ("POST", "/api/webhooks/subscriptions"),
+                ("GET", "/api/webhooks/subscriptions/1"),
+                ("DELETE", "/api/webhooks/subscriptions/1"),
+                ("GET", "/api/webhooks/subscriptions/1/deliveries"),
+            ] {
+                let mut request = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"url":"https://1.1.1.1/hook"}"#))
+                    .unwrap();
+                if let Some(principal) = principal.clone() {
+                    request.extensions_mut().insert(principal);
+                }
+                let response = app().oneshot(request).await.unwrap();
+                assert_eq!(response.status(), expected, "{} {}", method, uri);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_receive_webhook() {
+        use crate::config::WebhooksConfig;
+        use crate::webhooks::{
+            sign, InboundWebhooks, ID_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+        };
+
+        let mut config = WebhooksConfig::default();
+        config.providers.insert(
+            "parceiro".to_string(),
+            serde_json::from_value(serde_json::json!({ "secret": "s3gr3do" })).unwrap(),
+        );
+        let (inbound, mut jobs) = InboundWebhooks::new(&config);
+        let app = create_router(
+            AppState::new(Arc::new(InMemoryUserRepository::new()), Default::default())
+                .with_inbound_webhooks(inbound),
+        );
+
+        let body = r#"{"pedido":42}"#;
+        let timestamp = chrono::Utc::now().timestamp();
+        let request = |signature: String| {
+            Request::post("/api/webhooks/parceiro")
+                .header(ID_HEADER, "evt-42")
+                .header(TIMESTAMP_HEADER, timestamp)
+                .header(SIGNATURE_HEADER, signature)
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let signature = sign("s3gr3do", timestamp, body.as_bytes());
+
+        let response = app
+            .clone()
+            .oneshot(request(signature.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(jobs.recv().await.unwrap().payload["pedido"], 42);
+
+        let response = app.clone().oneshot(request(signature)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["data"]["duplicate"], true);
+
+        let response = app
+            .clone()
+            .oneshot(request("sha256=00".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .oneshot(
+                Request::post("/api/webhooks/desconhecido")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_delete_missing_is_not_found() {
+        let response = app()
+            .oneshot(admin(
+                Request::delete("/api/webhooks/subscriptions/42")
+                    .body(Body::empty())
+                    .unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,141 @@
+//! `AuditStore` em memória, para testes e uso sem banco
+
+use super::{AuditEntry, AuditStore, Principal};
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+
+/// Quantas entradas o log em memória guarda, por padrão
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+/// As entradas mais recentes, até `capacity`
+#[derive(Debug)]
+pub struct InMemoryAuditStore {
+    entries: Mutex<VecDeque<AuditEntry>>,
+    capacity: usize,
+}
+
+impl Default for InMemoryAuditStore {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl InMemoryAuditStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<AuditEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl AuditStore for InMemoryAuditStore {
+    async fn record(&self, entry: &AuditEntry) -> Result<()> {
+        let mut entries = self.lock();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry.clone());
+        Ok(())
+    }
+
+    async fn recent(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        Ok(self.lock().iter().rev().take(limit).cloned().collect())
+    }
+
+    async fn for_user(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        limit: i64,
+    ) -> Result<Vec<AuditEntry>> {
+        let actor = Principal::User {
+            id,
+            tenant: tenant.id().to_string(),
+        }
+        .actor();
+        Ok(self
+            .lock()
+            .iter()
+            .filter(|e| e.subject_id == Some(id) || e.actor == actor)
+            .take(usize::try_from(limit).unwrap_or(0))
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str) -> AuditEntry {
+        AuditEntry {
+            occurred_at: chrono::Utc::now(),
+            actor: "anonymous".to_string(),
+            ip: None,
+            user_agent: None,
+            method: "POST".to_string(),
+            path: path.to_string(),
+            status: 200,
+            request_id: None,
+            subject_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keeps_most_recent_entries() {
+        let store = InMemoryAuditStore::with_capacity(2);
+        for path in ["/a", "/b", "/c"] {
+            store.record(&entry(path)).await.unwrap();
+        }
+
+        let paths: Vec<String> = store
+            .recent(10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.path)
+            .collect();
+        assert_eq!(paths, vec!["/c", "/b"]);
+    }
+
+    #[tokio::test]
+    async fn test_for_user() {
+        let tenant = TenantContext::new("acme").unwrap();
+        let store = InMemoryAuditStore::new();
+        let own = AuditEntry {
+            actor: "user:acme/7".to_string(),
+            ..entry("/api/auth/2fa/setup")
+        };
+        let about = AuditEntry {
+            actor: "admin".to_string(),
+            subject_id: Some(7),
+            ..entry("/api/users/7/suspend")
+        };
+        let other = AuditEntry {
+            actor: "user:globex/7".to_string(),
+            ..entry("/api/auth/2fa/setup")
+        };
+        for e in [&own, &about, &other] {
+            store.record(e).await.unwrap();
+        }
+
+        assert_eq!(
+            store.for_user(&tenant, 7, 10).await.unwrap(),
+            vec![own.clone(), about]
+        );
+        assert_eq!(store.for_user(&tenant, 7, 1).await.unwrap(), vec![own]);
+    }
+}
@@ -0,0 +1,182 @@
+//! Log de auditoria das requisições que alteram dados (feature "api")
+//!
+//! O middleware `api::middleware::audit` grava uma `AuditEntry` para cada
+//! POST, PUT, PATCH e DELETE: quem fez (o `Principal` autenticado), de onde
+//! (IP e user agent), o quê (método e caminho), sobre quem (`subject_of`) e
+//! com que resultado.
+
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::Arc;
+
+pub mod memory;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+pub use memory::InMemoryAuditStore;
+#[cfg(feature = "postgres")]
+pub use postgres::PgAuditStore;
+
+/// Quem fez a requisição, colocado nas extensions pela autenticação
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Principal {
+    /// Sem credenciais (ou com credenciais inválidas onde elas são opcionais)
+    #[default]
+    Anonymous,
+    /// Portador do `admin_token`
+    Admin,
+    /// Usuário com um token de `POST /api/auth/login`
+    User { id: i32, tenant: String },
+    /// Usuário cujo papel exige 2FA e que ainda não o ativou: o token só
+    /// serve para as rotas de `/api/auth/2fa`
+    Enrolling { id: i32, tenant: String },
+    /// `actor` (um admin) agindo como o usuário, com um token de
+    /// `POST /api/admin/impersonate/:id`
+    Impersonating {
+        id: i32,
+        tenant: String,
+        actor: String,
+    },
+}
+
+impl Principal {
+    /// Identificação gravada no log
+    pub fn actor(&self) -> String {
+        match self {
+            Self::Anonymous => "anonymous".to_string(),
+            Self::Admin => "admin".to_string(),
+            Self::User { id, tenant } | Self::Enrolling { id, tenant } => {
+                format!("user:{tenant}/{id}")
+            }
+            Self::Impersonating { id, tenant, actor } => {
+                format!("{actor} as user:{tenant}/{id}")
+            }
+        }
+    }
+
+    /// Usuário do token; `None` para o admin e sem autenticação
+    pub fn user_id(&self) -> Option<i32> {
+        match self {
+            Self::User { id, .. } | Self::Enrolling { id, .. } | Self::Impersonating { id, .. } => {
+                Some(*id)
+            }
+            Self::Anonymous | Self::Admin => None,
+        }
+    }
+
+    /// Tenant do token; `None` para o admin e sem autenticação
+    pub fn tenant(&self) -> Option<&str> {
+        match self {
+            Self::User { tenant, .. }
+            | Self::Enrolling { tenant, .. }
+            | Self::Impersonating { tenant, .. } => Some(tenant),
+            Self::Anonymous | Self::Admin => None,
+        }
+    }
+
+    /// Se pode ver e alterar os dados do usuário `id` de `tenant`: o
+    /// próprio usuário ou um admin
+    pub fn can_manage_user(&self, tenant: &TenantContext, id: i32) -> bool {
+        match self {
+            Self::Admin => true,
+            Self::User {
+                id: own,
+                tenant: own_tenant,
+            }
+            | Self::Impersonating {
+                id: own,
+                tenant: own_tenant,
+                ..
+            } => *own == id && own_tenant == tenant.id(),
+            Self::Anonymous | Self::Enrolling { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.actor())
+    }
+}
+
+/// Extrai o `Principal` das extensions (`Anonymous` sem autenticação)
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Principal {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<Self>().cloned().unwrap_or_default())
+    }
+}
+
+/// Uma requisição auditada
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub occurred_at: DateTime<Utc>,
+    pub actor: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub method: String,
+    /// Caminho sem a query string (que pode carregar segredos)
+    pub path: String,
+    pub status: u16,
+    pub request_id: Option<String>,
+    /// Usuário afetado (ver `subject_of`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject_id: Option<i32>,
+}
+
+/// Usuário afetado por uma requisição: o do caminho (`/api/users/:id/...`,
+/// `/api/admin/impersonate/:id`) ou, sem ele, o do token
+pub fn subject_of(path: &str, principal: &Principal) -> Option<i32> {
+    path.strip_prefix("/api/users/")
+        .or_else(|| path.strip_prefix("/api/admin/impersonate/"))
+        .and_then(|rest| rest.split('/').next())
+        .and_then(|id| id.parse().ok())
+        .or_else(|| principal.user_id())
+}
+
+/// Armazenamento das entradas
+#[async_trait]
+pub trait AuditStore: Send + Sync {
+    async fn record(&self, entry: &AuditEntry) -> Result<()>;
+
+    /// As `limit` entradas mais recentes, da mais nova para a mais antiga
+    async fn recent(&self, limit: usize) -> Result<Vec<AuditEntry>>;
+
+    /// Até `limit` entradas sobre o usuário `id` (`subject_id`; os IDs são
+    /// únicos entre os tenants) ou feitas por ele, da mais antiga para a mais
+    /// nova
+    async fn for_user(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        limit: i64,
+    ) -> Result<Vec<AuditEntry>>;
+}
+
+/// Log usado pelo middleware (clonável; os clones compartilham o
+/// armazenamento)
+#[derive(Clone)]
+pub struct AuditLog {
+    pub store: Arc<dyn AuditStore>,
+}
+
+impl AuditLog {
+    pub fn new(store: Arc<dyn AuditStore>) -> Self {
+        Self { store }
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new(Arc::new(InMemoryAuditStore::default()))
+    }
+}
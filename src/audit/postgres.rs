@@ -0,0 +1,133 @@
+//! `AuditStore` sobre o Postgres (tabela `audit_log`)
+
+use super::{AuditEntry, AuditStore, Principal};
+use crate::db::timed;
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(Debug, Clone)]
+pub struct PgAuditStore {
+    pool: PgPool,
+}
+
+impl PgAuditStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+struct Row {
+    occurred_at: DateTime<Utc>,
+    actor: String,
+    ip: Option<String>,
+    user_agent: Option<String>,
+    method: String,
+    path: String,
+    status: i32,
+    request_id: Option<String>,
+    subject_id: Option<i32>,
+}
+
+impl From<Row> for AuditEntry {
+    fn from(row: Row) -> Self {
+        AuditEntry {
+            occurred_at: row.occurred_at,
+            actor: row.actor,
+            ip: row.ip,
+            user_agent: row.user_agent,
+            method: row.method,
+            path: row.path,
+            status: row.status as u16,
+            request_id: row.request_id,
+            subject_id: row.subject_id,
+        }
+    }
+}
+
+#[async_trait]
+impl AuditStore for PgAuditStore {
+    async fn record(&self, entry: &AuditEntry) -> Result<()> {
+        timed(
+            "audit.record",
+            sqlx::query!(
+                r#"
+                INSERT INTO audit_log
+                    (occurred_at, actor, ip, user_agent, method, path, status, request_id,
+                     subject_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+                entry.occurred_at,
+                entry.actor,
+                entry.ip,
+                entry.user_agent,
+                entry.method,
+                entry.path,
+                i32::from(entry.status),
+                entry.request_id,
+                entry.subject_id
+            )
+            .execute(&self.pool),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn recent(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        let rows = timed(
+            "audit.recent",
+            sqlx::query_as!(
+                Row,
+                r#"
+                SELECT occurred_at, actor, ip, user_agent, method, path, status, request_id,
+                       subject_id
+                FROM audit_log
+                ORDER BY id DESC
+                LIMIT $1
+                "#,
+                limit as i64
+            )
+            .fetch_all(&self.pool),
+        )
+        .await?;
+
+        Ok(rows.into_iter().map(AuditEntry::from).collect())
+    }
+
+    async fn for_user(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        limit: i64,
+    ) -> Result<Vec<AuditEntry>> {
+        let actor = Principal::User {
+            id,
+            tenant: tenant.id().to_string(),
+        }
+        .actor();
+        let rows = timed(
+            "audit.for_user",
+            sqlx::query_as!(
+                Row,
+                r#"
+                SELECT occurred_at, actor, ip, user_agent, method, path, status, request_id,
+                       subject_id
+                FROM audit_log
+                WHERE subject_id = $1 OR actor = $2
+                ORDER BY id
+                LIMIT $3
+                "#,
+                id,
+                actor,
+                limit
+            )
+            .fetch_all(&self.pool),
+        )
+        .await?;
+
+        Ok(rows.into_iter().map(AuditEntry::from).collect())
+    }
+}
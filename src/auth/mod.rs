@@ -0,0 +1,16 @@
+//! Autenticação dos usuários (feature "api")
+//!
+//! - `password`: hash e verificação das senhas (argon2id)
+//! - `token`: tokens de acesso assinados (HMAC-SHA256), emitidos por
+//!   `POST /api/auth/login` e aceitos em `Authorization: Bearer` pelo
+//!   middleware `authenticate`
+//! - `totp`: segundo fator (TOTP) e códigos de recuperação
+//! - `passkeys`: login sem senha por WebAuthn (feature "passkeys")
+
+#[cfg(feature = "passkeys")]
+pub mod passkeys;
+pub mod password;
+pub mod token;
+pub mod totp;
+
+pub use token::{Claims, IssuedToken, TokenError, TokenSigner};
@@ -0,0 +1,235 @@
+//! Passkeys (WebAuthn) com o `webauthn-rs` (feature "passkeys")
+//!
+//! Cada cerimônia tem duas etapas: `start_*` devolve as opções para o
+//! `navigator.credentials` do navegador e guarda o estado em memória sob um
+//! id; `take` o recupera quando a resposta do autenticador chega, e
+//! `finish_*` a confere. O estado vale por `CEREMONY_TTL` e só na instância
+//! que o criou.
+
+use crate::config::PasskeyConfig;
+use crate::models::StoredPasskey;
+use crate::tenant::TenantContext;
+use anyhow::Context;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use webauthn_rs::prelude::{
+    CreationChallengeResponse, Passkey, PasskeyAuthentication, PasskeyRegistration,
+    PublicKeyCredential, RegisterPublicKeyCredential, RequestChallengeResponse, Url, Webauthn,
+    WebauthnBuilder, WebauthnError,
+};
+
+/// Tempo para concluir uma cerimônia
+pub const CEREMONY_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Por que uma cerimônia falhou
+#[derive(Debug, thiserror::Error)]
+pub enum PasskeyError {
+    #[error("Unknown or expired ceremony")]
+    UnknownCeremony,
+    #[error("The passkey is not registered")]
+    UnknownPasskey,
+    #[error("Invalid stored passkey: {0}")]
+    Storage(#[from] serde_json::Error),
+    #[error("WebAuthn verification failed: {0}")]
+    Webauthn(#[from] WebauthnError),
+}
+
+/// Cerimônia em andamento, devolvida por `PasskeyService::take`
+pub struct Ceremony {
+    pub tenant: String,
+    pub user_id: i32,
+    state: CeremonyState,
+    expires_at: Instant,
+}
+
+enum CeremonyState {
+    Registration(PasskeyRegistration),
+    Authentication(PasskeyAuthentication),
+}
+
+/// Relying party e cerimônias em andamento (clonável; os clones
+/// compartilham as cerimônias)
+#[derive(Clone)]
+pub struct PasskeyService {
+    webauthn: Arc<Webauthn>,
+    ceremonies: Arc<Mutex<HashMap<String, Ceremony>>>,
+}
+
+impl PasskeyService {
+    pub fn from_config(config: &PasskeyConfig) -> anyhow::Result<Self> {
+        let origin = Url::parse(&config.rp_origin)
+            .with_context(|| format!("invalid passkey origin '{}'", config.rp_origin))?;
+        let webauthn = WebauthnBuilder::new(&config.rp_id, &origin)
+            .and_then(|builder| builder.rp_name(&config.rp_name).build())
+            .with_context(|| {
+                format!(
+                    "invalid passkey relying party '{}' for origin '{}'",
+                    config.rp_id, config.rp_origin
+                )
+            })?;
+        Ok(Self {
+            webauthn: Arc::new(webauthn),
+            ceremonies: Arc::default(),
+        })
+    }
+
+    /// Começa o cadastro de uma passkey do usuário; as que ele já tem não
+    /// podem ser cadastradas de novo
+    pub fn start_registration(
+        &self,
+        tenant: &TenantContext,
+        user_id: i32,
+        email: &str,
+        name: &str,
+        existing: &[StoredPasskey],
+    ) -> Result<(String, CreationChallengeResponse), PasskeyError> {
+        let exclude = existing
+            .iter()
+            .map(|stored| parse(stored).map(|passkey| passkey.cred_id().clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (options, state) = self.webauthn.start_passkey_registration(
+            user_handle(tenant, user_id),
+            email,
+            name,
+            Some(exclude),
+        )?;
+        let id = self.insert(tenant, user_id, CeremonyState::Registration(state));
+        Ok((id, options))
+    }
+
+    /// Confere a resposta do autenticador ao cadastro e devolve a passkey
+    /// a guardar
+    pub fn finish_registration(
+        &self,
+        ceremony: &Ceremony,
+        credential: &RegisterPublicKeyCredential,
+        name: &str,
+    ) -> Result<StoredPasskey, PasskeyError> {
+        let CeremonyState::Registration(state) = &ceremony.state else {
+            return Err(PasskeyError::UnknownCeremony);
+        };
+        let passkey = self
+            .webauthn
+            .finish_passkey_registration(credential, state)?;
+        Ok(StoredPasskey {
+            credential_id: hex::encode(passkey.cred_id()),
+            name: name.to_string(),
+            passkey: serde_json::to_string(&passkey)?,
+            created_at: Utc::now(),
+            last_used_at: None,
+        })
+    }
+
+    /// Começa o login com uma das passkeys do usuário
+    pub fn start_authentication(
+        &self,
+        tenant: &TenantContext,
+        user_id: i32,
+        passkeys: &[StoredPasskey],
+    ) -> Result<(String, RequestChallengeResponse), PasskeyError> {
+        let passkeys = passkeys.iter().map(parse).collect::<Result<Vec<_>, _>>()?;
+        let (options, state) = self.webauthn.start_passkey_authentication(&passkeys)?;
+        let id = self.insert(tenant, user_id, CeremonyState::Authentication(state));
+        Ok((id, options))
+    }
+
+    /// Confere a resposta do autenticador ao login e devolve a passkey
+    /// usada, com a credencial (o contador) atualizada
+    pub fn finish_authentication(
+        &self,
+        ceremony: &Ceremony,
+        credential: &PublicKeyCredential,
+        passkeys: &[StoredPasskey],
+    ) -> Result<StoredPasskey, PasskeyError> {
+        let CeremonyState::Authentication(state) = &ceremony.state else {
+            return Err(PasskeyError::UnknownCeremony);
+        };
+        let result = self
+            .webauthn
+            .finish_passkey_authentication(credential, state)?;
+        let credential_id = hex::encode(result.cred_id());
+        let mut stored = passkeys
+            .iter()
+            .find(|stored| stored.credential_id == credential_id)
+            .cloned()
+            .ok_or(PasskeyError::UnknownPasskey)?;
+        let mut passkey = parse(&stored)?;
+        passkey.update_credential(&result);
+        stored.passkey = serde_json::to_string(&passkey)?;
+        Ok(stored)
+    }
+
+    /// Retira a cerimônia `id` (cada uma é usada uma vez); `None` se ela
+    /// não existir ou tiver vencido
+    pub fn take(&self, id: &str) -> Option<Ceremony> {
+        let ceremony = self.lock().remove(id)?;
+        (ceremony.expires_at > Instant::now()).then_some(ceremony)
+    }
+
+    fn insert(&self, tenant: &TenantContext, user_id: i32, state: CeremonyState) -> String {
+        let id = Uuid::new_v4().to_string();
+        let now = Instant::now();
+        let mut ceremonies = self.lock();
+        ceremonies.retain(|_, ceremony| ceremony.expires_at > now);
+        ceremonies.insert(
+            id.clone(),
+            Ceremony {
+                tenant: tenant.id().to_string(),
+                user_id,
+                state,
+                expires_at: now + CEREMONY_TTL,
+            },
+        );
+        id
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Ceremony>> {
+        self.ceremonies.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Identificador estável do usuário para os autenticadores
+fn user_handle(tenant: &TenantContext, user_id: i32) -> Uuid {
+    Uuid::new_v5(
+        &Uuid::NAMESPACE_OID,
+        format!("{}/{}", tenant.id(), user_id).as_bytes(),
+    )
+}
+
+fn parse(stored: &StoredPasskey) -> Result<Passkey, serde_json::Error> {
+    serde_json::from_str(&stored.passkey)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_ceremony_is_taken_once() {
+        let service = PasskeyService::from_config(&PasskeyConfig::default()).unwrap();
+        let tenant = TenantContext::new("acme").unwrap();
+        let (id, options) = service
+            .start_registration(&tenant, 7, "ana@example.com", "Ana", &[])
+            .unwrap();
+        assert_eq!(options.public_key.rp.id, "localhost");
+        assert_eq!(options.public_key.user.name, "ana@example.com");
+
+        let ceremony = service.take(&id).unwrap();
+        assert_eq!((ceremony.tenant.as_str(), ceremony.user_id), ("acme", 7));
+        assert!(service.take(&id).is_none());
+        assert!(service.take("unknown").is_none());
+    }
+
+    #[test]
+    fn test_rejects_origin_outside_rp_id() {
+        let config = PasskeyConfig {
+            rp_id: "example.com".to_string(),
+            rp_origin: "https://evil.test".to_string(),
+            ..PasskeyConfig::default()
+        };
+        assert!(PasskeyService::from_config(&config).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+//! Hash das senhas com argon2id
+//!
+//! As funções gastam CPU de propósito; nos handlers, rode-as em
+//! `tokio::task::spawn_blocking`.
+
+use anyhow::{anyhow, Result};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use std::sync::OnceLock;
+
+/// Hash em formato PHC (`$argon2id$...`), com salt aleatório
+pub fn hash(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| anyhow!("failed to hash password: {e}"))
+}
+
+/// Confere `password` com `hash`
+///
+/// Sem hash (usuário inexistente ou sem senha), confere com um hash fixo e
+/// devolve `false`: a resposta leva o mesmo tempo e não revela quais
+/// e-mails existem.
+pub fn verify(password: &str, hash: Option<&str>) -> bool {
+    static DUMMY: OnceLock<String> = OnceLock::new();
+    let (hash, known) = match hash {
+        Some(hash) => (hash, true),
+        None => (
+            DUMMY
+                .get_or_init(|| self::hash("dummy password").expect("argon2 hashes a fixed input"))
+                .as_str(),
+            false,
+        ),
+    };
+    let matches = PasswordHash::new(hash).is_ok_and(|parsed| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok()
+    });
+    known && matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_verify() {
+        let hash = hash("correct horse").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify("correct horse", Some(&hash)));
+        assert!(!verify("wrong horse", Some(&hash)));
+        assert!(!verify("correct horse", Some("not a hash")));
+        assert!(!verify("dummy password", None));
+    }
+}
@@ -0,0 +1,241 @@
+//! Tokens de acesso dos usuários
+//!
+//! Um token é `<claims>.<assinatura>`, ambos em hex: as claims em JSON e a
+//! assinatura HMAC-SHA256 delas com o segredo de `auth.token_secret`. Não
+//! há estado no servidor; o token vale até `exp`.
+
+use crate::config::AuthConfig;
+use crate::tenant::TenantContext;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// Quem o token identifica e até quando
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    /// Id do usuário
+    pub sub: i32,
+    /// Tenant do usuário
+    pub tenant: String,
+    /// Vencimento (segundos desde a época Unix)
+    pub exp: i64,
+    /// O papel do usuário exige 2FA e ele ainda não o ativou: o token só
+    /// serve para ativá-lo
+    #[serde(default)]
+    pub two_factor_pending: bool,
+    /// Admin agindo como o usuário (impersonação), como gravado na
+    /// auditoria
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<String>,
+}
+
+/// Token emitido por `TokenSigner::issue`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuedToken {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Por que um token foi recusado
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum TokenError {
+    #[error("Malformed token")]
+    Malformed,
+    #[error("Invalid token signature")]
+    InvalidSignature,
+    #[error("The token has expired")]
+    Expired,
+}
+
+/// Emite e confere tokens (clonável)
+#[derive(Clone)]
+pub struct TokenSigner {
+    secret: Arc<[u8]>,
+    /// Validade dos tokens emitidos
+    pub ttl: Duration,
+    /// Validade dos tokens de impersonação
+    pub impersonation_ttl: Duration,
+}
+
+impl TokenSigner {
+    pub fn new(secret: &[u8], ttl: Duration) -> Self {
+        Self {
+            secret: secret.into(),
+            ttl,
+            impersonation_ttl: ttl,
+        }
+    }
+
+    pub fn with_impersonation_ttl(mut self, ttl: Duration) -> Self {
+        self.impersonation_ttl = ttl;
+        self
+    }
+
+    /// Signer da configuração; sem `token_secret`, usa um segredo aleatório
+    /// do processo (o mesmo para a API e o middleware de autenticação)
+    pub fn from_config(config: &AuthConfig) -> Self {
+        static RANDOM_SECRET: OnceLock<[u8; 32]> = OnceLock::new();
+        let ttl = Duration::from_secs(config.token_ttl_seconds);
+        let signer = match &config.token_secret {
+            Some(secret) => Self::new(secret.as_bytes(), ttl),
+            None => {
+                let secret = RANDOM_SECRET.get_or_init(|| {
+                    let mut secret = [0u8; 32];
+                    rand::thread_rng().fill_bytes(&mut secret);
+                    secret
+                });
+                Self::new(secret, ttl)
+            }
+        };
+        signer.with_impersonation_ttl(Duration::from_secs(config.impersonation_ttl_seconds))
+    }
+
+    /// Claims do usuário `user_id` de `tenant`, vencendo daqui a `ttl`
+    pub fn claims(&self, user_id: i32, tenant: &TenantContext) -> Claims {
+        Claims {
+            sub: user_id,
+            tenant: tenant.id().to_string(),
+            exp: expires_after(self.ttl),
+            two_factor_pending: false,
+            act: None,
+        }
+    }
+
+    /// Claims de `actor` agindo como o usuário `user_id` de `tenant`,
+    /// vencendo daqui a `impersonation_ttl`
+    pub fn impersonation_claims(
+        &self,
+        user_id: i32,
+        tenant: &TenantContext,
+        actor: &str,
+    ) -> Claims {
+        Claims {
+            exp: expires_after(self.impersonation_ttl),
+            act: Some(actor.to_string()),
+            ..self.claims(user_id, tenant)
+        }
+    }
+
+    /// Token com `claims`, e quando ele vence
+    pub fn issue(&self, claims: &Claims) -> IssuedToken {
+        IssuedToken {
+            token: self.sign(claims),
+            expires_at: DateTime::from_timestamp(claims.exp, 0).unwrap_or_default(),
+        }
+    }
+
+    /// Token com exatamente estas claims
+    pub fn sign(&self, claims: &Claims) -> String {
+        let payload = serde_json::to_vec(claims).expect("claims serialize to JSON");
+        format!(
+            "{}.{}",
+            hex::encode(&payload),
+            hex::encode(self.mac(&payload).finalize().into_bytes())
+        )
+    }
+
+    /// Claims de `token`, se a assinatura conferir e ele não tiver vencido
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> Result<Claims, TokenError> {
+        let (payload, signature) = token.split_once('.').ok_or(TokenError::Malformed)?;
+        let payload = hex::decode(payload).map_err(|_| TokenError::Malformed)?;
+        let signature = hex::decode(signature).map_err(|_| TokenError::Malformed)?;
+        self.mac(&payload)
+            .verify_slice(&signature)
+            .map_err(|_| TokenError::InvalidSignature)?;
+        let claims: Claims = serde_json::from_slice(&payload).map_err(|_| TokenError::Malformed)?;
+        if now.timestamp() >= claims.exp {
+            return Err(TokenError::Expired);
+        }
+        Ok(claims)
+    }
+
+    fn mac(&self, payload: &[u8]) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any size");
+        mac.update(payload);
+        mac
+    }
+}
+
+fn expires_after(ttl: Duration) -> i64 {
+    (Utc::now() + chrono::Duration::from_std(ttl).unwrap_or_default()).timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer() -> TokenSigner {
+        TokenSigner::new(b"segredo", Duration::from_secs(60))
+    }
+
+    #[test]
+    fn test_issue_and_verify() {
+        let signer = signer();
+        let issued = signer.issue(&signer.claims(7, &TenantContext::new("acme").unwrap()));
+        let claims = signer.verify(&issued.token, Utc::now()).unwrap();
+        assert_eq!(claims.sub, 7);
+        assert_eq!(claims.tenant, "acme");
+        assert_eq!(claims.exp, issued.expires_at.timestamp());
+
+        let later = issued.expires_at + chrono::Duration::seconds(1);
+        assert_eq!(
+            signer.verify(&issued.token, later),
+            Err(TokenError::Expired)
+        );
+    }
+
+    #[test]
+    fn test_impersonation_claims() {
+        let signer = signer().with_impersonation_ttl(Duration::from_secs(5));
+        let claims = signer.impersonation_claims(7, &TenantContext::default_tenant(), "admin");
+        assert_eq!(claims.act.as_deref(), Some("admin"));
+        assert!(claims.exp <= Utc::now().timestamp() + 5);
+
+        let verified = signer.verify(&signer.sign(&claims), Utc::now()).unwrap();
+        assert_eq!(verified, claims);
+        // Tokens comuns não levam o campo
+        let plain =
+            serde_json::to_value(signer.claims(7, &TenantContext::default_tenant())).unwrap();
+        assert!(plain.get("act").is_none());
+    }
+
+    #[test]
+    fn test_rejects_tampered_and_foreign_tokens() {
+        let signer = signer();
+        let token = signer
+            .issue(&signer.claims(7, &TenantContext::default_tenant()))
+            .token;
+        let (_, signature) = token.split_once('.').unwrap();
+        let forged = Claims {
+            sub: 1,
+            tenant: "default".to_string(),
+            exp: i64::MAX,
+            two_factor_pending: false,
+            act: None,
+        };
+        let tampered = format!(
+            "{}.{}",
+            hex::encode(serde_json::to_vec(&forged).unwrap()),
+            signature
+        );
+        assert_eq!(
+            signer.verify(&tampered, Utc::now()),
+            Err(TokenError::InvalidSignature)
+        );
+
+        let other = TokenSigner::new(b"outro", Duration::from_secs(60));
+        assert_eq!(
+            other.verify(&token, Utc::now()),
+            Err(TokenError::InvalidSignature)
+        );
+        assert_eq!(
+            signer.verify("admin-token", Utc::now()),
+            Err(TokenError::Malformed)
+        );
+    }
+}
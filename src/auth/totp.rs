@@ -0,0 +1,134 @@
+//! Segundo fator por TOTP (RFC 6238) e códigos de recuperação
+//!
+//! Os parâmetros são os que os apps autenticadores assumem: SHA-1, 6
+//! dígitos, passos de 30 segundos, aceitando um passo de diferença no
+//! relógio. `verify_step` devolve o passo do código, para que quem chama
+//! recuse um código já usado. Os códigos de recuperação são aleatórios o
+//! bastante para um SHA-256 simples (sem salt) bastar, o que permite
+//! buscá-los pelo hash.
+
+use anyhow::{anyhow, Result};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+/// Códigos de recuperação gerados a cada cadastro
+pub const BACKUP_CODES: usize = 10;
+
+/// Segredo novo, em base32
+pub fn generate_secret() -> String {
+    Secret::generate_secret().to_encoded().to_string()
+}
+
+/// URI `otpauth://` para o app autenticador (é o conteúdo do QR code)
+pub fn otpauth_uri(secret: &str, issuer: &str, account: &str) -> Result<String> {
+    Ok(totp(secret, issuer, account)?.get_url())
+}
+
+/// Código de `secret` em `now` (o que o app autenticador mostraria)
+pub fn code(secret: &str, now: u64) -> Result<String> {
+    Ok(totp(secret, "", "")?.generate(now))
+}
+
+/// Duração de um passo, em segundos
+pub const STEP_SECONDS: u64 = 30;
+
+/// Se `code` é o código de `secret` em `now` (segundos desde a época Unix)
+pub fn verify(secret: &str, code: &str, now: u64) -> bool {
+    verify_step(secret, code, now).is_some()
+}
+
+/// Passo (`tempo / STEP_SECONDS`) de que `code` é o código, dentro da
+/// tolerância de um passo em volta de `now`
+pub fn verify_step(secret: &str, code: &str, now: u64) -> Option<u64> {
+    let totp = totp(secret, "", "").ok()?;
+    let current = now / STEP_SECONDS;
+    [current.saturating_sub(1), current, current + 1]
+        .into_iter()
+        .find(|step| totp.check(code.trim(), step * STEP_SECONDS))
+}
+
+/// Códigos de recuperação novos, no formato `xxxxx-xxxxx`
+pub fn generate_backup_codes() -> Vec<String> {
+    const ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+    let mut rng = rand::thread_rng();
+    (0..BACKUP_CODES)
+        .map(|_| {
+            let mut code: String = (0..10)
+                .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+                .collect();
+            code.insert(5, '-');
+            code
+        })
+        .collect()
+}
+
+/// Hash guardado de um código de recuperação (ignora caixa, espaços e `-`)
+pub fn hash_backup_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+fn totp(secret: &str, issuer: &str, account: &str) -> Result<TOTP> {
+    let secret = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| anyhow!("invalid TOTP secret: {e:?}"))?;
+    let issuer = (!issuer.is_empty()).then(|| issuer.replace(':', ""));
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        0,
+        STEP_SECONDS,
+        secret,
+        issuer,
+        account.replace(':', ""),
+    )
+    .map_err(|e| anyhow!("invalid TOTP parameters: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_accepts_one_step_of_skew() {
+        let secret = generate_secret();
+        let now = 1_700_000_000;
+        let code = code(&secret, now).unwrap();
+
+        assert!(verify(&secret, &code, now));
+        assert!(verify(&secret, &format!(" {code} "), now + 30));
+        assert!(!verify(&secret, &code, now + 90));
+        assert!(!verify(&secret, "000000x", now));
+        assert!(!verify("not base32!", &code, now));
+
+        assert_eq!(verify_step(&secret, &code, now), Some(now / STEP_SECONDS));
+        assert_eq!(
+            verify_step(&secret, &code, now + STEP_SECONDS),
+            Some(now / STEP_SECONDS)
+        );
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        let uri = otpauth_uri(&generate_secret(), "Acme", "ana@example.com").unwrap();
+        assert!(uri.starts_with("otpauth://totp/Acme:ana%40example.com?secret="));
+        assert!(uri.contains("issuer=Acme"));
+    }
+
+    #[test]
+    fn test_backup_codes() {
+        let codes = generate_backup_codes();
+        assert_eq!(codes.len(), BACKUP_CODES);
+        assert!(codes.iter().all(|code| code.len() == 11));
+        assert_eq!(
+            hash_backup_code(&codes[0]),
+            hash_backup_code(&codes[0].to_uppercase().replace('-', " "))
+        );
+        assert_ne!(hash_backup_code(&codes[0]), hash_backup_code(&codes[1]));
+    }
+}
@@ -0,0 +1,59 @@
+//! Informações de build embutidas pelo `build.rs`
+
+use serde::Serialize;
+
+/// Saída longa do `--version` da CLI
+pub const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    "\ncommit:  ",
+    env!("BUILD_GIT_SHA"),
+    "\nbranch:  ",
+    env!("BUILD_GIT_BRANCH"),
+    "\nbuilt:   ",
+    env!("BUILD_TIMESTAMP"),
+    "\nrustc:   ",
+    env!("BUILD_RUSTC_VERSION"),
+);
+
+/// Versão e procedência do binário em execução
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub git_sha: &'static str,
+    pub git_branch: &'static str,
+    pub build_timestamp: &'static str,
+    pub rustc_version: &'static str,
+}
+
+impl BuildInfo {
+    /// Informações do binário atual
+    pub const fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_sha: env!("BUILD_GIT_SHA"),
+            git_branch: env!("BUILD_GIT_BRANCH"),
+            build_timestamp: env!("BUILD_TIMESTAMP"),
+            rustc_version: env!("BUILD_RUSTC_VERSION"),
+        }
+    }
+
+    /// SHA abreviado (7 caracteres), como no `git log --oneline`
+    pub fn short_sha(&self) -> &'static str {
+        self.git_sha.get(..7).unwrap_or(self.git_sha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info_is_embedded() {
+        let info = BuildInfo::current();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_sha.is_empty());
+        assert!(info.short_sha().len() <= 7);
+        assert!(info.rustc_version.starts_with("rustc") || info.rustc_version == "unknown");
+        assert!(LONG_VERSION.contains(info.build_timestamp));
+    }
+}
@@ -0,0 +1,3 @@
+//! Utilitários compartilhados pelos comandos de linha de comando
+
+pub mod prompt;
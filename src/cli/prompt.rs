@@ -0,0 +1,67 @@
+//! Perguntas interativas dos comandos destrutivos
+//!
+//! `confirm` pergunta "(y/N)" no stderr e lê a resposta do stdin, mas só
+//! quando o stdin é um terminal: em scripts e pipelines o comando segue
+//! sem perguntar, como antes. `--yes`/`-y` pula a pergunta também no
+//! terminal.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Pede confirmação para `question`; `true` para seguir
+///
+/// Com `assume_yes` ou sem um terminal no stdin, segue sem perguntar.
+pub fn confirm(question: &str, assume_yes: bool) -> io::Result<bool> {
+    if assume_yes || !io::stdin().is_terminal() {
+        return Ok(true);
+    }
+    confirm_with(question, &mut io::stdin().lock(), &mut io::stderr())
+}
+
+/// `confirm` com a entrada e a saída dadas: só "y"/"yes" (ou "s"/"sim")
+/// confirmam; vazio, qualquer outra coisa e fim da entrada, não
+pub fn confirm_with(
+    question: &str,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> io::Result<bool> {
+    write!(output, "{} (y/N) ", question)?;
+    output.flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_lowercase().as_str(),
+        "y" | "yes" | "s" | "sim"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(input: &str) -> (bool, String) {
+        let mut output = Vec::new();
+        let confirmed =
+            confirm_with("Deletar o usuário #3?", &mut input.as_bytes(), &mut output).unwrap();
+        (confirmed, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_confirm_with() {
+        let (confirmed, prompt) = answer("y\n");
+        assert!(confirmed);
+        assert_eq!(prompt, "Deletar o usuário #3? (y/N) ");
+
+        assert!(answer("YES\n").0);
+        assert!(answer(" sim \n").0);
+        assert!(!answer("\n").0);
+        assert!(!answer("n\n").0);
+        assert!(!answer("talvez\n").0);
+        assert!(!answer("").0);
+    }
+
+    #[test]
+    fn test_assume_yes_skips_the_question() {
+        assert!(confirm("Deletar tudo?", true).unwrap());
+    }
+}
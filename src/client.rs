@@ -0,0 +1,296 @@
+//! Cliente HTTP tipado da API (features "client" e "api")
+//!
+//! `ApiClient` fala com uma instância em execução e devolve os mesmos DTOs
+//! dos handlers (`UserResponse`, `HealthReport`, ...), já tirados do
+//! envelope `ApiResponse`. Respostas com `success: false` viram
+//! `ClientError::Api`, com o status, a mensagem e os `details` do servidor.
+
+use crate::api::handlers::{CreateUserRequest, UserResponse};
+use crate::api::pagination::{PageMeta, Pagination};
+use crate::api::ApiResponse;
+use crate::health::HealthReport;
+use crate::tenant::TENANT_HEADER;
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// Timeout padrão de cada requisição
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// Falha de conexão, timeout ou corpo ilegível
+    #[error("Request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// O servidor recusou a requisição
+    #[error("API error ({status}): {message}")]
+    Api {
+        status: StatusCode,
+        message: String,
+        details: Option<serde_json::Value>,
+    },
+    /// Resposta fora do envelope `ApiResponse` esperado
+    #[error("Invalid response ({status}): {message}")]
+    InvalidResponse { status: StatusCode, message: String },
+}
+
+impl ClientError {
+    /// Status HTTP da resposta, se houve uma
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Http(e) => e.status(),
+            Self::Api { status, .. } | Self::InvalidResponse { status, .. } => Some(*status),
+        }
+    }
+}
+
+/// Uma página de `list_users`
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub meta: Option<PageMeta>,
+}
+
+/// Cliente da API (clonável; os clones compartilham as conexões)
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+    tenant: Option<String>,
+}
+
+impl ApiClient {
+    /// Cliente para `base_url` (ex.: `http://localhost:8080`)
+    pub fn new(base_url: impl Into<String>) -> Result<Self, ClientError> {
+        Self::with_timeout(base_url, DEFAULT_TIMEOUT)
+    }
+
+    pub fn with_timeout(
+        base_url: impl Into<String>,
+        timeout: Duration,
+    ) -> Result<Self, ClientError> {
+        let http = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: None,
+            tenant: None,
+        })
+    }
+
+    /// Envia `Authorization: Bearer <token>` em todas as requisições
+    pub fn token(self, token: impl Into<String>) -> Self {
+        Self {
+            token: Some(token.into()),
+            ..self
+        }
+    }
+
+    /// Envia `X-Tenant-Id` em todas as requisições
+    pub fn tenant(self, tenant: impl Into<String>) -> Self {
+        Self {
+            tenant: Some(tenant.into()),
+            ..self
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// `GET /health`; o relatório volta também quando o servidor responde
+    /// 503 (ver `HealthReport::is_critical`)
+    pub async fn health(&self) -> Result<HealthReport, ClientError> {
+        self.data(self.request(Method::GET, "/health")).await
+    }
+
+    /// `GET /api/users`, uma página por vez
+    pub async fn list_users(
+        &self,
+        pagination: Pagination,
+    ) -> Result<Page<UserResponse>, ClientError> {
+        let mut query = Vec::new();
+        if let Some(page) = pagination.page {
+            query.push(("page", page));
+        }
+        if let Some(per_page) = pagination.per_page {
+            query.push(("per_page", per_page));
+        }
+        let request = self.request(Method::GET, "/api/users").query(&query);
+        let response = self.send::<Vec<UserResponse>>(request).await?;
+        Ok(Page {
+            items: response.data.unwrap_or_default(),
+            meta: response.meta,
+        })
+    }
+
+    /// `GET /api/users/:id`; `None` se o usuário não existe
+    pub async fn get_user(&self, id: i32) -> Result<Option<UserResponse>, ClientError> {
+        let request = self.request(Method::GET, &format!("/api/users/{}", id));
+        match self.data(request).await {
+            Ok(user) => Ok(Some(user)),
+            Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// `POST /api/users`
+    pub async fn create_user(&self, user: &CreateUserRequest) -> Result<UserResponse, ClientError> {
+        self.data(self.request(Method::POST, "/api/users").json(user))
+            .await
+    }
+
+    /// `DELETE /api/users/:id`; com carência no servidor, devolve o usuário
+    /// com a exclusão agendada
+    pub async fn delete_user(&self, id: i32) -> Result<Option<UserResponse>, ClientError> {
+        let request = self.request(Method::DELETE, &format!("/api/users/{}", id));
+        Ok(self
+            .send::<Option<UserResponse>>(request)
+            .await?
+            .data
+            .flatten())
+    }
+
+    /// `POST /api/users/:id/restore`
+    pub async fn restore_user(&self, id: i32) -> Result<UserResponse, ClientError> {
+        self.data(self.request(Method::POST, &format!("/api/users/{}/restore", id)))
+            .await
+    }
+
+    /// `POST /api/users/:id/anonymize`
+    pub async fn anonymize_user(&self, id: i32) -> Result<UserResponse, ClientError> {
+        self.data(self.request(Method::POST, &format!("/api/users/{}/anonymize", id)))
+            .await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let mut request = self
+            .http
+            .request(method, format!("{}{}", self.base_url, path))
+            .header(reqwest::header::ACCEPT, "application/json");
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(tenant) = &self.tenant {
+            request = request.header(TENANT_HEADER, tenant);
+        }
+        request
+    }
+
+    /// `data` da resposta, que precisa estar presente
+    async fn data<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ClientError> {
+        let response = self.send(request).await?;
+        response.data.ok_or_else(|| ClientError::InvalidResponse {
+            status: StatusCode::OK,
+            message: "missing data".to_string(),
+        })
+    }
+
+    /// Envia e abre o envelope; `success: false` vira `ClientError::Api`
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<ApiResponse<T>, ClientError> {
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+
+        match serde_json::from_slice::<ApiResponse<T>>(&body) {
+            Ok(envelope) if envelope.success => Ok(envelope),
+            Ok(envelope) => Err(ClientError::Api {
+                status,
+                message: envelope.error.unwrap_or_else(|| status.to_string()),
+                details: envelope.details,
+            }),
+            // Erros fora do envelope (ex.: de um proxy)
+            Err(_) if !status.is_success() => Err(ClientError::Api {
+                status,
+                message: String::from_utf8_lossy(&body).trim().to_string(),
+                details: None,
+            }),
+            Err(e) => Err(ClientError::InvalidResponse {
+                status,
+                message: e.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router, AppState};
+    use crate::repository::InMemoryUserRepository;
+    use std::sync::Arc;
+
+    async fn serve() -> ApiClient {
+        let app = create_router(AppState::new(
+            Arc::new(InMemoryUserRepository::new()),
+            Default::default(),
+        ));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        ApiClient::new(format!("http://{}/", address)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_user_lifecycle() {
+        let client = serve().await;
+        assert!(!client.health().await.unwrap().is_critical());
+
+        let created = client
+            .create_user(&CreateUserRequest {
+                name: "Ana".to_string(),
+                email: "ana@example.com".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(created.name, "Ana");
+        assert_eq!(
+            client.get_user(created.id).await.unwrap(),
+            Some(created.clone())
+        );
+
+        let page = client.list_users(Pagination::default()).await.unwrap();
+        assert_eq!(page.items, vec![created.clone()]);
+        assert_eq!(page.meta.unwrap().total, 1);
+
+        assert_eq!(client.delete_user(created.id).await.unwrap(), None);
+        assert_eq!(client.get_user(created.id).await.unwrap(), None);
+
+        // Os usuários ficam no tenant do cliente
+        let other = client.clone().tenant("other");
+        assert!(other
+            .list_users(Pagination::default())
+            .await
+            .unwrap()
+            .items
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_api_errors_keep_details() {
+        let client = serve().await;
+        let error = client
+            .create_user(&CreateUserRequest {
+                name: String::new(),
+                email: "invalid".to_string(),
+            })
+            .await
+            .unwrap_err();
+        match error {
+            ClientError::Api {
+                status, details, ..
+            } => {
+                assert!(status.is_client_error());
+                assert!(details.is_some());
+            }
+            other => panic!("expected API error, got {:?}", other),
+        }
+
+        let offline = ApiClient::new("http://127.0.0.1:1").unwrap();
+        assert!(matches!(offline.health().await, Err(ClientError::Http(_))));
+    }
+}
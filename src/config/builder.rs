@@ -0,0 +1,246 @@
+//! Construção programática de configuração
+//!
+//! Permite que quem embute a biblioteca (ex.: testes) monte um `AppConfig`
+//! em código, sem arquivos nem variáveis de ambiente.
+
+use super::{AppConfig, DatabaseConfig, FeaturesConfig, LogFormat, LoggingConfig, ServerConfig};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Um campo definido por um setter
+type Edit = Arc<dyn Fn(&mut AppConfig) + Send + Sync>;
+
+/// Builder de `AppConfig` com setters tipados
+///
+/// Parte de `AppConfig::default()`; cada setter sobrescreve apenas o campo
+/// correspondente. O builder guarda quais campos foram definidos, então
+/// também serve de camada para `merge`: só esses campos são aplicados por
+/// cima, mesmo quando o valor é igual ao padrão.
+#[derive(Clone, Default)]
+pub struct AppConfigBuilder {
+    config: AppConfig,
+    edits: Vec<Edit>,
+}
+
+impl fmt::Debug for AppConfigBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppConfigBuilder")
+            .field("config", &self.config)
+            .field("fields_set", &self.edits.len())
+            .finish()
+    }
+}
+
+impl AppConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parte de uma configuração existente em vez dos valores padrão
+    ///
+    /// Os campos dela não contam como definidos para `merge`.
+    pub fn from_config(config: AppConfig) -> Self {
+        Self {
+            config,
+            edits: Vec::new(),
+        }
+    }
+
+    fn set(mut self, edit: impl Fn(&mut AppConfig) + Send + Sync + 'static) -> Self {
+        edit(&mut self.config);
+        self.edits.push(Arc::new(edit));
+        self
+    }
+
+    // Servidor
+
+    pub fn server(self, server: ServerConfig) -> Self {
+        self.set(move |config| config.server = server.clone())
+    }
+
+    pub fn server_host(self, host: impl Into<String>) -> Self {
+        let host = host.into();
+        self.set(move |config| config.server.host = host.clone())
+    }
+
+    pub fn server_port(self, port: u16) -> Self {
+        self.set(move |config| config.server.port = port)
+    }
+
+    pub fn workers(self, workers: usize) -> Self {
+        self.set(move |config| config.server.workers = Some(workers))
+    }
+
+    pub fn timeout_seconds(self, seconds: u64) -> Self {
+        self.set(move |config| config.server.timeout_seconds = seconds)
+    }
+
+    // Banco de dados
+
+    pub fn database(self, database: DatabaseConfig) -> Self {
+        self.set(move |config| config.database = database.clone())
+    }
+
+    pub fn database_host(self, host: impl Into<String>) -> Self {
+        let host = host.into();
+        self.set(move |config| config.database.host = host.clone())
+    }
+
+    pub fn database_port(self, port: u16) -> Self {
+        self.set(move |config| config.database.port = port)
+    }
+
+    pub fn database_name(self, database: impl Into<String>) -> Self {
+        let database = database.into();
+        self.set(move |config| config.database.database = database.clone())
+    }
+
+    pub fn database_username(self, username: impl Into<String>) -> Self {
+        let username = username.into();
+        self.set(move |config| config.database.username = username.clone())
+    }
+
+    pub fn database_password(self, password: impl Into<String>) -> Self {
+        let password = Some(password.into());
+        self.set(move |config| config.database.password = password.clone())
+    }
+
+    pub fn max_connections(self, max: u32) -> Self {
+        self.set(move |config| config.database.max_connections = max)
+    }
+
+    pub fn min_connections(self, min: u32) -> Self {
+        self.set(move |config| config.database.min_connections = min)
+    }
+
+    pub fn slow_query_threshold_ms(self, millis: u64) -> Self {
+        self.set(move |config| config.database.slow_query_threshold_ms = millis)
+    }
+
+    pub fn drain_timeout_ms(self, millis: u64) -> Self {
+        self.set(move |config| config.database.drain_timeout_ms = millis)
+    }
+
+    pub fn health_check_interval_ms(self, millis: u64) -> Self {
+        self.set(move |config| config.database.health_check_interval_ms = millis)
+    }
+
+    pub fn retry_attempts(self, attempts: u32) -> Self {
+        self.set(move |config| config.database.retry_attempts = attempts)
+    }
+
+    pub fn verify_schema(self, enabled: bool) -> Self {
+        self.set(move |config| config.database.verify_schema = enabled)
+    }
+
+    // Logging
+
+    pub fn logging(self, logging: LoggingConfig) -> Self {
+        self.set(move |config| config.logging = logging.clone())
+    }
+
+    pub fn log_level(self, level: impl Into<String>) -> Self {
+        let level = level.into();
+        self.set(move |config| config.logging.level = level.clone())
+    }
+
+    pub fn log_format(self, format: LogFormat) -> Self {
+        self.set(move |config| config.logging.format = format.clone())
+    }
+
+    pub fn log_file(self, file: impl Into<PathBuf>) -> Self {
+        let file = Some(file.into());
+        self.set(move |config| config.logging.file = file.clone())
+    }
+
+    // Features
+
+    pub fn features(self, features: FeaturesConfig) -> Self {
+        self.set(move |config| config.features = features.clone())
+    }
+
+    pub fn api_enabled(self, enabled: bool) -> Self {
+        self.set(move |config| config.features.api_enabled = enabled)
+    }
+
+    pub fn metrics_enabled(self, enabled: bool) -> Self {
+        self.set(move |config| config.features.metrics_enabled = enabled)
+    }
+
+    pub fn cors_enabled(self, enabled: bool) -> Self {
+        self.set(move |config| config.features.cors_enabled = enabled)
+    }
+
+    pub fn compression_enabled(self, enabled: bool) -> Self {
+        self.set(move |config| config.features.compression_enabled = enabled)
+    }
+
+    pub fn rate_limit_per_minute(self, requests: u32) -> Self {
+        self.set(move |config| config.features.rate_limit_per_minute = Some(requests))
+    }
+
+    pub fn management_port(self, port: u16) -> Self {
+        self.set(move |config| config.features.management_port = Some(port))
+    }
+
+    pub fn management_host(self, host: impl Into<String>) -> Self {
+        let host = host.into();
+        self.set(move |config| config.features.management_host = host.clone())
+    }
+
+    pub fn admin_token(self, token: impl Into<String>) -> Self {
+        let token = Some(token.into());
+        self.set(move |config| config.features.admin_token = token.clone())
+    }
+
+    /// Aplica por cima os campos definidos em `overlay`, na ordem em que
+    /// foram definidos
+    pub fn merge(mut self, overlay: AppConfigBuilder) -> Self {
+        for edit in overlay.edits {
+            edit(&mut self.config);
+            self.edits.push(edit);
+        }
+        self
+    }
+
+    pub fn build(self) -> AppConfig {
+        self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_setters() {
+        let config = AppConfig::builder()
+            .server_host("127.0.0.1")
+            .server_port(3000)
+            .database_name("testdb")
+            .max_connections(3)
+            .log_level("debug")
+            .log_format(LogFormat::Json)
+            .metrics_enabled(true)
+            .build();
+
+        assert_eq!(config.server_address(), "127.0.0.1:3000");
+        assert_eq!(config.database.database, "testdb");
+        assert_eq!(config.database.max_connections, 3);
+        assert_eq!(config.logging.level, "debug");
+        assert!(matches!(config.logging.format, LogFormat::Json));
+        assert!(config.features.metrics_enabled);
+    }
+
+    #[test]
+    fn test_builder_merge() {
+        let overrides = AppConfig::builder().server_port(9000);
+        let config = AppConfig::builder()
+            .server_host("127.0.0.1")
+            .merge(overrides)
+            .build();
+
+        assert_eq!(config.server_address(), "127.0.0.1:9000");
+    }
+}
@@ -0,0 +1,297 @@
+//! Deserializers tolerantes para valores de configuração
+//!
+//! Permitem escrever durações no estilo humantime (`"30s"`, `"2m"`, `"1h 30m"`)
+//! e números como strings (`"10"`), mantendo as formas numéricas funcionando.
+
+use serde::de::{self, Deserializer, Visitor};
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Converte uma duração textual em `Duration`
+///
+/// Um número sem unidade é interpretado como segundos.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+    humantime::parse_duration(s).map_err(|e| format!("invalid duration '{}': {}", s, e))
+}
+
+/// Converte um tamanho textual em bytes (`"512"`, `"10KB"`, `"5MiB"`, `"1GB"`)
+///
+/// Sufixos decimais (KB, MB, GB) usam potências de 1000 e binários
+/// (KiB, MiB, GiB) potências de 1024.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", s))?;
+
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        other => return Err(format!("invalid size unit '{}' in '{}'", other, s)),
+    };
+
+    Ok((value * multiplier as f64) as u64)
+}
+
+/// Deserializa uma duração em segundos a partir de número ou string
+///
+/// Durações que não são um número inteiro de segundos (`"500ms"`, `"1.5s"`)
+/// são recusadas em vez de arredondadas para baixo: `"500ms"` viraria 0,
+/// que em vários campos desliga o recurso.
+pub fn duration_secs<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct DurationVisitor;
+
+    impl Visitor<'_> for DurationVisitor {
+        type Value = u64;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a number of seconds or a duration like \"30s\" or \"2m\"")
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<u64, E> {
+            Ok(v)
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<u64, E> {
+            u64::try_from(v).map_err(|_| E::custom("duration must not be negative"))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<u64, E> {
+            let duration = parse_duration(v).map_err(E::custom)?;
+            if duration.subsec_nanos() != 0 {
+                return Err(E::custom(format!(
+                    "duration '{}' must be a whole number of seconds",
+                    v.trim()
+                )));
+            }
+            Ok(duration.as_secs())
+        }
+    }
+
+    deserializer.deserialize_any(DurationVisitor)
+}
+
+/// Deserializa uma duração em milissegundos a partir de número ou string
+///
+/// Números (inclusive em string) são milissegundos; `"2s"` e `"1m"` também
+/// são aceitos.
+pub fn duration_millis<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct MillisVisitor;
+
+    impl Visitor<'_> for MillisVisitor {
+        type Value = u64;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a number of milliseconds or a duration like \"500ms\" or \"2s\"")
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<u64, E> {
+            Ok(v)
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<u64, E> {
+            u64::try_from(v).map_err(|_| E::custom("duration must not be negative"))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<u64, E> {
+            if let Ok(millis) = v.trim().parse::<u64>() {
+                return Ok(millis);
+            }
+            parse_duration(v)
+                .map(|d| d.as_millis() as u64)
+                .map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_any(MillisVisitor)
+}
+
+/// Deserializa um tamanho em bytes a partir de número ou string
+pub fn size<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct SizeVisitor;
+
+    impl Visitor<'_> for SizeVisitor {
+        type Value = u64;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a number of bytes or a size like \"10MB\"")
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<u64, E> {
+            Ok(v)
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<u64, E> {
+            u64::try_from(v).map_err(|_| E::custom("size must not be negative"))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<u64, E> {
+            parse_size(v).map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_any(SizeVisitor)
+}
+
+/// Deserializa um inteiro a partir de número ou string (`10` ou `"10"`)
+pub fn number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<u64> + FromStr,
+{
+    struct NumberVisitor<T>(PhantomData<T>);
+
+    impl<T> Visitor<'_> for NumberVisitor<T>
+    where
+        T: TryFrom<u64> + FromStr,
+    {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an integer or a string containing an integer")
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+            T::try_from(v).map_err(|_| E::custom(format!("number {} out of range", v)))
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> {
+            let v = u64::try_from(v).map_err(|_| E::custom("number must not be negative"))?;
+            self.visit_u64(v)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+            v.trim()
+                .parse()
+                .map_err(|_| E::custom(format!("invalid number '{}'", v)))
+        }
+    }
+
+    deserializer.deserialize_any(NumberVisitor(PhantomData))
+}
+
+/// Deserializa um booleano a partir de bool ou string (`true`, `"false"`,
+/// `"1"`, `"no"`), como nas colunas de um CSV
+pub fn boolean<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct BoolVisitor;
+
+    impl Visitor<'_> for BoolVisitor {
+        type Value = bool;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a boolean or a string containing a boolean")
+        }
+
+        fn visit_bool<E: de::Error>(self, v: bool) -> Result<bool, E> {
+            Ok(v)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<bool, E> {
+            match v.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" => Ok(true),
+                "false" | "0" | "no" => Ok(false),
+                _ => Err(E::custom(format!("invalid boolean '{}'", v))),
+            }
+        }
+    }
+
+    deserializer.deserialize_any(BoolVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Sample {
+        #[serde(deserialize_with = "duration_secs")]
+        timeout: u64,
+        #[serde(deserialize_with = "number")]
+        limit: u32,
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration("1h 30m").unwrap(), Duration::from_secs(5400));
+        assert!(parse_duration("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("10KB").unwrap(), 10_000);
+        assert_eq!(parse_size("5MiB").unwrap(), 5 * 1024 * 1024);
+        assert_eq!(parse_size("1.5 GB").unwrap(), 1_500_000_000);
+        assert!(parse_size("10 parsecs").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_numeric_and_string_forms() {
+        let numeric: Sample = serde_json::from_str(r#"{"timeout": 30, "limit": 10}"#).unwrap();
+        assert_eq!(numeric.timeout, 30);
+        assert_eq!(numeric.limit, 10);
+
+        let textual: Sample = serde_json::from_str(r#"{"timeout": "2m", "limit": "10"}"#).unwrap();
+        assert_eq!(textual.timeout, 120);
+        assert_eq!(textual.limit, 10);
+
+        assert!(serde_json::from_str::<Sample>(r#"{"timeout": "2m", "limit": "ten"}"#).is_err());
+
+        for fractional in ["500ms", "1.5s", "1s 200ms"] {
+            let json = format!(r#"{{"timeout": "{}", "limit": 1}}"#, fractional);
+            let error = serde_json::from_str::<Sample>(&json).unwrap_err();
+            assert!(
+                error.to_string().contains("whole number of seconds"),
+                "{}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_deserialize_boolean() {
+        #[derive(Deserialize)]
+        struct Flag {
+            #[serde(deserialize_with = "boolean")]
+            active: bool,
+        }
+
+        let parse = |json: &str| serde_json::from_str::<Flag>(json).map(|f| f.active);
+        assert!(parse(r#"{"active": true}"#).unwrap());
+        assert!(!parse(r#"{"active": "false"}"#).unwrap());
+        assert!(parse(r#"{"active": "YES"}"#).unwrap());
+        assert!(parse(r#"{"active": "maybe"}"#).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! Segredos cifrados na configuração (`enc:`)
+//!
+//! Qualquer valor de texto da configuração pode ser `enc:<base64>`: um
+//! conteúdo cifrado com [age](https://age-encryption.org) e decifrado ao
+//! carregar, antes de qualquer validação. A chave é uma identidade
+//! `AGE-SECRET-KEY-1...`, em `APP_CONFIG_KEY` ou num arquivo de identidades
+//! (como o do `age-keygen`) apontado por `APP_CONFIG_IDENTITY_FILE`.
+//! `config encrypt-value` e `config decrypt-value` geram e conferem esses
+//! valores. Decifrar exige a feature "secrets".
+
+#[cfg(feature = "secrets")]
+use anyhow::Context;
+use anyhow::Result;
+
+/// Prefixo dos valores cifrados
+pub const ENCRYPTED_PREFIX: &str = "enc:";
+
+/// Variável com a identidade age que decifra os valores
+pub const KEY_ENV: &str = "APP_CONFIG_KEY";
+
+/// Variável com o caminho de um arquivo de identidades age
+pub const IDENTITY_FILE_ENV: &str = "APP_CONFIG_IDENTITY_FILE";
+
+/// Caminhos (`database.password`, `lista[0]`) dos valores `enc:` de `value`
+pub fn encrypted_paths(value: &serde_json::Value) -> Vec<String> {
+    fn walk(value: &serde_json::Value, path: String, paths: &mut Vec<String>) {
+        match value {
+            serde_json::Value::String(s) if s.starts_with(ENCRYPTED_PREFIX) => paths.push(path),
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    let path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", path, key)
+                    };
+                    walk(value, path, paths);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for (i, value) in items.iter().enumerate() {
+                    walk(value, format!("{}[{}]", path, i), paths);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut paths = Vec::new();
+    walk(value, String::new(), &mut paths);
+    paths
+}
+
+/// Decifra os valores `enc:` de `settings` com a chave do ambiente
+///
+/// Sem valores cifrados, não exige chave nem a feature "secrets".
+pub(crate) fn decrypt_settings(settings: config::Config) -> Result<config::Config> {
+    let paths = encrypted_paths(&settings.clone().try_deserialize()?);
+    let Some(first) = paths.first() else {
+        return Ok(settings);
+    };
+
+    #[cfg(not(feature = "secrets"))]
+    anyhow::bail!(
+        "{} is encrypted, but this build lacks the \"secrets\" feature",
+        first
+    );
+
+    #[cfg(feature = "secrets")]
+    {
+        let key = SecretKey::from_env()?.with_context(|| {
+            format!(
+                "{} is encrypted: set {} or {}",
+                first, KEY_ENV, IDENTITY_FILE_ENV
+            )
+        })?;
+        decrypt_paths(settings, &paths, &key)
+    }
+}
+
+/// Decifra os valores `enc:` de `settings` com `key`
+#[cfg(feature = "secrets")]
+pub fn decrypt_with(settings: config::Config, key: &SecretKey) -> Result<config::Config> {
+    let paths = encrypted_paths(&settings.clone().try_deserialize()?);
+    decrypt_paths(settings, &paths, key)
+}
+
+#[cfg(feature = "secrets")]
+fn decrypt_paths(
+    settings: config::Config,
+    paths: &[String],
+    key: &SecretKey,
+) -> Result<config::Config> {
+    if paths.is_empty() {
+        return Ok(settings);
+    }
+    let mut builder = config::Config::builder();
+    for path in paths {
+        let value: String = settings.get(path)?;
+        let plain = key
+            .decrypt(&value)
+            .with_context(|| format!("failed to decrypt {}", path))?;
+        builder = builder.set_override(path.as_str(), plain)?;
+    }
+    Ok(builder.add_source(settings).build()?)
+}
+
+/// Identidades age que cifram e decifram os valores
+#[cfg(feature = "secrets")]
+pub struct SecretKey {
+    identities: Vec<age::x25519::Identity>,
+}
+
+#[cfg(feature = "secrets")]
+impl SecretKey {
+    /// Identidades `AGE-SECRET-KEY-1...`, uma por linha (linhas vazias e
+    /// comentários `#` são ignorados)
+    pub fn parse(text: &str) -> Result<Self> {
+        let identities = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                line.parse::<age::x25519::Identity>()
+                    .map_err(|e| anyhow::anyhow!("invalid age identity: {}", e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if identities.is_empty() {
+            anyhow::bail!("no age identity found");
+        }
+        Ok(Self { identities })
+    }
+
+    /// Chave de `APP_CONFIG_KEY` ou, sem ela, do arquivo em
+    /// `APP_CONFIG_IDENTITY_FILE`; `None` sem nenhuma das duas
+    pub fn from_env() -> Result<Option<Self>> {
+        if let Ok(key) = std::env::var(KEY_ENV) {
+            return Self::parse(&key)
+                .with_context(|| format!("invalid {}", KEY_ENV))
+                .map(Some);
+        }
+        match std::env::var_os(IDENTITY_FILE_ENV) {
+            Some(path) => {
+                let text = std::fs::read_to_string(&path).with_context(|| {
+                    format!("failed to read {}", std::path::Path::new(&path).display())
+                })?;
+                Self::parse(&text)
+                    .with_context(|| format!("invalid {}", IDENTITY_FILE_ENV))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Uma chave nova, e a identidade dela (para `APP_CONFIG_KEY`)
+    pub fn generate() -> (Self, String) {
+        use age::secrecy::ExposeSecret;
+
+        let identity = age::x25519::Identity::generate();
+        let text = identity.to_string().expose_secret().to_string();
+        (
+            Self {
+                identities: vec![identity],
+            },
+            text,
+        )
+    }
+
+    /// `plain` cifrado para a primeira identidade, como `enc:<base64>`
+    pub fn encrypt(&self, plain: &str) -> Result<String> {
+        use base64::Engine;
+
+        let recipient = self.identities[0].to_public();
+        let ciphertext = age::encrypt(&recipient, plain.as_bytes())?;
+        Ok(format!(
+            "{}{}",
+            ENCRYPTED_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(ciphertext)
+        ))
+    }
+
+    /// Conteúdo de um valor `enc:<base64>`, com qualquer das identidades
+    pub fn decrypt(&self, value: &str) -> Result<String> {
+        use base64::Engine;
+
+        let encoded = value
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .with_context(|| format!("value does not start with '{}'", ENCRYPTED_PREFIX))?;
+        let ciphertext = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .context("invalid base64")?;
+        let plain = self
+            .identities
+            .iter()
+            .find_map(|identity| age::decrypt(identity, &ciphertext).ok())
+            .context("no identity matches (wrong key?)")?;
+        String::from_utf8(plain).context("decrypted value is not UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_paths() {
+        let value = serde_json::json!({
+            "database": { "password": "enc:abc", "user": "app" },
+            "webhooks": { "endpoints": [{ "secret": "plain" }, { "secret": "enc:def" }] },
+        });
+        assert_eq!(
+            encrypted_paths(&value),
+            ["database.password", "webhooks.endpoints[1].secret"]
+        );
+    }
+
+    #[test]
+    fn test_plain_settings_load_without_a_key() {
+        let config = crate::config::AppConfig::from_str(
+            "[auth]\ntoken_secret = \"not-encrypted\"",
+            config::FileFormat::Toml,
+        )
+        .unwrap();
+        assert_eq!(config.auth.token_secret.as_deref(), Some("not-encrypted"));
+    }
+
+    #[cfg(not(feature = "secrets"))]
+    #[test]
+    fn test_encrypted_values_need_the_feature() {
+        let error = crate::config::AppConfig::from_str(
+            "[auth]\ntoken_secret = \"enc:YWdl\"",
+            config::FileFormat::Toml,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("auth.token_secret"));
+    }
+
+    #[cfg(feature = "secrets")]
+    #[test]
+    fn test_decrypts_values() {
+        let (key, identity) = SecretKey::generate();
+        let password = key.encrypt("s3nha").unwrap();
+        assert!(password.starts_with(ENCRYPTED_PREFIX));
+        assert_eq!(key.decrypt(&password).unwrap(), "s3nha");
+
+        let toml = format!(
+            "[database]\npassword = \"{}\"\nport = 6543\n[auth]\ntoken_secret = \"{}\"",
+            password,
+            key.encrypt("segredo").unwrap()
+        );
+        let settings = config::Config::builder()
+            .add_source(config::File::from_str(&toml, config::FileFormat::Toml))
+            .build()
+            .unwrap();
+        let parsed = SecretKey::parse(&format!("# chave\n{}\n", identity)).unwrap();
+        let settings = decrypt_with(settings, &parsed).unwrap();
+        assert_eq!(settings.get_string("database.password").unwrap(), "s3nha");
+        assert_eq!(settings.get_string("auth.token_secret").unwrap(), "segredo");
+        assert_eq!(settings.get_int("database.port").unwrap(), 6543);
+
+        let (other, _) = SecretKey::generate();
+        assert!(other.decrypt(&password).is_err());
+        assert!(key.decrypt("enc:não é base64").is_err());
+        assert!(SecretKey::parse("# só comentários").is_err());
+    }
+}
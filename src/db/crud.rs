@@ -0,0 +1,233 @@
+//! CRUD genérico para as entidades do banco
+//!
+//! Uma entidade nova (orgs, profiles, api_keys...) implementa `Entity` —
+//! tabela, colunas graváveis e como ligar os valores delas — e ganha um
+//! `CrudRepository<T>` com inserir, buscar, listar, contar, atualizar e
+//! remover, sem copiar o SQL de cada um. A tabela precisa de `id` serial e
+//! `tenant_id`, como `users`: toda operação vale só para o tenant pedido.
+//!
+//! As operações não gravam eventos no outbox; entidades que precisam disso
+//! (como `DbUser`) continuam com as próprias consultas.
+
+use super::{timed, DbError};
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::QueryAs;
+use sqlx::{FromRow, PgPool, Postgres};
+use std::marker::PhantomData;
+
+/// Consulta com os valores de uma entidade ligados
+pub type Query<'q, T> = QueryAs<'q, Postgres, T, PgArguments>;
+
+/// Entidade guardada numa tabela com `id` e `tenant_id`
+pub trait Entity: for<'r> FromRow<'r, PgRow> + Send + Unpin {
+    /// Nome da tabela
+    const TABLE: &'static str;
+    /// Colunas gravadas por `insert` e `update`, na ordem de `bind_columns`
+    /// (sem `id`, `tenant_id` e as preenchidas pelo banco)
+    const COLUMNS: &'static [&'static str];
+
+    fn id(&self) -> i32;
+
+    /// Liga à consulta os valores de `COLUMNS`, na mesma ordem
+    fn bind_columns<'q>(&'q self, query: Query<'q, Self>) -> Query<'q, Self>
+    where
+        Self: Sized;
+}
+
+/// SQL das operações de `CrudRepository` para `T`
+///
+/// O texto é estável, então cada consulta ocupa uma só entrada no cache de
+/// statements das conexões.
+pub mod sql {
+    use super::Entity;
+
+    pub fn insert<T: Entity>() -> String {
+        let placeholders: Vec<String> = (2..T::COLUMNS.len() + 2)
+            .map(|n| format!("${}", n))
+            .collect();
+        format!(
+            "INSERT INTO {} (tenant_id, {}) VALUES ($1, {}) RETURNING *",
+            T::TABLE,
+            T::COLUMNS.join(", "),
+            placeholders.join(", ")
+        )
+    }
+
+    pub fn find<T: Entity>() -> String {
+        format!(
+            "SELECT * FROM {} WHERE tenant_id = $1 AND id = $2",
+            T::TABLE
+        )
+    }
+
+    pub fn list_page<T: Entity>() -> String {
+        format!(
+            "SELECT * FROM {} WHERE tenant_id = $1 ORDER BY id LIMIT $2 OFFSET $3",
+            T::TABLE
+        )
+    }
+
+    pub fn count<T: Entity>() -> String {
+        format!("SELECT COUNT(*) FROM {} WHERE tenant_id = $1", T::TABLE)
+    }
+
+    /// Os valores vêm primeiro; `tenant_id` e `id` são os dois últimos
+    pub fn update<T: Entity>() -> String {
+        let n = T::COLUMNS.len();
+        let assignments: Vec<String> = T::COLUMNS
+            .iter()
+            .enumerate()
+            .map(|(i, column)| format!("{} = ${}", column, i + 1))
+            .collect();
+        format!(
+            "UPDATE {} SET {} WHERE tenant_id = ${} AND id = ${} RETURNING *",
+            T::TABLE,
+            assignments.join(", "),
+            n + 1,
+            n + 2
+        )
+    }
+
+    pub fn delete<T: Entity>() -> String {
+        format!("DELETE FROM {} WHERE tenant_id = $1 AND id = $2", T::TABLE)
+    }
+}
+
+/// Operações básicas sobre as linhas de `T` num tenant
+#[derive(Debug)]
+pub struct CrudRepository<T> {
+    pool: PgPool,
+    entity: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for CrudRepository<T> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            entity: PhantomData,
+        }
+    }
+}
+
+impl<T: Entity> CrudRepository<T> {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            entity: PhantomData,
+        }
+    }
+
+    /// Insere `entity` (o `id` dela é ignorado) e devolve a linha gravada
+    pub async fn insert(&self, tenant: &TenantContext, entity: &T) -> Result<T> {
+        let sql = sql::insert::<T>();
+        let query = sqlx::query_as::<_, T>(&sql).bind(tenant.id());
+        Ok(timed(
+            "crud.insert",
+            entity.bind_columns(query).fetch_one(&self.pool),
+        )
+        .await?)
+    }
+
+    pub async fn find(&self, tenant: &TenantContext, id: i32) -> Result<Option<T>> {
+        let sql = sql::find::<T>();
+        let query = sqlx::query_as::<_, T>(&sql).bind(tenant.id()).bind(id);
+        Ok(timed("crud.find", query.fetch_optional(&self.pool)).await?)
+    }
+
+    /// Como `find`, mas com `DbError::NotFound` se não existir
+    pub async fn get(&self, tenant: &TenantContext, id: i32) -> Result<T, DbError> {
+        self.find(tenant, id).await?.ok_or(DbError::NotFound { id })
+    }
+
+    /// Até `limit` linhas, ordenadas por ID, pulando as `offset` primeiras
+    pub async fn list_page(
+        &self,
+        tenant: &TenantContext,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<T>> {
+        let sql = sql::list_page::<T>();
+        let query = sqlx::query_as::<_, T>(&sql)
+            .bind(tenant.id())
+            .bind(limit)
+            .bind(offset);
+        Ok(timed("crud.list_page", query.fetch_all(&self.pool)).await?)
+    }
+
+    pub async fn count(&self, tenant: &TenantContext) -> Result<i64> {
+        let sql = sql::count::<T>();
+        let (count,): (i64,) = timed(
+            "crud.count",
+            sqlx::query_as(&sql).bind(tenant.id()).fetch_one(&self.pool),
+        )
+        .await?;
+        Ok(count)
+    }
+
+    /// Grava as `COLUMNS` de `entity` e devolve a linha atualizada;
+    /// `DbError::NotFound` se ela não existir
+    pub async fn update(&self, tenant: &TenantContext, entity: &T) -> Result<T, DbError> {
+        let sql = sql::update::<T>();
+        let query = entity
+            .bind_columns(sqlx::query_as::<_, T>(&sql))
+            .bind(tenant.id())
+            .bind(entity.id());
+        timed("crud.update", query.fetch_optional(&self.pool))
+            .await?
+            .ok_or(DbError::NotFound { id: entity.id() })
+    }
+
+    /// Remove a linha; `DbError::NotFound` se ela não existir
+    pub async fn delete(&self, tenant: &TenantContext, id: i32) -> Result<(), DbError> {
+        let sql = sql::delete::<T>();
+        let result = timed(
+            "crud.delete",
+            sqlx::query(&sql)
+                .bind(tenant.id())
+                .bind(id)
+                .execute(&self.pool),
+        )
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound { id });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::COUNT_SQL;
+    use crate::models::DbUser;
+
+    #[test]
+    fn test_generated_sql() {
+        assert_eq!(
+            sql::insert::<DbUser>(),
+            "INSERT INTO users (tenant_id, name, email, status) VALUES ($1, $2, $3, $4) RETURNING *"
+        );
+        assert_eq!(
+            sql::update::<DbUser>(),
+            "UPDATE users SET name = $1, email = $2, status = $3 \
+             WHERE tenant_id = $4 AND id = $5 RETURNING *"
+        );
+        assert_eq!(
+            sql::delete::<DbUser>(),
+            "DELETE FROM users WHERE tenant_id = $1 AND id = $2"
+        );
+
+        assert_eq!(
+            sql::find::<DbUser>(),
+            "SELECT * FROM users WHERE tenant_id = $1 AND id = $2"
+        );
+        assert_eq!(
+            sql::list_page::<DbUser>(),
+            "SELECT * FROM users WHERE tenant_id = $1 ORDER BY id LIMIT $2 OFFSET $3"
+        );
+        // A contagem é a mesma de `DbUser`, já preparada pelo warmup
+        assert_eq!(sql::count::<DbUser>(), COUNT_SQL);
+    }
+}
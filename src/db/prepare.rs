@@ -0,0 +1,143 @@
+//! Metadados das consultas verificadas em compilação (`.sqlx`)
+//!
+//! As consultas estáticas usam `sqlx::query!`/`query_as!`: o compilador
+//! confere o SQL, os parâmetros e as colunas contra o schema. Com
+//! `DATABASE_URL` definida na compilação, a conferência é feita no banco;
+//! sem ela, a partir dos arquivos de `.sqlx`, versionados no repositório
+//! para o build (CI, Docker) não depender de um banco. `db prepare`
+//! regenera esses arquivos depois de mudar uma consulta ou uma migration.
+//!
+//! Ficam com `sqlx::query` as consultas montadas em tempo de execução
+//! (`CrudRepository`, `list_page_fields`) e as que usam a tabela temporária
+//! do `COPY`. As de `HOT_QUERIES` também são verificadas: o literal de cada
+//! uma fica numa macro, usada pelo `query_as!` e pelo warmup.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Diretório dos metadados, relativo à raiz do projeto
+pub const OFFLINE_DIR: &str = ".sqlx";
+
+/// Features compiladas pelo `prepare`: todas as que têm consultas
+pub const PREPARE_FEATURES: &str = "full,test-util";
+
+/// Pacote com as consultas
+const PACKAGE: &str = env!("CARGO_PKG_NAME");
+
+/// Recompila o projeto em `dir` contra o banco de `database_url` (já
+/// migrado) e troca os arquivos de `.sqlx` pelos gerados; devolve quantas
+/// consultas foram gravadas
+///
+/// Compila num diretório de build à parte (`target/sqlx-prepare`), então
+/// não invalida o build de desenvolvimento. Se a compilação falhar, `.sqlx`
+/// fica como estava.
+pub fn prepare(dir: &Path, database_url: &str) -> Result<usize> {
+    if !dir.join("Cargo.toml").is_file() {
+        anyhow::bail!("{} is not the project root (no Cargo.toml)", dir.display());
+    }
+    let target_dir = dir.join("target").join("sqlx-prepare");
+    let staging = target_dir.join("queries");
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    std::fs::create_dir_all(&staging)?;
+
+    // Sem isso o cargo reaproveitaria o build anterior e as macros não
+    // rodariam de novo
+    cargo(dir)
+        .args(["clean", "--package", PACKAGE, "--target-dir"])
+        .arg(&target_dir)
+        .status()
+        .context("failed to run cargo clean")?;
+
+    let status = cargo(dir)
+        .args(["check", "--package", PACKAGE, "--all-targets", "--features"])
+        .arg(PREPARE_FEATURES)
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .env("DATABASE_URL", database_url)
+        .env("SQLX_OFFLINE", "false")
+        .env("SQLX_OFFLINE_DIR", &staging)
+        .status()
+        .context("failed to run cargo check")?;
+    if !status.success() {
+        anyhow::bail!(
+            "cargo check failed ({}); {} was left unchanged",
+            status,
+            OFFLINE_DIR
+        );
+    }
+
+    replace_queries(&staging, &dir.join(OFFLINE_DIR))
+}
+
+fn cargo(dir: &Path) -> Command {
+    let mut command = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+    command.current_dir(dir);
+    command
+}
+
+/// Troca os `query-*.json` de `offline_dir` pelos de `staging`, removendo
+/// os de consultas que não existem mais
+fn replace_queries(staging: &Path, offline_dir: &Path) -> Result<usize> {
+    std::fs::create_dir_all(offline_dir)?;
+    for entry in std::fs::read_dir(offline_dir)? {
+        let path = entry?.path();
+        if is_query_file(&path) {
+            std::fs::remove_file(&path)?;
+        }
+    }
+
+    let mut copied = 0;
+    for entry in std::fs::read_dir(staging)? {
+        let path = entry?.path();
+        if let (true, Some(name)) = (is_query_file(&path), path.file_name()) {
+            std::fs::copy(&path, offline_dir.join(name))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+fn is_query_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("query-") && name.ends_with(".json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_queries() {
+        let root = std::env::temp_dir().join(format!("prepare-test-{}", uuid::Uuid::new_v4()));
+        let staging = root.join("staging");
+        let offline_dir = root.join(".sqlx");
+        std::fs::create_dir_all(&staging).unwrap();
+        std::fs::create_dir_all(&offline_dir).unwrap();
+        std::fs::write(offline_dir.join("query-old.json"), "{}").unwrap();
+        std::fs::write(offline_dir.join("README.md"), "keep").unwrap();
+        std::fs::write(staging.join("query-a.json"), "{}").unwrap();
+        std::fs::write(staging.join("query-b.json"), "{}").unwrap();
+
+        assert_eq!(replace_queries(&staging, &offline_dir).unwrap(), 2);
+
+        let mut names: Vec<String> = std::fs::read_dir(&offline_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["README.md", "query-a.json", "query-b.json"]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_prepare_requires_project_root() {
+        let dir = std::env::temp_dir();
+        let error = prepare(&dir, "postgres://localhost/app").unwrap_err();
+        assert!(error.to_string().contains("no Cargo.toml"));
+    }
+}
@@ -0,0 +1,576 @@
+//! Verificação do schema do banco contra as migrations embutidas
+//!
+//! Detecta divergências (migrations pendentes, alteradas ou desconhecidas,
+//! tabelas e colunas ausentes) na inicialização, em vez de deixar cada
+//! consulta falhar em tempo de execução.
+
+use super::MIGRATOR;
+use anyhow::Result;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Tabelas e colunas das quais o código depende
+pub const REQUIRED_COLUMNS: &[(&str, &[&str])] = &[
+    (
+        "users",
+        &[
+            "id",
+            "tenant_id",
+            "name",
+            "email",
+            "status",
+            "created_at",
+            "updated_at",
+            "deletion_scheduled_at",
+            "password_hash",
+            "last_login_at",
+            "role",
+            "totp_secret",
+            "totp_enabled_at",
+        ],
+    ),
+    ("backup_codes", &["user_id", "code_hash"]),
+    (
+        "passkeys",
+        &[
+            "id",
+            "user_id",
+            "credential_id",
+            "name",
+            "passkey",
+            "created_at",
+            "last_used_at",
+        ],
+    ),
+    (
+        "login_events",
+        &[
+            "id",
+            "user_id",
+            "occurred_at",
+            "ip",
+            "user_agent",
+            "success",
+        ],
+    ),
+    (
+        "webhooks",
+        &["id", "url", "secret", "events", "active", "created_at"],
+    ),
+    (
+        "webhook_deliveries",
+        &[
+            "id",
+            "webhook_id",
+            "event_id",
+            "event_type",
+            "attempt",
+            "status_code",
+            "error",
+            "success",
+            "duration_ms",
+            "created_at",
+        ],
+    ),
+    (
+        "outbox",
+        &[
+            "id",
+            "event_type",
+            "payload",
+            "created_at",
+            "published_at",
+            "attempts",
+            "last_error",
+            "traceparent",
+            "tracestate",
+        ],
+    ),
+    (
+        "audit_log",
+        &[
+            "id",
+            "occurred_at",
+            "actor",
+            "ip",
+            "user_agent",
+            "method",
+            "path",
+            "status",
+            "request_id",
+        ],
+    ),
+    (
+        "rate_limit_overrides",
+        &["key", "requests_per_minute", "note", "updated_at"],
+    ),
+    (
+        "quotas",
+        &["tenant_id", "resource", "monthly_limit", "updated_at"],
+    ),
+    (
+        "quota_usage",
+        &["tenant_id", "resource", "period_start", "used"],
+    ),
+    (
+        "idempotency_keys",
+        &[
+            "key",
+            "request_hash",
+            "status_code",
+            "content_type",
+            "body",
+            "created_at",
+            "expires_at",
+        ],
+    ),
+];
+
+/// Uma divergência entre o banco e o que o binário espera
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaProblem {
+    /// Migration embutida que ainda não foi aplicada
+    Pending {
+        version: i64,
+        description: String,
+    },
+    /// Migration aplicada que não existe neste binário (versão mais nova?)
+    Unknown {
+        version: i64,
+        description: String,
+    },
+    /// Migration aplicada cujo conteúdo mudou desde então
+    ChecksumMismatch {
+        version: i64,
+        description: String,
+    },
+    /// Migration que falhou no meio da aplicação
+    Failed {
+        version: i64,
+        description: String,
+    },
+    MissingTable {
+        table: String,
+    },
+    MissingColumn {
+        table: String,
+        column: String,
+    },
+}
+
+impl fmt::Display for SchemaProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pending {
+                version,
+                description,
+            } => write!(f, "migration {} ({}) is not applied", version, description),
+            Self::Unknown {
+                version,
+                description,
+            } => write!(
+                f,
+                "migration {} ({}) is applied but unknown to this binary",
+                version, description
+            ),
+            Self::ChecksumMismatch {
+                version,
+                description,
+            } => write!(
+                f,
+                "migration {} ({}) was modified after being applied",
+                version, description
+            ),
+            Self::Failed {
+                version,
+                description,
+            } => write!(f, "migration {} ({}) failed to apply", version, description),
+            Self::MissingTable { table } => write!(f, "table \"{}\" is missing", table),
+            Self::MissingColumn { table, column } => {
+                write!(f, "column \"{}.{}\" is missing", table, column)
+            }
+        }
+    }
+}
+
+/// Resultado de `Database::check_schema`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaReport {
+    pub problems: Vec<SchemaProblem>,
+}
+
+impl SchemaReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// `Err` com o relatório completo se houver divergências
+    pub fn into_result(self) -> Result<()> {
+        if self.is_ok() {
+            Ok(())
+        } else {
+            anyhow::bail!("{}", self)
+        }
+    }
+}
+
+impl fmt::Display for SchemaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "database schema matches the embedded migrations");
+        }
+
+        write!(
+            f,
+            "database schema does not match the embedded migrations ({} problem(s)):",
+            self.problems.len()
+        )?;
+        for problem in &self.problems {
+            write!(f, "\n  - {}", problem)?;
+        }
+        if self
+            .problems
+            .iter()
+            .any(|p| matches!(p, SchemaProblem::Pending { .. }))
+        {
+            write!(f, "\nrun `db init` to apply pending migrations")?;
+        }
+        Ok(())
+    }
+}
+
+/// Uma linha de `_sqlx_migrations`
+#[derive(Debug, Clone)]
+pub(crate) struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    pub checksum: Vec<u8>,
+    pub success: bool,
+}
+
+/// Compara as migrations aplicadas com as embutidas (só as `.up.sql`)
+pub(crate) fn compare_migrations(applied: &[AppliedMigration]) -> Vec<SchemaProblem> {
+    let expected: BTreeMap<i64, _> = MIGRATOR
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+        .map(|m| (m.version, m))
+        .collect();
+    let mut problems = Vec::new();
+
+    for row in applied {
+        match expected.get(&row.version) {
+            None => problems.push(SchemaProblem::Unknown {
+                version: row.version,
+                description: row.description.clone(),
+            }),
+            Some(_) if !row.success => problems.push(SchemaProblem::Failed {
+                version: row.version,
+                description: row.description.clone(),
+            }),
+            Some(m) if *m.checksum != *row.checksum => {
+                problems.push(SchemaProblem::ChecksumMismatch {
+                    version: row.version,
+                    description: m.description.to_string(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    let applied: BTreeSet<i64> = applied.iter().map(|row| row.version).collect();
+    for (version, m) in &expected {
+        if !applied.contains(version) {
+            problems.push(SchemaProblem::Pending {
+                version: *version,
+                description: m.description.to_string(),
+            });
+        }
+    }
+
+    problems
+}
+
+/// Compara as colunas existentes (`tabela -> colunas`) com `REQUIRED_COLUMNS`
+pub(crate) fn compare_columns(existing: &BTreeMap<String, BTreeSet<String>>) -> Vec<SchemaProblem> {
+    let mut problems = Vec::new();
+
+    for (table, columns) in REQUIRED_COLUMNS {
+        let Some(found) = existing.get(*table) else {
+            problems.push(SchemaProblem::MissingTable {
+                table: table.to_string(),
+            });
+            continue;
+        };
+        for column in *columns {
+            if !found.contains(*column) {
+                problems.push(SchemaProblem::MissingColumn {
+                    table: table.to_string(),
+                    column: column.to_string(),
+                });
+            }
+        }
+    }
+
+    problems
+}
+
+/// Consulta `_sqlx_migrations` e `information_schema` e monta o relatório
+pub(crate) async fn check(pool: &PgPool) -> Result<SchemaReport> {
+    let has_migrations_table: bool =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+
+    let applied = if has_migrations_table {
+        sqlx::query_as::<_, (i64, String, Vec<u8>, bool)>(
+            "SELECT version, description, checksum, success FROM _sqlx_migrations ORDER BY version",
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(
+            |(version, description, checksum, success)| AppliedMigration {
+                version,
+                description,
+                checksum,
+                success,
+            },
+        )
+        .collect()
+    } else {
+        Vec::new()
+    };
+
+    let tables: Vec<&str> = REQUIRED_COLUMNS.iter().map(|(table, _)| *table).collect();
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT table_name::text, column_name::text FROM information_schema.columns \
+         WHERE table_schema = current_schema() AND table_name = ANY($1)",
+    )
+    .bind(&tables)
+    .fetch_all(pool)
+    .await?;
+
+    let mut existing: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (table, column) in rows {
+        existing.entry(table).or_default().insert(column);
+    }
+
+    let mut problems = compare_migrations(&applied);
+    problems.extend(compare_columns(&existing));
+    Ok(SchemaReport { problems })
+}
+
+/// Migration que cria o índice único `(tenant_id, lower(email))`
+pub const EMAIL_LOWER_UNIQUE_VERSION: i64 = 20240215000000;
+
+/// Quantos grupos de emails conflitantes entram na mensagem de erro
+const MAX_LISTED_CONFLICTS: i64 = 20;
+
+/// Emails de um mesmo tenant que só diferem em maiúsculas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailConflict {
+    pub tenant_id: String,
+    pub emails: Vec<String>,
+}
+
+/// Mensagem de erro com os conflitos e como resolvê-los
+pub(crate) fn email_conflicts_message(conflicts: &[EmailConflict]) -> String {
+    let mut message = format!(
+        "migration {} would fail: these users have emails that differ only in case",
+        EMAIL_LOWER_UNIQUE_VERSION
+    );
+    for conflict in conflicts {
+        message.push_str(&format!(
+            "\n  - tenant '{}': {}",
+            conflict.tenant_id,
+            conflict.emails.join(", ")
+        ));
+    }
+    if conflicts.len() as i64 >= MAX_LISTED_CONFLICTS {
+        message.push_str("\n  - ...");
+    }
+    message.push_str(
+        "\ndelete or rename the duplicates (see \"Emails duplicados\" in the README) \
+         and run the migrations again",
+    );
+    message
+}
+
+/// Antes da migration `EMAIL_LOWER_UNIQUE_VERSION`, procura emails que só
+/// diferem em maiúsculas e falha listando-os, em vez de deixar o
+/// `CREATE UNIQUE INDEX` abortar a migration sem dizer quais linhas conflitam
+pub(crate) async fn check_email_conflicts(pool: &PgPool) -> Result<()> {
+    let has_users: bool = sqlx::query_scalar("SELECT to_regclass('users') IS NOT NULL")
+        .fetch_one(pool)
+        .await?;
+    if !has_users {
+        return Ok(());
+    }
+
+    let has_migrations_table: bool =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+    if has_migrations_table {
+        let applied: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM _sqlx_migrations WHERE version = $1)")
+                .bind(EMAIL_LOWER_UNIQUE_VERSION)
+                .fetch_one(pool)
+                .await?;
+        if applied {
+            return Ok(());
+        }
+    }
+
+    // Antes de 20240207 não há `tenant_id`; a coluna nasce com 'default'
+    let has_tenant: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM information_schema.columns \
+         WHERE table_schema = current_schema() AND table_name = 'users' \
+         AND column_name = 'tenant_id')",
+    )
+    .fetch_one(pool)
+    .await?;
+    let tenant = if has_tenant { "tenant_id" } else { "'default'" };
+
+    let conflicts: Vec<EmailConflict> = sqlx::query_as::<_, (String, Vec<String>)>(&format!(
+        "SELECT {tenant}::text, array_agg(email::text ORDER BY id) FROM users \
+         GROUP BY 1, lower(email) HAVING count(*) > 1 ORDER BY 1, min(id) LIMIT $1"
+    ))
+    .bind(MAX_LISTED_CONFLICTS)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|(tenant_id, emails)| EmailConflict { tenant_id, emails })
+    .collect();
+
+    if conflicts.is_empty() {
+        Ok(())
+    } else {
+        anyhow::bail!(email_conflicts_message(&conflicts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied_all() -> Vec<AppliedMigration> {
+        MIGRATOR
+            .iter()
+            .filter(|m| m.migration_type.is_up_migration())
+            .map(|m| AppliedMigration {
+                version: m.version,
+                description: m.description.to_string(),
+                checksum: m.checksum.to_vec(),
+                success: true,
+            })
+            .collect()
+    }
+
+    fn all_columns() -> BTreeMap<String, BTreeSet<String>> {
+        REQUIRED_COLUMNS
+            .iter()
+            .map(|(table, columns)| {
+                (
+                    table.to_string(),
+                    columns.iter().map(|c| c.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_up_to_date_schema_has_no_problems() {
+        assert!(compare_migrations(&applied_all()).is_empty());
+        assert!(compare_columns(&all_columns()).is_empty());
+        assert!(SchemaReport::default().into_result().is_ok());
+    }
+
+    #[test]
+    fn test_detects_migration_drift() {
+        let mut applied = applied_all();
+        let last = applied.pop().unwrap();
+        applied[0].checksum = vec![0; 48];
+        applied[1].success = false;
+        applied.push(AppliedMigration {
+            version: 29990101000000,
+            description: "from the future".to_string(),
+            checksum: vec![],
+            success: true,
+        });
+
+        let problems = compare_migrations(&applied);
+        assert_eq!(problems.len(), 4);
+        assert!(matches!(
+            problems[0],
+            SchemaProblem::ChecksumMismatch { version, .. } if version == applied[0].version
+        ));
+        assert!(matches!(problems[1], SchemaProblem::Failed { .. }));
+        assert!(matches!(
+            problems[2],
+            SchemaProblem::Unknown {
+                version: 29990101000000,
+                ..
+            }
+        ));
+        assert_eq!(
+            problems[3],
+            SchemaProblem::Pending {
+                version: last.version,
+                description: last.description,
+            }
+        );
+    }
+
+    #[test]
+    fn test_email_conflicts_message_lists_duplicates() {
+        assert!(MIGRATOR
+            .iter()
+            .any(|m| m.version == EMAIL_LOWER_UNIQUE_VERSION && m.description.contains("email")));
+
+        let message = email_conflicts_message(&[
+            EmailConflict {
+                tenant_id: "default".to_string(),
+                emails: vec!["Ana@x.com".to_string(), "ana@x.com".to_string()],
+            },
+            EmailConflict {
+                tenant_id: "acme".to_string(),
+                emails: vec!["BOB@y.com".to_string(), "bob@y.com".to_string()],
+            },
+        ]);
+        assert!(message.contains("20240215000000"));
+        assert!(message.contains("tenant 'default': Ana@x.com, ana@x.com"));
+        assert!(message.contains("tenant 'acme': BOB@y.com, bob@y.com"));
+        assert!(message.contains("README"));
+        assert!(!message.contains("..."));
+    }
+
+    #[test]
+    fn test_detects_missing_tables_and_columns() {
+        let mut existing = all_columns();
+        existing.remove("outbox");
+        existing.get_mut("users").unwrap().remove("status");
+
+        let report = SchemaReport {
+            problems: compare_columns(&existing),
+        };
+        assert_eq!(
+            report.problems,
+            vec![
+                SchemaProblem::MissingColumn {
+                    table: "users".to_string(),
+                    column: "status".to_string(),
+                },
+                SchemaProblem::MissingTable {
+                    table: "outbox".to_string(),
+                },
+            ]
+        );
+
+        let message = report.into_result().unwrap_err().to_string();
+        assert!(message.contains("2 problem(s)"));
+        assert!(message.contains("column \"users.status\" is missing"));
+        assert!(message.contains("table \"outbox\" is missing"));
+    }
+}
@@ -0,0 +1,250 @@
+//! Dados iniciais declarativos (`db seed --file`)
+//!
+//! Um arquivo JSON, YAML ou CSV descreve o estado desejado; aplicá-lo é
+//! idempotente (upsert pela chave natural), então pode rodar em todo
+//! bootstrap de ambiente. Em JSON/YAML o documento é um objeto com uma lista
+//! por entidade (`{"users": [...]}`); um CSV, ou uma lista solta, é tratado
+//! como `users`.
+//!
+//! Arquivos grandes (a partir de `database.copy_threshold` linhas) são
+//! carregados com `COPY` numa tabela temporária e aplicados num único
+//! upsert, em vez de um comando por linha.
+
+use super::{copy_to_staging, timed, DbUser};
+use crate::events::DomainEvent;
+use crate::formats::{self, DocumentFormat};
+use crate::models::{NewUser, UserStatus};
+use crate::outbox;
+use crate::tenant::TenantContext;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, PgConnection, PgPool, Row};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Conteúdo de um arquivo de seed (novas entidades entram como novos campos)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SeedData {
+    pub users: Vec<SeedUser>,
+}
+
+/// Usuário desejado, identificado pelo email
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SeedUser {
+    pub name: String,
+    pub email: String,
+    /// Aplicado direto, sem passar pelas transições de `UserStatus`
+    #[serde(default)]
+    pub status: UserStatus,
+}
+
+/// Contagem do que `apply` fez, por linha
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeedReport {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+}
+
+impl SeedData {
+    /// Lê e valida um arquivo (formato pela extensão)
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read seed file {}", path.display()))?;
+        Self::parse(&content, DocumentFormat::from_path(path))
+            .with_context(|| format!("invalid seed file {}", path.display()))
+    }
+
+    pub fn parse(content: &str, format: DocumentFormat) -> Result<Self> {
+        let data = match formats::parse(content, format)? {
+            users @ Value::Array(_) => serde_json::from_value(users).map(|users| Self { users }),
+            document => serde_json::from_value(document),
+        }?;
+        data.validate()?;
+        Ok(data)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut emails = BTreeSet::new();
+        for (i, user) in self.users.iter().enumerate() {
+            if user.name.trim().is_empty() {
+                anyhow::bail!("users[{}]: name must not be empty", i);
+            }
+            if !user.email.contains('@') {
+                anyhow::bail!("users[{}]: invalid email '{}'", i, user.email);
+            }
+            if !emails.insert(user.email.to_lowercase()) {
+                anyhow::bail!("users[{}]: duplicate email '{}'", i, user.email);
+            }
+        }
+        Ok(())
+    }
+
+    /// Aplica os dados no `tenant` numa única transação; linhas já iguais
+    /// não são tocadas nem geram eventos no outbox
+    ///
+    /// Com `copy_threshold` linhas ou mais, os dados vão por `COPY`.
+    pub async fn apply(
+        &self,
+        pool: &PgPool,
+        tenant: &TenantContext,
+        copy_threshold: usize,
+    ) -> Result<SeedReport> {
+        let mut tx = pool.begin().await?;
+        let report = if self.users.len() >= copy_threshold {
+            self.apply_copy(&mut tx, tenant).await?
+        } else {
+            self.apply_rows(&mut tx, tenant).await?
+        };
+        tx.commit().await?;
+        Ok(report)
+    }
+
+    /// Um upsert por usuário
+    async fn apply_rows(
+        &self,
+        conn: &mut PgConnection,
+        tenant: &TenantContext,
+    ) -> Result<SeedReport> {
+        let mut report = SeedReport::default();
+        for user in &self.users {
+            let row = timed(
+                "users.seed",
+                sqlx::query(
+                    "INSERT INTO users (tenant_id, name, email, status) VALUES ($1, $2, $3, $4) \
+                     ON CONFLICT (tenant_id, email) DO UPDATE SET name = EXCLUDED.name, status = EXCLUDED.status \
+                     WHERE (users.name, users.status) IS DISTINCT FROM (EXCLUDED.name, EXCLUDED.status) \
+                     RETURNING *, (xmax = 0) AS inserted",
+                )
+                .bind(tenant.id())
+                .bind(&user.name)
+                .bind(&user.email)
+                .bind(user.status)
+                .fetch_optional(&mut *conn),
+            )
+            .await?;
+
+            match row {
+                Some(row) => {
+                    let event = report.record(&row)?;
+                    outbox::enqueue(&mut *conn, tenant, &event).await?;
+                }
+                None => report.unchanged += 1,
+            }
+        }
+        Ok(report)
+    }
+
+    /// Todos os usuários por `COPY` e um único upsert a partir dele
+    async fn apply_copy(
+        &self,
+        conn: &mut PgConnection,
+        tenant: &TenantContext,
+    ) -> Result<SeedReport> {
+        let users = self.users.iter().map(|user| NewUser {
+            name: user.name.clone(),
+            email: user.email.clone(),
+            status: user.status,
+        });
+        timed("users.copy", copy_to_staging(&mut *conn, users)).await?;
+        let rows = timed(
+            "users.seed_copy",
+            sqlx::query(
+                "INSERT INTO users (tenant_id, name, email, status) \
+                 SELECT $1, name, email, status FROM users_import ORDER BY n \
+                 ON CONFLICT (tenant_id, email) DO UPDATE SET name = EXCLUDED.name, status = EXCLUDED.status \
+                 WHERE (users.name, users.status) IS DISTINCT FROM (EXCLUDED.name, EXCLUDED.status) \
+                 RETURNING *, (xmax = 0) AS inserted",
+            )
+            .bind(tenant.id())
+            .fetch_all(&mut *conn),
+        )
+        .await?;
+
+        let mut report = SeedReport {
+            unchanged: self.users.len() - rows.len(),
+            ..SeedReport::default()
+        };
+        let events = rows
+            .iter()
+            .map(|row| report.record(row))
+            .collect::<Result<Vec<_>>>()?;
+        outbox::enqueue_all(conn, tenant, &events).await?;
+        Ok(report)
+    }
+}
+
+impl SeedReport {
+    /// Conta a linha devolvida por um upsert e monta o evento dela
+    fn record(&mut self, row: &PgRow) -> Result<DomainEvent> {
+        let saved = DbUser::from_row(row)?;
+        Ok(if row.try_get("inserted")? {
+            self.created += 1;
+            DomainEvent::UserCreated(saved)
+        } else {
+            self.updated += 1;
+            DomainEvent::UserUpdated(saved)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_seed_documents() {
+        let expected = SeedData {
+            users: vec![
+                SeedUser {
+                    name: "Ana".to_string(),
+                    email: "ana@example.com".to_string(),
+                    status: UserStatus::Active,
+                },
+                SeedUser {
+                    name: "Bia".to_string(),
+                    email: "bia@example.com".to_string(),
+                    status: UserStatus::Suspended,
+                },
+            ],
+        };
+
+        let json = r#"{"users": [
+            {"name": "Ana", "email": "ana@example.com"},
+            {"name": "Bia", "email": "bia@example.com", "status": "suspended"}
+        ]}"#;
+        let csv = "name,email,status\nAna,ana@example.com,active\nBia,bia@example.com,suspended\n";
+        let yaml = "- name: Ana\n  email: ana@example.com\n- name: Bia\n  email: bia@example.com\n  status: suspended\n";
+
+        assert_eq!(
+            SeedData::parse(json, DocumentFormat::Json).unwrap(),
+            expected
+        );
+        assert_eq!(SeedData::parse(csv, DocumentFormat::Csv).unwrap(), expected);
+        assert_eq!(
+            SeedData::parse(yaml, DocumentFormat::Yaml).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_seed() {
+        let invalid = [
+            r#"{"posts": []}"#,
+            r#"[{"name": "", "email": "a@example.com"}]"#,
+            r#"[{"name": "Ana", "email": "ana"}]"#,
+            r#"[{"name": "Ana", "email": "a@example.com", "status": "inactive"}]"#,
+            r#"[{"name": "Ana", "email": "a@example.com"}, {"name": "A", "email": "A@example.com"}]"#,
+        ];
+        for doc in invalid {
+            assert!(
+                SeedData::parse(doc, DocumentFormat::Json).is_err(),
+                "{}",
+                doc
+            );
+        }
+    }
+}
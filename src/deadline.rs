@@ -0,0 +1,132 @@
+//! Prazo de uma requisição, propagado até o banco
+//!
+//! O middleware `propagate_deadline` da API cria um `Deadline` a partir de
+//! `server.timeout_seconds` (o mesmo do timeout HTTP), coloca-o nas
+//! extensions e executa o restante da pilha com ele como o atual.
+//! `Deadline::current` o devolve em qualquer ponto da task, e `within`
+//! limita uma operação ao tempo que resta: quando o cliente já recebeu o
+//! timeout, as consultas do `PgUserRepository` param em vez de seguir
+//! ocupando conexões do pool.
+//!
+//! Fora de uma requisição (CLI, filas, tarefas de fundo) não há prazo e
+//! `within` só repassa a operação.
+
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+tokio::task_local! {
+    static CURRENT: Deadline;
+}
+
+/// Instante até o qual o trabalho de uma requisição ainda é útil
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// Prazo daqui a `timeout`
+    pub fn after(timeout: Duration) -> Self {
+        Self(Instant::now() + timeout)
+    }
+
+    /// Prazo em `instant`
+    pub fn at(instant: Instant) -> Self {
+        Self(instant)
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// Tempo restante (zero se o prazo já passou)
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.0
+    }
+
+    /// Prazo da task atual, se houver
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|deadline| *deadline).ok()
+    }
+
+    /// Executa `future` com este prazo como o atual
+    ///
+    /// Um prazo mais curto já em vigor continua valendo.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        let deadline = Self::current().map_or(self, |current| current.min(self));
+        CURRENT.scope(deadline, future).await
+    }
+
+    /// Executa `future` até o prazo; `DeadlineExceeded` se ele passar antes
+    pub async fn run<F: Future>(self, future: F) -> Result<F::Output, DeadlineExceeded> {
+        if self.is_expired() {
+            return Err(DeadlineExceeded);
+        }
+        tokio::time::timeout_at(self.0, future)
+            .await
+            .map_err(|_| DeadlineExceeded)
+    }
+}
+
+/// Executa `future` até o prazo da task atual (sem prazo, até o fim)
+pub async fn within<F: Future>(future: F) -> Result<F::Output, DeadlineExceeded> {
+    match Deadline::current() {
+        Some(deadline) => deadline.run(future).await,
+        None => Ok(future.await),
+    }
+}
+
+/// O prazo da requisição passou antes de a operação terminar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadlineExceeded;
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("request deadline exceeded")
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_within_without_deadline_runs_to_completion() {
+        assert_eq!(Deadline::current(), None);
+        assert_eq!(within(async { 42 }).await, Ok(42));
+    }
+
+    #[tokio::test]
+    async fn test_within_stops_at_the_deadline() {
+        let deadline = Deadline::after(Duration::from_millis(20));
+        let slow = tokio::time::sleep(Duration::from_secs(5));
+
+        let result = deadline
+            .scope(async {
+                assert_eq!(Deadline::current(), Some(deadline));
+                within(slow).await
+            })
+            .await;
+        assert_eq!(result, Err(DeadlineExceeded));
+        assert!(deadline.is_expired());
+        assert_eq!(deadline.remaining(), Duration::ZERO);
+
+        // Expirado, nem começa
+        assert_eq!(deadline.run(async { 1 }).await, Err(DeadlineExceeded));
+    }
+
+    #[tokio::test]
+    async fn test_scope_keeps_the_shorter_deadline() {
+        let short = Deadline::after(Duration::from_secs(1));
+        let long = Deadline::after(Duration::from_secs(10));
+
+        let inner = short.scope(long.scope(async { Deadline::current() })).await;
+        assert_eq!(inner, Some(short));
+    }
+}
@@ -0,0 +1,251 @@
+//! Barramento de eventos de domínio em processo
+//!
+//! Os handlers publicam um `DomainEvent` a cada mudança (ex.: usuário
+//! criado); consumidores como os webhooks assinam o barramento e recebem
+//! cada `Event` com ID sequencial, data e o tenant do usuário (quem repassa
+//! eventos para fora, como o SSE e os webhooks, filtra por ele). Assinantes lentos demais perdem
+//! os eventos mais antigos (`RecvError::Lagged`), sem travar quem publica.
+//!
+//! Os últimos eventos ficam guardados para quem precisa retomar de um ID
+//! (ex.: `Last-Event-ID` no stream SSE), via `subscribe_after`.
+//!
+//! Com Postgres, `postgres::spawn_bridge` traz para o barramento as mudanças
+//! feitas por outras instâncias (LISTEN/NOTIFY no canal `user_changes`).
+
+use crate::models::DbUser;
+use crate::tenant::TenantContext;
+use crate::trace_context::TraceContext;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+/// Eventos guardados para cada assinante (e para retomadas) antes de os mais
+/// antigos serem descartados
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Mudanças no domínio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DomainEvent {
+    #[serde(rename = "user.created")]
+    UserCreated(DbUser),
+    #[serde(rename = "user.updated")]
+    UserUpdated(DbUser),
+    #[serde(rename = "user.deleted")]
+    UserDeleted { id: i32 },
+}
+
+impl DomainEvent {
+    /// Nomes de todos os eventos
+    pub const NAMES: &'static [&'static str] = &["user.created", "user.updated", "user.deleted"];
+
+    /// ID do usuário do evento
+    pub fn user_id(&self) -> i32 {
+        match self {
+            Self::UserCreated(user) | Self::UserUpdated(user) => user.id,
+            Self::UserDeleted { id } => *id,
+        }
+    }
+
+    /// Nome do evento (ex.: `"user.created"`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UserCreated(_) => "user.created",
+            Self::UserUpdated(_) => "user.updated",
+            Self::UserDeleted { .. } => "user.deleted",
+        }
+    }
+}
+
+/// Evento publicado no barramento
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    /// Sequencial, crescente dentro do processo
+    pub id: u64,
+    pub occurred_at: DateTime<Utc>,
+    /// Tenant do usuário (`TenantContext::id`)
+    pub tenant: String,
+    #[serde(flatten)]
+    pub payload: DomainEvent,
+    /// Mudança feita por outra instância (ver `postgres::spawn_bridge`);
+    /// efeitos colaterais como webhooks ficam com a instância de origem
+    #[serde(skip)]
+    pub remote: bool,
+    /// Trace da operação que publicou o evento, propagado nas entregas
+    #[serde(skip)]
+    pub trace: Option<TraceContext>,
+}
+
+/// Barramento de eventos (clonável; os clones compartilham o canal)
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+    history: Arc<Mutex<History>>,
+}
+
+/// Próximo ID e os eventos mais recentes; o lock também garante que os
+/// eventos saiam na ordem dos IDs
+#[derive(Debug)]
+struct History {
+    next_id: u64,
+    capacity: usize,
+    events: VecDeque<Event>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            history: Arc::new(Mutex::new(History {
+                next_id: 1,
+                capacity,
+                events: VecDeque::with_capacity(capacity),
+            })),
+        }
+    }
+
+    /// Publica um evento do tenant; sem assinantes, ele fica só no histórico
+    pub fn publish(&self, tenant: &TenantContext, payload: DomainEvent) -> Event {
+        self.send(tenant, payload, false)
+    }
+
+    /// Publica uma mudança feita por outra instância
+    pub fn publish_remote(&self, tenant: &TenantContext, payload: DomainEvent) -> Event {
+        self.send(tenant, payload, true)
+    }
+
+    fn send(&self, tenant: &TenantContext, payload: DomainEvent, remote: bool) -> Event {
+        let mut history = self.history.lock().unwrap();
+        let event = Event {
+            id: history.next_id,
+            occurred_at: Utc::now(),
+            tenant: tenant.id().to_string(),
+            payload,
+            remote,
+            trace: TraceContext::current(),
+        };
+        history.next_id += 1;
+        if history.events.len() == history.capacity {
+            history.events.pop_front();
+        }
+        history.events.push_back(event.clone());
+        let _ = self.sender.send(event.clone());
+        drop(history);
+
+        tracing::debug!(
+            event_id = event.id,
+            event = event.payload.name(),
+            tenant = %event.tenant,
+            remote,
+            "Event published"
+        );
+        event
+    }
+
+    /// Recebe os eventos publicados a partir de agora
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+
+    /// Eventos guardados com ID maior que `last_id`, seguidos (sem lacunas
+    /// nem repetições) pelos publicados a partir de agora
+    ///
+    /// Eventos que já saíram do histórico não são devolvidos.
+    pub fn subscribe_after(&self, last_id: u64) -> (Vec<Event>, broadcast::Receiver<Event>) {
+        let history = self.history.lock().unwrap();
+        let missed = history
+            .events
+            .iter()
+            .filter(|event| event.id > last_id)
+            .cloned()
+            .collect();
+        (missed, self.sender.subscribe())
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acme() -> TenantContext {
+        TenantContext::new("acme").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_subscribe() {
+        let bus = EventBus::default();
+        let mut events = bus.subscribe();
+
+        bus.publish(&acme(), DomainEvent::UserDeleted { id: 1 });
+        bus.clone()
+            .publish(&acme(), DomainEvent::UserDeleted { id: 2 });
+
+        let first = events.recv().await.unwrap();
+        let second = events.recv().await.unwrap();
+        assert_eq!(first.payload, DomainEvent::UserDeleted { id: 1 });
+        assert_eq!(second.id, first.id + 1);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_after() {
+        let bus = EventBus::new(2);
+        for id in 1..=3 {
+            bus.publish(&acme(), DomainEvent::UserDeleted { id });
+        }
+
+        // O evento 1 já saiu do histórico (capacidade 2)
+        let (missed, _) = bus.subscribe_after(0);
+        assert_eq!(missed.iter().map(|e| e.id).collect::<Vec<_>>(), [2, 3]);
+
+        let (missed, mut events) = bus.subscribe_after(2);
+        assert_eq!(missed.iter().map(|e| e.id).collect::<Vec<_>>(), [3]);
+
+        bus.publish(&acme(), DomainEvent::UserDeleted { id: 4 });
+        assert_eq!(events.recv().await.unwrap().id, 4);
+
+        let (missed, _) = bus.subscribe_after(4);
+        assert!(missed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_publish_carries_current_trace() {
+        let bus = EventBus::default();
+        assert_eq!(
+            bus.publish(&acme(), DomainEvent::UserDeleted { id: 1 })
+                .trace,
+            None
+        );
+
+        let context = TraceContext::new_root();
+        let event = context
+            .clone()
+            .scope(async { bus.publish(&acme(), DomainEvent::UserDeleted { id: 2 }) })
+            .await;
+        assert_eq!(event.trace, Some(context));
+    }
+
+    #[test]
+    fn test_event_json_shape() {
+        let bus = EventBus::default();
+        let event = bus.publish(&acme(), DomainEvent::UserDeleted { id: 7 });
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["type"], "user.deleted");
+        assert_eq!(json["data"]["id"], 7);
+        assert_eq!(json["tenant"], "acme");
+        assert_eq!(json["id"], event.id);
+        assert_eq!(serde_json::from_value::<Event>(json).unwrap(), event);
+    }
+}
@@ -0,0 +1,163 @@
+//! Ponte entre o LISTEN/NOTIFY do Postgres e o `EventBus`
+//!
+//! O trigger `users_notify_changes` (migration `notify_user_changes`) avisa
+//! cada INSERT, UPDATE e DELETE em `users` no canal `user_changes`, com o
+//! tenant do usuário e o `app.instance_id` da conexão de origem. A ponte escuta o canal e publica
+//! como evento remoto o que veio de outras instâncias; as mudanças da própria
+//! instância já foram publicadas pelos handlers.
+
+use super::{DomainEvent, EventBus};
+use crate::db;
+use crate::models::DbUser;
+use crate::tenant::TenantContext;
+use serde::Deserialize;
+use sqlx::postgres::{PgListener, PgPool};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Canal das notificações de mudanças em usuários
+pub const CHANNEL: &str = "user_changes";
+
+/// Espera antes de tentar escutar de novo após uma falha
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Corpo de uma notificação de `user_changes`
+#[derive(Debug, Deserialize)]
+struct Change {
+    op: String,
+    id: i32,
+    tenant_id: String,
+    origin: Option<String>,
+    user: Option<DbUser>,
+}
+
+/// Escuta `user_changes` em segundo plano e republica no barramento
+///
+/// Se a conexão cair, o `PgListener` reconecta sozinho; notificações
+/// enviadas enquanto isso são perdidas. Termina quando o pool é fechado.
+pub fn spawn_bridge(pool: PgPool, bus: EventBus) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let result = listen(&pool, &bus).await;
+            // Pool fechado no desligamento (`Database::close`)
+            if pool.is_closed() {
+                break;
+            }
+            if let Err(e) = result {
+                tracing::error!(error = %e, channel = CHANNEL, "Event bridge failed; retrying");
+            }
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    })
+}
+
+async fn listen(pool: &PgPool, bus: &EventBus) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(CHANNEL).await?;
+    tracing::info!(
+        channel = CHANNEL,
+        "Listening for changes from other instances"
+    );
+
+    loop {
+        let notification = listener.recv().await?;
+        match parse(notification.payload(), db::instance_id()) {
+            Ok(Some((tenant, event))) => {
+                bus.publish_remote(&tenant, event);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(
+                error = %e,
+                payload = notification.payload(),
+                "Ignoring malformed change notification"
+            ),
+        }
+    }
+}
+
+/// Converte a notificação no tenant e no evento; `None` para mudanças desta
+/// instância
+fn parse(
+    payload: &str,
+    own_instance: &str,
+) -> anyhow::Result<Option<(TenantContext, DomainEvent)>> {
+    let change: Change = serde_json::from_str(payload)?;
+    if change.origin.as_deref() == Some(own_instance) {
+        return Ok(None);
+    }
+    let tenant = TenantContext::new(&change.tenant_id)?;
+
+    let event = match (change.op.as_str(), change.user) {
+        ("INSERT", Some(user)) => DomainEvent::UserCreated(user),
+        ("UPDATE", Some(user)) => DomainEvent::UserUpdated(user),
+        ("DELETE", _) => DomainEvent::UserDeleted { id: change.id },
+        (op, _) => anyhow::bail!("unexpected change '{}'", op),
+    };
+    Ok(Some((tenant, event)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = r#"{"id": 7, "name": "Ana", "email": "ana@example.com",
+        "status": "suspended", "created_at": "2024-02-01T12:30:00.123456"}"#;
+
+    #[test]
+    fn test_parse_changes() {
+        let insert = format!(
+            r#"{{"op": "INSERT", "id": 7, "tenant_id": "acme", "origin": "b", "user": {}}}"#,
+            USER
+        );
+        match parse(&insert, "a").unwrap() {
+            Some((tenant, DomainEvent::UserCreated(user))) => {
+                assert_eq!(tenant.id(), "acme");
+                assert_eq!(user.email, "ana@example.com");
+                assert_eq!(user.status, crate::models::UserStatus::Suspended);
+                assert!(user.created_at.is_some());
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let update = format!(
+            r#"{{"op": "UPDATE", "id": 7, "tenant_id": "acme", "origin": null, "user": {}}}"#,
+            USER
+        );
+        assert!(matches!(
+            parse(&update, "a").unwrap(),
+            Some((_, DomainEvent::UserUpdated(_)))
+        ));
+
+        assert_eq!(
+            parse(
+                r#"{"op": "DELETE", "id": 7, "tenant_id": "acme", "origin": "b"}"#,
+                "a"
+            )
+            .unwrap(),
+            Some((
+                TenantContext::new("acme").unwrap(),
+                DomainEvent::UserDeleted { id: 7 }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_skips_own_changes() {
+        assert_eq!(
+            parse(
+                r#"{"op": "DELETE", "id": 7, "tenant_id": "acme", "origin": "a"}"#,
+                "a"
+            )
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed() {
+        assert!(parse("{}", "a").is_err());
+        assert!(parse(r#"{"op": "TRUNCATE", "id": 0, "tenant_id": "acme"}"#, "a").is_err());
+        assert!(parse(r#"{"op": "INSERT", "id": 7, "tenant_id": "acme"}"#, "a").is_err());
+        assert!(parse(r#"{"op": "DELETE", "id": 7, "tenant_id": "a b"}"#, "a").is_err());
+    }
+}
@@ -0,0 +1,338 @@
+//! Exportação de usuários em segundo plano (feature "api")
+//!
+//! O padrão das operações longas: `POST /api/exports` registra um
+//! `ExportJob` e responde 202 na hora; uma task gera o CSV página a página e
+//! o cliente consulta `GET /api/exports/:id` até o status `completed`, quando
+//! a resposta traz a URL de download. Jobs e arquivos ficam na memória do
+//! processo (cada réplica só conhece os seus) e são descartados `ttl` depois
+//! de terminar.
+//!
+//! O mesmo registro guarda as exportações dos dados de um usuário
+//! (`GET /api/users/:id/export` de contas grandes): `ExportKind` diz o que
+//! o arquivo contém e em que formato.
+
+use crate::models::DbUser;
+use crate::repository::UserRepository;
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Usuários lidos por consulta durante a exportação
+pub const EXPORT_BATCH_SIZE: i64 = 500;
+
+/// Por quanto tempo um job terminado (e o seu arquivo) continua disponível
+pub const DEFAULT_EXPORT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Colunas do CSV exportado
+pub const EXPORT_COLUMNS: [&str; 6] = [
+    "id",
+    "name",
+    "email",
+    "status",
+    "created_at",
+    "deletion_scheduled_at",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl ExportStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed)
+    }
+}
+
+/// O que uma exportação contém
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportKind {
+    /// Os usuários do tenant, em CSV
+    Users,
+    /// Tudo o que está guardado sobre um usuário, em JSON
+    User { user_id: i32 },
+}
+
+impl ExportKind {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Users => "text/csv; charset=utf-8",
+            Self::User { .. } => "application/json",
+        }
+    }
+
+    /// Nome do arquivo baixado
+    pub fn file_name(self, id: Uuid) -> String {
+        match self {
+            Self::Users => format!("users-{}.csv", id),
+            Self::User { user_id } => format!("user-{}-export.json", user_id),
+        }
+    }
+
+    /// Extensão do arquivo guardado no storage
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Users => "csv",
+            Self::User { .. } => "json",
+        }
+    }
+}
+
+/// Situação de uma exportação
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportJob {
+    pub id: Uuid,
+    #[serde(flatten)]
+    pub kind: ExportKind,
+    pub status: ExportStatus,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Usuários (ou, na exportação de um usuário, registros) exportados,
+    /// quando concluída
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct Entry {
+    tenant: TenantContext,
+    job: ExportJob,
+    file: Option<Arc<[u8]>>,
+}
+
+/// Registro das exportações (clonável; os clones compartilham o registro)
+#[derive(Clone)]
+pub struct ExportJobs {
+    jobs: Arc<RwLock<HashMap<Uuid, Entry>>>,
+    pub ttl: Duration,
+}
+
+impl Default for ExportJobs {
+    fn default() -> Self {
+        Self::new(DEFAULT_EXPORT_TTL)
+    }
+}
+
+impl ExportJobs {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    /// Registra a exportação dos usuários do `tenant` e a executa numa task
+    pub fn start(&self, users: Arc<dyn UserRepository>, tenant: TenantContext) -> ExportJob {
+        self.spawn(tenant.clone(), ExportKind::Users, async move {
+            export_csv(users.as_ref(), &tenant).await
+        })
+    }
+
+    /// Registra uma exportação do `tenant` e executa `work` numa task; o
+    /// resultado é a quantidade de registros e o conteúdo do arquivo
+    pub fn spawn<F>(&self, tenant: TenantContext, kind: ExportKind, work: F) -> ExportJob
+    where
+        F: Future<Output = Result<(usize, Vec<u8>)>> + Send + 'static,
+    {
+        self.prune(Utc::now());
+
+        let job = ExportJob {
+            id: Uuid::new_v4(),
+            kind,
+            status: ExportStatus::Pending,
+            created_at: Utc::now(),
+            finished_at: None,
+            rows: None,
+            error: None,
+        };
+        self.write().insert(
+            job.id,
+            Entry {
+                tenant,
+                job: job.clone(),
+                file: None,
+            },
+        );
+
+        let jobs = self.clone();
+        let id = job.id;
+        tokio::spawn(async move {
+            jobs.update(id, |entry| entry.job.status = ExportStatus::Running);
+            let result = work.await;
+            if let Err(e) = &result {
+                tracing::warn!(export_id = %id, kind = ?kind, error = %e, "Export failed");
+            }
+            jobs.update(id, |entry| {
+                entry.job.finished_at = Some(Utc::now());
+                match result {
+                    Ok((rows, file)) => {
+                        entry.job.status = ExportStatus::Completed;
+                        entry.job.rows = Some(rows);
+                        entry.file = Some(file.into());
+                    }
+                    Err(e) => {
+                        entry.job.status = ExportStatus::Failed;
+                        entry.job.error = Some(e.to_string());
+                    }
+                }
+            });
+        });
+
+        job
+    }
+
+    /// Situação de uma exportação do `tenant`
+    pub fn get(&self, tenant: &TenantContext, id: Uuid) -> Option<ExportJob> {
+        self.read()
+            .get(&id)
+            .filter(|entry| entry.tenant == *tenant)
+            .map(|entry| entry.job.clone())
+    }
+
+    /// O arquivo de uma exportação concluída do `tenant`
+    pub fn download(&self, tenant: &TenantContext, id: Uuid) -> Option<Arc<[u8]>> {
+        self.read()
+            .get(&id)
+            .filter(|entry| entry.tenant == *tenant)
+            .and_then(|entry| entry.file.clone())
+    }
+
+    /// Descarta os jobs terminados há mais de `ttl`
+    fn prune(&self, now: DateTime<Utc>) {
+        let ttl = chrono::Duration::from_std(self.ttl).unwrap_or(chrono::Duration::MAX);
+        self.write().retain(|_, entry| {
+            entry
+                .job
+                .finished_at
+                .is_none_or(|finished_at| now - finished_at <= ttl)
+        });
+    }
+
+    fn update(&self, id: Uuid, apply: impl FnOnce(&mut Entry)) {
+        if let Some(entry) = self.write().get_mut(&id) {
+            apply(entry);
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<Uuid, Entry>> {
+        self.jobs.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<Uuid, Entry>> {
+        self.jobs.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Gera o CSV com todos os usuários do `tenant`; devolve quantos foram
+/// exportados e o conteúdo
+pub async fn export_csv(
+    users: &dyn UserRepository,
+    tenant: &TenantContext,
+) -> Result<(usize, Vec<u8>)> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(EXPORT_COLUMNS)?;
+
+    let mut rows = 0;
+    loop {
+        let page = users
+            .list_page(tenant, EXPORT_BATCH_SIZE, rows as i64)
+            .await?;
+        for user in &page {
+            writer.write_record(record(user))?;
+        }
+        rows += page.len();
+        if (page.len() as i64) < EXPORT_BATCH_SIZE {
+            break;
+        }
+    }
+
+    let csv = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok((rows, csv))
+}
+
+fn record(user: &DbUser) -> [String; 6] {
+    [
+        user.id.to_string(),
+        user.name.clone(),
+        user.email.clone(),
+        user.status.to_string(),
+        user.created_at.map(|at| at.to_string()).unwrap_or_default(),
+        user.deletion_scheduled_at
+            .map(|at| at.to_rfc3339())
+            .unwrap_or_default(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::Fixtures;
+    use crate::repository::InMemoryUserRepository;
+
+    async fn wait_finished(jobs: &ExportJobs, tenant: &TenantContext, id: Uuid) -> ExportJob {
+        for _ in 0..100 {
+            let job = jobs.get(tenant, id).unwrap();
+            if job.status.is_finished() {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("export {} did not finish", id);
+    }
+
+    #[tokio::test]
+    async fn test_export_job_produces_csv() {
+        let users = Fixtures::seeded(1).users(3);
+        let repo = Arc::new(InMemoryUserRepository::with_users(users.clone()));
+        let tenant = TenantContext::default_tenant();
+        let jobs = ExportJobs::default();
+
+        let job = jobs.start(repo, tenant.clone());
+        assert_eq!(job.status, ExportStatus::Pending);
+        assert!(jobs.download(&tenant, job.id).is_none());
+
+        let job = wait_finished(&jobs, &tenant, job.id).await;
+        assert_eq!(job.status, ExportStatus::Completed);
+        assert_eq!(job.rows, Some(3));
+
+        let csv = jobs.download(&tenant, job.id).unwrap();
+        let csv = std::str::from_utf8(&csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(EXPORT_COLUMNS.join(",").as_str()));
+        assert!(lines.next().unwrap().contains(&users[0].email));
+        assert_eq!(lines.count(), 2);
+
+        // Outro tenant não enxerga o job
+        let other = TenantContext::new("other").unwrap();
+        assert!(jobs.get(&other, job.id).is_none());
+        assert!(jobs.download(&other, job.id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_finished_jobs_expire() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let tenant = TenantContext::default_tenant();
+        let jobs = ExportJobs::new(Duration::from_secs(60));
+
+        let job = jobs.start(repo, tenant.clone());
+        wait_finished(&jobs, &tenant, job.id).await;
+
+        jobs.prune(Utc::now());
+        assert!(jobs.get(&tenant, job.id).is_some());
+        jobs.prune(Utc::now() + chrono::Duration::minutes(2));
+        assert!(jobs.get(&tenant, job.id).is_none());
+    }
+}
@@ -0,0 +1,196 @@
+//! Interface C (feature "ffi")
+//!
+//! Funções `extern "C"` para embutir a biblioteca em C/C++. `User` é um
+//! handle opaco: crie com `rae_user_new`, libere com `rae_user_free`.
+//! Strings devolvidas pela biblioteca são liberadas com `rae_string_free`.
+//!
+//! O header `include/rust_app_exemplo.h` é gerado pelo cbindgen
+//! (`make ffi-header`).
+
+use crate::User;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+/// Maior `n` cujo fibonacci cabe em `u64`
+const MAX_FIBONACCI_INPUT: u64 = 93;
+
+/// Converte uma string C em `String`; `None` se for nula ou não for UTF-8
+///
+/// # Safety
+///
+/// `s` deve ser nulo ou apontar para uma string C válida.
+unsafe fn to_string(s: *const c_char) -> Option<String> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok().map(str::to_owned)
+}
+
+/// Cria um usuário ativo
+///
+/// Devolve `NULL` se `name` ou `email` forem nulos ou não forem UTF-8.
+///
+/// # Safety
+///
+/// `name` e `email` devem ser nulos ou apontar para strings C válidas.
+#[no_mangle]
+pub unsafe extern "C" fn rae_user_new(
+    id: u64,
+    name: *const c_char,
+    email: *const c_char,
+) -> *mut User {
+    match (to_string(name), to_string(email)) {
+        (Some(name), Some(email)) => Box::into_raw(Box::new(User::new(id, name, email))),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Libera um usuário criado por `rae_user_new` (aceita `NULL`)
+///
+/// # Safety
+///
+/// `user` deve ser nulo ou ter vindo de `rae_user_new`, e não pode ser
+/// usado depois desta chamada.
+#[no_mangle]
+pub unsafe extern "C" fn rae_user_free(user: *mut User) {
+    if !user.is_null() {
+        drop(Box::from_raw(user));
+    }
+}
+
+/// ID do usuário
+///
+/// # Safety
+///
+/// `user` deve ser um handle válido (não nulo).
+#[no_mangle]
+pub unsafe extern "C" fn rae_user_id(user: *const User) -> u64 {
+    (*user).id
+}
+
+/// Nome do usuário; libere o resultado com `rae_string_free`
+///
+/// # Safety
+///
+/// `user` deve ser um handle válido (não nulo).
+#[no_mangle]
+pub unsafe extern "C" fn rae_user_name(user: *const User) -> *mut c_char {
+    into_c_string(&(*user).name)
+}
+
+/// Email do usuário; libere o resultado com `rae_string_free`
+///
+/// # Safety
+///
+/// `user` deve ser um handle válido (não nulo).
+#[no_mangle]
+pub unsafe extern "C" fn rae_user_email(user: *const User) -> *mut c_char {
+    into_c_string(&(*user).email)
+}
+
+/// Indica se o usuário está ativo
+///
+/// # Safety
+///
+/// `user` deve ser um handle válido (não nulo).
+#[no_mangle]
+pub unsafe extern "C" fn rae_user_is_active(user: *const User) -> bool {
+    (*user).active
+}
+
+/// Ativa ou desativa o usuário
+///
+/// # Safety
+///
+/// `user` deve ser um handle válido (não nulo).
+#[no_mangle]
+pub unsafe extern "C" fn rae_user_set_active(user: *mut User, active: bool) {
+    if active {
+        (*user).activate();
+    } else {
+        (*user).deactivate();
+    }
+}
+
+/// Libera uma string devolvida pela biblioteca (aceita `NULL`)
+///
+/// # Safety
+///
+/// `s` deve ser nulo ou ter vindo desta biblioteca, e não pode ser usado
+/// depois desta chamada.
+#[no_mangle]
+pub unsafe extern "C" fn rae_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// N-ésimo número de Fibonacci; `UINT64_MAX` se `n` > 93 (não cabe em 64 bits)
+#[no_mangle]
+pub extern "C" fn rae_fibonacci(n: u64) -> u64 {
+    if n > MAX_FIBONACCI_INPUT {
+        return u64::MAX;
+    }
+    crate::fibonacci_optimized(n)
+}
+
+/// Verifica se `n` é primo
+#[no_mangle]
+pub extern "C" fn rae_is_prime(n: u64) -> bool {
+    crate::is_prime(n)
+}
+
+/// Copia para uma string C; bytes nulos internos a truncariam, então viram `NULL`
+fn into_c_string(s: &str) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_lifecycle() {
+        let name = CString::new("Ana").unwrap();
+        let email = CString::new("ana@example.com").unwrap();
+
+        unsafe {
+            let user = rae_user_new(7, name.as_ptr(), email.as_ptr());
+            assert!(!user.is_null());
+            assert_eq!(rae_user_id(user), 7);
+            assert!(rae_user_is_active(user));
+
+            rae_user_set_active(user, false);
+            assert!(!rae_user_is_active(user));
+
+            let returned = rae_user_email(user);
+            assert_eq!(
+                CStr::from_ptr(returned).to_str().unwrap(),
+                "ana@example.com"
+            );
+            rae_string_free(returned);
+
+            rae_user_free(user);
+            rae_user_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_user_new_rejects_invalid_strings() {
+        let invalid = [0xffu8, 0];
+        let email = CString::new("ana@example.com").unwrap();
+
+        unsafe {
+            assert!(rae_user_new(1, ptr::null(), email.as_ptr()).is_null());
+            assert!(rae_user_new(1, invalid.as_ptr().cast(), email.as_ptr()).is_null());
+        }
+    }
+
+    #[test]
+    fn test_math() {
+        assert_eq!(rae_fibonacci(10), 55);
+        assert_eq!(rae_fibonacci(94), u64::MAX);
+        assert!(rae_is_prime(97));
+        assert!(!rae_is_prime(1));
+    }
+}
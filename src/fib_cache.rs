@@ -0,0 +1,150 @@
+//! Cache de fibonacci compartilhado entre chamadas
+//!
+//! `FibCache` guarda a tabela `F(0)..=F(k)` já calculada; pedir um `n` já
+//! coberto é só uma leitura, e um `n` maior estende a tabela a partir do
+//! último valor. Como só cabem em `u64` os valores até `F(93)`, a tabela
+//! nunca passa de 94 entradas.
+//!
+//! `fib_cached` usa a instância global (`FibCache::global`), cujas
+//! estatísticas o `/metrics` da API exporta.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+/// Maior `n` cujo fibonacci cabe em `u64`
+pub const MAX_FIBONACCI_INPUT: u64 = 93;
+
+static GLOBAL: OnceLock<FibCache> = OnceLock::new();
+
+/// Contadores de uso de um `FibCache`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FibCacheStats {
+    /// Consultas respondidas pela tabela
+    pub hits: u64,
+    /// Consultas que estenderam a tabela
+    pub misses: u64,
+    /// Valores guardados na tabela
+    pub entries: usize,
+}
+
+/// Tabela de fibonacci memoizada, segura para uso entre threads
+#[derive(Debug)]
+pub struct FibCache {
+    table: RwLock<Vec<u64>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Default for FibCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FibCache {
+    /// Cria um cache vazio (só com `F(0)` e `F(1)`)
+    pub fn new() -> Self {
+        Self {
+            table: RwLock::new(vec![0, 1]),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Instância compartilhada pelo processo
+    pub fn global() -> &'static FibCache {
+        GLOBAL.get_or_init(FibCache::new)
+    }
+
+    /// N-ésimo número de Fibonacci; `None` se `n` > 93 (não cabe em `u64`)
+    pub fn get(&self, n: u64) -> Option<u64> {
+        if n > MAX_FIBONACCI_INPUT {
+            return None;
+        }
+        let index = n as usize;
+
+        let cached = self
+            .table
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(index)
+            .copied();
+        if let Some(value) = cached {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Some(value);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let mut table = self.table.write().unwrap_or_else(|e| e.into_inner());
+        // Outra thread pode ter estendido a tabela entre os dois locks
+        while table.len() <= index {
+            let next = table[table.len() - 1] + table[table.len() - 2];
+            table.push(next);
+        }
+        Some(table[index])
+    }
+
+    /// Contadores atuais
+    pub fn stats(&self) -> FibCacheStats {
+        FibCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.table.read().unwrap_or_else(|e| e.into_inner()).len(),
+        }
+    }
+}
+
+/// `FibCache::get` na instância global
+pub fn fib_cached(n: u64) -> Option<u64> {
+    FibCache::global().get(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci_optimized;
+
+    #[test]
+    fn test_matches_fibonacci_optimized() {
+        let cache = FibCache::new();
+        for n in [10, 0, 93, 1, 50] {
+            assert_eq!(cache.get(n), Some(fibonacci_optimized(n)), "n = {}", n);
+        }
+        assert_eq!(cache.get(94), None);
+        assert_eq!(cache.get(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_stats_count_hits_and_misses() {
+        let cache = FibCache::new();
+        assert_eq!(
+            cache.stats(),
+            FibCacheStats {
+                hits: 0,
+                misses: 0,
+                entries: 2
+            }
+        );
+
+        cache.get(20);
+        cache.get(20);
+        cache.get(5);
+        cache.get(1);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (3, 1));
+        assert_eq!(stats.entries, 21);
+    }
+
+    #[test]
+    fn test_concurrent_gets() {
+        let cache = FibCache::new();
+        std::thread::scope(|s| {
+            for n in [93, 40, 70, 93] {
+                let cache = &cache;
+                s.spawn(move || assert_eq!(cache.get(n), Some(fibonacci_optimized(n))));
+            }
+        });
+        assert_eq!(cache.stats().entries, 94);
+    }
+}
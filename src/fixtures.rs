@@ -0,0 +1,227 @@
+//! Geração de dados de teste realistas
+//!
+//! `fixtures::user()` devolve um `UserBuilder` com nome, email e data de
+//! criação aleatórios, que podem ser sobrescritos; `fixtures::users(n)` gera
+//! `n` usuários de uma vez. Para dados reproduzíveis, use
+//! `Fixtures::seeded(seed)`: a mesma semente produz sempre os mesmos usuários.
+//!
+//! ```
+//! use rust_app_exemplo::fixtures::{self, Fixtures};
+//!
+//! let admin = fixtures::user().name("Admin").build();
+//! assert_eq!(admin.name, "Admin");
+//!
+//! let a = Fixtures::seeded(42).users(3);
+//! let b = Fixtures::seeded(42).users(3);
+//! assert_eq!(a, b);
+//! ```
+
+use crate::models::{DbUser, UserStatus, DEFAULT_ROLE};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const FIRST_NAMES: &[&str] = &[
+    "Ana", "Bruno", "Carla", "Daniel", "Eduarda", "Felipe", "Gabriela", "Henrique", "Isabela",
+    "João", "Larissa", "Marcos", "Natália", "Otávio", "Paula", "Rafael", "Sofia", "Thiago",
+    "Vitória", "William",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Silva",
+    "Santos",
+    "Oliveira",
+    "Souza",
+    "Rodrigues",
+    "Ferreira",
+    "Alves",
+    "Pereira",
+    "Lima",
+    "Gomes",
+    "Costa",
+    "Ribeiro",
+    "Martins",
+    "Carvalho",
+    "Almeida",
+];
+
+const DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
+
+/// Fração de usuários gerados como desativados
+const INACTIVE_RATIO: f64 = 0.1;
+
+/// Janela, em dias, em que as datas de criação são sorteadas
+const CREATED_WITHIN_DAYS: i64 = 365;
+
+/// Gerador de fixtures com estado (RNG e sequência de IDs/emails)
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    rng: StdRng,
+    sequence: i32,
+}
+
+impl Fixtures {
+    /// Gerador com semente aleatória
+    pub fn new() -> Self {
+        Self::from_rng(StdRng::from_entropy())
+    }
+
+    /// Gerador determinístico
+    pub fn seeded(seed: u64) -> Self {
+        Self::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng(rng: StdRng) -> Self {
+        Self { rng, sequence: 0 }
+    }
+
+    /// Builder de um usuário aleatório
+    pub fn user(&mut self) -> UserBuilder {
+        self.sequence += 1;
+        let n = self.sequence;
+
+        let first = pick(&mut self.rng, FIRST_NAMES);
+        let last = pick(&mut self.rng, LAST_NAMES);
+        let domain = pick(&mut self.rng, DOMAINS);
+        let email = format!("{}.{}{}@{}", slug(first), slug(last), n, domain);
+
+        let epoch = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .expect("valid date");
+        let offset = self.rng.gen_range(0..CREATED_WITHIN_DAYS * 24 * 60 * 60);
+
+        UserBuilder {
+            user: DbUser {
+                id: n,
+                name: format!("{} {}", first, last),
+                email,
+                status: if self.rng.gen_bool(INACTIVE_RATIO) {
+                    UserStatus::Deactivated
+                } else {
+                    UserStatus::Active
+                },
+                created_at: Some(epoch + Duration::seconds(offset)),
+                deletion_scheduled_at: None,
+                last_login_at: None,
+                role: DEFAULT_ROLE.to_string(),
+            },
+        }
+    }
+
+    /// `n` usuários aleatórios com IDs e emails únicos
+    pub fn users(&mut self, n: usize) -> Vec<DbUser> {
+        (0..n).map(|_| self.user().build()).collect()
+    }
+}
+
+impl Default for Fixtures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder de usuário com valores aleatórios sobrescrevíveis
+#[derive(Debug, Clone)]
+pub struct UserBuilder {
+    user: DbUser,
+}
+
+impl UserBuilder {
+    pub fn id(mut self, id: i32) -> Self {
+        self.user.id = id;
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.user.name = name.into();
+        self
+    }
+
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.user.email = email.into();
+        self
+    }
+
+    pub fn status(mut self, status: UserStatus) -> Self {
+        self.user.status = status;
+        self
+    }
+
+    pub fn created_at(mut self, created_at: NaiveDateTime) -> Self {
+        self.user.created_at = Some(created_at);
+        self
+    }
+
+    pub fn build(self) -> DbUser {
+        self.user
+    }
+}
+
+/// Builder de um usuário aleatório (sem semente)
+pub fn user() -> UserBuilder {
+    Fixtures::new().user()
+}
+
+/// `n` usuários aleatórios (sem semente)
+pub fn users(n: usize) -> Vec<DbUser> {
+    Fixtures::new().users(n)
+}
+
+fn pick<'a>(rng: &mut StdRng, values: &[&'a str]) -> &'a str {
+    values[rng.gen_range(0..values.len())]
+}
+
+/// Parte local de email: minúsculas e sem acentos
+fn slug(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'Á' => 'a',
+            'é' | 'ê' | 'É' => 'e',
+            'í' | 'Í' => 'i',
+            'ó' | 'ô' | 'õ' | 'Ó' => 'o',
+            'ú' | 'Ú' => 'u',
+            'ç' => 'c',
+            c => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_seeded_fixtures_are_deterministic() {
+        assert_eq!(Fixtures::seeded(7).users(5), Fixtures::seeded(7).users(5));
+        assert_ne!(Fixtures::seeded(7).users(5), Fixtures::seeded(8).users(5));
+    }
+
+    #[test]
+    fn test_users_have_unique_ids_and_emails() {
+        let users = users(200);
+        let ids: HashSet<i32> = users.iter().map(|u| u.id).collect();
+        let emails: HashSet<&str> = users.iter().map(|u| u.email.as_str()).collect();
+
+        assert_eq!(ids.len(), 200);
+        assert_eq!(emails.len(), 200);
+        assert!(users
+            .iter()
+            .all(|u| u.email.is_ascii() && u.email.contains('@')));
+        assert!(users.iter().all(|u| u.created_at.is_some()));
+    }
+
+    #[test]
+    fn test_builder_overrides() {
+        let user = user()
+            .id(10)
+            .name("Ana")
+            .email("ana@example.com")
+            .status(UserStatus::Suspended)
+            .build();
+        assert_eq!(user.id, 10);
+        assert_eq!(user.name, "Ana");
+        assert_eq!(user.email, "ana@example.com");
+        assert_eq!(user.status, UserStatus::Suspended);
+    }
+}
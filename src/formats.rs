@@ -0,0 +1,114 @@
+//! Leitura de documentos JSON, CSV e YAML
+//!
+//! Usado pelo comando `process`: o formato é deduzido pela extensão do
+//! arquivo e o conteúdo é convertido para `serde_json::Value`. Um CSV vira
+//! uma lista de objetos indexados pelo cabeçalho.
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// Formatos de documento suportados
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Json,
+    Csv,
+    Yaml,
+}
+
+impl DocumentFormat {
+    /// Deduz o formato pela extensão; sem extensão conhecida, assume JSON
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("csv") => Self::Csv,
+            Some("yaml" | "yml") => Self::Yaml,
+            _ => Self::Json,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Csv => "CSV",
+            Self::Yaml => "YAML",
+        }
+    }
+}
+
+/// Converte o conteúdo de um documento para JSON
+pub fn parse(content: &str, format: DocumentFormat) -> Result<Value> {
+    match format {
+        DocumentFormat::Json => serde_json::from_str(content).context("invalid JSON document"),
+        DocumentFormat::Yaml => serde_yaml::from_str(content).context("invalid YAML document"),
+        DocumentFormat::Csv => parse_csv(content),
+    }
+}
+
+fn parse_csv(content: &str) -> Result<Value> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers().context("invalid CSV header")?.clone();
+
+    let mut rows = Vec::new();
+    for (line, record) in reader.records().enumerate() {
+        let record = record.with_context(|| format!("invalid CSV record {}", line + 1))?;
+        let row: Map<String, Value> = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(header, field)| (header.to_string(), Value::String(field.to_string())))
+            .collect();
+        rows.push(Value::Object(row));
+    }
+
+    Ok(Value::Array(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("a.json")),
+            DocumentFormat::Json
+        );
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("a.CSV")),
+            DocumentFormat::Csv
+        );
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("a.yml")),
+            DocumentFormat::Yaml
+        );
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("a")),
+            DocumentFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_parse_documents() {
+        let expected = json!([{"name": "Ana", "email": "ana@example.com"}]);
+
+        let json_doc = r#"[{"name": "Ana", "email": "ana@example.com"}]"#;
+        let csv_doc = "name,email\nAna,ana@example.com\n";
+        let yaml_doc = "- name: Ana\n  email: ana@example.com\n";
+
+        assert_eq!(parse(json_doc, DocumentFormat::Json).unwrap(), expected);
+        assert_eq!(parse(csv_doc, DocumentFormat::Csv).unwrap(), expected);
+        assert_eq!(parse(yaml_doc, DocumentFormat::Yaml).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_input() {
+        assert!(parse("{", DocumentFormat::Json).is_err());
+        assert!(parse("a,b\n1,2,3\n", DocumentFormat::Csv).is_err());
+        assert!(parse("key: [unclosed", DocumentFormat::Yaml).is_err());
+    }
+}
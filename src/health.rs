@@ -0,0 +1,298 @@
+//! Subsistema de health check
+//!
+//! Cada verificação produz um `CheckResult`; o `HealthReport` agrega os
+//! resultados e assume o pior status entre eles. As verificações de disco e
+//! memória dependem da feature "system-health" e são ligadas em
+//! `[health.disk]` / `[health.memory]`.
+//!
+//! Verificações feitas em segundo plano (ex.: o monitor do pool do banco)
+//! gravam o último resultado num `HealthRegistry`, incluído no relatório.
+
+use crate::config::HealthConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// Status de uma verificação (ordenado do melhor para o pior)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    Warning,
+    Critical,
+}
+
+/// Resultado de uma verificação individual
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub details: Map<String, Value>,
+}
+
+impl CheckResult {
+    pub fn new(name: impl Into<String>, status: HealthStatus) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            message: None,
+            details: Map::new(),
+        }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub fn with_detail(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.details.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+/// Relatório consolidado exposto em `/health`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<CheckResult>,
+}
+
+impl HealthReport {
+    /// Agrega os resultados; sem verificações, o status é `healthy`
+    pub fn new(checks: Vec<CheckResult>) -> Self {
+        let status = checks
+            .iter()
+            .map(|check| check.status)
+            .max()
+            .unwrap_or(HealthStatus::Healthy);
+
+        Self { status, checks }
+    }
+
+    pub fn is_critical(&self) -> bool {
+        self.status == HealthStatus::Critical
+    }
+}
+
+/// Últimos resultados das verificações feitas em segundo plano, por nome
+#[derive(Debug, Clone, Default)]
+pub struct HealthRegistry {
+    checks: Arc<RwLock<BTreeMap<String, CheckResult>>>,
+}
+
+impl HealthRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grava o resultado, substituindo o anterior de mesmo nome
+    pub fn record(&self, check: CheckResult) {
+        self.checks
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(check.name.clone(), check);
+    }
+
+    pub fn remove(&self, name: &str) {
+        self.checks
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(name);
+    }
+
+    pub fn get(&self, name: &str) -> Option<CheckResult> {
+        self.checks
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(name)
+            .cloned()
+    }
+
+    /// Resultados gravados, em ordem de nome
+    pub fn checks(&self) -> Vec<CheckResult> {
+        self.checks
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Classifica um uso percentual segundo os limites de warning/critical
+pub fn threshold_status(used_percent: f64, warning: f64, critical: f64) -> HealthStatus {
+    if used_percent >= critical {
+        HealthStatus::Critical
+    } else if used_percent >= warning {
+        HealthStatus::Warning
+    } else {
+        HealthStatus::Healthy
+    }
+}
+
+/// Executa as verificações de recursos habilitadas na configuração
+pub fn system_checks(config: &HealthConfig) -> Vec<CheckResult> {
+    let mut checks = Vec::new();
+
+    if config.disk.enabled {
+        checks.push(system::disk_check(config));
+    }
+    if config.memory.enabled {
+        checks.push(system::memory_check(config));
+    }
+
+    checks
+}
+
+#[cfg(feature = "system-health")]
+mod system {
+    use super::{threshold_status, CheckResult};
+    use crate::config::HealthConfig;
+    use sysinfo::{Disks, System};
+
+    const GIB: f64 = (1u64 << 30) as f64;
+
+    pub fn disk_check(config: &HealthConfig) -> CheckResult {
+        let path = &config.disk.path;
+        let disks = Disks::new_with_refreshed_list();
+
+        // O volume do caminho é o de ponto de montagem mais específico
+        let disk = disks
+            .list()
+            .iter()
+            .filter(|disk| path.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().as_os_str().len());
+
+        let Some(disk) = disk.filter(|disk| disk.total_space() > 0) else {
+            return CheckResult::new("disk", super::HealthStatus::Warning)
+                .with_message(format!("no disk found for {}", path.display()));
+        };
+
+        let total = disk.total_space();
+        let available = disk.available_space();
+        let used_percent = used_percent(total - available.min(total), total);
+
+        CheckResult::new(
+            "disk",
+            threshold_status(
+                used_percent,
+                config.disk.warning_percent,
+                config.disk.critical_percent,
+            ),
+        )
+        .with_detail("path", path.display().to_string())
+        .with_detail("used_percent", round(used_percent))
+        .with_detail("available_gib", round(available as f64 / GIB))
+        .with_detail("total_gib", round(total as f64 / GIB))
+    }
+
+    pub fn memory_check(config: &HealthConfig) -> CheckResult {
+        let mut system = System::new();
+        system.refresh_memory();
+
+        let total = system.total_memory();
+        let available = system.available_memory();
+        let used_percent = used_percent(total - available.min(total), total);
+
+        CheckResult::new(
+            "memory",
+            threshold_status(
+                used_percent,
+                config.memory.warning_percent,
+                config.memory.critical_percent,
+            ),
+        )
+        .with_detail("used_percent", round(used_percent))
+        .with_detail("available_gib", round(available as f64 / GIB))
+        .with_detail("total_gib", round(total as f64 / GIB))
+    }
+
+    fn used_percent(used: u64, total: u64) -> f64 {
+        if total == 0 {
+            return 0.0;
+        }
+        used as f64 / total as f64 * 100.0
+    }
+
+    fn round(value: f64) -> f64 {
+        (value * 10.0).round() / 10.0
+    }
+}
+
+#[cfg(not(feature = "system-health"))]
+mod system {
+    use super::{CheckResult, HealthStatus};
+    use crate::config::HealthConfig;
+
+    const UNAVAILABLE: &str = "requires the `system-health` feature";
+
+    pub fn disk_check(_config: &HealthConfig) -> CheckResult {
+        CheckResult::new("disk", HealthStatus::Warning).with_message(UNAVAILABLE)
+    }
+
+    pub fn memory_check(_config: &HealthConfig) -> CheckResult {
+        CheckResult::new("memory", HealthStatus::Warning).with_message(UNAVAILABLE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_status() {
+        assert_eq!(threshold_status(50.0, 80.0, 95.0), HealthStatus::Healthy);
+        assert_eq!(threshold_status(80.0, 80.0, 95.0), HealthStatus::Warning);
+        assert_eq!(threshold_status(99.0, 80.0, 95.0), HealthStatus::Critical);
+    }
+
+    #[test]
+    fn test_report_takes_worst_status() {
+        let report = HealthReport::new(vec![
+            CheckResult::new("disk", HealthStatus::Warning),
+            CheckResult::new("memory", HealthStatus::Healthy),
+        ]);
+        assert_eq!(report.status, HealthStatus::Warning);
+        assert!(!report.is_critical());
+
+        assert_eq!(HealthReport::new(vec![]).status, HealthStatus::Healthy);
+    }
+
+    #[test]
+    fn test_registry_keeps_latest_result() {
+        let registry = HealthRegistry::new();
+        registry.record(CheckResult::new("database", HealthStatus::Critical));
+        registry.record(CheckResult::new("database", HealthStatus::Healthy));
+        registry.record(CheckResult::new("cache", HealthStatus::Warning));
+
+        let checks = registry.checks();
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].name, "cache");
+        assert_eq!(
+            registry.get("database").unwrap().status,
+            HealthStatus::Healthy
+        );
+
+        registry.remove("cache");
+        assert_eq!(registry.checks().len(), 1);
+    }
+
+    #[test]
+    fn test_system_checks_follow_config() {
+        let mut config = HealthConfig::default();
+        assert!(system_checks(&config).is_empty());
+
+        config.memory.enabled = true;
+        config.memory.critical_percent = 101.0;
+        config.memory.warning_percent = 101.0;
+        let checks = system_checks(&config);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].name, "memory");
+    }
+}
@@ -0,0 +1,154 @@
+//! Verificação usada pelo comando `healthcheck` (HEALTHCHECK do Docker,
+//! probes do Kubernetes)
+//!
+//! Faz um `GET` HTTP/1.1 direto no socket, sem depender de um cliente HTTP,
+//! de modo que funcione em qualquer build e em imagens sem `curl`. Só
+//! `http://` é aceito: a verificação roda dentro do container, ao lado do
+//! servidor.
+
+use anyhow::{bail, Context, Result};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+/// Partes de uma URL `http://host[:porta]/caminho`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpTarget {
+    /// `host:porta`, como aceito por `TcpStream::connect`
+    pub address: String,
+    /// Valor do header `Host`
+    pub host: String,
+    pub path: String,
+}
+
+impl HttpTarget {
+    pub fn parse(url: &str) -> Result<Self> {
+        let Some(rest) = url.strip_prefix("http://") else {
+            bail!("unsupported URL '{}': only http:// is supported", url);
+        };
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        if host.is_empty() {
+            bail!("invalid URL '{}': missing host", url);
+        }
+
+        // Sem porta (inclusive em `[::1]`), vale a 80
+        let has_port = match host.rfind(']') {
+            Some(end) => host[end..].contains(':'),
+            None => host.contains(':'),
+        };
+        let address = if has_port {
+            host.to_string()
+        } else {
+            format!("{}:80", host)
+        };
+
+        Ok(Self {
+            address,
+            host: host.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+/// Status HTTP de um `GET` em `url`, com `timeout` para a requisição toda
+pub async fn http_status(url: &str, timeout: Duration) -> Result<u16> {
+    let target = HttpTarget::parse(url)?;
+    tokio::time::timeout(timeout, get(&target))
+        .await
+        .with_context(|| format!("timed out after {:?}", timeout))?
+}
+
+/// Falha se `url` não responder 2xx dentro de `timeout`
+pub async fn check_http(url: &str, timeout: Duration) -> Result<()> {
+    let status = http_status(url, timeout).await?;
+    if !(200..300).contains(&status) {
+        bail!("{} returned HTTP {}", url, status);
+    }
+    Ok(())
+}
+
+async fn get(target: &HttpTarget) -> Result<u16> {
+    let mut stream = TcpStream::connect(&target.address)
+        .await
+        .with_context(|| format!("failed to connect to {}", target.address))?;
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: rust-app-exemplo-healthcheck\r\nAccept: application/json\r\nConnection: close\r\n\r\n",
+        target.path, target.host
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    // Basta a linha de status: "HTTP/1.1 200 OK"
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line).await?;
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .with_context(|| format!("invalid HTTP status line '{}'", status_line.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_parse_target() {
+        let target = HttpTarget::parse("http://127.0.0.1:8080/health").unwrap();
+        assert_eq!(target.address, "127.0.0.1:8080");
+        assert_eq!(target.path, "/health");
+
+        let target = HttpTarget::parse("http://app").unwrap();
+        assert_eq!(
+            (target.address.as_str(), target.path.as_str()),
+            ("app:80", "/")
+        );
+        assert_eq!(
+            HttpTarget::parse("http://[::1]/health").unwrap().address,
+            "[::1]:80"
+        );
+        assert_eq!(
+            HttpTarget::parse("http://[::1]:9090/").unwrap().address,
+            "[::1]:9090"
+        );
+
+        assert!(HttpTarget::parse("https://app/health").is_err());
+        assert!(HttpTarget::parse("http:///health").is_err());
+    }
+
+    /// Servidor que responde a uma conexão com `status_line`
+    async fn respond_once(status_line: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = String::new();
+            let mut reader = BufReader::new(&mut socket);
+            reader.read_line(&mut request).await.unwrap();
+            assert_eq!(request, "GET /health HTTP/1.1\r\n");
+            socket
+                .write_all(format!("{}\r\ncontent-length: 0\r\n\r\n", status_line).as_bytes())
+                .await
+                .unwrap();
+        });
+        format!("http://{}/health", address)
+    }
+
+    #[tokio::test]
+    async fn test_check_http() {
+        let timeout = Duration::from_secs(2);
+        let url = respond_once("HTTP/1.1 200 OK").await;
+        assert!(check_http(&url, timeout).await.is_ok());
+
+        let url = respond_once("HTTP/1.1 503 Service Unavailable").await;
+        let error = check_http(&url, timeout).await.unwrap_err();
+        assert!(error.to_string().contains("503"));
+
+        assert!(check_http("http://127.0.0.1:1/health", timeout)
+            .await
+            .is_err());
+    }
+}
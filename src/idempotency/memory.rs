@@ -0,0 +1,150 @@
+//! `IdempotencyStore` em memória, para testes e uso sem banco
+
+use super::{Begin, IdempotencyStore, StoredResponse};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Debug)]
+struct Entry {
+    request_hash: String,
+    /// `None` enquanto a requisição está em andamento
+    response: Option<StoredResponse>,
+    expires_at: DateTime<Utc>,
+}
+
+/// Chaves num `HashMap` protegido por `Mutex`
+#[derive(Debug, Default)]
+pub struct InMemoryIdempotencyStore {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl InMemoryIdempotencyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for InMemoryIdempotencyStore {
+    async fn begin(
+        &self,
+        key: &str,
+        request_hash: &str,
+        locked_until: DateTime<Utc>,
+    ) -> Result<Begin> {
+        let mut entries = self.lock();
+
+        if let Some(entry) = entries.get(key).filter(|e| e.expires_at > Utc::now()) {
+            return Ok(if entry.request_hash != request_hash {
+                Begin::Mismatch
+            } else if let Some(response) = &entry.response {
+                Begin::Completed(response.clone())
+            } else {
+                Begin::InProgress
+            });
+        }
+
+        entries.insert(
+            key.to_string(),
+            Entry {
+                request_hash: request_hash.to_string(),
+                response: None,
+                expires_at: locked_until,
+            },
+        );
+        Ok(Begin::Started)
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        response: &StoredResponse,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        if let Some(entry) = self.lock().get_mut(key) {
+            entry.response = Some(response.clone());
+            entry.expires_at = expires_at;
+        }
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<()> {
+        self.lock().remove(key);
+        Ok(())
+    }
+
+    async fn purge_expired(&self) -> Result<u64> {
+        let mut entries = self.lock();
+        let before = entries.len();
+        let now = Utc::now();
+        entries.retain(|_, entry| entry.expires_at > now);
+        Ok((before - entries.len()) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::idempotency::deadline;
+    use std::time::Duration;
+
+    fn response() -> StoredResponse {
+        StoredResponse {
+            status: 201,
+            content_type: Some("application/json".to_string()),
+            body: b"{}".to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_begin_complete_and_replay() {
+        let store = InMemoryIdempotencyStore::new();
+        let lock = deadline(Duration::from_secs(60));
+
+        assert_eq!(store.begin("k", "h", lock).await.unwrap(), Begin::Started);
+        assert_eq!(
+            store.begin("k", "h", lock).await.unwrap(),
+            Begin::InProgress
+        );
+        assert_eq!(
+            store.begin("k", "outro", lock).await.unwrap(),
+            Begin::Mismatch
+        );
+
+        store
+            .complete("k", &response(), deadline(Duration::from_secs(60)))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.begin("k", "h", lock).await.unwrap(),
+            Begin::Completed(response())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_release_and_expiry() {
+        let store = InMemoryIdempotencyStore::new();
+        let lock = deadline(Duration::from_secs(60));
+
+        store.begin("k", "h", lock).await.unwrap();
+        store.release("k").await.unwrap();
+        assert_eq!(store.begin("k", "h", lock).await.unwrap(), Begin::Started);
+
+        // Expirada: pode ser reservada de novo, até com outro conteúdo
+        store.complete("k", &response(), Utc::now()).await.unwrap();
+        assert_eq!(
+            store.begin("k", "outro", lock).await.unwrap(),
+            Begin::Started
+        );
+
+        store.complete("k", &response(), Utc::now()).await.unwrap();
+        assert_eq!(store.purge_expired().await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,195 @@
+//! Respostas guardadas por `Idempotency-Key` (feature "api")
+//!
+//! O middleware `api::middleware::idempotency` reserva a chave com `begin`,
+//! executa a requisição e guarda a resposta com `complete`; uma nova
+//! requisição com a mesma chave (e o mesmo conteúdo) recebe a resposta
+//! guardada em vez de ser executada de novo. A chave vale só para quem a
+//! usou: o hash da requisição inclui o `Principal`. Respostas que dependem
+//! de um estado passageiro (5xx e as de `is_storable`) não são guardadas
+//! (`release`), para que o cliente possa tentar de novo.
+//!
+//! A reserva expira em `IN_PROGRESS_TIMEOUT`, caso a instância caia no meio
+//! da requisição; a resposta guardada, em `Idempotency::ttl`.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod memory;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+pub use memory::InMemoryIdempotencyStore;
+#[cfg(feature = "postgres")]
+pub use postgres::PgIdempotencyStore;
+
+/// Por quanto tempo as respostas ficam guardadas, por padrão
+pub const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Prazo da reserva de uma chave cuja requisição ainda não terminou
+pub const IN_PROGRESS_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Resposta guardada para ser repetida
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Resultado da reserva de uma chave
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Begin {
+    /// Chave nova (ou expirada): a requisição deve ser executada
+    Started,
+    /// Outra requisição com a mesma chave ainda está em andamento
+    InProgress,
+    /// Requisição já executada; repetir a resposta
+    Completed(StoredResponse),
+    /// A chave já foi usada com outro conteúdo
+    Mismatch,
+}
+
+/// Armazenamento das chaves e respostas
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    /// Reserva a chave até `locked_until`, a menos que ela já exista e não
+    /// tenha expirado
+    async fn begin(
+        &self,
+        key: &str,
+        request_hash: &str,
+        locked_until: DateTime<Utc>,
+    ) -> Result<Begin>;
+
+    /// Guarda a resposta da requisição que reservou a chave
+    async fn complete(
+        &self,
+        key: &str,
+        response: &StoredResponse,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()>;
+
+    /// Libera a chave sem guardar resposta
+    async fn release(&self, key: &str) -> Result<()>;
+
+    /// Remove as chaves expiradas; devolve quantas
+    async fn purge_expired(&self) -> Result<u64>;
+}
+
+/// Armazenamento e TTL usados pelo middleware
+#[derive(Clone)]
+pub struct Idempotency {
+    pub store: Arc<dyn IdempotencyStore>,
+    pub ttl: Duration,
+}
+
+impl Idempotency {
+    pub fn new(store: Arc<dyn IdempotencyStore>) -> Self {
+        Self {
+            store,
+            ttl: DEFAULT_TTL,
+        }
+    }
+
+    /// Remove as chaves expiradas periodicamente, em segundo plano
+    pub fn spawn_purge(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let store = Arc::clone(&self.store);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match store.purge_expired().await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::debug!(purged, "Expired idempotency keys purged"),
+                    Err(e) => tracing::warn!(error = %e, "Failed to purge idempotency keys"),
+                }
+            }
+        })
+    }
+}
+
+/// Hash (SHA-256, hex) do método, caminho, tenant (`X-Tenant-Id`), autor
+/// (`Principal::actor`) e corpo da requisição; com o tenant e o autor, uma
+/// chave repetida por outro tenant ou outro cliente não devolve a resposta
+/// guardada
+pub fn request_hash(
+    method: &str,
+    path_and_query: &str,
+    tenant: Option<&[u8]>,
+    actor: &str,
+    body: &[u8],
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path_and_query.as_bytes());
+    hasher.update(b"\n");
+    if let Some(tenant) = tenant {
+        hasher.update(tenant);
+    }
+    hasher.update(b"\n");
+    hasher.update(actor.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+/// Se a resposta pode ser guardada: não as 5xx nem as que mudam numa nova
+/// tentativa (401, 403, 408, 409 e 429)
+pub fn is_storable(status: u16) -> bool {
+    !matches!(status, 401 | 403 | 408 | 409 | 429 | 500..)
+}
+
+/// `agora + duração`, para os prazos passados ao `IdempotencyStore`
+pub fn deadline(after: Duration) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::from_std(after).unwrap_or(chrono::Duration::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_hash() {
+        let hash = request_hash("POST", "/api/users", None, "admin", b"{}");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(
+            hash,
+            request_hash("POST", "/api/users", None, "admin", b"{}")
+        );
+        assert_ne!(
+            hash,
+            request_hash("POST", "/api/users", None, "admin", b"{ }")
+        );
+        assert_ne!(
+            hash,
+            request_hash("POST", "/api/users?x=1", None, "admin", b"{}")
+        );
+        assert_ne!(
+            hash,
+            request_hash("PUT", "/api/users", None, "admin", b"{}")
+        );
+        assert_ne!(
+            hash,
+            request_hash("POST", "/api/users", Some(b"acme"), "admin", b"{}")
+        );
+        assert_ne!(
+            hash,
+            request_hash("POST", "/api/users", None, "user:default/1", b"{}")
+        );
+    }
+
+    #[test]
+    fn test_is_storable() {
+        for status in [200, 201, 204, 400, 404, 422] {
+            assert!(is_storable(status), "{}", status);
+        }
+        for status in [401, 403, 408, 409, 429, 500, 503] {
+            assert!(!is_storable(status), "{}", status);
+        }
+    }
+}
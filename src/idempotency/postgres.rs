@@ -0,0 +1,146 @@
+//! `IdempotencyStore` sobre o Postgres (tabela `idempotency_keys`)
+
+use super::{Begin, IdempotencyStore, StoredResponse};
+use crate::db::timed;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(Debug, Clone)]
+pub struct PgIdempotencyStore {
+    pool: PgPool,
+}
+
+impl PgIdempotencyStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+struct Row {
+    request_hash: String,
+    status_code: Option<i32>,
+    content_type: Option<String>,
+    body: Option<Vec<u8>>,
+}
+
+#[async_trait]
+impl IdempotencyStore for PgIdempotencyStore {
+    async fn begin(
+        &self,
+        key: &str,
+        request_hash: &str,
+        locked_until: DateTime<Utc>,
+    ) -> Result<Begin> {
+        // Insere, ou assume a chave se ela expirou; concorrentes esperam a
+        // trava da linha e não veem a chave como livre
+        let reserved = timed(
+            "idempotency.begin",
+            sqlx::query!(
+                r#"
+                INSERT INTO idempotency_keys (key, request_hash, expires_at)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (key) DO UPDATE SET
+                    request_hash = EXCLUDED.request_hash,
+                    status_code = NULL,
+                    content_type = NULL,
+                    body = NULL,
+                    created_at = NOW(),
+                    expires_at = EXCLUDED.expires_at
+                WHERE idempotency_keys.expires_at <= NOW()
+                "#,
+                key,
+                request_hash,
+                locked_until
+            )
+            .execute(&self.pool),
+        )
+        .await?
+        .rows_affected()
+            > 0;
+
+        if reserved {
+            return Ok(Begin::Started);
+        }
+
+        let row = timed(
+            "idempotency.find",
+            sqlx::query_as!(
+                Row,
+                r#"
+                SELECT request_hash, status_code, content_type, body
+                FROM idempotency_keys
+                WHERE key = $1
+                "#,
+                key
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await?;
+
+        Ok(match row {
+            Some(row) if row.request_hash != request_hash => Begin::Mismatch,
+            Some(Row {
+                status_code: Some(status),
+                content_type,
+                body,
+                ..
+            }) => Begin::Completed(StoredResponse {
+                status: status as u16,
+                content_type,
+                body: body.unwrap_or_default(),
+            }),
+            // Em andamento, ou liberada entre as duas consultas
+            _ => Begin::InProgress,
+        })
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        response: &StoredResponse,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        timed(
+            "idempotency.complete",
+            sqlx::query!(
+                r#"
+                UPDATE idempotency_keys
+                SET status_code = $2, content_type = $3, body = $4, expires_at = $5
+                WHERE key = $1
+                "#,
+                key,
+                i32::from(response.status),
+                response.content_type,
+                response.body,
+                expires_at
+            )
+            .execute(&self.pool),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<()> {
+        timed(
+            "idempotency.release",
+            sqlx::query!("DELETE FROM idempotency_keys WHERE key = $1", key).execute(&self.pool),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn purge_expired(&self) -> Result<u64> {
+        let result = timed(
+            "idempotency.purge_expired",
+            sqlx::query!("DELETE FROM idempotency_keys WHERE expires_at <= NOW()")
+                .execute(&self.pool),
+        )
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
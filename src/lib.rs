@@ -6,17 +6,150 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+// Informações de build (git, data, rustc)
+pub mod build_info;
+
 // Módulo de configuração
 pub mod config;
 
+// Leitura de documentos JSON, CSV e YAML (comando `process`)
+pub mod formats;
+
+// Health checks (disco e memória com a feature "system-health")
+pub mod health;
+
+// Inicialização de logs (console e arquivo com rotação; fora do wasm32)
+#[cfg(not(target_arch = "wasm32"))]
+pub mod logging;
+
+// Modelos persistidos (independentes do banco)
+pub mod models;
+
+// Mascaramento de dados pessoais em logs e na exibição
+pub mod pii;
+
+// Geração de dados de teste (usuários aleatórios ou com semente)
+pub mod fixtures;
+
+// Isolamento dos dados por tenant (`TenantContext`)
+pub mod tenant;
+
+// Repositórios (Postgres ou em memória)
+pub mod repository;
+
 // Módulo de banco de dados (apenas quando feature "postgres" está habilitada)
 #[cfg(feature = "postgres")]
 pub mod db;
 
+// Outbox transacional dos eventos de usuário (apenas com a feature "postgres")
+#[cfg(feature = "postgres")]
+pub mod outbox;
+
+// Propagação do W3C Trace Context (fora do wasm32)
+#[cfg(not(target_arch = "wasm32"))]
+pub mod trace_context;
+
+// Prazo das requisições, propagado até o banco (fora do wasm32)
+#[cfg(not(target_arch = "wasm32"))]
+pub mod deadline;
+
+// Retentativas com backoff exponencial (fora do wasm32)
+#[cfg(not(target_arch = "wasm32"))]
+pub mod retry;
+
+// Barramento de eventos de domínio (fora do wasm32)
+#[cfg(not(target_arch = "wasm32"))]
+pub mod events;
+
+// Webhooks de saída e de entrada (apenas quando feature "webhooks" está habilitada)
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
+
+// Consumidor de comandos via NATS (apenas quando feature "queue" está habilitada)
+#[cfg(feature = "queue")]
+pub mod queue;
+
+// Verificação do comando `healthcheck` (fora do wasm32)
+#[cfg(not(target_arch = "wasm32"))]
+pub mod healthcheck;
+
+// Perguntas de confirmação dos comandos destrutivos (fora do wasm32)
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
+
+// Subcomandos externos `rust-app-exemplo-<nome>` no PATH
+pub mod plugins;
+
+// Funções matemáticas em lote, paralelas (apenas quando feature "parallel" está habilitada)
+#[cfg(feature = "parallel")]
+pub mod parallel;
+
+// Fibonacci memoizado, compartilhado entre chamadas
+pub mod fib_cache;
+
+// Estatísticas de latência (percentis)
+pub mod stats;
+
+// Snapshot das métricas de uma instância (comando `metrics dump`)
+pub mod metrics_snapshot;
+
+// Cliente HTTP tipado da API (apenas com as features "client" e "api")
+#[cfg(all(feature = "client", feature = "api"))]
+pub mod client;
+
+// Teste de carga HTTP (apenas quando feature "client" está habilitada)
+#[cfg(feature = "client")]
+pub mod loadtest;
+
+// Utilitários de teste: estratégias proptest e Postgres em container
+// (exportados com a feature "test-util")
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_support;
+
 // Módulo de API (apenas quando feature "api" está habilitada)
 #[cfg(feature = "api")]
 pub mod api;
 
+// Autenticação dos usuários: senhas e tokens (apenas quando feature "api" está habilitada)
+#[cfg(feature = "api")]
+pub mod auth;
+
+// Log de auditoria das requisições (apenas quando feature "api" está habilitada)
+#[cfg(feature = "api")]
+pub mod audit;
+
+// Respostas guardadas por Idempotency-Key (apenas quando feature "api" está habilitada)
+#[cfg(feature = "api")]
+pub mod idempotency;
+
+// Exportações de usuários em segundo plano (apenas quando feature "api" está habilitada)
+#[cfg(feature = "api")]
+pub mod exports;
+
+// Cotas mensais por tenant (apenas quando feature "api" está habilitada)
+#[cfg(feature = "api")]
+pub mod quotas;
+
+// Arquivos baixados por URL pré-assinada (apenas quando feature "api" está habilitada)
+#[cfg(feature = "api")]
+pub mod storage;
+
+// Módulo de validação de domínio (apenas quando feature "api" está habilitada)
+#[cfg(feature = "api")]
+pub mod validation;
+
+// Exports para JavaScript via wasm-bindgen (apenas quando feature "wasm" está habilitada)
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// Módulo Python via pyo3 (apenas quando feature "python" está habilitada)
+#[cfg(feature = "python")]
+pub mod python;
+
+// Interface C (apenas quando feature "ffi" está habilitada)
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
 /// Estrutura que representa um usuário do sistema
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct User {
@@ -58,88 +191,8 @@ impl fmt::Display for User {
     }
 }
 
-/// Calcula fibonacci de forma otimizada usando iteração
-pub fn fibonacci_optimized(n: u64) -> u64 {
-    if n == 0 {
-        return 0;
-    }
-    if n == 1 {
-        return 1;
-    }
-
-    let mut prev = 0;
-    let mut curr = 1;
-
-    for _ in 2..=n {
-        let next = prev + curr;
-        prev = curr;
-        curr = next;
-    }
-
-    curr
-}
-
-/// Calcula o fatorial de um número
-pub fn factorial(n: u64) -> u64 {
-    match n {
-        0 | 1 => 1,
-        _ => (2..=n).product(),
-    }
-}
-
-/// Verifica se um número é primo
-pub fn is_prime(n: u64) -> bool {
-    if n < 2 {
-        return false;
-    }
-    if n == 2 {
-        return true;
-    }
-    if n.is_multiple_of(2) {
-        return false;
-    }
-
-    let limit = (n as f64).sqrt() as u64;
-    for i in (3..=limit).step_by(2) {
-        if n.is_multiple_of(i) {
-            return false;
-        }
-    }
-
-    true
-}
-
-/// Módulo de processamento de strings
-pub mod string_utils {
-    /// Converte uma string para título (primeira letra de cada palavra em maiúscula)
-    pub fn to_title_case(s: &str) -> String {
-        s.split_whitespace()
-            .map(|word| {
-                let mut chars = word.chars();
-                match chars.next() {
-                    None => String::new(),
-                    Some(first) => {
-                        first.to_uppercase().collect::<String>()
-                            + chars.as_str().to_lowercase().as_str()
-                    }
-                }
-            })
-            .collect::<Vec<String>>()
-            .join(" ")
-    }
-
-    /// Conta o número de vogais em uma string
-    pub fn count_vowels(s: &str) -> usize {
-        s.chars()
-            .filter(|c| matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u'))
-            .count()
-    }
-
-    /// Inverte uma string
-    pub fn reverse(s: &str) -> String {
-        s.chars().rev().collect()
-    }
-}
+// Algoritmos matemáticos e de strings (crate `core-utils`, compatível com no_std)
+pub use core_utils::{self, factorial, fibonacci_optimized, is_prime, string_utils};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,157 @@
+//! Teste de carga HTTP simples (feature "client")
+//!
+//! Dispara requisições GET contra uma URL com `concurrency` workers durante
+//! `duration` e resume latências (via `stats`) e taxa de erros.
+
+use crate::stats::Summary;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Parâmetros do teste de carga
+#[derive(Debug, Clone)]
+pub struct LoadTestConfig {
+    pub url: String,
+    pub concurrency: usize,
+    pub duration: Duration,
+    /// Timeout por requisição
+    pub timeout: Duration,
+}
+
+/// Resultado agregado do teste
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadTestReport {
+    pub requests: u64,
+    /// Falhas de conexão/timeout e respostas 5xx
+    pub errors: u64,
+    /// Respostas por status HTTP
+    pub status_counts: BTreeMap<u16, u64>,
+    pub elapsed: Duration,
+    pub latency: Option<Summary>,
+}
+
+impl LoadTestReport {
+    /// Requisições por segundo
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        self.requests as f64 / secs
+    }
+
+    /// Fração de requisições com erro (0.0 a 1.0)
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.requests as f64
+    }
+}
+
+#[derive(Default)]
+struct WorkerResult {
+    latencies: Vec<Duration>,
+    errors: u64,
+    status_counts: BTreeMap<u16, u64>,
+}
+
+/// Executa o teste de carga
+pub async fn run(config: &LoadTestConfig) -> Result<LoadTestReport> {
+    anyhow::ensure!(config.concurrency > 0, "concurrency must be at least 1");
+
+    let client = reqwest::Client::builder()
+        .timeout(config.timeout)
+        .build()
+        .context("failed to build HTTP client")?;
+
+    let start = Instant::now();
+    let deadline = start + config.duration;
+
+    let workers: Vec<_> = (0..config.concurrency)
+        .map(|_| tokio::spawn(worker(client.clone(), config.url.clone(), deadline)))
+        .collect();
+
+    let mut latencies = Vec::new();
+    let mut errors = 0;
+    let mut status_counts = BTreeMap::new();
+
+    for worker in workers {
+        let result = worker.await.context("load test worker panicked")?;
+        latencies.extend(result.latencies);
+        errors += result.errors;
+        for (status, count) in result.status_counts {
+            *status_counts.entry(status).or_insert(0) += count;
+        }
+    }
+
+    Ok(LoadTestReport {
+        requests: latencies.len() as u64,
+        errors,
+        status_counts,
+        elapsed: start.elapsed(),
+        latency: Summary::from_samples(&latencies),
+    })
+}
+
+async fn worker(client: reqwest::Client, url: String, deadline: Instant) -> WorkerResult {
+    let mut result = WorkerResult::default();
+
+    while Instant::now() < deadline {
+        let sent = Instant::now();
+        let response = client.get(&url).send().await;
+
+        match response {
+            Ok(response) => {
+                let status = response.status();
+                // Consome o corpo para medir a resposta completa
+                let body = response.bytes().await;
+                result.latencies.push(sent.elapsed());
+                *result.status_counts.entry(status.as_u16()).or_insert(0) += 1;
+                if body.is_err() || status.is_server_error() {
+                    result.errors += 1;
+                }
+            }
+            Err(_) => {
+                result.latencies.push(sent.elapsed());
+                result.errors += 1;
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(all(test, feature = "api"))]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::get, Router};
+
+    #[tokio::test]
+    async fn test_run_against_local_server() {
+        let app = Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route("/fail", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut config = LoadTestConfig {
+            url: format!("http://{}/ok", address),
+            concurrency: 2,
+            duration: Duration::from_millis(200),
+            timeout: Duration::from_secs(1),
+        };
+
+        let report = run(&config).await.unwrap();
+        assert!(report.requests > 0);
+        assert_eq!(report.errors, 0);
+        assert_eq!(report.status_counts[&200], report.requests);
+        assert!(report.latency.is_some());
+
+        config.url = format!("http://{}/fail", address);
+        let report = run(&config).await.unwrap();
+        assert_eq!(report.error_rate(), 1.0);
+    }
+}
@@ -0,0 +1,286 @@
+//! Inicialização do tracing a partir de `LoggingConfig`
+//!
+//! Compõe as saídas configuradas (console, arquivo com rotação e
+//! syslog/journald) sobre um único `EnvFilter`, que pode ser trocado em
+//! tempo de execução com `set_level`. As escritas em arquivo passam por
+//! writers não-bloqueantes; mantenha o `LoggingGuard` vivo até o fim do
+//! processo para que os logs pendentes sejam gravados.
+//!
+//! Os campos listados em `logging.redact_fields` saem como `[REDACTED]` em
+//! todas as saídas formatadas pelo tracing (ver `redact`); o journald
+//! recebe os eventos sem essa filtragem.
+
+use crate::config::{LogFormat, LogRotation, LoggingConfig, SystemLogTarget};
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::Layered;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+pub mod record;
+pub mod redact;
+pub mod rotation;
+#[cfg(unix)]
+pub mod syslog;
+
+pub use record::LogRecord;
+pub use redact::Redacted;
+pub use rotation::SizeRotatingWriter;
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+type FilterHandle = reload::Handle<EnvFilter, Layered<Vec<BoxedLayer>, Registry>>;
+
+/// Handle para trocar o filtro do subscriber global
+static FILTER_HANDLE: OnceLock<FilterHandle> = OnceLock::new();
+
+/// Arquivos mantidos na rotação por tamanho quando `max_files` não é definido
+const DEFAULT_SIZE_RETENTION: usize = 5;
+
+/// Mantém os writers não-bloqueantes ativos; os logs pendentes são
+/// descarregados quando o guard é dropado
+#[must_use = "logs em arquivo são perdidos se o guard for dropado"]
+pub struct LoggingGuard {
+    _guards: Vec<WorkerGuard>,
+}
+
+/// Inicializa o subscriber global de tracing
+pub fn init(config: &LoggingConfig) -> Result<LoggingGuard> {
+    let filter = EnvFilter::try_new(&config.level)
+        .with_context(|| format!("invalid log level '{}'", config.level))?;
+
+    let mut layers: Vec<BoxedLayer> = Vec::new();
+    let mut guards = Vec::new();
+
+    if config.console {
+        layers.push(format_layer(config, std::io::stdout, true));
+    }
+
+    if let Some(path) = &config.file {
+        let (writer, guard) = file_writer(path, config)?;
+        guards.push(guard);
+        layers.push(format_layer(config, writer, false));
+    }
+
+    if let Some(layer) = system_layer(config)? {
+        layers.push(layer);
+    }
+
+    let (filter, handle) = reload::Layer::new(filter);
+
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(filter)
+        .try_init()
+        .context("failed to install tracing subscriber")?;
+
+    let _ = FILTER_HANDLE.set(handle);
+
+    Ok(LoggingGuard { _guards: guards })
+}
+
+/// Verifica se as diretivas de log são válidas
+pub fn validate_level(directives: &str) -> Result<()> {
+    EnvFilter::try_new(directives)
+        .map(|_| ())
+        .with_context(|| format!("invalid log level '{}'", directives))
+}
+
+/// Troca o nível/diretivas de log em tempo de execução (ex.: `"debug"`,
+/// `"info,rust_app_exemplo=trace"`)
+pub fn set_level(directives: &str) -> Result<()> {
+    let filter = EnvFilter::try_new(directives)
+        .with_context(|| format!("invalid log level '{}'", directives))?;
+    let handle = FILTER_HANDLE
+        .get()
+        .context("logging has not been initialized")?;
+
+    handle
+        .reload(filter)
+        .context("failed to reload log filter")?;
+    tracing::info!(level = directives, "Log level changed");
+
+    Ok(())
+}
+
+/// Diretivas de log atualmente em vigor
+pub fn current_level() -> Option<String> {
+    FILTER_HANDLE
+        .get()
+        .and_then(|handle| handle.with_current(|filter| filter.to_string()).ok())
+}
+
+/// Camada de formatação para um writer, no formato configurado
+fn format_layer<W>(config: &LoggingConfig, writer: W, ansi: bool) -> BoxedLayer
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let redact_fields = &config.redact_fields;
+    if let LogFormat::Json = config.format {
+        return json_layer(writer, redact_fields).boxed();
+    }
+
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi);
+
+    match config.format {
+        LogFormat::Compact => layer
+            .compact()
+            .map_event_format(|format| Redacted::new(format, redact_fields))
+            .boxed(),
+        _ => layer
+            .pretty()
+            .map_event_format(|format| Redacted::new(format, redact_fields))
+            .boxed(),
+    }
+}
+
+/// Camada JSON no esquema documentado em `LogRecord`
+fn json_layer<S, W>(writer: W, redact_fields: &[String]) -> impl Layer<S> + Send + Sync
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt::layer()
+        .json()
+        .with_writer(writer)
+        .with_current_span(true)
+        .with_span_list(true)
+        .flatten_event(false)
+        .map_event_format(|format| Redacted::new(format, redact_fields))
+}
+
+/// Camada para o log do sistema (syslog ou journald), se configurada
+fn system_layer(config: &LoggingConfig) -> Result<Option<BoxedLayer>> {
+    let identifier = config
+        .syslog_identifier
+        .clone()
+        .unwrap_or_else(|| env!("CARGO_PKG_NAME").to_string());
+
+    match config.system {
+        SystemLogTarget::None => Ok(None),
+        #[cfg(unix)]
+        SystemLogTarget::Syslog => {
+            let syslog =
+                syslog::Syslog::connect(identifier).context("failed to connect to syslog")?;
+            // O syslog registra data/hora por conta própria
+            let layer = tracing_subscriber::fmt::layer()
+                .with_writer(syslog)
+                .with_ansi(false)
+                .without_time()
+                .compact()
+                .map_event_format(|format| Redacted::new(format, &config.redact_fields));
+            Ok(Some(layer.boxed()))
+        }
+        #[cfg(all(unix, feature = "journald"))]
+        SystemLogTarget::Journald => {
+            let layer = tracing_journald::layer()
+                .context("failed to connect to journald")?
+                .with_syslog_identifier(identifier);
+            Ok(Some(layer.boxed()))
+        }
+        #[cfg(not(all(unix, feature = "journald")))]
+        SystemLogTarget::Journald => {
+            let _ = identifier;
+            anyhow::bail!("journald logging requires the `journald` feature on a Unix target")
+        }
+        #[cfg(not(unix))]
+        SystemLogTarget::Syslog => anyhow::bail!("syslog logging is only supported on Unix"),
+    }
+}
+
+/// Writer não-bloqueante para o arquivo de log, com a rotação configurada
+fn file_writer(
+    path: &Path,
+    config: &LoggingConfig,
+) -> Result<(tracing_appender::non_blocking::NonBlocking, WorkerGuard)> {
+    if config.rotation == LogRotation::Size {
+        let max_files = config.max_files.unwrap_or(DEFAULT_SIZE_RETENTION);
+        let writer = SizeRotatingWriter::new(path, config.max_size, max_files)
+            .with_context(|| format!("failed to open log file {}", path.display()))?;
+        return Ok(tracing_appender::non_blocking(writer));
+    }
+
+    let directory = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let prefix = path.file_stem().and_then(|s| s.to_str()).unwrap_or("app");
+
+    let rotation = match config.rotation {
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Daily => Rotation::DAILY,
+        LogRotation::Never | LogRotation::Size => Rotation::NEVER,
+    };
+
+    let mut builder = RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(prefix);
+    if let Some(suffix) = path.extension().and_then(|s| s.to_str()) {
+        builder = builder.filename_suffix(suffix);
+    }
+    if let Some(max_files) = config.max_files {
+        builder = builder.max_log_files(max_files);
+    }
+
+    let appender = builder
+        .build(directory)
+        .with_context(|| format!("failed to open log file {}", path.display()))?;
+
+    Ok(tracing_appender::non_blocking(appender))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_output_matches_log_record() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber =
+            tracing_subscriber::registry().with(json_layer(move || writer.clone(), &[]));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", request_id = "abc-123");
+            let _entered = span.enter();
+            tracing::info!(status = 200, "Request completed");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let record: LogRecord = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+
+        assert_eq!(record.level, "INFO");
+        assert_eq!(record.message(), Some("Request completed"));
+        assert_eq!(record.fields["status"], 200);
+        assert_eq!(record.request_id(), Some("abc-123"));
+        assert_eq!(record.spans.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_level() {
+        assert!(validate_level("debug").is_ok());
+        assert!(validate_level("info,sqlx=warn").is_ok());
+        assert!(validate_level("info,sqlx=loud").is_err());
+    }
+}
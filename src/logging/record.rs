@@ -0,0 +1,54 @@
+//! Esquema estável dos logs em formato JSON
+//!
+//! Cada linha emitida com `format = "json"` desserializa em um `LogRecord`:
+//!
+//! ```json
+//! {
+//!   "timestamp": "2024-01-01T12:00:00.000000Z",
+//!   "level": "INFO",
+//!   "target": "rust_app_exemplo::api::middleware",
+//!   "fields": { "message": "Request completed", "status": "200 OK" },
+//!   "span": { "name": "request", "request_id": "…" },
+//!   "spans": [ { "name": "request", "request_id": "…" } ]
+//! }
+//! ```
+//!
+//! `span` é o span atual e `spans` a pilha completa, da raiz até o atual.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Uma linha de log em JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogRecord {
+    /// Data/hora em RFC 3339 (UTC)
+    pub timestamp: String,
+    /// `TRACE`, `DEBUG`, `INFO`, `WARN` ou `ERROR`
+    pub level: String,
+    /// Módulo que emitiu o evento
+    pub target: String,
+    /// Campos do evento; a mensagem fica em `message`
+    pub fields: Map<String, Value>,
+    /// Span atual (com `name` e seus campos)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Map<String, Value>>,
+    /// Spans ativos, do mais externo ao atual
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<Map<String, Value>>,
+}
+
+impl LogRecord {
+    /// Mensagem do evento
+    pub fn message(&self) -> Option<&str> {
+        self.fields.get("message").and_then(Value::as_str)
+    }
+
+    /// Id da requisição, procurado do span mais interno para o mais externo
+    pub fn request_id(&self) -> Option<&str> {
+        self.spans
+            .iter()
+            .rev()
+            .chain(self.span.iter())
+            .find_map(|span| span.get("request_id").and_then(Value::as_str))
+    }
+}
@@ -0,0 +1,246 @@
+//! Remoção de campos sensíveis dos eventos antes da formatação
+//!
+//! `Redacted` envolve um formatador de eventos (`FormatEvent`) e, quando o
+//! evento tem algum dos campos configurados em `logging.redact_fields`
+//! (comparados sem diferenciar maiúsculas), entrega ao formatador uma cópia
+//! com o valor desses campos trocado por `[REDACTED]`. Vale para todos os
+//! formatos (json, pretty e compact) e destinos.
+//!
+//! Só os campos do evento são filtrados; os dos spans (ex.: `request_id`)
+//! são formatados quando o span é criado e saem como estão.
+
+use std::fmt;
+use std::sync::Arc;
+use tracing::field::{display, DisplayValue, Field, Value, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Valor escrito no lugar dos campos removidos
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Formatador de eventos que remove os campos configurados
+#[derive(Debug, Clone)]
+pub struct Redacted<E> {
+    inner: E,
+    fields: Arc<[String]>,
+}
+
+impl<E> Redacted<E> {
+    /// Envolve `inner`, removendo os campos chamados `fields`
+    pub fn new(inner: E, fields: &[String]) -> Self {
+        Self {
+            inner,
+            fields: fields.iter().cloned().collect(),
+        }
+    }
+
+    fn redacts(&self, field: &Field) -> bool {
+        self.fields
+            .iter()
+            .any(|f| field.name().eq_ignore_ascii_case(f))
+    }
+}
+
+impl<S, N, E> FormatEvent<S, N> for Redacted<E>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    E: FormatEvent<S, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let fields = event.metadata().fields();
+        if !fields.iter().any(|field| self.redacts(&field)) {
+            return self.inner.format_event(ctx, writer, event);
+        }
+
+        // Copia os valores registrados, trocando os sensíveis
+        let mut captured = Capture {
+            redacted: self,
+            values: (0..fields.len()).map(|_| None).collect(),
+        };
+        event.record(&mut captured);
+        let values: Vec<Option<&dyn Value>> = captured
+            .values
+            .iter()
+            .map(|value| value.as_ref().map(Captured::as_value))
+            .collect();
+        let value_set = fields.value_set_all(&values);
+
+        let copy = if event.is_contextual() {
+            Event::new(event.metadata(), &value_set)
+        } else {
+            Event::new_child_of(event.parent().cloned(), event.metadata(), &value_set)
+        };
+        self.inner.format_event(ctx, writer, &copy)
+    }
+}
+
+/// Valor de um campo, com o tipo com que foi registrado
+enum Captured {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    I128(i128),
+    U128(u128),
+    F64(f64),
+    Str(String),
+    /// Já formatado com `{:?}`; exibido como está
+    Debug(DisplayValue<String>),
+}
+
+impl Captured {
+    fn as_value(&self) -> &dyn Value {
+        match self {
+            Captured::Bool(v) => v,
+            Captured::I64(v) => v,
+            Captured::U64(v) => v,
+            Captured::I128(v) => v,
+            Captured::U128(v) => v,
+            Captured::F64(v) => v,
+            Captured::Str(v) => v,
+            Captured::Debug(v) => v,
+        }
+    }
+}
+
+struct Capture<'a, E> {
+    redacted: &'a Redacted<E>,
+    values: Vec<Option<Captured>>,
+}
+
+impl<E> Capture<'_, E> {
+    fn set(&mut self, field: &Field, value: Captured) {
+        let value = if self.redacted.redacts(field) {
+            Captured::Str(REDACTED.to_string())
+        } else {
+            value
+        };
+        if let Some(slot) = self.values.get_mut(field.index()) {
+            *slot = Some(value);
+        }
+    }
+}
+
+impl<E> Visit for Capture<'_, E> {
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.set(field, Captured::Bool(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.set(field, Captured::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.set(field, Captured::U64(value));
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.set(field, Captured::I128(value));
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.set(field, Captured::U128(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.set(field, Captured::F64(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.set(field, Captured::Str(value.to_string()));
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.set(field, Captured::Debug(display(value.to_string())));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.set(field, Captured::Debug(display(format!("{:?}", value))));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tracing_subscriber::prelude::*;
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn redact_fields() -> Vec<String> {
+        vec!["email".to_string(), "Password".to_string()]
+    }
+
+    #[test]
+    fn test_redacts_configured_fields_in_json() {
+        let captured = Output::default();
+        let writer = captured.clone();
+        let layer = tracing_subscriber::fmt::layer()
+            .json()
+            .with_writer(move || writer.clone())
+            .map_event_format(|format| Redacted::new(format, &redact_fields()));
+
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::info!(
+                email = "joao@example.com",
+                password = %"s3cret",
+                user_id = 7,
+                active = true,
+                "User created"
+            );
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let record: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        let fields = &record["fields"];
+        assert_eq!(fields["email"], REDACTED);
+        assert_eq!(fields["password"], REDACTED);
+        assert_eq!(fields["user_id"], 7);
+        assert_eq!(fields["active"], true);
+        assert_eq!(fields["message"], "User created");
+        assert!(!output.contains("joao@example.com"));
+        assert!(!output.contains("s3cret"));
+    }
+
+    #[test]
+    fn test_redacts_in_text_formats_and_keeps_other_events() {
+        let captured = Output::default();
+        let writer = captured.clone();
+        let layer = tracing_subscriber::fmt::layer()
+            .compact()
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .map_event_format(|format| Redacted::new(format, &redact_fields()));
+
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::warn!(EMAIL = ?"ana@example.com", attempt = 3, "Login failed");
+            tracing::info!(status = 200, "Request completed");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("Login failed"), "{}", output);
+        assert!(output.contains("EMAIL=\"[REDACTED]\""), "{}", output);
+        assert!(output.contains("attempt=3"), "{}", output);
+        assert!(output.contains("status=200"), "{}", output);
+        assert!(!output.contains("ana@example.com"), "{}", output);
+    }
+}
@@ -0,0 +1,159 @@
+//! Saída de logs para o syslog local (socket `/dev/log`)
+//!
+//! Cada evento vira um datagrama no formato `<PRI>ident[pid]: mensagem`,
+//! aceito pelo rsyslog, syslog-ng e pelo próprio journald.
+
+use std::io::{self, Write};
+use std::os::unix::net::UnixDatagram;
+use std::sync::Arc;
+use tracing::{Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Sockets tentados, em ordem (Linux, macOS, BSD)
+const SOCKET_PATHS: [&str; 3] = ["/dev/log", "/var/run/syslog", "/var/run/log"];
+
+/// Facility `daemon`
+const FACILITY_DAEMON: u8 = 3;
+
+/// Conexão com o syslog local
+#[derive(Debug, Clone)]
+pub struct Syslog {
+    socket: Arc<UnixDatagram>,
+    identifier: String,
+    pid: u32,
+}
+
+impl Syslog {
+    /// Conecta ao primeiro socket de syslog disponível
+    pub fn connect(identifier: impl Into<String>) -> io::Result<Self> {
+        let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no syslog socket found");
+
+        for path in SOCKET_PATHS {
+            let socket = UnixDatagram::unbound()?;
+            match socket.connect(path) {
+                Ok(()) => {
+                    return Ok(Self {
+                        socket: Arc::new(socket),
+                        identifier: identifier.into(),
+                        pid: std::process::id(),
+                    })
+                }
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+}
+
+/// Severidade syslog correspondente ao nível do tracing
+fn severity(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        Level::DEBUG | Level::TRACE => 7,
+    }
+}
+
+/// Monta a mensagem `<PRI>ident[pid]: mensagem`
+fn format_message(severity: u8, identifier: &str, pid: u32, message: &[u8]) -> Vec<u8> {
+    let message = message.strip_suffix(b"\n").unwrap_or(message);
+    let mut out = format!(
+        "<{}>{}[{}]: ",
+        FACILITY_DAEMON * 8 + severity,
+        identifier,
+        pid
+    )
+    .into_bytes();
+    out.extend_from_slice(message);
+    out
+}
+
+/// Acumula um evento e o envia como um único datagrama ao ser dropado
+pub struct SyslogEvent<'a> {
+    syslog: &'a Syslog,
+    severity: u8,
+    buf: Vec<u8>,
+}
+
+impl Write for SyslogEvent<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for SyslogEvent<'_> {
+    fn drop(&mut self) {
+        if self.buf.is_empty() {
+            return;
+        }
+        let message = format_message(
+            self.severity,
+            &self.syslog.identifier,
+            self.syslog.pid,
+            &self.buf,
+        );
+        // Falhas ao logar não podem derrubar a aplicação
+        let _ = self.syslog.socket.send(&message);
+    }
+}
+
+impl<'a> MakeWriter<'a> for Syslog {
+    type Writer = SyslogEvent<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        SyslogEvent {
+            syslog: self,
+            severity: severity(&Level::INFO),
+            buf: Vec::new(),
+        }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        SyslogEvent {
+            syslog: self,
+            severity: severity(meta.level()),
+            buf: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_message() {
+        let message = format_message(severity(&Level::ERROR), "rust-app", 42, b"boom\n");
+        assert_eq!(message, b"<27>rust-app[42]: boom");
+
+        let message = format_message(severity(&Level::INFO), "rust-app", 42, b"ok");
+        assert_eq!(message, b"<30>rust-app[42]: ok");
+    }
+
+    #[test]
+    fn test_event_is_sent_as_single_datagram() {
+        let (receiver, sender) = UnixDatagram::pair().unwrap();
+        let syslog = Syslog {
+            socket: Arc::new(sender),
+            identifier: "test".to_string(),
+            pid: 1,
+        };
+
+        {
+            let mut event = syslog.make_writer();
+            event.write_all(b"hello ").unwrap();
+            event.write_all(b"world\n").unwrap();
+        }
+
+        let mut buf = [0u8; 64];
+        let n = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"<30>test[1]: hello world");
+    }
+}
@@ -1,12 +1,24 @@
 use anyhow::Result;
 use clap::Parser;
+use rust_app_exemplo::config::de::parse_duration;
+use rust_app_exemplo::config::{AppConfig, ConfigOverrides, LogFormat};
+use rust_app_exemplo::fib_cache;
+use rust_app_exemplo::formats::{self, DocumentFormat};
+use rust_app_exemplo::plugins;
 use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
 use std::fs;
 use std::path::PathBuf;
 
 /// Aplicação Rust modelo criada com Nix
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    long_version = rust_app_exemplo::build_info::LONG_VERSION,
+    about,
+    long_about = None
+)]
 struct Args {
     /// Nome do usuário
     #[arg(short, long)]
@@ -20,6 +32,35 @@ struct Args {
     #[arg(short, long)]
     verbose: bool,
 
+    /// Sobrescreve o host do servidor (server.host)
+    #[arg(long, global = true)]
+    server_host: Option<String>,
+
+    /// Sobrescreve a porta do servidor (server.port)
+    #[arg(long, global = true)]
+    server_port: Option<u16>,
+
+    /// URL de conexão do banco de dados (database.url)
+    #[arg(long, global = true)]
+    db_url: Option<String>,
+
+    /// Nível de log (logging.level)
+    #[arg(long, global = true)]
+    log_level: Option<String>,
+
+    /// Formato de log: json, pretty ou compact (logging.format)
+    #[arg(long, global = true)]
+    log_format: Option<LogFormat>,
+
+    /// Só mostra o que os comandos destrutivos (delete-user,
+    /// anonymize-user, cleanup, revert) mudariam, sem gravar nada
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Não pede confirmação nos comandos destrutivos
+    #[arg(short = 'y', long, global = true)]
+    yes: bool,
+
     /// Comando a executar
     #[command(subcommand)]
     command: Option<Commands>,
@@ -32,7 +73,7 @@ enum Commands {
         /// Nome da pessoa
         name: String,
     },
-    /// Processa um arquivo JSON
+    /// Processa um arquivo JSON, CSV ou YAML
     Process {
         /// Caminho do arquivo
         file: PathBuf,
@@ -42,12 +83,216 @@ enum Commands {
         /// Número para calcular
         n: u64,
     },
+    /// Compara dois arquivos de texto, linha a linha (sai com 1 se diferirem)
+    Diff {
+        /// Arquivo original
+        file1: PathBuf,
+        /// Arquivo modificado
+        file2: PathBuf,
+        /// Não colore a saída (padrão quando ela não é um terminal ou com
+        /// `NO_COLOR`)
+        #[arg(long)]
+        no_color: bool,
+    },
+    /// Análise de textos
+    Text {
+        #[command(subcommand)]
+        command: TextCommands,
+    },
+    /// Usuários num arquivo JSON local, sem Postgres
+    Users {
+        /// Arquivo JSON dos usuários (criado no primeiro cadastro)
+        #[arg(long, global = true, default_value = "data/users.json")]
+        file: PathBuf,
+        #[command(subcommand)]
+        command: UsersCommands,
+    },
+    #[cfg(feature = "api")]
+    /// Inicia o servidor HTTP da API
+    Serve,
+    /// Verifica se a instância está saudável (sai com 0 ou 1), para o
+    /// HEALTHCHECK do Docker e probes do Kubernetes
+    Healthcheck {
+        /// URL verificada (padrão: `/health` na porta de `server.port`)
+        #[arg(long)]
+        url: Option<String>,
+        /// Testa a conexão com o banco em vez do `/health`
+        #[cfg(feature = "postgres")]
+        #[arg(long, conflicts_with = "url")]
+        db: bool,
+        /// Tempo máximo da verificação
+        #[arg(long, default_value = "3s", value_parser = parse_duration)]
+        timeout: std::time::Duration,
+    },
+    #[cfg(feature = "queue")]
+    /// Consome comandos de usuário da fila NATS até receber Ctrl+C/SIGTERM
+    Consume,
+    #[cfg(feature = "client")]
+    /// Teste de carga HTTP contra um endpoint
+    Loadtest {
+        /// URL alvo (ex.: http://localhost:8080/health)
+        #[arg(long)]
+        url: String,
+        /// Número de workers simultâneos
+        #[arg(long, default_value_t = 10)]
+        concurrency: usize,
+        /// Duração do teste (ex.: 30s, 2m)
+        #[arg(long, default_value = "10s", value_parser = parse_duration)]
+        duration: std::time::Duration,
+        /// Timeout por requisição
+        #[arg(long, default_value = "5s", value_parser = parse_duration)]
+        timeout: std::time::Duration,
+    },
+    #[cfg(feature = "client")]
+    /// Métricas de uma instância em execução
+    Metrics {
+        #[command(subcommand)]
+        command: MetricsCommands,
+    },
+    #[cfg(all(feature = "client", feature = "api"))]
+    /// Comandos contra uma instância em execução, pela API HTTP
+    Api {
+        /// URL da instância
+        #[arg(
+            long,
+            global = true,
+            env = "APP_API_URL",
+            default_value = "http://localhost:8080"
+        )]
+        base_url: String,
+        /// Token enviado em `Authorization: Bearer`
+        #[arg(long, global = true, env = "APP_API_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+        /// Tenant das requisições (`X-Tenant-Id`)
+        #[arg(long, global = true)]
+        tenant: Option<String>,
+        #[command(subcommand)]
+        command: ApiCommands,
+    },
     #[cfg(feature = "postgres")]
     /// Comandos de banco de dados
     Db {
+        /// Tenant dos comandos de usuário (padrão: `tenancy.default_tenant`)
+        #[arg(long, global = true)]
+        tenant: Option<String>,
         #[command(subcommand)]
         command: DbCommands,
     },
+    #[cfg(feature = "secrets")]
+    /// Valores cifrados (`enc:`) da configuração
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Plugin externo (`rust-app-exemplo-<comando>` no PATH)
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
+}
+
+#[derive(Parser, Debug)]
+enum TextCommands {
+    /// Frequência de palavras e n-gramas de um arquivo de texto
+    Stats {
+        /// Caminho do arquivo
+        file: PathBuf,
+        /// Quantas palavras e n-gramas mais frequentes mostrar
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        /// Palavras por n-grama
+        #[arg(long, default_value_t = 2)]
+        ngram: usize,
+    },
+}
+
+#[derive(Parser, Debug)]
+enum UsersCommands {
+    /// Lista os usuários
+    List,
+    /// Cria um novo usuário
+    Create {
+        /// Nome do usuário
+        name: String,
+        /// Email do usuário
+        email: String,
+    },
+    /// Remove um usuário
+    Delete {
+        /// ID do usuário
+        id: i32,
+    },
+    /// Ativa um usuário
+    Activate {
+        /// ID do usuário
+        id: i32,
+    },
+    /// Desativa um usuário
+    Deactivate {
+        /// ID do usuário
+        id: i32,
+    },
+}
+
+#[cfg(feature = "secrets")]
+#[derive(Parser, Debug)]
+enum ConfigCommands {
+    /// Gera uma chave age para `APP_CONFIG_KEY`
+    GenerateKey,
+    /// Cifra um valor com a chave de `APP_CONFIG_KEY` ou
+    /// `APP_CONFIG_IDENTITY_FILE` e imprime o `enc:...` para o config.toml
+    EncryptValue {
+        /// Valor a cifrar (padrão: lido do stdin, para não ficar no
+        /// histórico do shell)
+        value: Option<String>,
+    },
+    /// Decifra um valor `enc:...` com a mesma chave
+    DecryptValue {
+        /// Valor cifrado
+        value: String,
+    },
+}
+
+#[cfg(feature = "client")]
+#[derive(Parser, Debug)]
+enum MetricsCommands {
+    /// Imprime os contadores e histogramas atuais de `/metrics`
+    Dump {
+        /// URL do `/metrics` (padrão: na porta de gestão, se houver, ou na
+        /// de `server.port`)
+        #[arg(long)]
+        url: Option<String>,
+        /// openmetrics ou json
+        #[arg(long, default_value = "openmetrics")]
+        format: rust_app_exemplo::metrics_snapshot::SnapshotFormat,
+        /// Token de `/metrics` (padrão: `features.admin_token`)
+        #[arg(long, env = "APP_API_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+        /// Tempo máximo da requisição
+        #[arg(long, default_value = "5s", value_parser = parse_duration)]
+        timeout: std::time::Duration,
+    },
+}
+
+#[cfg(all(feature = "client", feature = "api"))]
+#[derive(Parser, Debug)]
+enum ApiCommands {
+    /// Verifica o `/health` da instância
+    Health,
+    /// Lista os usuários, uma página por vez
+    ListUsers {
+        /// Página, a partir de 1
+        #[arg(long)]
+        page: Option<u32>,
+        /// Usuários por página
+        #[arg(long)]
+        per_page: Option<u32>,
+    },
+    /// Cria um novo usuário
+    CreateUser {
+        /// Nome do usuário
+        name: String,
+        /// Email do usuário
+        email: String,
+    },
 }
 
 #[cfg(feature = "postgres")]
@@ -55,6 +300,29 @@ enum Commands {
 enum DbCommands {
     /// Inicializa o banco de dados e executa migrations
     Init,
+    /// Desfaz as últimas migrations aplicadas
+    Revert {
+        /// Quantidade de migrations a desfazer
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+    /// Cria uma migration reversível (`.up.sql` e `.down.sql`)
+    NewMigration {
+        /// Nome da migration (ex.: "create orders")
+        name: String,
+        /// Diretório das migrations
+        #[arg(long, default_value = "migrations")]
+        dir: PathBuf,
+    },
+    /// Confere o schema do banco contra as migrations embutidas
+    CheckSchema,
+    /// Migra o banco e regenera os metadados das consultas (`.sqlx`)
+    /// usados para compilar sem banco
+    Prepare {
+        /// Raiz do projeto
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+    },
     /// Testa a conexão com o banco
     Ping,
     /// Cria um novo usuário
@@ -76,6 +344,47 @@ enum DbCommands {
         /// ID do usuário
         id: i32,
     },
+    /// Remove os dados pessoais de um usuário (irreversível), mantendo o ID
+    AnonymizeUser {
+        /// ID do usuário
+        id: i32,
+    },
+    /// Desativa (ou remove, com `--action purge`) as contas sem alterações
+    /// há mais que a retenção, em todos os tenants
+    Cleanup {
+        /// Retenção (ex.: "180days"); padrão: users.inactive_retention_seconds
+        #[arg(long, value_parser = parse_duration)]
+        retention: Option<std::time::Duration>,
+        /// deactivate ou purge; padrão: users.inactive_action
+        #[arg(long)]
+        action: Option<rust_app_exemplo::config::InactiveUserAction>,
+    },
+    /// Popula o banco com usuários de exemplo gerados aleatoriamente, ou
+    /// aplica um arquivo de seed (idempotente)
+    Seed {
+        /// Quantidade de usuários
+        #[arg(long, default_value_t = 10)]
+        count: usize,
+        /// Semente para gerar sempre os mesmos usuários
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Arquivo JSON, YAML ou CSV com os dados desejados
+        #[arg(long, conflicts_with_all = ["count", "seed"])]
+        file: Option<PathBuf>,
+    },
+}
+
+impl Args {
+    /// Sobrescritas de `AppConfig` vindas das flags globais
+    fn config_overrides(&self) -> ConfigOverrides {
+        ConfigOverrides {
+            server_host: self.server_host.clone(),
+            server_port: self.server_port,
+            database_url: self.db_url.clone(),
+            log_level: self.log_level.clone(),
+            log_format: self.log_format.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -98,6 +407,7 @@ impl Default for Config {
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
+    let overrides = args.config_overrides();
 
     if args.verbose {
         println!("🦀 Modo verbose ativado");
@@ -125,12 +435,118 @@ async fn main() -> Result<()> {
             process_file(file)?;
         }
         Some(Commands::Fibonacci { n }) => {
-            let result = fibonacci(n);
+            let result = fibonacci(n)?;
             println!("Fibonacci({}) = {}", n, result);
         }
+        Some(Commands::Diff {
+            file1,
+            file2,
+            no_color,
+        }) => {
+            if !diff_files(file1, file2, no_color)? {
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Text {
+            command: TextCommands::Stats { file, top, ngram },
+        }) => {
+            text_stats(file, top, ngram)?;
+        }
+        Some(Commands::Users { file, command }) => {
+            handle_users_command(file, command).await?;
+        }
+        #[cfg(feature = "api")]
+        Some(Commands::Serve) => {
+            serve(overrides).await?;
+        }
+        Some(Commands::Healthcheck {
+            url,
+            #[cfg(feature = "postgres")]
+            db,
+            timeout,
+        }) => {
+            let app_config = AppConfig::load_with_overrides(&overrides)?;
+            #[cfg(feature = "postgres")]
+            let result = if db {
+                ping_database(&app_config, timeout).await
+            } else {
+                check_health(&app_config, url, timeout).await
+            };
+            #[cfg(not(feature = "postgres"))]
+            let result = check_health(&app_config, url, timeout).await;
+
+            match result {
+                Ok(target) => println!("✅ {} OK", target),
+                Err(e) => {
+                    eprintln!("❌ Verificação falhou: {:#}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        #[cfg(feature = "queue")]
+        Some(Commands::Consume) => {
+            consume(overrides).await?;
+        }
+        #[cfg(feature = "client")]
+        Some(Commands::Loadtest {
+            url,
+            concurrency,
+            duration,
+            timeout,
+        }) => {
+            loadtest(rust_app_exemplo::loadtest::LoadTestConfig {
+                url,
+                concurrency,
+                duration,
+                timeout,
+            })
+            .await?;
+        }
+        #[cfg(feature = "client")]
+        Some(Commands::Metrics {
+            command:
+                MetricsCommands::Dump {
+                    url,
+                    format,
+                    token,
+                    timeout,
+                },
+        }) => {
+            let app_config = AppConfig::load_with_overrides(&overrides)?;
+            dump_metrics(&app_config, url, format, token, timeout).await?;
+        }
+        #[cfg(all(feature = "client", feature = "api"))]
+        Some(Commands::Api {
+            base_url,
+            token,
+            tenant,
+            command,
+        }) => {
+            let mut client = rust_app_exemplo::client::ApiClient::new(base_url)?;
+            if let Some(token) = token {
+                client = client.token(token);
+            }
+            if let Some(tenant) = tenant {
+                client = client.tenant(tenant);
+            }
+            handle_api_command(command, &client).await?;
+        }
         #[cfg(feature = "postgres")]
-        Some(Commands::Db { command }) => {
-            handle_db_command(command).await?;
+        Some(Commands::Db { tenant, command }) => {
+            let app_config = AppConfig::load_with_overrides(&overrides)?;
+            let tenant = match tenant {
+                Some(tenant) => rust_app_exemplo::tenant::TenantContext::new(&tenant)?,
+                None => app_config.tenancy.tenant()?,
+            };
+            handle_db_command(command, &tenant, app_config, args.dry_run, args.yes).await?;
+        }
+        #[cfg(feature = "secrets")]
+        Some(Commands::Config { command }) => {
+            handle_config_command(command)?;
+        }
+        Some(Commands::External(args)) => {
+            let code = run_plugin(args, &overrides)?;
+            std::process::exit(code);
         }
         None => {
             if let Some(name) = args.name {
@@ -143,51 +559,132 @@ async fn main() -> Result<()> {
     }
 
     #[cfg(feature = "postgres")]
-    async fn handle_db_command(command: DbCommands) -> Result<()> {
+    async fn handle_db_command(
+        command: DbCommands,
+        tenant: &rust_app_exemplo::tenant::TenantContext,
+        app_config: AppConfig,
+        dry_run: bool,
+        yes: bool,
+    ) -> Result<()> {
+        use rust_app_exemplo::cli::prompt::confirm;
         use rust_app_exemplo::db::{Database, DbUser};
 
+        let db_config = rust_app_exemplo::db::DatabaseConfig::from(&app_config.database);
+
+        // Melhor recusar que gravar achando que é só uma simulação
+        if dry_run
+            && !matches!(
+                command,
+                DbCommands::DeleteUser { .. }
+                    | DbCommands::AnonymizeUser { .. }
+                    | DbCommands::Cleanup { .. }
+                    | DbCommands::Revert { .. }
+            )
+        {
+            anyhow::bail!(
+                "--dry-run is only supported by delete-user, anonymize-user, cleanup and revert"
+            );
+        }
+
         match command {
             DbCommands::Init => {
                 println!("🔧 Inicializando banco de dados...");
-                let db = Database::from_env().await?;
+                let db = Database::new(db_config).await?;
                 db.migrate().await?;
                 println!("✅ Banco de dados inicializado com sucesso!");
                 println!("📊 Migrations executadas!");
             }
+            DbCommands::Revert { steps } => {
+                let db = Database::new(db_config).await?;
+                let versions = db.to_revert(steps).await?;
+                if versions.is_empty() {
+                    println!("ℹ️  Nenhuma migration aplicada para desfazer");
+                    return Ok(());
+                }
+                if dry_run {
+                    println!(
+                        "🔎 [dry-run] {} migration(s) seriam desfeitas:",
+                        versions.len()
+                    );
+                    for version in &versions {
+                        println!("  ↩️  {}", version);
+                    }
+                    return Ok(());
+                }
+
+                let question = format!(
+                    "Desfazer {} migration(s) (os dados das tabelas removidas se perdem)?",
+                    versions.len()
+                );
+                if !confirm(&question, yes)? {
+                    println!("❎ Cancelado");
+                    return Ok(());
+                }
+                println!("⏪ Desfazendo {} migration(s)...", versions.len());
+                let reverted = db.revert(steps).await?;
+                for version in reverted {
+                    println!("  ↩️  {}", version);
+                }
+                println!("✅ Migrations desfeitas!");
+            }
+            DbCommands::NewMigration { name, dir } => {
+                let (up, down) =
+                    rust_app_exemplo::db::new_migration(&dir, &name, chrono::Utc::now())?;
+                println!("📝 Migration criada:");
+                println!("  {}", up.display());
+                println!("  {}", down.display());
+            }
+            DbCommands::CheckSchema => {
+                println!("🔍 Conferindo o schema do banco...");
+                let db = Database::new(db_config).await?;
+                db.check_schema().await?.into_result()?;
+                println!("✅ Schema em dia com as migrations!");
+            }
+            DbCommands::Prepare { dir } => {
+                println!("🔧 Migrando o banco...");
+                let db = Database::new(db_config.clone()).await?;
+                db.migrate().await?;
+                println!("🔨 Compilando as consultas contra o banco...");
+                let queries =
+                    rust_app_exemplo::db::prepare::prepare(&dir, &db_config.connection_string())?;
+                println!(
+                    "✅ {} consulta(s) gravada(s) em {}",
+                    queries,
+                    dir.join(rust_app_exemplo::db::prepare::OFFLINE_DIR)
+                        .display()
+                );
+            }
             DbCommands::Ping => {
                 println!("🔍 Testando conexão com o banco...");
-                let db = Database::from_env().await?;
+                let db = Database::new(db_config).await?;
                 db.ping().await?;
                 println!("✅ Conexão OK!");
             }
             DbCommands::CreateUser { name, email } => {
                 println!("👤 Criando usuário...");
-                let db = Database::from_env().await?;
-                let user = DbUser::create(db.pool(), &name, &email).await?;
+                let db = Database::new(db_config).await?;
+                let user = DbUser::create(db.pool(), tenant, &name, &email).await?;
                 println!("✅ Usuário criado com sucesso!");
                 println!("{}", serde_json::to_string_pretty(&user)?);
             }
             DbCommands::ListUsers => {
                 println!("📋 Listando usuários...");
-                let db = Database::from_env().await?;
-                let users = DbUser::list_all(db.pool()).await?;
-                let count = DbUser::count(db.pool()).await?;
+                let db = Database::new(db_config).await?;
+                let users = DbUser::list_all(db.pool(), tenant).await?;
+                let count = DbUser::count(db.pool(), tenant).await?;
 
                 println!("\n{} usuário(s) encontrado(s):\n", count);
                 for user in users {
                     println!(
                         "  [{}] {} - {} ({})",
-                        user.id,
-                        user.name,
-                        user.email,
-                        if user.active { "ativo" } else { "inativo" }
+                        user.id, user.name, user.email, user.status
                     );
                 }
             }
             DbCommands::GetUser { id } => {
                 println!("🔍 Buscando usuário #{}...", id);
-                let db = Database::from_env().await?;
-                match DbUser::find_by_id(db.pool(), id).await? {
+                let db = Database::new(db_config).await?;
+                match DbUser::find_by_id(db.pool(), tenant, id).await? {
                     Some(user) => {
                         println!("✅ Usuário encontrado!");
                         println!("{}", serde_json::to_string_pretty(&user)?);
@@ -197,12 +694,138 @@ async fn main() -> Result<()> {
                     }
                 }
             }
+            DbCommands::DeleteUser { id } | DbCommands::AnonymizeUser { id } if dry_run => {
+                let verb = if matches!(command, DbCommands::DeleteUser { .. }) {
+                    "deletado"
+                } else {
+                    "anonimizado"
+                };
+                let db = Database::new(db_config).await?;
+                match DbUser::find_by_id(db.pool(), tenant, id).await? {
+                    Some(user) => {
+                        println!("🔎 [dry-run] 1 usuário seria {}:", verb);
+                        println!("  [{}] {} - {}", user.id, user.name, user.email);
+                    }
+                    None => println!("🔎 [dry-run] Usuário #{} não encontrado: nada mudaria", id),
+                }
+            }
             DbCommands::DeleteUser { id } => {
+                if !confirm(&format!("Deletar o usuário #{}?", id), yes)? {
+                    println!("❎ Cancelado");
+                    return Ok(());
+                }
                 println!("🗑️  Deletando usuário #{}...", id);
-                let db = Database::from_env().await?;
-                DbUser::delete(db.pool(), id).await?;
+                let db = Database::new(db_config).await?;
+                DbUser::delete(db.pool(), tenant, id).await?;
                 println!("✅ Usuário deletado com sucesso!");
             }
+            DbCommands::AnonymizeUser { id } => {
+                let question = format!("Anonimizar o usuário #{} (irreversível)?", id);
+                if !confirm(&question, yes)? {
+                    println!("❎ Cancelado");
+                    return Ok(());
+                }
+                println!("🕶️  Anonimizando usuário #{}...", id);
+                let db = Database::new(db_config).await?;
+                match DbUser::anonymize(db.pool(), tenant, id).await? {
+                    Some(user) => {
+                        println!("✅ Usuário anonimizado!");
+                        println!("{}", serde_json::to_string_pretty(&user)?);
+                    }
+                    None => {
+                        println!("❌ Usuário não encontrado!");
+                    }
+                }
+            }
+            DbCommands::Cleanup { retention, action } => {
+                let retention = retention.unwrap_or(std::time::Duration::from_secs(
+                    app_config.users.inactive_retention_seconds,
+                ));
+                if retention.is_zero() {
+                    anyhow::bail!(
+                        "no retention: pass --retention or set users.inactive_retention_seconds"
+                    );
+                }
+                let action = action.unwrap_or(app_config.users.inactive_action);
+                let before = chrono::Utc::now() - chrono::Duration::from_std(retention)?;
+
+                let verb = match action {
+                    rust_app_exemplo::config::InactiveUserAction::Deactivate => "desativada(s)",
+                    rust_app_exemplo::config::InactiveUserAction::Purge => "removida(s)",
+                };
+                let db = Database::new(db_config).await?;
+                if dry_run {
+                    let users = DbUser::inactive_candidates(db.pool(), before, action).await?;
+                    println!("🔎 [dry-run] {} conta(s) seriam {}:", users.len(), verb);
+                    for user in &users {
+                        println!("  [{}] {} - {}", user.id, user.name, user.email);
+                    }
+                    return Ok(());
+                }
+
+                let question = format!("Limpar as contas sem alterações desde {}?", before);
+                if !confirm(&question, yes)? {
+                    println!("❎ Cancelado");
+                    return Ok(());
+                }
+                println!("🧹 Limpando contas sem alterações desde {}...", before);
+                let users = DbUser::cleanup_inactive(db.pool(), before, action).await?;
+                for (tenant, user) in &users {
+                    println!("  [{}/{}] {} - {}", tenant, user.id, user.name, user.email);
+                }
+                tracing::info!(
+                    ?action,
+                    affected = users.len(),
+                    %before,
+                    "Inactive accounts cleaned up"
+                );
+                println!("✅ {} conta(s) {}", users.len(), verb);
+            }
+            DbCommands::Seed {
+                file: Some(file), ..
+            } => {
+                use rust_app_exemplo::db::seed::SeedData;
+
+                let data = SeedData::load(&file)?;
+                println!(
+                    "🌱 Aplicando {} ({} usuários)...",
+                    file.display(),
+                    data.users.len()
+                );
+                let db = Database::new(db_config).await?;
+                let report = data
+                    .apply(db.pool(), tenant, app_config.database.copy_threshold)
+                    .await?;
+                println!(
+                    "✅ Seed aplicado: {} criados, {} atualizados, {} sem mudanças",
+                    report.created, report.updated, report.unchanged
+                );
+            }
+            DbCommands::Seed {
+                count,
+                seed,
+                file: None,
+            } => {
+                use rust_app_exemplo::fixtures::Fixtures;
+
+                println!("🌱 Gerando {} usuários...", count);
+                let db = Database::new(db_config).await?;
+                let mut fixtures = seed.map(Fixtures::seeded).unwrap_or_default();
+
+                for user in fixtures.users(count) {
+                    let created =
+                        DbUser::create(db.pool(), tenant, &user.name, &user.email).await?;
+                    if !user.is_active() {
+                        DbUser {
+                            status: user.status,
+                            ..created
+                        }
+                        .update(db.pool(), tenant)
+                        .await?;
+                    }
+                }
+                println!("✅ {} usuários criados!", count);
+            }
         }
 
         Ok(())
@@ -211,6 +834,653 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "api")]
+async fn serve(overrides: ConfigOverrides) -> Result<()> {
+    use rust_app_exemplo::api::{
+        create_management_router, create_router, middleware::build_stack_with_rate_limit,
+        rate_limit::RateLimit, AppState,
+    };
+
+    let config = AppConfig::load_with_overrides(&overrides)?;
+    let _logging = rust_app_exemplo::logging::init(&config.logging)?;
+
+    #[cfg(unix)]
+    reload_log_level_on_sighup(overrides);
+
+    #[cfg(feature = "observability")]
+    if config.features.metrics_enabled {
+        rust_app_exemplo::api::metrics::install_recorder()?;
+    }
+
+    #[cfg(feature = "postgres")]
+    let databases = rust_app_exemplo::db::DatabaseRegistry::connect(&config).await?;
+
+    #[cfg(feature = "postgres")]
+    if config.database.verify_schema {
+        if let Some(db) = databases.primary() {
+            db.check_schema().await?.into_result()?;
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    if config.database.warmup {
+        if let Some(db) = databases.primary() {
+            match db.warmup().await {
+                Ok(connections) => tracing::info!(connections, "Database pool warmed up"),
+                Err(error) => tracing::warn!(%error, "Database warmup failed"),
+            }
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    let state = AppState::with_databases(databases, config.health.clone())?;
+
+    // Sem Postgres, os usuários ficam em memória, gravados em `users.store_file`
+    #[cfg(not(feature = "postgres"))]
+    let users = memory_users(&config)?;
+    #[cfg(not(feature = "postgres"))]
+    let state = AppState::new(users.clone(), config.health.clone());
+
+    #[cfg(feature = "webhooks")]
+    let dispatcher = rust_app_exemplo::webhooks::WebhookDispatcher::new(
+        state.webhooks.clone(),
+        config.webhooks.clone(),
+    )?;
+
+    #[cfg(feature = "postgres")]
+    if let Some(db) = &state.db {
+        use rust_app_exemplo::outbox::OutboxRelay;
+
+        if config.database.health_check_interval_ms > 0 {
+            state.databases.spawn_monitors(
+                &state.health_checks,
+                std::time::Duration::from_millis(config.database.health_check_interval_ms),
+            );
+        }
+
+        // Mudanças feitas por outras instâncias chegam via LISTEN/NOTIFY
+        rust_app_exemplo::events::postgres::spawn_bridge(db.pool().clone(), state.events.clone());
+
+        // Webhooks e fila recebem os eventos pelo outbox, sem perdas
+        let relay = OutboxRelay::new(db.pool().clone(), config.outbox.clone());
+        #[cfg(feature = "webhooks")]
+        let relay = relay.with_sink(std::sync::Arc::new(dispatcher.clone()));
+        #[cfg(feature = "queue")]
+        let relay = if config.queue.publish_events {
+            relay.with_sink(std::sync::Arc::new(
+                rust_app_exemplo::queue::nats::EventPublisher::connect(&config.queue).await?,
+            ))
+        } else {
+            relay
+        };
+        relay.spawn();
+    }
+
+    // Sem Postgres, os webhooks assinam direto o barramento em memória
+    #[cfg(all(feature = "webhooks", not(feature = "postgres")))]
+    dispatcher.spawn(&state.events);
+
+    #[cfg(feature = "webhooks")]
+    let state = {
+        use rust_app_exemplo::webhooks::{inbound, InboundWebhooks};
+
+        let (webhooks, jobs) = InboundWebhooks::new(&config.webhooks);
+        inbound::spawn_worker(jobs, std::sync::Arc::new(inbound::LogHandler));
+        state
+            .with_inbound_webhooks(webhooks)
+            .with_private_webhook_destinations(config.webhooks.allow_private_destinations)
+    };
+
+    let state = state.with_idempotency_ttl(std::time::Duration::from_secs(
+        config.idempotency.ttl_seconds,
+    ));
+    state
+        .idempotency
+        .spawn_purge(std::time::Duration::from_secs(
+            config.idempotency.purge_interval_seconds.max(1),
+        ));
+
+    let state = state.with_deletion_grace(std::time::Duration::from_secs(
+        config.users.deletion_grace_seconds,
+    ));
+    if config.users.deletion_grace_seconds > 0 {
+        state.spawn_deletion_purge(std::time::Duration::from_secs(
+            config.users.deletion_purge_interval_seconds.max(1),
+        ));
+    }
+
+    if config.users.inactive_retention_seconds > 0 {
+        state.spawn_inactive_cleanup(
+            std::time::Duration::from_secs(config.users.inactive_retention_seconds),
+            config.users.inactive_action,
+            std::time::Duration::from_secs(config.users.inactive_cleanup_interval_seconds.max(1)),
+        );
+    }
+
+    let state = state
+        .with_quota_defaults(config.quotas.clone())
+        .with_storage(rust_app_exemplo::storage::Storage::from_config(
+            &config.storage,
+        ))
+        .with_tokens(rust_app_exemplo::auth::TokenSigner::from_config(
+            &config.auth,
+        ))
+        .with_two_factor(config.auth.two_factor.clone());
+    #[cfg(feature = "passkeys")]
+    let state = state.with_passkeys(
+        rust_app_exemplo::auth::passkeys::PasskeyService::from_config(&config.auth.passkeys)?,
+    );
+
+    let state = state.with_default_tenant(if config.tenancy.require_header {
+        None
+    } else {
+        Some(config.tenancy.tenant()?)
+    });
+
+    let state = if config.cache.enabled {
+        state.with_response_cache(rust_app_exemplo::api::cache::ResponseCache::new(
+            &config.cache,
+        ))
+    } else {
+        state
+    };
+
+    #[cfg(feature = "postgres")]
+    let databases = state.databases.clone();
+
+    let rate_limit = RateLimit::from_config(&config)?;
+    #[cfg(feature = "postgres")]
+    let rate_limit = rate_limit.map(|rate_limit| {
+        let overrides = rust_app_exemplo::api::rate_limit::RateLimitOverrides::default();
+        if let Some(db) = &state.db {
+            overrides.spawn_refresh(
+                db.pool().clone(),
+                std::time::Duration::from_secs(config.rate_limit.overrides_refresh_seconds.max(1)),
+            );
+        }
+        rate_limit.with_overrides(overrides)
+    });
+
+    // Os endpoints de gestão também entram no log de auditoria
+    let management = create_management_router(config.features.admin_token.clone()).route_layer(
+        axum::middleware::from_fn_with_state(
+            state.audit.clone(),
+            rust_app_exemplo::api::middleware::audit,
+        ),
+    );
+    let mut app = create_router(state);
+
+    match config.features.management_port {
+        Some(port) => {
+            let address = format!("{}:{}", config.features.management_host, port);
+            let listener = tokio::net::TcpListener::bind(&address).await?;
+            println!("🔧 Endpoints de gestão em http://{}", address);
+            tokio::spawn(async move {
+                if let Err(e) = axum::serve(listener, management).await {
+                    tracing::error!(error = %e, "Management server failed");
+                }
+            });
+        }
+        // Na porta pública, só com o token: sem ele, qualquer um trocaria o
+        // nível de log ou leria as métricas
+        None if config.features.admin_token.is_some() => {
+            app = app.merge(management);
+        }
+        None => {
+            tracing::warn!(
+                "Management endpoints are disabled; \
+                 set features.management_port or features.admin_token to enable them"
+            );
+        }
+    }
+
+    let app = build_stack_with_rate_limit(app, &config, rate_limit);
+    let listener = tokio::net::TcpListener::bind(config.server_address()).await?;
+
+    println!("🚀 Servidor ouvindo em http://{}", config.server_address());
+    // `ConnectInfo` identifica o cliente no limite de requisições
+    let (stopping_tx, mut stopping) = tokio::sync::watch::channel(false);
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        let _ = stopping_tx.send(true);
+    });
+    let mut server = std::pin::pin!(std::future::IntoFuture::into_future(server));
+
+    tokio::select! {
+        result = &mut server => result?,
+        _ = stopping.changed() => {
+            println!("🛑 Encerrando: aguardando as requisições em andamento...");
+            // Conexões longas (SSE) não terminam sozinhas
+            let grace = std::time::Duration::from_secs(config.server.timeout_seconds.max(1));
+            match tokio::time::timeout(grace, server).await {
+                Ok(result) => result?,
+                Err(_) => tracing::warn!("Open connections did not finish in time; closing them"),
+            }
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    databases
+        .close_all(std::time::Duration::from_millis(
+            config.database.drain_timeout_ms,
+        ))
+        .await;
+    #[cfg(not(feature = "postgres"))]
+    users.flush()?;
+
+    println!("👋 Servidor encerrado");
+
+    Ok(())
+}
+
+#[cfg(feature = "queue")]
+async fn consume(overrides: ConfigOverrides) -> Result<()> {
+    let config = AppConfig::load_with_overrides(&overrides)?;
+    let _logging = rust_app_exemplo::logging::init(&config.logging)?;
+
+    #[cfg(feature = "postgres")]
+    let users = {
+        let db = rust_app_exemplo::db::Database::new((&config.database).into()).await?;
+        std::sync::Arc::new(
+            rust_app_exemplo::repository::PgUserRepository::new(db.pool().clone())
+                .with_retry(db.retry_policy()),
+        )
+    };
+
+    // Sem Postgres, os usuários ficam em memória, gravados em `users.store_file`
+    #[cfg(not(feature = "postgres"))]
+    let users = memory_users(&config)?;
+
+    println!(
+        "📥 Consumindo {} em {} (Ctrl+C para encerrar)",
+        config.queue.subject, config.queue.url
+    );
+    rust_app_exemplo::queue::nats::run(
+        &config.queue,
+        users.clone(),
+        config.tenancy.tenant()?,
+        shutdown_signal(),
+    )
+    .await?;
+    #[cfg(not(feature = "postgres"))]
+    users.flush()?;
+    println!("👋 Consumidor encerrado");
+
+    Ok(())
+}
+
+/// Usuários sem Postgres: lidos de `users.store_file` e gravados nele em
+/// segundo plano (só em memória com o caminho vazio)
+#[cfg(all(any(feature = "api", feature = "queue"), not(feature = "postgres")))]
+fn memory_users(
+    config: &AppConfig,
+) -> Result<std::sync::Arc<rust_app_exemplo::repository::InMemoryUserRepository>> {
+    use rust_app_exemplo::repository::InMemoryUserRepository;
+
+    let users = &config.users;
+    if users.store_file.as_os_str().is_empty() {
+        return Ok(std::sync::Arc::new(InMemoryUserRepository::new()));
+    }
+    let repo = std::sync::Arc::new(InMemoryUserRepository::load(&users.store_file)?);
+    repo.spawn_flusher(std::time::Duration::from_secs(
+        users.store_flush_interval_seconds.max(1),
+    ));
+    tracing::info!(file = %users.store_file.display(), "Users are stored in a local file");
+    Ok(repo)
+}
+
+/// Completa no primeiro Ctrl+C ou SIGTERM
+#[cfg(any(feature = "api", feature = "queue"))]
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// `GET` no `/health` da instância; devolve a URL verificada
+async fn check_health(
+    config: &AppConfig,
+    url: Option<String>,
+    timeout: std::time::Duration,
+) -> Result<String> {
+    let url = url.unwrap_or_else(|| local_url(&config.server.host, config.server.port, "/health"));
+    rust_app_exemplo::healthcheck::check_http(&url, timeout).await?;
+    Ok(url)
+}
+
+/// URL de `path` na instância local da configuração, na porta `port`
+fn local_url(host: &str, port: u16, path: &str) -> String {
+    // Servidor ouvindo em todas as interfaces: acessa pelo loopback
+    let host = match host {
+        "0.0.0.0" | "::" | "[::]" => "127.0.0.1",
+        host => host,
+    };
+    format!("http://{}:{}{}", host, port, path)
+}
+
+async fn handle_users_command(file: PathBuf, command: UsersCommands) -> Result<()> {
+    use rust_app_exemplo::models::UserStatus;
+    use rust_app_exemplo::repository::{InMemoryUserRepository, UserRepository};
+    use rust_app_exemplo::tenant::TenantContext;
+
+    let repo = InMemoryUserRepository::load(file)?;
+    let tenant = TenantContext::default_tenant();
+    match command {
+        UsersCommands::List => {
+            let users = repo.list_all(&tenant).await?;
+            println!("{} usuário(s) encontrado(s):\n", users.len());
+            for user in users {
+                println!(
+                    "  [{}] {} - {} ({})",
+                    user.id, user.name, user.email, user.status
+                );
+            }
+        }
+        UsersCommands::Create { name, email } => {
+            let user = repo.create(&tenant, &name, &email).await?;
+            println!("✅ Usuário criado com sucesso!");
+            println!("{}", serde_json::to_string_pretty(&user)?);
+        }
+        UsersCommands::Delete { id } => match repo.find_by_id(&tenant, id).await? {
+            Some(user) => {
+                repo.delete(&tenant, id).await?;
+                println!("🗑️  Usuário #{} ({}) removido", id, user.email);
+            }
+            None => println!("❌ Usuário #{} não encontrado", id),
+        },
+        UsersCommands::Activate { id } | UsersCommands::Deactivate { id } => {
+            let mut user = repo.get(&tenant, id).await?;
+            let to = if matches!(command, UsersCommands::Activate { .. }) {
+                UserStatus::Active
+            } else {
+                UserStatus::Deactivated
+            };
+            user.status = user.status.transition(to)?;
+            repo.update(&tenant, &user).await?;
+            println!(
+                "✅ [{}] {} - {} ({})",
+                user.id, user.name, user.email, user.status
+            );
+        }
+    }
+    repo.flush()?;
+    Ok(())
+}
+
+#[cfg(feature = "secrets")]
+fn handle_config_command(command: ConfigCommands) -> Result<()> {
+    use rust_app_exemplo::config::secrets::{SecretKey, IDENTITY_FILE_ENV, KEY_ENV};
+
+    let key = || {
+        SecretKey::from_env()?
+            .ok_or_else(|| anyhow::anyhow!("no key: set {} or {}", KEY_ENV, IDENTITY_FILE_ENV))
+    };
+    match command {
+        ConfigCommands::GenerateKey => {
+            let (_, identity) = SecretKey::generate();
+            println!("{}", identity);
+        }
+        ConfigCommands::EncryptValue { value } => {
+            let value = match value {
+                Some(value) => value,
+                None => {
+                    let mut value = String::new();
+                    std::io::stdin().read_line(&mut value)?;
+                    value.trim_end_matches(['\r', '\n']).to_string()
+                }
+            };
+            println!("{}", key()?.encrypt(&value)?);
+        }
+        ConfigCommands::DecryptValue { value } => {
+            println!("{}", key()?.decrypt(&value)?);
+        }
+    }
+    Ok(())
+}
+
+/// Busca o `/metrics` de uma instância e o imprime em `format`
+#[cfg(feature = "client")]
+async fn dump_metrics(
+    config: &AppConfig,
+    url: Option<String>,
+    format: rust_app_exemplo::metrics_snapshot::SnapshotFormat,
+    token: Option<String>,
+    timeout: std::time::Duration,
+) -> Result<()> {
+    use rust_app_exemplo::metrics_snapshot;
+
+    let url = url.unwrap_or_else(|| match config.features.management_port {
+        Some(port) => local_url(&config.features.management_host, port, "/metrics"),
+        None => local_url(&config.server.host, config.server.port, "/metrics"),
+    });
+    let token = token.or_else(|| config.features.admin_token.clone());
+    let text = metrics_snapshot::fetch(&url, token.as_deref(), timeout).await?;
+    let families = metrics_snapshot::parse(&text)?;
+    print!("{}", metrics_snapshot::render(&families, format)?);
+    Ok(())
+}
+
+/// Conecta e faz `SELECT 1` no banco da configuração
+#[cfg(feature = "postgres")]
+async fn ping_database(config: &AppConfig, timeout: std::time::Duration) -> Result<String> {
+    use rust_app_exemplo::db::{Database, DatabaseConfig};
+
+    let ping = async {
+        let db = Database::new(DatabaseConfig::from(&config.database)).await?;
+        db.ping().await
+    };
+    tokio::time::timeout(timeout, ping)
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out after {:?}", timeout))??;
+    Ok("database".to_string())
+}
+
+#[cfg(all(feature = "client", feature = "api"))]
+async fn handle_api_command(
+    command: ApiCommands,
+    client: &rust_app_exemplo::client::ApiClient,
+) -> Result<()> {
+    use rust_app_exemplo::api::handlers::CreateUserRequest;
+    use rust_app_exemplo::api::pagination::Pagination;
+
+    match command {
+        ApiCommands::Health => {
+            println!("🔍 Verificando {}...", client.base_url());
+            let report = client.health().await?;
+            for check in &report.checks {
+                println!(
+                    "  [{:?}] {}{}",
+                    check.status,
+                    check.name,
+                    check
+                        .message
+                        .as_deref()
+                        .map(|message| format!(" - {}", message))
+                        .unwrap_or_default()
+                );
+            }
+            if report.is_critical() {
+                anyhow::bail!("instance is unhealthy");
+            }
+            println!("✅ Instância saudável ({:?})", report.status);
+        }
+        ApiCommands::ListUsers { page, per_page } => {
+            println!("📋 Listando usuários...");
+            let page = client.list_users(Pagination { page, per_page }).await?;
+
+            match page.meta {
+                Some(meta) => println!(
+                    "\n{} usuário(s) encontrado(s) (página {} de {}):\n",
+                    meta.total,
+                    meta.page,
+                    meta.total_pages.max(1)
+                ),
+                None => println!("\n{} usuário(s) encontrado(s):\n", page.items.len()),
+            }
+            for user in page.items {
+                println!(
+                    "  [{}] {} - {} ({})",
+                    user.id, user.name, user.email, user.status
+                );
+            }
+        }
+        ApiCommands::CreateUser { name, email } => {
+            println!("👤 Criando usuário...");
+            let user = client
+                .create_user(&CreateUserRequest { name, email })
+                .await?;
+            println!("✅ Usuário criado com sucesso!");
+            println!("{}", serde_json::to_string_pretty(&user)?);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "client")]
+async fn loadtest(config: rust_app_exemplo::loadtest::LoadTestConfig) -> Result<()> {
+    use rust_app_exemplo::stats::format_millis;
+
+    println!(
+        "🔨 Disparando contra {} ({} workers por {})...",
+        config.url,
+        config.concurrency,
+        humantime::format_duration(config.duration)
+    );
+
+    let report = rust_app_exemplo::loadtest::run(&config).await?;
+
+    println!("\n📊 Resultado");
+    println!("   Requisições: {}", report.requests);
+    println!("   Vazão:       {:.1} req/s", report.throughput());
+    println!(
+        "   Erros:       {} ({:.2}%)",
+        report.errors,
+        report.error_rate() * 100.0
+    );
+    for (status, count) in &report.status_counts {
+        println!("   HTTP {}:     {}", status, count);
+    }
+
+    if let Some(latency) = report.latency {
+        println!("\n⏱️  Latência");
+        println!("   min: {}", format_millis(latency.min));
+        println!("   média: {}", format_millis(latency.mean));
+        println!("   p50: {}", format_millis(latency.p50));
+        println!("   p90: {}", format_millis(latency.p90));
+        println!("   p95: {}", format_millis(latency.p95));
+        println!("   p99: {}", format_millis(latency.p99));
+        println!("   max: {}", format_millis(latency.max));
+    }
+
+    Ok(())
+}
+
+/// Recarrega o nível de log da configuração ao receber SIGHUP
+#[cfg(all(unix, feature = "api"))]
+fn reload_log_level_on_sighup(overrides: ConfigOverrides) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    tokio::spawn(async move {
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to install SIGHUP handler");
+                return;
+            }
+        };
+
+        while hangup.recv().await.is_some() {
+            let result = AppConfig::load_with_overrides(&overrides)
+                .and_then(|config| rust_app_exemplo::logging::set_level(&config.logging.level));
+            if let Err(e) = result {
+                tracing::warn!(error = %e, "Failed to reload log level on SIGHUP");
+            }
+        }
+    });
+}
+
+/// Executa um subcomando externo e devolve o código de saída dele
+fn run_plugin(args: Vec<OsString>, overrides: &ConfigOverrides) -> Result<i32> {
+    let (name, args) = args
+        .split_first()
+        .expect("clap always passes the subcommand name");
+    let name = name.to_string_lossy();
+
+    let Some(path) = plugins::find(&name) else {
+        let available = plugins::discover();
+        let suggestions = suggest_commands(&name, &available);
+        if !suggestions.is_empty() {
+            anyhow::bail!(
+                "unknown command '{}' (did you mean: {}?)",
+                name,
+                suggestions.join(", ")
+            );
+        }
+        if available.is_empty() {
+            anyhow::bail!(
+                "unknown command '{}' (no {}{} found on PATH)",
+                name,
+                plugins::PREFIX,
+                name
+            );
+        }
+        anyhow::bail!(
+            "unknown command '{}' (available plugins: {})",
+            name,
+            available.join(", ")
+        );
+    };
+
+    let config = AppConfig::load_with_overrides(overrides)?;
+    let status = plugins::run(&path, args, &config)?;
+
+    // Encerrado por sinal: segue a convenção do shell (128 + sinal)
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return Ok(128 + signal);
+    }
+
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Até 3 subcomandos (embutidos ou plugins) parecidos com `name`
+fn suggest_commands(name: &str, plugins: &[String]) -> Vec<String> {
+    use clap::CommandFactory;
+
+    let command = Args::command();
+    let candidates = command
+        .get_subcommands()
+        .map(|c| c.get_name())
+        .chain(plugins.iter().map(String::as_str));
+    rust_app_exemplo::string_utils::fuzzy_match(name, candidates)
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
 fn greet(name: &str) {
     println!("Olá, {}! 👋", name);
     println!("Bem-vindo à aplicação Rust com Nix!");
@@ -220,20 +1490,66 @@ fn process_file(path: PathBuf) -> Result<()> {
     println!("📄 Processando arquivo: {:?}", path);
 
     let content = fs::read_to_string(&path)?;
-    let data: serde_json::Value = serde_json::from_str(&content)?;
+    let format = DocumentFormat::from_path(&path);
+    let data = formats::parse(&content, format)?;
 
-    println!("✅ Arquivo processado com sucesso!");
+    println!("✅ Arquivo {} processado com sucesso!", format.as_str());
     println!("Conteúdo: {}", serde_json::to_string_pretty(&data)?);
 
     Ok(())
 }
 
-fn fibonacci(n: u64) -> u64 {
-    match n {
-        0 => 0,
-        1 => 1,
-        _ => fibonacci(n - 1) + fibonacci(n - 2),
+/// Imprime o diff dos arquivos; `true` se forem iguais
+fn diff_files(path1: PathBuf, path2: PathBuf, no_color: bool) -> Result<bool> {
+    use rust_app_exemplo::string_utils;
+    use std::io::IsTerminal;
+
+    let old = fs::read_to_string(&path1)?;
+    let new = fs::read_to_string(&path2)?;
+    let changes = string_utils::diff(&old, &new);
+    if string_utils::is_unchanged(&changes) {
+        return Ok(true);
+    }
+
+    let color =
+        !no_color && std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal();
+    println!("--- {}", path1.display());
+    println!("+++ {}", path2.display());
+    print!("{}", string_utils::render(&changes, color));
+    Ok(false)
+}
+
+fn text_stats(path: PathBuf, top: usize, ngram: usize) -> Result<()> {
+    use rust_app_exemplo::string_utils;
+
+    let content = fs::read_to_string(&path)?;
+    let frequencies = string_utils::word_frequencies(&content);
+
+    println!("📊 Estatísticas de {:?}", path);
+    println!("Palavras: {}", frequencies.values().sum::<usize>());
+    println!("Palavras distintas: {}", frequencies.len());
+
+    println!("\nPalavras mais frequentes:");
+    for (word, count) in string_utils::top_words(&content, top) {
+        println!("  {:>6}  {}", count, word);
+    }
+
+    println!("\n{}-gramas mais frequentes:", ngram);
+    for (gram, count) in string_utils::top_ngrams(&content, ngram, top) {
+        println!("  {:>6}  {}", count, gram);
     }
+
+    Ok(())
+}
+
+fn fibonacci(n: u64) -> Result<u64> {
+    fib_cache::fib_cached(n).ok_or_else(|| {
+        anyhow::anyhow!(
+            "fibonacci({}) does not fit in 64 bits (max n is {})",
+            n,
+            fib_cache::MAX_FIBONACCI_INPUT
+        )
+    })
 }
 
 #[cfg(test)]
@@ -242,13 +1558,21 @@ mod tests {
 
     #[test]
     fn test_fibonacci() {
-        assert_eq!(fibonacci(0), 0);
-        assert_eq!(fibonacci(1), 1);
-        assert_eq!(fibonacci(2), 1);
-        assert_eq!(fibonacci(3), 2);
-        assert_eq!(fibonacci(4), 3);
-        assert_eq!(fibonacci(5), 5);
-        assert_eq!(fibonacci(10), 55);
+        assert_eq!(fibonacci(0).unwrap(), 0);
+        assert_eq!(fibonacci(1).unwrap(), 1);
+        assert_eq!(fibonacci(2).unwrap(), 1);
+        assert_eq!(fibonacci(3).unwrap(), 2);
+        assert_eq!(fibonacci(4).unwrap(), 3);
+        assert_eq!(fibonacci(5).unwrap(), 5);
+        assert_eq!(fibonacci(10).unwrap(), 55);
+        assert!(fibonacci(94).is_err());
+    }
+
+    #[test]
+    fn test_suggest_commands() {
+        assert_eq!(suggest_commands("fib", &[]), ["fibonacci"]);
+        assert_eq!(suggest_commands("prt", &["porta".to_string()]), ["porta"]);
+        assert!(suggest_commands("zzz", &[]).is_empty());
     }
 
     #[test]
@@ -258,4 +1582,30 @@ mod tests {
         assert_eq!(config.version, "0.1.0");
         assert!(config.features.contains(&"cli".to_string()));
     }
+
+    #[cfg(all(feature = "client", feature = "api"))]
+    #[test]
+    fn test_api_flags_are_global() {
+        let args = Args::try_parse_from([
+            "rust-app-exemplo",
+            "api",
+            "list-users",
+            "--per-page",
+            "5",
+            "--base-url",
+            "http://app:8080",
+        ])
+        .unwrap();
+        match args.command {
+            Some(Commands::Api {
+                base_url,
+                command: ApiCommands::ListUsers { page, per_page },
+                ..
+            }) => {
+                assert_eq!(base_url, "http://app:8080");
+                assert_eq!((page, per_page), (None, Some(5)));
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
 }
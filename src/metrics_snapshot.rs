@@ -0,0 +1,330 @@
+//! Snapshot das métricas de uma instância, para o comando `metrics dump`
+//!
+//! `parse` lê o formato texto do Prometheus (o de `/metrics`) em famílias
+//! de métricas, que `render` imprime de volta em OpenMetrics (terminado em
+//! `# EOF`) ou em JSON. `/metrics?format=json` usa o mesmo caminho para o
+//! registro do próprio processo.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Formato de saída do snapshot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotFormat {
+    #[default]
+    OpenMetrics,
+    Json,
+}
+
+impl std::str::FromStr for SnapshotFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "openmetrics" | "text" => Ok(SnapshotFormat::OpenMetrics),
+            "json" => Ok(SnapshotFormat::Json),
+            other => Err(format!(
+                "invalid metrics format '{}' (openmetrics, json)",
+                other
+            )),
+        }
+    }
+}
+
+/// Uma métrica com seu tipo e todas as suas séries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricFamily {
+    pub name: String,
+    /// counter, gauge, histogram, summary ou untyped
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help: Option<String>,
+    pub samples: Vec<Sample>,
+}
+
+/// Um valor de uma série (num histograma, um bucket, a soma ou a contagem)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    pub value: f64,
+}
+
+/// Famílias de métricas do formato texto do Prometheus
+///
+/// Uma série sem `# TYPE` antes dela vira uma família `untyped`; os
+/// timestamps opcionais das séries são descartados.
+pub fn parse(text: &str) -> Result<Vec<MetricFamily>> {
+    let mut families: Vec<MetricFamily> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut parts = comment.trim_start().splitn(3, ' ');
+            let (keyword, name, rest) = (parts.next(), parts.next(), parts.next());
+            let (Some(keyword @ ("HELP" | "TYPE")), Some(name)) = (keyword, name) else {
+                continue;
+            };
+            let family = family_mut(&mut families, name);
+            let rest = rest.unwrap_or_default().trim();
+            if keyword == "HELP" {
+                family.help = Some(rest.to_string());
+            } else {
+                family.kind = rest.to_string();
+            }
+            continue;
+        }
+
+        let sample =
+            parse_sample(line).with_context(|| format!("invalid sample on line {}", number + 1))?;
+        let belongs = |family: &MetricFamily| {
+            sample.name == family.name
+                || sample
+                    .name
+                    .strip_prefix(family.name.as_str())
+                    .is_some_and(|suffix| {
+                        matches!(
+                            suffix,
+                            "_bucket" | "_sum" | "_count" | "_total" | "_created"
+                        )
+                    })
+        };
+        match families.last_mut() {
+            Some(family) if belongs(family) => family.samples.push(sample),
+            _ => family_mut(&mut families, &sample.name.clone())
+                .samples
+                .push(sample),
+        }
+    }
+    Ok(families)
+}
+
+/// Família `name`: a atual, se for ela, ou uma nova `untyped`
+fn family_mut<'a>(families: &'a mut Vec<MetricFamily>, name: &str) -> &'a mut MetricFamily {
+    if families.last().is_none_or(|family| family.name != name) {
+        families.push(MetricFamily {
+            name: name.to_string(),
+            kind: "untyped".to_string(),
+            help: None,
+            samples: Vec::new(),
+        });
+    }
+    families.last_mut().expect("just pushed")
+}
+
+/// `nome{label="valor",...} valor [timestamp]`
+fn parse_sample(line: &str) -> Result<Sample> {
+    let name_end = line
+        .find(|c: char| c == '{' || c.is_whitespace())
+        .context("missing value")?;
+    let name = &line[..name_end];
+    let mut rest = &line[name_end..];
+    let mut labels = BTreeMap::new();
+
+    if let Some(inner) = rest.strip_prefix('{') {
+        let mut chars = inner.char_indices().peekable();
+        let mut end = None;
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '}' => {
+                    end = Some(i);
+                    break;
+                }
+                ',' | ' ' => continue,
+                _ => {}
+            }
+            let eq = inner[i..].find('=').context("label without value")? + i;
+            let key = inner[i..eq].trim().to_string();
+            while chars.peek().is_some_and(|&(j, _)| j <= eq) {
+                chars.next();
+            }
+            if !matches!(chars.next(), Some((_, '"'))) {
+                bail!("label value must be quoted");
+            }
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => value.push('\n'),
+                        Some((_, escaped)) => value.push(escaped),
+                        None => bail!("unterminated label value"),
+                    },
+                    Some((_, c)) => value.push(c),
+                    None => bail!("unterminated label value"),
+                }
+            }
+            labels.insert(key, value);
+        }
+        let end = end.context("unterminated label set")?;
+        rest = &inner[end + 1..];
+    }
+
+    let value = rest.split_whitespace().next().context("missing value")?;
+    Ok(Sample {
+        name: name.to_string(),
+        labels,
+        value: parse_value(value)?,
+    })
+}
+
+fn parse_value(value: &str) -> Result<f64> {
+    match value {
+        "+Inf" | "Inf" => Ok(f64::INFINITY),
+        "-Inf" => Ok(f64::NEG_INFINITY),
+        "NaN" => Ok(f64::NAN),
+        _ => value
+            .parse()
+            .with_context(|| format!("invalid value '{}'", value)),
+    }
+}
+
+/// `families` em `format`
+pub fn render(families: &[MetricFamily], format: SnapshotFormat) -> Result<String> {
+    match format {
+        SnapshotFormat::Json => Ok(serde_json::to_string_pretty(families)? + "\n"),
+        SnapshotFormat::OpenMetrics => Ok(render_openmetrics(families)),
+    }
+}
+
+fn render_openmetrics(families: &[MetricFamily]) -> String {
+    let mut out = String::new();
+    for family in families {
+        if let Some(help) = &family.help {
+            let _ = writeln!(out, "# HELP {} {}", family.name, help);
+        }
+        let _ = writeln!(out, "# TYPE {} {}", family.name, family.kind);
+        for sample in &family.samples {
+            out.push_str(&sample.name);
+            if !sample.labels.is_empty() {
+                let labels: Vec<String> = sample
+                    .labels
+                    .iter()
+                    .map(|(key, value)| {
+                        let value = value
+                            .replace('\\', r"\\")
+                            .replace('"', "\\\"")
+                            .replace('\n', r"\n");
+                        format!("{}=\"{}\"", key, value)
+                    })
+                    .collect();
+                let _ = write!(out, "{{{}}}", labels.join(","));
+            }
+            let _ = writeln!(out, " {}", format_value(sample.value));
+        }
+    }
+    out.push_str("# EOF\n");
+    out
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Texto de `/metrics` em `url`, com `token` em `Authorization: Bearer`
+#[cfg(feature = "client")]
+pub async fn fetch(url: &str, token: Option<&str>, timeout: std::time::Duration) -> Result<String> {
+    let client = reqwest::Client::builder().timeout(timeout).build()?;
+    let mut request = client.get(url);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("failed to reach {}", url))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("{} returned HTTP {}", url, status.as_u16());
+    }
+    Ok(response.text().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"# TYPE http_requests_total counter
+http_requests_total{method="GET",route="/api/users/:id",status="2xx"} 3
+http_requests_total{method="POST",route="/api/users",status="4xx"} 1
+
+# HELP fibonacci_cache_entries Valores guardados
+# TYPE fibonacci_cache_entries gauge
+fibonacci_cache_entries 31
+
+# TYPE http_request_duration_seconds histogram
+http_request_duration_seconds_bucket{route="/version",le="0.005"} 2
+http_request_duration_seconds_bucket{route="/version",le="+Inf"} 2
+http_request_duration_seconds_sum{route="/version"} 0.0012
+http_request_duration_seconds_count{route="/version"} 2
+orphan{note="a \"quoted\" value, with comma"} NaN 1700000000
+"#;
+
+    #[test]
+    fn test_parse_families() {
+        let families = parse(TEXT).unwrap();
+        let names: Vec<_> = families.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "http_requests_total",
+                "fibonacci_cache_entries",
+                "http_request_duration_seconds",
+                "orphan"
+            ]
+        );
+
+        let requests = &families[0];
+        assert_eq!(requests.kind, "counter");
+        assert_eq!(requests.samples.len(), 2);
+        assert_eq!(requests.samples[0].labels["route"], "/api/users/:id");
+        assert_eq!(requests.samples[0].value, 3.0);
+
+        assert_eq!(families[1].help.as_deref(), Some("Valores guardados"));
+        let histogram = &families[2];
+        assert_eq!(histogram.kind, "histogram");
+        assert_eq!(histogram.samples.len(), 4);
+        assert_eq!(histogram.samples[1].labels["le"], "+Inf");
+
+        let orphan = &families[3];
+        assert_eq!(orphan.kind, "untyped");
+        assert_eq!(
+            orphan.samples[0].labels["note"],
+            r#"a "quoted" value, with comma"#
+        );
+        assert!(orphan.samples[0].value.is_nan());
+
+        assert!(parse("broken{le=\"1\" 2").is_err());
+        assert!(parse("no_value").is_err());
+    }
+
+    #[test]
+    fn test_render_round_trips() {
+        let families = parse(TEXT).unwrap();
+        let text = render(&families, SnapshotFormat::OpenMetrics).unwrap();
+        assert!(text.ends_with("# EOF\n"));
+        assert!(text.contains(
+            "http_requests_total{method=\"GET\",route=\"/api/users/:id\",status=\"2xx\"} 3\n"
+        ));
+        assert_eq!(parse(&text).unwrap().len(), families.len());
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&families, SnapshotFormat::Json).unwrap()).unwrap();
+        assert_eq!(json[1]["type"], "gauge");
+        assert_eq!(json[1]["samples"][0]["value"], 31.0);
+        assert_eq!("JSON".parse(), Ok(SnapshotFormat::Json));
+    }
+}
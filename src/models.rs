@@ -0,0 +1,470 @@
+//! Modelos persistidos, independentes do backend de armazenamento
+
+use serde::{Deserialize, Serialize};
+
+/// Usuário como armazenado no banco (tabela `users`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "postgres", derive(sqlx::FromRow))]
+pub struct DbUser {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    /// Situação da conta (ver `UserStatus`)
+    #[serde(default)]
+    pub status: UserStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::NaiveDateTime>,
+    /// Quando a conta será removida, se a exclusão foi pedida (ver
+    /// `UserRepository::schedule_deletion`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deletion_scheduled_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Último login bem-sucedido (ver `UserRepository::record_login`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_login_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Papel do usuário, que define por exemplo se o 2FA é obrigatório
+    /// (`auth.two_factor.roles`)
+    #[serde(default = "default_role")]
+    pub role: String,
+}
+
+/// Papel dos usuários criados pela API
+pub const DEFAULT_ROLE: &str = "user";
+
+fn default_role() -> String {
+    DEFAULT_ROLE.to_string()
+}
+
+/// Nome gravado no lugar do original ao anonimizar um usuário
+pub const ANONYMIZED_NAME: &str = "Deleted User";
+
+/// Domínio dos emails de usuários anonimizados (`.invalid` nunca resolve)
+pub const ANONYMIZED_EMAIL_DOMAIN: &str = "anonymized.invalid";
+
+impl DbUser {
+    /// Se a conta está ativa (pode entrar e usar a API)
+    pub fn is_active(&self) -> bool {
+        self.status == UserStatus::Active
+    }
+
+    /// Se o usuário já teve os dados pessoais removidos
+    pub fn is_anonymized(&self) -> bool {
+        self.email
+            .strip_suffix(ANONYMIZED_EMAIL_DOMAIN)
+            .is_some_and(|local| local.ends_with('@'))
+    }
+
+    /// Cópia sem dados pessoais (LGPD/GDPR): o ID e a data de criação são
+    /// mantidos, para preservar referências e histórico
+    ///
+    /// O email vira uma lápide aleatória, sem relação com o original (não dá
+    /// para recuperá-lo por dicionário), mas ainda única.
+    pub fn anonymized(&self) -> Self {
+        Self {
+            name: ANONYMIZED_NAME.to_string(),
+            email: format!(
+                "deleted-{:032x}@{}",
+                rand::random::<u128>(),
+                ANONYMIZED_EMAIL_DOMAIN
+            ),
+            status: self.status.deactivate().unwrap_or(self.status),
+            ..self.clone()
+        }
+    }
+}
+
+/// Usuário a inserir, sem os campos que o banco preenche (ver
+/// `DbUser::copy_in`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewUser {
+    pub name: String,
+    pub email: String,
+    #[serde(default)]
+    pub status: UserStatus,
+}
+
+impl NewUser {
+    /// Usuário ativo, como os criados pela API
+    pub fn new(name: impl Into<String>, email: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            email: email.into(),
+            status: UserStatus::Active,
+        }
+    }
+}
+
+/// Situação da conta de um usuário (coluna `users.status`)
+///
+/// As mudanças passam pelas transições (`suspend`, `reinstate`...), que
+/// recusam as que não fazem sentido: uma conta banida não volta, e uma
+/// pendente só pode ser ativada, desativada ou banida.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "postgres", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "postgres",
+    sqlx(type_name = "user_status", rename_all = "lowercase")
+)]
+pub enum UserStatus {
+    /// Criada, aguardando ativação
+    Pending,
+    #[default]
+    Active,
+    /// Bloqueada temporariamente; `reinstate` reativa
+    Suspended,
+    /// Desativada (a pedido ou por inatividade); `reinstate` reativa
+    Deactivated,
+    /// Bloqueada de vez
+    Banned,
+}
+
+/// Transição de `UserStatus` não permitida
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("cannot change a {from} account to {to}")]
+pub struct InvalidTransition {
+    pub from: UserStatus,
+    pub to: UserStatus,
+}
+
+impl UserStatus {
+    pub const ALL: [Self; 5] = [
+        Self::Pending,
+        Self::Active,
+        Self::Suspended,
+        Self::Deactivated,
+        Self::Banned,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Active => "active",
+            Self::Suspended => "suspended",
+            Self::Deactivated => "deactivated",
+            Self::Banned => "banned",
+        }
+    }
+
+    /// Se a conta pode ir de `self` para `to`
+    pub fn can_transition_to(self, to: Self) -> bool {
+        use UserStatus::*;
+        matches!(
+            (self, to),
+            (Pending, Active)
+                | (Active, Suspended)
+                | (Suspended | Deactivated, Active)
+                | (Pending | Active | Suspended, Deactivated)
+                | (Pending | Active | Suspended | Deactivated, Banned)
+        )
+    }
+
+    /// `to`, se a transição for permitida
+    pub fn transition(self, to: Self) -> Result<Self, InvalidTransition> {
+        if self.can_transition_to(to) {
+            Ok(to)
+        } else {
+            Err(InvalidTransition { from: self, to })
+        }
+    }
+
+    /// Pendente → ativa
+    pub fn activate(self) -> Result<Self, InvalidTransition> {
+        match self {
+            Self::Pending => Ok(Self::Active),
+            from => Err(InvalidTransition {
+                from,
+                to: Self::Active,
+            }),
+        }
+    }
+
+    /// Ativa → suspensa
+    pub fn suspend(self) -> Result<Self, InvalidTransition> {
+        self.transition(Self::Suspended)
+    }
+
+    /// Suspensa ou desativada → ativa
+    pub fn reinstate(self) -> Result<Self, InvalidTransition> {
+        match self {
+            Self::Suspended | Self::Deactivated => Ok(Self::Active),
+            from => Err(InvalidTransition {
+                from,
+                to: Self::Active,
+            }),
+        }
+    }
+
+    /// Qualquer uma, menos banida → desativada
+    pub fn deactivate(self) -> Result<Self, InvalidTransition> {
+        self.transition(Self::Deactivated)
+    }
+
+    /// Qualquer uma → banida
+    pub fn ban(self) -> Result<Self, InvalidTransition> {
+        self.transition(Self::Banned)
+    }
+}
+
+impl std::fmt::Display for UserStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for UserStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| {
+                format!(
+                    "invalid user status '{}' (pending, active, suspended, deactivated, banned)",
+                    s
+                )
+            })
+    }
+}
+
+/// Uma tentativa de login de um usuário (tabela `login_events`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "postgres", derive(sqlx::FromRow))]
+pub struct LoginEvent {
+    pub user_id: i32,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub success: bool,
+}
+
+/// Segundo fator (TOTP) de um usuário
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "postgres", derive(sqlx::FromRow))]
+pub struct TwoFactor {
+    /// Segredo TOTP (base32); `None` sem cadastro
+    #[cfg_attr(feature = "postgres", sqlx(rename = "totp_secret"))]
+    pub secret: Option<String>,
+    /// Quando o cadastro foi confirmado; antes disso o segredo está pendente
+    #[cfg_attr(feature = "postgres", sqlx(rename = "totp_enabled_at"))]
+    pub enabled_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Último passo TOTP aceito; códigos de passos até ele não valem mais
+    #[cfg_attr(feature = "postgres", sqlx(rename = "totp_last_step"))]
+    pub last_step: Option<i64>,
+    /// Códigos errados seguidos desde o último certo ou bloqueio
+    #[cfg_attr(feature = "postgres", sqlx(rename = "totp_failed_attempts"))]
+    pub failed_attempts: i32,
+    /// Até quando o segundo fator está bloqueado por tentativas erradas
+    #[cfg_attr(feature = "postgres", sqlx(rename = "totp_locked_until"))]
+    pub locked_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl TwoFactor {
+    pub fn is_enabled(&self) -> bool {
+        self.secret.is_some() && self.enabled_at.is_some()
+    }
+
+    /// Bloqueio em vigor em `now`, se houver
+    pub fn locked_at(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.locked_until.filter(|until| *until > now)
+    }
+}
+
+/// Passkey (credencial WebAuthn) de um usuário (tabela `passkeys`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "postgres", derive(sqlx::FromRow))]
+pub struct StoredPasskey {
+    /// Id da credencial (hex)
+    pub credential_id: String,
+    /// Nome dado pelo usuário (ex.: "Notebook")
+    pub name: String,
+    /// Credencial serializada pelo `webauthn-rs`, em JSON
+    #[serde(skip)]
+    pub passkey: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Campo de usuário que pode ser pedido em `?fields=` (os expostos pela API)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserField {
+    Id,
+    Name,
+    Email,
+    Status,
+    /// `status == active`, mantido para os clientes antigos
+    Active,
+    DeletionScheduledAt,
+}
+
+/// Projeção de um usuário: só os campos pedidos
+pub type UserProjection = serde_json::Map<String, serde_json::Value>;
+
+impl UserField {
+    pub const ALL: [Self; 6] = [
+        Self::Id,
+        Self::Name,
+        Self::Email,
+        Self::Status,
+        Self::Active,
+        Self::DeletionScheduledAt,
+    ];
+
+    /// Nome do campo na resposta e da coluna na tabela `users`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Name => "name",
+            Self::Email => "email",
+            Self::Status => "status",
+            Self::Active => "active",
+            Self::DeletionScheduledAt => "deletion_scheduled_at",
+        }
+    }
+
+    /// Lê uma lista separada por vírgulas (`id,name`), sem repetições
+    pub fn parse_list(list: &str) -> Result<Vec<Self>, UnknownField> {
+        let mut fields = Vec::new();
+        for name in list.split(',').map(str::trim) {
+            let field = Self::ALL
+                .into_iter()
+                .find(|f| f.name() == name)
+                .ok_or_else(|| UnknownField(name.to_string()))?;
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Só os `fields` do usuário
+    pub fn project(fields: &[Self], user: &DbUser) -> UserProjection {
+        fields
+            .iter()
+            .map(|field| {
+                let value = match field {
+                    Self::Id => user.id.into(),
+                    Self::Name => user.name.clone().into(),
+                    Self::Email => user.email.clone().into(),
+                    Self::Status => user.status.as_str().into(),
+                    Self::Active => user.is_active().into(),
+                    Self::DeletionScheduledAt => {
+                        serde_json::to_value(user.deletion_scheduled_at).unwrap_or_default()
+                    }
+                };
+                (field.name().to_string(), value)
+            })
+            .collect()
+    }
+}
+
+/// Campo fora de `UserField::ALL`
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown field '{0}' (allowed: id, name, email, status, active, deletion_scheduled_at)")]
+pub struct UnknownField(pub String);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anonymized_user_keeps_only_identity() {
+        let user = DbUser {
+            id: 7,
+            name: "Ana".to_string(),
+            email: "ana@example.com".to_string(),
+            status: UserStatus::Active,
+            created_at: Some(chrono::Utc::now().naive_utc()),
+            deletion_scheduled_at: None,
+            last_login_at: None,
+            role: DEFAULT_ROLE.to_string(),
+        };
+        assert!(!user.is_anonymized());
+
+        let anonymized = user.anonymized();
+        assert!(anonymized.is_anonymized());
+        assert_eq!(anonymized.id, user.id);
+        assert_eq!(anonymized.created_at, user.created_at);
+        assert_eq!(anonymized.name, ANONYMIZED_NAME);
+        assert_eq!(anonymized.status, UserStatus::Deactivated);
+        assert!(!anonymized.email.contains("ana"));
+        assert_ne!(anonymized.email, user.anonymized().email);
+    }
+
+    #[test]
+    fn test_user_fields() {
+        assert_eq!(
+            UserField::parse_list("id, name,id"),
+            Ok(vec![UserField::Id, UserField::Name])
+        );
+        assert_eq!(
+            UserField::parse_list("id,password"),
+            Err(UnknownField("password".to_string()))
+        );
+        assert!(UserField::parse_list("").is_err());
+
+        let user = DbUser {
+            id: 7,
+            name: "Ana".to_string(),
+            email: "ana@example.com".to_string(),
+            status: UserStatus::Active,
+            created_at: None,
+            deletion_scheduled_at: None,
+            last_login_at: None,
+            role: DEFAULT_ROLE.to_string(),
+        };
+        let fields = [
+            UserField::Name,
+            UserField::Status,
+            UserField::Active,
+            UserField::DeletionScheduledAt,
+        ];
+        assert_eq!(
+            serde_json::Value::Object(UserField::project(&fields, &user)),
+            serde_json::json!({
+                "name": "Ana",
+                "status": "active",
+                "active": true,
+                "deletion_scheduled_at": null
+            })
+        );
+    }
+
+    #[test]
+    fn test_user_status_transitions() {
+        use UserStatus::*;
+
+        assert_eq!(Pending.activate(), Ok(Active));
+        assert_eq!(Active.suspend(), Ok(Suspended));
+        assert_eq!(Suspended.reinstate(), Ok(Active));
+        assert_eq!(Deactivated.reinstate(), Ok(Active));
+        assert_eq!(Suspended.deactivate(), Ok(Deactivated));
+        assert_eq!(Deactivated.ban(), Ok(Banned));
+
+        assert_eq!(
+            Pending.suspend(),
+            Err(InvalidTransition {
+                from: Pending,
+                to: Suspended
+            })
+        );
+        assert!(Active.activate().is_err());
+        assert!(Active.reinstate().is_err());
+        assert!(Suspended.suspend().is_err());
+        for status in UserStatus::ALL {
+            assert!(!Banned.can_transition_to(status));
+        }
+        assert_eq!(
+            Banned.reinstate().unwrap_err().to_string(),
+            "cannot change a banned account to active"
+        );
+
+        assert_eq!("suspended".parse(), Ok(Suspended));
+        assert!("inactive".parse::<UserStatus>().is_err());
+        assert_eq!(serde_json::to_value(Deactivated).unwrap(), "deactivated");
+    }
+}
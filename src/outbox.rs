@@ -0,0 +1,307 @@
+//! Outbox transacional (feature "postgres")
+//!
+//! As mudanças em `users` gravam o evento correspondente na tabela `outbox`
+//! na mesma transação (`enqueue`), então um evento existe se e somente se a
+//! mudança foi confirmada. O `OutboxRelay` publica os pendentes, em ordem,
+//! em cada `OutboxSink` (webhooks, fila) e só então os marca como
+//! publicados; se um destino falhar, o evento (e os seguintes) ficam para a
+//! próxima rodada. A entrega é "pelo menos uma vez": o ID do evento é o da
+//! linha no outbox, estável entre tentativas, para os destinos descartarem
+//! repetições. O tenant do usuário e o trace da requisição que fez a
+//! mudança (`TraceContext`) também são gravados, para seguir com o evento
+//! até as entregas.
+
+use crate::config::OutboxConfig;
+use crate::db::timed;
+use crate::events::{DomainEvent, Event};
+use crate::tenant::TenantContext;
+use crate::trace_context::TraceContext;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Grava o evento do tenant no outbox, dentro da transação da mudança, com
+/// o trace atual (`TraceContext::current`)
+pub async fn enqueue(
+    conn: &mut PgConnection,
+    tenant: &TenantContext,
+    event: &DomainEvent,
+) -> Result<i64> {
+    let trace = TraceContext::current();
+    let id = timed(
+        "outbox.enqueue",
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO outbox (tenant_id, user_id, event_type, payload, traceparent, tracestate)
+            VALUES ($1, $2, $3, $4::text::jsonb, $5, $6)
+            RETURNING id
+            "#,
+            tenant.id(),
+            event.user_id(),
+            event.name(),
+            serde_json::to_string(event)?,
+            trace.as_ref().map(TraceContext::traceparent),
+            trace.and_then(|trace| trace.tracestate)
+        )
+        .fetch_one(conn),
+    )
+    .await?;
+
+    Ok(id)
+}
+
+/// Como `enqueue`, para vários eventos num único `INSERT` (usado nas cargas
+/// em lote); os IDs seguem a ordem de `events`
+pub async fn enqueue_all(
+    conn: &mut PgConnection,
+    tenant: &TenantContext,
+    events: &[DomainEvent],
+) -> Result<Vec<i64>> {
+    if events.is_empty() {
+        return Ok(Vec::new());
+    }
+    let user_ids: Vec<i32> = events.iter().map(DomainEvent::user_id).collect();
+    let names: Vec<String> = events.iter().map(|e| e.name().to_string()).collect();
+    let payloads = events
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()?;
+    let trace = TraceContext::current();
+    let ids = timed(
+        "outbox.enqueue_all",
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO outbox (tenant_id, user_id, event_type, payload, traceparent, tracestate)
+            SELECT $1, user_id, event_type, payload::jsonb, $5, $6
+            FROM UNNEST($2::int4[], $3::text[], $4::text[])
+                WITH ORDINALITY AS e(user_id, event_type, payload, n)
+            ORDER BY n
+            RETURNING id
+            "#,
+            tenant.id(),
+            &user_ids,
+            &names,
+            &payloads,
+            trace.as_ref().map(TraceContext::traceparent),
+            trace.and_then(|trace| trace.tracestate)
+        )
+        .fetch_all(conn),
+    )
+    .await?;
+
+    Ok(ids)
+}
+
+/// Até `limit` eventos gravados sobre um usuário do tenant, do mais antigo
+/// ao mais recente (publicados ou não); é o histórico usado na exportação
+/// dos dados dele
+pub async fn events_for_user(
+    pool: &PgPool,
+    tenant: &TenantContext,
+    user_id: i32,
+    limit: i64,
+) -> Result<Vec<Event>> {
+    let rows = timed(
+        "outbox.events_for_user",
+        sqlx::query_as!(
+            PendingRow,
+            r#"
+            SELECT id, tenant_id, payload::text AS "payload!", created_at, traceparent,
+                   tracestate
+            FROM outbox
+            WHERE user_id = $1 AND tenant_id = $2
+            ORDER BY id
+            LIMIT $3
+            "#,
+            user_id,
+            tenant.id(),
+            limit
+        )
+        .fetch_all(pool),
+    )
+    .await?;
+
+    rows.iter().map(PendingRow::event).collect()
+}
+
+/// Destino dos eventos do outbox
+#[async_trait]
+pub trait OutboxSink: Send + Sync {
+    /// Nome usado nos logs e em `last_error`
+    fn name(&self) -> &'static str;
+
+    /// Publica o evento; um erro faz o relay tentar de novo depois
+    async fn publish(&self, event: &Event) -> Result<()>;
+}
+
+/// Publica os eventos pendentes do outbox
+pub struct OutboxRelay {
+    pool: PgPool,
+    sinks: Vec<Arc<dyn OutboxSink>>,
+    config: OutboxConfig,
+}
+
+struct PendingRow {
+    id: i64,
+    tenant_id: String,
+    payload: String,
+    created_at: DateTime<Utc>,
+    traceparent: Option<String>,
+    tracestate: Option<String>,
+}
+
+impl PendingRow {
+    fn trace(&self) -> Option<TraceContext> {
+        let traceparent = self.traceparent.as_deref()?;
+        TraceContext::parse(traceparent, self.tracestate.as_deref())
+    }
+
+    fn event(&self) -> Result<Event> {
+        Ok(Event {
+            id: self.id as u64,
+            occurred_at: self.created_at,
+            tenant: self.tenant_id.clone(),
+            payload: serde_json::from_str(&self.payload).context("invalid outbox payload")?,
+            remote: false,
+            trace: self.trace(),
+        })
+    }
+}
+
+impl OutboxRelay {
+    /// Relay sem destinos: só marca os eventos como publicados
+    pub fn new(pool: PgPool, config: OutboxConfig) -> Self {
+        Self {
+            pool,
+            sinks: Vec::new(),
+            config,
+        }
+    }
+
+    /// Acrescenta um destino; os eventos passam por eles na ordem
+    pub fn with_sink(mut self, sink: Arc<dyn OutboxSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Publica em segundo plano, até o pool ser fechado
+    pub fn spawn(self) -> JoinHandle<()> {
+        let poll_interval = Duration::from_millis(self.config.poll_interval_ms);
+
+        tokio::spawn(async move {
+            while !self.pool.is_closed() {
+                match self.relay_batch().await {
+                    // Lote cheio: provavelmente há mais pendentes
+                    Ok(published) if published as i64 >= self.config.batch_size => continue,
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(error = %e, "Outbox relay failed; retrying"),
+                }
+                tokio::time::sleep(poll_interval).await;
+            }
+        })
+    }
+
+    /// Publica um lote de pendentes; devolve quantos foram publicados
+    ///
+    /// As linhas ficam travadas (`FOR UPDATE SKIP LOCKED`) durante o lote,
+    /// então várias instâncias podem rodar o relay sem publicar o mesmo
+    /// evento em paralelo.
+    pub async fn relay_batch(&self) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+        let pending = timed(
+            "outbox.pending",
+            sqlx::query_as!(
+                PendingRow,
+                r#"
+                SELECT id, tenant_id, payload::text AS "payload!", created_at, traceparent,
+                       tracestate
+                FROM outbox
+                WHERE published_at IS NULL
+                ORDER BY id
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+                "#,
+                self.config.batch_size.max(1)
+            )
+            .fetch_all(&mut *tx),
+        )
+        .await?;
+
+        let mut published = 0;
+        for row in pending {
+            match self.publish(&row).await {
+                Ok(()) => {
+                    timed(
+                        "outbox.mark_published",
+                        sqlx::query!(
+                            "UPDATE outbox SET published_at = NOW() WHERE id = $1",
+                            row.id
+                        )
+                        .execute(&mut *tx),
+                    )
+                    .await?;
+                    published += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, outbox_id = row.id, "Failed to publish outbox event");
+                    timed(
+                        "outbox.mark_failed",
+                        sqlx::query!(
+                            "UPDATE outbox SET attempts = attempts + 1, last_error = $2 WHERE id = $1",
+                            row.id,
+                            format!("{:#}", e)
+                        )
+                        .execute(&mut *tx),
+                    )
+                    .await?;
+                    // Para aqui para não publicar fora de ordem
+                    break;
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(published)
+    }
+
+    async fn publish(&self, row: &PendingRow) -> Result<()> {
+        let event = row.event()?;
+
+        for sink in &self.sinks {
+            sink.publish(&event)
+                .await
+                .with_context(|| format!("{} sink failed", sink.name()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Entrega aos webhooks cadastrados (as tentativas seguem no dispatcher)
+#[cfg(feature = "webhooks")]
+#[async_trait]
+impl OutboxSink for crate::webhooks::WebhookDispatcher {
+    fn name(&self) -> &'static str {
+        "webhooks"
+    }
+
+    async fn publish(&self, event: &Event) -> Result<()> {
+        self.dispatch(event.clone()).await
+    }
+}
+
+/// Publica no NATS JetStream
+#[cfg(feature = "queue")]
+#[async_trait]
+impl OutboxSink for crate::queue::nats::EventPublisher {
+    fn name(&self) -> &'static str {
+        "nats"
+    }
+
+    async fn publish(&self, event: &Event) -> Result<()> {
+        crate::queue::nats::EventPublisher::publish(self, event).await
+    }
+}
@@ -0,0 +1,47 @@
+//! Versões em lote das funções matemáticas, paralelizadas com rayon
+//! (feature "parallel")
+//!
+//! Cada função distribui o trabalho pelo pool global do rayon e devolve os
+//! resultados na ordem da entrada. Os limites são os das versões escalares:
+//! `fibonacci_optimized` passa de `u64` acima de 93 e `factorial` acima de
+//! 20.
+
+use crate::{factorial, fibonacci_optimized, is_prime};
+use rayon::prelude::*;
+use std::ops::Range;
+
+/// `fibonacci_optimized` de cada valor
+pub fn fibonacci_batch(values: &[u64]) -> Vec<u64> {
+    values.par_iter().map(|&n| fibonacci_optimized(n)).collect()
+}
+
+/// `factorial` de cada valor
+pub fn factorial_batch(values: &[u64]) -> Vec<u64> {
+    values.par_iter().map(|&n| factorial(n)).collect()
+}
+
+/// Primos do intervalo, em ordem crescente
+pub fn primes_parallel(range: Range<u64>) -> Vec<u64> {
+    range.into_par_iter().filter(|&n| is_prime(n)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batches_keep_input_order() {
+        assert_eq!(fibonacci_batch(&[10, 0, 20, 1]), [55, 0, 6765, 1]);
+        assert_eq!(factorial_batch(&[5, 0, 10]), [120, 1, 3_628_800]);
+        assert!(fibonacci_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_primes_parallel_matches_sequential() {
+        assert_eq!(primes_parallel(0..30), [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert!(primes_parallel(24..29).is_empty());
+
+        let sequential: Vec<u64> = (10_000..20_000).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes_parallel(10_000..20_000), sequential);
+    }
+}
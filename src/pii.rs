@@ -0,0 +1,245 @@
+//! Mascaramento de dados pessoais (PII) para logs e exibição
+//!
+//! `mask_email` e `mask_phone` mantêm só o suficiente para reconhecer o
+//! dado (`j***@e***.com`, `(**) *****-4321`). Para usar em `format!` ou
+//! nos campos do `tracing` sem alocar antes, envolva o valor em `Masked`:
+//!
+//! ```
+//! use rust_app_exemplo::pii::Masked;
+//!
+//! let email = "joao@example.com";
+//! assert_eq!(Masked(email).to_string(), "j***@e***.com");
+//! // tracing::info!(email = %Masked(email), "User created");
+//! ```
+
+use crate::models::DbUser;
+use crate::User;
+use std::fmt;
+
+/// Trecho que substitui o que foi escondido
+const HIDDEN: &str = "***";
+
+/// Dígitos finais que `mask_phone` mantém
+const PHONE_VISIBLE_DIGITS: usize = 4;
+
+/// Mascara um email: `joao@example.com` vira `j***@e***.com`
+///
+/// Sem `@`, mascara como texto qualquer (`mask_text`).
+pub fn mask_email(email: &str) -> String {
+    let Some((local, domain)) = email.rsplit_once('@') else {
+        return mask_text(email);
+    };
+    let domain = match domain.rsplit_once('.') {
+        Some((name, tld)) => format!("{}.{}", mask_text(name), tld),
+        None => mask_text(domain),
+    };
+    format!("{}@{}", mask_text(local), domain)
+}
+
+/// Mascara um telefone, trocando por `*` todos os dígitos menos os 4 últimos
+/// e mantendo a pontuação: `+55 11 98765-4321` vira `+** ** *****-4321`
+pub fn mask_phone(phone: &str) -> String {
+    let digits = phone.chars().filter(char::is_ascii_digit).count();
+    let mut hidden = digits.saturating_sub(PHONE_VISIBLE_DIGITS);
+    phone
+        .chars()
+        .map(|c| {
+            if c.is_ascii_digit() && hidden > 0 {
+                hidden -= 1;
+                '*'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Mascara um texto qualquer, mantendo só o primeiro caractere: `João`
+/// vira `J***`
+pub fn mask_text(text: &str) -> String {
+    match text.chars().next() {
+        Some(first) => format!("{}{}", first, HIDDEN),
+        None => String::new(),
+    }
+}
+
+/// Se o texto tem cara de telefone: só dígitos, espaços e `+-().`, com
+/// pelo menos 8 dígitos
+pub fn looks_like_phone(text: &str) -> bool {
+    text.chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, ' ' | '+' | '-' | '(' | ')' | '.'))
+        && text.chars().filter(char::is_ascii_digit).count() >= 8
+}
+
+/// Mascara um valor de formato desconhecido: email se tiver `@`, telefone
+/// se parecer um, senão texto
+pub fn mask(value: &str) -> String {
+    if value.contains('@') {
+        mask_email(value)
+    } else if looks_like_phone(value) {
+        mask_phone(value)
+    } else {
+        mask_text(value)
+    }
+}
+
+/// Tipos que sabem se exibir sem os dados pessoais
+pub trait Mask {
+    /// Escreve a versão mascarada de `self`
+    fn fmt_masked(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+/// Exibe o valor mascarado (ver `Mask`)
+#[derive(Debug, Clone, Copy)]
+pub struct Masked<T>(pub T);
+
+impl<T: Mask> fmt::Display for Masked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_masked(f)
+    }
+}
+
+impl<T: Mask + ?Sized> Mask for &T {
+    fn fmt_masked(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt_masked(f)
+    }
+}
+
+impl Mask for str {
+    fn fmt_masked(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&mask(self))
+    }
+}
+
+impl Mask for String {
+    fn fmt_masked(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt_masked(f)
+    }
+}
+
+impl<T: Mask> Mask for Option<T> {
+    fn fmt_masked(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Some(value) => value.fmt_masked(f),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+impl Mask for User {
+    fn fmt_masked(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "User(id: {}, name: {}, email: {}, active: {})",
+            self.id,
+            mask_text(&self.name),
+            mask_email(&self.email),
+            self.active
+        )
+    }
+}
+
+impl Mask for DbUser {
+    fn fmt_masked(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DbUser(id: {}, name: {}, email: {}, status: {})",
+            self.id,
+            mask_text(&self.name),
+            mask_email(&self.email),
+            self.status
+        )
+    }
+}
+
+/// URI com os valores da query mascarados (o caminho fica como está)
+///
+/// Emails em query costumam vir codificados (`%40`); são decodificados
+/// antes de mascarar. `?page=2` vira `?page=2`, mas `?email=joao%40x.com`
+/// vira `?email=j***@x***.com`.
+#[cfg(feature = "api")]
+impl Mask for axum::http::Uri {
+    fn fmt_masked(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.path())?;
+        let Some(query) = self.query() else {
+            return Ok(());
+        };
+        for (i, pair) in query.split('&').enumerate() {
+            f.write_str(if i == 0 { "?" } else { "&" })?;
+            match pair.split_once('=') {
+                Some((key, value)) => write!(f, "{}={}", key, mask_query_value(value))?,
+                None => f.write_str(pair)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Mascara o valor se for um email ou telefone; os demais (paginação,
+/// filtros) ficam como estão
+#[cfg(feature = "api")]
+fn mask_query_value(value: &str) -> String {
+    let decoded = value
+        .replace('+', " ")
+        .replace("%40", "@")
+        .replace("%2B", "+")
+        .replace("%2b", "+");
+    if decoded.contains('@') || looks_like_phone(&decoded) {
+        mask(&decoded)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_email() {
+        assert_eq!(mask_email("joao@example.com"), "j***@e***.com");
+        assert_eq!(mask_email("ana.maria@mail.empresa.com.br"), "a***@m***.br");
+        assert_eq!(mask_email("x@localhost"), "x***@l***");
+        assert_eq!(mask_email("sem-arroba"), "s***");
+        assert_eq!(mask_email(""), "");
+    }
+
+    #[test]
+    fn test_mask_phone() {
+        assert_eq!(mask_phone("+55 11 98765-4321"), "+** ** *****-4321");
+        assert_eq!(mask_phone("(11) 98765-4321"), "(**) *****-4321");
+        assert_eq!(mask_phone("123"), "123");
+        assert!(looks_like_phone("+55 (11) 98765-4321"));
+        assert!(!looks_like_phone("2024"));
+        assert!(!looks_like_phone("abc 12345678"));
+    }
+
+    #[test]
+    fn test_masked_display() {
+        assert_eq!(Masked("joao@example.com").to_string(), "j***@e***.com");
+        assert_eq!(Masked("11 98765-4321").to_string(), "** *****-4321");
+        assert_eq!(Masked("João Silva".to_string()).to_string(), "J***");
+        assert_eq!(Masked(None::<&str>).to_string(), "None");
+
+        let user = User::new(7, "João".to_string(), "joao@example.com".to_string());
+        assert_eq!(
+            format!("{}", Masked(&user)),
+            "User(id: 7, name: J***, email: j***@e***.com, active: true)"
+        );
+    }
+
+    #[cfg(feature = "api")]
+    #[test]
+    fn test_masked_uri() {
+        let uri: axum::http::Uri = "/api/users?page=2&email=joao%40example.com&flag"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            Masked(&uri).to_string(),
+            "/api/users?page=2&email=j***@e***.com&flag"
+        );
+
+        let uri: axum::http::Uri = "/api/users/1".parse().unwrap();
+        assert_eq!(Masked(uri).to_string(), "/api/users/1");
+    }
+}
@@ -0,0 +1,175 @@
+//! Subcomandos externos (plugins), no estilo do git
+//!
+//! `rust-app-exemplo foo args...` executa o binário `rust-app-exemplo-foo`
+//! encontrado no `PATH`, repassando os argumentos. O plugin recebe:
+//!
+//! - a configuração carregada (`AppConfig`) em JSON pelo stdin, sem os
+//!   segredos (`AppConfig::without_secrets`): senhas dos bancos, tokens e
+//!   chaves de assinatura chegam vazios, mesmo os decifrados de `enc:`, já
+//!   que o plugin é qualquer binário com o nome certo no `PATH`;
+//! - `RUST_APP_EXEMPLO_BIN`: caminho do executável principal;
+//! - `RUST_APP_EXEMPLO_VERSION`: versão da aplicação.
+//!
+//! O código de saída do plugin vira o código de saída do comando.
+
+use crate::config::AppConfig;
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+/// Prefixo dos binários de plugin
+pub const PREFIX: &str = "rust-app-exemplo-";
+
+/// Procura o plugin `name` no `PATH`
+pub fn find(name: &str) -> Option<PathBuf> {
+    find_in(name, std::env::var_os("PATH")?)
+}
+
+/// Nomes dos plugins disponíveis no `PATH`, em ordem alfabética
+pub fn discover() -> Vec<String> {
+    let Some(path) = std::env::var_os("PATH") else {
+        return Vec::new();
+    };
+
+    let mut names = BTreeSet::new();
+    for dir in std::env::split_paths(&path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(name) = file_name
+                .to_str()
+                .and_then(|f| f.strip_prefix(PREFIX))
+                .map(|f| f.trim_end_matches(std::env::consts::EXE_SUFFIX))
+            else {
+                continue;
+            };
+            if !name.is_empty() && is_executable(&entry.path()) {
+                names.insert(name.to_string());
+            }
+        }
+    }
+
+    names.into_iter().collect()
+}
+
+/// Executa o plugin em `path` e espera o término
+pub fn run(path: &Path, args: &[OsString], config: &AppConfig) -> Result<ExitStatus> {
+    let mut command = Command::new(path);
+    command
+        .args(args)
+        .env("RUST_APP_EXEMPLO_VERSION", env!("CARGO_PKG_VERSION"))
+        .stdin(Stdio::piped());
+    if let Ok(exe) = std::env::current_exe() {
+        command.env("RUST_APP_EXEMPLO_BIN", exe);
+    }
+
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("failed to start plugin {}", path.display()))?;
+
+    let config =
+        serde_json::to_vec(&config.without_secrets()).context("failed to serialize config")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Plugins que não leem o stdin podem fechá-lo antes da escrita
+        match stdin.write_all(&config) {
+            Err(e) if e.kind() != ErrorKind::BrokenPipe => {
+                return Err(e).context("failed to send config to plugin");
+            }
+            _ => {}
+        }
+    }
+
+    child
+        .wait()
+        .with_context(|| format!("failed to wait for plugin {}", path.display()))
+}
+
+fn find_in(name: &str, path: OsString) -> Option<PathBuf> {
+    // Nomes com separadores escapariam do diretório do PATH
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return None;
+    }
+
+    let file_name = format!("{}{}{}", PREFIX, name, std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn plugin_dir(script: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("plugins-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let plugin = dir.join(format!("{}hello", PREFIX));
+        std::fs::write(&plugin, script).unwrap();
+        std::fs::set_permissions(&plugin, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        // Mesmo prefixo, mas sem permissão de execução
+        std::fs::write(dir.join(format!("{}disabled", PREFIX)), script).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn test_find_in() {
+        let dir = plugin_dir("#!/bin/sh\n");
+        let path = std::env::join_paths(["/nonexistent".into(), dir.clone()]).unwrap();
+
+        assert_eq!(
+            find_in("hello", path.clone()),
+            Some(dir.join(format!("{}hello", PREFIX)))
+        );
+        assert_eq!(find_in("disabled", path.clone()), None);
+        assert_eq!(find_in("missing", path.clone()), None);
+        assert_eq!(find_in("../hello", path), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_run_passes_args_config_and_exit_code() {
+        let dir = plugin_dir("#!/bin/sh\ncat > \"$(dirname \"$0\")/stdin.json\"\necho \"$1\" > \"$(dirname \"$0\")/args\"\nexit 3\n");
+        let plugin = dir.join(format!("{}hello", PREFIX));
+
+        let mut config = AppConfig::builder().server_port(9999).build();
+        config.features.admin_token = Some("s3cret".to_string());
+        let status = run(&plugin, &["world".into()], &config).unwrap();
+
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(
+            std::fs::read_to_string(dir.join("args")).unwrap(),
+            "world\n"
+        );
+
+        let received: AppConfig =
+            serde_json::from_slice(&std::fs::read(dir.join("stdin.json")).unwrap()).unwrap();
+        assert_eq!(received.server.port, 9999);
+        assert_eq!(received.features.admin_token, None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
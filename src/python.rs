@@ -0,0 +1,253 @@
+//! Módulo Python (feature "python")
+//!
+//! Expõe `User`, as funções matemáticas e `string_utils` via pyo3. Gere o
+//! pacote com [maturin](https://www.maturin.rs) (configurado em
+//! `pyproject.toml`):
+//!
+//! ```bash
+//! maturin develop
+//! python -c "import rust_app_exemplo as r; print(r.fibonacci(10))"
+//! ```
+//!
+//! `User.from_dict` e `User.to_dict` convertem de/para `dict`:
+//!
+//! ```python
+//! from rust_app_exemplo import User
+//!
+//! user = User.from_dict({"id": 1, "name": "Ana", "email": "ana@example.com"})
+//! user.deactivate()
+//! user.to_dict()  # {'id': 1, 'name': 'Ana', 'email': 'ana@example.com', 'active': False}
+//! ```
+
+use crate::{string_utils, User};
+use pyo3::exceptions::{PyKeyError, PyOverflowError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// Maior `n` cujo fibonacci cabe em `u64`
+const MAX_FIBONACCI_INPUT: u64 = 93;
+
+/// Maior `n` cujo fatorial cabe em `u64`
+const MAX_FACTORIAL_INPUT: u64 = 20;
+
+/// `User` visto do Python
+#[pyclass(name = "User", module = "rust_app_exemplo", eq)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PyUser {
+    inner: User,
+}
+
+#[pymethods]
+impl PyUser {
+    #[new]
+    fn new(id: u64, name: String, email: String) -> Self {
+        User::new(id, name, email).into()
+    }
+
+    /// Cria um usuário a partir de um `dict` (`active` é opcional)
+    #[staticmethod]
+    fn from_dict(dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let field = |key: &str| {
+            dict.get_item(key)?
+                .ok_or_else(|| PyKeyError::new_err(format!("missing field '{}'", key)))
+        };
+
+        let active = match dict.get_item("active")? {
+            Some(value) => value.extract()?,
+            None => true,
+        };
+
+        Ok(User {
+            id: field("id")?.extract()?,
+            name: field("name")?.extract()?,
+            email: field("email")?.extract()?,
+            active,
+        }
+        .into())
+    }
+
+    /// Converte o usuário para `dict`
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("id", self.inner.id)?;
+        dict.set_item("name", &self.inner.name)?;
+        dict.set_item("email", &self.inner.email)?;
+        dict.set_item("active", self.inner.active)?;
+        Ok(dict)
+    }
+
+    #[getter]
+    fn id(&self) -> u64 {
+        self.inner.id
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    #[setter]
+    fn set_name(&mut self, name: String) {
+        self.inner.name = name;
+    }
+
+    #[getter]
+    fn email(&self) -> &str {
+        &self.inner.email
+    }
+
+    #[setter]
+    fn set_email(&mut self, email: String) {
+        self.inner.email = email;
+    }
+
+    #[getter]
+    fn active(&self) -> bool {
+        self.inner.active
+    }
+
+    fn activate(&mut self) {
+        self.inner.activate();
+    }
+
+    fn deactivate(&mut self) {
+        self.inner.deactivate();
+    }
+
+    fn __repr__(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+impl From<User> for PyUser {
+    fn from(inner: User) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<PyUser> for User {
+    fn from(user: PyUser) -> Self {
+        user.inner
+    }
+}
+
+/// N-ésimo número de Fibonacci; `OverflowError` acima de 93
+#[pyfunction]
+fn fibonacci(n: u64) -> PyResult<u64> {
+    if n > MAX_FIBONACCI_INPUT {
+        return Err(PyOverflowError::new_err(format!(
+            "fibonacci({}) does not fit in 64 bits",
+            n
+        )));
+    }
+    Ok(crate::fibonacci_optimized(n))
+}
+
+/// Fatorial de `n`; `OverflowError` acima de 20
+#[pyfunction]
+fn factorial(n: u64) -> PyResult<u64> {
+    if n > MAX_FACTORIAL_INPUT {
+        return Err(PyOverflowError::new_err(format!(
+            "factorial({}) does not fit in 64 bits",
+            n
+        )));
+    }
+    Ok(crate::factorial(n))
+}
+
+#[pyfunction]
+fn is_prime(n: u64) -> bool {
+    crate::is_prime(n)
+}
+
+#[pyfunction]
+fn to_title_case(s: &str) -> String {
+    string_utils::to_title_case(s)
+}
+
+#[pyfunction]
+fn count_vowels(s: &str) -> usize {
+    string_utils::count_vowels(s)
+}
+
+#[pyfunction]
+fn reverse(s: &str) -> String {
+    string_utils::reverse(s)
+}
+
+/// Ponto de entrada do módulo `rust_app_exemplo`
+#[pymodule]
+fn rust_app_exemplo(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyUser>()?;
+    m.add_function(wrap_pyfunction!(fibonacci, m)?)?;
+    m.add_function(wrap_pyfunction!(factorial, m)?)?;
+    m.add_function(wrap_pyfunction!(is_prime, m)?)?;
+
+    let strings = PyModule::new(m.py(), "string_utils")?;
+    strings.add_function(wrap_pyfunction!(to_title_case, &strings)?)?;
+    strings.add_function(wrap_pyfunction!(count_vowels, &strings)?)?;
+    strings.add_function(wrap_pyfunction!(reverse, &strings)?)?;
+    m.add_submodule(&strings)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::IntoPyDict;
+
+    fn with_module(test: impl FnOnce(Python<'_>, &Bound<'_, PyModule>)) {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "rust_app_exemplo").unwrap();
+            rust_app_exemplo(&module).unwrap();
+            test(py, &module);
+        });
+    }
+
+    #[test]
+    fn test_user_dict_roundtrip() {
+        with_module(|py, _| {
+            let dict = [("id", 7u64)].into_py_dict(py).unwrap();
+            dict.set_item("name", "Ana").unwrap();
+            dict.set_item("email", "ana@example.com").unwrap();
+
+            let mut user = PyUser::from_dict(&dict).unwrap();
+            assert!(user.active());
+            user.deactivate();
+
+            let back = user.to_dict(py).unwrap();
+            let active: bool = back.get_item("active").unwrap().unwrap().extract().unwrap();
+            assert!(!active);
+            assert_eq!(User::from(user).email, "ana@example.com");
+
+            dict.del_item("email").unwrap();
+            let error = PyUser::from_dict(&dict).unwrap_err();
+            assert!(error.is_instance_of::<PyKeyError>(py));
+        });
+    }
+
+    #[test]
+    fn test_module_functions() {
+        with_module(|py, module| {
+            let fib: u64 = module
+                .call_method1("fibonacci", (10,))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(fib, 55);
+
+            let error = module.call_method1("factorial", (21,)).unwrap_err();
+            assert!(error.is_instance_of::<PyOverflowError>(py));
+
+            let strings = module.getattr("string_utils").unwrap();
+            let title: String = strings
+                .call_method1("to_title_case", ("olá mundo",))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(title, "Olá Mundo");
+        });
+    }
+}
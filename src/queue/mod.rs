@@ -0,0 +1,481 @@
+//! Consumidor de comandos de usuário vindos de uma fila (feature "queue")
+//!
+//! Cada mensagem é um `UserCommand` em JSON, aplicado ao `UserRepository`:
+//!
+//! ```json
+//! {"op": "create", "name": "Ana", "email": "ana@example.com"}
+//! {"op": "set_status", "id": 3, "status": "suspended"}
+//! {"op": "set_active", "id": 3, "active": false}
+//! {"op": "delete", "id": 3}
+//! ```
+//!
+//! Os comandos são idempotentes (criar um email existente ou remover um
+//! usuário inexistente não é erro), então reentregas são seguras.
+//! `set_active` é o mesmo que `set_status` com `active` ou `deactivated`, e
+//! uma transição de status não permitida conta como mensagem inválida. Mensagens
+//! inválidas falham de vez (`CommandError::Invalid`) e vão direto para o dead
+//! letter; falhas do repositório (`CommandError::Repository`) são
+//! reentregues com backoff até `max_deliver`. O transporte fica em `nats`.
+//!
+//! Os comandos valem para um tenant: o do header `X-Tenant-Id` da mensagem
+//! ou, sem ele, o tenant padrão do consumidor.
+
+use crate::config::QueueConfig;
+use crate::models::{DbUser, UserStatus};
+use crate::repository::{DbError, UserRepository};
+use crate::tenant::TenantContext;
+use serde::Deserialize;
+use std::time::Duration;
+
+pub mod nats;
+
+/// Operação pedida por uma mensagem
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum UserCommand {
+    Create { name: String, email: String },
+    SetStatus { id: i32, status: UserStatus },
+    SetActive { id: i32, active: bool },
+    Delete { id: i32 },
+}
+
+/// Resultado de um comando aplicado
+#[derive(Debug, Clone, PartialEq)]
+pub enum Applied {
+    Created(DbUser),
+    Updated(DbUser),
+    Deleted(i32),
+    /// Nada a fazer (ex.: email já cadastrado, usuário inexistente)
+    Unchanged,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    /// Mensagem que nunca vai ser processada com sucesso
+    #[error("invalid message: {0}")]
+    Invalid(String),
+    /// Falha possivelmente temporária
+    #[error(transparent)]
+    Repository(#[from] anyhow::Error),
+}
+
+/// O que fazer com a mensagem depois de processá-la
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Disposition {
+    Ack,
+    /// Reentregar depois da espera
+    Retry(Duration),
+    /// Desistir e publicar no dead letter, com o motivo
+    DeadLetter(String),
+}
+
+impl UserCommand {
+    pub fn parse(payload: &[u8]) -> Result<Self, CommandError> {
+        let command: Self =
+            serde_json::from_slice(payload).map_err(|e| CommandError::Invalid(e.to_string()))?;
+
+        if let Self::Create { name, email } = &command {
+            if name.trim().is_empty() {
+                return Err(CommandError::Invalid("name must not be empty".to_string()));
+            }
+            if !email.contains('@') {
+                return Err(CommandError::Invalid(format!("invalid email '{}'", email)));
+            }
+        }
+        Ok(command)
+    }
+
+    /// Aplica o comando ao repositório, no `tenant`
+    pub async fn apply(
+        self,
+        users: &dyn UserRepository,
+        tenant: &TenantContext,
+    ) -> Result<Applied, CommandError> {
+        match self {
+            Self::Create { name, email } => {
+                let (user, created) = users.create_or_get(tenant, &name, &email).await?;
+                Ok(if created {
+                    Applied::Created(user)
+                } else {
+                    Applied::Unchanged
+                })
+            }
+            Self::SetStatus { id, status } => set_status(users, tenant, id, status).await,
+            Self::SetActive { id, active } => {
+                let status = if active {
+                    UserStatus::Active
+                } else {
+                    UserStatus::Deactivated
+                };
+                set_status(users, tenant, id, status).await
+            }
+            Self::Delete { id } => match users.delete(tenant, id).await {
+                Ok(()) => Ok(Applied::Deleted(id)),
+                Err(DbError::NotFound { .. }) => Ok(Applied::Unchanged),
+                Err(DbError::Other(e)) => Err(e.into()),
+            },
+        }
+    }
+}
+
+/// Processa uma mensagem e decide o destino dela
+///
+/// `delivered` é o número desta entrega, começando em 1.
+pub async fn handle(
+    users: &dyn UserRepository,
+    tenant: &TenantContext,
+    payload: &[u8],
+    delivered: u32,
+    config: &QueueConfig,
+) -> Disposition {
+    let result = match UserCommand::parse(payload) {
+        Ok(command) => command.apply(users, tenant).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(applied) => {
+            tracing::debug!(?applied, "Queue command applied");
+            Disposition::Ack
+        }
+        Err(e @ CommandError::Invalid(_)) => Disposition::DeadLetter(e.to_string()),
+        Err(e) if delivered >= config.max_deliver => {
+            Disposition::DeadLetter(format!("gave up after {} deliveries: {}", delivered, e))
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, delivered, "Queue command failed; will retry");
+            Disposition::Retry(retry_delay(config, delivered))
+        }
+    }
+}
+
+/// Espera antes da entrega `delivered + 1`: `retry_delay_ms`, dobrando a cada
+/// falha, até 5 minutos
+fn retry_delay(config: &QueueConfig, delivered: u32) -> Duration {
+    const MAX_DELAY: Duration = Duration::from_secs(300);
+
+    let factor = 2u64.saturating_pow(delivered.saturating_sub(1));
+    Duration::from_millis(config.retry_delay_ms.saturating_mul(factor)).min(MAX_DELAY)
+}
+
+async fn set_status(
+    users: &dyn UserRepository,
+    tenant: &TenantContext,
+    id: i32,
+    status: UserStatus,
+) -> Result<Applied, CommandError> {
+    match users.find_by_id(tenant, id).await? {
+        Some(user) if user.status != status => {
+            let status = user
+                .status
+                .transition(status)
+                .map_err(|e| CommandError::Invalid(e.to_string()))?;
+            let user = DbUser { status, ..user };
+            match users.update(tenant, &user).await {
+                Ok(()) => Ok(Applied::Updated(user)),
+                // Removido entre a leitura e a atualização
+                Err(DbError::NotFound { .. }) => Ok(Applied::Unchanged),
+                Err(DbError::Other(e)) => Err(e.into()),
+            }
+        }
+        _ => Ok(Applied::Unchanged),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::InMemoryUserRepository;
+    use anyhow::Result;
+    use async_trait::async_trait;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            UserCommand::parse(br#"{"op": "delete", "id": 3}"#).unwrap(),
+            UserCommand::Delete { id: 3 }
+        );
+        assert_eq!(
+            UserCommand::parse(br#"{"op": "set_active", "id": 3, "active": false}"#).unwrap(),
+            UserCommand::SetActive {
+                id: 3,
+                active: false
+            }
+        );
+        assert_eq!(
+            UserCommand::parse(br#"{"op": "set_status", "id": 3, "status": "banned"}"#).unwrap(),
+            UserCommand::SetStatus {
+                id: 3,
+                status: UserStatus::Banned
+            }
+        );
+
+        for invalid in [
+            &b"not json"[..],
+            br#"{"op": "explode"}"#,
+            br#"{"op": "delete"}"#,
+            br#"{"op": "delete", "id": 3, "extra": 1}"#,
+            br#"{"op": "set_status", "id": 3, "status": "gone"}"#,
+            br#"{"op": "create", "name": " ", "email": "a@b.c"}"#,
+            br#"{"op": "create", "name": "Ana", "email": "ana"}"#,
+        ] {
+            assert!(
+                matches!(UserCommand::parse(invalid), Err(CommandError::Invalid(_))),
+                "{}",
+                String::from_utf8_lossy(invalid)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_is_idempotent() {
+        let users = InMemoryUserRepository::new();
+        let tenant = TenantContext::default_tenant();
+        let create = UserCommand::Create {
+            name: "Ana".to_string(),
+            email: "ana@example.com".to_string(),
+        };
+
+        let Applied::Created(ana) = create.clone().apply(&users, &tenant).await.unwrap() else {
+            panic!("expected user to be created");
+        };
+        assert_eq!(
+            create.apply(&users, &tenant).await.unwrap(),
+            Applied::Unchanged
+        );
+
+        let deactivate = UserCommand::SetActive {
+            id: ana.id,
+            active: false,
+        };
+        assert!(matches!(
+            deactivate.clone().apply(&users, &tenant).await.unwrap(),
+            Applied::Updated(DbUser {
+                status: UserStatus::Deactivated,
+                ..
+            })
+        ));
+        assert_eq!(
+            deactivate.apply(&users, &tenant).await.unwrap(),
+            Applied::Unchanged
+        );
+
+        let suspend = UserCommand::SetStatus {
+            id: ana.id,
+            status: UserStatus::Suspended,
+        };
+        assert!(matches!(
+            suspend.apply(&users, &tenant).await,
+            Err(CommandError::Invalid(_))
+        ));
+
+        let delete = UserCommand::Delete { id: ana.id };
+        assert_eq!(
+            delete.clone().apply(&users, &tenant).await.unwrap(),
+            Applied::Deleted(ana.id)
+        );
+        assert_eq!(
+            delete.apply(&users, &tenant).await.unwrap(),
+            Applied::Unchanged
+        );
+    }
+
+    /// Repositório que sempre falha, como um banco fora do ar
+    struct Unavailable;
+
+    #[async_trait]
+    impl UserRepository for Unavailable {
+        async fn create(&self, _: &TenantContext, _: &str, _: &str) -> Result<DbUser> {
+            anyhow::bail!("connection refused")
+        }
+        async fn create_or_get(
+            &self,
+            _: &TenantContext,
+            _: &str,
+            _: &str,
+        ) -> Result<(DbUser, bool)> {
+            anyhow::bail!("connection refused")
+        }
+        async fn find_by_id(&self, _: &TenantContext, _: i32) -> Result<Option<DbUser>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn find_by_email(&self, _: &TenantContext, _: &str) -> Result<Option<DbUser>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn list_all(&self, _: &TenantContext) -> Result<Vec<DbUser>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn list_page(&self, _: &TenantContext, _: i64, _: i64) -> Result<Vec<DbUser>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn list_page_fields(
+            &self,
+            _: &TenantContext,
+            _: &[crate::models::UserField],
+            _: i64,
+            _: i64,
+        ) -> Result<Vec<crate::models::UserProjection>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn update(&self, _: &TenantContext, _: &DbUser) -> Result<(), DbError> {
+            Err(anyhow::anyhow!("connection refused").into())
+        }
+        async fn delete(&self, _: &TenantContext, _: i32) -> Result<(), DbError> {
+            Err(anyhow::anyhow!("connection refused").into())
+        }
+        async fn anonymize(&self, _: &TenantContext, _: i32) -> Result<Option<DbUser>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn schedule_deletion(
+            &self,
+            _: &TenantContext,
+            _: i32,
+            _: chrono::DateTime<chrono::Utc>,
+        ) -> Result<Option<DbUser>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn cancel_deletion(&self, _: &TenantContext, _: i32) -> Result<Option<DbUser>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn purge_deletions(
+            &self,
+            _: chrono::DateTime<chrono::Utc>,
+        ) -> Result<Vec<(TenantContext, i32)>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn cleanup_inactive(
+            &self,
+            _: chrono::DateTime<chrono::Utc>,
+            _: crate::config::InactiveUserAction,
+        ) -> Result<Vec<(TenantContext, DbUser)>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn count(&self, _: &TenantContext) -> Result<i64> {
+            anyhow::bail!("connection refused")
+        }
+        async fn set_password_hash(&self, _: &TenantContext, _: i32, _: &str) -> Result<bool> {
+            anyhow::bail!("connection refused")
+        }
+        async fn password_hash(&self, _: &TenantContext, _: i32) -> Result<Option<String>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn record_login(
+            &self,
+            _: &TenantContext,
+            _: &crate::models::LoginEvent,
+        ) -> Result<()> {
+            anyhow::bail!("connection refused")
+        }
+        async fn logins(
+            &self,
+            _: &TenantContext,
+            _: i32,
+            _: i64,
+        ) -> Result<Vec<crate::models::LoginEvent>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn two_factor(
+            &self,
+            _: &TenantContext,
+            _: i32,
+        ) -> Result<Option<crate::models::TwoFactor>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn set_two_factor_secret(
+            &self,
+            _: &TenantContext,
+            _: i32,
+            _: Option<&str>,
+        ) -> Result<bool> {
+            anyhow::bail!("connection refused")
+        }
+        async fn enable_two_factor(&self, _: &TenantContext, _: i32, _: &[String]) -> Result<bool> {
+            anyhow::bail!("connection refused")
+        }
+        async fn consume_backup_code(&self, _: &TenantContext, _: i32, _: &str) -> Result<bool> {
+            anyhow::bail!("connection refused")
+        }
+        async fn accept_totp_step(&self, _: &TenantContext, _: i32, _: i64) -> Result<bool> {
+            anyhow::bail!("connection refused")
+        }
+        async fn record_two_factor_failure(
+            &self,
+            _: &TenantContext,
+            _: i32,
+            _: i32,
+            _: chrono::DateTime<chrono::Utc>,
+        ) -> Result<bool> {
+            anyhow::bail!("connection refused")
+        }
+        async fn reset_two_factor_failures(&self, _: &TenantContext, _: i32) -> Result<()> {
+            anyhow::bail!("connection refused")
+        }
+        async fn passkeys(
+            &self,
+            _: &TenantContext,
+            _: i32,
+        ) -> Result<Vec<crate::models::StoredPasskey>> {
+            anyhow::bail!("connection refused")
+        }
+        async fn add_passkey(
+            &self,
+            _: &TenantContext,
+            _: i32,
+            _: &crate::models::StoredPasskey,
+        ) -> Result<bool> {
+            anyhow::bail!("connection refused")
+        }
+        async fn update_passkey(
+            &self,
+            _: &TenantContext,
+            _: i32,
+            _: &str,
+            _: &str,
+            _: chrono::DateTime<chrono::Utc>,
+        ) -> Result<bool> {
+            anyhow::bail!("connection refused")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_dispositions() {
+        let config = QueueConfig {
+            max_deliver: 3,
+            retry_delay_ms: 100,
+            ..Default::default()
+        };
+        let users = InMemoryUserRepository::new();
+        let tenant = TenantContext::default_tenant();
+        let delete = br#"{"op": "delete", "id": 1}"#;
+
+        assert_eq!(
+            handle(&users, &tenant, delete, 1, &config).await,
+            Disposition::Ack
+        );
+        assert!(matches!(
+            handle(&users, &tenant, b"{}", 1, &config).await,
+            Disposition::DeadLetter(_)
+        ));
+
+        assert_eq!(
+            handle(&Unavailable, &tenant, delete, 1, &config).await,
+            Disposition::Retry(Duration::from_millis(100))
+        );
+        assert_eq!(
+            handle(&Unavailable, &tenant, delete, 2, &config).await,
+            Disposition::Retry(Duration::from_millis(200))
+        );
+        assert!(matches!(
+            handle(&Unavailable, &tenant, delete, 3, &config).await,
+            Disposition::DeadLetter(reason) if reason.contains("connection refused")
+        ));
+    }
+
+    #[test]
+    fn test_retry_delay_is_capped() {
+        let config = QueueConfig::default();
+        assert_eq!(retry_delay(&config, 1), Duration::from_secs(1));
+        assert_eq!(retry_delay(&config, 4), Duration::from_secs(8));
+        assert_eq!(retry_delay(&config, 40), Duration::from_secs(300));
+    }
+}
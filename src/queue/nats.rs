@@ -0,0 +1,278 @@
+//! Transporte NATS JetStream
+//!
+//! Cria (se preciso) o stream e o consumidor durável, puxa mensagens em
+//! lotes de `prefetch` e processa até `prefetch` delas ao mesmo tempo. Cada
+//! mensagem é confirmada (`ack`), reentregue com espera (`nak`) ou publicada
+//! no dead letter e descartada (`term`), conforme a `Disposition`. O tenant
+//! vem do header `X-Tenant-Id` de cada mensagem; sem ele, vale o tenant
+//! passado a `run`.
+//!
+//! `EventPublisher` faz o caminho inverso: publica os eventos de domínio (via
+//! outbox) em `<events_subject>.<evento>`.
+
+use super::{handle, Disposition};
+use crate::config::QueueConfig;
+use crate::events::Event;
+use crate::repository::UserRepository;
+use crate::tenant::TenantContext;
+use crate::trace_context::{TraceContext, TRACEPARENT_HEADER, TRACESTATE_HEADER};
+use anyhow::{Context, Result};
+use async_nats::jetstream::{self, consumer::pull, consumer::AckPolicy, context::Publish, AckKind};
+use futures_util::StreamExt;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+
+/// Tenant ao qual o comando se aplica
+pub const TENANT_HEADER: &str = "X-Tenant-Id";
+/// Motivo da falha, nas mensagens do dead letter
+pub const ERROR_HEADER: &str = "X-Error";
+/// Assunto original, nas mensagens do dead letter
+pub const SUBJECT_HEADER: &str = "X-Original-Subject";
+/// Número de entregas até a desistência, nas mensagens do dead letter
+pub const DELIVERIES_HEADER: &str = "X-Deliveries";
+
+/// Consome os comandos até `shutdown` completar
+///
+/// No desligamento, para de puxar mensagens e espera as que estão em
+/// processamento; as já recebidas e não iniciadas são reentregues pelo
+/// servidor.
+pub async fn run(
+    config: &QueueConfig,
+    users: Arc<dyn UserRepository>,
+    default_tenant: TenantContext,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let client = async_nats::connect(&config.url)
+        .await
+        .with_context(|| format!("failed to connect to NATS at {}", config.url))?;
+    let context = jetstream::new(client.clone());
+
+    let stream = context
+        .get_or_create_stream(jetstream::stream::Config {
+            name: config.stream.clone(),
+            subjects: vec![config.subject.clone()],
+            ..Default::default()
+        })
+        .await
+        .with_context(|| format!("failed to set up stream {}", config.stream))?;
+
+    let prefetch = config.prefetch.max(1);
+    let consumer = stream
+        .get_or_create_consumer(
+            &config.durable,
+            pull::Config {
+                durable_name: Some(config.durable.clone()),
+                filter_subject: config.subject.clone(),
+                ack_policy: AckPolicy::Explicit,
+                max_ack_pending: prefetch as i64,
+                max_deliver: i64::from(config.max_deliver.max(1)),
+                ..Default::default()
+            },
+        )
+        .await
+        .with_context(|| format!("failed to set up consumer {}", config.durable))?;
+
+    let mut messages = consumer
+        .stream()
+        .max_messages_per_batch(prefetch)
+        .messages()
+        .await?;
+
+    tracing::info!(
+        subject = %config.subject,
+        durable = %config.durable,
+        prefetch,
+        "Consuming queue"
+    );
+
+    let permits = Arc::new(Semaphore::new(prefetch));
+    let mut tasks = JoinSet::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        let permit = tokio::select! {
+            _ = &mut shutdown => break,
+            permit = Arc::clone(&permits).acquire_owned() => {
+                permit.expect("semaphore is never closed")
+            }
+        };
+        let message = tokio::select! {
+            _ = &mut shutdown => break,
+            message = messages.next() => message,
+        };
+
+        match message {
+            Some(Ok(message)) => {
+                tasks.spawn(process(
+                    message,
+                    Arc::clone(&users),
+                    default_tenant.clone(),
+                    client.clone(),
+                    config.clone(),
+                    permit,
+                ));
+            }
+            Some(Err(e)) => tracing::warn!(error = %e, "Failed to receive message"),
+            None => anyhow::bail!("message stream ended"),
+        }
+
+        while let Some(result) = tasks.try_join_next() {
+            log_panic(result);
+        }
+    }
+
+    tracing::info!(
+        in_flight = tasks.len(),
+        "Shutting down consumer; waiting for in-flight messages"
+    );
+    while let Some(result) = tasks.join_next().await {
+        log_panic(result);
+    }
+    // Garante que os últimos acks e dead letters saíram
+    client.flush().await?;
+
+    Ok(())
+}
+
+async fn process(
+    message: jetstream::Message,
+    users: Arc<dyn UserRepository>,
+    default_tenant: TenantContext,
+    client: async_nats::Client,
+    config: QueueConfig,
+    _permit: OwnedSemaphorePermit,
+) {
+    let delivered = message
+        .info()
+        .map(|info| info.delivered.max(1) as u32)
+        .unwrap_or(1);
+
+    let tenant = match message.headers.as_ref().and_then(|h| h.get(TENANT_HEADER)) {
+        Some(value) => TenantContext::new(value.as_str()),
+        None => Ok(default_tenant),
+    };
+    // O trace de quem publicou a mensagem segue nos eventos gerados por ela
+    let header = |name: &str| {
+        message
+            .headers
+            .as_ref()
+            .and_then(|h| h.get(name))
+            .map(|v| v.as_str())
+    };
+    let trace = header(TRACEPARENT_HEADER)
+        .and_then(|traceparent| TraceContext::parse(traceparent, header(TRACESTATE_HEADER)))
+        .map(|trace| trace.child())
+        .unwrap_or_else(TraceContext::new_root);
+
+    let disposition = match tenant {
+        Ok(tenant) => {
+            trace
+                .scope(handle(
+                    users.as_ref(),
+                    &tenant,
+                    &message.payload,
+                    delivered,
+                    &config,
+                ))
+                .await
+        }
+        Err(e) => Disposition::DeadLetter(e.to_string()),
+    };
+
+    let acked = match disposition {
+        Disposition::Ack => message.ack().await,
+        Disposition::Retry(delay) => message.ack_with(AckKind::Nak(Some(delay))).await,
+        Disposition::DeadLetter(reason) => {
+            tracing::error!(
+                reason,
+                subject = %message.subject,
+                delivered,
+                "Moving message to dead letter"
+            );
+
+            let mut headers = async_nats::HeaderMap::new();
+            headers.insert(ERROR_HEADER, reason.as_str());
+            headers.insert(SUBJECT_HEADER, message.subject.as_str());
+            headers.insert(DELIVERIES_HEADER, delivered.to_string());
+
+            match client
+                .publish_with_headers(
+                    config.dead_letter_subject.clone(),
+                    headers,
+                    message.payload.clone(),
+                )
+                .await
+            {
+                Ok(()) => message.ack_with(AckKind::Term).await,
+                // Sem dead letter, melhor reentregar que perder a mensagem
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to publish dead letter");
+                    message.ack_with(AckKind::Nak(None)).await
+                }
+            }
+        }
+    };
+
+    if let Err(e) = acked {
+        tracing::warn!(error = %e, subject = %message.subject, "Failed to acknowledge message");
+    }
+}
+
+fn log_panic(result: Result<(), tokio::task::JoinError>) {
+    if let Err(e) = result {
+        tracing::error!(error = %e, "Queue message handler panicked");
+    }
+}
+
+/// Publica eventos de domínio no JetStream
+#[derive(Clone)]
+pub struct EventPublisher {
+    context: jetstream::Context,
+    subject: String,
+}
+
+impl EventPublisher {
+    /// Conecta e cria o stream dos eventos, se não existir
+    pub async fn connect(config: &QueueConfig) -> Result<Self> {
+        let client = async_nats::connect(&config.url)
+            .await
+            .with_context(|| format!("failed to connect to NATS at {}", config.url))?;
+        let context = jetstream::new(client);
+
+        context
+            .get_or_create_stream(jetstream::stream::Config {
+                name: config.events_stream.clone(),
+                subjects: vec![format!("{}.>", config.events_subject)],
+                ..Default::default()
+            })
+            .await
+            .with_context(|| format!("failed to set up stream {}", config.events_stream))?;
+
+        Ok(Self {
+            context,
+            subject: config.events_subject.clone(),
+        })
+    }
+
+    /// Publica e espera a confirmação do servidor
+    ///
+    /// O ID do evento vai como `Nats-Msg-Id`, então republicar o mesmo evento
+    /// (ex.: o relay do outbox tentando de novo) não gera duplicatas.
+    pub async fn publish(&self, event: &Event) -> Result<()> {
+        let subject = format!("{}.{}", self.subject, event.payload.name());
+        let payload = serde_json::to_vec(event)?;
+
+        let mut publish = Publish::build()
+            .payload(payload.into())
+            .message_id(event.id.to_string());
+        for (name, value) in event.trace.iter().flat_map(|t| t.outgoing_headers()) {
+            publish = publish.header(name, value.as_str());
+        }
+
+        self.context.send_publish(subject, publish).await?.await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+//! `QuotaStore` em memória, para testes e uso sem banco
+
+use super::{QuotaStore, Resource};
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+type UsageKey = (String, Resource, DateTime<Utc>);
+
+#[derive(Debug, Default)]
+struct State {
+    limits: HashMap<(String, Resource), u32>,
+    usage: HashMap<UsageKey, u32>,
+}
+
+/// Limites e uso na memória do processo
+#[derive(Debug, Default)]
+pub struct InMemoryQuotaStore {
+    state: Mutex<State>,
+}
+
+impl InMemoryQuotaStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define o limite próprio do tenant (a linha de `quotas`)
+    pub fn set_limit(&self, tenant: &TenantContext, resource: Resource, limit: u32) {
+        self.lock()
+            .limits
+            .insert((tenant.id().to_string(), resource), limit);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn usage_key(tenant: &TenantContext, resource: Resource, period_start: DateTime<Utc>) -> UsageKey {
+    (tenant.id().to_string(), resource, period_start)
+}
+
+#[async_trait]
+impl QuotaStore for InMemoryQuotaStore {
+    async fn limit(&self, tenant: &TenantContext, resource: Resource) -> Result<Option<u32>> {
+        Ok(self
+            .lock()
+            .limits
+            .get(&(tenant.id().to_string(), resource))
+            .copied())
+    }
+
+    async fn consume(
+        &self,
+        tenant: &TenantContext,
+        resource: Resource,
+        period_start: DateTime<Utc>,
+        limit: Option<u32>,
+    ) -> Result<Option<u32>> {
+        let mut state = self.lock();
+        let used = state
+            .usage
+            .entry(usage_key(tenant, resource, period_start))
+            .or_default();
+        if limit.is_some_and(|limit| *used >= limit) {
+            return Ok(None);
+        }
+        *used += 1;
+        Ok(Some(*used))
+    }
+
+    async fn release(
+        &self,
+        tenant: &TenantContext,
+        resource: Resource,
+        period_start: DateTime<Utc>,
+    ) -> Result<()> {
+        if let Some(used) = self
+            .lock()
+            .usage
+            .get_mut(&usage_key(tenant, resource, period_start))
+        {
+            *used = used.saturating_sub(1);
+        }
+        Ok(())
+    }
+
+    async fn used(
+        &self,
+        tenant: &TenantContext,
+        resource: Resource,
+        period_start: DateTime<Utc>,
+    ) -> Result<u32> {
+        Ok(self
+            .lock()
+            .usage
+            .get(&usage_key(tenant, resource, period_start))
+            .copied()
+            .unwrap_or(0))
+    }
+}
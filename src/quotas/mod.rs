@@ -0,0 +1,288 @@
+//! Cotas mensais por tenant (feature "api")
+//!
+//! Operações caras (criar usuários, exportar) consomem uma unidade da cota
+//! do tenant no mês corrente (UTC) antes de executar; estourada a cota, a
+//! API responde 429 com o uso em `details.quota`. O limite vem da tabela
+//! `quotas` (por tenant) ou, sem linha, de `[quotas]` na configuração; sem
+//! nenhum dos dois, o recurso é ilimitado, mas o uso continua contado.
+
+use crate::config::QuotaConfig;
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+
+pub mod memory;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+pub use memory::InMemoryQuotaStore;
+#[cfg(feature = "postgres")]
+pub use postgres::PgQuotaStore;
+
+/// O que as cotas limitam
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    /// Usuários criados (`POST /api/users`)
+    UsersCreated,
+    /// Exportações iniciadas (`POST /api/exports`)
+    Exports,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 2] = [Resource::UsersCreated, Resource::Exports];
+
+    /// Nome na coluna `resource` das tabelas
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UsersCreated => "users_created",
+            Self::Exports => "exports",
+        }
+    }
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Uso de uma cota no período corrente
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuotaUsage {
+    pub resource: Resource,
+    /// `None`: ilimitado
+    pub limit: Option<u32>,
+    pub used: u32,
+    pub period_start: DateTime<Utc>,
+    /// Quando o uso volta a zero
+    pub resets_at: DateTime<Utc>,
+}
+
+impl QuotaUsage {
+    /// Quanto ainda pode ser consumido (`None`: ilimitado)
+    pub fn remaining(&self) -> Option<u32> {
+        self.limit.map(|limit| limit.saturating_sub(self.used))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum QuotaError {
+    #[error("Quota exceeded for {}", .0.resource)]
+    Exceeded(QuotaUsage),
+    #[error(transparent)]
+    Store(#[from] anyhow::Error),
+}
+
+/// Armazenamento dos limites e do uso
+#[async_trait]
+pub trait QuotaStore: Send + Sync {
+    /// Limite próprio do tenant, se houver
+    async fn limit(&self, tenant: &TenantContext, resource: Resource) -> Result<Option<u32>>;
+
+    /// Consome uma unidade no período se o uso ainda estiver abaixo de
+    /// `limit`; devolve o uso novo, ou `None` se a cota já estava esgotada
+    async fn consume(
+        &self,
+        tenant: &TenantContext,
+        resource: Resource,
+        period_start: DateTime<Utc>,
+        limit: Option<u32>,
+    ) -> Result<Option<u32>>;
+
+    /// Devolve uma unidade consumida no período
+    async fn release(
+        &self,
+        tenant: &TenantContext,
+        resource: Resource,
+        period_start: DateTime<Utc>,
+    ) -> Result<()>;
+
+    /// Uso no período
+    async fn used(
+        &self,
+        tenant: &TenantContext,
+        resource: Resource,
+        period_start: DateTime<Utc>,
+    ) -> Result<u32>;
+}
+
+/// Início do mês (UTC) de `now` e o início do mês seguinte
+pub fn month_of(now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = Utc
+        .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .unwrap();
+    let (year, month) = match now.month() {
+        12 => (now.year() + 1, 1),
+        month => (now.year(), month + 1),
+    };
+    let next = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap();
+    (start, next)
+}
+
+/// Cotas consultadas pelos handlers (clonável; os clones compartilham o
+/// armazenamento)
+#[derive(Clone)]
+pub struct QuotaService {
+    pub store: Arc<dyn QuotaStore>,
+    /// Limites dos tenants sem linha em `quotas`
+    pub defaults: QuotaConfig,
+}
+
+impl QuotaService {
+    pub fn new(store: Arc<dyn QuotaStore>) -> Self {
+        Self {
+            store,
+            defaults: QuotaConfig::default(),
+        }
+    }
+
+    pub fn with_defaults(self, defaults: QuotaConfig) -> Self {
+        Self { defaults, ..self }
+    }
+
+    /// Limite do tenant: o próprio ou o padrão da configuração
+    pub async fn limit(&self, tenant: &TenantContext, resource: Resource) -> Result<Option<u32>> {
+        Ok(self
+            .store
+            .limit(tenant, resource)
+            .await?
+            .or(self.defaults.limit(resource)))
+    }
+
+    /// Consome uma unidade da cota do mês corrente; com a cota esgotada,
+    /// devolve `QuotaError::Exceeded` com o uso
+    pub async fn consume(
+        &self,
+        tenant: &TenantContext,
+        resource: Resource,
+    ) -> Result<QuotaUsage, QuotaError> {
+        let (period_start, resets_at) = month_of(Utc::now());
+        let limit = self.limit(tenant, resource).await?;
+        let consumed = match limit {
+            Some(0) => None,
+            _ => {
+                self.store
+                    .consume(tenant, resource, period_start, limit)
+                    .await?
+            }
+        };
+
+        match consumed {
+            Some(used) => Ok(QuotaUsage {
+                resource,
+                limit,
+                used,
+                period_start,
+                resets_at,
+            }),
+            None => {
+                let used = self.store.used(tenant, resource, period_start).await?;
+                Err(QuotaError::Exceeded(QuotaUsage {
+                    resource,
+                    limit,
+                    used,
+                    period_start,
+                    resets_at,
+                }))
+            }
+        }
+    }
+
+    /// Devolve a unidade de um `consume` cuja operação falhou
+    pub async fn release(&self, tenant: &TenantContext, usage: &QuotaUsage) {
+        if let Err(e) = self
+            .store
+            .release(tenant, usage.resource, usage.period_start)
+            .await
+        {
+            tracing::warn!(
+                tenant = tenant.id(),
+                resource = %usage.resource,
+                error = %e,
+                "Failed to release quota"
+            );
+        }
+    }
+
+    /// Uso de todas as cotas do tenant no mês corrente
+    pub async fn usage(&self, tenant: &TenantContext) -> Result<Vec<QuotaUsage>> {
+        let (period_start, resets_at) = month_of(Utc::now());
+        let mut usage = Vec::with_capacity(Resource::ALL.len());
+        for resource in Resource::ALL {
+            usage.push(QuotaUsage {
+                resource,
+                limit: self.limit(tenant, resource).await?,
+                used: self.store.used(tenant, resource, period_start).await?,
+                period_start,
+                resets_at,
+            });
+        }
+        Ok(usage)
+    }
+}
+
+impl Default for QuotaService {
+    fn default() -> Self {
+        Self::new(Arc::new(InMemoryQuotaStore::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_of() {
+        let (start, next) = month_of(Utc.with_ymd_and_hms(2024, 12, 15, 10, 30, 0).unwrap());
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 12, 1, 0, 0, 0).unwrap());
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_consume_until_exceeded() {
+        let store = Arc::new(InMemoryQuotaStore::new());
+        let quotas = QuotaService::new(store.clone()).with_defaults(QuotaConfig {
+            users_created_per_month: Some(2),
+            exports_per_month: Some(0),
+        });
+        let tenant = TenantContext::default_tenant();
+
+        let first = quotas
+            .consume(&tenant, Resource::UsersCreated)
+            .await
+            .unwrap();
+        assert_eq!((first.used, first.remaining()), (1, Some(1)));
+        quotas.release(&tenant, &first).await;
+        for used in 1..=2 {
+            let usage = quotas
+                .consume(&tenant, Resource::UsersCreated)
+                .await
+                .unwrap();
+            assert_eq!(usage.used, used);
+        }
+        match quotas.consume(&tenant, Resource::UsersCreated).await {
+            Err(QuotaError::Exceeded(usage)) => {
+                assert_eq!((usage.used, usage.limit), (2, Some(2)));
+            }
+            other => panic!("expected exceeded, got {:?}", other),
+        }
+        assert!(matches!(
+            quotas.consume(&tenant, Resource::Exports).await,
+            Err(QuotaError::Exceeded(_))
+        ));
+
+        // O limite próprio do tenant vale sobre o padrão
+        let other = TenantContext::new("other").unwrap();
+        store.set_limit(&other, Resource::UsersCreated, 5);
+        assert!(quotas.consume(&other, Resource::UsersCreated).await.is_ok());
+        let usage = quotas.usage(&other).await.unwrap();
+        assert_eq!(usage[0].limit, Some(5));
+        assert_eq!(usage[0].used, 1);
+        assert_eq!(usage[1].limit, Some(0));
+    }
+}
@@ -0,0 +1,118 @@
+//! `QuotaStore` sobre o Postgres (tabelas `quotas` e `quota_usage`)
+
+use super::{QuotaStore, Resource};
+use crate::db::timed;
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+#[derive(Debug, Clone)]
+pub struct PgQuotaStore {
+    pool: PgPool,
+}
+
+impl PgQuotaStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl QuotaStore for PgQuotaStore {
+    async fn limit(&self, tenant: &TenantContext, resource: Resource) -> Result<Option<u32>> {
+        let limit = timed(
+            "quotas.limit",
+            sqlx::query_scalar!(
+                "SELECT monthly_limit FROM quotas WHERE tenant_id = $1 AND resource = $2",
+                tenant.id(),
+                resource.as_str()
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await?;
+
+        Ok(limit.map(|limit| limit.max(0) as u32))
+    }
+
+    async fn consume(
+        &self,
+        tenant: &TenantContext,
+        resource: Resource,
+        period_start: DateTime<Utc>,
+        limit: Option<u32>,
+    ) -> Result<Option<u32>> {
+        // O teste do limite e o incremento numa instrução só: requisições
+        // concorrentes não passam juntas do limite
+        let used = timed(
+            "quotas.consume",
+            sqlx::query_scalar!(
+                r#"
+                INSERT INTO quota_usage (tenant_id, resource, period_start, used)
+                VALUES ($1, $2, $3, 1)
+                ON CONFLICT (tenant_id, resource, period_start)
+                DO UPDATE SET used = quota_usage.used + 1
+                WHERE $4::BIGINT IS NULL OR quota_usage.used < $4
+                RETURNING used
+                "#,
+                tenant.id(),
+                resource.as_str(),
+                period_start,
+                limit.map(i64::from)
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await?;
+
+        Ok(used.map(|used| used as u32))
+    }
+
+    async fn release(
+        &self,
+        tenant: &TenantContext,
+        resource: Resource,
+        period_start: DateTime<Utc>,
+    ) -> Result<()> {
+        timed(
+            "quotas.release",
+            sqlx::query!(
+                r#"
+                UPDATE quota_usage SET used = used - 1
+                WHERE tenant_id = $1 AND resource = $2 AND period_start = $3 AND used > 0
+                "#,
+                tenant.id(),
+                resource.as_str(),
+                period_start
+            )
+            .execute(&self.pool),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn used(
+        &self,
+        tenant: &TenantContext,
+        resource: Resource,
+        period_start: DateTime<Utc>,
+    ) -> Result<u32> {
+        let used = timed(
+            "quotas.used",
+            sqlx::query_scalar!(
+                r#"
+                SELECT used FROM quota_usage
+                WHERE tenant_id = $1 AND resource = $2 AND period_start = $3
+                "#,
+                tenant.id(),
+                resource.as_str(),
+                period_start
+            )
+            .fetch_optional(&self.pool),
+        )
+        .await?;
+
+        Ok(used.unwrap_or(0) as u32)
+    }
+}
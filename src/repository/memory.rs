@@ -0,0 +1,1203 @@
+//! `UserRepository` em memória, para testes e uso sem banco
+//!
+//! Aberto com `InMemoryUserRepository::load`, o repositório fica ligado a
+//! um arquivo JSON: as alterações ficam só em memória até o `flush` (que
+//! grava num arquivo temporário e o renomeia, para nunca deixar o JSON pela
+//! metade), feito periodicamente por `spawn_flusher` e, por garantia, ao
+//! descartar o repositório.
+
+use super::{DbError, UserRepository};
+use crate::config::InactiveUserAction;
+use crate::models::{
+    DbUser, LoginEvent, StoredPasskey, TwoFactor, UserField, UserProjection, UserStatus,
+    DEFAULT_ROLE,
+};
+use crate::tenant::TenantContext;
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::RwLock;
+
+/// Repositório de usuários em um `HashMap` protegido por `RwLock`
+///
+/// Reproduz as regras da tabela `users`: IDs sequenciais a partir de 1
+/// (compartilhados entre os tenants) e email único dentro de cada tenant.
+#[derive(Debug)]
+pub struct InMemoryUserRepository {
+    users: RwLock<HashMap<i32, Stored>>,
+    next_id: AtomicI32,
+    /// Arquivo do `load`, se houver
+    path: Option<PathBuf>,
+    /// Alterado desde o último `flush`
+    dirty: AtomicBool,
+}
+
+/// Um usuário e o tenant dono dele
+#[derive(Debug, Clone)]
+struct Stored {
+    tenant: TenantContext,
+    user: DbUser,
+    /// Última alteração (a coluna `updated_at`)
+    updated_at: DateTime<Utc>,
+    password_hash: Option<String>,
+    /// Tentativas de login, da mais antiga para a mais nova
+    logins: Vec<LoginEvent>,
+    two_factor: TwoFactor,
+    /// Hashes dos códigos de recuperação ainda não usados
+    backup_codes: Vec<String>,
+    passkeys: Vec<StoredPasskey>,
+}
+
+/// Conteúdo do arquivo
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    next_id: i32,
+    users: Vec<Record>,
+}
+
+/// Um usuário no arquivo, com tudo o que o `Stored` guarda
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+    tenant: String,
+    user: DbUser,
+    updated_at: DateTime<Utc>,
+    #[serde(default)]
+    password_hash: Option<String>,
+    #[serde(default)]
+    logins: Vec<LoginEvent>,
+    #[serde(default)]
+    two_factor: TwoFactor,
+    #[serde(default)]
+    backup_codes: Vec<String>,
+    #[serde(default)]
+    passkeys: Vec<PasskeyRecord>,
+}
+
+/// Passkey no arquivo: a credencial não sai no JSON do `StoredPasskey`
+#[derive(Debug, Serialize, Deserialize)]
+struct PasskeyRecord {
+    #[serde(flatten)]
+    info: StoredPasskey,
+    passkey: String,
+}
+
+impl From<&Stored> for Record {
+    fn from(stored: &Stored) -> Self {
+        Self {
+            tenant: stored.tenant.id().to_string(),
+            user: stored.user.clone(),
+            updated_at: stored.updated_at,
+            password_hash: stored.password_hash.clone(),
+            logins: stored.logins.clone(),
+            two_factor: stored.two_factor.clone(),
+            backup_codes: stored.backup_codes.clone(),
+            passkeys: stored
+                .passkeys
+                .iter()
+                .map(|passkey| PasskeyRecord {
+                    info: passkey.clone(),
+                    passkey: passkey.passkey.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<Record> for Stored {
+    type Error = anyhow::Error;
+
+    fn try_from(record: Record) -> Result<Self> {
+        Ok(Self {
+            tenant: TenantContext::new(&record.tenant)?,
+            user: record.user,
+            updated_at: record.updated_at,
+            password_hash: record.password_hash,
+            logins: record.logins,
+            two_factor: record.two_factor,
+            backup_codes: record.backup_codes,
+            passkeys: record
+                .passkeys
+                .into_iter()
+                .map(|record| StoredPasskey {
+                    passkey: record.passkey,
+                    ..record.info
+                })
+                .collect(),
+        })
+    }
+}
+
+impl Default for InMemoryUserRepository {
+    fn default() -> Self {
+        Self {
+            users: RwLock::new(HashMap::new()),
+            next_id: AtomicI32::new(1),
+            path: None,
+            dirty: AtomicBool::new(false),
+        }
+    }
+}
+
+impl InMemoryUserRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cria o repositório já populado, no tenant padrão; os IDs seguintes
+    /// continuam após o maior
+    pub fn with_users(users: impl IntoIterator<Item = DbUser>) -> Self {
+        let tenant = TenantContext::default_tenant();
+        let users: HashMap<i32, Stored> = users
+            .into_iter()
+            .map(|user| {
+                let updated_at = user
+                    .created_at
+                    .map(|at| at.and_utc())
+                    .unwrap_or_else(Utc::now);
+                let stored = Stored {
+                    tenant: tenant.clone(),
+                    user,
+                    updated_at,
+                    password_hash: None,
+                    logins: Vec::new(),
+                    two_factor: TwoFactor::default(),
+                    backup_codes: Vec::new(),
+                    passkeys: Vec::new(),
+                };
+                (stored.user.id, stored)
+            })
+            .collect();
+        Self::from_stored(users)
+    }
+
+    fn from_stored(users: HashMap<i32, Stored>) -> Self {
+        let next_id = users.keys().max().copied().unwrap_or(0) + 1;
+
+        Self {
+            users: RwLock::new(users),
+            next_id: AtomicI32::new(next_id),
+            path: None,
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Repositório do arquivo JSON em `path` (vazio, se ele ainda não
+    /// existe), gravado nele pelo `flush`
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let mut repo = match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                let invalid = || format!("invalid user store {}", path.display());
+                let snapshot: Snapshot = serde_json::from_str(&content).with_context(invalid)?;
+                let users = snapshot
+                    .users
+                    .into_iter()
+                    .map(|record| Ok((record.user.id, Stored::try_from(record)?)))
+                    .collect::<Result<HashMap<_, _>>>()
+                    .with_context(invalid)?;
+                let repo = Self::from_stored(users);
+                repo.next_id.fetch_max(snapshot.next_id, Ordering::Relaxed);
+                repo
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::new(),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+        };
+        repo.path = Some(path);
+        Ok(repo)
+    }
+
+    /// Arquivo do repositório, se ele veio do `load`
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Grava as alterações pendentes no arquivo; `false` se não havia
+    /// nada a gravar (ou o repositório não tem arquivo)
+    pub fn flush(&self) -> Result<bool> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(false);
+        }
+
+        let result = self.write_to(path);
+        if result.is_err() {
+            self.dirty.store(true, Ordering::Release);
+        }
+        result.map(|()| true)
+    }
+
+    fn write_to(&self, path: &Path) -> Result<()> {
+        let json = {
+            let users = self.read();
+            let mut records: Vec<Record> = users.values().map(Record::from).collect();
+            records.sort_by_key(|record| record.user.id);
+            serde_json::to_vec_pretty(&Snapshot {
+                next_id: self.next_id.load(Ordering::Relaxed),
+                users: records,
+            })?
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json).with_context(|| format!("failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("failed to replace {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Thread que faz o `flush` a cada `interval`, até o repositório ser
+    /// descartado
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn_flusher(
+        self: &std::sync::Arc<Self>,
+        interval: std::time::Duration,
+    ) -> std::thread::JoinHandle<()> {
+        let repo = std::sync::Arc::downgrade(self);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            let Some(repo) = repo.upgrade() else {
+                break;
+            };
+            if let Err(e) = repo.flush() {
+                tracing::warn!(error = %format!("{:#}", e), "Failed to flush user store");
+            }
+        })
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<i32, Stored>> {
+        self.users.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Acesso para alterar; marca o repositório para o próximo `flush`
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<i32, Stored>> {
+        let users = self.users.write().unwrap_or_else(|e| e.into_inner());
+        self.dirty.store(true, Ordering::Release);
+        users
+    }
+
+    /// Insere um usuário novo e ativo (o email já foi conferido)
+    fn insert(
+        &self,
+        users: &mut HashMap<i32, Stored>,
+        tenant: &TenantContext,
+        name: &str,
+        email: &str,
+    ) -> DbUser {
+        let user = DbUser {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            name: name.to_string(),
+            email: email.to_string(),
+            status: UserStatus::Active,
+            created_at: Some(chrono::Utc::now().naive_utc()),
+            deletion_scheduled_at: None,
+            last_login_at: None,
+            role: DEFAULT_ROLE.to_string(),
+        };
+        users.insert(
+            user.id,
+            Stored {
+                tenant: tenant.clone(),
+                user: user.clone(),
+                updated_at: Utc::now(),
+                password_hash: None,
+                logins: Vec::new(),
+                two_factor: TwoFactor::default(),
+                backup_codes: Vec::new(),
+                passkeys: Vec::new(),
+            },
+        );
+
+        user
+    }
+}
+
+/// Usuários do tenant
+fn of<'a>(
+    users: &'a HashMap<i32, Stored>,
+    tenant: &'a TenantContext,
+) -> impl Iterator<Item = &'a DbUser> {
+    users
+        .values()
+        .filter(move |s| s.tenant == *tenant)
+        .map(|s| &s.user)
+}
+
+/// O usuário `id`, se ele for do tenant
+fn get_mut<'a>(
+    users: &'a mut HashMap<i32, Stored>,
+    tenant: &TenantContext,
+    id: i32,
+) -> Option<&'a mut DbUser> {
+    users
+        .get_mut(&id)
+        .filter(|s| s.tenant == *tenant)
+        .map(|s| &mut s.user)
+}
+
+/// O registro do usuário `id`, se ele for do tenant
+fn stored_mut<'a>(
+    users: &'a mut HashMap<i32, Stored>,
+    tenant: &TenantContext,
+    id: i32,
+) -> Option<&'a mut Stored> {
+    users.get_mut(&id).filter(|s| s.tenant == *tenant)
+}
+
+/// Registra a alteração do usuário `id` agora
+fn touch(users: &mut HashMap<i32, Stored>, id: i32) {
+    if let Some(stored) = users.get_mut(&id) {
+        stored.updated_at = Utc::now();
+    }
+}
+
+fn email_taken(
+    users: &HashMap<i32, Stored>,
+    tenant: &TenantContext,
+    email: &str,
+    except: Option<i32>,
+) -> bool {
+    // Como o índice único do banco, sem diferenciar maiúsculas
+    let email = email.to_lowercase();
+    of(users, tenant).any(|u| u.email.to_lowercase() == email && Some(u.id) != except)
+}
+
+impl Drop for InMemoryUserRepository {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::warn!(error = %format!("{:#}", e), "Failed to flush user store");
+        }
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn create(&self, tenant: &TenantContext, name: &str, email: &str) -> Result<DbUser> {
+        let mut users = self.write();
+        if email_taken(&users, tenant, email, None) {
+            bail!("a user with email '{}' already exists", email);
+        }
+        Ok(self.insert(&mut users, tenant, name, email))
+    }
+
+    async fn create_or_get(
+        &self,
+        tenant: &TenantContext,
+        name: &str,
+        email: &str,
+    ) -> Result<(DbUser, bool)> {
+        let mut users = self.write();
+        if let Some(existing) =
+            of(&users, tenant).find(|u| u.email.to_lowercase() == email.to_lowercase())
+        {
+            return Ok((existing.clone(), false));
+        }
+        Ok((self.insert(&mut users, tenant, name, email), true))
+    }
+
+    async fn find_by_id(&self, tenant: &TenantContext, id: i32) -> Result<Option<DbUser>> {
+        Ok(of(&self.read(), tenant).find(|u| u.id == id).cloned())
+    }
+
+    async fn find_by_email(&self, tenant: &TenantContext, email: &str) -> Result<Option<DbUser>> {
+        let email = email.to_lowercase();
+        Ok(of(&self.read(), tenant)
+            .find(|u| u.email.to_lowercase() == email)
+            .cloned())
+    }
+
+    async fn list_all(&self, tenant: &TenantContext) -> Result<Vec<DbUser>> {
+        let mut users: Vec<DbUser> = of(&self.read(), tenant).cloned().collect();
+        users.sort_by_key(|u| u.id);
+        Ok(users)
+    }
+
+    async fn list_page(
+        &self,
+        tenant: &TenantContext,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<DbUser>> {
+        let users = self.list_all(tenant).await?;
+        let offset = usize::try_from(offset).unwrap_or(0);
+        let limit = usize::try_from(limit).unwrap_or(0);
+        Ok(users.into_iter().skip(offset).take(limit).collect())
+    }
+
+    async fn list_page_fields(
+        &self,
+        tenant: &TenantContext,
+        fields: &[UserField],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<UserProjection>> {
+        let users = self.list_page(tenant, limit, offset).await?;
+        Ok(users
+            .iter()
+            .map(|user| UserField::project(fields, user))
+            .collect())
+    }
+
+    async fn update(&self, tenant: &TenantContext, user: &DbUser) -> Result<(), DbError> {
+        let mut users = self.write();
+        let taken = email_taken(&users, tenant, &user.email, Some(user.id));
+
+        let Some(existing) = get_mut(&mut users, tenant, user.id) else {
+            return Err(DbError::NotFound { id: user.id });
+        };
+        if taken {
+            return Err(anyhow!("a user with email '{}' already exists", user.email).into());
+        }
+        existing.name = user.name.clone();
+        existing.email = user.email.clone();
+        existing.status = user.status;
+        touch(&mut users, user.id);
+
+        Ok(())
+    }
+
+    async fn delete(&self, tenant: &TenantContext, id: i32) -> Result<(), DbError> {
+        let mut users = self.write();
+        if get_mut(&mut users, tenant, id).is_none() {
+            return Err(DbError::NotFound { id });
+        }
+        users.remove(&id);
+        Ok(())
+    }
+
+    async fn anonymize(&self, tenant: &TenantContext, id: i32) -> Result<Option<DbUser>> {
+        let mut users = self.write();
+        let Some(user) = get_mut(&mut users, tenant, id) else {
+            return Ok(None);
+        };
+        if user.is_anonymized() {
+            return Ok(Some(user.clone()));
+        }
+        *user = user.anonymized();
+        let user = user.clone();
+        touch(&mut users, id);
+        Ok(Some(user))
+    }
+
+    async fn schedule_deletion(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        at: DateTime<Utc>,
+    ) -> Result<Option<DbUser>> {
+        let mut users = self.write();
+        Ok(get_mut(&mut users, tenant, id).map(|user| {
+            user.deletion_scheduled_at.get_or_insert(at);
+            user.clone()
+        }))
+    }
+
+    async fn cancel_deletion(&self, tenant: &TenantContext, id: i32) -> Result<Option<DbUser>> {
+        let mut users = self.write();
+        Ok(get_mut(&mut users, tenant, id).map(|user| {
+            user.deletion_scheduled_at = None;
+            user.clone()
+        }))
+    }
+
+    async fn purge_deletions(&self, now: DateTime<Utc>) -> Result<Vec<(TenantContext, i32)>> {
+        let mut users = self.write();
+        let mut due: Vec<(TenantContext, i32)> = users
+            .values()
+            .filter(|s| s.user.deletion_scheduled_at.is_some_and(|at| at <= now))
+            .map(|s| (s.tenant.clone(), s.user.id))
+            .collect();
+        due.sort_unstable_by_key(|(_, id)| *id);
+        for (_, id) in &due {
+            users.remove(id);
+        }
+        Ok(due)
+    }
+
+    async fn cleanup_inactive(
+        &self,
+        before: DateTime<Utc>,
+        action: InactiveUserAction,
+    ) -> Result<Vec<(TenantContext, DbUser)>> {
+        let mut users = self.write();
+        let now = Utc::now();
+        let mut affected: Vec<(TenantContext, DbUser)> = users
+            .values_mut()
+            .filter(|s| {
+                s.updated_at < before
+                    && match action {
+                        InactiveUserAction::Deactivate => s.user.status == UserStatus::Active,
+                        InactiveUserAction::Purge => s.user.status == UserStatus::Deactivated,
+                    }
+            })
+            .map(|s| {
+                if action == InactiveUserAction::Deactivate {
+                    s.user.status = UserStatus::Deactivated;
+                    s.updated_at = now;
+                }
+                (s.tenant.clone(), s.user.clone())
+            })
+            .collect();
+        affected.sort_by_key(|(_, u)| u.id);
+        if action == InactiveUserAction::Purge {
+            for (_, user) in &affected {
+                users.remove(&user.id);
+            }
+        }
+        Ok(affected)
+    }
+
+    async fn count(&self, tenant: &TenantContext) -> Result<i64> {
+        Ok(of(&self.read(), tenant).count() as i64)
+    }
+
+    async fn set_password_hash(&self, tenant: &TenantContext, id: i32, hash: &str) -> Result<bool> {
+        let mut users = self.write();
+        Ok(match stored_mut(&mut users, tenant, id) {
+            Some(stored) => {
+                stored.password_hash = Some(hash.to_string());
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn password_hash(&self, tenant: &TenantContext, id: i32) -> Result<Option<String>> {
+        Ok(self
+            .read()
+            .get(&id)
+            .filter(|s| s.tenant == *tenant)
+            .and_then(|s| s.password_hash.clone()))
+    }
+
+    async fn record_login(&self, tenant: &TenantContext, event: &LoginEvent) -> Result<()> {
+        let mut users = self.write();
+        if let Some(stored) = stored_mut(&mut users, tenant, event.user_id) {
+            if event.success {
+                stored.user.last_login_at = Some(event.occurred_at);
+            }
+            stored.logins.push(event.clone());
+        }
+        Ok(())
+    }
+
+    async fn logins(&self, tenant: &TenantContext, id: i32, limit: i64) -> Result<Vec<LoginEvent>> {
+        let limit = usize::try_from(limit).unwrap_or(0);
+        Ok(self
+            .read()
+            .get(&id)
+            .filter(|s| s.tenant == *tenant)
+            .map(|s| s.logins.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn two_factor(&self, tenant: &TenantContext, id: i32) -> Result<Option<TwoFactor>> {
+        Ok(self
+            .read()
+            .get(&id)
+            .filter(|s| s.tenant == *tenant)
+            .map(|s| s.two_factor.clone()))
+    }
+
+    async fn set_two_factor_secret(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        secret: Option<&str>,
+    ) -> Result<bool> {
+        let mut users = self.write();
+        Ok(match stored_mut(&mut users, tenant, id) {
+            Some(stored) => {
+                stored.two_factor.secret = secret.map(str::to_string);
+                stored.two_factor.enabled_at = None;
+                stored.backup_codes.clear();
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn enable_two_factor(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        backup_code_hashes: &[String],
+    ) -> Result<bool> {
+        let mut users = self.write();
+        Ok(match stored_mut(&mut users, tenant, id) {
+            Some(stored)
+                if stored.two_factor.secret.is_some() && stored.two_factor.enabled_at.is_none() =>
+            {
+                stored.two_factor.enabled_at = Some(Utc::now());
+                stored.backup_codes = backup_code_hashes.to_vec();
+                true
+            }
+            _ => false,
+        })
+    }
+
+    async fn consume_backup_code(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        code_hash: &str,
+    ) -> Result<bool> {
+        let mut users = self.write();
+        let Some(stored) = stored_mut(&mut users, tenant, id) else {
+            return Ok(false);
+        };
+        let before = stored.backup_codes.len();
+        stored.backup_codes.retain(|hash| hash != code_hash);
+        Ok(stored.backup_codes.len() < before)
+    }
+
+    async fn accept_totp_step(&self, tenant: &TenantContext, id: i32, step: i64) -> Result<bool> {
+        let mut users = self.write();
+        Ok(match stored_mut(&mut users, tenant, id) {
+            Some(stored) if stored.two_factor.last_step.is_none_or(|last| last < step) => {
+                stored.two_factor.last_step = Some(step);
+                true
+            }
+            _ => false,
+        })
+    }
+
+    async fn record_two_factor_failure(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        max_attempts: i32,
+        locked_until: DateTime<Utc>,
+    ) -> Result<bool> {
+        let mut users = self.write();
+        let Some(stored) = stored_mut(&mut users, tenant, id) else {
+            return Ok(false);
+        };
+        let two_factor = &mut stored.two_factor;
+        two_factor.failed_attempts += 1;
+        if two_factor.failed_attempts < max_attempts {
+            return Ok(false);
+        }
+        two_factor.failed_attempts = 0;
+        two_factor.locked_until = Some(locked_until);
+        Ok(true)
+    }
+
+    async fn reset_two_factor_failures(&self, tenant: &TenantContext, id: i32) -> Result<()> {
+        if let Some(stored) = stored_mut(&mut self.write(), tenant, id) {
+            stored.two_factor.failed_attempts = 0;
+        }
+        Ok(())
+    }
+
+    async fn passkeys(&self, tenant: &TenantContext, id: i32) -> Result<Vec<StoredPasskey>> {
+        Ok(self
+            .read()
+            .get(&id)
+            .filter(|s| s.tenant == *tenant)
+            .map(|s| s.passkeys.clone())
+            .unwrap_or_default())
+    }
+
+    async fn add_passkey(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        passkey: &StoredPasskey,
+    ) -> Result<bool> {
+        let mut users = self.write();
+        let taken = users.values().any(|s| {
+            s.passkeys
+                .iter()
+                .any(|p| p.credential_id == passkey.credential_id)
+        });
+        if taken {
+            bail!("passkey {} is already registered", passkey.credential_id);
+        }
+        Ok(match stored_mut(&mut users, tenant, id) {
+            Some(stored) => {
+                stored.passkeys.push(passkey.clone());
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn update_passkey(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        credential_id: &str,
+        passkey: &str,
+        used_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let mut users = self.write();
+        let stored = stored_mut(&mut users, tenant, id).and_then(|s| {
+            s.passkeys
+                .iter_mut()
+                .find(|p| p.credential_id == credential_id)
+        });
+        Ok(match stored {
+            Some(stored) => {
+                stored.passkey = passkey.to_string();
+                stored.last_used_at = Some(used_at);
+                true
+            }
+            None => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_crud() {
+        let tenant = TenantContext::default_tenant();
+        let repo = InMemoryUserRepository::new();
+
+        let ana = repo
+            .create(&tenant, "Ana", "ana@example.com")
+            .await
+            .unwrap();
+        let bia = repo
+            .create(&tenant, "Bia", "bia@example.com")
+            .await
+            .unwrap();
+        assert_eq!((ana.id, bia.id), (1, 2));
+        assert!(ana.is_active());
+        assert_eq!(repo.count(&tenant).await.unwrap(), 2);
+        assert_eq!(repo.get(&tenant, ana.id).await.unwrap(), ana);
+        let missing = repo.get(&tenant, 99).await.unwrap_err();
+        assert!(matches!(missing, DbError::NotFound { id: 99 }));
+        assert_eq!(missing.to_string(), "User with id 99 not found");
+
+        let mut found = repo
+            .find_by_email(&tenant, "bia@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        found.status = UserStatus::Suspended;
+        repo.update(&tenant, &found).await.unwrap();
+        assert_eq!(
+            repo.find_by_id(&tenant, bia.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            UserStatus::Suspended
+        );
+
+        repo.delete(&tenant, ana.id).await.unwrap();
+        assert!(matches!(
+            repo.delete(&tenant, ana.id).await,
+            Err(DbError::NotFound { id }) if id == ana.id
+        ));
+        let ids: Vec<i32> = repo
+            .list_all(&tenant)
+            .await
+            .unwrap()
+            .iter()
+            .map(|u| u.id)
+            .collect();
+        assert_eq!(ids, vec![bia.id]);
+    }
+
+    #[tokio::test]
+    async fn test_list_page() {
+        let tenant = TenantContext::default_tenant();
+        let repo = InMemoryUserRepository::new();
+        for i in 0..5 {
+            let email = format!("user{}@example.com", i);
+            repo.create(&tenant, "User", &email).await.unwrap();
+        }
+
+        let ids = |users: Vec<DbUser>| users.iter().map(|u| u.id).collect::<Vec<_>>();
+        assert_eq!(ids(repo.list_page(&tenant, 2, 0).await.unwrap()), [1, 2]);
+        assert_eq!(ids(repo.list_page(&tenant, 2, 4).await.unwrap()), [5]);
+        assert!(repo.list_page(&tenant, 2, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_email_is_unique() {
+        let tenant = TenantContext::default_tenant();
+        let repo = InMemoryUserRepository::new();
+        repo.create(&tenant, "Ana", "ana@example.com")
+            .await
+            .unwrap();
+        let mut bia = repo
+            .create(&tenant, "Bia", "bia@example.com")
+            .await
+            .unwrap();
+
+        assert!(repo
+            .create(&tenant, "Outra Ana", "ana@example.com")
+            .await
+            .is_err());
+
+        bia.email = "ANA@example.com".to_string();
+        assert!(repo.update(&tenant, &bia).await.is_err());
+
+        let found = repo
+            .find_by_email(&tenant, "BIA@Example.com")
+            .await
+            .unwrap();
+        assert_eq!(found.map(|u| u.id), Some(bia.id));
+    }
+
+    #[tokio::test]
+    async fn test_create_or_get() {
+        let tenant = TenantContext::default_tenant();
+        let repo = InMemoryUserRepository::new();
+
+        let (ana, created) = repo
+            .create_or_get(&tenant, "Ana", "ana@example.com")
+            .await
+            .unwrap();
+        assert!(created);
+        let (again, created) = repo
+            .create_or_get(&tenant, "Outra Ana", "Ana@Example.com")
+            .await
+            .unwrap();
+        assert!(!created);
+        assert_eq!(again, ana);
+        assert_eq!(repo.count(&tenant).await.unwrap(), 1);
+
+        let acme = TenantContext::new("acme").unwrap();
+        let (_, created) = repo
+            .create_or_get(&acme, "Ana", "ana@example.com")
+            .await
+            .unwrap();
+        assert!(created);
+    }
+
+    #[tokio::test]
+    async fn test_anonymize() {
+        let tenant = TenantContext::default_tenant();
+        let repo = InMemoryUserRepository::new();
+        let ana = repo
+            .create(&tenant, "Ana", "ana@example.com")
+            .await
+            .unwrap();
+
+        let anonymized = repo.anonymize(&tenant, ana.id).await.unwrap().unwrap();
+        assert!(anonymized.is_anonymized());
+        assert_eq!(
+            repo.find_by_email(&tenant, "ana@example.com")
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            repo.anonymize(&tenant, ana.id).await.unwrap(),
+            Some(anonymized)
+        );
+        assert_eq!(repo.anonymize(&tenant, 99).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_deletion_with_grace_period() {
+        let tenant = TenantContext::default_tenant();
+        let repo = InMemoryUserRepository::new();
+        let ana = repo
+            .create(&tenant, "Ana", "ana@example.com")
+            .await
+            .unwrap();
+        let bia = repo
+            .create(&tenant, "Bia", "bia@example.com")
+            .await
+            .unwrap();
+        let now = Utc::now();
+        let later = now + chrono::Duration::days(30);
+
+        let scheduled = repo
+            .schedule_deletion(&tenant, ana.id, later)
+            .await
+            .unwrap();
+        assert_eq!(scheduled.unwrap().deletion_scheduled_at, Some(later));
+        // Um segundo pedido não adia o prazo
+        let again = repo.schedule_deletion(&tenant, ana.id, later + chrono::Duration::days(1));
+        let again = again.await.unwrap().unwrap();
+        assert_eq!(again.deletion_scheduled_at, Some(later));
+        repo.schedule_deletion(&tenant, bia.id, later)
+            .await
+            .unwrap();
+        repo.cancel_deletion(&tenant, bia.id).await.unwrap();
+
+        assert!(repo.purge_deletions(now).await.unwrap().is_empty());
+        assert_eq!(
+            repo.purge_deletions(later).await.unwrap(),
+            vec![(tenant.clone(), ana.id)]
+        );
+        assert_eq!(repo.find_by_id(&tenant, ana.id).await.unwrap(), None);
+        assert!(repo.find_by_id(&tenant, bia.id).await.unwrap().is_some());
+        assert_eq!(
+            repo.schedule_deletion(&tenant, ana.id, later)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_with_users_continues_ids() {
+        let tenant = TenantContext::default_tenant();
+        let repo = InMemoryUserRepository::with_users([DbUser {
+            id: 7,
+            name: "Ana".to_string(),
+            email: "ana@example.com".to_string(),
+            status: UserStatus::Active,
+            created_at: None,
+            deletion_scheduled_at: None,
+            last_login_at: None,
+            role: DEFAULT_ROLE.to_string(),
+        }]);
+
+        let user = repo
+            .create(&tenant, "Bia", "bia@example.com")
+            .await
+            .unwrap();
+        assert_eq!(user.id, 8);
+    }
+
+    #[tokio::test]
+    async fn test_tenants_are_isolated() {
+        let repo = InMemoryUserRepository::new();
+        let acme = TenantContext::new("acme").unwrap();
+        let globex = TenantContext::new("globex").unwrap();
+
+        let ana = repo.create(&acme, "Ana", "ana@example.com").await.unwrap();
+        // O mesmo email pode existir em outro tenant
+        let other = repo
+            .create(&globex, "Ana", "ana@example.com")
+            .await
+            .unwrap();
+        assert_ne!(ana.id, other.id);
+
+        assert_eq!(repo.find_by_id(&globex, ana.id).await.unwrap(), None);
+        assert_eq!(repo.list_all(&globex).await.unwrap(), vec![other.clone()]);
+        assert_eq!(repo.count(&acme).await.unwrap(), 1);
+        assert_eq!(repo.anonymize(&globex, ana.id).await.unwrap(), None);
+        assert_eq!(
+            repo.schedule_deletion(&globex, ana.id, Utc::now())
+                .await
+                .unwrap(),
+            None
+        );
+
+        let renamed = DbUser {
+            name: "Invasor".to_string(),
+            ..ana.clone()
+        };
+        assert!(matches!(
+            repo.update(&globex, &renamed).await,
+            Err(DbError::NotFound { .. })
+        ));
+        assert!(matches!(
+            repo.delete(&globex, ana.id).await,
+            Err(DbError::NotFound { .. })
+        ));
+        assert_eq!(repo.find_by_id(&acme, ana.id).await.unwrap(), Some(ana));
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_inactive() {
+        let acme = TenantContext::new("acme").unwrap();
+        let globex = TenantContext::new("globex").unwrap();
+        let repo = InMemoryUserRepository::new();
+        let ana = repo.create(&acme, "Ana", "ana@example.com").await.unwrap();
+        let bia = repo
+            .create(&globex, "Bia", "bia@example.com")
+            .await
+            .unwrap();
+
+        // Nada mudou antes do corte
+        let past = Utc::now() - chrono::Duration::days(1);
+        let none = repo
+            .cleanup_inactive(past, InactiveUserAction::Deactivate)
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+
+        // Todos os tenants; só os ativos são desativados
+        let cutoff = Utc::now() + chrono::Duration::seconds(1);
+        let deactivated = repo
+            .cleanup_inactive(cutoff, InactiveUserAction::Deactivate)
+            .await
+            .unwrap();
+        assert_eq!(
+            deactivated
+                .iter()
+                .map(|(tenant, u)| (tenant.clone(), u.id))
+                .collect::<Vec<_>>(),
+            vec![(acme.clone(), ana.id), (globex.clone(), bia.id)]
+        );
+        assert!(deactivated
+            .iter()
+            .all(|(_, u)| u.status == UserStatus::Deactivated));
+        assert!(!repo
+            .find_by_id(&acme, ana.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .is_active());
+
+        // A desativação conta como alteração: com o mesmo corte, nada a remover
+        let purged = repo
+            .cleanup_inactive(past, InactiveUserAction::Purge)
+            .await
+            .unwrap();
+        assert!(purged.is_empty());
+
+        let cutoff = Utc::now() + chrono::Duration::seconds(1);
+        let purged = repo
+            .cleanup_inactive(cutoff, InactiveUserAction::Purge)
+            .await
+            .unwrap();
+        assert_eq!(purged.len(), 2);
+        assert_eq!(repo.count(&acme).await.unwrap(), 0);
+        assert_eq!(repo.count(&globex).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_record_login() {
+        let acme = TenantContext::new("acme").unwrap();
+        let globex = TenantContext::new("globex").unwrap();
+        let repo = InMemoryUserRepository::new();
+        let ana = repo.create(&acme, "Ana", "ana@example.com").await.unwrap();
+
+        assert!(repo.set_password_hash(&acme, ana.id, "hash").await.unwrap());
+        assert!(!repo.set_password_hash(&globex, ana.id, "x").await.unwrap());
+        assert_eq!(
+            repo.password_hash(&acme, ana.id).await.unwrap().as_deref(),
+            Some("hash")
+        );
+
+        let attempt = |success: bool| LoginEvent {
+            user_id: ana.id,
+            occurred_at: Utc::now(),
+            ip: Some("127.0.0.1".to_string()),
+            user_agent: None,
+            success,
+        };
+        repo.record_login(&acme, &attempt(false)).await.unwrap();
+        let ok = attempt(true);
+        repo.record_login(&acme, &ok).await.unwrap();
+        // De outro tenant, ignorada
+        repo.record_login(&globex, &attempt(true)).await.unwrap();
+
+        let user = repo.find_by_id(&acme, ana.id).await.unwrap().unwrap();
+        assert_eq!(user.last_login_at, Some(ok.occurred_at));
+        let logins = repo.logins(&acme, ana.id, 10).await.unwrap();
+        assert_eq!(
+            logins.iter().map(|e| e.success).collect::<Vec<_>>(),
+            vec![true, false]
+        );
+        assert_eq!(repo.logins(&acme, ana.id, 1).await.unwrap().len(), 1);
+        assert!(repo.logins(&globex, ana.id, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_passkeys() {
+        let acme = TenantContext::new("acme").unwrap();
+        let globex = TenantContext::new("globex").unwrap();
+        let repo = InMemoryUserRepository::new();
+        let ana = repo.create(&acme, "Ana", "ana@example.com").await.unwrap();
+        let passkey = StoredPasskey {
+            credential_id: "0a0b".to_string(),
+            name: "Notebook".to_string(),
+            passkey: "{}".to_string(),
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+
+        assert!(!repo.add_passkey(&globex, ana.id, &passkey).await.unwrap());
+        assert!(repo.add_passkey(&acme, ana.id, &passkey).await.unwrap());
+        // O id da credencial é único
+        assert!(repo.add_passkey(&acme, ana.id, &passkey).await.is_err());
+
+        let used_at = Utc::now();
+        assert!(repo
+            .update_passkey(&acme, ana.id, "0a0b", r#"{"counter":1}"#, used_at)
+            .await
+            .unwrap());
+        assert!(!repo
+            .update_passkey(&acme, ana.id, "ffff", "{}", used_at)
+            .await
+            .unwrap());
+        let passkeys = repo.passkeys(&acme, ana.id).await.unwrap();
+        assert_eq!(passkeys.len(), 1);
+        assert_eq!(passkeys[0].passkey, r#"{"counter":1}"#);
+        assert_eq!(passkeys[0].last_used_at, Some(used_at));
+        assert!(repo.passkeys(&globex, ana.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_and_flush() {
+        let dir = std::env::temp_dir().join(format!("users-test-{}", rand::random::<u64>()));
+        let path = dir.join("nested/users.json");
+        let acme = TenantContext::new("acme").unwrap();
+
+        let repo = InMemoryUserRepository::load(&path).unwrap();
+        assert_eq!(repo.path(), Some(path.as_path()));
+        assert!(!repo.flush().unwrap());
+        let ana = repo.create(&acme, "Ana", "ana@example.com").await.unwrap();
+        let bia = repo
+            .create(&TenantContext::default_tenant(), "Bia", "bia@example.com")
+            .await
+            .unwrap();
+        repo.set_password_hash(&acme, ana.id, "hash").await.unwrap();
+        repo.set_two_factor_secret(&acme, ana.id, Some("JBSWY3DP"))
+            .await
+            .unwrap();
+        let passkey = StoredPasskey {
+            credential_id: "0a0b".to_string(),
+            name: "Notebook".to_string(),
+            passkey: r#"{"counter":1}"#.to_string(),
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+        repo.add_passkey(&acme, ana.id, &passkey).await.unwrap();
+        assert!(repo.flush().unwrap());
+        assert!(!repo.flush().unwrap());
+
+        // O drop grava o que ficou pendente
+        repo.delete(&TenantContext::default_tenant(), bia.id)
+            .await
+            .unwrap();
+        drop(repo);
+
+        let repo = InMemoryUserRepository::load(&path).unwrap();
+        assert_eq!(repo.get(&acme, ana.id).await.unwrap(), ana);
+        assert_eq!(
+            repo.password_hash(&acme, ana.id).await.unwrap(),
+            Some("hash".to_string())
+        );
+        let two_factor = repo.two_factor(&acme, ana.id).await.unwrap().unwrap();
+        assert_eq!(two_factor.secret.as_deref(), Some("JBSWY3DP"));
+        assert_eq!(repo.passkeys(&acme, ana.id).await.unwrap(), vec![passkey]);
+        assert_eq!(
+            repo.count(&TenantContext::default_tenant()).await.unwrap(),
+            0
+        );
+        // O ID removido não volta
+        let caio = repo
+            .create(&acme, "Caio", "caio@example.com")
+            .await
+            .unwrap();
+        assert_eq!(caio.id, 3);
+        drop(repo);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(InMemoryUserRepository::load(&path).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,227 @@
+//! Repositórios de dados
+//!
+//! `UserRepository` abstrai o armazenamento de usuários: em produção o
+//! `PgUserRepository` (feature "postgres"); em testes, ou sem banco, o
+//! `InMemoryUserRepository`.
+//!
+//! As operações recebem o `TenantContext` de quem pede e só enxergam os
+//! usuários daquele tenant; `purge_deletions` e `cleanup_inactive`, que
+//! são manutenção, valem para todos.
+
+use crate::config::InactiveUserAction;
+use crate::models::{DbUser, LoginEvent, StoredPasskey, TwoFactor, UserField, UserProjection};
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+pub mod memory;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+pub use memory::InMemoryUserRepository;
+#[cfg(feature = "postgres")]
+pub use postgres::PgUserRepository;
+
+/// Falha de uma consulta que exige que o usuário exista
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    /// Nenhum usuário com o ID (no tenant)
+    #[error("User with id {id} not found")]
+    NotFound { id: i32 },
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg(feature = "postgres")]
+impl From<sqlx::Error> for DbError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Other(error.into())
+    }
+}
+
+/// `Option` de uma consulta por ID transformado em `DbError::NotFound`
+pub trait OrNotFound<T> {
+    fn or_not_found(self, id: i32) -> Result<T, DbError>;
+}
+
+impl<T> OrNotFound<T> for Result<Option<T>> {
+    fn or_not_found(self, id: i32) -> Result<T, DbError> {
+        self?.ok_or(DbError::NotFound { id })
+    }
+}
+
+/// Operações de persistência de usuários
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    /// Cria um usuário ativo; o email é único dentro do tenant
+    async fn create(&self, tenant: &TenantContext, name: &str, email: &str) -> Result<DbUser>;
+
+    /// Cria um usuário ativo ou, se o email (sem diferenciar maiúsculas) já
+    /// existe no tenant, devolve o existente; o `bool` diz se criou. Para
+    /// provisionamento e importações, que podem repetir o mesmo email
+    async fn create_or_get(
+        &self,
+        tenant: &TenantContext,
+        name: &str,
+        email: &str,
+    ) -> Result<(DbUser, bool)>;
+
+    /// Busca um usuário por ID
+    async fn find_by_id(&self, tenant: &TenantContext, id: i32) -> Result<Option<DbUser>>;
+
+    /// Como `find_by_id`, mas com `DbError::NotFound` se ele não existir
+    async fn get(&self, tenant: &TenantContext, id: i32) -> Result<DbUser, DbError> {
+        self.find_by_id(tenant, id).await.or_not_found(id)
+    }
+
+    /// Busca um usuário por email, sem diferenciar maiúsculas
+    async fn find_by_email(&self, tenant: &TenantContext, email: &str) -> Result<Option<DbUser>>;
+
+    /// Lista todos os usuários, ordenados por ID
+    async fn list_all(&self, tenant: &TenantContext) -> Result<Vec<DbUser>>;
+
+    /// Até `limit` usuários, ordenados por ID, pulando os `offset` primeiros
+    async fn list_page(
+        &self,
+        tenant: &TenantContext,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<DbUser>>;
+
+    /// Como `list_page`, mas lendo só os `fields` (`?fields=`)
+    async fn list_page_fields(
+        &self,
+        tenant: &TenantContext,
+        fields: &[UserField],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<UserProjection>>;
+
+    /// Atualiza nome, email e status do usuário; `DbError::NotFound` se
+    /// ele não existir
+    async fn update(&self, tenant: &TenantContext, user: &DbUser) -> Result<(), DbError>;
+
+    /// Remove um usuário; `DbError::NotFound` se ele não existir
+    async fn delete(&self, tenant: &TenantContext, id: i32) -> Result<(), DbError>;
+
+    /// Troca os dados pessoais por valores anônimos, mantendo a linha (ver
+    /// `DbUser::anonymized`); `None` se o usuário não existir. Um usuário já
+    /// anonimizado é devolvido sem mudanças
+    async fn anonymize(&self, tenant: &TenantContext, id: i32) -> Result<Option<DbUser>>;
+
+    /// Marca o usuário para ser removido em `at` (exclusão com carência);
+    /// `None` se ele não existir. Um pedido anterior mantém o prazo original
+    async fn schedule_deletion(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        at: DateTime<Utc>,
+    ) -> Result<Option<DbUser>>;
+
+    /// Cancela a exclusão marcada; `None` se o usuário não existir
+    async fn cancel_deletion(&self, tenant: &TenantContext, id: i32) -> Result<Option<DbUser>>;
+
+    /// Remove os usuários, de todos os tenants, cujo prazo de exclusão
+    /// venceu até `now`; devolve o tenant e o ID de cada removido, ordenados
+    /// por ID
+    async fn purge_deletions(&self, now: DateTime<Utc>) -> Result<Vec<(TenantContext, i32)>>;
+
+    /// Limpa as contas, de todos os tenants, sem alterações desde `before`:
+    /// desativa as ativas (`Deactivate`) ou remove as já desativadas
+    /// (`Purge`); devolve os usuários afetados, com o tenant, como ficaram
+    /// (ou como estavam, se removidos), ordenados por ID
+    async fn cleanup_inactive(
+        &self,
+        before: DateTime<Utc>,
+        action: InactiveUserAction,
+    ) -> Result<Vec<(TenantContext, DbUser)>>;
+
+    /// Conta quantos usuários existem
+    async fn count(&self, tenant: &TenantContext) -> Result<i64>;
+
+    /// Grava o hash da senha do usuário; `false` se ele não existir
+    async fn set_password_hash(&self, tenant: &TenantContext, id: i32, hash: &str) -> Result<bool>;
+
+    /// Hash da senha do usuário; `None` se ele não existir ou não tiver senha
+    async fn password_hash(&self, tenant: &TenantContext, id: i32) -> Result<Option<String>>;
+
+    /// Registra uma tentativa de login do usuário `event.user_id` (sem efeito
+    /// se ele não existir); as bem-sucedidas atualizam `last_login_at`
+    async fn record_login(&self, tenant: &TenantContext, event: &LoginEvent) -> Result<()>;
+
+    /// As `limit` tentativas de login mais recentes do usuário, da mais nova
+    /// para a mais antiga
+    async fn logins(&self, tenant: &TenantContext, id: i32, limit: i64) -> Result<Vec<LoginEvent>>;
+
+    /// Estado do 2FA do usuário; `None` se ele não existir
+    async fn two_factor(&self, tenant: &TenantContext, id: i32) -> Result<Option<TwoFactor>>;
+
+    /// Começa (com `Some`) ou remove (com `None`) o cadastro do 2FA: troca o
+    /// segredo, volta o cadastro a pendente e apaga os códigos de
+    /// recuperação; `false` se o usuário não existir
+    async fn set_two_factor_secret(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        secret: Option<&str>,
+    ) -> Result<bool>;
+
+    /// Confirma o cadastro pendente do 2FA, guardando os hashes dos códigos
+    /// de recuperação; `false` se não houver cadastro pendente
+    async fn enable_two_factor(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        backup_code_hashes: &[String],
+    ) -> Result<bool>;
+
+    /// Gasta um código de recuperação; `false` se ele não existir ou já
+    /// tiver sido usado
+    async fn consume_backup_code(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        code_hash: &str,
+    ) -> Result<bool>;
+
+    /// Grava `step` como o último passo TOTP aceito; `false` se ele não for
+    /// posterior ao último (o código já foi usado) ou o usuário não existir
+    async fn accept_totp_step(&self, tenant: &TenantContext, id: i32, step: i64) -> Result<bool>;
+
+    /// Conta um código errado do segundo fator; na `max_attempts`-ésima
+    /// falha seguida, bloqueia até `locked_until` e zera a contagem.
+    /// `true` se bloqueou
+    async fn record_two_factor_failure(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        max_attempts: i32,
+        locked_until: DateTime<Utc>,
+    ) -> Result<bool>;
+
+    /// Zera a contagem de códigos errados, depois de um certo
+    async fn reset_two_factor_failures(&self, tenant: &TenantContext, id: i32) -> Result<()>;
+
+    /// Passkeys do usuário, da mais antiga para a mais nova
+    async fn passkeys(&self, tenant: &TenantContext, id: i32) -> Result<Vec<StoredPasskey>>;
+
+    /// Guarda uma passkey nova do usuário; `false` se ele não existir
+    async fn add_passkey(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        passkey: &StoredPasskey,
+    ) -> Result<bool>;
+
+    /// Atualiza a credencial serializada (o contador) depois de um login e
+    /// marca o uso; `false` se a passkey não for do usuário
+    async fn update_passkey(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        credential_id: &str,
+        passkey: &str,
+        used_at: DateTime<Utc>,
+    ) -> Result<bool>;
+}
@@ -0,0 +1,299 @@
+//! `UserRepository` sobre o Postgres
+//!
+//! Cada operação respeita o prazo da requisição em curso (`deadline`):
+//! quando ele passa, a consulta é abandonada com `DeadlineExceeded`.
+//!
+//! As operações idempotentes (leituras, `update`, `anonymize`, a troca de
+//! senha e o agendamento de exclusões) são repetidas diante de erros
+//! transitórios (`db::is_transient`), conforme a `RetryPolicy`; `create`,
+//! `delete`, `purge_deletions`, `cleanup_inactive` e `record_login` rodam
+//! uma vez só, porque uma tentativa que falhou pode ter sido aplicada.
+
+use super::{DbError, UserRepository};
+use crate::config::InactiveUserAction;
+use crate::db::with_retry;
+use crate::deadline::within;
+use crate::models::{DbUser, LoginEvent, StoredPasskey, TwoFactor, UserField, UserProjection};
+use crate::retry::RetryPolicy;
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// Repositório de usuários no Postgres
+#[derive(Debug, Clone)]
+pub struct PgUserRepository {
+    pool: PgPool,
+    retry: RetryPolicy,
+}
+
+impl PgUserRepository {
+    /// Repositório com a `RetryPolicy` padrão
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Troca as retentativas das operações idempotentes
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+#[async_trait]
+impl UserRepository for PgUserRepository {
+    async fn create(&self, tenant: &TenantContext, name: &str, email: &str) -> Result<DbUser> {
+        within(DbUser::create(&self.pool, tenant, name, email)).await?
+    }
+
+    async fn create_or_get(
+        &self,
+        tenant: &TenantContext,
+        name: &str,
+        email: &str,
+    ) -> Result<(DbUser, bool)> {
+        within(DbUser::create_or_get(&self.pool, tenant, name, email)).await?
+    }
+
+    async fn find_by_id(&self, tenant: &TenantContext, id: i32) -> Result<Option<DbUser>> {
+        within(with_retry(&self.retry, "users.find_by_id", || {
+            DbUser::find_by_id(&self.pool, tenant, id)
+        }))
+        .await?
+    }
+
+    async fn find_by_email(&self, tenant: &TenantContext, email: &str) -> Result<Option<DbUser>> {
+        within(with_retry(&self.retry, "users.find_by_email", || {
+            DbUser::find_by_email(&self.pool, tenant, email)
+        }))
+        .await?
+    }
+
+    async fn list_all(&self, tenant: &TenantContext) -> Result<Vec<DbUser>> {
+        within(with_retry(&self.retry, "users.list_all", || {
+            DbUser::list_all(&self.pool, tenant)
+        }))
+        .await?
+    }
+
+    async fn list_page(
+        &self,
+        tenant: &TenantContext,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<DbUser>> {
+        within(with_retry(&self.retry, "users.list_page", || {
+            DbUser::list_page(&self.pool, tenant, limit, offset)
+        }))
+        .await?
+    }
+
+    async fn list_page_fields(
+        &self,
+        tenant: &TenantContext,
+        fields: &[UserField],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<UserProjection>> {
+        within(with_retry(&self.retry, "users.list_page_fields", || {
+            DbUser::list_page_fields(&self.pool, tenant, fields, limit, offset)
+        }))
+        .await?
+    }
+
+    async fn update(&self, tenant: &TenantContext, user: &DbUser) -> Result<(), DbError> {
+        within(with_retry(&self.retry, "users.update", || async {
+            // Só as falhas do banco são candidatas a retentativa
+            match user.update(&self.pool, tenant).await {
+                Err(DbError::Other(e)) => Err(e),
+                result => Ok(result),
+            }
+        }))
+        .await
+        .map_err(anyhow::Error::from)??
+    }
+
+    async fn delete(&self, tenant: &TenantContext, id: i32) -> Result<(), DbError> {
+        within(DbUser::delete(&self.pool, tenant, id))
+            .await
+            .map_err(anyhow::Error::from)?
+    }
+
+    async fn anonymize(&self, tenant: &TenantContext, id: i32) -> Result<Option<DbUser>> {
+        within(with_retry(&self.retry, "users.anonymize", || {
+            DbUser::anonymize(&self.pool, tenant, id)
+        }))
+        .await?
+    }
+
+    async fn schedule_deletion(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        at: DateTime<Utc>,
+    ) -> Result<Option<DbUser>> {
+        within(with_retry(&self.retry, "users.schedule_deletion", || {
+            DbUser::schedule_deletion(&self.pool, tenant, id, at)
+        }))
+        .await?
+    }
+
+    async fn cancel_deletion(&self, tenant: &TenantContext, id: i32) -> Result<Option<DbUser>> {
+        within(with_retry(&self.retry, "users.cancel_deletion", || {
+            DbUser::cancel_deletion(&self.pool, tenant, id)
+        }))
+        .await?
+    }
+
+    async fn purge_deletions(&self, now: DateTime<Utc>) -> Result<Vec<(TenantContext, i32)>> {
+        within(DbUser::purge_deletions(&self.pool, now)).await?
+    }
+
+    async fn cleanup_inactive(
+        &self,
+        before: DateTime<Utc>,
+        action: InactiveUserAction,
+    ) -> Result<Vec<(TenantContext, DbUser)>> {
+        within(DbUser::cleanup_inactive(&self.pool, before, action)).await?
+    }
+
+    async fn count(&self, tenant: &TenantContext) -> Result<i64> {
+        within(with_retry(&self.retry, "users.count", || {
+            DbUser::count(&self.pool, tenant)
+        }))
+        .await?
+    }
+
+    async fn set_password_hash(&self, tenant: &TenantContext, id: i32, hash: &str) -> Result<bool> {
+        within(with_retry(&self.retry, "users.set_password_hash", || {
+            DbUser::set_password_hash(&self.pool, tenant, id, hash)
+        }))
+        .await?
+    }
+
+    async fn password_hash(&self, tenant: &TenantContext, id: i32) -> Result<Option<String>> {
+        within(with_retry(&self.retry, "users.password_hash", || {
+            DbUser::password_hash(&self.pool, tenant, id)
+        }))
+        .await?
+    }
+
+    async fn record_login(&self, tenant: &TenantContext, event: &LoginEvent) -> Result<()> {
+        within(DbUser::record_login(&self.pool, tenant, event)).await?
+    }
+
+    async fn logins(&self, tenant: &TenantContext, id: i32, limit: i64) -> Result<Vec<LoginEvent>> {
+        within(with_retry(&self.retry, "login_events.list", || {
+            DbUser::logins(&self.pool, tenant, id, limit)
+        }))
+        .await?
+    }
+
+    async fn two_factor(&self, tenant: &TenantContext, id: i32) -> Result<Option<TwoFactor>> {
+        within(with_retry(&self.retry, "users.two_factor", || {
+            DbUser::two_factor(&self.pool, tenant, id)
+        }))
+        .await?
+    }
+
+    async fn set_two_factor_secret(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        secret: Option<&str>,
+    ) -> Result<bool> {
+        within(with_retry(&self.retry, "users.set_totp_secret", || {
+            DbUser::set_two_factor_secret(&self.pool, tenant, id, secret)
+        }))
+        .await?
+    }
+
+    async fn enable_two_factor(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        backup_code_hashes: &[String],
+    ) -> Result<bool> {
+        within(DbUser::enable_two_factor(
+            &self.pool,
+            tenant,
+            id,
+            backup_code_hashes,
+        ))
+        .await?
+    }
+
+    async fn consume_backup_code(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        code_hash: &str,
+    ) -> Result<bool> {
+        within(DbUser::consume_backup_code(
+            &self.pool, tenant, id, code_hash,
+        ))
+        .await?
+    }
+
+    async fn accept_totp_step(&self, tenant: &TenantContext, id: i32, step: i64) -> Result<bool> {
+        within(DbUser::accept_totp_step(&self.pool, tenant, id, step)).await?
+    }
+
+    async fn record_two_factor_failure(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        max_attempts: i32,
+        locked_until: DateTime<Utc>,
+    ) -> Result<bool> {
+        within(DbUser::record_two_factor_failure(
+            &self.pool,
+            tenant,
+            id,
+            max_attempts,
+            locked_until,
+        ))
+        .await?
+    }
+
+    async fn reset_two_factor_failures(&self, tenant: &TenantContext, id: i32) -> Result<()> {
+        within(with_retry(&self.retry, "users.reset_totp_failures", || {
+            DbUser::reset_two_factor_failures(&self.pool, tenant, id)
+        }))
+        .await?
+    }
+
+    async fn passkeys(&self, tenant: &TenantContext, id: i32) -> Result<Vec<StoredPasskey>> {
+        within(with_retry(&self.retry, "passkeys.list", || {
+            DbUser::passkeys(&self.pool, tenant, id)
+        }))
+        .await?
+    }
+
+    async fn add_passkey(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        passkey: &StoredPasskey,
+    ) -> Result<bool> {
+        within(DbUser::add_passkey(&self.pool, tenant, id, passkey)).await?
+    }
+
+    async fn update_passkey(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+        credential_id: &str,
+        passkey: &str,
+        used_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        within(with_retry(&self.retry, "passkeys.update", || {
+            DbUser::update_passkey(&self.pool, tenant, id, credential_id, passkey, used_at)
+        }))
+        .await?
+    }
+}
@@ -0,0 +1,171 @@
+//! Retentativas com backoff exponencial
+//!
+//! `retry` repete uma operação enquanto o erro for considerado transitório
+//! e houver tentativas, esperando `initial_backoff`, depois o dobro, e assim
+//! por diante até `max_backoff`. Só faz sentido para operações idempotentes:
+//! uma tentativa que falhou pode ter surtido efeito.
+//!
+//! A espera entre as tentativas respeita o `Deadline` da requisição quando
+//! a chamada toda roda dentro de `deadline::within`.
+
+use std::future::Future;
+use std::time::Duration;
+
+/// Quantas vezes e com que espera repetir uma operação
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total de tentativas, contando a primeira (1 não repete)
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Uma tentativa só, sem retentativas
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Espera depois da tentativa `attempt` (a partir de 1)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Executa `operation` até dar certo, o erro não ser transitório
+/// (`is_transient`) ou acabarem as tentativas
+///
+/// `on_retry` é chamado antes de cada espera, com o erro, o número da
+/// tentativa que falhou e a espera (para logs e métricas).
+pub async fn retry<T, E, Op, Fut>(
+    policy: &RetryPolicy,
+    is_transient: impl Fn(&E) -> bool,
+    mut on_retry: impl FnMut(&E, u32, Duration),
+    mut operation: Op,
+) -> Result<T, E>
+where
+    Op: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match operation().await {
+            Err(e) if attempt < policy.max_attempts && is_transient(&e) => {
+                let delay = policy.backoff(attempt);
+                on_retry(&e, attempt, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn fast() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_limit() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+        assert_eq!(policy.backoff(40), Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors() {
+        let calls = Cell::new(0);
+        let mut retries = Vec::new();
+        let result = retry(
+            &fast(),
+            |e: &&str| *e == "transient",
+            |_, attempt, _| retries.push(attempt),
+            || async {
+                calls.set(calls.get() + 1);
+                if calls.get() < 3 {
+                    Err("transient")
+                } else {
+                    Ok(calls.get())
+                }
+            },
+        )
+        .await;
+        assert_eq!(result, Ok(3));
+        assert_eq!(retries, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_on_permanent_errors_and_after_max_attempts() {
+        let calls = Cell::new(0);
+        let permanent: Result<(), _> = retry(
+            &fast(),
+            |e: &&str| *e == "transient",
+            |_, _, _| {},
+            || async {
+                calls.set(calls.get() + 1);
+                Err("permanent")
+            },
+        )
+        .await;
+        assert_eq!(permanent, Err("permanent"));
+        assert_eq!(calls.get(), 1);
+
+        calls.set(0);
+        let exhausted: Result<(), _> = retry(
+            &fast(),
+            |_: &&str| true,
+            |_, _, _| {},
+            || async {
+                calls.set(calls.get() + 1);
+                Err("transient")
+            },
+        )
+        .await;
+        assert_eq!(exhausted, Err("transient"));
+        assert_eq!(calls.get(), 3);
+
+        calls.set(0);
+        let _: Result<(), _> = retry(
+            &RetryPolicy::none(),
+            |_: &&str| true,
+            |_, _, _| {},
+            || async {
+                calls.set(calls.get() + 1);
+                Err("transient")
+            },
+        )
+        .await;
+        assert_eq!(calls.get(), 1);
+    }
+}
@@ -0,0 +1,96 @@
+//! Estatísticas de latência (mínimo, máximo, média e percentis)
+//!
+//! Usado pelo `loadtest` e por qualquer código que precise resumir uma
+//! série de durações.
+
+use serde::Serialize;
+use std::time::Duration;
+
+/// Resumo de uma série de durações
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Summary {
+    pub count: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+}
+
+impl Summary {
+    /// Calcula o resumo; `None` se não houver amostras
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+
+        let total: Duration = sorted.iter().sum();
+
+        Some(Self {
+            count: sorted.len(),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            mean: total / sorted.len() as u32,
+            p50: percentile(&sorted, 50.0),
+            p90: percentile(&sorted, 90.0),
+            p95: percentile(&sorted, 95.0),
+            p99: percentile(&sorted, 99.0),
+        })
+    }
+}
+
+/// Percentil pelo método nearest-rank; `sorted` deve estar ordenado
+///
+/// # Panics
+///
+/// Se `sorted` estiver vazio.
+pub fn percentile<T: Copy>(sorted: &[T], p: f64) -> T {
+    assert!(!sorted.is_empty(), "percentile of an empty series");
+    let rank = (p.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.saturating_sub(1).min(sorted.len() - 1)]
+}
+
+/// Formata uma duração em milissegundos com duas casas (ex.: `"12.34ms"`)
+pub fn format_millis(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let values: Vec<u32> = (1..=100).collect();
+        assert_eq!(percentile(&values, 50.0), 50);
+        assert_eq!(percentile(&values, 99.0), 99);
+        assert_eq!(percentile(&values, 100.0), 100);
+        assert_eq!(percentile(&values, 0.0), 1);
+        assert_eq!(percentile(&[7], 90.0), 7);
+    }
+
+    #[test]
+    fn test_summary() {
+        let samples: Vec<Duration> = (1..=10).rev().map(Duration::from_millis).collect();
+        let summary = Summary::from_samples(&samples).unwrap();
+
+        assert_eq!(summary.count, 10);
+        assert_eq!(summary.min, Duration::from_millis(1));
+        assert_eq!(summary.max, Duration::from_millis(10));
+        assert_eq!(summary.mean, Duration::from_micros(5500));
+        assert_eq!(summary.p50, Duration::from_millis(5));
+        assert_eq!(summary.p90, Duration::from_millis(9));
+
+        assert!(Summary::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn test_format_millis() {
+        assert_eq!(format_millis(Duration::from_micros(1_500)), "1.50ms");
+    }
+}
@@ -0,0 +1,284 @@
+//! Arquivos servidos por URL pré-assinada (feature "api")
+//!
+//! Os arquivos ficam num diretório local (`storage.dir`), sob chaves como
+//! `exports/<id>.csv`. `Storage::presign` gera uma URL de `GET /files/<chave>`
+//! com validade e uma assinatura HMAC-SHA256 da chave e do vencimento; quem
+//! tem a URL baixa o arquivo sem headers de autenticação até ela vencer.
+
+use crate::config::StorageConfig;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Maior chave aceita
+const MAX_KEY_LEN: usize = 512;
+
+/// Por que uma URL pré-assinada foi recusada
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum PresignError {
+    #[error("The link has expired")]
+    Expired,
+    #[error("Invalid signature")]
+    InvalidSignature,
+}
+
+/// URL gerada por `Storage::presign`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct PresignedUrl {
+    /// Caminho e query; relativa à raiz do servidor
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Diretório dos arquivos e segredo das assinaturas (clonável)
+#[derive(Clone)]
+pub struct Storage {
+    root: PathBuf,
+    secret: Arc<[u8]>,
+    /// Maior validade aceita por `presign`
+    pub max_expiry: Duration,
+}
+
+impl Storage {
+    pub fn new(root: impl Into<PathBuf>, secret: &[u8]) -> Self {
+        Self {
+            root: root.into(),
+            secret: secret.into(),
+            max_expiry: Duration::from_secs(7 * 24 * 60 * 60),
+        }
+    }
+
+    /// Storage da configuração; sem `signing_secret`, usa um segredo
+    /// aleatório (as URLs deixam de valer quando o processo reinicia)
+    pub fn from_config(config: &StorageConfig) -> Self {
+        let secret = match &config.signing_secret {
+            Some(secret) => secret.as_bytes().to_vec(),
+            None => {
+                let mut secret = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut secret);
+                secret
+            }
+        };
+        Self {
+            max_expiry: Duration::from_secs(config.max_expiry_seconds),
+            ..Self::new(&config.dir, &secret)
+        }
+    }
+
+    /// Grava `bytes` sob `key`, substituindo o que houver
+    pub async fn put(&self, key: &str, bytes: &[u8]) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, bytes).await?;
+        Ok(())
+    }
+
+    /// Conteúdo de `key`, se existir
+    pub async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// URL que baixa `key` durante `expiry` (limitada a `max_expiry`)
+    pub fn presign(&self, key: &str, expiry: Duration) -> Result<PresignedUrl> {
+        check_key(key)?;
+        let expiry = chrono::Duration::from_std(expiry.min(self.max_expiry))?;
+        let expires = (Utc::now() + expiry).timestamp();
+        Ok(PresignedUrl {
+            url: format!(
+                "/files/{}?expires={}&signature={}",
+                key,
+                expires,
+                self.signature(key, expires)
+            ),
+            expires_at: DateTime::from_timestamp(expires, 0).unwrap_or_default(),
+        })
+    }
+
+    /// Confere a assinatura e a validade de uma URL de `presign`
+    pub fn verify(
+        &self,
+        key: &str,
+        expires: i64,
+        signature: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), PresignError> {
+        let expected = hex::decode(signature).map_err(|_| PresignError::InvalidSignature)?;
+        self.mac(key, expires)
+            .verify_slice(&expected)
+            .map_err(|_| PresignError::InvalidSignature)?;
+        if now.timestamp() > expires {
+            return Err(PresignError::Expired);
+        }
+        Ok(())
+    }
+
+    fn signature(&self, key: &str, expires: i64) -> String {
+        hex::encode(self.mac(key, expires).finalize().into_bytes())
+    }
+
+    fn mac(&self, key: &str, expires: i64) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any size");
+        mac.update(key.as_bytes());
+        mac.update(b"\n");
+        mac.update(expires.to_string().as_bytes());
+        mac
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        check_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+/// Chaves são caminhos relativos de segmentos `[A-Za-z0-9._-]`, sem `.` e
+/// `..`: nunca saem do diretório do storage
+pub fn check_key(key: &str) -> Result<()> {
+    let valid_segment = |segment: &str| {
+        !segment.is_empty()
+            && segment != "."
+            && segment != ".."
+            && segment
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'))
+    };
+    if key.len() > MAX_KEY_LEN || !key.split('/').all(valid_segment) {
+        bail!("invalid storage key '{}'", key);
+    }
+    Ok(())
+}
+
+/// Content-Type pela extensão da chave
+pub fn content_type(key: &str) -> &'static str {
+    match key
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+    {
+        Some(ext) => match ext.as_str() {
+            "csv" => "text/csv; charset=utf-8",
+            "json" => "application/json",
+            "txt" => "text/plain; charset=utf-8",
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            "pdf" => "application/pdf",
+            _ => "application/octet-stream",
+        },
+        None => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage() -> Storage {
+        let dir = std::env::temp_dir().join(format!("storage-test-{}", uuid::Uuid::new_v4()));
+        Storage::new(dir, b"segredo")
+    }
+
+    fn query(url: &str) -> (i64, String) {
+        let query = url.split_once('?').unwrap().1;
+        let (expires, signature) = query.split_once('&').unwrap();
+        (
+            expires.strip_prefix("expires=").unwrap().parse().unwrap(),
+            signature.strip_prefix("signature=").unwrap().to_string(),
+        )
+    }
+
+    #[test]
+    fn test_presign_and_verify() {
+        let storage = storage();
+        let presigned = storage
+            .presign("avatars/7.png", Duration::from_secs(60))
+            .unwrap();
+        let url = presigned.url;
+        assert!(url.starts_with("/files/avatars/7.png?expires="));
+
+        let (expires, signature) = query(&url);
+        assert_eq!(presigned.expires_at.timestamp(), expires);
+        let now = Utc::now();
+        assert_eq!(
+            storage.verify("avatars/7.png", expires, &signature, now),
+            Ok(())
+        );
+        assert_eq!(
+            storage.verify("avatars/8.png", expires, &signature, now),
+            Err(PresignError::InvalidSignature)
+        );
+        assert_eq!(
+            storage.verify("avatars/7.png", expires + 60, &signature, now),
+            Err(PresignError::InvalidSignature)
+        );
+        assert_eq!(
+            storage.verify(
+                "avatars/7.png",
+                expires,
+                &signature,
+                now + chrono::Duration::minutes(2)
+            ),
+            Err(PresignError::Expired)
+        );
+
+        // Outro segredo não reconhece a assinatura
+        let other = Storage::new(std::env::temp_dir(), b"outro");
+        assert!(other
+            .verify("avatars/7.png", expires, &signature, now)
+            .is_err());
+    }
+
+    #[test]
+    fn test_expiry_is_capped() {
+        let mut storage = storage();
+        storage.max_expiry = Duration::from_secs(60);
+        let url = storage
+            .presign("a.csv", Duration::from_secs(3600))
+            .unwrap()
+            .url;
+        assert!(query(&url).0 <= Utc::now().timestamp() + 60);
+    }
+
+    #[test]
+    fn test_check_key() {
+        for key in ["a.csv", "exports/1f2e.csv", "avatars/user-7_v2.PNG"] {
+            assert!(check_key(key).is_ok(), "{}", key);
+        }
+        for key in [
+            "",
+            "/etc/passwd",
+            "../x",
+            "a/../../x",
+            "a//b",
+            "a/./b",
+            "a b",
+            "a\\b",
+        ] {
+            assert!(check_key(key).is_err(), "{}", key);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_and_get() {
+        let storage = storage();
+        assert_eq!(storage.get("exports/1.csv").await.unwrap(), None);
+        storage.put("exports/1.csv", b"id\n1\n").await.unwrap();
+        assert_eq!(
+            storage.get("exports/1.csv").await.unwrap().as_deref(),
+            Some(&b"id\n1\n"[..])
+        );
+        assert!(storage.put("../fora.csv", b"x").await.is_err());
+        std::fs::remove_dir_all(&storage.root).unwrap();
+    }
+}
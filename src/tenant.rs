@@ -0,0 +1,303 @@
+//! Isolamento de dados por tenant
+//!
+//! Toda consulta de usuários (`UserRepository` e `DbUser`) recebe um
+//! `&TenantContext` e só enxerga as linhas daquele tenant. O tipo é selado:
+//! o campo é privado e não há `Default`, `Deserialize` nem `From<String>`,
+//! então a única forma de obter um é `TenantContext::new` (que valida o
+//! identificador) ou o extrator da API, que lê o header `X-Tenant-Id`.
+//! Esquecer o tenant numa consulta nova vira erro de compilação.
+//!
+//! Com o token de um usuário, o tenant é o do token: o header é opcional e,
+//! se vier, precisa ser o mesmo. Só o admin escolhe o tenant pelo header;
+//! as demais requisições ficam no tenant padrão, salvo o login
+//! (`LoginTenant`), em que o header diz em que tenant procurar a conta.
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Header com o tenant da requisição
+pub const TENANT_HEADER: &str = "x-tenant-id";
+
+/// Tenant dos dados criados antes do isolamento e das instalações com um só
+pub const DEFAULT_TENANT: &str = "default";
+
+/// Maior identificador de tenant aceito (o tamanho da coluna `tenant_id`)
+pub const MAX_TENANT_LEN: usize = 64;
+
+/// Tenant em nome de quem as consultas são feitas
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TenantContext {
+    id: Arc<str>,
+}
+
+/// Identificador de tenant fora do formato aceito
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid tenant id '{0}': expected 1 to 64 letters, digits, '-' or '_'")]
+pub struct InvalidTenant(pub String);
+
+impl TenantContext {
+    /// Valida o identificador: de 1 a 64 letras ASCII, dígitos, `-` ou `_`
+    pub fn new(id: &str) -> Result<Self, InvalidTenant> {
+        let valid = !id.is_empty()
+            && id.len() <= MAX_TENANT_LEN
+            && id
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+        if !valid {
+            return Err(InvalidTenant(id.to_string()));
+        }
+        Ok(Self { id: Arc::from(id) })
+    }
+
+    /// O tenant `DEFAULT_TENANT`
+    pub fn default_tenant() -> Self {
+        Self {
+            id: Arc::from(DEFAULT_TENANT),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl fmt::Display for TenantContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+/// `X-Tenant-Id` da requisição, se veio; 400 se ele não é válido
+#[cfg(feature = "api")]
+fn header_tenant(
+    parts: &axum::http::request::Parts,
+) -> Result<Option<TenantContext>, crate::api::ApiError> {
+    parts
+        .headers
+        .get(TENANT_HEADER)
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|id| TenantContext::new(id).ok())
+                .ok_or_else(|| {
+                    crate::api::ApiError::BadRequest("Invalid X-Tenant-Id header".to_string())
+                })
+        })
+        .transpose()
+}
+
+/// `AppState::default_tenant`, ou 400 se ele não existe
+/// (`tenancy.require_header`)
+#[cfg(feature = "api")]
+fn default_tenant(state: &crate::api::AppState) -> Result<TenantContext, crate::api::ApiError> {
+    state
+        .default_tenant
+        .clone()
+        .ok_or_else(|| crate::api::ApiError::BadRequest("Missing X-Tenant-Id header".to_string()))
+}
+
+/// Extrai o tenant do token do usuário ou, para o admin, do header
+/// `X-Tenant-Id`
+///
+/// Um header diferente do tenant do token responde 403. Sem token de
+/// usuário, o header só vale para o admin: as demais requisições ficam em
+/// `AppState::default_tenant` e, se ele não existe
+/// (`tenancy.require_header`), respondem 400.
+#[cfg(feature = "api")]
+#[axum::async_trait]
+impl axum::extract::FromRequestParts<crate::api::AppState> for TenantContext {
+    type Rejection = crate::api::ApiError;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        state: &crate::api::AppState,
+    ) -> Result<Self, Self::Rejection> {
+        use crate::api::ApiError;
+        use crate::audit::Principal;
+
+        let header = header_tenant(parts)?;
+        let principal = parts.extensions.get::<Principal>();
+        let claim = principal
+            .and_then(|principal| principal.tenant())
+            .map(|id| {
+                Self::new(id).map_err(|e| ApiError::InternalError(format!("token tenant: {e}")))
+            })
+            .transpose()?;
+        let admin = matches!(principal, Some(Principal::Admin));
+
+        match (claim, header) {
+            (Some(claim), Some(header)) if claim != header => Err(ApiError::Forbidden(
+                "X-Tenant-Id does not match the token's tenant".to_string(),
+            )),
+            (Some(tenant), _) => Ok(tenant),
+            (None, Some(tenant)) if admin => Ok(tenant),
+            (None, _) => default_tenant(state),
+        }
+    }
+}
+
+/// Tenant em que alguém sem credenciais tenta entrar (login por senha ou
+/// passkey): o do header `X-Tenant-Id`, ou `AppState::default_tenant`
+///
+/// Ao contrário do `TenantContext`, vale para qualquer um, porque só serve
+/// para procurar a conta: quem entra no tenant são as credenciais
+/// conferidas pelo handler.
+#[cfg(feature = "api")]
+#[derive(Debug, Clone)]
+pub struct LoginTenant(pub TenantContext);
+
+#[cfg(feature = "api")]
+#[axum::async_trait]
+impl axum::extract::FromRequestParts<crate::api::AppState> for LoginTenant {
+    type Rejection = crate::api::ApiError;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        state: &crate::api::AppState,
+    ) -> Result<Self, Self::Rejection> {
+        match header_tenant(parts)? {
+            Some(tenant) => Ok(Self(tenant)),
+            None => default_tenant(state).map(Self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenant_id_validation() {
+        for id in [
+            "default",
+            "acme",
+            "acme-corp_2",
+            &"a".repeat(MAX_TENANT_LEN),
+        ] {
+            assert_eq!(TenantContext::new(id).unwrap().id(), id);
+        }
+        for id in [
+            "",
+            "acme corp",
+            "acme/other",
+            "ação",
+            &"a".repeat(MAX_TENANT_LEN + 1),
+        ] {
+            assert_eq!(
+                TenantContext::new(id),
+                Err(InvalidTenant(id.to_string())),
+                "{}",
+                id
+            );
+        }
+        assert_eq!(TenantContext::default_tenant().id(), DEFAULT_TENANT);
+    }
+
+    #[cfg(feature = "api")]
+    #[tokio::test]
+    async fn test_extractor_prefers_the_token_tenant() {
+        use crate::api::AppState;
+        use crate::audit::Principal;
+        use crate::repository::InMemoryUserRepository;
+        use axum::{body::Body, http::Request, http::StatusCode, routing::get, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route(
+                "/",
+                get(|tenant: TenantContext| async move { tenant.to_string() }),
+            )
+            .with_state(AppState::new(
+                Arc::new(InMemoryUserRepository::new()),
+                Default::default(),
+            ));
+        let user = Principal::User {
+            id: 1,
+            tenant: "acme".to_string(),
+        };
+        let call = |principal: Option<Principal>, header: Option<&str>| {
+            let mut request = Request::get("/");
+            if let Some(header) = header {
+                request = request.header(TENANT_HEADER, header);
+            }
+            let mut request = request.body(Body::empty()).unwrap();
+            if let Some(principal) = principal {
+                request.extensions_mut().insert(principal);
+            }
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            }
+        };
+
+        assert_eq!(call(None, None).await.1, DEFAULT_TENANT);
+        // Sem credenciais, o header não escolhe o tenant
+        assert_eq!(call(None, Some("globex")).await.1, DEFAULT_TENANT);
+        assert_eq!(
+            call(Some(Principal::Anonymous), Some("globex")).await.1,
+            DEFAULT_TENANT
+        );
+        assert_eq!(
+            call(Some(Principal::Admin), Some("globex")).await.1,
+            "globex"
+        );
+        assert_eq!(call(Some(user.clone()), None).await.1, "acme");
+        assert_eq!(call(Some(user.clone()), Some("acme")).await.1, "acme");
+        assert_eq!(
+            call(Some(user), Some("globex")).await.0,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[cfg(feature = "api")]
+    #[tokio::test]
+    async fn test_login_tenant_comes_from_the_header() {
+        use crate::api::AppState;
+        use crate::repository::InMemoryUserRepository;
+        use axum::{body::Body, http::Request, http::StatusCode, routing::get, Router};
+        use tower::ServiceExt;
+
+        let app = |default_tenant: Option<TenantContext>| {
+            Router::new()
+                .route(
+                    "/",
+                    get(|LoginTenant(tenant): LoginTenant| async move { tenant.to_string() }),
+                )
+                .with_state(
+                    AppState::new(Arc::new(InMemoryUserRepository::new()), Default::default())
+                        .with_default_tenant(default_tenant),
+                )
+        };
+        let call = |app: Router, header: Option<&str>| {
+            let mut request = Request::get("/");
+            if let Some(header) = header {
+                request = request.header(TENANT_HEADER, header);
+            }
+            let request = request.body(Body::empty()).unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            }
+        };
+
+        let default = Some(TenantContext::default_tenant());
+        assert_eq!(call(app(default.clone()), None).await.1, DEFAULT_TENANT);
+        assert_eq!(call(app(default.clone()), Some("globex")).await.1, "globex");
+        assert_eq!(
+            call(app(default), Some("not a tenant")).await.0,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(call(app(None), None).await.0, StatusCode::BAD_REQUEST);
+        assert_eq!(call(app(None), Some("globex")).await.1, "globex");
+    }
+}
@@ -0,0 +1,108 @@
+//! Postgres descartável para testes
+//!
+//! `TestDatabase::start` sobe um Postgres em container (requer Docker),
+//! executa as migrations e devolve um `Database` pronto, junto com os
+//! usuários semeados pelas migrations. O container é removido quando o
+//! `TestDatabase` é dropado.
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use rust_app_exemplo::test_support::TestDatabase;
+//!
+//! let test_db = TestDatabase::start().await?;
+//! let users = test_db.seed_users(&[("Ana", "ana@example.com")]).await?;
+//! assert_eq!(users[0].name, "Ana");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::db::{Database, DatabaseConfig, DbUser};
+use crate::tenant::TenantContext;
+use anyhow::{Context, Result};
+use testcontainers_modules::postgres::Postgres;
+use testcontainers_modules::testcontainers::runners::AsyncRunner;
+use testcontainers_modules::testcontainers::{ContainerAsync, ImageExt};
+
+/// Tag da imagem `postgres` usada nos testes
+pub const POSTGRES_TAG: &str = "16-alpine";
+
+/// Banco de dados descartável para testes
+pub struct TestDatabase {
+    // Mantém o container vivo enquanto o banco estiver em uso
+    _container: ContainerAsync<Postgres>,
+    db: Database,
+    url: String,
+    /// Usuários inseridos pelas migrations de seed (no tenant padrão)
+    pub seeded_users: Vec<DbUser>,
+}
+
+impl TestDatabase {
+    /// Sobe o container, conecta e executa as migrations
+    pub async fn start() -> Result<Self> {
+        let container = Postgres::default()
+            .with_tag(POSTGRES_TAG)
+            .start()
+            .await
+            .context("failed to start Postgres container (is Docker running?)")?;
+
+        let url = format!(
+            "postgres://postgres:postgres@{}:{}/postgres",
+            container.get_host().await?,
+            container.get_host_port_ipv4(5432).await?
+        );
+
+        let db = Database::new(DatabaseConfig {
+            url: Some(url.clone()),
+            ..Default::default()
+        })
+        .await?;
+        db.migrate().await?;
+
+        let seeded_users = DbUser::list_all(db.pool(), &TenantContext::default_tenant()).await?;
+
+        Ok(Self {
+            _container: container,
+            db,
+            url,
+            seeded_users,
+        })
+    }
+
+    /// Banco conectado e migrado
+    pub fn db(&self) -> &Database {
+        &self.db
+    }
+
+    /// URL de conexão do container
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Insere usuários `(nome, email)` no tenant padrão e os retorna na
+    /// mesma ordem
+    pub async fn seed_users(&self, users: &[(&str, &str)]) -> Result<Vec<DbUser>> {
+        self.seed_users_in(&TenantContext::default_tenant(), users)
+            .await
+    }
+
+    /// Como `seed_users`, no `tenant`
+    pub async fn seed_users_in(
+        &self,
+        tenant: &TenantContext,
+        users: &[(&str, &str)],
+    ) -> Result<Vec<DbUser>> {
+        let mut created = Vec::with_capacity(users.len());
+        for (name, email) in users {
+            created.push(DbUser::create(self.db.pool(), tenant, name, email).await?);
+        }
+        Ok(created)
+    }
+
+    /// Remove todos os usuários (inclusive os semeados)
+    pub async fn truncate_users(&self) -> Result<()> {
+        sqlx::query("TRUNCATE users RESTART IDENTITY")
+            .execute(self.db.pool())
+            .await?;
+        Ok(())
+    }
+}
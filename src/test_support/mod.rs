@@ -0,0 +1,66 @@
+//! Utilitários para testes (feature "test-util")
+//!
+//! - `strategies`: estratégias proptest para os tipos do domínio
+//! - `TestDatabase`: Postgres em container, migrado e pronto para uso
+//! - `assert_text_eq`: comparação de textos (snapshots, arquivos gerados)
+//!   que, ao falhar, mostra o diff em vez dos dois textos inteiros
+
+use crate::string_utils;
+use std::io::IsTerminal;
+
+pub mod strategies;
+
+#[cfg(feature = "test-util")]
+mod database;
+
+#[cfg(feature = "test-util")]
+pub use database::{TestDatabase, POSTGRES_TAG};
+
+/// Falha se `actual` for diferente de `expected`, mostrando o diff por
+/// linha (colorido quando o stderr é um terminal e `NO_COLOR` não está
+/// definida)
+#[track_caller]
+pub fn assert_text_eq(expected: &str, actual: &str) {
+    if expected == actual {
+        return;
+    }
+    let color = std::env::var_os("NO_COLOR").is_none() && std::io::stderr().is_terminal();
+    panic!(
+        "texts differ (- expected, + actual):\n{}",
+        text_diff(expected, actual, color)
+    );
+}
+
+fn text_diff(expected: &str, actual: &str, color: bool) -> String {
+    let changes = string_utils::diff(expected, actual);
+    if string_utils::is_unchanged(&changes) {
+        // Só o final de linha difere (`\r\n` ou o `\n` do fim)
+        return format!("{:?}\n{:?}\n", expected, actual);
+    }
+    string_utils::render(&changes, color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assert_text_eq_passes_on_equal_texts() {
+        assert_text_eq("a\nb\n", "a\nb\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "texts differ")]
+    fn test_assert_text_eq_panics_on_different_texts() {
+        assert_text_eq("a\nb\nc", "a\nx\nc");
+    }
+
+    #[test]
+    fn test_text_diff() {
+        assert_eq!(
+            text_diff("a\nb\nc", "a\nx\nc", false),
+            "  a\n- b\n+ x\n  c\n"
+        );
+        assert_eq!(text_diff("a\n", "a", false), "\"a\\n\"\n\"a\"\n");
+    }
+}
@@ -0,0 +1,155 @@
+//! Estratégias proptest para os tipos do domínio
+//!
+//! `User` e `DbUser` implementam `Arbitrary`, então `any::<User>()` funciona
+//! direto; as funções abaixo cobrem valores isolados (emails, nomes) e as
+//! faixas de entrada válidas das funções matemáticas.
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use rust_app_exemplo::test_support::strategies::email_address;
+//!
+//! proptest!(|(email in email_address())| {
+//!     prop_assert!(email.contains('@'));
+//! });
+//! ```
+
+use crate::models::{DbUser, UserStatus, DEFAULT_ROLE};
+use crate::User;
+use proptest::prelude::*;
+
+/// Maior `n` cujo `fibonacci_optimized(n)` cabe em `u64`
+pub const MAX_FIBONACCI_INPUT: u64 = 93;
+
+/// Maior `n` cujo `factorial(n)` cabe em `u64`
+pub const MAX_FACTORIAL_INPUT: u64 = 20;
+
+/// Endereços de email sintaticamente válidos (ex.: `ana.silva@exemplo.com.br`)
+pub fn email_address() -> impl Strategy<Value = String> {
+    "[a-z][a-z0-9]{0,10}(\\.[a-z0-9]{1,10})?@[a-z][a-z0-9]{0,10}\\.(com|org|net|com\\.br)"
+}
+
+/// Nomes de pessoa com uma a três palavras capitalizadas
+pub fn person_name() -> impl Strategy<Value = String> {
+    "[A-Z][a-z]{1,12}( [A-Z][a-z]{1,12}){0,2}"
+}
+
+/// Entradas de `fibonacci_optimized` que não estouram `u64`
+pub fn fibonacci_input() -> impl Strategy<Value = u64> {
+    0..=MAX_FIBONACCI_INPUT
+}
+
+/// Entradas de `factorial` que não estouram `u64`
+pub fn factorial_input() -> impl Strategy<Value = u64> {
+    0..=MAX_FACTORIAL_INPUT
+}
+
+impl Arbitrary for User {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (any::<u64>(), person_name(), email_address(), any::<bool>())
+            .prop_map(|(id, name, email, active)| User {
+                id,
+                name,
+                email,
+                active,
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for DbUser {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            1..=i32::MAX,
+            person_name(),
+            email_address(),
+            prop::sample::select(UserStatus::ALL.to_vec()),
+        )
+            .prop_map(|(id, name, email, status)| DbUser {
+                id,
+                name,
+                email,
+                status,
+                created_at: None,
+                deletion_scheduled_at: None,
+                last_login_at: None,
+                role: DEFAULT_ROLE.to_string(),
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{factorial, fibonacci_optimized, is_prime, string_utils};
+
+    proptest! {
+        #[test]
+        fn fibonacci_recurrence(n in 0..=MAX_FIBONACCI_INPUT - 2) {
+            prop_assert_eq!(
+                fibonacci_optimized(n + 2),
+                fibonacci_optimized(n) + fibonacci_optimized(n + 1)
+            );
+        }
+
+        #[test]
+        fn factorial_recurrence(n in 0..MAX_FACTORIAL_INPUT) {
+            prop_assert_eq!(factorial(n + 1), (n + 1) * factorial(n));
+        }
+
+        #[test]
+        fn composites_are_not_prime(a in 2u64..10_000, b in 2u64..10_000) {
+            prop_assert!(!is_prime(a * b));
+        }
+
+        #[test]
+        fn is_prime_matches_trial_division(n in 0u64..20_000) {
+            let expected = n >= 2 && (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0);
+            prop_assert_eq!(is_prime(n), expected);
+        }
+
+        #[test]
+        fn reverse_is_an_involution(s in ".*") {
+            prop_assert_eq!(string_utils::reverse(&string_utils::reverse(&s)), s);
+        }
+
+        #[test]
+        fn vowel_count_is_bounded(s in ".*") {
+            prop_assert!(string_utils::count_vowels(&s) <= s.chars().count());
+        }
+
+        #[test]
+        fn vowel_count_matches_reference(s in ".*") {
+            prop_assert_eq!(string_utils::count_vowels(&s), string_utils::count_vowels_chars(&s));
+        }
+
+        #[test]
+        fn user_serde_roundtrip(user in any::<User>()) {
+            let json = serde_json::to_string(&user).unwrap();
+            prop_assert_eq!(serde_json::from_str::<User>(&json).unwrap(), user);
+        }
+
+        #[test]
+        fn user_activation_toggles(mut user in any::<User>()) {
+            user.deactivate();
+            prop_assert!(!user.active);
+            user.activate();
+            prop_assert!(user.active);
+        }
+    }
+
+    #[cfg(feature = "api")]
+    proptest! {
+        #[test]
+        fn generated_emails_are_valid(email in email_address()) {
+            use crate::validation::{Email, Rule};
+            prop_assert!(Email.check(&email).is_ok(), "{} should be valid", email);
+        }
+    }
+}
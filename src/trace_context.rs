@@ -0,0 +1,233 @@
+//! Propagação do W3C Trace Context (`traceparent` e `tracestate`)
+//!
+//! Cada requisição HTTP (e cada mensagem da fila) roda dentro de um
+//! `TraceContext`: o recebido no `traceparent`, com um `span_id` novo, ou
+//! um trace novo. `TraceContext::current` o devolve em qualquer ponto da
+//! task; os eventos publicados o levam (também pelo outbox) até as chamadas
+//! de saída, que mandam um `traceparent` filho (webhooks, NATS). Assim os
+//! traces de quem chama e de quem é chamado se encontram.
+//!
+//! <https://www.w3.org/TR/trace-context/>
+
+use rand::RngCore;
+use std::fmt;
+use std::future::Future;
+
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+pub const TRACESTATE_HEADER: &str = "tracestate";
+
+/// Maior `tracestate` repassado (a especificação pede suporte a 512)
+const MAX_TRACESTATE_LEN: usize = 512;
+
+tokio::task_local! {
+    static CURRENT: TraceContext;
+}
+
+/// Posição de uma operação num trace distribuído
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    pub trace_id: [u8; 16],
+    /// Identifica esta operação; é o pai das chamadas que ela faz
+    pub span_id: [u8; 8],
+    pub sampled: bool,
+    /// Dados dos fornecedores de tracing, repassados sem interpretação
+    pub tracestate: Option<String>,
+}
+
+impl TraceContext {
+    /// Início de um trace (amostrado)
+    pub fn new_root() -> Self {
+        let mut trace_id = [0u8; 16];
+        while trace_id == [0; 16] {
+            rand::thread_rng().fill_bytes(&mut trace_id);
+        }
+        Self {
+            trace_id,
+            span_id: random_span_id(),
+            sampled: true,
+            tracestate: None,
+        }
+    }
+
+    /// Lê os headers recebidos; `None` se o `traceparent` falta ou é
+    /// inválido (aí o `tracestate` também é descartado)
+    pub fn parse(traceparent: &str, tracestate: Option<&str>) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let span_id = parts.next()?;
+        let flags = parts.next()?;
+
+        // Versões futuras podem acrescentar campos; a 00 tem exatamente 4
+        let valid_version = version.len() == 2 && version != "ff" && is_lower_hex(version);
+        if !valid_version || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        if trace_id.len() != 32 || span_id.len() != 16 || flags.len() != 2 {
+            return None;
+        }
+        if ![trace_id, span_id, flags]
+            .iter()
+            .all(|part| is_lower_hex(part))
+        {
+            return None;
+        }
+
+        let mut context = Self {
+            trace_id: [0; 16],
+            span_id: [0; 8],
+            sampled: false,
+            tracestate: None,
+        };
+        decode_hex(trace_id, &mut context.trace_id);
+        decode_hex(span_id, &mut context.span_id);
+        if context.trace_id == [0; 16] || context.span_id == [0; 8] {
+            return None;
+        }
+        context.sampled = u8::from_str_radix(flags, 16).ok()? & 1 == 1;
+        context.tracestate = tracestate
+            .map(str::trim)
+            .filter(|state| !state.is_empty() && state.len() <= MAX_TRACESTATE_LEN)
+            .map(str::to_owned);
+        Some(context)
+    }
+
+    /// O contexto de uma operação feita por esta: mesmo trace, `span_id`
+    /// novo
+    pub fn child(&self) -> Self {
+        Self {
+            span_id: random_span_id(),
+            ..self.clone()
+        }
+    }
+
+    /// Valor do header `traceparent`
+    pub fn traceparent(&self) -> String {
+        format!(
+            "00-{}-{}-{:02x}",
+            self.trace_id_hex(),
+            encode_hex(&self.span_id),
+            u8::from(self.sampled)
+        )
+    }
+
+    pub fn trace_id_hex(&self) -> String {
+        encode_hex(&self.trace_id)
+    }
+
+    /// Headers de uma chamada de saída feita por esta operação
+    pub fn outgoing_headers(&self) -> Vec<(&'static str, String)> {
+        let child = self.child();
+        let mut headers = vec![(TRACEPARENT_HEADER, child.traceparent())];
+        if let Some(state) = child.tracestate {
+            headers.push((TRACESTATE_HEADER, state));
+        }
+        headers
+    }
+
+    /// O contexto da task atual, se ela roda dentro de `scope`
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+
+    /// Executa `future` com este contexto como o atual
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+}
+
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.traceparent())
+    }
+}
+
+fn random_span_id() -> [u8; 8] {
+    let mut span_id = [0u8; 8];
+    while span_id == [0; 8] {
+        rand::thread_rng().fill_bytes(&mut span_id);
+    }
+    span_id
+}
+
+fn is_lower_hex(s: &str) -> bool {
+    s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// `hex` já validado por `is_lower_hex`, com o dobro do tamanho de `out`
+fn decode_hex(hex: &str, out: &mut [u8]) {
+    for (byte, pair) in out.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(pair).unwrap_or("00");
+        *byte = u8::from_str_radix(digits, 16).unwrap_or(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_parse_and_format() {
+        let context = TraceContext::parse(TRACEPARENT, Some("congo=t61rcWkgMzE")).unwrap();
+        assert_eq!(context.trace_id_hex(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert!(context.sampled);
+        assert_eq!(context.tracestate.as_deref(), Some("congo=t61rcWkgMzE"));
+        assert_eq!(context.traceparent(), TRACEPARENT);
+
+        let unsampled = TraceContext::parse(&TRACEPARENT.replace("-01", "-00"), None).unwrap();
+        assert!(!unsampled.sampled);
+
+        // Versões futuras com campos a mais são aceitas
+        assert!(TraceContext::parse(&format!("01{}-extra", &TRACEPARENT[2..]), None).is_some());
+    }
+
+    #[test]
+    fn test_rejects_invalid_traceparent() {
+        for invalid in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01",
+        ] {
+            assert_eq!(
+                TraceContext::parse(invalid, Some("a=b")),
+                None,
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_child_keeps_trace() {
+        let context = TraceContext::parse(TRACEPARENT, Some("a=b")).unwrap();
+        let child = context.child();
+        assert_eq!(child.trace_id, context.trace_id);
+        assert_ne!(child.span_id, context.span_id);
+
+        let headers = context.outgoing_headers();
+        assert_eq!(headers[0].0, TRACEPARENT_HEADER);
+        assert!(headers[0]
+            .1
+            .starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+        assert_eq!(headers[1], (TRACESTATE_HEADER, "a=b".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_current_follows_scope() {
+        assert_eq!(TraceContext::current(), None);
+        let root = TraceContext::new_root();
+        let inside = root.clone().scope(async { TraceContext::current() }).await;
+        assert_eq!(inside, Some(root));
+    }
+}
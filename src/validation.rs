@@ -0,0 +1,385 @@
+//! Framework de validação de domínio para DTOs
+//!
+//! Regras componíveis (tamanho, regex, email, intervalo) aplicadas campo a
+//! campo, produzindo um mapa de erros por campo. Os erros do crate
+//! `validator` podem ser convertidos para o mesmo formato, de modo que a API
+//! responda sempre com a mesma estrutura.
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Erro de validação de um campo
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub code: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Mapa de erros por campo (ordenado para respostas estáveis)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct FieldErrors(BTreeMap<String, Vec<FieldError>>);
+
+impl FieldErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adiciona um erro a um campo
+    pub fn add(&mut self, field: impl Into<String>, error: FieldError) {
+        self.0.entry(field.into()).or_default().push(error);
+    }
+
+    /// Incorpora os erros de outro mapa
+    pub fn merge(&mut self, other: FieldErrors) {
+        for (field, errors) in other.0 {
+            self.0.entry(field).or_default().extend(errors);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Erros de um campo específico
+    pub fn get(&self, field: &str) -> Option<&[FieldError]> {
+        self.0.get(field).map(Vec::as_slice)
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// Retorna `Ok(())` se não houver erros
+    pub fn into_result(self) -> Result<(), FieldErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for FieldErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (field, errors) in &self.0 {
+            for error in errors {
+                if !first {
+                    write!(f, "; ")?;
+                }
+                write!(f, "{}: {}", field, error.message)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FieldErrors {}
+
+impl From<&validator::ValidationErrors> for FieldErrors {
+    fn from(errors: &validator::ValidationErrors) -> Self {
+        let mut result = FieldErrors::new();
+        collect_validator_errors(errors, "", &mut result);
+        result
+    }
+}
+
+impl From<validator::ValidationErrors> for FieldErrors {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        FieldErrors::from(&errors)
+    }
+}
+
+fn collect_validator_errors(
+    errors: &validator::ValidationErrors,
+    prefix: &str,
+    out: &mut FieldErrors,
+) {
+    use validator::ValidationErrorsKind;
+
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                for error in field_errors {
+                    let message = error
+                        .message
+                        .as_ref()
+                        .map(|m| m.to_string())
+                        .unwrap_or_else(|| format!("failed '{}' validation", error.code));
+                    out.add(
+                        path.clone(),
+                        FieldError::new(error.code.to_string(), message),
+                    );
+                }
+            }
+            ValidationErrorsKind::Struct(nested) => collect_validator_errors(nested, &path, out),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_validator_errors(nested, &format!("{}[{}]", path, index), out);
+                }
+            }
+        }
+    }
+}
+
+/// Uma regra de validação aplicável a valores do tipo `T`
+pub trait Rule<T: ?Sized> {
+    fn check(&self, value: &T) -> Result<(), FieldError>;
+}
+
+/// Tamanho em caracteres de uma string
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Length {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
+impl Length {
+    pub fn min(min: usize) -> Self {
+        Self {
+            min: Some(min),
+            max: None,
+        }
+    }
+
+    pub fn max(max: usize) -> Self {
+        Self {
+            min: None,
+            max: Some(max),
+        }
+    }
+
+    pub fn between(min: usize, max: usize) -> Self {
+        Self {
+            min: Some(min),
+            max: Some(max),
+        }
+    }
+}
+
+impl<T: AsRef<str> + ?Sized> Rule<T> for Length {
+    fn check(&self, value: &T) -> Result<(), FieldError> {
+        let len = value.as_ref().chars().count();
+        if self.min.is_some_and(|min| len < min) || self.max.is_some_and(|max| len > max) {
+            let message = match (self.min, self.max) {
+                (Some(min), Some(max)) => format!("length must be between {} and {}", min, max),
+                (Some(min), None) => format!("length must be at least {}", min),
+                (None, Some(max)) => format!("length must be at most {}", max),
+                (None, None) => unreachable!(),
+            };
+            return Err(FieldError::new("length", message));
+        }
+        Ok(())
+    }
+}
+
+/// Valor deve casar com uma expressão regular
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    regex: Regex,
+    code: String,
+}
+
+impl Pattern {
+    /// Cria a regra; falha se a expressão for inválida
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            regex: Regex::new(pattern)?,
+            code: "regex".to_string(),
+        })
+    }
+
+    /// Define o código de erro reportado (padrão: `regex`)
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = code.into();
+        self
+    }
+}
+
+impl<T: AsRef<str> + ?Sized> Rule<T> for Pattern {
+    fn check(&self, value: &T) -> Result<(), FieldError> {
+        if self.regex.is_match(value.as_ref()) {
+            Ok(())
+        } else {
+            Err(FieldError::new(
+                self.code.clone(),
+                format!("must match pattern {}", self.regex.as_str()),
+            ))
+        }
+    }
+}
+
+/// Endereço de email válido (mesma verificação do crate `validator`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Email;
+
+impl<T: AsRef<str> + ?Sized> Rule<T> for Email {
+    fn check(&self, value: &T) -> Result<(), FieldError> {
+        use validator::ValidateEmail;
+
+        if value.as_ref().validate_email() {
+            Ok(())
+        } else {
+            Err(FieldError::new("email", "must be a valid email address"))
+        }
+    }
+}
+
+/// Valor numérico dentro de um intervalo (inclusivo)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Range<N> {
+    pub min: Option<N>,
+    pub max: Option<N>,
+}
+
+impl<N> Range<N> {
+    pub fn between(min: N, max: N) -> Self {
+        Self {
+            min: Some(min),
+            max: Some(max),
+        }
+    }
+}
+
+impl<N: PartialOrd + fmt::Display> Rule<N> for Range<N> {
+    fn check(&self, value: &N) -> Result<(), FieldError> {
+        let below = self.min.as_ref().is_some_and(|min| value < min);
+        let above = self.max.as_ref().is_some_and(|max| value > max);
+        if below || above {
+            let message = match (&self.min, &self.max) {
+                (Some(min), Some(max)) => format!("must be between {} and {}", min, max),
+                (Some(min), None) => format!("must be at least {}", min),
+                (None, Some(max)) => format!("must be at most {}", max),
+                (None, None) => unreachable!(),
+            };
+            return Err(FieldError::new("range", message));
+        }
+        Ok(())
+    }
+}
+
+/// Acumulador de validações campo a campo
+///
+/// ```
+/// use rust_app_exemplo::validation::{Email, Length, Validator};
+///
+/// let result = Validator::new()
+///     .field("name", "Maria", &[&Length::between(1, 255)])
+///     .field("email", "maria@example.com", &[&Email])
+///     .finish();
+/// assert!(result.is_ok());
+/// ```
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: FieldErrors,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aplica as regras ao valor, registrando os erros sob `name`
+    pub fn field<T: ?Sized>(mut self, name: &str, value: &T, rules: &[&dyn Rule<T>]) -> Self {
+        for rule in rules {
+            if let Err(error) = rule.check(value) {
+                self.errors.add(name, error);
+            }
+        }
+        self
+    }
+
+    /// Incorpora erros do crate `validator`
+    pub fn extend(mut self, errors: impl Into<FieldErrors>) -> Self {
+        self.errors.merge(errors.into());
+        self
+    }
+
+    pub fn finish(self) -> Result<(), FieldErrors> {
+        self.errors.into_result()
+    }
+}
+
+/// Validação de domínio implementada por DTOs
+pub trait ValidateDomain {
+    fn validate_domain(&self) -> Result<(), FieldErrors>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Payload {
+        #[validate(length(min = 1))]
+        name: String,
+        #[validate(email)]
+        email: String,
+    }
+
+    #[test]
+    fn test_rules() {
+        assert!(Length::between(1, 3).check("abc").is_ok());
+        assert!(Length::between(1, 3).check("").is_err());
+        assert!(Length::max(3).check("abcd").is_err());
+        assert!(Email.check("joao@example.com").is_ok());
+        assert!(Email.check("joao").is_err());
+        assert!(Range::between(1, 10).check(&5).is_ok());
+        assert!(Range::between(1, 10).check(&11).is_err());
+
+        let slug = Pattern::new("^[a-z-]+$").unwrap().with_code("slug");
+        assert!(slug.check("rust-app").is_ok());
+        assert_eq!(slug.check("Rust App").unwrap_err().code, "slug");
+    }
+
+    #[test]
+    fn test_validator_collects_field_errors() {
+        let errors = Validator::new()
+            .field("name", "", &[&Length::min(1)])
+            .field("email", "invalid", &[&Email, &Length::max(3)])
+            .field("age", &200, &[&Range::between(0, 150)])
+            .finish()
+            .unwrap_err();
+
+        assert_eq!(
+            errors.fields().collect::<Vec<_>>(),
+            ["age", "email", "name"]
+        );
+        assert_eq!(errors.get("email").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_bridge_from_validator() {
+        let payload = Payload {
+            name: String::new(),
+            email: "invalid".to_string(),
+        };
+        let errors = FieldErrors::from(payload.validate().unwrap_err());
+
+        assert_eq!(errors.get("name").unwrap()[0].code, "length");
+        assert_eq!(errors.get("email").unwrap()[0].code, "email");
+
+        let json = serde_json::to_value(&errors).unwrap();
+        assert_eq!(json["email"][0]["code"], "email");
+    }
+}
@@ -0,0 +1,57 @@
+//! Exports para JavaScript (feature "wasm")
+//!
+//! Expõe as funções matemáticas e de `string_utils` via `wasm-bindgen`.
+//! Compile com:
+//!
+//! ```bash
+//! cargo build --lib --release --target wasm32-unknown-unknown --no-default-features --features wasm
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/rust_app_exemplo.wasm
+//! ```
+//!
+//! No JavaScript, os `u64` viram `BigInt`:
+//!
+//! ```js
+//! import init, { fibonacci, toTitleCase } from "./pkg/rust_app_exemplo.js";
+//!
+//! await init();
+//! fibonacci(10n); // 55n
+//! toTitleCase("olá mundo"); // "Olá Mundo"
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+/// N-ésimo número de Fibonacci (iterativo)
+#[wasm_bindgen]
+pub fn fibonacci(n: u64) -> u64 {
+    crate::fibonacci_optimized(n)
+}
+
+/// Fatorial de `n`
+#[wasm_bindgen]
+pub fn factorial(n: u64) -> u64 {
+    crate::factorial(n)
+}
+
+/// Verifica se `n` é primo
+#[wasm_bindgen(js_name = isPrime)]
+pub fn is_prime(n: u64) -> bool {
+    crate::is_prime(n)
+}
+
+/// Primeira letra de cada palavra em maiúscula
+#[wasm_bindgen(js_name = toTitleCase)]
+pub fn to_title_case(s: &str) -> String {
+    crate::string_utils::to_title_case(s)
+}
+
+/// Número de vogais (sem acento) em `s`
+#[wasm_bindgen(js_name = countVowels)]
+pub fn count_vowels(s: &str) -> usize {
+    crate::string_utils::count_vowels(s)
+}
+
+/// Inverte `s` caractere a caractere
+#[wasm_bindgen]
+pub fn reverse(s: &str) -> String {
+    crate::string_utils::reverse(s)
+}
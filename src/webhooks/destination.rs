@@ -0,0 +1,170 @@
+//! Destinos aceitos para os webhooks de saída
+//!
+//! A URL de um webhook é escolhida por quem o cadastra, mas quem a chama é o
+//! servidor; sem restrição, um webhook alcançaria a rede interna (o banco,
+//! o metadata da nuvem em `169.254.169.254`). Por isso só valem URLs `http`
+//! e `https` cujos endereços sejam públicos: o cadastro confere a URL
+//! (`check`), a entrega confere de novo e o cliente das entregas resolve os
+//! nomes com o `PublicResolver`, que descarta os endereços internos na hora
+//! de conectar (um nome que passe a apontar para a rede interna depois da
+//! conferência não adianta). Redirecionamentos não são seguidos.
+//!
+//! `webhooks.allow_private_destinations` desliga a restrição
+//! (desenvolvimento e testes).
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// URL recusada como destino de webhook
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DestinationError {
+    #[error("invalid webhook URL: {0}")]
+    InvalidUrl(String),
+    #[error("webhook URL must use http or https, not '{0}'")]
+    Scheme(String),
+    #[error("could not resolve webhook host '{0}'")]
+    Unresolvable(String),
+    #[error("webhook host '{0}' points to a private or reserved address ({1})")]
+    Private(String, IpAddr),
+}
+
+/// Se o endereço é alcançável pela internet: fora das faixas privadas
+/// (RFC 1918, `fc00::/7`), de loopback, link-local (`169.254.0.0/16`,
+/// `fe80::/10`), CGNAT (`100.64.0.0/10`), documentação, multicast e
+/// reservadas
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        || a >= 240
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 198 && (18..20).contains(&b)))
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let [first, second, ..] = ip.segments();
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        || first & 0xfe00 == 0xfc00
+        || first & 0xffc0 == 0xfe80
+        || (first == 0x2001 && second == 0x0db8))
+}
+
+/// Confere a URL de um webhook: `http` ou `https` e todos os endereços do
+/// host públicos
+pub async fn check(url: &str) -> Result<(), DestinationError> {
+    let url = Url::parse(url).map_err(|e| DestinationError::InvalidUrl(e.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(DestinationError::Scheme(url.scheme().to_string()));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| DestinationError::InvalidUrl("missing host".to_string()))?;
+    let port = url.port_or_known_default().unwrap_or(443);
+
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    let addresses: Vec<IpAddr> = match literal.parse::<IpAddr>() {
+        Ok(ip) => vec![ip],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|_| DestinationError::Unresolvable(host.to_string()))?
+            .map(|addr| addr.ip())
+            .collect(),
+    };
+    if addresses.is_empty() {
+        return Err(DestinationError::Unresolvable(host.to_string()));
+    }
+    match addresses.into_iter().find(|ip| !is_public(*ip)) {
+        Some(ip) => Err(DestinationError::Private(host.to_string(), ip)),
+        None => Ok(()),
+    }
+}
+
+/// Resolve os nomes pelo sistema, como o resolvedor padrão, mas só devolve
+/// os endereços públicos (ver o módulo)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str();
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addresses.is_empty() {
+                return Err(DestinationError::Unresolvable(host.to_string()).into());
+            }
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public() {
+        for ip in ["1.1.1.1", "93.184.215.14", "2606:4700::1111"] {
+            assert!(is_public(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "127.0.0.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:10.0.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        assert_eq!(check("https://1.1.1.1/hook").await, Ok(()));
+        assert_eq!(check("http://[2606:4700::1111]:8080/hook").await, Ok(()));
+        assert_eq!(
+            check("http://169.254.169.254/latest/meta-data").await,
+            Err(DestinationError::Private(
+                "169.254.169.254".to_string(),
+                "169.254.169.254".parse().unwrap()
+            ))
+        );
+        assert!(matches!(
+            check("http://localhost:5432/").await,
+            Err(DestinationError::Private(..))
+        ));
+        assert!(matches!(
+            check("http://[::1]/").await,
+            Err(DestinationError::Private(..))
+        ));
+        assert_eq!(
+            check("file:///etc/passwd").await,
+            Err(DestinationError::Scheme("file".to_string()))
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! Entrega dos eventos aos webhooks, com retentativas e backoff exponencial
+//!
+//! Sem `webhooks.allow_private_destinations`, cada entrega confere o destino
+//! de novo e o cliente só conecta em endereços públicos (ver `destination`).
+
+use super::destination::{self, PublicResolver};
+use super::{
+    sign, NewDelivery, Webhook, WebhookStore, EVENT_HEADER, ID_HEADER, SIGNATURE_HEADER,
+    TIMESTAMP_HEADER,
+};
+use crate::config::WebhooksConfig;
+use crate::events::{Event, EventBus};
+use crate::tenant::TenantContext;
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// Assina o `EventBus` e entrega cada evento aos webhooks interessados
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    store: Arc<dyn WebhookStore>,
+    client: reqwest::Client,
+    config: WebhooksConfig,
+}
+
+impl WebhookDispatcher {
+    pub fn new(store: Arc<dyn WebhookStore>, config: WebhooksConfig) -> Result<Self> {
+        let mut client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .redirect(reqwest::redirect::Policy::none());
+        if !config.allow_private_destinations {
+            client = client.dns_resolver(Arc::new(PublicResolver));
+        }
+        let client = client
+            .build()
+            .context("failed to build webhook HTTP client")?;
+
+        Ok(Self {
+            store,
+            client,
+            config,
+        })
+    }
+
+    /// Processa os eventos do barramento em segundo plano
+    pub fn spawn(self, bus: &EventBus) -> JoinHandle<()> {
+        let mut events = bus.subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        let event_id = event.id;
+                        if let Err(e) = self.dispatch(event).await {
+                            tracing::error!(error = %e, event_id, "Failed to dispatch event");
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "Webhook dispatcher lagged behind; events dropped");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Inicia uma entrega, em segundo plano, para cada webhook interessado
+    /// do tenant do evento
+    ///
+    /// Eventos remotos são ignorados: a instância de origem já os entrega.
+    /// Só falha se não conseguir carregar os webhooks.
+    pub async fn dispatch(&self, event: Event) -> Result<()> {
+        if event.remote {
+            return Ok(());
+        }
+
+        let tenant = TenantContext::new(&event.tenant)?;
+        let webhooks = self
+            .store
+            .list(&tenant)
+            .await
+            .context("failed to load webhooks")?;
+
+        let event = Arc::new(event);
+        for webhook in webhooks
+            .into_iter()
+            .filter(|w| w.wants(event.payload.name()))
+        {
+            let dispatcher = self.clone();
+            let event = Arc::clone(&event);
+            tokio::spawn(async move { dispatcher.deliver(&webhook, &event).await });
+        }
+        Ok(())
+    }
+
+    /// Entrega o evento, repetindo em caso de falha; `true` se foi aceito
+    pub async fn deliver(&self, webhook: &Webhook, event: &Event) -> bool {
+        let body = serde_json::to_vec(event).expect("events are always serializable");
+        let max_attempts = self.config.max_attempts.max(1);
+
+        // Destino recusado não é repetido: registra a tentativa e desiste
+        if !self.config.allow_private_destinations {
+            if let Err(e) = destination::check(&webhook.url).await {
+                tracing::error!(webhook_id = webhook.id, error = %e, "Refusing webhook delivery");
+                let record = NewDelivery {
+                    webhook_id: webhook.id,
+                    event_id: event.id as i64,
+                    event_type: event.payload.name().to_string(),
+                    attempt: 1,
+                    status_code: None,
+                    error: Some(e.to_string()),
+                    success: false,
+                    duration_ms: 0,
+                };
+                if let Err(e) = self.store.record_delivery(record).await {
+                    tracing::error!(error = %e, webhook_id = webhook.id, "Failed to record webhook delivery");
+                }
+                return false;
+            }
+        }
+
+        for attempt in 1..=max_attempts {
+            let started = Instant::now();
+            let timestamp = chrono::Utc::now().timestamp();
+
+            let mut request = self
+                .client
+                .post(&webhook.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(ID_HEADER, event.id)
+                .header(EVENT_HEADER, event.payload.name())
+                .header(TIMESTAMP_HEADER, timestamp)
+                .header(SIGNATURE_HEADER, sign(&webhook.secret, timestamp, &body));
+            // Cada tentativa é uma chamada filha do trace que gerou o evento
+            for (name, value) in event.trace.iter().flat_map(|t| t.outgoing_headers()) {
+                request = request.header(name, value);
+            }
+            let result = request.body(body.clone()).send().await;
+
+            let (status_code, error) = match result {
+                Ok(response) if response.status().is_success() => {
+                    (Some(response.status().as_u16()), None)
+                }
+                Ok(response) => (
+                    Some(response.status().as_u16()),
+                    Some(format!("HTTP {}", response.status())),
+                ),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            let success = error.is_none();
+
+            let record = NewDelivery {
+                webhook_id: webhook.id,
+                event_id: event.id as i64,
+                event_type: event.payload.name().to_string(),
+                attempt: attempt as i32,
+                status_code: status_code.map(i32::from),
+                error: error.clone(),
+                success,
+                duration_ms: started.elapsed().as_millis() as i64,
+            };
+            if let Err(e) = self.store.record_delivery(record).await {
+                tracing::error!(error = %e, webhook_id = webhook.id, "Failed to record webhook delivery");
+            }
+
+            if success {
+                tracing::debug!(
+                    webhook_id = webhook.id,
+                    event_id = event.id,
+                    attempt,
+                    "Webhook delivered"
+                );
+                return true;
+            }
+
+            tracing::warn!(
+                webhook_id = webhook.id,
+                event_id = event.id,
+                attempt,
+                error = error.as_deref().unwrap_or_default(),
+                "Webhook delivery failed"
+            );
+
+            if attempt < max_attempts {
+                tokio::time::sleep(backoff(&self.config, attempt)).await;
+            }
+        }
+
+        tracing::error!(
+            webhook_id = webhook.id,
+            event_id = event.id,
+            attempts = max_attempts,
+            "Giving up on webhook delivery"
+        );
+        false
+    }
+}
+
+/// Espera após a tentativa `attempt` (1, 2, ...): dobra a cada falha, até o limite
+fn backoff(config: &WebhooksConfig, attempt: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+    Duration::from_millis(
+        config
+            .initial_backoff_ms
+            .saturating_mul(factor)
+            .min(config.max_backoff_ms),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::DomainEvent;
+    use crate::webhooks::{verify, InMemoryWebhookStore, NewWebhook};
+    use axum::{body::Bytes, http::HeaderMap, http::StatusCode, routing::post, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn acme() -> TenantContext {
+        TenantContext::new("acme").unwrap()
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_limit() {
+        let config = WebhooksConfig {
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1_000,
+            ..Default::default()
+        };
+        assert_eq!(backoff(&config, 1), Duration::from_millis(100));
+        assert_eq!(backoff(&config, 2), Duration::from_millis(200));
+        assert_eq!(backoff(&config, 4), Duration::from_millis(800));
+        assert_eq!(backoff(&config, 5), Duration::from_millis(1_000));
+        assert_eq!(backoff(&config, 100), Duration::from_millis(1_000));
+    }
+
+    #[tokio::test]
+    async fn test_deliver_retries_and_signs() {
+        // Falha na primeira chamada; depois aceita se a assinatura conferir
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: Bytes| async move {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return StatusCode::SERVICE_UNAVAILABLE;
+                }
+                let header = |name: &str| headers[name].to_str().unwrap().to_string();
+                let timestamp: i64 = header(TIMESTAMP_HEADER).parse().unwrap();
+                if verify("segredo", timestamp, &body, &header(SIGNATURE_HEADER)) {
+                    StatusCode::NO_CONTENT
+                } else {
+                    StatusCode::UNAUTHORIZED
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let store = Arc::new(InMemoryWebhookStore::new());
+        let webhook = store
+            .create(NewWebhook {
+                tenant: acme(),
+                url: format!("http://{}/hook", address),
+                secret: "segredo".to_string(),
+                events: vec![],
+            })
+            .await
+            .unwrap();
+
+        let config = WebhooksConfig {
+            initial_backoff_ms: 1,
+            allow_private_destinations: true,
+            ..Default::default()
+        };
+        let dispatcher = WebhookDispatcher::new(store.clone(), config).unwrap();
+        let event = EventBus::default().publish(&acme(), DomainEvent::UserDeleted { id: 1 });
+
+        assert!(dispatcher.deliver(&webhook, &event).await);
+
+        let deliveries = store.deliveries(webhook.id, 10).await.unwrap();
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].attempt, 2);
+        assert_eq!(deliveries[0].status_code, Some(204));
+        assert!(deliveries[0].success);
+        assert_eq!(deliveries[1].status_code, Some(503));
+        assert!(!deliveries[1].success);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_gives_up_after_max_attempts() {
+        let store = Arc::new(InMemoryWebhookStore::new());
+        let webhook = store
+            .create(NewWebhook {
+                tenant: acme(),
+                // Porta fechada: falha de conexão
+                url: "http://127.0.0.1:9/hook".to_string(),
+                secret: "segredo".to_string(),
+                events: vec![],
+            })
+            .await
+            .unwrap();
+
+        let config = WebhooksConfig {
+            max_attempts: 3,
+            initial_backoff_ms: 1,
+            allow_private_destinations: true,
+            ..Default::default()
+        };
+        let dispatcher = WebhookDispatcher::new(store.clone(), config).unwrap();
+        let event = EventBus::default().publish(&acme(), DomainEvent::UserDeleted { id: 1 });
+
+        assert!(!dispatcher.deliver(&webhook, &event).await);
+
+        let deliveries = store.deliveries(webhook.id, 10).await.unwrap();
+        assert_eq!(deliveries.len(), 3);
+        assert!(deliveries.iter().all(|d| !d.success && d.error.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_deliver_refuses_private_destinations() {
+        let store = Arc::new(InMemoryWebhookStore::new());
+        let webhook = store
+            .create(NewWebhook {
+                tenant: acme(),
+                url: "http://169.254.169.254/latest/meta-data".to_string(),
+                secret: "segredo".to_string(),
+                events: vec![],
+            })
+            .await
+            .unwrap();
+
+        let dispatcher = WebhookDispatcher::new(store.clone(), WebhooksConfig::default()).unwrap();
+        let event = EventBus::default().publish(&acme(), DomainEvent::UserDeleted { id: 1 });
+
+        assert!(!dispatcher.deliver(&webhook, &event).await);
+
+        let deliveries = store.deliveries(webhook.id, 10).await.unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert!(deliveries[0].error.as_deref().unwrap().contains("private"));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_only_to_the_event_tenant() {
+        let store = Arc::new(InMemoryWebhookStore::new());
+        let mut webhooks = Vec::new();
+        for tenant in [acme(), TenantContext::new("globex").unwrap()] {
+            let webhook = store
+                .create(NewWebhook {
+                    tenant,
+                    url: "http://127.0.0.1:9/hook".to_string(),
+                    secret: "segredo".to_string(),
+                    events: vec![],
+                })
+                .await
+                .unwrap();
+            webhooks.push(webhook);
+        }
+
+        let config = WebhooksConfig {
+            max_attempts: 1,
+            allow_private_destinations: true,
+            ..Default::default()
+        };
+        let dispatcher = WebhookDispatcher::new(store.clone(), config).unwrap();
+        let event = EventBus::default().publish(&acme(), DomainEvent::UserDeleted { id: 1 });
+        dispatcher.dispatch(event).await.unwrap();
+
+        for _ in 0..100 {
+            if !store
+                .deliveries(webhooks[0].id, 10)
+                .await
+                .unwrap()
+                .is_empty()
+            {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(store.deliveries(webhooks[0].id, 10).await.unwrap().len(), 1);
+        assert!(store
+            .deliveries(webhooks[1].id, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,350 @@
+//! Webhooks de entrada: `POST /api/webhooks/:provider`
+//!
+//! Cada provedor configurado em `[webhooks.providers.<nome>]` tem o próprio
+//! segredo e headers. Requisições com assinatura válida viram um
+//! `InboundEvent` na fila de processamento; reenvios do mesmo evento (mesmo
+//! provedor e ID) são aceitos mas descartados.
+//!
+//! Os IDs já vistos ficam em memória (os últimos `MAX_REMEMBERED_IDS`), então
+//! o descarte vale por instância e não sobrevive a um reinício.
+
+use super::{verify, verify_body};
+use crate::config::{WebhookProviderConfig, WebhooksConfig};
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Quantos IDs de evento são lembrados para descartar reenvios
+pub const MAX_REMEMBERED_IDS: usize = 10_000;
+
+/// Evento recebido de um provedor, aguardando processamento
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InboundEvent {
+    pub provider: String,
+    pub event_id: String,
+    pub payload: serde_json::Value,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Resultado de uma requisição aceita
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Accepted {
+    /// Novo evento, enfileirado
+    Queued,
+    /// Reenvio de um evento já recebido
+    Duplicate,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum InboundError {
+    #[error("unknown webhook provider '{0}'")]
+    UnknownProvider(String),
+    #[error("missing header {0}")]
+    MissingHeader(String),
+    #[error("invalid timestamp")]
+    InvalidTimestamp,
+    #[error("timestamp outside the accepted window")]
+    StaleTimestamp,
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("invalid JSON payload: {0}")]
+    InvalidPayload(String),
+    #[error("webhook queue is full")]
+    QueueFull,
+}
+
+/// Recebe, verifica e enfileira webhooks de entrada
+#[derive(Clone)]
+pub struct InboundWebhooks {
+    providers: Arc<BTreeMap<String, WebhookProviderConfig>>,
+    queue: mpsc::Sender<InboundEvent>,
+    seen: Arc<Mutex<RecentIds>>,
+}
+
+impl InboundWebhooks {
+    /// Cria a fila; os eventos aceitos saem pelo `Receiver` (ver `spawn_worker`)
+    pub fn new(config: &WebhooksConfig) -> (Self, mpsc::Receiver<InboundEvent>) {
+        let (queue, jobs) = mpsc::channel(config.queue_capacity.max(1));
+        let inbound = Self {
+            providers: Arc::new(config.providers.clone()),
+            queue,
+            seen: Arc::new(Mutex::new(RecentIds::new(MAX_REMEMBERED_IDS))),
+        };
+        (inbound, jobs)
+    }
+
+    /// Verifica a assinatura e enfileira o evento, se ainda não foi visto
+    pub fn receive(
+        &self,
+        provider: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<(Accepted, String), InboundError> {
+        let config = self
+            .providers
+            .get(provider)
+            .ok_or_else(|| InboundError::UnknownProvider(provider.to_string()))?;
+
+        let signature = header(headers, &config.signature_header)?;
+        if config.timestamp_header.is_empty() {
+            if !verify_body(&config.secret, body, signature) {
+                return Err(InboundError::InvalidSignature);
+            }
+        } else {
+            let timestamp: i64 = header(headers, &config.timestamp_header)?
+                .trim()
+                .parse()
+                .map_err(|_| InboundError::InvalidTimestamp)?;
+            if (Utc::now().timestamp() - timestamp).unsigned_abs() > config.tolerance_seconds {
+                return Err(InboundError::StaleTimestamp);
+            }
+            if !verify(&config.secret, timestamp, body, signature) {
+                return Err(InboundError::InvalidSignature);
+            }
+        }
+
+        let event_id = header(headers, &config.event_id_header)?.to_string();
+        let payload: serde_json::Value = serde_json::from_slice(body)
+            .map_err(|e| InboundError::InvalidPayload(e.to_string()))?;
+
+        let key = (provider.to_string(), event_id.clone());
+        let mut seen = self.seen.lock().unwrap();
+        if seen.contains(&key) {
+            return Ok((Accepted::Duplicate, event_id));
+        }
+
+        self.queue
+            .try_send(InboundEvent {
+                provider: provider.to_string(),
+                event_id: event_id.clone(),
+                payload,
+                received_at: Utc::now(),
+            })
+            .map_err(|_| InboundError::QueueFull)?;
+        seen.insert(key);
+
+        Ok((Accepted::Queued, event_id))
+    }
+}
+
+/// Processa os eventos recebidos
+#[async_trait]
+pub trait InboundHandler: Send + Sync {
+    async fn handle(&self, event: &InboundEvent) -> Result<()>;
+}
+
+/// Só registra o evento no log; ponto de partida para handlers reais
+pub struct LogHandler;
+
+#[async_trait]
+impl InboundHandler for LogHandler {
+    async fn handle(&self, event: &InboundEvent) -> Result<()> {
+        tracing::info!(
+            provider = %event.provider,
+            event_id = %event.event_id,
+            "Inbound webhook received"
+        );
+        Ok(())
+    }
+}
+
+/// Consome a fila em segundo plano, um evento por vez
+pub fn spawn_worker(
+    mut jobs: mpsc::Receiver<InboundEvent>,
+    handler: Arc<dyn InboundHandler>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some(event) = jobs.recv().await {
+            if let Err(e) = handler.handle(&event).await {
+                tracing::error!(
+                    error = %e,
+                    provider = %event.provider,
+                    event_id = %event.event_id,
+                    "Failed to process inbound webhook"
+                );
+            }
+        }
+    })
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str, InboundError> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| InboundError::MissingHeader(name.to_string()))
+}
+
+/// Conjunto limitado: ao encher, esquece os IDs mais antigos
+struct RecentIds {
+    capacity: usize,
+    order: VecDeque<(String, String)>,
+    ids: HashSet<(String, String)>,
+}
+
+impl RecentIds {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            order: VecDeque::new(),
+            ids: HashSet::new(),
+        }
+    }
+
+    fn contains(&self, key: &(String, String)) -> bool {
+        self.ids.contains(key)
+    }
+
+    fn insert(&mut self, key: (String, String)) {
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        self.order.push_back(key.clone());
+        self.ids.insert(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::{sign, ID_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+
+    const SECRET: &str = "segredo-de-teste";
+
+    fn config(capacity: usize) -> WebhooksConfig {
+        let mut config = WebhooksConfig {
+            queue_capacity: capacity,
+            ..Default::default()
+        };
+        let provider: WebhookProviderConfig =
+            serde_json::from_value(serde_json::json!({ "secret": SECRET })).unwrap();
+        config.providers.insert("app".to_string(), provider);
+        config
+    }
+
+    fn signed(id: &str, timestamp: i64, body: &[u8]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ID_HEADER, id.parse().unwrap());
+        headers.insert(TIMESTAMP_HEADER, timestamp.to_string().parse().unwrap());
+        headers.insert(
+            SIGNATURE_HEADER,
+            sign(SECRET, timestamp, body).parse().unwrap(),
+        );
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_receive_queues_once() {
+        let (inbound, mut jobs) = InboundWebhooks::new(&config(8));
+        let body = br#"{"type":"ping"}"#;
+        let headers = signed("evt-1", Utc::now().timestamp(), body);
+
+        assert_eq!(
+            inbound.receive("app", &headers, body),
+            Ok((Accepted::Queued, "evt-1".to_string()))
+        );
+        assert_eq!(
+            inbound.receive("app", &headers, body),
+            Ok((Accepted::Duplicate, "evt-1".to_string()))
+        );
+
+        let event = jobs.recv().await.unwrap();
+        assert_eq!(event.provider, "app");
+        assert_eq!(event.payload["type"], "ping");
+        assert!(jobs.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_receive_rejects_bad_requests() {
+        let (inbound, _jobs) = InboundWebhooks::new(&config(8));
+        let body = b"{}";
+        let now = Utc::now().timestamp();
+
+        assert_eq!(
+            inbound.receive("outro", &signed("1", now, body), body),
+            Err(InboundError::UnknownProvider("outro".to_string()))
+        );
+        assert_eq!(
+            inbound.receive("app", &signed("1", now, body), b"{\"x\":1}"),
+            Err(InboundError::InvalidSignature)
+        );
+        assert_eq!(
+            inbound.receive("app", &signed("1", now - 3_600, body), body),
+            Err(InboundError::StaleTimestamp)
+        );
+        assert_eq!(
+            inbound.receive("app", &HeaderMap::new(), body),
+            Err(InboundError::MissingHeader(SIGNATURE_HEADER.to_string()))
+        );
+
+        let not_json = b"oi";
+        assert!(matches!(
+            inbound.receive("app", &signed("1", now, not_json), not_json),
+            Err(InboundError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn test_receive_reports_full_queue() {
+        let (inbound, _jobs) = InboundWebhooks::new(&config(1));
+        let body = b"{}";
+        let now = Utc::now().timestamp();
+
+        assert!(inbound
+            .receive("app", &signed("1", now, body), body)
+            .is_ok());
+        assert_eq!(
+            inbound.receive("app", &signed("2", now, body), body),
+            Err(InboundError::QueueFull)
+        );
+        // Não marcado como visto: o reenvio pode entrar quando houver espaço
+        assert_eq!(
+            inbound.receive("app", &signed("2", now, body), body),
+            Err(InboundError::QueueFull)
+        );
+    }
+
+    #[test]
+    fn test_body_only_signature() {
+        let mut config = config(8);
+        let provider = config.providers.get_mut("app").unwrap();
+        provider.timestamp_header = String::new();
+        provider.signature_header = "X-Hub-Signature-256".to_string();
+        let (inbound, _jobs) = InboundWebhooks::new(&config);
+
+        use hmac::Mac;
+        let body = b"{}";
+        let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(body);
+        let mut headers = HeaderMap::new();
+        headers.insert(ID_HEADER, "1".parse().unwrap());
+        headers.insert(
+            "X-Hub-Signature-256",
+            format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+                .parse()
+                .unwrap(),
+        );
+
+        assert!(inbound.receive("app", &headers, body).is_ok());
+    }
+
+    #[test]
+    fn test_recent_ids_forget_oldest() {
+        let mut ids = RecentIds::new(2);
+        let key = |id: &str| ("app".to_string(), id.to_string());
+        ids.insert(key("1"));
+        ids.insert(key("2"));
+        ids.insert(key("3"));
+
+        assert!(!ids.contains(&key("1")));
+        assert!(ids.contains(&key("2")));
+        assert!(ids.contains(&key("3")));
+    }
+}
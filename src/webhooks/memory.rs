@@ -0,0 +1,170 @@
+//! `WebhookStore` em memória, para testes e uso sem banco
+
+use super::{Delivery, NewDelivery, NewWebhook, Webhook, WebhookStore};
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Debug, Default)]
+struct State {
+    webhooks: Vec<Webhook>,
+    deliveries: Vec<Delivery>,
+    next_webhook_id: i32,
+    next_delivery_id: i64,
+}
+
+/// Webhooks e entregas em vetores protegidos por `Mutex`
+#[derive(Debug, Default)]
+pub struct InMemoryWebhookStore {
+    state: Mutex<State>,
+}
+
+impl InMemoryWebhookStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl WebhookStore for InMemoryWebhookStore {
+    async fn create(&self, webhook: NewWebhook) -> Result<Webhook> {
+        let mut state = self.lock();
+        state.next_webhook_id += 1;
+
+        let webhook = Webhook {
+            id: state.next_webhook_id,
+            tenant_id: webhook.tenant.id().to_string(),
+            url: webhook.url,
+            secret: webhook.secret,
+            events: webhook.events,
+            active: true,
+            created_at: Some(chrono::Utc::now().naive_utc()),
+        };
+        state.webhooks.push(webhook.clone());
+        Ok(webhook)
+    }
+
+    async fn list(&self, tenant: &TenantContext) -> Result<Vec<Webhook>> {
+        Ok(self
+            .lock()
+            .webhooks
+            .iter()
+            .filter(|w| w.tenant_id == tenant.id())
+            .cloned()
+            .collect())
+    }
+
+    async fn find(&self, tenant: &TenantContext, id: i32) -> Result<Option<Webhook>> {
+        Ok(self
+            .lock()
+            .webhooks
+            .iter()
+            .find(|w| w.id == id && w.tenant_id == tenant.id())
+            .cloned())
+    }
+
+    async fn delete(&self, tenant: &TenantContext, id: i32) -> Result<bool> {
+        let mut state = self.lock();
+        let before = state.webhooks.len();
+        state
+            .webhooks
+            .retain(|w| w.id != id || w.tenant_id != tenant.id());
+        let deleted = state.webhooks.len() < before;
+        if deleted {
+            state.deliveries.retain(|d| d.webhook_id != id);
+        }
+        Ok(deleted)
+    }
+
+    async fn record_delivery(&self, delivery: NewDelivery) -> Result<Delivery> {
+        let mut state = self.lock();
+        state.next_delivery_id += 1;
+
+        let delivery = Delivery {
+            id: state.next_delivery_id,
+            webhook_id: delivery.webhook_id,
+            event_id: delivery.event_id,
+            event_type: delivery.event_type,
+            attempt: delivery.attempt,
+            status_code: delivery.status_code,
+            error: delivery.error,
+            success: delivery.success,
+            duration_ms: delivery.duration_ms,
+            created_at: Some(chrono::Utc::now().naive_utc()),
+        };
+        state.deliveries.push(delivery.clone());
+        Ok(delivery)
+    }
+
+    async fn deliveries(&self, webhook_id: i32, limit: i64) -> Result<Vec<Delivery>> {
+        Ok(self
+            .lock()
+            .deliveries
+            .iter()
+            .rev()
+            .filter(|d| d.webhook_id == webhook_id)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delivery(webhook_id: i32, attempt: i32) -> NewDelivery {
+        NewDelivery {
+            webhook_id,
+            event_id: 1,
+            event_type: "user.created".to_string(),
+            attempt,
+            status_code: Some(500),
+            error: None,
+            success: false,
+            duration_ms: 3,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_and_deliveries() {
+        let acme = TenantContext::new("acme").unwrap();
+        let globex = TenantContext::new("globex").unwrap();
+        let store = InMemoryWebhookStore::new();
+        let webhook = store
+            .create(NewWebhook {
+                tenant: acme.clone(),
+                url: "http://example.com/hook".to_string(),
+                secret: "segredo".to_string(),
+                events: vec![],
+            })
+            .await
+            .unwrap();
+        assert_eq!(webhook.id, 1);
+        assert_eq!(store.find(&acme, 1).await.unwrap(), Some(webhook.clone()));
+        assert_eq!(store.list(&acme).await.unwrap(), vec![webhook]);
+
+        // Outro tenant não vê nem remove o webhook
+        assert_eq!(store.find(&globex, 1).await.unwrap(), None);
+        assert!(store.list(&globex).await.unwrap().is_empty());
+        assert!(!store.delete(&globex, 1).await.unwrap());
+
+        store.record_delivery(delivery(1, 1)).await.unwrap();
+        store.record_delivery(delivery(1, 2)).await.unwrap();
+        store.record_delivery(delivery(2, 1)).await.unwrap();
+
+        let deliveries = store.deliveries(1, 10).await.unwrap();
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].attempt, 2);
+        assert_eq!(store.deliveries(1, 1).await.unwrap().len(), 1);
+
+        assert!(store.delete(&acme, 1).await.unwrap());
+        assert!(!store.delete(&acme, 1).await.unwrap());
+        assert!(store.deliveries(1, 10).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+//! Webhooks de saída e de entrada (feature "webhooks")
+//!
+//! Webhooks cadastrados via `/api/webhooks/subscriptions` recebem, por POST,
+//! os eventos que assinam do tenant em que foram cadastrados: do outbox (`crate::outbox`) com Postgres, ou do
+//! `EventBus` sem ele. Cada requisição leva:
+//!
+//! - `X-Webhook-Id`: ID do evento;
+//! - `X-Webhook-Event`: nome do evento (ex.: `user.created`);
+//! - `X-Webhook-Timestamp`: segundos desde a época Unix;
+//! - `X-Webhook-Signature`: `sha256=<hex>`, o HMAC-SHA256 de
+//!   `"<timestamp>.<corpo>"` com o segredo do webhook.
+//!
+//! Respostas fora de 2xx (ou falhas de rede) são repetidas com backoff
+//! exponencial; cada tentativa fica registrada no log de entregas.
+//!
+//! Só são aceitos destinos públicos (ver `destination`).
+//!
+//! Webhooks de entrada (`POST /api/webhooks/:provider`) ficam em `inbound`.
+
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::Sha256;
+
+pub mod destination;
+pub mod dispatcher;
+pub mod inbound;
+pub mod memory;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+pub use destination::DestinationError;
+pub use dispatcher::WebhookDispatcher;
+pub use inbound::{InboundEvent, InboundWebhooks};
+pub use memory::InMemoryWebhookStore;
+#[cfg(feature = "postgres")]
+pub use postgres::PgWebhookStore;
+
+pub const ID_HEADER: &str = "X-Webhook-Id";
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// Webhook cadastrado
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "postgres", derive(sqlx::FromRow))]
+pub struct Webhook {
+    pub id: i32,
+    /// Tenant cujos eventos o webhook recebe
+    pub tenant_id: String,
+    pub url: String,
+    /// Nunca devolvido pela API depois do cadastro
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Eventos assinados; vazio = todos
+    pub events: Vec<String>,
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::NaiveDateTime>,
+}
+
+impl Webhook {
+    /// Indica se o webhook deve receber o evento
+    pub fn wants(&self, event: &str) -> bool {
+        self.active && (self.events.is_empty() || self.events.iter().any(|e| e == event))
+    }
+}
+
+/// Dados para cadastrar um webhook
+#[derive(Debug, Clone)]
+pub struct NewWebhook {
+    pub tenant: TenantContext,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+}
+
+/// Uma tentativa de entrega
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "postgres", derive(sqlx::FromRow))]
+pub struct Delivery {
+    pub id: i64,
+    pub webhook_id: i32,
+    pub event_id: i64,
+    pub event_type: String,
+    /// Começa em 1
+    pub attempt: i32,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub success: bool,
+    pub duration_ms: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::NaiveDateTime>,
+}
+
+/// Tentativa de entrega a registrar
+#[derive(Debug, Clone)]
+pub struct NewDelivery {
+    pub webhook_id: i32,
+    pub event_id: i64,
+    pub event_type: String,
+    pub attempt: i32,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub success: bool,
+    pub duration_ms: i64,
+}
+
+/// Persistência dos webhooks e do log de entregas
+#[async_trait]
+pub trait WebhookStore: Send + Sync {
+    async fn create(&self, webhook: NewWebhook) -> Result<Webhook>;
+
+    /// Lista os webhooks do tenant, ordenados por ID
+    async fn list(&self, tenant: &TenantContext) -> Result<Vec<Webhook>>;
+
+    async fn find(&self, tenant: &TenantContext, id: i32) -> Result<Option<Webhook>>;
+
+    /// Remove o webhook e o log dele; `false` se ele não existia no tenant
+    async fn delete(&self, tenant: &TenantContext, id: i32) -> Result<bool>;
+
+    async fn record_delivery(&self, delivery: NewDelivery) -> Result<Delivery>;
+
+    /// Entregas mais recentes primeiro
+    async fn deliveries(&self, webhook_id: i32, limit: i64) -> Result<Vec<Delivery>>;
+}
+
+/// Assinatura `sha256=<hex>` de `"<timestamp>.<corpo>"`
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = hmac(secret);
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Confere uma assinatura gerada por `sign` (comparação em tempo constante)
+pub fn verify(secret: &str, timestamp: i64, body: &[u8], signature: &str) -> bool {
+    let mut mac = hmac(secret);
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    check(mac, signature)
+}
+
+/// Confere uma assinatura HMAC-SHA256 só do corpo, como a de provedores que
+/// não assinam timestamp
+pub fn verify_body(secret: &str, body: &[u8], signature: &str) -> bool {
+    let mut mac = hmac(secret);
+    mac.update(body);
+    check(mac, signature)
+}
+
+/// Aceita `sha256=<hex>` ou só `<hex>`
+fn check(mac: Hmac<Sha256>, signature: &str) -> bool {
+    let signature = signature.trim();
+    let hex = signature.strip_prefix("sha256=").unwrap_or(signature);
+    match hex::decode(hex) {
+        Ok(expected) => mac.verify_slice(&expected).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Segredo aleatório para webhooks cadastrados sem um
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+fn hmac(secret: &str) -> Hmac<Sha256> {
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signature = sign("segredo", 1_700_000_000, b"{\"id\":1}");
+        assert!(signature.starts_with("sha256="));
+        assert!(verify("segredo", 1_700_000_000, b"{\"id\":1}", &signature));
+
+        assert!(!verify("outro", 1_700_000_000, b"{\"id\":1}", &signature));
+        assert!(!verify("segredo", 1_700_000_001, b"{\"id\":1}", &signature));
+        assert!(!verify("segredo", 1_700_000_000, b"{\"id\":2}", &signature));
+        assert!(!verify(
+            "segredo",
+            1_700_000_000,
+            b"{\"id\":1}",
+            "sha256=zz"
+        ));
+    }
+
+    #[test]
+    fn test_verify_body() {
+        let mut mac = hmac("segredo");
+        mac.update(b"{}");
+        let hex = hex::encode(mac.finalize().into_bytes());
+
+        assert!(verify_body("segredo", b"{}", &hex));
+        assert!(verify_body("segredo", b"{}", &format!("sha256={}", hex)));
+        assert!(!verify_body("segredo", b"{ }", &hex));
+        assert!(!verify_body("outro", b"{}", &hex));
+    }
+
+    #[test]
+    fn test_wants() {
+        let mut webhook = Webhook {
+            id: 1,
+            tenant_id: "acme".to_string(),
+            url: "http://example.com".to_string(),
+            secret: generate_secret(),
+            events: vec![],
+            active: true,
+            created_at: None,
+        };
+        assert!(webhook.wants("user.created"));
+
+        webhook.events = vec!["user.deleted".to_string()];
+        assert!(!webhook.wants("user.created"));
+        assert!(webhook.wants("user.deleted"));
+
+        webhook.active = false;
+        assert!(!webhook.wants("user.deleted"));
+    }
+}
@@ -0,0 +1,123 @@
+//! `WebhookStore` sobre o Postgres (tabelas `webhooks` e `webhook_deliveries`)
+
+use super::{Delivery, NewDelivery, NewWebhook, Webhook, WebhookStore};
+use crate::db::timed;
+use crate::tenant::TenantContext;
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+#[derive(Debug, Clone)]
+pub struct PgWebhookStore {
+    pool: PgPool,
+}
+
+impl PgWebhookStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WebhookStore for PgWebhookStore {
+    async fn create(&self, webhook: NewWebhook) -> Result<Webhook> {
+        let query = sqlx::query_as!(
+            Webhook,
+            r#"
+            INSERT INTO webhooks (tenant_id, url, secret, events)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, tenant_id, url, secret, events, active, created_at AS "created_at?"
+            "#,
+            webhook.tenant.id(),
+            webhook.url,
+            webhook.secret,
+            &webhook.events
+        )
+        .fetch_one(&self.pool);
+
+        Ok(timed("webhooks.create", query).await?)
+    }
+
+    async fn list(&self, tenant: &TenantContext) -> Result<Vec<Webhook>> {
+        let query = sqlx::query_as!(
+            Webhook,
+            r#"
+            SELECT id, tenant_id, url, secret, events, active, created_at AS "created_at?"
+            FROM webhooks WHERE tenant_id = $1 ORDER BY id
+            "#,
+            tenant.id()
+        )
+        .fetch_all(&self.pool);
+
+        Ok(timed("webhooks.list", query).await?)
+    }
+
+    async fn find(&self, tenant: &TenantContext, id: i32) -> Result<Option<Webhook>> {
+        let query = sqlx::query_as!(
+            Webhook,
+            r#"
+            SELECT id, tenant_id, url, secret, events, active, created_at AS "created_at?"
+            FROM webhooks WHERE tenant_id = $1 AND id = $2
+            "#,
+            tenant.id(),
+            id
+        )
+        .fetch_optional(&self.pool);
+
+        Ok(timed("webhooks.find", query).await?)
+    }
+
+    async fn delete(&self, tenant: &TenantContext, id: i32) -> Result<bool> {
+        let query = sqlx::query!(
+            "DELETE FROM webhooks WHERE tenant_id = $1 AND id = $2",
+            tenant.id(),
+            id
+        )
+        .execute(&self.pool);
+
+        Ok(timed("webhooks.delete", query).await?.rows_affected() > 0)
+    }
+
+    async fn record_delivery(&self, delivery: NewDelivery) -> Result<Delivery> {
+        let query = sqlx::query_as!(
+            Delivery,
+            r#"
+            INSERT INTO webhook_deliveries
+                (webhook_id, event_id, event_type, attempt, status_code, error, success, duration_ms)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, webhook_id, event_id, event_type, attempt, status_code, error,
+                      success, duration_ms, created_at AS "created_at?"
+            "#,
+            delivery.webhook_id,
+            delivery.event_id,
+            delivery.event_type,
+            delivery.attempt,
+            delivery.status_code,
+            delivery.error,
+            delivery.success,
+            delivery.duration_ms
+        )
+        .fetch_one(&self.pool);
+
+        Ok(timed("webhooks.record_delivery", query).await?)
+    }
+
+    async fn deliveries(&self, webhook_id: i32, limit: i64) -> Result<Vec<Delivery>> {
+        let query = sqlx::query_as!(
+            Delivery,
+            r#"
+            SELECT id, webhook_id, event_id, event_type, attempt, status_code, error,
+                   success, duration_ms, created_at AS "created_at?"
+            FROM webhook_deliveries
+            WHERE webhook_id = $1
+            ORDER BY id DESC
+            LIMIT $2
+            "#,
+            webhook_id,
+            limit
+        )
+        .fetch_all(&self.pool);
+
+        Ok(timed("webhooks.deliveries", query).await?)
+    }
+}
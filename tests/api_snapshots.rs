@@ -0,0 +1,173 @@
+//! Snapshots (insta) das respostas JSON da API
+//!
+//! Cada handler é exercitado via `tower::ServiceExt::oneshot`, nos casos de
+//! sucesso e de erro. Após uma mudança intencional no formato, revise e
+//! aceite os novos snapshots com `cargo insta review`.
+
+#![cfg(feature = "api")]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
+use rust_app_exemplo::api::{create_management_router, create_router, ApiError, AppState};
+use rust_app_exemplo::fixtures::Fixtures;
+use rust_app_exemplo::repository::InMemoryUserRepository;
+use rust_app_exemplo::validation::{FieldError, FieldErrors};
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn app() -> Router {
+    let repo = InMemoryUserRepository::with_users(Fixtures::seeded(42).users(2));
+    create_router(AppState::new(Arc::new(repo), Default::default()))
+        .merge(create_management_router(None))
+}
+
+async fn json(response: Response) -> (StatusCode, Value) {
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+async fn send(app: Router, request: Request<Body>) -> (StatusCode, Value) {
+    json(app.oneshot(request).await.unwrap()).await
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::get(uri).body(Body::empty()).unwrap()
+}
+
+fn with_json(method: &str, uri: &str, body: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn snapshot_info_endpoints() {
+    let (status, body) = send(app(), get("/health")).await;
+    assert_eq!(status, StatusCode::OK);
+    insta::assert_json_snapshot!("health", body);
+
+    let (status, body) = send(app(), get("/ready")).await;
+    assert_eq!(status, StatusCode::OK);
+    insta::assert_json_snapshot!("ready", body);
+
+    let (status, body) = send(app(), get("/")).await;
+    assert_eq!(status, StatusCode::OK);
+    insta::assert_json_snapshot!("root", body, { ".data.version" => "[version]" });
+
+    let (status, body) = send(app(), get("/version")).await;
+    assert_eq!(status, StatusCode::OK);
+    insta::assert_json_snapshot!("version", body, {
+        ".data.git_sha" => "[git_sha]",
+        ".data.git_branch" => "[git_branch]",
+        ".data.build_timestamp" => "[build_timestamp]",
+        ".data.rustc_version" => "[rustc_version]",
+        ".data.version" => "[version]",
+    });
+}
+
+#[tokio::test]
+async fn snapshot_users_endpoints() {
+    let (status, body) = send(app(), get("/api/users")).await;
+    assert_eq!(status, StatusCode::OK);
+    insta::assert_json_snapshot!("list_users", body);
+
+    let (status, body) = send(app(), get("/api/users/1")).await;
+    assert_eq!(status, StatusCode::OK);
+    insta::assert_json_snapshot!("get_user", body);
+
+    let (status, body) = send(app(), get("/api/users/999")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    insta::assert_json_snapshot!("get_user_not_found", body);
+
+    let request = with_json(
+        "POST",
+        "/api/users",
+        r#"{"name": "Ana Souza", "email": "ana.souza@example.com"}"#,
+    );
+    let (status, body) = send(app(), request).await;
+    assert_eq!(status, StatusCode::OK);
+    insta::assert_json_snapshot!("create_user", body);
+
+    let request = with_json("POST", "/api/users", r#"{"name": "", "email": "nope"}"#);
+    let (status, body) = send(app(), request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    insta::assert_json_snapshot!("create_user_validation_failed", body);
+
+    let (status, body) = send(
+        app(),
+        Request::delete("/api/users/1").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    insta::assert_json_snapshot!("delete_user", body);
+}
+
+#[tokio::test]
+async fn snapshot_admin_endpoints() {
+    let (status, body) = send(app(), get("/api/admin/log-level")).await;
+    assert_eq!(status, StatusCode::OK);
+    insta::assert_json_snapshot!("get_log_level", body);
+
+    let request = with_json("PUT", "/api/admin/log-level", r#"{"level": ""}"#);
+    let (status, body) = send(app(), request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    insta::assert_json_snapshot!("set_log_level_empty", body);
+
+    let protected = create_management_router(Some("s3cret".to_string()));
+    let (status, body) = send(protected, get("/api/admin/log-level")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    insta::assert_json_snapshot!("admin_unauthorized", body);
+}
+
+#[tokio::test]
+async fn snapshot_error_variants() {
+    let mut fields = FieldErrors::new();
+    fields.add("email", FieldError::new("email", "must be a valid email"));
+
+    let errors = [
+        (
+            "not_found",
+            ApiError::NotFound("User not found".to_string()),
+        ),
+        (
+            "bad_request",
+            ApiError::BadRequest("Malformed input".to_string()),
+        ),
+        (
+            "unauthorized",
+            ApiError::Unauthorized("Missing token".to_string()),
+        ),
+        ("conflict", ApiError::Conflict("Already exists".to_string())),
+        (
+            "internal_error",
+            ApiError::InternalError("Unexpected failure".to_string()),
+        ),
+        (
+            "database_error",
+            ApiError::DatabaseError("Connection refused".to_string()),
+        ),
+        ("validation_failed", ApiError::ValidationFailed(fields)),
+    ];
+
+    for (name, error) in errors {
+        let (status, body) = json(error.into_response()).await;
+        insta::assert_json_snapshot!(
+            format!("error_{}", name),
+            serde_json::json!({
+                "status": status.as_u16(),
+                "body": body,
+            })
+        );
+    }
+}
@@ -0,0 +1,477 @@
+//! Testes de banco com Postgres em container
+//!
+//! Requerem Docker: `cargo test --features test-util -- --ignored`
+
+#![cfg(feature = "test-util")]
+
+use rust_app_exemplo::db::{DbError, DbUser};
+use rust_app_exemplo::models::UserStatus;
+use rust_app_exemplo::tenant::TenantContext;
+use rust_app_exemplo::test_support::TestDatabase;
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_migrations_seed_users() {
+    let test_db = TestDatabase::start().await.unwrap();
+    let tenant = TenantContext::default_tenant();
+
+    assert_eq!(test_db.seeded_users.len(), 3);
+    assert_eq!(
+        DbUser::count(test_db.db().pool(), &tenant).await.unwrap(),
+        3
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_revert_lists_the_versions_before_undoing_them() {
+    let test_db = TestDatabase::start().await.unwrap();
+    let db = test_db.db();
+
+    // Só listar (`--dry-run`) não desfaz nada
+    let planned = db.to_revert(2).await.unwrap();
+    assert_eq!(planned.len(), 2);
+    assert!(planned[0] > planned[1]);
+    assert_eq!(db.to_revert(2).await.unwrap(), planned);
+
+    assert_eq!(db.revert(2).await.unwrap(), planned);
+    assert!(db.to_revert(1).await.unwrap()[0] < planned[1]);
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_user_crud() {
+    let test_db = TestDatabase::start().await.unwrap();
+    test_db.truncate_users().await.unwrap();
+    let pool = test_db.db().pool();
+    let tenant = TenantContext::default_tenant();
+
+    let created = test_db
+        .seed_users(&[("Ana", "ana@example.com")])
+        .await
+        .unwrap()
+        .remove(0);
+
+    let mut found = DbUser::find_by_email(pool, &tenant, "Ana@Example.com")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, created.id);
+
+    found.status = UserStatus::Suspended;
+    found.update(pool, &tenant).await.unwrap();
+    let updated = DbUser::find_by_id(pool, &tenant, found.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.status, UserStatus::Suspended);
+
+    DbUser::delete(pool, &tenant, found.id).await.unwrap();
+    assert!(DbUser::find_by_id(pool, &tenant, found.id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_changes_from_other_instances_reach_the_event_bus() {
+    use rust_app_exemplo::events::{postgres::spawn_bridge, DomainEvent, EventBus};
+    use std::time::Duration;
+
+    let test_db = TestDatabase::start().await.unwrap();
+    let pool = test_db.db().pool();
+    let tenant = TenantContext::default_tenant();
+    let bus = EventBus::default();
+    let mut events = bus.subscribe();
+    let bridge = spawn_bridge(pool.clone(), bus.clone());
+    // Dá tempo para o LISTEN antes das mudanças
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    // Mudança desta instância: ignorada pela ponte
+    DbUser::create(pool, &tenant, "Local", "local@example.com")
+        .await
+        .unwrap();
+
+    // Mudança de "outra instância"
+    let mut tx = pool.begin().await.unwrap();
+    sqlx::query("SET LOCAL app.instance_id = 'outra'")
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO users (name, email) VALUES ('Remota', 'remota@example.com')")
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(event.remote);
+    match event.payload {
+        DomainEvent::UserCreated(user) => assert_eq!(user.email, "remota@example.com"),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    bridge.abort();
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_user_queries_are_scoped_to_the_tenant() {
+    let test_db = TestDatabase::start().await.unwrap();
+    let pool = test_db.db().pool();
+    let acme = TenantContext::new("acme").unwrap();
+    let globex = TenantContext::new("globex").unwrap();
+
+    // O mesmo email em dois tenants
+    let ana = test_db
+        .seed_users_in(&acme, &[("Ana", "ana@example.com")])
+        .await
+        .unwrap()
+        .remove(0);
+    test_db
+        .seed_users_in(&globex, &[("Ana", "ana@example.com")])
+        .await
+        .unwrap();
+    assert!(test_db
+        .seed_users_in(&acme, &[("Outra", "ana@example.com")])
+        .await
+        .is_err());
+
+    assert_eq!(DbUser::count(pool, &acme).await.unwrap(), 1);
+    assert!(DbUser::find_by_id(pool, &globex, ana.id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(DbUser::anonymize(pool, &globex, ana.id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(matches!(
+        DbUser {
+            status: UserStatus::Deactivated,
+            ..ana.clone()
+        }
+        .update(pool, &globex)
+        .await,
+        Err(DbError::NotFound { .. })
+    ));
+    assert!(matches!(
+        DbUser::delete(pool, &globex, ana.id).await,
+        Err(DbError::NotFound { .. })
+    ));
+
+    let found = DbUser::find_by_id(pool, &acme, ana.id).await.unwrap();
+    assert_eq!(found.map(|u| u.status), Some(UserStatus::Active));
+}
+
+/// Destino do outbox que guarda os eventos (ou falha, se `failing`)
+#[derive(Default)]
+struct RecordingSink {
+    failing: std::sync::atomic::AtomicBool,
+    events: std::sync::Mutex<Vec<rust_app_exemplo::events::Event>>,
+}
+
+#[async_trait::async_trait]
+impl rust_app_exemplo::outbox::OutboxSink for RecordingSink {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+
+    async fn publish(&self, event: &rust_app_exemplo::events::Event) -> anyhow::Result<()> {
+        if self.failing.load(std::sync::atomic::Ordering::SeqCst) {
+            anyhow::bail!("unavailable");
+        }
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_outbox_relay_publishes_committed_changes_in_order() {
+    use rust_app_exemplo::config::OutboxConfig;
+    use rust_app_exemplo::outbox::OutboxRelay;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    let test_db = TestDatabase::start().await.unwrap();
+    let pool = test_db.db().pool();
+    let tenant = TenantContext::default_tenant();
+
+    let ana = DbUser::create(pool, &tenant, "Ana", "ana@example.com")
+        .await
+        .unwrap();
+    DbUser {
+        status: UserStatus::Deactivated,
+        ..ana.clone()
+    }
+    .update(pool, &tenant)
+    .await
+    .unwrap();
+    DbUser::delete(pool, &tenant, ana.id).await.unwrap();
+    // Nada removido, nada no outbox
+    assert!(DbUser::delete(pool, &tenant, ana.id).await.is_err());
+
+    let sink = Arc::new(RecordingSink::default());
+    let relay = OutboxRelay::new(pool.clone(), OutboxConfig::default()).with_sink(sink.clone());
+
+    sink.failing.store(true, Ordering::SeqCst);
+    assert_eq!(relay.relay_batch().await.unwrap(), 0);
+    let (attempts, error): (i32, Option<String>) =
+        sqlx::query_as("SELECT attempts, last_error FROM outbox ORDER BY id LIMIT 1")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+    assert_eq!(attempts, 1);
+    assert!(error.unwrap().contains("unavailable"));
+
+    sink.failing.store(false, Ordering::SeqCst);
+    assert_eq!(relay.relay_batch().await.unwrap(), 3);
+    assert_eq!(relay.relay_batch().await.unwrap(), 0);
+
+    let events = sink.events.lock().unwrap();
+    let names: Vec<_> = events.iter().map(|e| e.payload.name()).collect();
+    assert_eq!(names, ["user.created", "user.updated", "user.deleted"]);
+    assert!(events.windows(2).all(|pair| pair[0].id < pair[1].id));
+    assert!(events.iter().all(|e| e.tenant == tenant.id()));
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_close_drains_in_flight_queries() {
+    use rust_app_exemplo::db::{Database, DatabaseConfig};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let test_db = TestDatabase::start().await.unwrap();
+    let db = Arc::new(
+        Database::new(DatabaseConfig {
+            url: Some(test_db.url().to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap(),
+    );
+
+    let in_flight = tokio::spawn({
+        let db = db.clone();
+        async move { sqlx::query("SELECT pg_sleep(0.3)").execute(db.pool()).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert!(db.close(Duration::from_secs(5)).await);
+    assert!(in_flight.await.unwrap().is_ok());
+
+    // Depois de fechado, o pool recusa novas conexões
+    assert!(db.is_closed());
+    assert!(db.ping().await.is_err());
+    assert!(matches!(
+        sqlx::query("SELECT 1").execute(db.pool()).await,
+        Err(sqlx::Error::PoolClosed)
+    ));
+}
+
+#[cfg(feature = "api")]
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_rate_limit_overrides_are_loaded() {
+    use rust_app_exemplo::api::rate_limit::RateLimitOverrides;
+
+    let test_db = TestDatabase::start().await.unwrap();
+    let pool = test_db.db().pool();
+    sqlx::query(
+        "INSERT INTO rate_limit_overrides (key, requests_per_minute) VALUES ('user:acme/42', 6000)",
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let overrides = RateLimitOverrides::default();
+    overrides.replace(RateLimitOverrides::load(pool).await.unwrap());
+    assert_eq!(overrides.get("user:acme/42"), Some(6000));
+    assert_eq!(overrides.get("ip:10.0.0.1"), None);
+}
+
+#[cfg(feature = "api")]
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_quota_usage_is_counted_atomically() {
+    use rust_app_exemplo::quotas::{month_of, PgQuotaStore, QuotaStore, Resource};
+    use rust_app_exemplo::tenant::TenantContext;
+
+    let test_db = TestDatabase::start().await.unwrap();
+    let store = PgQuotaStore::new(test_db.db().pool().clone());
+    let tenant = TenantContext::default_tenant();
+    let (period, _) = month_of(chrono::Utc::now());
+
+    let mut attempts = tokio::task::JoinSet::new();
+    for _ in 0..10 {
+        let (store, tenant) = (store.clone(), tenant.clone());
+        attempts.spawn(async move {
+            store
+                .consume(&tenant, Resource::UsersCreated, period, Some(3))
+                .await
+        });
+    }
+    let mut granted = 0;
+    while let Some(used) = attempts.join_next().await {
+        granted += used.unwrap().unwrap().is_some() as usize;
+    }
+    assert_eq!(granted, 3);
+    assert_eq!(
+        store
+            .used(&tenant, Resource::UsersCreated, period)
+            .await
+            .unwrap(),
+        3
+    );
+
+    store
+        .release(&tenant, Resource::UsersCreated, period)
+        .await
+        .unwrap();
+    assert_eq!(
+        store
+            .used(&tenant, Resource::UsersCreated, period)
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        store.limit(&tenant, Resource::UsersCreated).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_copy_in_and_seed_with_copy() {
+    use rust_app_exemplo::db::seed::SeedData;
+    use rust_app_exemplo::formats::DocumentFormat;
+    use rust_app_exemplo::models::NewUser;
+
+    let test_db = TestDatabase::start().await.unwrap();
+    test_db.truncate_users().await.unwrap();
+    let pool = test_db.db().pool();
+    let tenant = TenantContext::default_tenant();
+
+    let users =
+        (0..2500).map(|i| NewUser::new(format!("User, \"{}\"", i), format!("u{}@example.com", i)));
+    let created = DbUser::copy_in(pool, &tenant, users).await.unwrap();
+    assert_eq!(created.len(), 2500);
+    assert_eq!(created[1].name, "User, \"1\"");
+    assert!(created.windows(2).all(|pair| pair[0].id < pair[1].id));
+    assert_eq!(DbUser::count(pool, &tenant).await.unwrap(), 2500);
+
+    let json = r#"[
+        {"name": "Nova", "email": "u0@example.com"},
+        {"name": "User, \"1\"", "email": "u1@example.com", "status": "suspended"},
+        {"name": "User, \"2\"", "email": "u2@example.com"},
+        {"name": "Zé", "email": "ze@example.com"}
+    ]"#;
+    let data = SeedData::parse(json, DocumentFormat::Json).unwrap();
+    let report = data.apply(pool, &tenant, 0).await.unwrap();
+    assert_eq!(
+        (report.created, report.updated, report.unchanged),
+        (1, 2, 1)
+    );
+    let report = data.apply(pool, &tenant, 0).await.unwrap();
+    assert_eq!(report.unchanged, 4);
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_ping_works_with_the_pool_exhausted() {
+    use rust_app_exemplo::db::{Database, DatabaseConfig};
+    use std::time::Duration;
+
+    let test_db = TestDatabase::start().await.unwrap();
+    let db = Database::new(DatabaseConfig {
+        url: Some(test_db.url().to_string()),
+        max_connections: 1,
+        acquire_timeout_ms: 200,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+
+    let _held = db.pool().acquire().await.unwrap();
+    assert!(db.pool().acquire().await.is_err());
+    tokio::time::timeout(Duration::from_secs(1), db.ping())
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_create_or_get_ignores_email_case() {
+    let test_db = TestDatabase::start().await.unwrap();
+    test_db.truncate_users().await.unwrap();
+    let pool = test_db.db().pool();
+    let tenant = TenantContext::default_tenant();
+
+    let (ana, created) = DbUser::create_or_get(pool, &tenant, "Ana", "ana@example.com")
+        .await
+        .unwrap();
+    assert!(created);
+    let (again, created) = DbUser::create_or_get(pool, &tenant, "Outra", "ANA@example.com")
+        .await
+        .unwrap();
+    assert!(!created);
+    assert_eq!(again.id, ana.id);
+    assert!(DbUser::create(pool, &tenant, "Outra", "Ana@Example.com")
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_crud_repository() {
+    use rust_app_exemplo::db::CrudRepository;
+
+    let test_db = TestDatabase::start().await.unwrap();
+    test_db.truncate_users().await.unwrap();
+    let tenant = TenantContext::default_tenant();
+    let users = CrudRepository::<DbUser>::new(test_db.db().pool().clone());
+
+    let template = DbUser::create(test_db.db().pool(), &tenant, "Modelo", "modelo@example.com")
+        .await
+        .unwrap();
+    let ana = users
+        .insert(
+            &tenant,
+            &DbUser {
+                name: "Ana".to_string(),
+                email: "ana@example.com".to_string(),
+                ..template
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(users.get(&tenant, ana.id).await.unwrap().name, "Ana");
+    assert_eq!(users.count(&tenant).await.unwrap(), 2);
+    assert_eq!(users.list_page(&tenant, 1, 1).await.unwrap()[0].id, ana.id);
+
+    let suspended = users
+        .update(
+            &tenant,
+            &DbUser {
+                status: UserStatus::Suspended,
+                ..ana.clone()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(suspended.status, UserStatus::Suspended);
+
+    users.delete(&tenant, ana.id).await.unwrap();
+    assert!(matches!(
+        users.delete(&tenant, ana.id).await,
+        Err(DbError::NotFound { .. })
+    ));
+}
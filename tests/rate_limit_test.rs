@@ -0,0 +1,34 @@
+//! Limite de requisições no Redis em container
+//!
+//! Requer Docker: `cargo test --features "test-util redis" -- --ignored`
+
+#![cfg(all(feature = "test-util", feature = "redis"))]
+
+use rust_app_exemplo::api::rate_limit::{RateLimitStore, RedisRateLimiter};
+use testcontainers_modules::redis::{Redis, REDIS_PORT};
+use testcontainers_modules::testcontainers::runners::AsyncRunner;
+
+#[tokio::test]
+#[ignore = "requires Docker"]
+async fn test_redis_limit_is_shared_between_replicas() {
+    let container = Redis::default().start().await.unwrap();
+    let port = container.get_host_port_ipv4(REDIS_PORT).await.unwrap();
+    let url = format!("redis://127.0.0.1:{}", port);
+
+    // Duas réplicas, cada uma com o seu limitador, contando no mesmo Redis
+    let a = RedisRateLimiter::new(&url).unwrap();
+    let b = RedisRateLimiter::new(&url).unwrap();
+
+    assert_eq!(a.hit("key:abc", 2).await.unwrap(), Ok(()));
+    assert_eq!(b.hit("key:abc", 2).await.unwrap(), Ok(()));
+    let retry_after = a.hit("key:abc", 2).await.unwrap().unwrap_err();
+    assert!((1..=60).contains(&retry_after), "{}", retry_after);
+
+    // Outro cliente, e o mesmo cliente com um limite maior (override)
+    assert_eq!(b.hit("key:other", 2).await.unwrap(), Ok(()));
+    assert_eq!(b.hit("key:abc", 3).await.unwrap(), Ok(()));
+
+    // Prefixos diferentes não se misturam
+    let staging = RedisRateLimiter::new(&url).unwrap().with_prefix("staging:");
+    assert_eq!(staging.hit("key:abc", 1).await.unwrap(), Ok(()));
+}